@@ -4,10 +4,19 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::time::Instant;
-use crate::config::Config;
+use std::time::{Duration, Instant};
+use crate::config::{Config, ConfigFile, PackageLink};
 use crate::error::SpineError;
-use crate::platform::Platform;
+use crate::history::{self, BuildHistory, BuildRecord, BundleSizes};
+use crate::platform::{run_command, OutputMode, Platform};
+use crate::paths::display_path;
+use crate::report::{Report, ReportCase, ReportTarget};
+
+/// Limit for the quick git queries used to find affected libraries
+const GIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How far below the workspace root undeclared projects are looked for
+const DISCOVERY_DEPTH: usize = 5;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AngularWorkspace {
@@ -15,6 +24,31 @@ pub struct AngularWorkspace {
     pub projects: HashMap<String, AngularProject>,
     #[serde(rename = "defaultProject")]
     pub default_project: Option<String>,
+    #[serde(skip)]
+    pub source: WorkspaceSource,
+}
+
+/// Where a workspace's projects were read from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WorkspaceSource {
+    /// angular.json's projects map
+    #[default]
+    AngularJson,
+    /// project.json files that angular.json points to, or reads in place of
+    /// an empty projects map
+    ProjectJson,
+    /// An nx.json workspace, built with `nx build`
+    Nx,
+}
+
+impl WorkspaceSource {
+    pub fn describe(self) -> &'static str {
+        match self {
+            WorkspaceSource::AngularJson => "angular.json",
+            WorkspaceSource::ProjectJson => "project.json files",
+            WorkspaceSource::Nx => "Nx (nx.json and project.json files)",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +59,9 @@ pub struct AngularProject {
     #[serde(rename = "projectType")]
     pub project_type: String,
     pub architect: Option<HashMap<String, AngularArchitect>>,
+    /// Found by `discover_projects` rather than declared in angular.json
+    #[serde(skip)]
+    pub discovered: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +71,141 @@ pub struct AngularArchitect {
     pub configurations: Option<HashMap<String, serde_json::Value>>,
 }
 
+impl AngularWorkspace {
+    /// Whether a declared project is the source or output of `link`: by
+    /// project name, by the npm name in its root package.json, or by the
+    /// link's path lying in its root or its dist folder
+    fn has_project_for(&self, root: &Path, link: &PackageLink) -> bool {
+        self.projects.iter().any(|(name, project)| {
+            let project_root = root.join(&project.root);
+            name == &link.name
+                || (!project.root.trim_matches('/').is_empty() && link.path.starts_with(&project_root))
+                || link.path.starts_with(root.join("dist").join(name))
+                || crate::package::parse_package_json(&project_root.join("package.json"))
+                    .is_ok_and(|info| info.name == link.npm_name())
+        })
+    }
+
+    /// Older Nx presets leave projects out of angular.json and rely on
+    /// project.json discovery. When a package inside the workspace matches no
+    /// declared project, look for project.json and ng-package.json files
+    /// below `root` and add the projects they describe, marked `discovered`.
+    /// Returns how many were added.
+    pub fn discover_projects<'a>(&mut self, root: &Path, links: impl IntoIterator<Item = &'a PackageLink>) -> usize {
+        let unmatched = links.into_iter()
+            .any(|link| link.path.starts_with(root) && !self.has_project_for(root, link));
+        if !unmatched {
+            return 0;
+        }
+
+        let declared: HashSet<String> = self.projects.values()
+            .map(|project| project.root.trim_matches('/').to_string())
+            .collect();
+        let mut found = Vec::new();
+        collect_project_dirs(root, root, 0, &mut found);
+
+        let mut added = 0;
+        for dir in found {
+            let relative = dir.strip_prefix(root).unwrap_or(&dir).to_string_lossy().replace('\\', "/");
+            if declared.contains(&relative) {
+                continue;
+            }
+            if let Some((name, project)) = discovered_project(&dir, relative) {
+                if let std::collections::hash_map::Entry::Vacant(entry) = self.projects.entry(name) {
+                    entry.insert(project);
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+}
+
+/// Directories below `dir` with a project.json or ng-package.json, skipping
+/// node_modules, dist and hidden folders. A project's own subfolders aren't
+/// searched.
+fn collect_project_dirs(root: &Path, dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    if dir != root && (dir.join("project.json").exists() || dir.join("ng-package.json").exists()) {
+        found.push(dir.to_path_buf());
+        return;
+    }
+    if depth >= DISCOVERY_DEPTH {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut subdirs: Vec<PathBuf> = entries.flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && name != "node_modules" && name != "dist"
+        })
+        .map(|entry| entry.path())
+        .collect();
+    subdirs.sort();
+    for subdir in subdirs {
+        collect_project_dirs(root, &subdir, depth + 1, found);
+    }
+}
+
+/// The projects of every project.json below `root`, for workspaces that
+/// declare none in angular.json
+fn project_json_projects(root: &Path) -> HashMap<String, AngularProject> {
+    let mut found = Vec::new();
+    collect_project_dirs(root, root, 0, &mut found);
+    found.into_iter()
+        .filter(|dir| dir.join("project.json").is_file())
+        .filter_map(|dir| {
+            let relative = dir.strip_prefix(root).unwrap_or(&dir).to_string_lossy().replace('\\', "/");
+            let (name, project) = discovered_project(&dir, relative)?;
+            Some((name, AngularProject { discovered: false, ..project }))
+        })
+        .collect()
+}
+
+/// `ng build <project>`, or `nx build <project>` in an Nx workspace with nx
+/// installed. The flag is true when an Nx workspace fell back to ng.
+pub fn project_build_command(workspace_root: &Path, project: &str) -> (Command, bool) {
+    let is_nx = workspace_root.join("nx.json").exists();
+    let (mut cmd, fell_back) = match is_nx.then(|| Platform::nx_command(workspace_root)).flatten() {
+        Some(nx) => (nx, false),
+        None => (Platform::ng_command(), is_nx),
+    };
+    cmd.arg("build").arg(project).current_dir(workspace_root);
+    (cmd, fell_back)
+}
+
+/// The project a discovered directory describes. project.json gives the
+/// name, type and targets, as Nx reads it; a bare ng-package.json is a
+/// library named after its folder.
+fn discovered_project(dir: &Path, root: String) -> Option<(String, AngularProject)> {
+    let folder_name = dir.file_name()?.to_string_lossy().to_string();
+    let is_package = dir.join("ng-package.json").exists();
+    let project_json: serde_json::Value = crate::jsonc::read_jsonc_file(&dir.join("project.json"))
+        .unwrap_or(serde_json::Value::Null);
+
+    let name = project_json["name"].as_str().map(str::to_string).unwrap_or(folder_name);
+    let project_type = match project_json["projectType"].as_str() {
+        Some(project_type) => project_type.to_string(),
+        None if is_package => "library".to_string(),
+        None => "application".to_string(),
+    };
+    let source_root = project_json["sourceRoot"].as_str().map(str::to_string)
+        .or_else(|| dir.join("src").is_dir().then(|| format!("{}/src", root)));
+    let architect = project_json["targets"].as_object().map(|targets| {
+        targets.iter()
+            .map(|(target, options)| (target.clone(), AngularArchitect {
+                builder: options["executor"].as_str().or(options["builder"].as_str()).unwrap_or_default().to_string(),
+                options: options.get("options").cloned().unwrap_or(serde_json::Value::Object(Default::default())),
+                configurations: options["configurations"].as_object()
+                    .map(|configurations| configurations.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
+            }))
+            .collect()
+    });
+
+    Some((name, AngularProject { root, source_root, project_type, architect, discovered: true }))
+}
+
 #[derive(Debug, Clone)]
 pub struct BuildResult {
     pub library: String,
@@ -41,23 +213,51 @@ pub struct BuildResult {
     pub duration: std::time::Duration,
     pub output: String,
     pub error: Option<String>,
+    pub sizes: Option<BundleSizes>,
+    pub size_warning: Option<String>,
 }
 
 pub struct AngularBuildManager {
     workspace: Option<AngularWorkspace>,
     workspace_root: PathBuf,
     config: Config,
+    /// Build configuration passed to ng; "production" when unset
+    configuration: Option<String>,
+}
+
+/// Which kind of workspace project `spine build` targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ProjectKind {
+    App,
+    Lib,
+    Any,
+}
+
+impl ProjectKind {
+    fn matches(self, project_type: &str) -> bool {
+        match self {
+            ProjectKind::App => project_type == "application",
+            ProjectKind::Lib => project_type == "library",
+            ProjectKind::Any => true,
+        }
+    }
 }
 
 impl AngularBuildManager {
     pub fn new(config: Config) -> Result<Self> {
-        let workspace_root = std::env::current_dir()?;
-        let workspace = Self::detect_angular_workspace(&workspace_root)?;
+        Self::new_in(config, std::env::current_dir()?)
+    }
+
+    /// A manager for the workspace at `workspace_root` rather than the
+    /// current directory
+    pub fn new_in(config: Config, workspace_root: PathBuf) -> Result<Self> {
+        let workspace = Self::detect_workspace_for(&workspace_root, &config)?;
         
         Ok(Self {
             workspace,
             workspace_root,
             config,
+            configuration: None,
         })
     }
 
@@ -65,12 +265,13 @@ impl AngularBuildManager {
         // Try to find the Angular workspace that contains this package
         if let Some(package_link) = config.links.get(package_name) {
             let workspace_root = Self::find_workspace_root_for_package(&package_link.path)?;
-            let workspace = Self::detect_angular_workspace(&workspace_root)?;
+            let workspace = Self::detect_workspace_for(&workspace_root, &config)?;
             
             Ok(Self {
                 workspace,
                 workspace_root,
                 config,
+                configuration: None,
             })
         } else {
             // Fallback to current directory
@@ -78,29 +279,22 @@ impl AngularBuildManager {
         }
     }
 
+    pub fn with_configuration(mut self, configuration: Option<String>) -> Self {
+        self.configuration = configuration;
+        self
+    }
+
+    /// Whether `dir` holds an angular.json or an Nx workspace's nx.json
+    pub fn is_workspace_root(dir: &Path) -> bool {
+        dir.join("angular.json").exists() || dir.join("nx.json").exists()
+    }
+
+    /// The nearest directory above `package_path` with an angular.json, or
+    /// failing that an nx.json, within the walk limits (see `paths::walk_up`)
     pub fn find_workspace_root_for_package(package_path: &PathBuf) -> Result<PathBuf> {
-        let mut current_path = package_path.clone();
-        
-        // Walk up the directory tree looking for angular.json
-        loop {
-            // Check if this is a dist directory (built output)
-            if current_path.file_name().and_then(|n| n.to_str()) == Some("dist") {
-                // Go up one more level from dist directory
-                if let Some(parent) = current_path.parent() {
-                    current_path = parent.to_path_buf();
-                }
-            }
-            
-            // Check for angular.json in current directory
-            let angular_json = current_path.join("angular.json");
-            if angular_json.exists() {
-                return Ok(current_path);
-            }
-            
-            // Move up one directory
-            match current_path.parent() {
-                Some(parent) => current_path = parent.to_path_buf(),
-                None => break,
+        for marker in ["angular.json", "nx.json"] {
+            if let Some(root) = crate::paths::walk_up(package_path, marker).found {
+                return Ok(root);
             }
         }
         
@@ -110,20 +304,68 @@ impl AngularBuildManager {
             .to_path_buf())
     }
 
+    /// The workspace at `root`. Projects angular.json maps to a directory
+    /// ("lib": "libs/lib", as Nx writes it) are read from that directory's
+    /// project.json; with an empty projects map, or only an nx.json, every
+    /// project.json below `root` is read instead.
     pub fn detect_angular_workspace(root: &Path) -> Result<Option<AngularWorkspace>> {
         let angular_json_path = root.join("angular.json");
-        
-        if !angular_json_path.exists() {
+        let nx_json_path = root.join("nx.json");
+        let nx_json: Option<serde_json::Value> = match nx_json_path.exists() {
+            true => Some(crate::jsonc::read_jsonc_file(&nx_json_path)?),
+            false => None,
+        };
+        let nx_default = nx_json.as_ref().and_then(|nx_json| nx_json["defaultProject"].as_str().map(str::to_string));
+
+        let mut workspace = if angular_json_path.exists() {
+            let mut json: serde_json::Value = crate::jsonc::read_jsonc_file(&angular_json_path)?;
+            let mut referenced = Vec::new();
+            if let Some(projects) = json.get_mut("projects").and_then(|projects| projects.as_object_mut()) {
+                let names: Vec<String> = projects.iter().filter(|(_, project)| project.is_string()).map(|(name, _)| name.clone()).collect();
+                for name in names {
+                    if let Some(serde_json::Value::String(dir)) = projects.remove(&name) {
+                        referenced.push((name, dir.trim_start_matches("./").trim_matches('/').to_string()));
+                    }
+                }
+            }
+            let mut workspace: AngularWorkspace = serde_json::from_value(json)
+                .map_err(|e| SpineError::Config(format!("Invalid {}: {}", angular_json_path.display(), e)))?;
+            for (name, dir) in referenced {
+                if let Some((_, project)) = discovered_project(&root.join(&dir), dir) {
+                    workspace.projects.insert(name, AngularProject { discovered: false, ..project });
+                    workspace.source = WorkspaceSource::ProjectJson;
+                }
+            }
+            workspace
+        } else if nx_json.is_some() {
+            AngularWorkspace { version: 1, projects: HashMap::new(), default_project: None, source: WorkspaceSource::Nx }
+        } else {
             return Ok(None);
-        }
-
-        let content = fs::read_to_string(&angular_json_path)?;
-        let workspace: AngularWorkspace = serde_json::from_str(&content)
-            .map_err(|e| SpineError::Config(format!("Invalid angular.json: {}", e)))?;
+        };
 
+        if workspace.projects.is_empty() {
+            workspace.projects = project_json_projects(root);
+            if !workspace.projects.is_empty() {
+                workspace.source = WorkspaceSource::ProjectJson;
+            }
+        }
+        if nx_json.is_some() {
+            workspace.source = WorkspaceSource::Nx;
+            workspace.default_project = workspace.default_project.or(nx_default);
+        }
         Ok(Some(workspace))
     }
 
+    /// `detect_angular_workspace`, with undeclared projects discovered for
+    /// the configured packages (see `AngularWorkspace::discover_projects`)
+    pub fn detect_workspace_for(root: &Path, config: &Config) -> Result<Option<AngularWorkspace>> {
+        let mut workspace = Self::detect_angular_workspace(root)?;
+        if let Some(workspace) = &mut workspace {
+            workspace.discover_projects(root, config.links.values());
+        }
+        Ok(workspace)
+    }
+
     pub fn get_library_projects(&self) -> Vec<String> {
         match &self.workspace {
             Some(workspace) => {
@@ -164,9 +406,9 @@ impl AngularBuildManager {
                         let potential_dist_path = self.workspace_root.join("dist").join(lib_name);
                         
                         // Compare paths (handle symlinks and canonicalization)
-                        if let (Ok(package_canonical), Ok(dist_canonical)) = (
-                            package_link.path.canonicalize(),
-                            potential_dist_path.canonicalize()
+                        if let (Some(package_canonical), Some(dist_canonical)) = (
+                            crate::paths::canonicalize_cached(&package_link.path),
+                            crate::paths::canonicalize_cached(&potential_dist_path)
                         ) {
                             if package_canonical == dist_canonical {
                                 return Some(lib_name.clone());
@@ -186,39 +428,79 @@ impl AngularBuildManager {
         Some(package_name.to_string())
     }
 
+    pub fn workspace_root(&self) -> &Path {
+        &self.workspace_root
+    }
+
+    pub fn has_workspace(&self) -> bool {
+        self.workspace.is_some()
+    }
+
+    /// The library a package builds from and its project directory, when the
+    /// workspace has one for it
+    pub fn resolve_library_root(&self, package_name: &str) -> Option<(String, PathBuf)> {
+        let library = self.resolve_package_to_library_name(package_name).filter(|lib| self.library_exists(lib))?;
+        let root = self.get_library_path(&library).ok()?;
+        Some((library, root))
+    }
+
     pub fn build_library(&self, library: &str, watch: bool) -> Result<BuildResult> {
+        self.build_project(library, watch, ProjectKind::Lib)
+    }
+
+    /// Resolve a package or project name to the workspace project to build and
+    /// whether it is an application. Libraries win when a configured package
+    /// name resolves to one but an application has the same name.
+    fn resolve_build_target(&self, name: &str, kind: ProjectKind) -> Result<(String, bool)> {
+        if kind != ProjectKind::App {
+            if let Some(library) = self.resolve_package_to_library_name(name).filter(|lib| self.library_exists(lib)) {
+                if kind == ProjectKind::Any && library != name && self.application_exists(name) {
+                    println!("⚠️  '{}' resolves to library '{}' but is also an application; building the library (use --type app for the application)", name, library);
+                }
+                return Ok((library, false));
+            }
+        }
+
+        if kind != ProjectKind::Lib && self.application_exists(name) {
+            return Ok((name.to_string(), true));
+        }
+
+        let message = match kind {
+            ProjectKind::Lib => format!("Library '{}' not found in Angular workspace", name),
+            ProjectKind::App => format!("Application '{}' not found in Angular workspace", name),
+            ProjectKind::Any => format!("No library or application '{}' found in Angular workspace", name),
+        };
+        Err(SpineError::PackageNotFound(message).into())
+    }
+
+    pub fn build_project(&self, name: &str, watch: bool, kind: ProjectKind) -> Result<BuildResult> {
         let start_time = Instant::now();
         
-        // Resolve package name to actual library name in workspace
-        let actual_library_name = self.resolve_package_to_library_name(library)
-            .ok_or_else(|| SpineError::PackageNotFound(format!("Could not resolve package '{}' to a library in the workspace", library)))?;
-        
-        // Validate library exists in workspace
-        if !self.library_exists(&actual_library_name) {
-            return Err(SpineError::PackageNotFound(format!("Library '{}' not found in Angular workspace", actual_library_name)).into());
-        }
+        let (actual_library_name, is_app) = self.resolve_build_target(name, kind)?;
 
-        println!("Building library: {}{}", actual_library_name, if watch { " (watch mode)" } else { "" });
+        println!("Building {}: {}{}", if is_app { "application" } else { "library" },
+            actual_library_name, if watch { " (watch mode)" } else { "" });
 
-        let mut cmd = Platform::ng_command();
-        cmd.arg("build")
-           .arg(&actual_library_name)
-           .current_dir(&self.workspace_root);
+        let (mut cmd, fell_back) = project_build_command(&self.workspace_root, &actual_library_name);
+        if fell_back {
+            println!("⚠️  Nx workspace, but nx isn't installed in it or on PATH; building with ng");
+        }
 
         if watch {
             cmd.arg("--watch");
         }
 
-        // Add common Angular library build options
-        cmd.args(&["--configuration", "production"]);
+        cmd.args(["--configuration", self.configuration.as_deref().unwrap_or("production")]);
+        crate::throttle::prepare(&mut cmd, &self.config.build);
 
-        let output = if watch {
+        let mut output = if watch {
             // For watch mode, we need to handle it differently
             self.run_watch_command(cmd, &actual_library_name)?
         } else {
-            let result = cmd.output()?;
-            let stdout = String::from_utf8_lossy(&result.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&result.stderr).to_string();
+            let _slot = crate::throttle::acquire(&self.config.build);
+            let result = run_command(cmd, self.config.timeouts.build(), OutputMode::Progress("Building..."))?;
+            let stdout = result.stdout;
+            let stderr = result.stderr;
             
             if result.status.success() {
                 println!("✅ Successfully built {}", actual_library_name);
@@ -228,6 +510,8 @@ impl AngularBuildManager {
                     duration: start_time.elapsed(),
                     output: stdout,
                     error: None,
+                    sizes: None,
+                    size_warning: None,
                 }
             } else {
                 println!("❌ Failed to build {}", actual_library_name);
@@ -238,27 +522,152 @@ impl AngularBuildManager {
                     duration: start_time.elapsed(),
                     output: stdout,
                     error: Some(stderr),
+                    sizes: None,
+                    size_warning: None,
                 }
             }
         };
 
+        if !watch {
+            self.record_build_history(&mut output);
+        }
+
         Ok(output)
     }
 
-    pub fn build_all_libraries(&self) -> Result<Vec<BuildResult>> {
-        let libraries = self.get_linked_libraries();
+    /// Capture bundle sizes for a finished build and append it to the build history.
+    /// Any failure here is reported as a warning and never affects the build result.
+    fn record_build_history(&self, result: &mut BuildResult) {
+        // Bundle sizes are tracked for library output only
+        if result.success && self.library_exists(&result.library) {
+            result.sizes = self.library_output_path(&result.library)
+                .and_then(|dist| history::collect_bundle_sizes(&dist, &result.output));
+        }
+
+        let mut build_history = match BuildHistory::load(&self.config.file) {
+            Ok(h) => h,
+            Err(e) => {
+                eprintln!("Warning: Could not read build history: {}", e);
+                return;
+            }
+        };
+
+        if let Some(sizes) = &result.sizes {
+            if let Some(previous) = build_history.last_sizes(&result.library) {
+                if let Some(pct) = history::growth_percent(previous.total, sizes.total) {
+                    if pct > self.config.history.size_warn_percent {
+                        let warning = format!(
+                            "{} grew {:.1}% ({} → {}) since its previous build",
+                            result.library,
+                            pct,
+                            history::format_bytes(previous.total),
+                            history::format_bytes(sizes.total)
+                        );
+                        println!("⚠️  {}", warning);
+                        result.size_warning = Some(warning);
+                    }
+                }
+            }
+        }
+
+        build_history.record_build(BuildRecord {
+            library: result.library.clone(),
+            timestamp: history::now_timestamp(),
+            success: result.success,
+            duration_ms: result.duration.as_millis() as u64,
+            sizes: result.sizes.clone(),
+        });
+
+        // Snapshot the dist under its npm name so `spine diff` can compare against it
+        if result.success {
+            if let Some(dist) = self.library_output_path(&result.library) {
+                if let Ok(package_info) = crate::package::parse_package_json(&dist.join("package.json")) {
+                    build_history.record_snapshot(crate::diff::DistSnapshot::capture(&package_info.name, &dist, "build"));
+                }
+            }
+        }
+
+        if let Err(e) = build_history.save(&self.config.file) {
+            eprintln!("Warning: Could not save build history: {}", e);
+        }
+    }
+
+    /// npm package name of each library, from the package.json in its project root
+    pub fn library_npm_names(&self) -> HashMap<String, String> {
+        let mut names = HashMap::new();
+        if let Some(workspace) = &self.workspace {
+            for (library, project) in workspace.projects.iter().filter(|(_, p)| p.project_type == "library") {
+                let package_json = self.workspace_root.join(&project.root).join("package.json");
+                if let Ok(info) = crate::package::parse_package_json(&package_json) {
+                    names.insert(info.name, library.clone());
+                }
+            }
+        }
+        names
+    }
+
+    /// Locate a library's build output directory, preferring ng-package.json's `dest`
+    pub fn library_output_path(&self, library: &str) -> Option<PathBuf> {
+        let workspace = self.workspace.as_ref()?;
+        let project = workspace.projects.get(library)?;
+        let project_root = self.workspace_root.join(&project.root);
+
+        let ng_package = project_root.join("ng-package.json");
+        if let Ok(json) = crate::jsonc::read_jsonc_file::<serde_json::Value>(&ng_package) {
+            if let Some(dest) = json.get("dest").and_then(|d| d.as_str()) {
+                let dest_path = project_root.join(dest);
+                if dest_path.exists() {
+                    return Some(package_dir(&dest_path));
+                }
+            }
+        }
+
+        [
+            self.workspace_root.join("dist").join(library),
+            self.workspace_root.join("dist").join("libs").join(library),
+        ]
+        .into_iter()
+        .find(|p| p.exists())
+        .map(|p| package_dir(&p))
+    }
+
+    /// Build every project of `kind`, libraries before applications. With
+    /// `fail_fast` the first failed build stops the run and is returned as an
+    /// error. Results go into `results` as builds finish, so they are there
+    /// for a report even when the run stops early.
+    pub fn build_all(&self, kind: ProjectKind, fail_fast: bool, results: &mut Vec<BuildResult>) -> Result<()> {
+        let libraries = if kind.matches("library") { self.get_linked_libraries() } else { Vec::new() };
+        let applications = if kind.matches("application") { self.get_application_projects() } else { Vec::new() };
         
-        if libraries.is_empty() {
-            println!("No linked libraries found to build");
-            return Ok(Vec::new());
+        if libraries.is_empty() && applications.is_empty() {
+            match kind {
+                ProjectKind::Lib => println!("No linked libraries found to build"),
+                ProjectKind::App => println!("No applications found to build"),
+                ProjectKind::Any => println!("No linked libraries or applications found to build"),
+            }
+            return Ok(());
         }
 
-        println!("Building {} linked libraries...", libraries.len());
-        let mut results = Vec::new();
+        match kind {
+            ProjectKind::Lib => println!("Building {} linked libraries...", libraries.len()),
+            ProjectKind::App => println!("Building {} applications...", applications.len()),
+            ProjectKind::Any => println!("Building {} linked libraries and {} applications...", libraries.len(), applications.len()),
+        }
+        let mut aborted = None;
 
-        for library in libraries {
-            let result = self.build_library(&library, false)?;
+        // Libraries first, as applications consume their output
+        let projects: Vec<(String, ProjectKind)> = libraries.into_iter().map(|name| (name, ProjectKind::Lib))
+            .chain(applications.into_iter().map(|name| (name, ProjectKind::App)))
+            .collect();
+        for (index, (project, project_kind)) in projects.iter().enumerate() {
+            let result = self.build_project(project, false, *project_kind)?;
+            let failed = !result.success;
+            let error = result.error.clone().unwrap_or_else(|| "build failed".to_string());
             results.push(result);
+            if failed && fail_fast {
+                aborted = Some(crate::batch::BatchAbort::new(project, error, index, projects.len()));
+                break;
+            }
         }
 
         // Summary
@@ -270,8 +679,21 @@ impl AngularBuildManager {
         if failed > 0 {
             println!("  ❌ Failed: {}", failed);
         }
+        if kind == ProjectKind::Any {
+            for (label, is_app) in [("Libraries", false), ("Applications", true)] {
+                let built: Vec<&BuildResult> = results.iter().filter(|r| self.application_exists(&r.library) == is_app).collect();
+                let ok = built.iter().filter(|r| r.success).count();
+                println!("  {} {}: {}/{} built", if is_app { "🖥️ " } else { "📚" }, label, ok, built.len());
+            }
+        }
+        for warning in results.iter().filter_map(|r| r.size_warning.as_ref()) {
+            println!("  ⚠️  {}", warning);
+        }
 
-        Ok(results)
+        if let Some(abort) = aborted {
+            return Err(abort.into_error().into());
+        }
+        Ok(())
     }
 
     pub fn build_affected_libraries(&self) -> Result<Vec<BuildResult>> {
@@ -297,10 +719,7 @@ impl AngularBuildManager {
 
     fn detect_affected_libraries(&self) -> Result<Vec<String>> {
         // Check if git is available and we're in a git repository
-        let git_check = Command::new("git")
-            .args(&["rev-parse", "--git-dir"])
-            .current_dir(&self.workspace_root)
-            .output();
+        let git_check = run_command(self.git_command(&["rev-parse", "--git-dir"]), Some(GIT_TIMEOUT), OutputMode::Capture);
 
         if git_check.is_err() {
             // Fallback: build all linked libraries
@@ -309,36 +728,27 @@ impl AngularBuildManager {
         }
 
         // Get changed files since last commit
-        let output = Command::new("git")
-            .args(&["diff", "--name-only", "HEAD~1..HEAD"])
-            .current_dir(&self.workspace_root)
-            .output()?;
+        let output = run_command(self.git_command(&["diff", "--name-only", "HEAD~1..HEAD"]), Some(GIT_TIMEOUT), OutputMode::Capture)?;
 
-        let changed_files: HashSet<String> = String::from_utf8_lossy(&output.stdout)
+        let changed_files: HashSet<String> = output.stdout.as_str()
             .lines()
             .map(|s| s.to_string())
             .collect();
 
         if changed_files.is_empty() {
             // Check staged files if no committed changes
-            let staged_output = Command::new("git")
-                .args(&["diff", "--name-only", "--cached"])
-                .current_dir(&self.workspace_root)
-                .output()?;
+            let staged_output = run_command(self.git_command(&["diff", "--name-only", "--cached"]), Some(GIT_TIMEOUT), OutputMode::Capture)?;
 
-            let staged_files: HashSet<String> = String::from_utf8_lossy(&staged_output.stdout)
+            let staged_files: HashSet<String> = staged_output.stdout.as_str()
                 .lines()
                 .map(|s| s.to_string())
                 .collect();
 
             if staged_files.is_empty() {
                 // Check working directory changes
-                let working_output = Command::new("git")
-                    .args(&["diff", "--name-only"])
-                    .current_dir(&self.workspace_root)
-                    .output()?;
+                let working_output = run_command(self.git_command(&["diff", "--name-only"]), Some(GIT_TIMEOUT), OutputMode::Capture)?;
 
-                return Ok(self.get_affected_from_files(&String::from_utf8_lossy(&working_output.stdout)));
+                return Ok(self.get_affected_from_files(&working_output.stdout));
             } else {
                 return Ok(self.get_affected_from_files(&staged_files.iter().cloned().collect::<Vec<_>>().join("\n")));
             }
@@ -347,6 +757,12 @@ impl AngularBuildManager {
         Ok(self.get_affected_from_files(&changed_files.iter().cloned().collect::<Vec<_>>().join("\n")))
     }
 
+    fn git_command(&self, args: &[&str]) -> Command {
+        let mut cmd = Command::new("git");
+        cmd.args(args).current_dir(&self.workspace_root);
+        cmd
+    }
+
     fn get_affected_from_files(&self, files_content: &str) -> Vec<String> {
         let changed_files: HashSet<String> = files_content
             .lines()
@@ -390,19 +806,19 @@ impl AngularBuildManager {
         println!("🔄 Starting watch mode for {}...", library);
         println!("Press Ctrl+C to stop watching");
 
-        cmd.stdout(Stdio::inherit())
-           .stderr(Stdio::inherit())
-           .stdin(Stdio::null());
+        cmd.stdin(Stdio::null());
 
-        let start_time = Instant::now();
-        let status = cmd.status()?;
+        let result = run_command(cmd, None, OutputMode::Stream)?;
+        let status = result.status;
 
         Ok(BuildResult {
             library: library.to_string(),
             success: status.success(),
-            duration: start_time.elapsed(),
+            duration: result.elapsed,
             output: "Watch mode completed".to_string(),
             error: if status.success() { None } else { Some("Watch mode terminated with error".to_string()) },
+            sizes: None,
+            size_warning: None,
         })
     }
 
@@ -417,6 +833,25 @@ impl AngularBuildManager {
         }
     }
 
+    fn application_exists(&self, name: &str) -> bool {
+        self.workspace.as_ref()
+            .and_then(|workspace| workspace.projects.get(name))
+            .is_some_and(|p| p.project_type == "application")
+    }
+
+    pub fn get_application_projects(&self) -> Vec<String> {
+        let mut applications: Vec<String> = self.workspace.as_ref()
+            .map(|workspace| {
+                workspace.projects.iter()
+                    .filter(|(_, project)| project.project_type == "application")
+                    .map(|(name, _)| name.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        applications.sort();
+        applications
+    }
+
     pub fn get_build_dependencies(&self, library: &str) -> Result<Vec<String>> {
         // Read the library's package.json to get dependencies
         let lib_path = self.get_library_path(library)?;
@@ -505,17 +940,29 @@ impl AngularBuildManager {
     }
 }
 
-pub fn build_command(library: Option<String>, all: bool, watch: bool, affected: bool) -> Result<()> {
-    let config = Config::load_or_create()?;
-    
+#[derive(Default)]
+pub struct BuildOptions {
+    pub library: Option<String>,
+    pub all: bool,
+    pub watch: bool,
+    pub affected: bool,
+    pub kind: Option<ProjectKind>,
+    pub configuration: Option<String>,
+    pub fail_fast: bool,
+    pub report: Option<ReportTarget>,
+}
+
+pub fn build_command(config: &Config, project: &Path, options: BuildOptions) -> Result<()> {
+    let BuildOptions { library, all, watch, affected, kind, configuration, fail_fast, report } = options;
+
     // If we're building a specific library, try to find its workspace
     let build_manager = if let Some(ref lib_name) = library {
         // Try to create build manager from the linked package's workspace
         match AngularBuildManager::new_from_linked_package(config.clone(), lib_name) {
             Ok(manager) if manager.workspace.is_some() => manager,
             _ => {
-                // Fallback to current directory
-                let manager = AngularBuildManager::new(config)?;
+                // Fallback to the project's own workspace
+                let manager = AngularBuildManager::new_in(config.clone(), project.to_path_buf())?;
                 if manager.workspace.is_none() {
                     return Err(SpineError::Config(
                         format!("No Angular workspace detected for library '{}'. Make sure you're in an Angular project directory with angular.json, or that the package is linked to a path within an Angular workspace.", lib_name)
@@ -525,43 +972,120 @@ pub fn build_command(library: Option<String>, all: bool, watch: bool, affected:
             }
         }
     } else {
-        // For --all or --affected, use current directory
-        let manager = AngularBuildManager::new(config)?;
+        // For --all or --affected, use the project's workspace
+        let manager = AngularBuildManager::new_in(config.clone(), project.to_path_buf())?;
         if manager.workspace.is_none() {
             return Err(SpineError::Config("No Angular workspace detected. Make sure you're in an Angular project directory with angular.json".to_string()).into());
         }
         manager
     };
+    let build_manager = build_manager.with_configuration(configuration);
 
-    match (library, all, affected) {
+    let mut results = Vec::new();
+    let outcome = match (library, all, affected) {
         (Some(lib), false, false) => {
-            build_manager.build_library(&lib, watch)?;
+            build_manager.build_project(&lib, watch, kind.unwrap_or(ProjectKind::Any)).map(|result| results.push(result))
         }
         (None, true, false) => {
             if watch {
                 return Err(SpineError::Config("Watch mode is not supported with --all. Use individual library builds for watch mode.".to_string()).into());
             }
-            build_manager.build_all_libraries()?;
+            build_manager.build_all(kind.unwrap_or(ProjectKind::Lib), fail_fast, &mut results)
         }
         (None, false, true) => {
             if watch {
                 return Err(SpineError::Config("Watch mode is not supported with --affected. Use individual library builds for watch mode.".to_string()).into());
             }
-            build_manager.build_affected_libraries()?;
+            build_manager.build_affected_libraries().map(|built| results = built)
         }
         (None, false, false) => {
             // Show status if no specific action requested
-            build_manager.show_build_status()?;
+            build_manager.show_build_status()
         }
         _ => {
             return Err(SpineError::Config("Invalid combination of build options".to_string()).into());
         }
+    };
+
+    if let Some(target) = &report {
+        build_report(&results).write(target);
     }
+    outcome
+}
 
-    Ok(())
+/// `spine build --report`: each build is a test case, failing with its stderr
+fn build_report(results: &[BuildResult]) -> Report {
+    let cases = results.iter()
+        .map(|result| ReportCase {
+            duration: Some(result.duration),
+            ..match &result.error {
+                Some(error) if !result.success => ReportCase::failed(&result.library, error.trim()),
+                _ if !result.success => ReportCase::failed(&result.library, "build failed"),
+                _ => ReportCase::passed(&result.library),
+            }
+        })
+        .collect();
+    let builds: Vec<serde_json::Value> = results.iter()
+        .map(|result| serde_json::json!({
+            "library": result.library,
+            "success": result.success,
+            "duration_ms": result.duration.as_millis() as u64,
+            "error": result.error.as_deref().map(str::trim),
+            "sizes": result.sizes,
+            "size_warning": result.size_warning,
+        }))
+        .collect();
+    let successful = results.iter().filter(|result| result.success).count();
+    Report {
+        suite: "spine build".to_string(),
+        cases,
+        json: serde_json::json!({
+            "successful": successful,
+            "failed": results.len() - successful,
+            "builds": builds,
+        }),
+    }
+}
+
+/// Add a publish to the build history. Failures are only warnings; the
+/// package is already on the registry.
+fn record_publish(config_file: &ConfigFile, package: &str, package_json_path: &Path, dirty_files: Option<Vec<String>>) {
+    let result = BuildHistory::load(config_file).and_then(|mut history| {
+        history.record_publish(history::PublishRecord {
+            package: package.to_string(),
+            version: crate::package::get_package_version(package_json_path).ok(),
+            timestamp: history::now_timestamp(),
+            dirty: dirty_files.as_ref().map(|files| !files.is_empty()),
+            dirty_files: dirty_files.unwrap_or_default(),
+        });
+        history.save(config_file)
+    });
+    if let Err(e) = result {
+        eprintln!("Warning: Could not record the publish in the history: {}", e);
+    }
+}
+
+/// Uncommitted and untracked files under `dir`, from `git status --porcelain`
+/// scoped to it. None when `dir` isn't in a git repository.
+pub fn uncommitted_files(dir: &Path) -> Option<Vec<String>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["status", "--porcelain", "--untracked-files=all", "--", "."])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .map(|file| file.to_string())
+        .collect())
 }
 
-pub fn publish_command(config: &Config, package_name: &str, skip_build: bool, dry_run: bool) -> Result<()> {
+pub fn publish_command(config: &Config, package_name: &str, skip_build: bool, dry_run: bool, require_clean: bool, allow_dirty: bool) -> Result<()> {
     // Verify the package exists in config
     let package_link = config.links.get(package_name)
         .ok_or_else(|| SpineError::PackageNotFound(format!("Package '{}' not found in Spine configuration. Use 'spine add' to add it first.", package_name)))?;
@@ -579,6 +1103,34 @@ pub fn publish_command(config: &Config, package_name: &str, skip_build: bool, dr
     let library_name = build_manager.resolve_package_to_library_name(package_name)
         .ok_or_else(|| SpineError::PackageNotFound(format!("Could not resolve package '{}' to a library in the workspace", package_name)))?;
 
+    // Check the source tree before anything is built; the result is recorded either way
+    let library_path = build_manager.get_library_path(&library_name).ok();
+    let dirty_files = library_path.as_deref().and_then(uncommitted_files);
+    if let Some(files) = dirty_files.as_ref().filter(|files| !files.is_empty()) {
+        let icon = if require_clean && !allow_dirty { "❌" } else { "⚠️ " };
+        println!("{} {} has {} uncommitted file(s):", icon, library_name, files.len());
+        for file in files.iter().take(20) {
+            println!("     • {}", file);
+        }
+        if files.len() > 20 {
+            println!("     … and {} more", files.len() - 20);
+        }
+        if require_clean && !allow_dirty {
+            return Err(SpineError::Config(format!(
+                "Refusing to publish {} from a dirty working tree (--require-clean); commit or stash the changes, or pass --allow-dirty",
+                package_name
+            )).into());
+        }
+    } else if require_clean && dirty_files.is_none() && !allow_dirty {
+        let reason = match &library_path {
+            Some(path) => format!("{} is not in a git repository", display_path(path)),
+            None => "its source was not found in the workspace".to_string(),
+        };
+        return Err(SpineError::Config(format!(
+            "Cannot check {} for uncommitted changes: {} (pass --allow-dirty to publish anyway)", package_name, reason
+        )).into());
+    }
+
     // Step 1: Build the package (unless skipped)
     if !skip_build {
         println!("📦 Building package: {}", library_name);
@@ -598,7 +1150,7 @@ pub fn publish_command(config: &Config, package_name: &str, skip_build: bool, dr
     // Step 2: Find the built package directory
     let publish_dir = find_publish_directory(&build_manager, &library_name, &package_link.path)?;
     
-    println!("📂 Publishing from directory: {}", publish_dir.display());
+    println!("📂 Publishing from directory: {}", display_path(&publish_dir));
 
     // Verify package.json exists in publish directory
     let package_json_path = publish_dir.join("package.json");
@@ -608,7 +1160,42 @@ pub fn publish_command(config: &Config, package_name: &str, skip_build: bool, dr
         ).into());
     }
 
-    // Step 3: Run npm publish
+    // Step 3: Resolve registry settings. The publish directory usually lacks the
+    // project's .npmrc, so look at the library source and workspace root too.
+    let mut npmrc_dirs = Vec::new();
+    if let Ok(library_path) = build_manager.get_library_path(&library_name) {
+        npmrc_dirs.push(library_path);
+    }
+    npmrc_dirs.push(build_manager.workspace_root.clone());
+
+    let registry = crate::npmrc::resolve_registry(package_name, &npmrc_dirs);
+    println!("📮 Registry: {} (from {})", registry.registry, registry.source);
+
+    let _npmrc_guard = crate::npmrc::TemporaryNpmrc::install(&publish_dir, &npmrc_dirs)?;
+
+    // Step 4: Refuse a version the registry already has; npm only finds out
+    // after packing and uploading it
+    let built = crate::package::parse_package_json(&package_json_path)?;
+    let registry_client = crate::registry::Registry::new(config, &publish_dir).with_registry(registry.registry.as_str());
+    match registry_client.published_versions(&built.name) {
+        Ok(versions) if versions.contains(&built.version) => {
+            if !dry_run {
+                return Err(SpineError::Config(format!(
+                    "{}@{} is already published to {}; bump the version before publishing", built.name, built.version, registry.registry
+                )).into());
+            }
+            println!("⚠️  {}@{} is already published; a real publish would fail", built.name, built.version);
+        }
+        Ok(_) => {
+            let tags = registry_client.dist_tags(&built.name).unwrap_or_default();
+            let tags: Vec<String> = tags.iter().map(|(tag, version)| format!("{} {}", tag, version)).collect();
+            let current = if tags.is_empty() { "first publish".to_string() } else { tags.join(", ") };
+            println!("✓ {}@{} is not published yet ({})", built.name, built.version, current);
+        }
+        Err(e) => println!("⚠️  Could not check the registry for {}@{}: {}", built.name, built.version, e),
+    }
+
+    // Step 5: Run npm publish
     let mut cmd = Platform::npm_command();
     cmd.arg("publish")
        .current_dir(&publish_dir);
@@ -620,9 +1207,9 @@ pub fn publish_command(config: &Config, package_name: &str, skip_build: bool, dr
         println!("🚀 Publishing package to npm");
     }
 
-    let output = cmd.output()?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
+    let output = run_command(cmd, config.timeouts.registry(), OutputMode::Progress("Publishing..."))?;
+    let stdout = &output.stdout;
+    let stderr = &output.stderr;
 
     if output.status.success() {
         if dry_run {
@@ -630,6 +1217,13 @@ pub fn publish_command(config: &Config, package_name: &str, skip_build: bool, dr
             println!("📄 Package would be published with the following details:");
         } else {
             println!("✅ Package published successfully!");
+            record_publish(&config.file, package_name, &package_json_path, dirty_files);
+            match registry_client.tarball_manifest(&built.name, &built.version) {
+                Ok(Some(manifest)) => println!("📦 Registry serves {}{}", manifest.tarball,
+                    manifest.file_count.map(|count| format!(" ({} files)", count)).unwrap_or_default()),
+                Ok(None) => println!("⏳ {}@{} isn't visible on the registry yet", built.name, built.version),
+                Err(e) => println!("⚠️  Could not confirm the publish on the registry: {}", e),
+            }
         }
         
         if !stdout.is_empty() {
@@ -650,9 +1244,10 @@ pub fn publish_command(config: &Config, package_name: &str, skip_build: bool, dr
 }
 
 fn find_publish_directory(build_manager: &AngularBuildManager, library_name: &str, package_path: &PathBuf) -> Result<PathBuf> {
-    // First, try to use the package path directly if it contains a package.json
-    if package_path.join("package.json").exists() {
-        return Ok(package_path.clone());
+    // First, try to use the package path directly if it (or a known output
+    // subfolder of it) contains a package.json
+    if let Some(root) = package_root(package_path) {
+        return Ok(root);
     }
 
     // If not, try to find the dist output directory
@@ -665,10 +1260,8 @@ fn find_publish_directory(build_manager: &AngularBuildManager, library_name: &st
         workspace_root.join("projects").join(library_name).join("dist"),
     ];
 
-    for dist_path in possible_dist_paths {
-        if dist_path.exists() && dist_path.join("package.json").exists() {
-            return Ok(dist_path);
-        }
+    if let Some(root) = possible_dist_paths.iter().find_map(|dist_path| package_root(dist_path)) {
+        return Ok(root);
     }
 
     // If we still can't find it, try to get the library's architect build output path
@@ -678,9 +1271,8 @@ fn find_publish_directory(build_manager: &AngularBuildManager, library_name: &st
                 if let Some(build_config) = architect.get("build") {
                     if let Some(options) = build_config.options.as_object() {
                         if let Some(output_path) = options.get("outputPath").and_then(|v| v.as_str()) {
-                            let full_output_path = workspace_root.join(output_path);
-                            if full_output_path.exists() && full_output_path.join("package.json").exists() {
-                                return Ok(full_output_path);
+                            if let Some(root) = package_root(&workspace_root.join(output_path)) {
+                                return Ok(root);
                             }
                         }
                     }
@@ -692,4 +1284,203 @@ fn find_publish_directory(build_manager: &AngularBuildManager, library_name: &st
     Err(SpineError::Config(
         format!("Could not find built package directory for '{}'. Make sure the package has been built.", library_name)
     ).into())
-}
\ No newline at end of file
+}
+
+/// Where a link's package is built now when its configured path has gone
+/// missing, e.g. after an Angular upgrade moved the output from dist/ui-kit to
+/// dist/libs/ui-kit. The package must still be a library of the workspace its
+/// old path was in, matched by npm name, and already built at the new path.
+pub fn moved_output_path(link: &crate::config::PackageLink) -> Option<PathBuf> {
+    if link.pending || link.path.exists() {
+        return None;
+    }
+
+    let workspace_root = AngularBuildManager::find_workspace_root_for_package(&link.path).ok()?;
+    let mut workspace = AngularBuildManager::detect_angular_workspace(&workspace_root).ok()??;
+    workspace.discover_projects(&workspace_root, [link]);
+    let manager = AngularBuildManager {
+        workspace: Some(workspace),
+        workspace_root,
+        config: Config::default(),
+        configuration: None,
+    };
+
+    let library = manager.library_npm_names().remove(link.npm_name())
+        .or_else(|| manager.library_exists(&link.name).then(|| link.name.clone()))?;
+    let output = manager.library_output_path(&library)?;
+    let built = crate::package::parse_package_json(&output.join("package.json")).ok()?;
+    (built.name == link.npm_name() && output != link.path).then_some(output)
+}
+
+/// Folders one level down where newer builders put a build's package.json:
+/// the application builder's `browser` output. ng-packagr's nested layout,
+/// `dist/<name>/<name>`, is probed by name in `package_root`.
+const OUTPUT_SUBFOLDERS: [&str; 1] = ["browser"];
+
+/// The directory holding a build's package.json: `dir` itself, or a known
+/// output subfolder of it when only that has one. None when neither does.
+pub fn package_root(dir: &Path) -> Option<PathBuf> {
+    if dir.join("package.json").exists() {
+        return Some(dir.to_path_buf());
+    }
+    let nested = dir.file_name().map(|name| dir.join(name));
+    OUTPUT_SUBFOLDERS.iter()
+        .map(|folder| dir.join(folder))
+        .chain(nested)
+        .find(|candidate| candidate.join("package.json").exists())
+}
+
+/// When `dir` lacks a package.json but an output subfolder has one: that
+/// folder, and `written` (how the user typed `dir`) with the folder appended
+pub fn nested_package_root(dir: &Path, written: &str) -> Option<(PathBuf, String)> {
+    let root = package_root(dir).filter(|root| root != dir)?;
+    let folder = root.file_name()?.to_string_lossy().to_string();
+    Some((root, format!("{}/{}", written.trim_end_matches(['/', '\\']), folder)))
+}
+
+/// `package_root` of `dir`, or `dir` when it has no package.json anywhere
+pub fn package_dir(dir: &Path) -> PathBuf {
+    package_root(dir).unwrap_or_else(|| dir.to_path_buf())
+}
+
+/// Entry points a built package's package.json declares but its output
+/// directory doesn't contain. Empty when an app can resolve the package.
+pub fn missing_entry_points(output_dir: &Path) -> Vec<String> {
+    let package_json = output_dir.join("package.json");
+    let json = match fs::read_to_string(&package_json).ok().and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok()) {
+        Some(json) => json,
+        None => return vec!["package.json".to_string()],
+    };
+
+    let mut entry_points = Vec::new();
+    for field in ["main", "module", "typings", "types"] {
+        if let Some(entry) = json.get(field).and_then(|v| v.as_str()) {
+            entry_points.push(entry.to_string());
+        }
+    }
+    if let Some(root_export) = json.get("exports").and_then(|exports| exports.get(".")) {
+        collect_export_targets(root_export, &mut entry_points);
+    }
+
+    entry_points.sort();
+    entry_points.dedup();
+    entry_points.retain(|entry| !output_dir.join(entry).exists());
+    entry_points
+}
+
+/// File targets of an `exports` entry, through any nesting of conditions
+fn collect_export_targets(value: &serde_json::Value, targets: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(target) if target.starts_with("./") && !target.contains('*') => {
+            targets.push(target.to_string());
+        }
+        serde_json::Value::Object(conditions) => {
+            for condition in conditions.values() {
+                collect_export_targets(condition, targets);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("workspaces").join(name)
+    }
+
+    fn sorted(mut names: Vec<String>) -> Vec<String> {
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn detects_a_commented_angular_json() {
+        let root = fixture("commented");
+        let workspace = AngularBuildManager::detect_angular_workspace(&root).unwrap().expect("a workspace");
+        assert_eq!(workspace.default_project.as_deref(), Some("shell"));
+        assert_eq!(workspace.source, WorkspaceSource::ProjectJson);
+
+        let shell = &workspace.projects["shell"];
+        assert_eq!(shell.project_type, "application");
+        let options = &shell.architect.as_ref().unwrap()["build"].options;
+        assert_eq!(options["baseHref"], "https://cdn.example.com/shell/");
+        assert_eq!(options["styles"], serde_json::json!(["src/styles.scss"]));
+
+        // A directory reference reads that project's commented project.json
+        let feature = &workspace.projects["feature"];
+        assert_eq!(feature.root, "libs/feature");
+        assert_eq!(feature.project_type, "library");
+        assert!(!feature.discovered);
+        assert_eq!(feature.architect.as_ref().unwrap()["build"].builder, "@nx/angular:package");
+    }
+
+    #[test]
+    fn lists_libraries_of_a_commented_angular_json() {
+        let manager = AngularBuildManager::new_in(Config::default(), fixture("commented")).unwrap();
+        assert!(manager.has_workspace());
+        assert_eq!(sorted(manager.get_library_projects()), ["feature", "ui-kit"]);
+        assert_eq!(manager.get_application_projects(), ["shell"]);
+        assert_eq!(manager.library_npm_names().get("@acme/ui-kit").map(String::as_str), Some("ui-kit"));
+    }
+
+    #[test]
+    fn names_the_file_when_angular_json_is_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("angular.json"), "{ // unterminated\n  \"version\": 1,\n  \"projects\": {\n").unwrap();
+        let error = AngularBuildManager::detect_angular_workspace(dir.path()).unwrap_err().to_string();
+        assert!(error.contains("angular.json"), "{}", error);
+    }
+
+    #[test]
+    fn finds_package_json_in_old_and_new_dist_layouts() {
+        let dist = fixture("dist-layouts").join("dist");
+        // Old layout: package.json at the output root
+        assert_eq!(package_root(&dist.join("legacy")), Some(dist.join("legacy")));
+        // The application builder's browser/ folder
+        assert_eq!(package_root(&dist.join("shop")), Some(dist.join("shop").join("browser")));
+        // ng-packagr's nested dist/<name>/<name>
+        assert_eq!(package_root(&dist.join("nested")), Some(dist.join("nested").join("nested")));
+        assert_eq!(package_root(&dist.join("unbuilt")), None);
+        assert_eq!(package_dir(&dist.join("unbuilt")), dist.join("unbuilt"));
+    }
+
+    #[test]
+    fn appends_the_nested_folder_to_the_written_path() {
+        let dist = fixture("dist-layouts").join("dist");
+        assert_eq!(
+            nested_package_root(&dist.join("shop"), "~/acme/dist/shop/"),
+            Some((dist.join("shop").join("browser"), "~/acme/dist/shop/browser".to_string()))
+        );
+        assert_eq!(
+            nested_package_root(&dist.join("nested"), "dist/nested"),
+            Some((dist.join("nested").join("nested"), "dist/nested/nested".to_string()))
+        );
+        assert_eq!(nested_package_root(&dist.join("legacy"), "dist/legacy"), None);
+        assert_eq!(nested_package_root(&dist.join("unbuilt"), "dist/unbuilt"), None);
+    }
+
+    #[test]
+    fn resolves_library_output_in_either_layout() {
+        let root = fixture("dist-layouts");
+        let manager = AngularBuildManager::new_in(Config::default(), root.clone()).unwrap();
+        assert_eq!(manager.library_output_path("legacy"), Some(root.join("dist").join("legacy")));
+        // ng-package.json's dest points at the outer folder, as ../../dist/nested
+        let nested = manager.library_output_path("nested").unwrap();
+        assert_eq!(nested.canonicalize().unwrap(), root.join("dist").join("nested").join("nested").canonicalize().unwrap());
+    }
+
+    #[test]
+    fn publishes_the_directory_holding_package_json() {
+        let root = fixture("dist-layouts");
+        let dist = root.join("dist");
+        let manager = AngularBuildManager::new_in(Config::default(), root.clone()).unwrap();
+        assert_eq!(find_publish_directory(&manager, "legacy", &dist.join("legacy")).unwrap(), dist.join("legacy"));
+        assert_eq!(find_publish_directory(&manager, "shop", &dist.join("shop")).unwrap(), dist.join("shop").join("browser"));
+        // A link to the library's sources falls back to its dist output
+        let sources = root.join("projects").join("gone");
+        assert_eq!(find_publish_directory(&manager, "nested", &sources).unwrap(), dist.join("nested").join("nested"));
+    }
+}
@@ -1,17 +1,21 @@
 use anyhow::Result;
-use indicatif::{ProgressBar, ProgressStyle};
 use serde_json;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
-use std::time::Duration;
-use crate::angular::{AngularBuildManager, AngularWorkspace};
-use crate::config::Config;
+use std::time::{Duration, Instant};
+use crate::angular::{missing_entry_points, AngularBuildManager, AngularWorkspace};
+use crate::config::{BuildConfig, Config, ConfigFile};
 use crate::error::SpineError;
+use crate::history::{self, BuildHistory, RebuildStats, ServeSession, WatcherChange};
 use crate::platform::Platform;
+use crate::paths::display_path;
+use crate::progress::Progress;
 
 pub struct AngularCliIntegration {
     workspace: AngularWorkspace,
@@ -21,7 +25,7 @@ pub struct AngularCliIntegration {
 
 impl AngularCliIntegration {
     pub fn new(config: Config, workspace_root: PathBuf) -> Result<Self> {
-        let workspace = AngularBuildManager::detect_angular_workspace(&workspace_root)?
+        let workspace = AngularBuildManager::detect_workspace_for(&workspace_root, &config)?
             .ok_or_else(|| SpineError::angular_workspace_not_found(&workspace_root.display().to_string()))?;
 
         Ok(Self {
@@ -273,13 +277,7 @@ impl AngularCliIntegration {
         }
 
         // Create progress spinner for generation
-        let spinner = ProgressBar::new_spinner();
-        spinner.set_style(
-            ProgressStyle::default_spinner()
-                .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-                .template("{spinner:.blue} {msg}")
-                .unwrap()
-        );
+        let spinner = Progress::spinner(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"], "{spinner:.blue} {msg}");
         
         if let Some(library) = lib {
             spinner.set_message(format!("Generating in library '{}'...", library));
@@ -328,12 +326,19 @@ impl NgProxy {
 
         println!("🔄 Proxying Angular CLI command with Spine enhancements...");
         
-        let enhanced_args = self.enhance_ng_command(args)?;
-        
+        let mut enhanced_args = self.enhance_ng_command(args)?;
+        apply_prompt_flags(&mut enhanced_args, std::io::stdin().is_terminal());
+
+        // Everything Spine has to say is printed before ng starts, so nothing
+        // lands in the middle of a schematic's prompt
+        let _ = std::io::stdout().flush();
         let mut cmd = Platform::ng_command();
         cmd.args(enhanced_args)
            .current_dir(&self.workspace_root)
-           .env("NG_CLI_ANALYTICS", "false");
+           .env("NG_CLI_ANALYTICS", "false")
+           .stdin(Stdio::inherit())
+           .stdout(Stdio::inherit())
+           .stderr(Stdio::inherit());
 
         let status = cmd.status()?;
         
@@ -444,11 +449,185 @@ impl NgProxy {
     }
 }
 
+/// Commands whose schematics can prompt, with the flags that answer or skip
+/// the prompts for them
+fn prompt_flags(command: &str) -> &'static [&'static str] {
+    match command {
+        "generate" | "g" | "new" | "n" => &["--defaults"],
+        "add" => &["--defaults", "--skip-confirmation"],
+        _ => &[],
+    }
+}
+
+/// Whether `args` sets `flag`, as `--flag`, `--flag=value` or `--no-flag`
+fn has_flag(args: &[String], flag: &str) -> bool {
+    let name = flag.trim_start_matches("--");
+    args.iter().any(|arg| {
+        arg == flag
+            || arg.strip_prefix(flag).is_some_and(|rest| rest.starts_with('='))
+            || arg.strip_prefix("--no-").is_some_and(|rest| rest == name)
+    })
+}
+
+/// An explicit `--interactive`, `--interactive=<bool>` or `--no-interactive`
+fn interactive_flag(args: &[String]) -> Option<bool> {
+    args.iter().rev().find_map(|arg| match arg.as_str() {
+        "--interactive" => Some(true),
+        "--no-interactive" => Some(false),
+        _ => arg.strip_prefix("--interactive=").map(|value| value != "false"),
+    })
+}
+
+/// Keep schematics from waiting on a prompt nobody can answer. When stdin
+/// isn't a terminal (CI, pipes) or `--interactive=false` was passed, the
+/// command's default answers are used. An explicit `--interactive` wins.
+fn apply_prompt_flags(args: &mut Vec<String>, stdin_is_terminal: bool) {
+    let Some(command) = args.first() else { return };
+    let flags = prompt_flags(command);
+    if flags.is_empty() {
+        return;
+    }
+    let interactive = interactive_flag(args).unwrap_or(stdin_is_terminal);
+    if interactive {
+        return;
+    }
+
+    if interactive_flag(args).is_none() {
+        args.push("--interactive=false".to_string());
+    }
+    let added: Vec<&str> = flags.iter().copied().filter(|flag| !has_flag(args, flag)).collect();
+    args.extend(added.iter().map(|flag| flag.to_string()));
+    if stdin_is_terminal {
+        println!("  🤖 Non-interactive: answering prompts with defaults");
+    } else {
+        println!("  🤖 stdin is not a terminal: answering prompts with defaults");
+    }
+}
+
+/// Flags forwarded to `ng serve`. Anything left unset falls back to the
+/// serve options in angular.json.
+#[derive(Debug, Clone, Default)]
+pub struct ServeOptions {
+    pub port: Option<u16>,
+    pub hmr: bool,
+    pub host: Option<String>,
+    pub ssl: bool,
+    pub ssl_cert: Option<String>,
+    pub ssl_key: Option<String>,
+    pub public_host: Option<String>,
+    /// How long to wait for the app server to answer before failing; 0
+    /// reports it running as soon as it starts
+    pub wait_timeout_secs: u64,
+}
+
+impl ServeOptions {
+    /// `ng serve` arguments for the flags that were given (port excluded)
+    pub fn ng_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(host) = &self.host {
+            args.extend(["--host".to_string(), host.clone()]);
+        }
+        if self.ssl {
+            args.extend(["--ssl".to_string(), "true".to_string()]);
+        }
+        if let Some(cert) = &self.ssl_cert {
+            args.extend(["--ssl-cert".to_string(), cert.clone()]);
+        }
+        if let Some(key) = &self.ssl_key {
+            args.extend(["--ssl-key".to_string(), key.clone()]);
+        }
+        if let Some(public_host) = &self.public_host {
+            args.extend(["--public-host".to_string(), public_host.clone()]);
+        }
+        if self.hmr {
+            args.push("--hmr".to_string());
+        }
+        args
+    }
+}
+
 pub struct LibraryWatchServer {
+    /// Re-read when a watcher restarts, to pick up links changed meanwhile
+    config_file: ConfigFile,
+    /// `[build]` settings for the watchers and the app server
+    build: BuildConfig,
     workspace_root: PathBuf,
     linked_libraries: Vec<LibraryWatchInfo>,
     app_project: String,
     processes: Vec<Child>,
+    build_events: Option<mpsc::Receiver<LibraryBuildEvent>>,
+    /// Watchers restarted mid-session report here; the first channel closes
+    /// once its watchers are gone, so it can't take new senders
+    restart_sender: mpsc::Sender<LibraryBuildEvent>,
+    restart_events: mpsc::Receiver<LibraryBuildEvent>,
+    /// Libraries whose watcher said its project or a path doesn't exist
+    reported_missing: HashSet<String>,
+    watcher_changes: Vec<WatcherChange>,
+    rebuilds: RebuildTracker,
+    /// How long the app server took to become ready, once it has
+    ready_after: Option<Duration>,
+}
+
+/// Rebuild cycles observed per library while serving
+#[derive(Debug, Default)]
+struct RebuildTracker {
+    libraries: HashMap<String, LibraryCycles>,
+}
+
+#[derive(Debug, Default)]
+struct LibraryCycles {
+    cycle_started: Option<Instant>,
+    rebuilds: usize,
+    durations_ms: Vec<u64>,
+    failures: usize,
+    last_failed: bool,
+}
+
+impl RebuildTracker {
+    /// Feed a watcher event; returns a line to print when a cycle finishes
+    fn record(&mut self, event: LibraryBuildEvent) -> Option<String> {
+        match event {
+            LibraryBuildEvent::Started(lib) => {
+                let cycles = self.libraries.entry(lib).or_default();
+                cycles.cycle_started.get_or_insert_with(Instant::now);
+                cycles.last_failed = false;
+                None
+            }
+            LibraryBuildEvent::Complete(lib) => {
+                let cycles = self.libraries.entry(lib.clone()).or_default();
+                cycles.rebuilds += 1;
+                cycles.last_failed = false;
+                match cycles.cycle_started.take() {
+                    Some(started) => {
+                        let ms = started.elapsed().as_millis() as u64;
+                        cycles.durations_ms.push(ms);
+                        Some(format!("✅ {} rebuilt #{} in {}", lib, cycles.rebuilds, history::format_duration_ms(ms)))
+                    }
+                    None => Some(format!("✅ {} rebuilt #{}", lib, cycles.rebuilds)),
+                }
+            }
+            LibraryBuildEvent::Failed(lib) | LibraryBuildEvent::Missing(lib) => {
+                let cycles = self.libraries.entry(lib.clone()).or_default();
+                // A failing build usually prints several error lines; count it once
+                if cycles.last_failed {
+                    return None;
+                }
+                cycles.last_failed = true;
+                cycles.cycle_started = None;
+                cycles.failures += 1;
+                Some(format!("❌ {} rebuild failed ({} failure(s) this session)", lib, cycles.failures))
+            }
+        }
+    }
+
+    fn summary(&self) -> Vec<RebuildStats> {
+        let mut stats: Vec<RebuildStats> = self.libraries
+            .iter()
+            .map(|(lib, cycles)| RebuildStats::from_durations(lib, cycles.rebuilds, &cycles.durations_ms, cycles.failures))
+            .collect();
+        stats.sort_by(|a, b| a.library.cmp(&b.library));
+        stats
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -456,17 +635,22 @@ struct LibraryWatchInfo {
     library_name: String,
     workspace_root: PathBuf,
     package_name: String,
+    /// Directory the app resolves the package from
+    output_path: PathBuf,
+    /// The library's project directory, watched for disappearing mid-session
+    project_root: PathBuf,
 }
 
 // Helper function to get packages linked to a specific project
 fn get_linked_packages_for_project(config: &Config, project_path: &PathBuf) -> Result<Vec<String>> {
     let mut linked_packages = Vec::new();
-    let project_canonical = project_path.canonicalize()?;
+    let project_canonical = crate::paths::canonicalize_cached(project_path)
+        .ok_or_else(|| SpineError::InvalidPath(format!("Cannot resolve {}", display_path(project_path))))?;
     
     for (package_name, package_link) in &config.links {
         // Check if this package is linked to the current project
         for linked_project in &package_link.linked_projects {
-            if let Ok(linked_canonical) = linked_project.canonicalize() {
+            if let Some(linked_canonical) = crate::paths::canonicalize_cached(linked_project) {
                 if linked_canonical == project_canonical {
                     linked_packages.push(package_name.clone());
                     break;
@@ -493,55 +677,78 @@ impl LibraryWatchServer {
         Ok(linked_packages)
     }
 
-    fn get_configured_port(&self) -> Option<u16> {
-        // Try to read port from angular.json for the app project
+    /// A serve option for the app project from angular.json, checking
+    /// `options` first and then `configurations.development` (newer Angular CLI).
+    /// The flag is true when the value came from the development configuration.
+    fn configured_serve_option(&self, key: &str) -> Option<(serde_json::Value, bool)> {
         let angular_json_path = self.workspace_root.join("angular.json");
-        
-        if let Ok(content) = std::fs::read_to_string(&angular_json_path) {
-            if let Ok(workspace_config) = serde_json::from_str::<serde_json::Value>(&content) {
-                // Navigate to projects -> app_project -> architect -> serve -> options -> port
-                let port = workspace_config
-                    .get("projects")
-                    .and_then(|projects| projects.get(&self.app_project))
-                    .and_then(|project| project.get("architect"))
-                    .and_then(|architect| architect.get("serve"))
-                    .and_then(|serve| serve.get("options"))
-                    .and_then(|options| options.get("port"))
-                    .and_then(|port| port.as_u64())
-                    .and_then(|port| u16::try_from(port).ok());
-                
-                if let Some(p) = port {
-                    println!("📡 Using port {} from angular.json", p);
-                    return Some(p);
-                }
-                
-                // Also check configurations -> development -> port (for newer Angular CLI)
-                let dev_port = workspace_config
-                    .get("projects")
-                    .and_then(|projects| projects.get(&self.app_project))
-                    .and_then(|project| project.get("architect"))
-                    .and_then(|architect| architect.get("serve"))
-                    .and_then(|serve| serve.get("configurations"))
-                    .and_then(|configs| configs.get("development"))
-                    .and_then(|dev| dev.get("port"))
-                    .and_then(|port| port.as_u64())
-                    .and_then(|port| u16::try_from(port).ok());
-                    
-                if let Some(p) = dev_port {
-                    println!("📡 Using port {} from angular.json (development config)", p);
-                    return Some(p);
-                }
+        let workspace_config = crate::jsonc::read_jsonc_file::<serde_json::Value>(&angular_json_path).ok()?;
+        let serve = workspace_config
+            .get("projects")
+            .and_then(|projects| projects.get(&self.app_project))
+            .and_then(|project| project.get("architect"))
+            .and_then(|architect| architect.get("serve"))?;
+
+        if let Some(value) = serve.get("options").and_then(|options| options.get(key)) {
+            return Some((value.clone(), false));
+        }
+        serve
+            .get("configurations")
+            .and_then(|configs| configs.get("development"))
+            .and_then(|dev| dev.get(key))
+            .map(|value| (value.clone(), true))
+    }
+
+    fn get_configured_port(&self) -> Option<u16> {
+        let port = self.configured_serve_option("port")
+            .and_then(|(port, dev)| port.as_u64().and_then(|p| u16::try_from(p).ok()).map(|p| (p, dev)));
+
+        match port {
+            Some((p, false)) => {
+                println!("📡 Using port {} from angular.json", p);
+                Some(p)
+            }
+            Some((p, true)) => {
+                println!("📡 Using port {} from angular.json (development config)", p);
+                Some(p)
+            }
+            None => {
+                println!("📡 No port configured in angular.json, using default 4200");
+                None
             }
         }
-        
-        println!("📡 No port configured in angular.json, using default 4200");
-        None
+    }
+
+    /// The URL the app will be reachable at, honouring flags first and then
+    /// angular.json for the scheme and host
+    fn app_url(&self, options: &ServeOptions, port: u16) -> String {
+        let configured_ssl = self.configured_serve_option("ssl")
+            .and_then(|(ssl, _)| ssl.as_bool())
+            .unwrap_or(false);
+        let scheme = if options.ssl || configured_ssl { "https" } else { "http" };
+
+        let public_host = options.public_host.clone().or_else(|| {
+            self.configured_serve_option("publicHost").and_then(|(host, _)| host.as_str().map(|s| s.to_string()))
+        });
+        if let Some(public_host) = public_host {
+            return if public_host.contains("://") {
+                public_host
+            } else {
+                format!("{}://{}", scheme, public_host)
+            };
+        }
+
+        let host = options.host.clone()
+            .or_else(|| self.configured_serve_option("host").and_then(|(host, _)| host.as_str().map(|s| s.to_string())))
+            .filter(|host| host != "0.0.0.0")
+            .unwrap_or_else(|| "localhost".to_string());
+        format!("{}://{}:{}", scheme, host, port)
     }
 
     pub fn new(config: &Config, workspace_root: PathBuf) -> Result<Self> {
         // First try current directory for workspace
         let mut detected_workspace_root = workspace_root.clone();
-        let mut workspace = AngularBuildManager::detect_angular_workspace(&workspace_root)?;
+        let mut workspace = AngularBuildManager::detect_workspace_for(&workspace_root, config)?;
         
         // If no workspace in current directory, try to find workspace from linked packages
         if workspace.is_none() && !config.links.is_empty() {
@@ -551,8 +758,8 @@ impl LibraryWatchServer {
             for (package_name, package_link) in &config.links {
                 match AngularBuildManager::find_workspace_root_for_package(&package_link.path) {
                     Ok(found_workspace_root) => {
-                        if let Ok(Some(found_workspace)) = AngularBuildManager::detect_angular_workspace(&found_workspace_root) {
-                            println!("✅ Found Angular workspace from package '{}': {}", package_name, found_workspace_root.display());
+                        if let Ok(Some(found_workspace)) = AngularBuildManager::detect_workspace_for(&found_workspace_root, config) {
+                            println!("✅ Found Angular workspace from package '{}': {}", package_name, display_path(&found_workspace_root));
                             detected_workspace_root = found_workspace_root;
                             workspace = Some(found_workspace);
                             break;
@@ -578,14 +785,13 @@ impl LibraryWatchServer {
                 let mut _found_in_current_workspace = false;
                 
                 // Try direct name match first
-                if workspace.projects
-                    .get(package_name)
-                    .map(|p| p.project_type == "library")
-                    .unwrap_or(false) {
+                if let Some(project) = workspace.projects.get(package_name).filter(|p| p.project_type == "library") {
                     linked_libraries.push(LibraryWatchInfo {
                         library_name: package_name.clone(),
                         workspace_root: detected_workspace_root.clone(),
                         package_name: package_name.clone(),
+                        output_path: package_link.path.clone(),
+                        project_root: detected_workspace_root.join(&project.root),
                     });
                     _found_in_current_workspace = true;
                     continue;
@@ -598,15 +804,17 @@ impl LibraryWatchServer {
                         let potential_dist_path = detected_workspace_root.join("dist").join(lib_name);
                         
                         // Compare paths (handle symlinks and canonicalization)
-                        if let (Ok(package_canonical), Ok(dist_canonical)) = (
-                            package_link.path.canonicalize(),
-                            potential_dist_path.canonicalize()
+                        if let (Some(package_canonical), Some(dist_canonical)) = (
+                            crate::paths::canonicalize_cached(&package_link.path),
+                            crate::paths::canonicalize_cached(&potential_dist_path)
                         ) {
                             if package_canonical == dist_canonical {
                                 linked_libraries.push(LibraryWatchInfo {
                                     library_name: lib_name.clone(),
                                     workspace_root: detected_workspace_root.clone(),
                                     package_name: package_name.clone(),
+                                    output_path: package_link.path.clone(),
+                                    project_root: detected_workspace_root.join(&project.root),
                                 });
                                 println!("🔗 Mapped package '{}' -> workspace library '{}'", package_name, lib_name);
                                 _found_in_current_workspace = true;
@@ -621,6 +829,8 @@ impl LibraryWatchServer {
                                 library_name: lib_name.clone(),
                                 workspace_root: detected_workspace_root.clone(),
                                 package_name: package_name.clone(),
+                                output_path: package_link.path.clone(),
+                                project_root: detected_workspace_root.join(&project.root),
                             });
                             println!("🔗 Mapped package '{}' -> workspace library '{}'", package_name, lib_name);
                             _found_in_current_workspace = true;
@@ -633,25 +843,27 @@ impl LibraryWatchServer {
                 if !_found_in_current_workspace {
                     match AngularBuildManager::find_workspace_root_for_package(&package_link.path) {
                         Ok(lib_workspace_root) => {
-                            if let Ok(Some(lib_workspace)) = AngularBuildManager::detect_angular_workspace(&lib_workspace_root) {
+                            if let Ok(Some(lib_workspace)) = AngularBuildManager::detect_workspace_for(&lib_workspace_root, config) {
                                 // Look for library in its own workspace
                                 for (lib_name, project) in &lib_workspace.projects {
                                     if project.project_type == "library" {
                                         // Check if the package path corresponds to this library's dist output
                                         let potential_dist_path = lib_workspace_root.join("dist").join(lib_name);
                                         
-                                        if let (Ok(package_canonical), Ok(dist_canonical)) = (
-                                            package_link.path.canonicalize(),
-                                            potential_dist_path.canonicalize()
+                                        if let (Some(package_canonical), Some(dist_canonical)) = (
+                                            crate::paths::canonicalize_cached(&package_link.path),
+                                            crate::paths::canonicalize_cached(&potential_dist_path)
                                         ) {
                                             if package_canonical == dist_canonical {
                                                 linked_libraries.push(LibraryWatchInfo {
                                                     library_name: lib_name.clone(),
                                                     workspace_root: lib_workspace_root.clone(),
                                                     package_name: package_name.clone(),
+                                                    output_path: package_link.path.clone(),
+                                                    project_root: lib_workspace_root.join(&project.root),
                                                 });
                                                 println!("🔗 Mapped cross-workspace package '{}' -> library '{}' in {}", 
-                                                         package_name, lib_name, lib_workspace_root.display());
+                                                         package_name, lib_name, display_path(&lib_workspace_root));
                                                 break;
                                             }
                                         }
@@ -669,6 +881,7 @@ impl LibraryWatchServer {
 
         // Find the default application project
         let app_project = workspace.default_project
+            .filter(|name| workspace.projects.get(name).is_some_and(|project| project.project_type == "application"))
             .or_else(|| {
                 workspace.projects
                     .iter()
@@ -677,26 +890,30 @@ impl LibraryWatchServer {
             })
             .ok_or_else(|| SpineError::Config("No application project found in workspace".to_string()))?;
 
+        let (restart_sender, restart_events) = mpsc::channel();
         Ok(Self {
+            config_file: config.file.clone(),
+            build: config.build.clone(),
             workspace_root: detected_workspace_root,
             linked_libraries,
             app_project,
             processes: Vec::new(),
+            build_events: None,
+            restart_sender,
+            restart_events,
+            reported_missing: HashSet::new(),
+            watcher_changes: Vec::new(),
+            rebuilds: RebuildTracker::default(),
+            ready_after: None,
         })
     }
 
-    pub fn serve_with_libraries(&mut self, port: Option<u16>, hmr: bool) -> Result<()> {
+    pub fn serve_with_libraries(&mut self, options: &ServeOptions) -> Result<()> {
         // Get port from angular.json if not specified
-        let port = port.unwrap_or_else(|| self.get_configured_port().unwrap_or(4200));
+        let port = options.port.unwrap_or_else(|| self.get_configured_port().unwrap_or(4200));
         
         // Create main progress spinner
-        let main_spinner = ProgressBar::new_spinner();
-        main_spinner.set_style(
-            ProgressStyle::default_spinner()
-                .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-                .template("{spinner:.blue} {msg}")
-                .unwrap()
-        );
+        let main_spinner = Progress::spinner(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"], "{spinner:.blue} {msg}");
         
         main_spinner.set_message("🚀 Initializing development server...");
         main_spinner.enable_steady_tick(Duration::from_millis(100));
@@ -733,117 +950,325 @@ impl LibraryWatchServer {
         }
 
         // 3. Start the main application server
-        let app_spinner = ProgressBar::new_spinner();
-        app_spinner.set_style(
-            ProgressStyle::default_spinner()
-                .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-                .template("{spinner:.green} {msg}")
-                .unwrap()
-        );
+        let app_spinner = Progress::spinner(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"], "{spinner:.green} {msg}");
         app_spinner.set_message(format!("🌐 Starting application server on port {}...", port));
         app_spinner.enable_steady_tick(Duration::from_millis(100));
         
-        self.start_app_server(port, hmr)?;
-        
-        app_spinner.finish_with_message(format!("✅ Development server running at http://localhost:{}", port));
+        let (ready, recent_output) = self.start_app_server(port, options)?;
+        if options.wait_timeout_secs > 0 {
+            app_spinner.set_message(format!("⏳ Waiting for ng serve {} to compile and answer on port {}...", self.app_project, port));
+            let ready_after = self.wait_until_ready(&ready, &recent_output, port, options)?;
+            self.ready_after = Some(ready_after);
+            app_spinner.finish_with_message(format!("✅ Development server running at {} (ready after {:.1}s)",
+                self.app_url(options, port), ready_after.as_secs_f64()));
+        } else {
+            app_spinner.finish_with_message(format!("✅ Development server started at {}", self.app_url(options, port)));
+        }
+        self.print_process_dirs();
         
         // 4. Monitor and coordinate rebuilds
-        self.coordinate_rebuilds()
+        let session_started_at = history::now_timestamp();
+        let session_start = Instant::now();
+        let result = self.coordinate_rebuilds();
+        self.record_session(session_started_at, session_start.elapsed());
+        result
     }
 
-    fn start_library_watchers(&mut self) -> Result<()> {
+    /// Where each process runs, which matters when the app and the
+    /// libraries live in different workspaces
+    fn print_process_dirs(&self) {
+        println!("📂 Processes:");
+        println!("   • ng serve {}: {}", self.app_project, display_path(&self.workspace_root));
         for lib_info in &self.linked_libraries {
-            let mut cmd = Platform::ng_command();
-            cmd.args(&["build", &lib_info.library_name, "--watch"])
-               .current_dir(&lib_info.workspace_root)
-               .stdout(Stdio::piped())
-               .stderr(Stdio::piped())
-               .env("NG_CLI_ANALYTICS", "false");
-
-            let child = cmd.spawn()
-                .map_err(|e| SpineError::Config(format!("Failed to start library watcher for {}: {}", lib_info.library_name, e)))?;
-            
+            println!("   • ng build {} --watch: {}", lib_info.library_name, display_path(&lib_info.workspace_root));
+        }
+    }
+
+    fn record_session(&self, started_at: u64, elapsed: Duration) {
+        let session = ServeSession {
+            app: self.app_project.clone(),
+            started_at,
+            duration_ms: elapsed.as_millis() as u64,
+            ready_after_ms: self.ready_after.map(|ready_after| ready_after.as_millis() as u64),
+            libraries: self.rebuilds.summary(),
+            watcher_changes: self.watcher_changes.clone(),
+        };
+
+        println!();
+        history::print_serve_session(&session);
+
+        match BuildHistory::load(&self.config_file) {
+            Ok(mut build_history) => {
+                build_history.record_session(session);
+                if let Err(e) = build_history.save(&self.config_file) {
+                    println!("⚠️  Failed to save serve session to history: {}", e);
+                }
+            }
+            Err(e) => println!("⚠️  Failed to load build history: {}", e),
+        }
+    }
+
+    /// Start the watchers; with `build.max_concurrency` only that many, and
+    /// `wait_for_initial_builds` starts the rest as first builds finish
+    fn start_library_watchers(&mut self) -> Result<()> {
+        let limit = crate::throttle::max_concurrency(&self.build).unwrap_or(usize::MAX);
+        for lib_info in self.linked_libraries.iter().take(limit) {
+            let child = Self::spawn_watcher(lib_info, &self.build)?;
             self.processes.push(child);
         }
 
         Ok(())
     }
 
+    /// Start the first watcher `start_library_watchers` held back, if any
+    fn start_queued_watcher(
+        &mut self,
+        tx: &mpsc::Sender<LibraryBuildEvent>,
+        build_errors: &Arc<Mutex<HashMap<String, Vec<String>>>>,
+    ) -> Result<bool> {
+        let Some(lib_info) = self.linked_libraries.get(self.processes.len()).cloned() else { return Ok(false) };
+        let mut child = Self::spawn_watcher(&lib_info, &self.build)?;
+        if let Some(stdout) = child.stdout.take() {
+            watch_build_output(stdout, lib_info.library_name.clone(), tx.clone(), Arc::clone(build_errors));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            watch_build_output(stderr, lib_info.library_name.clone(), tx.clone(), Arc::clone(build_errors));
+        }
+        self.processes.push(child);
+        Ok(true)
+    }
+
+    fn spawn_watcher(lib_info: &LibraryWatchInfo, build: &BuildConfig) -> Result<Child> {
+        let (mut cmd, _) = crate::angular::project_build_command(&lib_info.workspace_root, &lib_info.library_name);
+        cmd.arg("--watch")
+           .stdout(Stdio::piped())
+           .stderr(Stdio::piped())
+           .env("NG_CLI_ANALYTICS", "false");
+        crate::throttle::prepare(&mut cmd, build);
+
+        Ok(cmd.spawn()
+            .map_err(|e| SpineError::Config(format!("Failed to start library watcher for {}: {}", lib_info.library_name, e)))?)
+    }
+
+    /// The first watcher that lost its library, with why: its project
+    /// directory or workspace is gone, or it reported a missing project and
+    /// exited. A watcher that exits for other reasons isn't lost.
+    fn lost_watcher(&mut self) -> Option<(usize, String)> {
+        for (index, lib_info) in self.linked_libraries.iter().enumerate() {
+            if !AngularBuildManager::is_workspace_root(&lib_info.workspace_root) {
+                return Some((index, format!("{} no longer has an angular.json or nx.json", display_path(&lib_info.workspace_root))));
+            }
+            if !lib_info.project_root.exists() {
+                return Some((index, format!("{} no longer exists", display_path(&lib_info.project_root))));
+            }
+            let exited = self.processes.get_mut(index).is_some_and(|process| !matches!(process.try_wait(), Ok(None)));
+            if exited && self.reported_missing.contains(&lib_info.library_name) {
+                return Some((index, "the watcher reported a missing project or path and stopped".to_string()));
+            }
+        }
+        None
+    }
+
+    /// Pause a watcher that lost its library and ask whether to find the
+    /// library again or drop it from the session. Without a terminal to ask
+    /// on, it is dropped.
+    fn handle_lost_watcher(&mut self, index: usize, reason: String, spinner: &Progress) {
+        let lib_info = self.linked_libraries[index].clone();
+        let _ = self.processes[index].kill();
+        let _ = self.processes[index].wait();
+        self.reported_missing.remove(&lib_info.library_name);
+        spinner.println(format!("⏸️  Paused the watcher for {}: {}", lib_info.library_name, reason));
+
+        let options = [
+            "Find the library again and restart its watcher".to_string(),
+            "Drop it from this session".to_string(),
+        ];
+        let question = format!("What should happen to {}?", lib_info.library_name);
+        let outcome = match spinner.suspend(|| crate::prompt::choose(&question, &options)) {
+            Some(0) => match self.restart_watcher(index, &lib_info) {
+                Ok(outcome) => {
+                    spinner.println(format!("▶️  {} {}", lib_info.library_name, outcome));
+                    outcome
+                }
+                Err(e) => {
+                    spinner.println(format!("⚠️  Could not find {} again: {}", lib_info.library_name, e));
+                    self.drop_watcher(index);
+                    "dropped, it could not be found again".to_string()
+                }
+            },
+            _ => {
+                self.drop_watcher(index);
+                "dropped".to_string()
+            }
+        };
+        if outcome.starts_with("dropped") {
+            spinner.println(format!("🗑️  Dropped {} from this session", lib_info.library_name));
+        }
+
+        self.watcher_changes.push(WatcherChange {
+            library: lib_info.library_name,
+            at: history::now_timestamp(),
+            reason,
+            outcome,
+        });
+    }
+
+    /// Re-run workspace detection for the package and start a watcher on
+    /// the library it resolves to now
+    fn restart_watcher(&mut self, index: usize, lib_info: &LibraryWatchInfo) -> Result<String> {
+        let config = Config::load_or_create(&self.config_file)?;
+        if !config.links.contains_key(&lib_info.package_name) {
+            return Err(SpineError::PackageNotFound(lib_info.package_name.clone()).into());
+        }
+        let manager = AngularBuildManager::new_from_linked_package(config, &lib_info.package_name)?;
+        let (library_name, project_root) = manager.resolve_library_root(&lib_info.package_name)
+            .filter(|(_, root)| root.exists())
+            .ok_or_else(|| SpineError::Config(format!(
+                "no library for {} in {}", lib_info.package_name, display_path(manager.workspace_root())
+            )))?;
+
+        let restarted = LibraryWatchInfo {
+            library_name,
+            workspace_root: manager.workspace_root().to_path_buf(),
+            package_name: lib_info.package_name.clone(),
+            output_path: lib_info.output_path.clone(),
+            project_root,
+        };
+        let mut child = Self::spawn_watcher(&restarted, &self.build)?;
+        let errors: Arc<Mutex<HashMap<String, Vec<String>>>> = Arc::default();
+        if let Some(stdout) = child.stdout.take() {
+            watch_build_output(stdout, restarted.library_name.clone(), self.restart_sender.clone(), Arc::clone(&errors));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            watch_build_output(stderr, restarted.library_name.clone(), self.restart_sender.clone(), errors);
+        }
+
+        let outcome = format!("re-resolved to {} at {}", restarted.library_name, display_path(&restarted.project_root));
+        self.processes[index] = child;
+        self.linked_libraries[index] = restarted;
+        Ok(outcome)
+    }
+
+    /// Watchers come first in `processes`, in `linked_libraries` order
+    fn drop_watcher(&mut self, index: usize) {
+        let mut process = self.processes.remove(index);
+        let _ = process.kill();
+        let _ = process.wait();
+        self.linked_libraries.remove(index);
+    }
+
     fn wait_for_initial_builds(&mut self) -> Result<()> {
         let total_libraries = self.linked_libraries.len();
         
+        // Libraries without usable output can't be resolved by the app, so the
+        // app server has to wait for their first build. The rest are served from
+        // their previous output if the watcher is slow or fails.
+        let mut unbuilt = HashSet::new();
+        for lib_info in &self.linked_libraries {
+            let missing = missing_entry_points(&lib_info.output_path);
+            if !missing.is_empty() {
+                println!("⏳ {} has not been built yet (missing {} in {})",
+                    lib_info.library_name, missing.join(", "), display_path(&lib_info.output_path));
+                unbuilt.insert(lib_info.library_name.clone());
+            }
+        }
+        
         // Create progress bar for library builds
-        let pb = ProgressBar::new(total_libraries as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] {bar:30.cyan/blue} {pos}/{len} {msg}")
-                .unwrap()
-                .progress_chars("█▉▊▋▌▍▎▏  ")
-        );
+        let pb = Progress::bar(total_libraries as u64, "{spinner:.green} [{elapsed_precise}] {bar:30.cyan/blue} {pos}/{len} {msg}", "█▉▊▋▌▍▎▏  ");
         pb.set_message("Building libraries...");
         
-        let mut completed_libraries = std::collections::HashSet::new();
+        let mut completed_libraries = HashSet::new();
         
         // Set up channel for build completion events
         let (tx, rx) = mpsc::channel();
+        let build_errors: Arc<Mutex<HashMap<String, Vec<String>>>> = Arc::default();
         
-        // Monitor each library build process for completion
+        // Monitor each library build process for completion; ng reports on both streams
         for (index, process) in self.processes.iter_mut().enumerate() {
             if index < self.linked_libraries.len() {
-                let lib_name = self.linked_libraries[index].library_name.clone();
-                let tx_clone = tx.clone();
-                
-                // Monitor stdout for initial build completion (suppress most output)
+                let lib_name = &self.linked_libraries[index].library_name;
                 if let Some(stdout) = process.stdout.take() {
-                    thread::spawn(move || {
-                        let reader = BufReader::new(stdout);
-                        for line in reader.lines() {
-                            if let Ok(line) = line {
-                                // Only show important lines, suppress verbose output
-                                if line.contains("Error") || line.contains("ERROR") || line.contains("Failed") {
-                                    eprintln!("  [{}] {}", lib_name, line);
-                                }
-                                
-                                // Check for build completion patterns
-                                if line.contains("✓ Built") || 
-                                   line.contains("Build complete") ||
-                                   line.contains("Compilation complete") ||
-                                   line.contains("webpack compiled") {
-                                    let _ = tx_clone.send(LibraryBuildEvent::Complete(lib_name.clone()));
-                                } else if line.contains("Build failed") || 
-                                         line.contains("✖ Failed") ||
-                                         line.contains("ERROR") {
-                                    let _ = tx_clone.send(LibraryBuildEvent::Failed(lib_name.clone()));
-                                }
-                            }
-                        }
-                    });
+                    watch_build_output(stdout, lib_name.clone(), tx.clone(), Arc::clone(&build_errors));
+                }
+                if let Some(stderr) = process.stderr.take() {
+                    watch_build_output(stderr, lib_name.clone(), tx.clone(), Arc::clone(&build_errors));
                 }
             }
         }
+        // Kept while watchers are queued behind build.max_concurrency
+        let mut tx = Some(tx).filter(|_| self.processes.len() < total_libraries);
         
         // Wait for all libraries to complete their initial build
         let timeout = Duration::from_secs(120); // 2 minute timeout
-        let start_time = std::time::Instant::now();
+        let mut start_time = std::time::Instant::now();
         
         while completed_libraries.len() < total_libraries {
+            // Each finished first build frees a place for a queued watcher
+            while self.processes.len() < total_libraries && self.processes.len() < completed_libraries.len() + crate::throttle::max_concurrency(&self.build).unwrap_or(usize::MAX) {
+                let Some(sender) = &tx else { break };
+                self.start_queued_watcher(sender, &build_errors)?;
+                start_time = std::time::Instant::now();
+            }
+            if self.processes.len() >= total_libraries {
+                tx = None;
+            }
+
+            let mut waiting_on: Vec<&String> = unbuilt.difference(&completed_libraries).collect();
+            waiting_on.sort();
+            
             if start_time.elapsed() > timeout {
-                pb.finish_with_message("❌ Timeout waiting for library builds");
-                return Err(SpineError::Config("Timeout waiting for library builds to complete".to_string()).into());
+                if waiting_on.is_empty() {
+                    pb.finish_with_message("⚠️  Timed out waiting for rebuilds; serving previous library output");
+                    break;
+                }
+                let waiting_on = waiting_on.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ");
+                pb.finish_with_message(format!("❌ Timeout waiting for first build of {}", waiting_on));
+                return Err(SpineError::Config(format!(
+                    "Timed out after {}s waiting for the first build of {}; not starting the app server",
+                    timeout.as_secs(), waiting_on
+                )).into());
+            }
+            
+            if !waiting_on.is_empty() {
+                pb.set_message(format!("Waiting on first build of {}", waiting_on[0]));
             }
             
             // Check for build events with timeout
             match rx.recv_timeout(Duration::from_millis(100)) {
                 Ok(LibraryBuildEvent::Complete(lib_name)) => {
+                    if unbuilt.contains(&lib_name) {
+                        let output_path = self.output_path(&lib_name);
+                        let missing = missing_entry_points(&output_path);
+                        if !missing.is_empty() {
+                            pb.finish_with_message(format!("❌ Library '{}' built without its entry points", lib_name));
+                            return Err(SpineError::Config(format!(
+                                "Library '{}' finished building but {} is still missing {}; not starting the app server",
+                                lib_name, display_path(&output_path), missing.join(", ")
+                            )).into());
+                        }
+                    }
                     if completed_libraries.insert(lib_name.clone()) {
                         pb.inc(1);
                         pb.set_message(format!("Built: {}", lib_name));
                     }
                 }
-                Ok(LibraryBuildEvent::Failed(lib_name)) => {
-                    pb.finish_with_message(format!("❌ Library '{}' build failed", lib_name));
-                    return Err(SpineError::Config(format!("Library '{}' build failed", lib_name)).into());
+                Ok(LibraryBuildEvent::Failed(lib_name) | LibraryBuildEvent::Missing(lib_name)) => {
+                    if unbuilt.contains(&lib_name) {
+                        pb.finish_with_message(format!("❌ Library '{}' build failed", lib_name));
+                        // Let the reader threads capture the rest of the error
+                        thread::sleep(Duration::from_millis(500));
+                        let errors = build_errors.lock().map(|e| e.get(&lib_name).cloned().unwrap_or_default()).unwrap_or_default();
+                        return Err(SpineError::Config(format!(
+                            "Library '{}' failed its first build; not starting the app server:\n{}",
+                            lib_name, errors.join("\n")
+                        )).into());
+                    }
+                    if completed_libraries.insert(lib_name.clone()) {
+                        pb.inc(1);
+                        pb.println(format!("⚠️  Library '{}' build failed; serving its previous output", lib_name));
+                    }
                 }
+                Ok(LibraryBuildEvent::Started(_)) => {}
                 Err(mpsc::RecvTimeoutError::Timeout) => {
                     // Continue waiting
                 }
@@ -853,51 +1278,139 @@ impl LibraryWatchServer {
             }
         }
         
+        // Watchers that exited before building leave nothing to serve
+        let mut never_built: Vec<&String> = unbuilt.difference(&completed_libraries).collect();
+        if !never_built.is_empty() {
+            never_built.sort();
+            let never_built = never_built.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ");
+            pb.finish_with_message(format!("❌ No build output for {}", never_built));
+            return Err(SpineError::Config(format!(
+                "Library watcher for {} stopped before its first build; not starting the app server", never_built
+            )).into());
+        }
+        
         if completed_libraries.len() == total_libraries {
             pb.finish_with_message(format!("🎉 All {} library builds completed!", total_libraries));
         } else {
             pb.finish_with_message(format!("⚠️  Only {}/{} libraries completed", completed_libraries.len(), total_libraries));
         }
         
+        // Keep listening to the watchers so rebuild cycles can be timed while serving
+        self.build_events = Some(rx);
         Ok(())
     }
 
-    fn start_app_server(&mut self, port: u16, hmr: bool) -> Result<()> {
+    fn output_path(&self, library: &str) -> PathBuf {
+        self.linked_libraries.iter()
+            .find(|lib| lib.library_name == library)
+            .map(|lib| lib.output_path.clone())
+            .unwrap_or_default()
+    }
+
+    /// Start `ng serve`, passing its output through. Returns a channel that
+    /// hears when the output says the app compiled, and the latest lines.
+    fn start_app_server(&mut self, port: u16, options: &ServeOptions) -> Result<(mpsc::Receiver<()>, RecentOutput)> {
         let mut cmd = Platform::ng_command();
         cmd.args(&["serve", &self.app_project])
            .args(&["--port", &port.to_string()])
-           .args(&["--host", "0.0.0.0"])
            .args(&["--live-reload", "true"])
+           .args(options.ng_args())
            .current_dir(&self.workspace_root)
+           .stdout(Stdio::piped())
+           .stderr(Stdio::piped())
            .env("NG_CLI_ANALYTICS", "false");
 
-        if hmr {
-            cmd.arg("--hmr");
+        // Keep listening on all interfaces unless a host is set on the command line or in angular.json
+        if options.host.is_none() && self.configured_serve_option("host").is_none() {
+            cmd.args(["--host", "0.0.0.0"]);
         }
 
-        let child = cmd.spawn()
+        crate::throttle::prepare(&mut cmd, &self.build);
+        let mut child = cmd.spawn()
             .map_err(|e| SpineError::Config(format!("Failed to start application server: {}", e)))?;
-        
+
+        let (tx, rx) = mpsc::channel();
+        let recent_output: RecentOutput = Arc::default();
+        if let Some(stdout) = child.stdout.take() {
+            watch_serve_output(stdout, false, tx.clone(), Arc::clone(&recent_output));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            watch_serve_output(stderr, true, tx, Arc::clone(&recent_output));
+        }
         self.processes.push(child);
         
-        Ok(())
+        Ok((rx, recent_output))
+    }
+
+    /// Wait until ng serve's output says the app compiled or, without
+    /// --ssl, the port answers an HTTP request. Fails when the process
+    /// exits first or `--wait-timeout` passes, showing its latest output.
+    fn wait_until_ready(
+        &mut self,
+        ready: &mpsc::Receiver<()>,
+        recent_output: &RecentOutput,
+        port: u16,
+        options: &ServeOptions,
+    ) -> Result<Duration> {
+        let started = Instant::now();
+        let timeout = Duration::from_secs(options.wait_timeout_secs);
+        let recent = || recent_output.lock().map(|lines| lines.iter().cloned().collect::<Vec<_>>().join("\n")).unwrap_or_default();
+        loop {
+            if ready.recv_timeout(Duration::from_millis(500)).is_ok() || (!options.ssl && http_responds(port)) {
+                return Ok(started.elapsed());
+            }
+            if let Some(status) = self.processes.last_mut().and_then(|process| process.try_wait().ok().flatten()) {
+                return Err(SpineError::Config(format!(
+                    "ng serve {} exited ({}) before it became ready; its last output:\n{}", self.app_project, status, recent()
+                )).into());
+            }
+            if started.elapsed() >= timeout {
+                return Err(SpineError::NeverReady {
+                    process: format!("ng serve {}", self.app_project),
+                    seconds: options.wait_timeout_secs,
+                    output: recent(),
+                }.into());
+            }
+        }
     }
 
     fn coordinate_rebuilds(&mut self) -> Result<()> {
         // Create a final spinner for the monitoring phase
-        let monitor_spinner = ProgressBar::new_spinner();
-        monitor_spinner.set_style(
-            ProgressStyle::default_spinner()
-                .tick_strings(&["🔄", "🔃", "🔄", "🔃"])
-                .template("{spinner} {msg}")
-                .unwrap()
-        );
+        let monitor_spinner = Progress::spinner(&["🔄", "🔃", "🔄", "🔃"], "{spinner} {msg}");
         monitor_spinner.set_message("Monitoring library and app servers (Press Ctrl+C to stop)");
         monitor_spinner.enable_steady_tick(Duration::from_millis(800));
         
+        // Catch Ctrl+C so the session summary is still printed and saved
+        let shutdown = Arc::new(AtomicBool::new(false));
+        signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&shutdown))?;
+        
         // Wait indefinitely (until user interrupts)
         loop {
-            thread::sleep(Duration::from_secs(1));
+            while let Ok(event) = self.restart_events.try_recv() {
+                self.record_event(event, &monitor_spinner);
+            }
+            match &self.build_events {
+                Some(rx) => match rx.recv_timeout(Duration::from_secs(1)) {
+                    Ok(event) => {
+                        self.record_event(event, &monitor_spinner);
+                        continue;
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        self.build_events = None;
+                    }
+                },
+                None => thread::sleep(Duration::from_secs(1)),
+            }
+            
+            if shutdown.load(Ordering::Relaxed) {
+                monitor_spinner.finish_with_message("🛑 Interrupted");
+                break;
+            }
+
+            while let Some((index, reason)) = self.lost_watcher() {
+                self.handle_lost_watcher(index, reason, &monitor_spinner);
+            }
             
             // Check if any processes have terminated
             let mut all_running = true;
@@ -927,6 +1440,15 @@ impl LibraryWatchServer {
 
         Ok(())
     }
+
+    fn record_event(&mut self, event: LibraryBuildEvent, spinner: &Progress) {
+        if let LibraryBuildEvent::Missing(lib) = &event {
+            self.reported_missing.insert(lib.clone());
+        }
+        if let Some(line) = self.rebuilds.record(event) {
+            spinner.println(line);
+        }
+    }
 }
 
 impl Drop for LibraryWatchServer {
@@ -940,28 +1462,143 @@ impl Drop for LibraryWatchServer {
 
 #[derive(Debug)]
 enum LibraryBuildEvent {
+    Started(String),
     Complete(String),
     Failed(String),
+    /// The watcher says its project or a path it needs doesn't exist
+    Missing(String),
+}
+
+/// Error lines kept per library for reporting a failed build
+const MAX_CAPTURED_ERROR_LINES: usize = 40;
+
+/// Turn a watcher's output into build events on a background thread, echoing
+/// and capturing error lines (suppressing the rest of the verbose output)
+fn watch_build_output<R: std::io::Read + Send + 'static>(
+    stream: R,
+    lib_name: String,
+    tx: mpsc::Sender<LibraryBuildEvent>,
+    build_errors: Arc<Mutex<HashMap<String, Vec<String>>>>,
+) {
+    thread::spawn(move || {
+        let reader = BufReader::new(stream);
+        for line in reader.lines().map_while(|line| line.ok()) {
+            if line.contains("Error") || line.contains("ERROR") || line.contains("Failed") {
+                eprintln!("  [{}] {}", lib_name, line);
+                if let Ok(mut errors) = build_errors.lock() {
+                    let captured = errors.entry(lib_name.clone()).or_default();
+                    if captured.len() < MAX_CAPTURED_ERROR_LINES {
+                        captured.push(line.clone());
+                    }
+                }
+            }
+            
+            // Check for build start and completion patterns
+            if is_missing_path_line(&line) {
+                let _ = tx.send(LibraryBuildEvent::Missing(lib_name.clone()));
+            } else if line.contains("File change detected") ||
+               line.contains("Starting incremental compilation") ||
+               line.contains("Building entry point") {
+                let _ = tx.send(LibraryBuildEvent::Started(lib_name.clone()));
+            } else if line.contains("✓ Built") || 
+               line.contains("Build complete") ||
+               line.contains("Compilation complete") ||
+               line.contains("webpack compiled") {
+                let _ = tx.send(LibraryBuildEvent::Complete(lib_name.clone()));
+            } else if line.contains("Build failed") || 
+                     line.contains("✖ Failed") ||
+                     line.contains("ERROR") {
+                let _ = tx.send(LibraryBuildEvent::Failed(lib_name.clone()));
+            }
+        }
+    });
+}
+
+/// Lines of ng serve output kept for a readiness failure
+const MAX_RECENT_SERVE_LINES: usize = 20;
+
+/// The latest lines of ng serve output, oldest first
+type RecentOutput = Arc<Mutex<VecDeque<String>>>;
+
+/// Pass ng serve's output through, keeping the latest lines and signalling
+/// `ready` on each line that says the app compiled
+fn watch_serve_output<R: std::io::Read + Send + 'static>(
+    stream: R,
+    is_stderr: bool,
+    ready: mpsc::Sender<()>,
+    recent_output: RecentOutput,
+) {
+    thread::spawn(move || {
+        let reader = BufReader::new(stream);
+        for line in reader.lines().map_while(|line| line.ok()) {
+            if is_stderr {
+                eprintln!("{}", line);
+            } else {
+                println!("{}", line);
+            }
+            if is_serve_ready_line(&line) {
+                let _ = ready.send(());
+            }
+            if let Ok(mut recent) = recent_output.lock() {
+                if recent.len() == MAX_RECENT_SERVE_LINES {
+                    recent.pop_front();
+                }
+                recent.push_back(line);
+            }
+        }
+    });
+}
+
+/// ng serve output printed once the first compile is done: webpack's
+/// "Compiled successfully" and the esbuild/Vite builders' bundle and URL lines
+fn is_serve_ready_line(line: &str) -> bool {
+    line.contains("Compiled successfully")
+        || line.contains("compiled successfully")
+        || line.contains("Application bundle generation complete")
+        || (line.contains("Local:") && line.contains("http"))
+}
+
+/// Whether something on the local port answers a plain HTTP request. The
+/// webpack dev server holds requests until it has compiled, so an answer
+/// means the app is being served.
+fn http_responds(port: u16) -> bool {
+    use std::io::Read;
+    use std::net::{TcpStream, ToSocketAddrs};
+    let Ok(addresses) = ("localhost", port).to_socket_addrs() else { return false };
+    addresses.into_iter().any(|address| {
+        let Ok(mut stream) = TcpStream::connect_timeout(&address, Duration::from_millis(300)) else { return false };
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(1)));
+        let request = format!("GET / HTTP/1.0\r\nHost: localhost:{}\r\n\r\n", port);
+        let mut first = [0u8; 1];
+        stream.write_all(request.as_bytes()).is_ok() && matches!(stream.read(&mut first), Ok(1))
+    })
+}
+
+/// Output of a watcher whose project was renamed or moved away
+fn is_missing_path_line(line: &str) -> bool {
+    line.contains("ENOENT")
+        || (line.contains("Project") && line.contains("does not exist"))
+        || line.contains("could not be found in workspace")
 }
 
 // CLI command implementations
 pub fn ng_generate_command(
+    config: &Config,
     schematic: &str,
     name: &str,
     lib: Option<&str>,
     args: Vec<String>,
 ) -> Result<()> {
-    let config = Config::load_or_create()?;
     let workspace_root = std::env::current_dir()?;
     
     // Auto-detect library if not provided and we're in a library directory
     let detected_lib = if lib.is_none() {
-        detect_current_library(&workspace_root, &config)?
+        detect_current_library(&workspace_root, config)?
     } else {
         lib.map(|s| s.to_string())
     };
     
-    let integration = AngularCliIntegration::new(config, workspace_root)?;
+    let integration = AngularCliIntegration::new(config.clone(), workspace_root)?;
     integration.generate_with_lib_context(schematic, name, detected_lib.as_deref(), args)
 }
 
@@ -971,10 +1608,9 @@ fn detect_current_library(current_dir: &std::path::PathBuf, config: &Config) ->
     
     // Walk up directories looking for angular.json (workspace root)
     while let Some(parent) = dir.parent() {
-        let angular_json = parent.join("angular.json");
-        if angular_json.exists() {
+        if AngularBuildManager::is_workspace_root(parent) {
             // Found workspace root, now check if current path is within a library
-            if let Ok(Some(workspace)) = AngularBuildManager::detect_angular_workspace(&parent.to_path_buf()) {
+            if let Ok(Some(workspace)) = AngularBuildManager::detect_workspace_for(parent, config) {
                 for (lib_name, project) in &workspace.projects {
                     if project.project_type == "library" {
                         let lib_path = parent.join(&project.root);
@@ -996,34 +1632,78 @@ fn detect_current_library(current_dir: &std::path::PathBuf, config: &Config) ->
     Ok(None)
 }
 
-pub fn ng_proxy_command(args: Vec<String>) -> Result<()> {
-    let config = Config::load_or_create()?;
+pub fn ng_proxy_command(config: &Config, args: Vec<String>) -> Result<()> {
     let workspace_root = std::env::current_dir()?;
     
-    let proxy = NgProxy::new(config, workspace_root);
+    let proxy = NgProxy::new(config.clone(), workspace_root);
     proxy.proxy_command(args)
 }
 
-pub fn serve_with_libs_command(port: Option<u16>, hmr: bool, project: Option<&str>) -> Result<()> {
-    let config = Config::load_or_create()?;
-    let workspace_root = std::env::current_dir()?;
+pub fn serve_with_libs_command(config: &Config, options: &ServeOptions, project: Option<&str>, app_dir: Option<&Path>) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let workspace_root = match app_dir {
+        Some(dir) => crate::npm::NpmManager::resolve_project_dir(dir)?,
+        None => consuming_app_dir(config, &current_dir)?.unwrap_or(current_dir),
+    };
     
-    let mut server = LibraryWatchServer::new(&config, workspace_root)?;
+    let mut server = LibraryWatchServer::new(config, workspace_root)?;
     
     // Override app project if specified
     if let Some(proj) = project {
         server.app_project = proj.to_string();
     }
     
-    server.serve_with_libraries(port, hmr)
+    server.serve_with_libraries(options)
+}
+
+/// When `dir` is an Angular workspace without an application whose libraries
+/// are linked into other projects, the project to serve instead: chosen on a
+/// terminal, otherwise an error naming them for --app-dir. None when `dir`
+/// has an application or nothing else uses its libraries.
+fn consuming_app_dir(config: &Config, dir: &Path) -> Result<Option<PathBuf>> {
+    let Some(workspace) = AngularBuildManager::detect_workspace_for(dir, config)? else { return Ok(None) };
+    if workspace.projects.values().any(|project| project.project_type == "application") {
+        return Ok(None);
+    }
+
+    let root = crate::paths::canonicalize_cached(dir).unwrap_or_else(|| dir.to_path_buf());
+    let inside = |path: &Path| crate::paths::canonicalize_cached(path).unwrap_or_else(|| path.to_path_buf()).starts_with(&root);
+    let mut consumers: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    for link in config.links.values().filter(|link| inside(&link.path)) {
+        let projects = link.linked_projects.iter().chain(link.durable.iter().map(|durable| &durable.project));
+        for project in projects.filter(|project| !inside(project) && project.exists()) {
+            consumers.entry(project.clone()).or_default().push(link.name.clone());
+        }
+    }
+    if consumers.is_empty() {
+        return Ok(None);
+    }
+
+    println!("💡 {} has no application project, but its libraries are linked into:", display_path(dir));
+    let choices: Vec<String> = consumers.iter()
+        .map(|(project, libraries)| format!("{} ({})", display_path(project), libraries.join(", ")))
+        .collect();
+    match crate::prompt::choose("Serve which application? Library watchers stay in this workspace.", &choices) {
+        Some(index) => Ok(consumers.into_keys().nth(index)),
+        None => Err(SpineError::Config(format!(
+            "No application to serve in {}. Run from the application, or pass --app-dir with one of: {}",
+            display_path(dir),
+            consumers.keys().map(|project| display_path(project)).collect::<Vec<_>>().join(", ")
+        )).into()),
+    }
 }
 
-pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
-    let config = Config::load_or_create()?;
+pub fn debug_command(config: &Config, show_workspace: bool, show_libs: bool) -> Result<()> {
     let workspace_root = std::env::current_dir()?;
     
     println!("🔍 Spine Angular Debug Information");
     println!("==================================");
+
+    println!("\n🗂️  Config schema: version {} (this build writes {})", config.schema_version, crate::config::CONFIG_SCHEMA_VERSION);
+    match config.migrated_from {
+        Some(version) => println!("  Migrated from version {} while loading; the old file is config.toml.bak-{}", version, version),
+        None => println!("  No migration ran while loading"),
+    }
     
     // Show Spine linked packages with linked project info
     println!("\n📦 Spine Linked Packages:");
@@ -1031,15 +1711,67 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
         println!("  (No packages linked in Spine)");
     } else {
         for (name, link) in &config.links {
-            println!("  • {} -> {}", name, link.path.display());
+            let workspace = if config.is_workspace_override(name) { " (workspace)" } else { "" };
+            println!("  • {}{} -> {}", name, workspace, display_path(&link.path));
             if !link.linked_projects.is_empty() {
                 println!("    🔗 Linked to {} project(s):", link.linked_projects.len());
                 for project in &link.linked_projects {
-                    println!("      • {}", project.display());
+                    println!("      • {}", display_path(project));
                 }
             }
         }
     }
+
+    if let Some(entry) = config.workspace_overrides.values().next() {
+        println!("\n🗂️  Workspace Link Overrides ({}):", display_path(&entry.file));
+        for (name, entry) in &config.workspace_overrides {
+            match &entry.global {
+                Some(global) => println!("  • {}: {} (global) → {}", name, display_path(&global.path), display_path(&entry.path)),
+                None => println!("  • {}: {} (workspace only)", name, display_path(&entry.path)),
+            }
+        }
+    }
+    
+    let limits = crate::paths::walk_limits();
+    println!("\n🧭 Workspace Search (up to {} level(s){}):", limits.max_levels,
+        if limits.stop_at_repository { ", stopping at repository roots" } else { "" });
+    let mut walked: Vec<&crate::config::PackageLink> = config.links.values().collect();
+    walked.sort_by(|a, b| a.name.cmp(&b.name));
+    for link in walked {
+        let outcome = crate::paths::walk_up(&link.path, "angular.json");
+        match &outcome.found {
+            Some(root) => println!("  • {}: angular.json at {}", link.name, display_path(root)),
+            None => println!("  • {}: stopped at {}, {}", link.name, display_path(&outcome.stopped_at), outcome.reason.describe()),
+        }
+    }
+
+    if !config.path_map.is_empty() {
+        println!("\n🗺️  Path Map:");
+        for remap in config.path_map.remaps() {
+            let state = if remap.applies {
+                let moved = config.links.values().filter(|link| link.path.starts_with(&remap.local)).count();
+                format!("applied, {} link(s) read from {}", moved, display_path(&remap.local))
+            } else if remap.canonical.exists() {
+                "not needed, the first root exists here".to_string()
+            } else {
+                "not applied, neither root exists here".to_string()
+            };
+            println!("  • {} → {}: {}", display_path(&remap.canonical), display_path(&remap.local), state);
+        }
+    }
+
+    println!("\n📦 Package Manager Detection:");
+    let detection = crate::pm::PackageManager::detect(&workspace_root);
+    println!("  Detected: {}", detection.manager);
+    for evidence in &detection.evidence {
+        println!("    • {}", evidence);
+    }
+    if detection.is_ambiguous() {
+        println!("  ⚠️  Conflicting lockfiles; falling back to npm");
+    }
+    if let Some(configured) = config.package_manager {
+        println!("  Configured override: {}", configured);
+    }
     
     // Use the same intelligent workspace detection as serve/build commands
     println!("\n🏗️  Smart Workspace Detection:");
@@ -1049,7 +1781,7 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
     
     // First try current directory for workspace
     let mut detected_workspace_root = workspace_root.clone();
-    let mut workspace = AngularBuildManager::detect_angular_workspace(&workspace_root)?;
+    let mut workspace = AngularBuildManager::detect_workspace_for(&workspace_root, config)?;
     
     // If no workspace in current directory, try to find workspace from linked packages
     if workspace.is_none() && !config.links.is_empty() {
@@ -1059,8 +1791,8 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
         for (package_name, package_link) in &config.links {
             match AngularBuildManager::find_workspace_root_for_package(&package_link.path) {
                 Ok(found_workspace_root) => {
-                    if let Ok(Some(found_workspace)) = AngularBuildManager::detect_angular_workspace(&found_workspace_root) {
-                        println!("  ✅ Found Angular workspace from package '{}': {}", package_name, found_workspace_root.display());
+                    if let Ok(Some(found_workspace)) = AngularBuildManager::detect_workspace_for(&found_workspace_root, config) {
+                        println!("  ✅ Found Angular workspace from package '{}': {}", package_name, display_path(&found_workspace_root));
                         detected_workspace_root = found_workspace_root;
                         workspace = Some(found_workspace);
                         break;
@@ -1074,13 +1806,26 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
     match workspace {
         Some(workspace) => {
             println!("  ✅ Angular workspace detected");
-            println!("  📁 Workspace root: {}", detected_workspace_root.display());
+            println!("  📁 Workspace root: {}", display_path(&detected_workspace_root));
+            println!("  🧾 Projects read from: {}", workspace.source.describe());
+            if workspace.source == crate::angular::WorkspaceSource::Nx {
+                let builder = match Platform::nx_command(&detected_workspace_root) {
+                    Some(_) => "nx build",
+                    None => "ng build (nx isn't installed in the workspace or on PATH)",
+                };
+                println!("  🔨 Builds run with: {}", builder);
+            }
             println!("  🎯 Default project: {}", workspace.default_project.as_deref().unwrap_or("(none)"));
+            let discovered = workspace.projects.values().filter(|project| project.discovered).count();
+            if discovered > 0 {
+                println!("  🧭 {} project(s) not in angular.json, discovered from project.json/ng-package.json", discovered);
+            }
             
             if show_workspace {
                 println!("\n📋 All Projects in Workspace:");
                 for (name, project) in &workspace.projects {
-                    println!("  • {} ({})", name, project.project_type);
+                    let origin = if project.discovered { ", discovered" } else { "" };
+                    println!("  • {} ({}{})", name, project.project_type, origin);
                     println!("    📂 Root: {}", project.root);
                     if let Some(src) = &project.source_root {
                         println!("    📄 Source: {}", src);
@@ -1096,8 +1841,8 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
                 .collect();
                 
             println!("  📚 Libraries in workspace: {}", library_projects.len());
-            for (name, _) in &library_projects {
-                println!("    • {}", name);
+            for (name, project) in &library_projects {
+                println!("    • {}{}", name, if project.discovered { " (discovered)" } else { "" });
             }
             
             println!("  🎯 Packages linked to current project: {}", linked_package_names.len());
@@ -1129,9 +1874,9 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
                             if project.project_type == "library" {
                                 let potential_dist_path = detected_workspace_root.join("dist").join(lib_name);
                                 
-                                if let (Ok(package_canonical), Ok(dist_canonical)) = (
-                                    package_link.path.canonicalize(),
-                                    potential_dist_path.canonicalize()
+                                if let (Some(package_canonical), Some(dist_canonical)) = (
+                                    crate::paths::canonicalize_cached(&package_link.path),
+                                    crate::paths::canonicalize_cached(&potential_dist_path)
                                 ) {
                                     if package_canonical == dist_canonical {
                                         local_matches.push(package_name);
@@ -1148,19 +1893,19 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
                     if !found_match {
                         match AngularBuildManager::find_workspace_root_for_package(&package_link.path) {
                             Ok(lib_workspace_root) => {
-                                if let Ok(Some(lib_workspace)) = AngularBuildManager::detect_angular_workspace(&lib_workspace_root) {
+                                if let Ok(Some(lib_workspace)) = AngularBuildManager::detect_workspace_for(&lib_workspace_root, config) {
                                     for (lib_name, project) in &lib_workspace.projects {
                                         if project.project_type == "library" {
                                             let potential_dist_path = lib_workspace_root.join("dist").join(lib_name);
                                             
-                                            if let (Ok(package_canonical), Ok(dist_canonical)) = (
-                                                package_link.path.canonicalize(),
-                                                potential_dist_path.canonicalize()
+                                            if let (Some(package_canonical), Some(dist_canonical)) = (
+                                                crate::paths::canonicalize_cached(&package_link.path),
+                                                crate::paths::canonicalize_cached(&potential_dist_path)
                                             ) {
                                                 if package_canonical == dist_canonical {
                                                     cross_workspace_matches.push((package_name.to_string(), lib_name.to_string(), lib_workspace_root.clone()));
                                                     println!("    🔗 {} -> {} (cross-workspace library in {})", 
-                                                             package_name, lib_name, lib_workspace_root.display());
+                                                             package_name, lib_name, display_path(&lib_workspace_root));
                                                     found_match = true;
                                                     break;
                                                 }
@@ -1190,9 +1935,9 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
                     println!("\n🌐 Cross-Workspace Details:");
                     for (package_name, lib_name, workspace_root) in cross_workspace_matches {
                         println!("  📦 {} -> {}", package_name, lib_name);
-                        println!("    🏠 Workspace: {}", workspace_root.display());
+                        println!("    🏠 Workspace: {}", display_path(&workspace_root));
                         if let Some(link) = config.links.get(&package_name) {
-                            println!("    📂 Package path: {}", link.path.display());
+                            println!("    📂 Package path: {}", display_path(&link.path));
                         }
                     }
                 }
@@ -1202,7 +1947,7 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
                     for package in &unmatched {
                         if let Some(link) = config.links.get(*package) {
                             println!("  📦 {}", package);
-                            println!("    🔗 Linked to: {}", link.path.display());
+                            println!("    🔗 Linked to: {}", display_path(&link.path));
                             
                             // Try to find similar library names
                             let similar: Vec<_> = library_projects
@@ -1223,7 +1968,7 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
                             match AngularBuildManager::find_workspace_root_for_package(&link.path) {
                                 Ok(package_workspace_root) => {
                                     if package_workspace_root != detected_workspace_root {
-                                        println!("    🏠 Package belongs to different workspace: {}", package_workspace_root.display());
+                                        println!("    🏠 Package belongs to different workspace: {}", display_path(&package_workspace_root));
                                     }
                                 }
                                 Err(_) => {
@@ -1238,14 +1983,14 @@ pub fn debug_command(show_workspace: bool, show_libs: bool) -> Result<()> {
         }
         None => {
             println!("  ❌ No Angular workspace detected in current directory or linked package paths");
-            println!("  📁 Current directory: {}", workspace_root.display());
+            println!("  📁 Current directory: {}", display_path(&workspace_root));
             
             if !config.links.is_empty() {
                 println!("  🔍 Checking individual package workspaces:");
                 for (package_name, package_link) in &config.links {
                     match AngularBuildManager::find_workspace_root_for_package(&package_link.path) {
                         Ok(package_workspace_root) => {
-                            println!("    📦 {} -> workspace at {}", package_name, package_workspace_root.display());
+                            println!("    📦 {} -> workspace at {}", package_name, display_path(&package_workspace_root));
                         }
                         Err(_) => {
                             println!("    📦 {} -> no workspace found", package_name);
@@ -0,0 +1,331 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::config::{Config, ConfigFile};
+use crate::npm::NpmManager;
+use crate::pm::PackageManager;
+use crate::scanner::Scanner;
+
+/// Version of the request and response format, raised on incompatible
+/// changes. Announced in the `ready` event; a request naming another
+/// version is refused.
+pub const API_VERSION: u32 = 1;
+
+/// One line of input
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    /// Echoed on the response and on progress events
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    pub api_version: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiError {
+    /// parse_error, unsupported_version, unknown_method, invalid_params or failed
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    fn failed(error: anyhow::Error) -> Self {
+        Self::new("failed", error.to_string())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ListParams {
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StatusParams {
+    project: Option<PathBuf>,
+    health: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct LinkParams {
+    packages: Vec<String>,
+    project: Option<PathBuf>,
+    pm: Option<PackageManager>,
+    strict_peers: bool,
+    /// unlink only: reinstall the declared ranges afterwards
+    restore: bool,
+    /// link only: the variant to link, kept for the project
+    variant: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct BuildParams {
+    library: Option<String>,
+    all: bool,
+    project: Option<PathBuf>,
+    configuration: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ScanParams {
+    path: Option<String>,
+    no_ignore: bool,
+    depth: Option<usize>,
+}
+
+/// `spine serve-api --stdio`: answer newline-delimited JSON requests on
+/// stdin until it closes or a `shutdown` request arrives. One config is
+/// kept for the session and requests run one at a time, so every change
+/// goes through this process's single writer.
+pub fn serve_stdio(config_file: &ConfigFile) -> Result<()> {
+    let out = take_stdout()?;
+    crate::prompt::disable();
+    let config = Config::load_or_create(config_file)?;
+    config.report_load_warnings();
+    let mut session = Session { out, stamps: Vec::new(), home: std::env::current_dir()?, config };
+    if crate::journal::recover_interrupted(&mut session.config)? {
+        session.config.save()?;
+    }
+    session.stamps = session.config_stamps();
+
+    session.send(&json!({ "event": "ready", "api_version": API_VERSION, "spine_version": env!("CARGO_PKG_VERSION") }))?;
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                session.respond(&Value::Null, Err(ApiError::new("parse_error", e.to_string())))?;
+                continue;
+            }
+        };
+        if request.method == "shutdown" {
+            session.respond(&request.id, Ok(Value::Null))?;
+            break;
+        }
+        let result = session.handle(&request);
+        session.respond(&request.id, result)?;
+    }
+    Ok(())
+}
+
+struct Session {
+    /// The real stdout, which carries only protocol lines
+    out: File,
+    /// Modification times of the config files when `config` was last read or written
+    stamps: Vec<Option<SystemTime>>,
+    /// Where the session started; requests without a project run here
+    home: PathBuf,
+    config: Config,
+}
+
+impl Session {
+    fn send(&mut self, message: &Value) -> io::Result<()> {
+        writeln!(self.out, "{}", message)?;
+        self.out.flush()
+    }
+
+    fn respond(&mut self, id: &Value, result: Result<Value, ApiError>) -> io::Result<()> {
+        match result {
+            Ok(result) => self.send(&json!({ "id": id, "result": result })),
+            Err(error) => self.send(&json!({ "id": id, "error": error })),
+        }
+    }
+
+    fn progress(&mut self, id: &Value, message: String) {
+        let _ = self.send(&json!({ "id": id, "event": "progress", "message": message }));
+    }
+
+    fn handle(&mut self, request: &Request) -> Result<Value, ApiError> {
+        if let Some(version) = request.api_version.filter(|version| *version != API_VERSION) {
+            return Err(ApiError::new("unsupported_version", format!(
+                "This spine speaks API version {}, not {}", API_VERSION, version
+            )));
+        }
+        self.reload_if_changed().map_err(ApiError::failed)?;
+
+        let result = match request.method.as_str() {
+            "list" => self.list(params(request)?),
+            "status" => self.status(params(request)?),
+            "link" => self.link(&request.id, params(request)?),
+            "unlink" => self.unlink(&request.id, params(request)?),
+            "build" => self.build(&request.id, params(request)?),
+            "scan" => self.scan(params(request)?),
+            other => return Err(ApiError::new("unknown_method", format!(
+                "Unknown method '{}'; expected list, status, link, unlink, build, scan or shutdown", other
+            ))),
+        };
+        result.map_err(ApiError::failed)
+    }
+
+    fn list(&self, params: ListParams) -> Result<Value> {
+        let mut links: Vec<_> = self.config.links.values().filter(|link| link.has_any_tag(&params.tags)).collect();
+        links.sort_by(|a, b| a.name.cmp(&b.name));
+        let links = links.into_iter()
+            .map(|link| {
+                let mut entry = serde_json::to_value(link)?;
+                entry["npm_name"] = Value::String(link.npm_name().to_string());
+                Ok(entry)
+            })
+            .collect::<Result<Vec<Value>>>()?;
+        Ok(json!({ "links": links }))
+    }
+
+    fn status(&mut self, params: StatusParams) -> Result<Value> {
+        let project = self.enter(params.project.as_deref())?;
+        Ok(NpmManager::status_json(&self.config, true, params.health, &project))
+    }
+
+    fn link(&mut self, id: &Value, params: LinkParams) -> Result<Value> {
+        let project = self.enter(params.project.as_deref())?;
+        let pm = PackageManager::resolve(params.pm, self.config.package_manager, &project);
+        if let Some(variant) = &params.variant {
+            for package in &params.packages {
+                self.config.select_variant(package, &project, variant)?;
+            }
+        }
+        self.progress(id, format!("Linking {} into {}", params.packages.join(", "), project.display()));
+        let result = NpmManager::link_packages(&mut self.config, &project, &params.packages, pm, params.strict_peers);
+        // Packages linked before a failure are in node_modules already
+        self.save()?;
+        result?;
+        Ok(json!({ "linked": params.packages, "project": project }))
+    }
+
+    fn unlink(&mut self, id: &Value, params: LinkParams) -> Result<Value> {
+        let project = self.enter(params.project.as_deref())?;
+        let pm = PackageManager::resolve(params.pm, self.config.package_manager, &project);
+        self.progress(id, format!("Unlinking {} from {}", params.packages.join(", "), project.display()));
+        let result = NpmManager::unlink_packages(&mut self.config, &project, &params.packages, pm, params.restore);
+        // Packages unlinked before a failure are already gone
+        self.save()?;
+        result?;
+        Ok(json!({ "unlinked": params.packages, "project": project }))
+    }
+
+    fn build(&mut self, id: &Value, params: BuildParams) -> Result<Value> {
+        let project = self.enter(params.project.as_deref())?;
+        let target = params.library.clone().unwrap_or_else(|| "every library".to_string());
+        self.progress(id, format!("Building {} in {}", target, project.display()));
+        crate::angular::build_command(&self.config, &project, crate::angular::BuildOptions {
+            library: params.library.clone(),
+            all: params.all,
+            configuration: params.configuration,
+            ..Default::default()
+        })?;
+        crate::paths::forget_cached_paths();
+        Ok(json!({ "built": params.library.map(Value::String).unwrap_or(Value::Bool(params.all)) }))
+    }
+
+    fn scan(&self, params: ScanParams) -> Result<Value> {
+        let outcome = Scanner::discover(&self.config, params.path.as_deref(), params.no_ignore, params.depth)?;
+        let packages: Vec<Value> = outcome.packages.into_iter()
+            .map(|package| json!({
+                "name": package.name,
+                "path": package.path,
+                "version": package.version,
+                "is_dist": package.is_dist,
+                "source": package.source,
+                "configured": self.config.links.contains_key(&package.name),
+            }))
+            .collect();
+        Ok(json!({ "packages": packages, "directories": outcome.directories }))
+    }
+
+    /// The request's project, relative to the session's starting directory,
+    /// or that directory itself. The project's .spine.toml link overrides
+    /// replace the previous request's, as `--to` does; the process's current
+    /// directory is never changed.
+    fn enter(&mut self, project: Option<&Path>) -> Result<PathBuf> {
+        let project = match project {
+            Some(project) => NpmManager::resolve_project_dir(&crate::paths::resolve_relative(&self.home, &project.to_string_lossy()))?,
+            None => self.home.clone(),
+        };
+        self.config.use_workspace_overrides_of(&project);
+        Ok(project)
+    }
+
+    fn save(&mut self) -> Result<()> {
+        self.config.save()?;
+        self.stamps = self.config_stamps();
+        crate::paths::forget_cached_paths();
+        Ok(())
+    }
+
+    /// Pick up edits another spine made to the config files since this
+    /// session last read or wrote them
+    fn reload_if_changed(&mut self) -> Result<()> {
+        if self.config_stamps() != self.stamps {
+            self.config = Config::load(&self.config.file)?;
+            self.stamps = self.config_stamps();
+        }
+        Ok(())
+    }
+
+    fn config_stamps(&self) -> Vec<Option<SystemTime>> {
+        let mut files = vec![self.config.file.path().ok()];
+        if let Some(profile) = &self.config.profile {
+            files.push(self.config.file.profile_path(profile).ok());
+        }
+        files.into_iter()
+            .map(|file| file.and_then(|file| fs::metadata(file).ok()).and_then(|metadata| metadata.modified().ok()))
+            .collect()
+    }
+}
+
+fn params<T: for<'de> Deserialize<'de> + Default>(request: &Request) -> Result<T, ApiError> {
+    if request.params.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(request.params.clone()).map_err(|e| ApiError::new("invalid_params", e.to_string()))
+}
+
+/// Keep the real stdout for machine-readable output and point the process's
+/// stdout, which every command prints its human output to, at stderr
+#[cfg(unix)]
+pub fn take_stdout() -> io::Result<File> {
+    use std::os::unix::io::FromRawFd;
+
+    io::stdout().flush()?;
+    // SAFETY: dup and dup2 only duplicate descriptors; the new one is owned by the File
+    unsafe {
+        let protocol = libc::dup(libc::STDOUT_FILENO);
+        if protocol < 0 || libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(File::from_raw_fd(protocol))
+    }
+}
+
+#[cfg(windows)]
+pub fn take_stdout() -> io::Result<File> {
+    use std::os::windows::io::FromRawHandle;
+    use windows_sys::Win32::System::Console::{GetStdHandle, SetStdHandle, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE};
+
+    io::stdout().flush()?;
+    // SAFETY: the handles come from the process's own standard handles;
+    // Rust's stdout looks its handle up on every write, so it follows the swap
+    unsafe {
+        let protocol = GetStdHandle(STD_OUTPUT_HANDLE);
+        if SetStdHandle(STD_OUTPUT_HANDLE, GetStdHandle(STD_ERROR_HANDLE)) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(File::from_raw_handle(protocol))
+    }
+}
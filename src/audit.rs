@@ -0,0 +1,268 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use anyhow::Result;
+use serde::Serialize;
+use crate::config::{Config, ConfigFile, PackageLink};
+use crate::diff::FileFingerprint;
+use crate::error::SpineError;
+use crate::paths::display_path;
+use crate::platform::{run_command, OutputMode, Platform};
+use crate::registry::Registry;
+
+/// Files listed per category before the rest are summarized
+const LISTED_FILES: usize = 10;
+
+/// How a linked package compares with what its registry publishes
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkAudit {
+    pub package: String,
+    pub npm_name: String,
+    pub path: PathBuf,
+    pub local_version: Option<String>,
+    /// Whether the local version is published; None when the registry couldn't be asked
+    pub version_published: Option<bool>,
+    /// The published version the files were compared with: the local one
+    /// when published, the latest otherwise
+    pub compared_with: Option<String>,
+    /// Files whose content differs from the published tarball
+    pub changed: Vec<String>,
+    /// Files in the linked directory that the tarball doesn't have
+    pub not_published: Vec<String>,
+    /// Files of the tarball missing from the linked directory
+    pub missing: Vec<String>,
+    pub git: Option<GitState>,
+    /// Why the comparison couldn't be made
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitState {
+    pub head: Option<String>,
+    pub branch: Option<String>,
+    pub uncommitted: Vec<String>,
+}
+
+impl LinkAudit {
+    /// The linked files are exactly a published version's
+    pub fn matches_published(&self) -> bool {
+        self.error.is_none()
+            && self.version_published == Some(true)
+            && self.changed.is_empty()
+            && self.not_published.is_empty()
+            && self.missing.is_empty()
+    }
+}
+
+/// `spine audit-links`: compare each linked package with the registry's
+/// tarball of its version, with the source repository's git state. With
+/// `fail_on_divergence`, any package that can't be shown to match is an error.
+pub fn audit_links(config: &Config, packages: &[String], json: bool, fail_on_divergence: bool) -> Result<()> {
+    let links: Vec<&PackageLink> = if packages.is_empty() {
+        let mut links: Vec<&PackageLink> = config.links.values()
+            .filter(|link| !link.pending && (!link.linked_projects.is_empty() || !link.durable.is_empty()))
+            .collect();
+        links.sort_by(|a, b| a.name.cmp(&b.name));
+        links
+    } else {
+        let available: Vec<String> = config.links.keys().cloned().collect();
+        packages.iter()
+            .map(|name| config.links.get(name).ok_or_else(|| SpineError::package_not_found_with_suggestions(name, &available)))
+            .collect::<Result<_, _>>()?
+    };
+
+    if !json {
+        if links.is_empty() {
+            println!("No linked packages to audit.");
+            return Ok(());
+        }
+        println!("🔎 Auditing {} linked package(s) against the registry", links.len());
+    }
+
+    let audits: Vec<LinkAudit> = links.iter().map(|link| audit_link(config, link)).collect();
+    let divergent = audits.iter().filter(|audit| !audit.matches_published()).count();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+            "packages": audits,
+            "divergent": divergent,
+        }))?);
+    } else {
+        for audit in &audits {
+            print_audit(audit);
+        }
+        if divergent == 0 {
+            println!("\n✅ Every linked package matches a published version");
+        } else {
+            println!("\n📊 {} of {} linked package(s) differ from what is published", divergent, audits.len());
+        }
+    }
+
+    if fail_on_divergence && divergent > 0 {
+        return Err(SpineError::Config(format!("{} linked package(s) differ from what is published", divergent)).into());
+    }
+    Ok(())
+}
+
+fn audit_link(config: &Config, link: &PackageLink) -> LinkAudit {
+    let local_version = crate::package::get_package_version(&link.path.join("package.json")).ok();
+    let mut audit = LinkAudit {
+        package: link.name.clone(),
+        npm_name: link.npm_name().to_string(),
+        path: link.path.clone(),
+        local_version: local_version.clone(),
+        version_published: None,
+        compared_with: None,
+        changed: Vec::new(),
+        not_published: Vec::new(),
+        missing: Vec::new(),
+        git: git_state(&link.path),
+        error: None,
+    };
+
+    // Run in the package's directory so its .npmrc registry settings apply
+    let registry = Registry::new(config, &link.path);
+    let (versions, latest) = match registry.published_versions(link.npm_name())
+        .and_then(|versions| Ok((versions, registry.latest_version(link.npm_name())?)))
+    {
+        Ok(published) => published,
+        Err(e) => {
+            audit.error = Some(e.to_string().trim().to_string());
+            return audit;
+        }
+    };
+    audit.version_published = Some(local_version.as_ref().is_some_and(|version| versions.contains(version)));
+    let compared_with = match (audit.version_published, local_version, latest) {
+        (Some(true), Some(version), _) => version,
+        (_, _, Some(latest)) => latest,
+        _ => {
+            audit.error = Some("Nothing is published under this name".to_string());
+            return audit;
+        }
+    };
+
+    match published_files(config, link, &compared_with) {
+        Ok(tarball) => {
+            let mut local = crate::diff::fingerprints(&link.path);
+            if let Some(publishable) = publishable_files(config, &link.path) {
+                local.retain(|file, _| publishable.contains(file));
+            }
+            for (file, fingerprint) in &local {
+                match tarball.get(file) {
+                    None => audit.not_published.push(file.clone()),
+                    Some(published) if published != fingerprint => audit.changed.push(file.clone()),
+                    Some(_) => {}
+                }
+            }
+            audit.missing = tarball.keys().filter(|file| !local.contains_key(*file)).cloned().collect();
+        }
+        Err(e) => audit.error = Some(e.to_string().trim().to_string()),
+    }
+    audit.compared_with = Some(compared_with);
+    audit
+}
+
+/// Fingerprints of a published tarball. Published versions never change, so
+/// each one is downloaded and unpacked once into Spine's cache.
+fn published_files(config: &Config, link: &PackageLink, version: &str) -> Result<BTreeMap<String, FileFingerprint>> {
+    let safe_name = link.npm_name().replace('/', "__");
+    let dir = cache_dir(&config.file)?.join(format!("{}@{}", safe_name, version));
+    let unpacked = dir.join("package");
+    if !unpacked.exists() {
+        fs::create_dir_all(&dir)?;
+        let mut pack = Platform::npm_command();
+        pack.arg("pack").arg(format!("{}@{}", link.npm_name(), version))
+            .arg("--pack-destination").arg(&dir)
+            .arg("--json")
+            .current_dir(&link.path);
+        let output = run_command(pack, config.timeouts.registry(), OutputMode::Capture)?;
+        if !output.success() {
+            return Err(SpineError::Config(format!("npm pack {}@{} failed: {}", link.npm_name(), version, output.stderr.trim())).into());
+        }
+        let packed: serde_json::Value = serde_json::from_str(&output.stdout)?;
+        let filename = packed[0]["filename"].as_str()
+            .ok_or_else(|| SpineError::Config("npm pack did not name the tarball".to_string()))?;
+        // Scoped tarballs are named like the package with the @ dropped and / as -
+        let tarball = dir.join(Path::new(filename).file_name().unwrap_or_default());
+
+        let mut extract = Command::new("tar");
+        extract.arg("-xzf").arg(&tarball).arg("-C").arg(&dir);
+        let output = run_command(extract, None, OutputMode::Capture)?;
+        let _ = fs::remove_file(&tarball);
+        if !output.success() {
+            let _ = fs::remove_dir_all(&unpacked);
+            return Err(SpineError::Config(format!("Could not unpack {}: {}", filename, output.stderr.trim())).into());
+        }
+    }
+    Ok(crate::diff::fingerprints(&unpacked))
+}
+
+/// Files `npm pack` would put in a tarball of `dir`, so files npm never
+/// publishes (.npmrc, ignored files) aren't reported. None if npm can't say.
+fn publishable_files(config: &Config, dir: &Path) -> Option<BTreeSet<String>> {
+    let mut command = Platform::npm_command();
+    command.args(["pack", "--dry-run", "--json", "--ignore-scripts"]).current_dir(dir);
+    let output = run_command(command, config.timeouts.registry(), OutputMode::Capture).ok().filter(|output| output.success())?;
+    let packed: serde_json::Value = serde_json::from_str(&output.stdout).ok()?;
+    let files = packed[0]["files"].as_array()?;
+    Some(files.iter().filter_map(|file| file["path"].as_str().map(str::to_string)).collect())
+}
+
+/// Where registry tarballs are unpacked for comparison, beside config.toml
+pub fn cache_dir(config_file: &ConfigFile) -> Result<PathBuf> {
+    Ok(config_file.dir()?.join("cache").join("tarballs"))
+}
+
+fn git_state(dir: &Path) -> Option<GitState> {
+    let uncommitted = crate::angular::uncommitted_files(dir)?;
+    let branch = Command::new("git").arg("-C").arg(dir).args(["rev-parse", "--abbrev-ref", "HEAD"]).output().ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+    Some(GitState { head: crate::diff::git_head(dir), branch, uncommitted })
+}
+
+fn print_audit(audit: &LinkAudit) {
+    println!("\n📦 {} {} ({})", audit.package, audit.local_version.as_deref().unwrap_or("(no version)"), display_path(&audit.path));
+
+    match (&audit.error, audit.version_published, &audit.compared_with) {
+        (Some(error), _, _) => println!("   ❔ Could not compare with the registry: {}", error),
+        (None, _, _) if audit.matches_published() => {
+            println!("   ✅ Matches the published {} tarball", audit.compared_with.as_deref().unwrap_or_default());
+        }
+        (None, published, Some(version)) => {
+            if published == Some(false) {
+                println!("   ⚠️  This version isn't published; compared with the latest, {}", version);
+            }
+            let differences = audit.changed.len() + audit.not_published.len() + audit.missing.len();
+            if differences == 0 {
+                println!("   Files match the published {} tarball", version);
+            } else {
+                println!("   ⚠️  Differs from the published {} tarball: {} changed, {} not published, {} missing",
+                    version, audit.changed.len(), audit.not_published.len(), audit.missing.len());
+            }
+            for (marker, files) in [("~", &audit.changed), ("+", &audit.not_published), ("-", &audit.missing)] {
+                for file in files.iter().take(LISTED_FILES) {
+                    println!("      {} {}", marker, file);
+                }
+                if files.len() > LISTED_FILES {
+                    println!("      {} … and {} more", marker, files.len() - LISTED_FILES);
+                }
+            }
+        }
+        (None, _, None) => {}
+    }
+
+    match &audit.git {
+        Some(git) => {
+            let head = git.head.as_deref().map(|sha| &sha[..sha.len().min(8)]).unwrap_or("no commits");
+            let state = if git.uncommitted.is_empty() {
+                "clean".to_string()
+            } else {
+                format!("{} uncommitted file(s)", git.uncommitted.len())
+            };
+            println!("   Git: {} @ {}, {}", git.branch.as_deref().unwrap_or("(detached)"), head, state);
+        }
+        None => println!("   Git: not in a repository"),
+    }
+}
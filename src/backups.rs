@@ -0,0 +1,196 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use crate::config::{Config, ConfigFile, PackageLink};
+use crate::error::SpineError;
+use crate::paths::display_path;
+
+/// A copy of config.toml or a profile file as it was before a save replaced it
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub timestamp: u64,
+    /// The profile file it came from; config.toml when None
+    pub profile: Option<String>,
+    pub path: PathBuf,
+}
+
+impl Backup {
+    /// `<timestamp>.toml` or `<timestamp>.<profile>.toml`
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let stem = path.file_name()?.to_str()?.strip_suffix(".toml")?.to_string();
+        let (timestamp, profile) = match stem.split_once('.') {
+            Some((timestamp, profile)) => (timestamp, Some(profile.to_string())),
+            None => (stem.as_str(), None),
+        };
+        Some(Self { timestamp: timestamp.parse().ok()?, profile, path })
+    }
+
+    /// The file this backup restores
+    fn target(&self, config_file: &ConfigFile) -> Result<PathBuf> {
+        match &self.profile {
+            Some(name) => config_file.profile_path(name),
+            None => config_file.path(),
+        }
+    }
+
+    fn label(&self) -> String {
+        match &self.profile {
+            Some(name) => format!("profile '{}'", name),
+            None => "config.toml".to_string(),
+        }
+    }
+}
+
+pub fn backups_dir(config_file: &ConfigFile) -> Result<PathBuf> {
+    Ok(config_file.dir()?.join("backups"))
+}
+
+/// Backups, oldest first
+pub fn list(config_file: &ConfigFile) -> Result<Vec<Backup>> {
+    let Ok(entries) = fs::read_dir(backups_dir(config_file)?) else { return Ok(Vec::new()) };
+    let mut backups: Vec<Backup> = entries.flatten()
+        .filter_map(|entry| Backup::from_path(entry.path()))
+        .collect();
+    backups.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.profile.cmp(&b.profile)));
+    Ok(backups)
+}
+
+/// Copy `path` into the backups folder before `new_content` replaces it,
+/// then drop all but the newest `keep`. Nothing is copied when the file
+/// doesn't exist yet or wouldn't change, nor when `keep` is 0. A second
+/// save within the same second keeps the first copy, the older state.
+pub fn snapshot(config_file: &ConfigFile, path: &Path, new_content: &str, keep: usize) -> Result<()> {
+    if keep == 0 {
+        return Ok(());
+    }
+    let Ok(current) = fs::read_to_string(path) else { return Ok(()) };
+    if current == new_content {
+        return Ok(());
+    }
+
+    let dir = backups_dir(config_file)?;
+    fs::create_dir_all(&dir)?;
+    let timestamp = crate::history::now_timestamp();
+    let name = if path == config_file.path()? {
+        format!("{}.toml", timestamp)
+    } else {
+        let profile = path.file_stem().unwrap_or_default().to_string_lossy();
+        format!("{}.{}.toml", timestamp, profile)
+    };
+    let backup = dir.join(name);
+    if !backup.exists() {
+        fs::write(&backup, current)?;
+    }
+
+    let backups = list(config_file)?;
+    for old in backups.iter().take(backups.len().saturating_sub(keep)) {
+        let _ = fs::remove_file(&old.path);
+    }
+    Ok(())
+}
+
+/// `spine config backups`
+pub fn list_command(config_file: &ConfigFile) -> Result<()> {
+    let backups = list(config_file)?;
+    if backups.is_empty() {
+        println!("No config backups yet in {}", display_path(&backups_dir(config_file)?));
+        return Ok(());
+    }
+
+    println!("🗄️  Config backups in {} (newest last):", display_path(&backups_dir(config_file)?));
+    for backup in &backups {
+        let links = fs::read_to_string(&backup.path).ok()
+            .and_then(|content| Config::from_toml_lenient(&content).ok())
+            .map(|config| format!("{} link(s)", config.links.len()))
+            .unwrap_or_else(|| "unreadable".to_string());
+        println!("  {}  {} UTC, {:<9}  {:<20} {}", backup.timestamp, crate::history::format_clock(backup.timestamp),
+            crate::history::format_age(backup.timestamp), backup.label(), links);
+    }
+    println!("\n💡 'spine config restore <timestamp>' or 'spine config restore latest' rolls back");
+    Ok(())
+}
+
+/// `spine config restore <timestamp|latest>`: put back every file backed up
+/// at that time, each after backing up what it replaces, and summarize how
+/// the links change
+pub fn restore_command(config_file: &ConfigFile, which: &str) -> Result<()> {
+    let backups = list(config_file)?;
+    let timestamp = match which {
+        "latest" => backups.last().map(|backup| backup.timestamp)
+            .ok_or_else(|| SpineError::Config("There are no config backups to restore".to_string()))?,
+        _ => which.parse::<u64>()
+            .map_err(|_| SpineError::Config(format!("Expected a backup timestamp or 'latest', got '{}'", which)))?,
+    };
+    let chosen: Vec<&Backup> = backups.iter().filter(|backup| backup.timestamp == timestamp).collect();
+    if chosen.is_empty() {
+        return Err(SpineError::Config(format!(
+            "No backup taken at {}. 'spine config backups' lists them", timestamp
+        )).into());
+    }
+
+    let keep = Config::load_main(config_file).map(|main| main.backups.keep).unwrap_or_default();
+    for backup in chosen {
+        let content = fs::read_to_string(&backup.path)?;
+        let restored = Config::from_toml_lenient(&content)
+            .map_err(|e| SpineError::Config(format!("Backup {} is unreadable: {}", display_path(&backup.path), e)))?;
+        let target = backup.target(config_file)?;
+        let current = fs::read_to_string(&target).ok()
+            .and_then(|content| Config::from_toml_lenient(&content).ok())
+            .unwrap_or_default();
+
+        {
+            let _lock = crate::lock::FileLock::acquire(&target)?;
+            if let Err(e) = snapshot(config_file, &target, &content, keep.max(1)) {
+                eprintln!("Warning: Failed to back up {}: {}", display_path(&target), e);
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            crate::config::write_replacing(&target, &content)?;
+        }
+        println!("✅ Restored {} from {} ({})", backup.label(), backup.timestamp, crate::history::format_age(backup.timestamp));
+        print_link_changes(&current, &restored);
+    }
+
+    let config = Config::load(config_file)?;
+    if let Err(e) = config.regenerate_completion() {
+        eprintln!("Warning: Failed to regenerate completion: {}", e);
+    }
+    Ok(())
+}
+
+/// Links the restore adds, removes and changes relative to `current`
+fn print_link_changes(current: &Config, restored: &Config) {
+    let names: BTreeSet<&String> = current.links.keys().chain(restored.links.keys()).collect();
+    let mut unchanged = 0;
+    for name in names {
+        match (current.links.get(name), restored.links.get(name)) {
+            (None, Some(link)) => println!("   ➕ {} -> {}", name, display_path(&link.path)),
+            (Some(_), None) => println!("   ➖ {}", name),
+            (Some(before), Some(after)) => {
+                let fields = changed_fields(before, after);
+                if fields.is_empty() {
+                    unchanged += 1;
+                } else {
+                    println!("   ✏️  {} ({})", name, fields.join(", "));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    if unchanged > 0 {
+        println!("   {} link(s) unchanged", unchanged);
+    }
+}
+
+/// Names of the fields that differ between two versions of a link
+fn changed_fields(before: &PackageLink, after: &PackageLink) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(before)), Ok(serde_json::Value::Object(after))) =
+        (serde_json::to_value(before), serde_json::to_value(after)) else { return Vec::new() };
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .cloned()
+        .collect()
+}
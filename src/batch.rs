@@ -0,0 +1,30 @@
+use crate::error::SpineError;
+
+/// Where a `--fail-fast` batch stopped
+#[derive(Debug, Clone)]
+pub struct BatchAbort {
+    /// Package whose failure stopped the batch
+    pub package: String,
+    pub error: String,
+    /// Packages after it that were never tried
+    pub not_attempted: usize,
+}
+
+impl BatchAbort {
+    /// Abort at position `index` of a batch of `total` packages
+    pub fn new(package: &str, error: impl Into<String>, index: usize, total: usize) -> Self {
+        Self {
+            package: package.to_string(),
+            error: error.into(),
+            not_attempted: total.saturating_sub(index + 1),
+        }
+    }
+
+    pub fn into_error(self) -> SpineError {
+        SpineError::BatchAborted {
+            package: self.package,
+            not_attempted: self.not_attempted,
+            error: self.error,
+        }
+    }
+}
@@ -3,44 +3,273 @@ use clap::{CommandFactory, Parser, Subcommand, ValueHint};
 use clap_complete;
 use std::io;
 use std::path::PathBuf;
-use crate::config::Config;
+use crate::angular_cli::ServeOptions;
+use crate::config::{Config, ConfigFile};
 use crate::completion;
 use crate::npm::NpmManager;
+use crate::pm::PackageManager;
+use crate::prompt_status::PromptFormat;
 use crate::scanner::Scanner;
 use crate::tui::TuiApp;
 
+const PROMPT_STATUS_EXAMPLES: &str = "\
+Examples:
+  spine prompt-status                 linked=3 broken=1
+  spine prompt-status --format json   {\"linked\":3,\"broken\":1}
+  spine prompt-status --porcelain     3 1
+
+zsh (~/.zshrc):
+  spine_prompt() {
+    local counts=($(spine prompt-status --porcelain 2>/dev/null))
+    (( ${counts[1]:-0} > 0 )) && print -n \"🔗${counts[1]} \"
+  }
+  setopt PROMPT_SUBST
+  PROMPT='$(spine_prompt)'$PROMPT
+
+starship (~/.config/starship.toml):
+  [custom.spine]
+  command = \"spine prompt-status --porcelain | cut -d' ' -f1\"
+  when = \"spine prompt-status --porcelain | grep -qv '^0 '\"
+  format = \"[🔗$output]($style) \"";
+
 #[derive(Parser)]
 #[command(name = "spine")]
 #[command(about = "A modern replacement for npm link with interactive configuration management")]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+    #[arg(long, global = true, help = "Show full paths instead of shortened ones")]
+    pub full_paths: bool,
+    #[arg(long, global = true, help = "Report timing and path lookup cache savings on exit")]
+    pub profile: bool,
+    #[arg(long, global = true, help = "Print progress as lines instead of spinners and bars")]
+    pub no_progress: bool,
+    #[arg(long, global = true, value_name = "PATH", env = "SPINE_CONFIG", help = "Config file to use instead of the one in the platform config directory")]
+    pub config: Option<PathBuf>,
+    #[arg(long, global = true, value_enum, env = "SPINE_LOG_FORMAT", help = "Output style: pretty (spinners in a terminal) or plain (no animation or escape codes from tools; progress lines lose their leading emoji)")]
+    pub log_format: Option<crate::progress::LogFormat>,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    #[command(about = "Report link entries that could not be loaded")]
+    Validate {
+        #[arg(long, help = "Also check config.toml and this project's .spine.toml against their JSON Schemas")]
+        schema: bool,
+    },
+    #[command(about = "Describe the configuration keys, or print the JSON Schema editors use")]
+    Schema {
+        #[arg(value_enum, default_value = "workspace", help = "Document to describe: workspace (.spine.toml) or config (config.toml)")]
+        target: crate::schema::SchemaTarget,
+        #[arg(long, help = "Print the JSON Schema itself")]
+        json_schema: bool,
+    },
+    #[command(about = "Print a configuration value, or the whole effective configuration")]
+    Get {
+        #[arg(help = "Dotted key, e.g. completion.auto_regenerate or links.<package>.path")]
+        key: Option<String>,
+        #[arg(long, help = "Print the value as JSON")]
+        json: bool,
+    },
+    #[command(about = "Set a configuration value, e.g. timeouts.link_secs or links.<package>.pre_link")]
+    Set {
+        #[arg(help = "Dotted key, e.g. completion.auto_regenerate or links.<package>.path")]
+        key: String,
+        #[arg(allow_hyphen_values = true, help = "The value; lists take one item or a JSON array, and \"\" clears the key")]
+        value: String,
+        #[arg(long, help = "Accept a link path that doesn't exist yet (the link becomes pending)")]
+        allow_missing: bool,
+    },
+    #[command(about = "List the copies of the configuration kept from before each save")]
+    Backups,
+    #[command(about = "Roll the configuration back to a backup")]
+    Restore {
+        #[arg(help = "Timestamp from 'spine config backups', or 'latest'")]
+        backup: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CompletionsAction {
+    #[command(about = "Install the completion script where your shell loads it automatically")]
+    Install {
+        #[arg(long, help = "Shell to install completions for (auto-detected if not specified)")]
+        shell: Option<clap_complete::Shell>,
+        #[arg(long, conflicts_with = "user", help = "Install for all users (usually needs sudo)")]
+        system: bool,
+        #[arg(long, help = "Install for the current user (default)")]
+        user: bool,
+        #[arg(long, help = "Remove a previously installed script")]
+        uninstall: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GroupAction {
+    #[command(about = "Add packages to a group, creating it if needed")]
+    Add {
+        #[arg(help = "Group name")]
+        group: String,
+        #[arg(help = "Package name(s)", required = true, num_args = 1.., value_hint = ValueHint::Other)]
+        packages: Vec<String>,
+    },
+    #[command(about = "Remove packages from a group, or the whole group if none are given")]
+    Remove {
+        #[arg(help = "Group name")]
+        group: String,
+        #[arg(help = "Package name(s)", value_hint = ValueHint::Other)]
+        packages: Vec<String>,
+    },
+    #[command(about = "List groups and their packages")]
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum UsageAction {
+    #[command(about = "Runs, failures and average duration per command")]
+    Report {
+        #[arg(long, help = "Output in JSON format")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    #[command(about = "Create an empty profile")]
+    Create {
+        #[arg(help = "Profile name")]
+        name: String,
+    },
+    #[command(about = "Switch to a profile (\"default\" is config.toml's own links)")]
+    Use {
+        #[arg(help = "Profile name")]
+        name: String,
+    },
+    #[command(about = "List profiles and show the active one")]
+    List,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     #[command(about = "Launch interactive configuration interface")]
-    Interactive,
+    Interactive {
+        #[arg(long, help = "Line-based prompts instead of the full-screen interface (for screen readers)")]
+        simple: bool,
+    },
     #[command(about = "List current package links")]
-    List,
+    List {
+        #[arg(long, help = "Also show each package's link hooks")]
+        detailed: bool,
+        #[arg(long = "tag", value_name = "TAG", help = "Only packages with this tag (repeat for any of several)")]
+        tags: Vec<String>,
+    },
     #[command(about = "Add a new package link")]
     Add {
         #[arg(help = "Package name (auto-detected from package.json if not provided)")]
         package: Option<String>,
         #[arg(help = "Local path to package (defaults to current directory)")]
         path: Option<String>,
+        #[arg(long, help = "Record the link as pending if the path doesn't exist yet (e.g. dist before the first build)")]
+        allow_missing: bool,
+        #[arg(long = "tag", value_name = "TAG", help = "Tag the link (repeatable)")]
+        tags: Vec<String>,
+        #[arg(long, help = "Why the link exists, shown by list --detailed and info")]
+        note: Option<String>,
+        #[arg(long, help = "Add the path as this named variant of the package, e.g. a compatibility branch's checkout")]
+        variant: Option<String>,
+        #[arg(long = "default", requires = "variant", help = "Make the variant the one projects get unless they choose another")]
+        make_default: bool,
     },
     #[command(about = "Remove a package link")]
     Remove {
         #[arg(help = "Package name", value_hint = ValueHint::Other)]
         package: String,
     },
+    #[command(about = "Rename a package link, keeping its linked projects")]
+    Rename {
+        #[arg(help = "Current name", value_hint = ValueHint::Other)]
+        old: String,
+        #[arg(help = "New name")]
+        new: String,
+    },
+    #[command(about = "Point a package link at another directory, keeping its linked projects")]
+    SetPath {
+        #[arg(help = "Package name", value_hint = ValueHint::Other)]
+        package: String,
+        #[arg(help = "New package directory", value_hint = ValueHint::DirPath)]
+        path: String,
+    },
+    #[command(about = "Add tags to a package link")]
+    Tag {
+        #[arg(help = "Package name", value_hint = ValueHint::Other)]
+        package: String,
+        #[arg(help = "Tag(s)", required = true, num_args = 1..)]
+        tags: Vec<String>,
+    },
+    #[command(about = "Show, set or clear the note on a package link")]
+    Note {
+        #[arg(help = "Package name", value_hint = ValueHint::Other)]
+        package: String,
+        #[arg(help = "New note (omit to show the current one)")]
+        note: Option<String>,
+        #[arg(long, conflicts_with = "note", help = "Remove the note")]
+        clear: bool,
+    },
+    #[command(about = "Remove tags from a package link, or all of them if none are given")]
+    Untag {
+        #[arg(help = "Package name", value_hint = ValueHint::Other)]
+        package: String,
+        #[arg(help = "Tag(s)")]
+        tags: Vec<String>,
+    },
     #[command(about = "Link all configured packages to current project")]
-    LinkAll,
+    LinkAll {
+        #[arg(long, value_enum, help = "Package manager to link with (defaults to the configured package_manager, then lockfile detection)")]
+        pm: Option<PackageManager>,
+        #[arg(long, help = "Show what would be linked without running the package manager or changing the config")]
+        dry_run: bool,
+        #[arg(long, help = "Exit with an error if any package still needs linking (implies --dry-run)")]
+        check: bool,
+        #[arg(long, help = "Stop at the first package that fails to link instead of continuing")]
+        fail_fast: bool,
+        #[arg(long, help = "Project to link into instead of the current directory", value_hint = ValueHint::DirPath)]
+        to: Option<PathBuf>,
+        #[arg(long = "tag", value_name = "TAG", help = "Only link packages with this tag (repeat for any of several)")]
+        tags: Vec<String>,
+    },
     #[command(about = "Link specific package to current project")]
     Link {
-        #[arg(help = "Package name", value_hint = ValueHint::Other)]
-        package: String,
+        #[arg(help = "Package name(s)", required_unless_present = "group", num_args = 1.., value_hint = ValueHint::Other)]
+        packages: Vec<String>,
+        #[arg(long, help = "Link every package in this group")]
+        group: Option<String>,
+        #[arg(long, help = "Link through an override in package.json so installs don't undo it")]
+        durable: bool,
+        #[arg(long, requires = "durable", help = "Show the package.json change without writing it or installing")]
+        dry_run: bool,
+        #[arg(long, value_enum, help = "Package manager to link with (defaults to the configured package_manager, then lockfile detection)")]
+        pm: Option<PackageManager>,
+        #[arg(long, help = "Refuse to link a package whose @angular/* peer dependencies this project doesn't satisfy")]
+        strict_peers: bool,
+        #[arg(short, long, requires = "group", help = "Link the group without asking to confirm the plan")]
+        yes: bool,
+        #[arg(long, requires = "group", conflicts_with = "dry_run", help = "Print the group's plan as JSON and exit without linking")]
+        plan: bool,
+        #[arg(long, help = "Project to link into instead of the current directory", value_hint = ValueHint::DirPath)]
+        to: Option<PathBuf>,
+        #[arg(long, help = "Also link the configured packages these depend on, dependencies first")]
+        with_deps: bool,
+        #[arg(long, conflicts_with = "with_deps", help = "Link this variant of the package(s); kept for later links and syncs in this project")]
+        variant: Option<String>,
+    },
+    #[command(about = "Unlink and link packages again (e.g. after npm install replaced the symlinks)")]
+    Relink {
+        #[arg(help = "Package name", required_unless_present = "all", conflicts_with = "all", value_hint = ValueHint::Other)]
+        package: Option<String>,
+        #[arg(long, help = "Relink every package configured for the current project")]
+        all: bool,
+        #[arg(long, value_enum, help = "Package manager to link with (defaults to the configured package_manager, then lockfile detection)")]
+        pm: Option<PackageManager>,
     },
     #[command(about = "Show npm link status for current project")]
     Status {
@@ -50,30 +279,178 @@ pub enum Commands {
         health: bool,
         #[arg(long, help = "Output in JSON format for scripts/CI")]
         json: bool,
+        #[arg(long, help = "Inspect this project directory instead of the current one", value_hint = ValueHint::DirPath)]
+        project: Option<PathBuf>,
+        #[arg(long, conflicts_with_all = ["detailed", "health"], help = "Show what 'spine sync' would do here, without changing anything")]
+        sync_preview: bool,
+        #[arg(long, value_name = "PATH", help = "Also write the results to this file, e.g. as a CI artifact")]
+        report: Option<PathBuf>,
+        #[arg(long, value_enum, default_value = "json", requires = "report", help = "Format of the --report file")]
+        report_format: crate::report::ReportFormat,
+        #[arg(long = "tag", value_name = "TAG", help = "Only packages with this tag (repeat for any of several)")]
+        tags: Vec<String>,
     },
-    #[command(about = "Unlink specific package from current project")]
-    Unlink {
+    #[command(about = "Show configuration and registry details for a package")]
+    Info {
         #[arg(help = "Package name", value_hint = ValueHint::Other)]
         package: String,
     },
+    #[command(about = "Unlink specific package from current project")]
+    Unlink {
+        #[arg(help = "Package name(s)", required_unless_present = "group", num_args = 1.., value_hint = ValueHint::Other)]
+        packages: Vec<String>,
+        #[arg(long, help = "Unlink every package in this group")]
+        group: Option<String>,
+        #[arg(long, help = "Only forget this project in the config so sync stops restoring it; node_modules is left untouched")]
+        forget: bool,
+        #[arg(long, conflicts_with = "forget", help = "Reinstall the version range this project's package.json declares after unlinking")]
+        restore: bool,
+        #[arg(long, value_enum, help = "Package manager to unlink with (defaults to the configured package_manager, then lockfile detection)")]
+        pm: Option<PackageManager>,
+        #[arg(short, long, requires = "group", help = "Unlink the group without asking to confirm the plan")]
+        yes: bool,
+        #[arg(long, requires = "group", help = "Print the group's plan as JSON and exit without unlinking")]
+        plan: bool,
+        #[arg(long, help = "Project to unlink from instead of the current directory", value_hint = ValueHint::DirPath)]
+        to: Option<PathBuf>,
+        #[arg(long, help = "Also unlink the dependencies 'link --with-deps' pulled in, unless another linked package still needs them")]
+        with_deps: bool,
+    },
     #[command(about = "Unlink all packages from current project")]
-    UnlinkAll,
+    UnlinkAll {
+        #[arg(long, value_enum, help = "Package manager to unlink with (defaults to the configured package_manager, then lockfile detection)")]
+        pm: Option<PackageManager>,
+        #[arg(long, help = "Show what would be unlinked without running the package manager or changing the config")]
+        dry_run: bool,
+        #[arg(long, help = "Exit with an error if any package would be unlinked (implies --dry-run)")]
+        check: bool,
+        #[arg(long, help = "Reinstall the version ranges this project's package.json declares after unlinking")]
+        restore: bool,
+        #[arg(long, help = "Stop at the first package that fails to unlink instead of continuing")]
+        fail_fast: bool,
+        #[arg(short, long, help = "Unlink without asking to confirm the plan")]
+        yes: bool,
+        #[arg(long, conflicts_with_all = ["dry_run", "check"], help = "Print the plan as JSON and exit without changing anything")]
+        plan: bool,
+    },
+    #[command(about = "Re-read every package.json and update the stored versions")]
+    RefreshVersions,
+    #[command(about = "Answer JSON requests on stdin for editor integrations (see the README's JSON API section)")]
+    ServeApi {
+        #[arg(long, required = true, help = "Read newline-delimited JSON requests on stdin and write responses on stdout")]
+        stdio: bool,
+    },
     #[command(about = "Verify and clean up broken package links")]
-    Verify,
+    Verify {
+        #[arg(long, help = "Re-read package.json files and update stored versions")]
+        refresh_versions: bool,
+        #[arg(long, help = "Exit with an error if any stored metadata needed refreshing (implies --refresh-versions)")]
+        strict: bool,
+        #[arg(long, help = "Relink missing or broken links into their projects instead of only pruning the config")]
+        fix: bool,
+        #[arg(long, conflicts_with = "fix", help = "Output what was found and removed as JSON")]
+        json: bool,
+        #[arg(long, conflicts_with = "fix", help = "Only report; exit with an error if verify would change anything")]
+        check: bool,
+    },
+    #[command(about = "List symlinked packages in this project that Spine doesn't manage")]
+    Prune {
+        #[arg(long, conflicts_with = "adopt", help = "Unlink them")]
+        remove: bool,
+        #[arg(long, help = "Add them to the configuration instead")]
+        adopt: bool,
+        #[arg(long, conflicts_with_all = ["remove", "adopt"], help = "Instead forget recorded projects that no longer exist or match projects.never_record, in every package")]
+        projects: bool,
+        #[arg(short, long, help = "Don't ask to confirm the plan")]
+        yes: bool,
+        #[arg(long, help = "Print the plan as JSON and exit without changing anything (with --remove or --adopt)")]
+        plan: bool,
+    },
+    #[command(about = "Create .spine.toml in this project, with a JSON Schema for editors")]
+    Init,
+    #[command(about = "Clone, build and link a package whose path is missing on this machine")]
+    Setup {
+        #[arg(help = "Package name", value_hint = ValueHint::Other)]
+        package: String,
+        #[arg(long, help = "Clone under this directory instead of setup.source_root (default ~/src)", value_hint = ValueHint::DirPath)]
+        source_root: Option<PathBuf>,
+        #[arg(long, help = "Show the clone, build and link steps without running them")]
+        dry_run: bool,
+        #[arg(short, long, help = "Run the steps without asking")]
+        yes: bool,
+        #[arg(long, value_enum, help = "Package manager to install, build and link with (detected per directory by default)")]
+        pm: Option<PackageManager>,
+    },
+    #[command(about = "Write the configured links to a manifest the team can apply")]
+    ExportLinks {
+        #[arg(help = "Manifest file; JSON if it ends in .json, TOML otherwise", value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+        #[arg(long, help = "Directory paths are written relative to (defaults to the manifest's directory)", value_hint = ValueHint::DirPath)]
+        root: Option<PathBuf>,
+    },
+    #[command(about = "Add and link the packages listed in a link manifest")]
+    Apply {
+        #[arg(help = "Manifest written by 'spine export-links'", value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+        #[arg(long, help = "Resolve the manifest's paths against this directory instead of its recorded root", value_hint = ValueHint::DirPath)]
+        root: Option<PathBuf>,
+        #[arg(long, help = "Replace configured packages whose path differs from the manifest")]
+        force: bool,
+        #[arg(long, value_enum, help = "Package manager to link with (defaults to the configured package_manager, then lockfile detection)")]
+        pm: Option<PackageManager>,
+    },
     #[command(about = "Scan for local packages in workspace")]
     Scan {
         #[arg(long, help = "Automatically add discovered packages")]
         add: bool,
         #[arg(long, help = "Search path (defaults to current directory)")]
         path: Option<String>,
+        #[arg(short, long, requires = "add", help = "Add without asking to confirm the plan")]
+        yes: bool,
+        #[arg(long, help = "Also scan directories that .gitignore and .ignore files exclude")]
+        no_ignore: bool,
+        #[arg(long, value_name = "N", help = "Levels below the search path to descend into (default 5)")]
+        depth: Option<usize>,
+    },
+    #[command(about = "Find local packages this project depends on, and optionally add and link them")]
+    Suggest {
+        #[arg(long, help = "Add the suggested packages to the configuration")]
+        add: bool,
+        #[arg(long, help = "Add them and link every suggested package into this project")]
+        link: bool,
+        #[arg(short, long, help = "Add without asking to confirm the plan")]
+        yes: bool,
+        #[arg(long, value_enum, requires = "link", help = "Package manager to link with (defaults to the configured package_manager, then lockfile detection)")]
+        pm: Option<PackageManager>,
+        #[arg(long, help = "Search path (defaults to the scan roots, then the current directory)")]
+        path: Option<String>,
+        #[arg(long, help = "Also scan directories that .gitignore and .ignore files exclude")]
+        no_ignore: bool,
+        #[arg(long, value_name = "N", help = "Levels below the search path to descend into (default 5)")]
+        depth: Option<usize>,
     },
     #[command(about = "Restore package links according to Spine configuration (useful after npm install)")]
-    Sync,
+    Sync {
+        #[arg(long, help = "Show what would be restored without running the package manager or changing the config")]
+        dry_run: bool,
+        #[arg(long, help = "Exit with an error if sync would change anything (implies --dry-run)")]
+        check: bool,
+        #[arg(long, help = "Output the sync report as JSON")]
+        json: bool,
+        #[arg(long, conflicts_with_all = ["dry_run", "check", "json"], help = "Keep running and restore links whenever an install removes them")]
+        watch: bool,
+        #[arg(long, default_value_t = 3, requires = "watch", help = "Seconds the project must be quiet before restoring (with --watch)")]
+        debounce: u64,
+        #[arg(short, long, help = "Make the changes without asking to confirm the plan")]
+        yes: bool,
+        #[arg(long, conflicts_with_all = ["dry_run", "check", "json", "watch"], help = "Print the plan as JSON and exit without changing anything")]
+        plan: bool,
+    },
     #[command(about = "Open configuration file in editor")]
     ConfigEdit,
     #[command(about = "Build Angular libraries")]
     Build {
-        #[arg(help = "Library name to build (optional)")]
+        #[arg(help = "Library or application name to build (optional)")]
         library: Option<String>,
         #[arg(long, help = "Build all linked libraries")]
         all: bool,
@@ -81,6 +458,16 @@ pub enum Commands {
         watch: bool,
         #[arg(long, help = "Build only affected libraries")]
         affected: bool,
+        #[arg(long = "type", value_enum, conflicts_with = "affected", help = "Project kind to build: lib (default for --all), app, or any")]
+        kind: Option<crate::angular::ProjectKind>,
+        #[arg(long, help = "Angular build configuration (default: production)")]
+        configuration: Option<String>,
+        #[arg(long, requires = "all", help = "Stop at the first failed build instead of continuing")]
+        fail_fast: bool,
+        #[arg(long, value_name = "PATH", help = "Also write the results to this file, e.g. as a CI artifact")]
+        report: Option<PathBuf>,
+        #[arg(long, value_enum, default_value = "json", requires = "report", help = "Format of the --report file")]
+        report_format: crate::report::ReportFormat,
     },
     #[command(about = "Generate shell completion scripts")]
     GenerateCompletion {
@@ -96,6 +483,11 @@ pub enum Commands {
     },
     #[command(about = "Disable automatic completion script regeneration")]
     DisableAutoCompletion,
+    #[command(about = "Install or remove shell completion scripts")]
+    Completions {
+        #[command(subcommand)]
+        action: CompletionsAction,
+    },
     #[command(about = "Angular CLI integration commands")]
     Ng {
         #[command(subcommand)]
@@ -114,6 +506,20 @@ pub enum Commands {
         port: Option<u16>,
         #[arg(long, help = "Enable Hot Module Replacement")]
         hmr: bool,
+        #[arg(long, help = "Host to listen on (defaults to angular.json, then 0.0.0.0)")]
+        host: Option<String>,
+        #[arg(long, help = "Serve over HTTPS")]
+        ssl: bool,
+        #[arg(long, value_name = "PATH", help = "SSL certificate to use for serving HTTPS")]
+        ssl_cert: Option<String>,
+        #[arg(long, value_name = "PATH", help = "SSL key to use for serving HTTPS")]
+        ssl_key: Option<String>,
+        #[arg(long, value_name = "URL", help = "URL the browser should use to reach the dev server (e.g. behind a proxy)")]
+        public_host: Option<String>,
+        #[arg(long, requires = "with_libs", value_hint = ValueHint::DirPath, help = "Serve the application in this project while watching libraries from their own workspaces")]
+        app_dir: Option<PathBuf>,
+        #[arg(long, value_name = "SECS", default_value_t = 300, requires = "with_libs", help = "Seconds to wait for the app to compile and answer before failing; 0 doesn't wait")]
+        wait_timeout: u64,
         #[arg(help = "Application project to serve (auto-detected if not specified)")]
         project: Option<String>,
     },
@@ -126,15 +532,122 @@ pub enum Commands {
     },
     #[command(about = "Build and publish a package to npm")]
     Publish {
-        #[arg(help = "Package name to build and publish")]
-        package: String,
+        #[arg(help = "Package name to build and publish", required_unless_present = "all", conflicts_with = "all")]
+        package: Option<String>,
+        #[arg(long, help = "Publish every linked library of the current workspace, dependencies first")]
+        all: bool,
+        #[arg(long, value_enum, requires = "all", help = "Bump every library's version in its source package.json before publishing")]
+        bump: Option<crate::release::Bump>,
+        #[arg(long, requires = "all", help = "Rewrite ranges between the libraries that don't admit the planned versions, instead of failing")]
+        update_ranges: bool,
         #[arg(long, help = "Skip build step and publish directly")]
         skip_build: bool,
         #[arg(long, help = "Dry run - show what would be published without actually publishing")]
         dry_run: bool,
+        #[arg(long, help = "Refuse to publish if the library's source has uncommitted or untracked files")]
+        require_clean: bool,
+        #[arg(long, help = "Publish even with uncommitted changes (overrides --require-clean); the release is recorded as dirty")]
+        allow_dirty: bool,
+    },
+    #[command(about = "Show the tools, files, registries and caches Spine resolves, and where each came from")]
+    Env {
+        #[arg(long, help = "Output in JSON format")]
+        json: bool,
+    },
+    #[command(about = "Build a package, relink it into a consuming project and compile that project")]
+    Smoke {
+        #[arg(help = "Package name", value_hint = ValueHint::Other)]
+        package: String,
+        #[arg(long, help = "Consuming project to compile (default: the one the package is linked into)", value_hint = ValueHint::DirPath)]
+        app: Option<PathBuf>,
+        #[arg(long, help = "Print the result as JSON; stage output goes to stderr")]
+        json: bool,
+    },
+    #[command(about = "Show build history for Angular libraries")]
+    History {
+        #[arg(help = "Library name to show history for (optional)")]
+        package: Option<String>,
+        #[arg(long, help = "Show bundle size trend for the library")]
+        sizes: bool,
+    },
+    #[command(about = "Summarize licenses and metadata of linked packages")]
+    Licenses {
+        #[arg(long, help = "Output in JSON format")]
+        json: bool,
+        #[arg(long, help = "Include each package's direct dependencies")]
+        deep: bool,
+    },
+    #[command(about = "Show what changed in a linked package since it was last linked or built")]
+    Diff {
+        #[arg(help = "Package name", value_hint = ValueHint::Other)]
+        package: String,
+        #[arg(long, help = "Snapshot to compare against (1 = latest, 2 = the one before, ...) or a source git sha")]
+        baseline: Option<String>,
+        #[arg(long, help = "Output in JSON format")]
+        json: bool,
+    },
+    #[command(about = "Compare linked packages with the versions their registry publishes")]
+    AuditLinks {
+        #[arg(help = "Packages to audit (default: every package linked somewhere)", value_hint = ValueHint::Other)]
+        packages: Vec<String>,
+        #[arg(long, help = "Output in JSON format")]
+        json: bool,
+        #[arg(long, help = "Fail when a package differs from its published tarball or can't be compared")]
+        fail_on_divergence: bool,
+    },
+    #[command(about = "Inspect and manage the Spine configuration")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    #[command(about = "Summarize the local usage log (see the usage_log setting)")]
+    Usage {
+        #[command(subcommand)]
+        action: UsageAction,
+    },
+    #[command(about = "Manage named groups of packages")]
+    Group {
+        #[command(subcommand)]
+        action: GroupAction,
+    },
+    #[command(about = "Switch between separate sets of links (SPINE_PROFILE selects one per shell)")]
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    #[command(about = "Check the environment and stored configuration for problems")]
+    Doctor {
+        #[arg(long, help = "Rewrite fixable problems in the configuration")]
+        fix: bool,
+        #[arg(long, value_name = "PATH", help = "Also write the results to this file, e.g. as a CI artifact")]
+        report: Option<PathBuf>,
+        #[arg(long, value_enum, default_value = "json", requires = "report", help = "Format of the --report file")]
+        report_format: crate::report::ReportFormat,
+    },
+    #[command(about = "Watch link health in the background and report changes")]
+    Monitor {
+        #[arg(long, default_value_t = 60, help = "Seconds between checks")]
+        interval: u64,
+        #[arg(long, help = "Send a desktop notification for new problems")]
+        notify: bool,
+        #[arg(long, help = "Check once against the last saved state and exit (non-zero if new problems appeared, or any on the first run)")]
+        once: bool,
+        #[arg(long, help = "Only report through the exit code and notifications")]
+        quiet: bool,
+    },
+    #[command(about = "Print link counts for the current project, fast enough for a shell prompt", after_help = PROMPT_STATUS_EXAMPLES)]
+    PromptStatus {
+        #[arg(long, value_enum, default_value = "text", help = "Output format")]
+        format: PromptFormat,
+        #[arg(long, conflicts_with = "format", help = "Print '<linked> <broken>'; this format will never change")]
+        porcelain: bool,
     },
     #[command(hide = true)]
     ListPackagesForCompletion,
+    #[command(hide = true)]
+    ListPackagePathsForCompletion,
+    #[command(hide = true)]
+    ListGroupsForCompletion,
     
     // Command aliases for better UX
     #[command(about = "Alias for 'serve'")]
@@ -145,17 +658,42 @@ pub enum Commands {
         port: Option<u16>,
         #[arg(long, help = "Enable Hot Module Replacement")]
         hmr: bool,
+        #[arg(long, help = "Host to listen on (defaults to angular.json, then 0.0.0.0)")]
+        host: Option<String>,
+        #[arg(long, help = "Serve over HTTPS")]
+        ssl: bool,
+        #[arg(long, value_name = "PATH", help = "SSL certificate to use for serving HTTPS")]
+        ssl_cert: Option<String>,
+        #[arg(long, value_name = "PATH", help = "SSL key to use for serving HTTPS")]
+        ssl_key: Option<String>,
+        #[arg(long, value_name = "URL", help = "URL the browser should use to reach the dev server (e.g. behind a proxy)")]
+        public_host: Option<String>,
+        #[arg(long, requires = "with_libs", value_hint = ValueHint::DirPath, help = "Serve the application in this project while watching libraries from their own workspaces")]
+        app_dir: Option<PathBuf>,
+        #[arg(long, value_name = "SECS", default_value_t = 300, requires = "with_libs", help = "Seconds to wait for the app to compile and answer before failing; 0 doesn't wait")]
+        wait_timeout: u64,
         #[arg(help = "Application project to serve (auto-detected if not specified)")]
         project: Option<String>,
     },
     #[command(about = "Alias for 'list'")]
-    L,
+    L {
+        #[arg(long, help = "Also show each package's link hooks")]
+        detailed: bool,
+        #[arg(long = "tag", value_name = "TAG", help = "Only packages with this tag (repeat for any of several)")]
+        tags: Vec<String>,
+    },
     #[command(about = "Alias for 'add' with smart defaults")]
     A {
         #[arg(help = "Package name (auto-detected if not provided)")]
         package: Option<String>,
         #[arg(help = "Local path to package (defaults to current directory)")]
         path: Option<String>,
+        #[arg(long, help = "Record the link as pending if the path doesn't exist yet")]
+        allow_missing: bool,
+        #[arg(long = "tag", value_name = "TAG", help = "Tag the link (repeatable)")]
+        tags: Vec<String>,
+        #[arg(long, help = "Why the link exists, shown by list --detailed and info")]
+        note: Option<String>,
     },
     #[command(about = "Alias for 'ng generate'")]
     G {
@@ -187,60 +725,387 @@ pub enum NgCommands {
 
 impl Cli {
     pub fn run(&self) -> Result<()> {
-        let mut config = Config::load_or_create()?;
+        let started = std::time::Instant::now();
+        let result = self.execute();
+        crate::usage::record(started.elapsed(), result.is_ok());
+        if !self.profile {
+            return result;
+        }
+
+        // stderr, so --json output stays parseable
+        let stats = crate::paths::canonicalize_stats();
+        eprintln!("\n⏱️  Profile: finished in {:.1?}", started.elapsed());
+        eprintln!("   canonicalize: {} lookup(s), {} served from cache, {:.1?} in filesystem lookups (~{:.1?} saved)",
+            stats.hits + stats.misses, stats.hits, stats.lookup_time, stats.estimated_savings());
+        result
+    }
+
+    fn execute(&self) -> Result<()> {
+        let config_file = ConfigFile::new(self.config.as_deref());
+        crate::paths::set_full_paths(self.full_paths);
+        crate::progress::configure(self.log_format.unwrap_or_default(), self.no_progress);
+        // Runs on every shell prompt, so skip load warnings and journal recovery
+        if let Some(Commands::PromptStatus { format, porcelain }) = &self.command {
+            return crate::prompt_status::prompt_status_command(&config_file, *format, *porcelain);
+        }
+        // Takes stdout over before anything prints, so load warnings go to stderr
+        if let Some(Commands::ServeApi { .. }) = &self.command {
+            return crate::api::serve_stdio(&config_file);
+        }
+        // Works on config.toml directly, so a missing profile can be fixed
+        if let Some(Commands::Profile { action }) = &self.command {
+            return match action {
+                ProfileAction::Create { name } => crate::profile::create_profile(&config_file, name),
+                ProfileAction::Use { name } => crate::profile::use_profile(&config_file, name),
+                ProfileAction::List => crate::profile::list_profiles(&config_file),
+            };
+        }
+        let mut config = Config::load_or_create(&config_file)?;
+        if !matches!(self.command, Some(Commands::Config { .. })) {
+            config.report_load_warnings();
+        }
+        if crate::journal::recover_interrupted(&mut config)? {
+            config.save()?;
+        }
 
         match &self.command {
-            Some(Commands::Interactive) | None => {
+            Some(Commands::Interactive { simple }) if *simple || crate::simple::preferred(&config) => {
+                crate::simple::SimpleApp::new(config)?.run()?;
+            }
+            None if crate::simple::preferred(&config) => {
+                crate::simple::SimpleApp::new(config)?.run()?;
+            }
+            Some(Commands::Interactive { .. }) | None => {
                 let mut app = TuiApp::new(config)?;
                 app.run()?;
             }
-            Some(Commands::List) => {
-                config.list_links();
+            Some(Commands::List { detailed, tags }) => {
+                config.list_links(*detailed, tags);
             }
-            Some(Commands::Add { package, path }) => {
-                let (detected_package, detected_path) = Self::detect_package_info(package, path)?;
-                config.add_link(detected_package.clone(), detected_path.clone())?;
+            Some(Commands::Add { package, path, allow_missing, tags, note, variant, make_default }) => {
+                let (detected_package, detected_path) = Self::detect_package_info(package, path, *allow_missing)?;
+                if let Some(variant) = variant {
+                    Self::add_variant(&mut config, &detected_package, variant, &detected_path, *allow_missing, *make_default)?;
+                    return Ok(());
+                }
+                config.add_link(detected_package.clone(), detected_path.clone(), *allow_missing)?;
+                config.tag_link(&detected_package, tags)?;
+                if note.is_some() {
+                    config.set_note(&detected_package, note.as_deref())?;
+                }
                 config.save()?;
                 println!("Added link: {} -> {}", detected_package, detected_path);
+                if config.links.get(&detected_package).is_some_and(|link| link.pending) {
+                    println!("⏳ Link is {}; it will be activated once the path exists", crate::config::PENDING_MARKER);
+                }
             }
             Some(Commands::Remove { package }) => {
                 config.remove_link(package)?;
                 config.save()?;
                 println!("Removed link: {}", package);
             }
-            Some(Commands::LinkAll) => {
-                NpmManager::link_all(&mut config)?;
+            Some(Commands::Rename { old, new }) => {
+                let still_linked = config.rename_link(old, new)?;
                 config.save()?;
+                println!("✅ Renamed {} to {}", old, new);
+                if !still_linked.is_empty() {
+                    println!("⚠️  {} is still symlinked under its old name in:", old);
+                    for project in &still_linked {
+                        println!("   • {}", crate::paths::display_path(project));
+                    }
+                    println!("💡 Run 'spine relink {}' in each to link it under the new name", new);
+                }
             }
-            Some(Commands::Link { package }) => {
-                NpmManager::link_package(&mut config, package)?;
+            Some(Commands::SetPath { package, path }) => {
+                config.set_link_path(package, path)?;
                 config.save()?;
+                let link = &config.links[package];
+                println!("✅ {} now points to {} (v{})", package, crate::paths::display_path(&link.path), link.version.as_deref().unwrap_or("unknown"));
+                if let Some(declared) = link.name_mismatch() {
+                    println!("⚠️  Its package.json names it {}, not {}", declared, link.npm_name());
+                }
+                if !link.linked_projects.is_empty() {
+                    println!("💡 Run 'spine relink {}' to point its {} linked project(s) at the new path", package, link.linked_projects.len());
+                }
             }
-            Some(Commands::Status { detailed, health, json }) => {
-                NpmManager::show_enhanced_status(&config, *detailed, *health, *json)?;
+            Some(Commands::Tag { package, tags }) => {
+                let added = config.tag_link(package, tags)?;
+                config.save()?;
+                if added.is_empty() {
+                    println!("✓ {} already has those tags", package);
+                } else {
+                    println!("🏷️  Tagged {}: {}", package, config.links[package].tags.join(", "));
+                }
             }
-            Some(Commands::Unlink { package }) => {
-                NpmManager::unlink_package(&mut config, package)?;
+            Some(Commands::Note { package, note, clear }) => {
+                if !*clear && note.is_none() {
+                    let available: Vec<String> = config.links.keys().cloned().collect();
+                    let link = config.links.get(package)
+                        .ok_or_else(|| crate::error::SpineError::package_not_found_with_suggestions(package, &available))?;
+                    match &link.note {
+                        Some(note) => println!("{}", note),
+                        None => println!("{} has no note", package),
+                    }
+                    return Ok(());
+                }
+                config.set_note(package, note.as_deref())?;
                 config.save()?;
+                match &config.links[package].note {
+                    Some(note) => println!("🗒️  Note on {}: {}", package, note),
+                    None => println!("🗒️  Cleared the note on {}", package),
+                }
             }
-            Some(Commands::UnlinkAll) => {
-                NpmManager::unlink_all(&mut config)?;
+            Some(Commands::Untag { package, tags }) => {
+                let removed = config.untag_link(package, tags)?;
                 config.save()?;
+                let remaining = &config.links[package].tags;
+                if removed.is_empty() {
+                    println!("✓ {} has none of those tags", package);
+                } else if remaining.is_empty() {
+                    println!("🏷️  Removed {} from {}; it has no tags left", removed.join(", "), package);
+                } else {
+                    println!("🏷️  Removed {} from {}; still tagged {}", removed.join(", "), package, remaining.join(", "));
+                }
             }
-            Some(Commands::Verify) => {
-                NpmManager::verify_links(&mut config)?;
+            Some(Commands::LinkAll { pm, dry_run, check, fail_fast, to, tags }) => {
+                let project = Self::target_project(&mut config, to.as_deref())?;
+                let pm = PackageManager::resolve(*pm, config.package_manager, &project);
+                let dry_run = *dry_run || *check;
+                let result = NpmManager::link_all(&mut config, &project, pm, dry_run, *fail_fast, tags);
+                if dry_run {
+                    let count = result?;
+                    if *check && count > 0 {
+                        return Err(anyhow::anyhow!("{} package(s) need linking", count));
+                    }
+                } else {
+                    // Saved by link_all, including the packages linked before a --fail-fast abort
+                    result?;
+                }
             }
-            Some(Commands::Scan { add, path }) => {
-                Scanner::scan_packages(*add, path.as_deref())?;
+            Some(Commands::Link { packages, group, durable, dry_run, pm, strict_peers, yes, plan, to, with_deps, variant }) => {
+                let project = Self::target_project(&mut config, to.as_deref())?;
+                // A pending path that has since been built or cloned can be linked now
+                if *plan {
+                    config.activate_pending();
+                } else {
+                    config.promote_pending()?;
+                }
+                let requested = Self::with_group_members(&config, packages, group.as_deref())?;
+                if let Some(variant) = variant {
+                    for package in &requested {
+                        config.select_variant(package, &project, variant)?;
+                    }
+                }
+                let closure = crate::deps::closure(&config, &requested);
+                if !*plan {
+                    if *with_deps {
+                        closure.print_tree(&requested);
+                        println!();
+                    } else {
+                        Self::hint_with_deps(&config, &project, &closure, &requested);
+                    }
+                    closure.print_unconfigured_hints(&config.file);
+                }
+                let packages = if *with_deps { closure.order.clone() } else { requested.clone() };
+                let action = if *durable { "link (durable)" } else { "link" };
+                if group.is_some() && !*dry_run && !Self::confirm_group_plan(&config, &project, "link", action, &packages, *yes, *plan)? {
+                    return Ok(());
+                }
+                // Dependencies already linked by request aren't recorded as pulled in
+                let explicit: Vec<String> = closure.added(&requested)
+                    .filter(|name| crate::deps::linked_explicitly(&config, &project, name))
+                    .cloned()
+                    .collect();
+                let pm = PackageManager::resolve(*pm, config.package_manager, &project);
+                let result = if *durable {
+                    NpmManager::link_durable(&mut config, &project, &packages, pm, *dry_run, *strict_peers)
+                } else {
+                    NpmManager::link_packages(&mut config, &project, &packages, pm, *strict_peers)
+                };
+                if result.is_ok() && *with_deps && !*dry_run {
+                    for dependency in closure.added(&requested).filter(|name| !explicit.contains(name)) {
+                        let dependents: Vec<String> = closure.edges.iter()
+                            .filter(|(_, to)| to == dependency)
+                            .map(|(from, _)| from.clone())
+                            .collect();
+                        config.add_required_by(dependency, &project, &dependents)?;
+                    }
+                }
+                // Packages linked before a failure are in node_modules already
+                if !*dry_run {
+                    config.save()?;
+                }
+                result?;
             }
-            Some(Commands::Sync) => {
-                Scanner::sync_links()?;
+            Some(Commands::Relink { package, all: _, pm }) => {
+                let pm = PackageManager::resolve(*pm, config.package_manager, &std::env::current_dir()?);
+                let result = NpmManager::relink(&mut config, package.as_deref(), pm);
+                config.save()?;
+                result?;
+            }
+            Some(Commands::Status { detailed, health, json, project, sync_preview, report, report_format, tags }) => {
+                let project = Self::target_project(&mut config, project.as_deref())?;
+                if *sync_preview {
+                    let report = Scanner::analyze_sync(&config, &project, true)?;
+                    if *json {
+                        println!("{}", serde_json::to_string_pretty(&report)?);
+                    } else {
+                        println!("Sync preview for {}:", crate::paths::display_path(&project));
+                        report.print_analysis();
+                        if report.has_work() {
+                            report.print_dry_run(&config);
+                        } else {
+                            println!("\n✅ 'spine sync' has nothing to do here.");
+                        }
+                    }
+                } else {
+                    if !*json {
+                        config.promote_pending()?;
+                    }
+                    config.refresh_linked_projects()?;
+                    let updates = config.sync_tracked_versions();
+                    if !updates.is_empty() {
+                        if !*json {
+                            for update in &updates {
+                                println!("🔄 {} is now v{} (was v{})", update.package, update.current, update.previous.as_deref().unwrap_or("unknown"));
+                            }
+                        }
+                        config.save()?;
+                    }
+                    NpmManager::show_enhanced_status(&config, &project, *detailed, *health, *json, tags)?;
+                }
+                if let Some(target) = crate::report::ReportTarget::from_args(report.as_deref(), *report_format) {
+                    NpmManager::status_report(&config.with_tags(tags), &project).write(&target);
+                }
+            }
+            Some(Commands::Info { package }) => {
+                NpmManager::show_package_info(&config, package)?;
+            }
+            Some(Commands::Unlink { packages, group, forget, restore, pm, yes, plan, to, with_deps }) => {
+                let project = Self::target_project(&mut config, to.as_deref())?;
+                let mut packages = Self::with_group_members(&config, packages, group.as_deref())?;
+                if *with_deps {
+                    let (remove, kept) = crate::deps::unlink_set(&config, &project, &packages);
+                    for (package, needed_by) in &kept {
+                        println!("📌 Keeping {}: {} still needs it", package, needed_by);
+                    }
+                    packages = remove;
+                }
+                let action = if *forget { "forget" } else if *restore { "unlink and restore" } else { "unlink" };
+                if group.is_some() && !Self::confirm_group_plan(&config, &project, "unlink", action, &packages, *yes, *plan)? {
+                    return Ok(());
+                }
+                if *forget {
+                    for package in &packages {
+                        NpmManager::forget_project(&mut config, &project, package)?;
+                    }
+                    config.save()?;
+                } else {
+                    let pm = PackageManager::resolve(*pm, config.package_manager, &project);
+                    // Save even if reinstalling failed, since the unlink itself went through
+                    let result = NpmManager::unlink_packages(&mut config, &project, &packages, pm, *restore);
+                    config.save()?;
+                    result?;
+                }
+            }
+            Some(Commands::UnlinkAll { pm, dry_run, check, restore, fail_fast, yes, plan }) => {
+                let pm = PackageManager::resolve(*pm, config.package_manager, &std::env::current_dir()?);
+                let dry_run = *dry_run || *check;
+                let result = NpmManager::unlink_all(&mut config, pm, dry_run, *restore, *fail_fast, *yes, *plan);
+                if *plan {
+                    result?;
+                } else if dry_run {
+                    let count = result?;
+                    if *check && count > 0 {
+                        return Err(anyhow::anyhow!("{} package(s) would be unlinked", count));
+                    }
+                } else {
+                    // Saved by unlink_all, including the packages unlinked before a --fail-fast abort
+                    result?;
+                }
+            }
+            Some(Commands::RefreshVersions) => {
+                let report = config.refresh_versions();
+                if report.is_clean() {
+                    println!("✓ All stored versions are up to date.");
+                } else {
+                    println!("Refreshed package versions:");
+                    report.print();
+                }
+                if !report.updated.is_empty() {
+                    config.save()?;
+                }
+            }
+            Some(Commands::Verify { refresh_versions, strict, fix, json, check }) => {
+                NpmManager::verify_links(&mut config, *refresh_versions, *strict, *fix, *json, *check)?;
+            }
+            Some(Commands::Prune { projects: true, yes, plan, .. }) => {
+                NpmManager::prune_projects(&mut config, *yes, *plan)?;
+            }
+            Some(Commands::Prune { remove, adopt, yes, plan, .. }) => {
+                NpmManager::prune(&mut config, *remove, *adopt, *yes, *plan)?;
+            }
+            Some(Commands::Init) => {
+                crate::workspace::WorkspaceManager::init_workspace_config()?;
+            }
+            Some(Commands::Setup { package, source_root, dry_run, yes, pm }) => {
+                let package = config.link_name(package);
+                crate::setup::setup_command(&mut config, &package, source_root.as_deref(), *dry_run, *yes, *pm)?;
+            }
+            Some(Commands::ExportLinks { file, root }) => {
+                crate::manifest::export_links(&config, file, root.as_deref())?;
+            }
+            Some(Commands::Apply { file, root, force, pm }) => {
+                crate::manifest::apply_manifest(&mut config, file, root.as_deref(), *force, *pm)?;
+            }
+            Some(Commands::Scan { add, path, yes, no_ignore, depth }) => {
+                Scanner::scan_packages(&config, *add, path.as_deref(), *yes, *no_ignore, *depth)?;
+            }
+            Some(Commands::Suggest { add, link, yes, pm, path, no_ignore, depth }) => {
+                Scanner::suggest_packages(&mut config, crate::scanner::SuggestOptions {
+                    add: *add,
+                    link: *link,
+                    yes: *yes,
+                    pm: *pm,
+                    search_path: path.clone(),
+                    no_ignore: *no_ignore,
+                    depth: *depth,
+                })?;
+            }
+            Some(Commands::Sync { watch: true, debounce, .. }) => {
+                crate::watch::watch_sync(&config.file, std::time::Duration::from_secs(*debounce))?;
+            }
+            Some(Commands::Sync { plan: true, .. }) => {
+                Scanner::sync_plan(&config)?.print_json()?;
+            }
+            Some(Commands::Sync { dry_run, check, json, yes, .. }) => {
+                if !*dry_run && !*check {
+                    config.refresh_linked_projects()?;
+                }
+                let report = Scanner::sync_links(&config.file, *dry_run || *check, *json, *yes)?;
+                if *json {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                }
+                if *check && report.has_work() {
+                    return Err(anyhow::anyhow!("Sync would make {} change(s)", report.change_count()));
+                }
             }
             Some(Commands::ConfigEdit) => {
-                Scanner::open_config_editor()?;
+                Scanner::open_config_editor(&config.file)?;
             }
-            Some(Commands::Build { library, all, watch, affected }) => {
-                crate::angular::build_command(library.clone(), *all, *watch, *affected)?;
+            Some(Commands::Build { library, all, watch, affected, kind, configuration, fail_fast, report, report_format }) => {
+                let report = crate::report::ReportTarget::from_args(report.as_deref(), *report_format);
+                crate::angular::build_command(&config, &std::env::current_dir()?, crate::angular::BuildOptions {
+                    library: library.clone(),
+                    all: *all,
+                    watch: *watch,
+                    affected: *affected,
+                    kind: *kind,
+                    configuration: configuration.clone(),
+                    fail_fast: *fail_fast,
+                    report,
+                })?;
+                config.promote_pending()?;
             }
             Some(Commands::GenerateCompletion { shell }) => {
                 Self::generate_completion(*shell)?;
@@ -252,10 +1117,16 @@ impl Cli {
             Some(Commands::DisableAutoCompletion) => {
                 config.disable_auto_completion()?;
             }
+            Some(Commands::Completions { action }) => match action {
+                CompletionsAction::Install { shell, system, user: _, uninstall } => {
+                    completion::install_completions(&mut config, *shell, *system, *uninstall)?;
+                }
+            },
             Some(Commands::Ng { command }) => {
                 match command {
                     NgCommands::Generate { schematic, name, lib, args } => {
                         crate::angular_cli::ng_generate_command(
+                            &config,
                             schematic,
                             name,
                             lib.as_deref(),
@@ -265,65 +1136,212 @@ impl Cli {
                 }
             }
             Some(Commands::NgProxy { args }) => {
-                crate::angular_cli::ng_proxy_command(args.clone())?;
+                crate::angular_cli::ng_proxy_command(&config, args.clone())?;
             }
-            Some(Commands::Serve { with_libs, port, hmr, project }) => {
+            Some(Commands::Serve { with_libs, port, hmr, host, ssl, ssl_cert, ssl_key, public_host, app_dir, wait_timeout, project }) => {
+                let options = ServeOptions {
+                    port: *port,
+                    hmr: *hmr,
+                    host: host.clone(),
+                    ssl: *ssl,
+                    ssl_cert: ssl_cert.clone(),
+                    ssl_key: ssl_key.clone(),
+                    public_host: public_host.clone(),
+                    wait_timeout_secs: *wait_timeout,
+                };
                 if *with_libs {
-                    crate::angular_cli::serve_with_libs_command(*port, *hmr, project.as_deref())?;
+                    crate::angular_cli::serve_with_libs_command(&config, &options, project.as_deref(), app_dir.as_deref())?;
                 } else {
                     // Regular serve command - just proxy to Angular CLI
                     let mut args = vec!["serve".to_string()];
                     if let Some(p) = port {
                         args.extend(vec!["--port".to_string(), p.to_string()]);
                     }
-                    if *hmr {
-                        args.push("--hmr".to_string());
-                    }
+                    args.extend(options.ng_args());
                     if let Some(proj) = project {
                         args.push(proj.clone());
                     }
-                    crate::angular_cli::ng_proxy_command(args)?;
+                    crate::angular_cli::ng_proxy_command(&config, args)?;
                 }
             }
             Some(Commands::Debug { workspace, libs }) => {
-                crate::angular_cli::debug_command(*workspace, *libs)?;
+                crate::angular_cli::debug_command(&config, *workspace, *libs)?;
+            }
+            Some(Commands::Publish { all: true, bump, update_ranges, skip_build, dry_run, require_clean, allow_dirty, .. }) => {
+                crate::release::publish_all_command(&config, crate::release::PublishAllOptions {
+                    skip_build: *skip_build,
+                    dry_run: *dry_run,
+                    require_clean: *require_clean,
+                    allow_dirty: *allow_dirty,
+                    bump: *bump,
+                    update_ranges: *update_ranges,
+                })?;
+            }
+            Some(Commands::Publish { package, skip_build, dry_run, require_clean, allow_dirty, .. }) => {
+                let package = package.as_deref().unwrap_or_default();
+                crate::angular::publish_command(&config, package, *skip_build, *dry_run, *require_clean, *allow_dirty)?;
+            }
+            Some(Commands::Env { json }) => {
+                crate::env::env_command(&config, *json)?;
+            }
+            Some(Commands::Smoke { package, app, json }) => {
+                crate::smoke::smoke_command(&mut config, package, app.as_deref(), *json)?;
+            }
+            Some(Commands::History { package, sizes }) => {
+                crate::history::history_command(&config.file, package.as_deref(), *sizes)?;
+            }
+            Some(Commands::Licenses { json, deep }) => {
+                crate::licenses::licenses_command(&config, *json, *deep)?;
+            }
+            Some(Commands::Diff { package, baseline, json }) => {
+                crate::diff::diff_command(&config, package, baseline.as_deref(), *json)?;
+            }
+            Some(Commands::AuditLinks { packages, json, fail_on_divergence }) => {
+                crate::audit::audit_links(&config, packages, *json, *fail_on_divergence)?;
+            }
+            Some(Commands::Config { action }) => match action {
+                ConfigAction::Validate { schema: false } => config.validate()?,
+                ConfigAction::Validate { schema: true } => {
+                    let result = config.validate();
+                    let mut problems = 0;
+                    let workspace_config = crate::workspace::WorkspaceManager::workspace_config_path();
+                    let documents = [
+                        (Some(config.file.path()?), crate::schema::SchemaTarget::Config),
+                        (workspace_config.exists().then_some(workspace_config), crate::schema::SchemaTarget::Workspace),
+                    ];
+                    println!("\n🔍 Checking against the JSON Schemas");
+                    for (path, target) in documents {
+                        let Some(path) = path.filter(|p| p.exists()) else { continue };
+                        let errors = crate::schema::check_file(&path, target)?;
+                        if errors.is_empty() {
+                            println!("  ✅ {} matches the {:?} schema", crate::paths::display_path(&path), target);
+                        } else {
+                            println!("  ❌ {} doesn't match the {:?} schema:", crate::paths::display_path(&path), target);
+                            for error in &errors {
+                                println!("    • {}", error);
+                            }
+                        }
+                        problems += errors.len();
+                    }
+                    result?;
+                    if problems > 0 {
+                        return Err(anyhow::anyhow!("{} schema violation(s)", problems));
+                    }
+                }
+                ConfigAction::Schema { target, json_schema } => {
+                    let schema = target.schema();
+                    if *json_schema {
+                        println!("{}", serde_json::to_string_pretty(&schema)?);
+                    } else {
+                        for line in crate::schema::describe(&schema) {
+                            println!("{}", line);
+                        }
+                    }
+                }
+                ConfigAction::Get { key, json } => {
+                    let value = config.get_value(key.as_deref())?;
+                    if *json {
+                        println!("{}", serde_json::to_string_pretty(&value)?);
+                    } else {
+                        println!("{}", crate::config_keys::display(&value));
+                    }
+                }
+                ConfigAction::Set { key, value, allow_missing } => {
+                    config.set_value(key, value, *allow_missing)?;
+                    config.save()?;
+                    println!("✓ Set {}", key);
+                }
+                ConfigAction::Backups => crate::backups::list_command(&config.file)?,
+                ConfigAction::Restore { backup } => crate::backups::restore_command(&config.file, backup)?,
+            },
+            Some(Commands::Usage { action: UsageAction::Report { json } }) => {
+                crate::usage::report_command(&config, *json)?;
             }
-            Some(Commands::Publish { package, skip_build, dry_run }) => {
-                crate::angular::publish_command(&config, package, *skip_build, *dry_run)?;
+            Some(Commands::Group { action }) => match action {
+                GroupAction::Add { group, packages } => {
+                    config.add_to_group(group, packages)?;
+                    config.save()?;
+                    println!("✅ Group '{}': {}", group, config.groups[group].join(", "));
+                }
+                GroupAction::Remove { group, packages } => {
+                    config.remove_from_group(group, packages)?;
+                    config.save()?;
+                    match config.groups.get(group) {
+                        Some(members) => println!("✅ Group '{}': {}", group, members.join(", ")),
+                        None => println!("🗑️  Removed group '{}'", group),
+                    }
+                }
+                GroupAction::List => config.list_groups(),
+            },
+            Some(Commands::Doctor { fix, report, report_format }) => {
+                let report = crate::report::ReportTarget::from_args(report.as_deref(), *report_format);
+                let result = crate::doctor::doctor_command(&mut config, *fix, report.as_ref());
+                if *fix {
+                    config.save()?;
+                }
+                result?;
+            }
+            Some(Commands::Monitor { interval, notify, once, quiet }) => {
+                crate::monitor::monitor_command(&config, *interval, *notify, *once, *quiet)?;
+            }
+            Some(Commands::PromptStatus { .. }) | Some(Commands::Profile { .. }) | Some(Commands::ServeApi { .. }) => {
+                // Handled before the config is loaded
             }
             Some(Commands::ListPackagesForCompletion) => {
-                completion::list_packages_for_completion()?;
+                completion::list_packages_for_completion(&config)?;
+            }
+            Some(Commands::ListPackagePathsForCompletion) => {
+                completion::list_package_paths_for_completion(&config)?;
+            }
+            Some(Commands::ListGroupsForCompletion) => {
+                completion::list_groups_for_completion(&config)?;
             }
             
             // Handle aliases
-            Some(Commands::S { with_libs, port, hmr, project }) => {
+            Some(Commands::S { with_libs, port, hmr, host, ssl, ssl_cert, ssl_key, public_host, app_dir, wait_timeout, project }) => {
+                let options = ServeOptions {
+                    port: *port,
+                    hmr: *hmr,
+                    host: host.clone(),
+                    ssl: *ssl,
+                    ssl_cert: ssl_cert.clone(),
+                    ssl_key: ssl_key.clone(),
+                    public_host: public_host.clone(),
+                    wait_timeout_secs: *wait_timeout,
+                };
                 if *with_libs {
-                    crate::angular_cli::serve_with_libs_command(*port, *hmr, project.as_deref())?;
+                    crate::angular_cli::serve_with_libs_command(&config, &options, project.as_deref(), app_dir.as_deref())?;
                 } else {
                     let mut args = vec!["serve".to_string()];
                     if let Some(p) = port {
                         args.extend(vec!["--port".to_string(), p.to_string()]);
                     }
-                    if *hmr {
-                        args.push("--hmr".to_string());
-                    }
+                    args.extend(options.ng_args());
                     if let Some(proj) = project {
                         args.push(proj.clone());
                     }
-                    crate::angular_cli::ng_proxy_command(args)?;
+                    crate::angular_cli::ng_proxy_command(&config, args)?;
                 }
             }
-            Some(Commands::L) => {
-                config.list_links();
+            Some(Commands::L { detailed, tags }) => {
+                config.list_links(*detailed, tags);
             }
-            Some(Commands::A { package, path }) => {
-                let (detected_package, detected_path) = Self::detect_package_info(package, path)?;
-                config.add_link(detected_package.clone(), detected_path.clone())?;
+            Some(Commands::A { package, path, allow_missing, tags, note }) => {
+                let (detected_package, detected_path) = Self::detect_package_info(package, path, *allow_missing)?;
+                config.add_link(detected_package.clone(), detected_path.clone(), *allow_missing)?;
+                config.tag_link(&detected_package, tags)?;
+                if note.is_some() {
+                    config.set_note(&detected_package, note.as_deref())?;
+                }
                 config.save()?;
                 println!("Added link: {} -> {}", detected_package, detected_path);
+                if config.links.get(&detected_package).is_some_and(|link| link.pending) {
+                    println!("⏳ Link is {}; it will be activated once the path exists", crate::config::PENDING_MARKER);
+                }
             }
             Some(Commands::G { schematic, name, lib, args }) => {
                 crate::angular_cli::ng_generate_command(
+                    &config,
                     schematic,
                     name,
                     lib.as_deref(),
@@ -335,18 +1353,138 @@ impl Cli {
         Ok(())
     }
 
-    fn detect_package_info(package: &Option<String>, path: &Option<String>) -> Result<(String, String)> {
+    /// Package names from the command line followed by the members of `--group`
+    fn with_group_members(config: &Config, packages: &[String], group: Option<&str>) -> Result<Vec<String>> {
+        let mut all: Vec<String> = packages.iter().map(|p| config.link_name(p)).collect();
+        if let Some(group) = group {
+            for member in config.expand_group(group)? {
+                if !all.contains(&member) {
+                    all.push(member);
+                }
+            }
+        }
+        Ok(all)
+    }
+
+    /// The project a command works on: `--to`'s (or `--project`'s), whose
+    /// .spine.toml link overrides then replace the current directory's, or
+    /// else the current directory. The package manager runs there and the
+    /// config records it as the linked project.
+    fn target_project(config: &mut Config, to: Option<&std::path::Path>) -> Result<std::path::PathBuf> {
+        match to {
+            Some(to) => {
+                let project = NpmManager::resolve_project_dir(to)?;
+                config.use_workspace_overrides_of(&project);
+                Ok(project)
+            }
+            None => Ok(std::env::current_dir()?),
+        }
+    }
+
+    /// Point out configured dependencies of the requested packages that aren't
+    /// linked in `project`, which `--with-deps` would have included
+    fn hint_with_deps(config: &Config, project: &std::path::Path, closure: &crate::deps::Closure, requested: &[String]) {
+        for dependency in closure.added(requested) {
+            let linked = config.links.get(dependency)
+                .is_some_and(|link| link.linked_projects.iter().any(|p| crate::paths::same_path(p, project)));
+            if !linked {
+                let needed_by = closure.edges.iter().find(|(_, to)| to == dependency).map(|(from, _)| from.as_str()).unwrap_or_default();
+                println!("💡 {} depends on {}, which Spine also manages; add --with-deps to link it too", needed_by, dependency);
+            }
+        }
+    }
+
+    /// Show what a `--group` operation will do to each member and ask to go
+    /// ahead; with `plan_only` print the plan as JSON instead. Returns whether
+    /// to continue.
+    fn confirm_group_plan(config: &Config, project: &std::path::Path, operation: &str, action: &str, packages: &[String], yes: bool, plan_only: bool) -> Result<bool> {
+        let mut plan = crate::plan::Plan::new(operation, Some(project.to_path_buf()));
+        for package in packages {
+            let path = config.links.get(package).map(|link| crate::paths::display_path(&link.for_project(project).path));
+            plan.push(action, package, path);
+        }
+        if plan_only {
+            plan.print_json()?;
+            return Ok(false);
+        }
+        if !plan.confirm(yes) {
+            println!("Cancelled; nothing was changed.");
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// `spine add --variant`: another checkout of a package, or its first
+    fn add_variant(config: &mut Config, package: &str, variant: &str, path: &str, allow_missing: bool, make_default: bool) -> Result<()> {
+        let unnamed = config.links.get(package).is_some_and(|link| link.default_variant.is_none());
+        config.add_variant(package.to_string(), variant.to_string(), path.to_string(), allow_missing)?;
+        if make_default {
+            config.set_default_variant(package, variant)?;
+        }
+        config.save()?;
+
+        let link = &config.links[package];
+        let variant_path = link.with_variant(variant).map(|chosen| chosen.path).unwrap_or_default();
+        println!("Added variant {} of {} -> {}", variant, package, crate::paths::display_path(&variant_path));
+        if unnamed && link.default_variant.as_deref() == Some(crate::config::DEFAULT_VARIANT) {
+            println!("   Its existing path {} is now the variant '{}'", crate::paths::display_path(&link.path), crate::config::DEFAULT_VARIANT);
+        }
+        if let Some(default) = &link.default_variant {
+            println!("   Default variant: {}. Link another with 'spine link {} --variant <name>'", default, package);
+        }
+        Ok(())
+    }
+
+    /// Warn when an explicit name differs from the package.json `name` and offer
+    /// to use the real one. Keeping the given name makes it an alias.
+    fn confirm_package_name(package: &str, package_json_path: &std::path::Path) -> String {
+        let declared = match crate::package::get_package_name(package_json_path) {
+            Ok(declared) if declared != package => declared,
+            _ => return package.to_string(),
+        };
+
+        println!("⚠️  {} is published as {} in its package.json", package, declared);
+        if crate::prompt::confirm(&format!("Add it as {} instead?", declared), false) {
+            declared
+        } else {
+            println!("   Keeping {} as an alias; npm operations will use {}", package, declared);
+            package.to_string()
+        }
+    }
+
+    fn detect_package_info(package: &Option<String>, path: &Option<String>, allow_missing: bool) -> Result<(String, String)> {
         let detected_path = path.as_deref().unwrap_or(".").to_string();
-        let path_buf = std::path::PathBuf::from(&detected_path);
+        // `~` and environment variables are kept as typed; add_link expands them
+        let portable = crate::paths::uses_expansion(&detected_path);
+        let path_buf = crate::paths::normalize_path(std::path::Path::new(&crate::paths::expand_path(&detected_path)));
         
-        // Ensure the path exists
+        // Ensure the path exists, unless the link is being recorded as pending
         if !path_buf.exists() {
-            return Err(anyhow::anyhow!("Path does not exist: {}", detected_path));
+            if !allow_missing {
+                return Err(anyhow::anyhow!("Path does not exist: {} (use --allow-missing to record it as pending)", detected_path));
+            }
+            let package = package.clone().ok_or_else(|| anyhow::anyhow!(
+                "Package name is required when the path does not exist yet: {}", detected_path
+            ))?;
+            if portable {
+                return Ok((package, detected_path));
+            }
+            let absolute_path = crate::paths::normalize_path(&std::env::current_dir()?.join(&path_buf));
+            return Ok((package, absolute_path.to_string_lossy().to_string()));
         }
+
+        // Angular 17+ builders can leave package.json in a subfolder such as browser/
+        let (path_buf, detected_path) = match crate::angular::nested_package_root(&path_buf, &detected_path) {
+            Some((root, written)) => {
+                println!("📂 Using {}, where the build put its package.json", crate::paths::display_path(&root));
+                (root, written)
+            }
+            None => (path_buf, detected_path),
+        };
         
         // Try to detect package name from package.json if not provided
         let detected_package = if let Some(pkg) = package {
-            pkg.clone()
+            Self::confirm_package_name(pkg, &path_buf.join("package.json"))
         } else {
             // Look for package.json in the specified path
             let package_json_path = path_buf.join("package.json");
@@ -370,8 +1508,13 @@ impl Cli {
             }
         };
         
+        if portable {
+            return Ok((detected_package, detected_path));
+        }
+
         // Convert to absolute path for consistency
         let absolute_path = path_buf.canonicalize()
+            .map(|p| crate::paths::normalize_path(&p))
             .map_err(|_| anyhow::anyhow!("Could not resolve absolute path for: {}", detected_path))?
             .to_string_lossy()
             .to_string();
@@ -1,7 +1,12 @@
-use clap::Command;
+use std::fs;
+use clap::{Command, CommandFactory};
 use clap_complete::{Shell, generate};
 use anyhow::Result;
 use crate::config::Config;
+use crate::error::SpineError;
+use crate::paths::display_path;
+use crate::platform::Platform;
+use crate::scanner::ScanCache;
 
 pub fn generate_completions(
     shell: Shell,
@@ -16,29 +21,77 @@ pub fn generate_completions(
     match shell {
         Shell::Bash => {
             writeln!(output, "\n# Custom completion for package names").unwrap();
+            writeln!(output, "# complete registers per command name, so dispatch on the subcommand here and").unwrap();
+            writeln!(output, "# leave everything else, flags included, to the generated _spine").unwrap();
             writeln!(output, "_spine_packages() {{").unwrap();
-            writeln!(output, "    local packages").unwrap();
-            writeln!(output, "    packages=$(spine list-packages-for-completion 2>/dev/null || echo \"\")").unwrap();
-            writeln!(output, "    COMPREPLY=($(compgen -W \"$packages\" -- \"${{COMP_WORDS[COMP_CWORD]}}\"))").unwrap();
+            writeln!(output, "    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\" prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\" packages").unwrap();
+            writeln!(output, "    if [ \"$COMP_CWORD\" -ge 2 ] && [[ \"$cur\" != -* ]]; then").unwrap();
+            writeln!(output, "        case \"${{COMP_WORDS[1]}}:$prev\" in").unwrap();
+            writeln!(output, "            link:--group|unlink:--group)").unwrap();
+            writeln!(output, "                packages=$(spine list-groups-for-completion 2>/dev/null)").unwrap();
+            writeln!(output, "                COMPREPLY=($(compgen -W \"$packages\" -- \"$cur\"))").unwrap();
+            writeln!(output, "                return ;;").unwrap();
+            writeln!(output, "            *:-*) ;;").unwrap();
+            writeln!(output, "            link:*|unlink:*|remove:*|info:*)").unwrap();
+            writeln!(output, "                packages=$(spine list-packages-for-completion 2>/dev/null)").unwrap();
+            writeln!(output, "                COMPREPLY=($(compgen -W \"$packages\" -- \"$cur\"))").unwrap();
+            writeln!(output, "                return ;;").unwrap();
+            writeln!(output, "            add:*|a:*)").unwrap();
+            writeln!(output, "                _spine_package_paths").unwrap();
+            writeln!(output, "                return ;;").unwrap();
+            writeln!(output, "        esac").unwrap();
+            writeln!(output, "    fi").unwrap();
+            writeln!(output, "    _spine \"$@\"").unwrap();
             writeln!(output, "}}").unwrap();
             writeln!(output, "").unwrap();
-            writeln!(output, "# Override completion for link, unlink, and remove commands").unwrap();
-            writeln!(output, "complete -F _spine_packages spine link").unwrap();
-            writeln!(output, "complete -F _spine_packages spine unlink").unwrap();
-            writeln!(output, "complete -F _spine_packages spine remove").unwrap();
+            writeln!(output, "# Package directories from the last scan, falling back to directory completion").unwrap();
+            writeln!(output, "_spine_package_paths() {{").unwrap();
+            writeln!(output, "    local paths").unwrap();
+            writeln!(output, "    paths=$(spine list-package-paths-for-completion 2>/dev/null)").unwrap();
+            writeln!(output, "    if [ -n \"$paths\" ]; then").unwrap();
+            writeln!(output, "        COMPREPLY=($(compgen -W \"$paths\" -- \"${{COMP_WORDS[COMP_CWORD]}}\"))").unwrap();
+            writeln!(output, "    else").unwrap();
+            writeln!(output, "        COMPREPLY=($(compgen -d -- \"${{COMP_WORDS[COMP_CWORD]}}\"))").unwrap();
+            writeln!(output, "    fi").unwrap();
+            writeln!(output, "}}").unwrap();
+            writeln!(output, "complete -F _spine_packages -o bashdefault -o default spine").unwrap();
         }
         Shell::Zsh => {
             writeln!(output, "\n# Custom completion for package names").unwrap();
+            writeln!(output, "# compdef registers per command name, so dispatch on the subcommand here and").unwrap();
+            writeln!(output, "# leave everything else, flags included, to the generated _spine").unwrap();
             writeln!(output, "_spine_packages() {{").unwrap();
             writeln!(output, "    local packages").unwrap();
-            writeln!(output, "    packages=($(spine list-packages-for-completion 2>/dev/null))").unwrap();
-            writeln!(output, "    _describe 'packages' packages").unwrap();
+            writeln!(output, "    if (( CURRENT > 2 )) && [[ ${{words[CURRENT]}} != -* ]]; then").unwrap();
+            writeln!(output, "        case \"${{words[2]}}:${{words[CURRENT-1]}}\" in").unwrap();
+            writeln!(output, "            link:--group|unlink:--group)").unwrap();
+            writeln!(output, "                packages=($(spine list-groups-for-completion 2>/dev/null))").unwrap();
+            writeln!(output, "                _describe 'groups' packages").unwrap();
+            writeln!(output, "                return ;;").unwrap();
+            writeln!(output, "            *:-*) ;;").unwrap();
+            writeln!(output, "            link:*|unlink:*|remove:*|info:*)").unwrap();
+            writeln!(output, "                packages=($(spine list-packages-for-completion 2>/dev/null))").unwrap();
+            writeln!(output, "                _describe 'packages' packages").unwrap();
+            writeln!(output, "                return ;;").unwrap();
+            writeln!(output, "            add:*|a:*)").unwrap();
+            writeln!(output, "                _spine_package_paths").unwrap();
+            writeln!(output, "                return ;;").unwrap();
+            writeln!(output, "        esac").unwrap();
+            writeln!(output, "    fi").unwrap();
+            writeln!(output, "    _spine \"$@\"").unwrap();
             writeln!(output, "}}").unwrap();
-            writeln!(output, "").unwrap();
-            writeln!(output, "# Override completion for specific commands").unwrap();
-            writeln!(output, "compdef _spine_packages 'spine link'").unwrap();
-            writeln!(output, "compdef _spine_packages 'spine unlink'").unwrap();
-            writeln!(output, "compdef _spine_packages 'spine remove'").unwrap();
+            writeln!(output).unwrap();
+            writeln!(output, "# Package directories from the last scan, falling back to directory completion").unwrap();
+            writeln!(output, "_spine_package_paths() {{").unwrap();
+            writeln!(output, "    local paths").unwrap();
+            writeln!(output, "    paths=(${{(f)\"$(spine list-package-paths-for-completion 2>/dev/null)\"}})").unwrap();
+            writeln!(output, "    if (( ${{#paths}} )); then").unwrap();
+            writeln!(output, "        compadd -a paths").unwrap();
+            writeln!(output, "    else").unwrap();
+            writeln!(output, "        _files -/").unwrap();
+            writeln!(output, "    fi").unwrap();
+            writeln!(output, "}}").unwrap();
+            writeln!(output, "compdef _spine_packages spine").unwrap();
         }
         Shell::Fish => {
             writeln!(output, "\n# Custom completion for package names").unwrap();
@@ -49,6 +102,14 @@ pub fn generate_completions(
             writeln!(output, "complete -c spine -n '__fish_seen_subcommand_from link' -a '(__spine_packages)'").unwrap();
             writeln!(output, "complete -c spine -n '__fish_seen_subcommand_from unlink' -a '(__spine_packages)'").unwrap();
             writeln!(output, "complete -c spine -n '__fish_seen_subcommand_from remove' -a '(__spine_packages)'").unwrap();
+            writeln!(output, "complete -c spine -n '__fish_seen_subcommand_from info' -a '(__spine_packages)'").unwrap();
+            writeln!(output, "complete -c spine -n '__fish_seen_subcommand_from link unlink' -l group -x -a '(spine list-groups-for-completion 2>/dev/null)'").unwrap();
+            writeln!(output).unwrap();
+            writeln!(output, "function __spine_package_paths").unwrap();
+            writeln!(output, "    spine list-package-paths-for-completion 2>/dev/null").unwrap();
+            writeln!(output, "end").unwrap();
+            writeln!(output).unwrap();
+            writeln!(output, "complete -c spine -n '__fish_seen_subcommand_from add a' -a '(__spine_package_paths)'").unwrap();
         }
         _ => {
             // For other shells, just generate basic completion
@@ -56,10 +117,111 @@ pub fn generate_completions(
     }
 }
 
-pub fn list_packages_for_completion() -> Result<()> {
-    let config = Config::load_or_create()?;
-    for package_name in config.links.keys() {
+pub fn list_packages_for_completion(config: &Config) -> Result<()> {
+    for (package_name, link) in &config.links {
         println!("{}", package_name);
+        if link.npm_name() != package_name {
+            println!("{}", link.npm_name());
+        }
+    }
+    Ok(())
+}
+
+/// Print package directories recorded by the last scan. Reads only the cache so
+/// completion stays fast; prints nothing when no scan has been run.
+pub fn list_package_paths_for_completion(config: &Config) -> Result<()> {
+    if let Ok(Some(cache)) = ScanCache::load(&config.file) {
+        for dir in &cache.package_dirs {
+            println!("{}", dir.display());
+        }
+    }
+    Ok(())
+}
+
+pub fn list_groups_for_completion(config: &Config) -> Result<()> {
+    for group_name in config.groups.keys() {
+        println!("{}", group_name);
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// The full completion script for a shell, including Spine's dynamic additions
+pub fn completion_script(shell: Shell) -> Vec<u8> {
+    let mut cmd = crate::cli::Cli::command();
+    let mut output = Vec::new();
+    generate_completions(shell, &mut cmd, "spine", &mut output);
+    output
+}
+
+/// Write the completion script where the shell picks it up automatically, or
+/// remove it again. User installs become the auto-regeneration target.
+pub fn install_completions(config: &mut Config, shell: Option<Shell>, system: bool, uninstall: bool) -> Result<()> {
+    let shell_name = match shell {
+        Some(shell) => shell.to_string(),
+        None => Platform::detect_current_shell()
+            .ok_or_else(|| SpineError::Config("Could not detect your shell; pass --shell".to_string()))?,
+    };
+    let shell: Shell = shell_name.parse()
+        .map_err(|_| SpineError::Config(format!("Unsupported shell: {}", shell_name)))?;
+
+    let path = Platform::completion_install_path(&shell_name, system).ok_or_else(|| {
+        SpineError::Config(format!(
+            "No standard {} completion location for {}; use 'spine generate-completion {}' and place it yourself",
+            if system { "system-wide" } else { "per-user" }, shell_name, shell_name
+        ))
+    })?;
+
+    if uninstall {
+        if path.exists() {
+            fs::remove_file(&path)?;
+            println!("🗑️  Removed {} completions from {}", shell_name, display_path(&path));
+        } else {
+            println!("No {} completions installed at {}", shell_name, display_path(&path));
+        }
+        if config.completion.script_path.as_ref() == Some(&path) {
+            config.completion.script_path = None;
+            config.completion.auto_regenerate = false;
+            config.save()?;
+        }
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| install_error(&path, e, system))?;
+    }
+    fs::write(&path, completion_script(shell)).map_err(|e| install_error(&path, e, system))?;
+    println!("✅ Installed {} completions to {}", shell_name, display_path(&path));
+
+    if system {
+        println!("💡 System-wide scripts aren't auto-regenerated; rerun this command after adding packages.");
+    } else {
+        config.completion.shell = Some(shell_name.clone());
+        config.completion.script_path = Some(path.clone());
+        config.save()?;
+        if config.completion.auto_regenerate {
+            println!("🔄 Auto-regeneration will keep this file up to date.");
+        }
+    }
+
+    match shell {
+        Shell::Bash => println!("💡 Loaded automatically by bash-completion 2.x. Without it, add to ~/.bashrc:\n   source {}", path.display()),
+        Shell::Zsh if !system => {
+            println!("💡 Add to ~/.zshrc (before compinit) if you haven't already:");
+            println!("   fpath=(~/.zsh/completions $fpath)");
+            println!("   autoload -U compinit && compinit");
+        }
+        Shell::PowerShell => println!("💡 Add to your PowerShell profile ($PROFILE):\n   . '{}'", path.display()),
+        _ => {}
+    }
+    println!("   Restart your shell to pick up the changes.");
+
+    Ok(())
+}
+
+fn install_error(path: &std::path::Path, error: std::io::Error, system: bool) -> SpineError {
+    if error.kind() == std::io::ErrorKind::PermissionDenied && system {
+        SpineError::Config(format!("Cannot write {}: {}. System-wide installs usually need sudo.", path.display(), error))
+    } else {
+        SpineError::Config(format!("Cannot write {}: {}", path.display(), error))
+    }
+}
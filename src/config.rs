@@ -1,26 +1,675 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use clap::CommandFactory;
 use crate::error::SpineError;
+use crate::hooks::HookPoint;
 use crate::platform::Platform;
+use crate::pm::PackageManager;
+use crate::paths::display_path;
+
+/// Version of the running Spine binary
+pub const SPINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Profile name for config.toml's own links
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Where config.toml is: the file given with --config or SPINE_CONFIG, or
+/// spine/config.toml in the platform config directory. Profiles, backups
+/// and the other files kept beside config.toml follow it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigFile {
+    given: Option<PathBuf>,
+}
+
+impl ConfigFile {
+    /// The file given with --config or SPINE_CONFIG, with `~` expanded and a
+    /// relative path taken from the working directory
+    pub fn new(given: Option<&std::path::Path>) -> Self {
+        let given = given.map(|path| {
+            let expanded = crate::paths::expand_path(&path.to_string_lossy());
+            match std::env::current_dir() {
+                Ok(current_dir) => crate::paths::resolve_relative(&current_dir, &expanded),
+                Err(_) => PathBuf::from(expanded),
+            }
+        });
+        Self { given }
+    }
+
+    /// Whether --config or SPINE_CONFIG chose the file
+    pub fn is_given(&self) -> bool {
+        self.given.is_some()
+    }
+
+    /// config.toml's path, creating the directories it goes in
+    pub fn path(&self) -> Result<PathBuf> {
+        if let Some(path) = &self.given {
+            if let Some(parent) = path.parent().filter(|parent| !parent.exists()) {
+                fs::create_dir_all(parent)?;
+            }
+            return Ok(path.clone());
+        }
+
+        let config_dir = dirs::config_dir()
+            .ok_or_else(|| SpineError::Config("Could not find config directory".to_string()))?;
+        
+        let spine_dir = config_dir.join("spine");
+        if !spine_dir.exists() {
+            fs::create_dir_all(&spine_dir)?;
+        }
+        
+        Ok(spine_dir.join("config.toml"))
+    }
+
+    /// The directory config.toml is in, where Spine keeps its other files
+    pub fn dir(&self) -> Result<PathBuf> {
+        Ok(self.path()?.parent().map(std::path::Path::to_path_buf).unwrap_or_default())
+    }
+
+    pub fn profiles_dir(&self) -> Result<PathBuf> {
+        Ok(self.dir()?.join("profiles"))
+    }
+
+    /// The profile's file. Fails for names that aren't safe file names,
+    /// which SPINE_PROFILE and a hand-edited active_profile can hold.
+    pub fn profile_path(&self, name: &str) -> Result<PathBuf> {
+        validate_profile_name(name)?;
+        Ok(self.profiles_dir()?.join(format!("{}.toml", name)))
+    }
+}
+
+/// Profile names become file names, so keep them to a safe set
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(SpineError::Config(format!(
+            "Invalid profile name '{}': use letters, digits, '-' and '_'", name
+        )).into());
+    }
+    Ok(())
+}
+
+/// Layout version of config.toml this build reads and writes. Files without
+/// `schema_version` predate it and count as version 0.
+pub const CONFIG_SCHEMA_VERSION: u32 = 2;
+
+/// Upgrades of the config.toml document, each from version `index` to
+/// `index + 1`. Add a step here when a change would otherwise make older
+/// files fail to parse or lose a setting.
+const MIGRATIONS: [fn(&mut toml::Table); CONFIG_SCHEMA_VERSION as usize] = [
+    migrate_to_v1,
+    migrate_to_v2,
+];
+
+/// Version 1: `[completion]` tables written before `auto_regenerate` had a
+/// default, which fail to parse without it
+fn migrate_to_v1(document: &mut toml::Table) {
+    if let Some(toml::Value::Table(completion)) = document.get_mut("completion") {
+        completion.entry("auto_regenerate").or_insert(toml::Value::Boolean(false));
+    }
+}
+
+/// Version 2: a link can hold several variants of its package. The flat
+/// entries of version 1 are packages with only their default variant and
+/// read as they are; the bump is what keeps older builds, which would drop
+/// `variants` on save, from loading the file.
+fn migrate_to_v2(_document: &mut toml::Table) {}
+
+/// Variant name given to a link's existing path when another variant is
+/// added to a link that had none
+pub const DEFAULT_VARIANT: &str = "default";
+
+/// Variant names are shown in brackets and typed after --variant
+fn validate_variant(variant: &str) -> Result<()> {
+    let valid = !variant.is_empty() && variant.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(SpineError::Config(format!("Invalid variant '{}': use letters, digits, '-', '_' or '.'", variant)).into());
+    }
+    Ok(())
+}
+
+fn unknown_variant(link: &PackageLink, variant: &str) -> SpineError {
+    let names = link.variant_names();
+    if names.is_empty() {
+        return SpineError::Config(format!(
+            "{} has no variants. Add one with 'spine add {} --variant <name> <path>'", link.name, link.name
+        ));
+    }
+    SpineError::Config(format!("{} has no variant '{}'; it has {}", link.name, variant, names.join(", ")))
+}
+
+/// Tags are typed on the command line, so keep them to single words
+fn validate_tag(tag: &str) -> Result<()> {
+    let valid = !tag.is_empty() && !tag.chars().any(|c| c.is_whitespace() || c == ',');
+    if !valid {
+        return Err(SpineError::Config(format!("Invalid tag '{}': tags can't be empty or contain spaces or commas", tag)).into());
+    }
+    Ok(())
+}
+
+/// Write beside `path` and rename over it, so readers never see half a file
+pub fn write_replacing(path: &std::path::Path, content: &str) -> Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackageLink {
     pub name: String,
     pub path: PathBuf,
     pub version: Option<String>,
+    /// Name of the variant `path` holds when the package has several
+    /// checkouts; projects that didn't choose one get this one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_variant: Option<String>,
+    /// The package's other checkouts by variant name, e.g. one per
+    /// compatibility branch
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variants: BTreeMap<String, LinkVariant>,
+    /// The variant last linked into each project with `--variant`, which
+    /// later links and syncs there keep using
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub project_variants: BTreeMap<PathBuf, String>,
+    /// Package manager for this package, beating the global `package_manager`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<PackageManager>,
+    /// Managers for particular consuming projects, beating `package_manager`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub project_package_managers: BTreeMap<PathBuf, PackageManager>,
+    /// The version stored before the last automatic or forced refresh
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
     #[serde(default)]
     pub linked_projects: Vec<PathBuf>,
+    /// Unix timestamp of when the package was last linked into each of
+    /// `linked_projects`; missing for links made before this was recorded
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub linked_at: BTreeMap<PathBuf, u64>,
+    /// Unix timestamp of when each of `linked_projects` was last found on
+    /// disk by status or sync, so projects deleted long ago can expire
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_seen: BTreeMap<PathBuf, u64>,
+    /// Spine version that added the link (or first loaded it, for older entries)
+    #[serde(default)]
+    pub created_by_version: Option<String>,
+    /// Unix timestamp of when the link was added; unknown for migrated entries
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    /// Added with --allow-missing before the path existed; skipped by link
+    /// operations until the path appears
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pending: bool,
+    /// The `name` from the package's package.json when the link was added under
+    /// a different name. `name` is then only an alias for display; npm and
+    /// node_modules always use this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub npm_name: Option<String>,
+    /// Projects linked through a package.json override instead of a symlink,
+    /// so installs keep the link
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub durable: Vec<DurableLink>,
+    /// Shell commands run in the package directory before it is linked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_link: Vec<String>,
+    /// Shell commands run in the project after the package is linked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_link: Vec<String>,
+    /// Shell commands run in the package directory before it is unlinked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_unlink: Vec<String>,
+    /// Shell commands run in the project after the package is unlinked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_unlink: Vec<String>,
+    /// Projects the package was linked into by `link --with-deps` only
+    /// because other linked packages depend on it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_by: Vec<RequiredBy>,
+    /// Git URL of the source the package is built from, for `spine setup`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// Where the package sits inside a checkout of `repository`, with `/`
+    /// separators; the checkout root when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_path: Option<String>,
+    /// Free-form labels for filtering list, status and link-all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Why the link exists, e.g. "pinned to fork until PR #812 merges"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// `path` as written in the config when it uses `~` or environment
+    /// variables; saved back in its place so the file stays portable
+    #[serde(skip)]
+    pub path_spec: Option<String>,
+}
+
+/// A checkout of a link's package other than the default one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkVariant {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Added with --allow-missing before the path existed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pending: bool,
+    /// `path` as written, like `PackageLink::path_spec`
+    #[serde(skip)]
+    pub path_spec: Option<String>,
+}
+
+/// Why a package was linked into a project it wasn't asked for in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequiredBy {
+    pub project: PathBuf,
+    /// Linked packages there that depend on it
+    pub packages: Vec<String>,
+}
+
+/// A project linked through an override in its package.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DurableLink {
+    pub project: PathBuf,
+    /// Dependency field whose entry for the package was pointed at the local
+    /// path too (npm rejects overrides that conflict with a direct dependency)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependency_field: Option<String>,
+    /// The range that entry had before, put back on unlink
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_range: Option<String>,
+}
+
+/// Which Spine build last saved the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriterStamp {
+    pub version: String,
+    pub os: String,
+    #[serde(default)]
+    pub hostname: Option<String>,
+}
+
+impl WriterStamp {
+    pub fn current() -> Self {
+        Self {
+            version: SPINE_VERSION.to_string(),
+            os: std::env::consts::OS.to_string(),
+            hostname: std::env::var("HOSTNAME")
+                .or_else(|_| std::env::var("COMPUTERNAME"))
+                .ok()
+                .filter(|h| !h.is_empty()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
+    /// Package manager used for link/unlink unless overridden with --pm;
+    /// detected from each project's lockfiles when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<PackageManager>,
     pub links: HashMap<String, PackageLink>,
+    /// Named sets of packages that are linked and unlinked together
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub groups: HashMap<String, Vec<String>>,
+    /// Update stored versions whenever link, status or the TUI finds that a
+    /// package.json changed, instead of only on `verify --refresh-versions`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub track_versions: bool,
     #[serde(default)]
     pub completion: CompletionConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub scan: ScanConfig,
+    #[serde(default)]
+    pub timeouts: TimeoutConfig,
+    #[serde(default)]
+    pub build: BuildConfig,
+    #[serde(default)]
+    pub licenses: LicenseConfig,
+    #[serde(default)]
+    pub tui: TuiConfig,
+    #[serde(default)]
+    pub setup: SetupConfig,
+    #[serde(default)]
+    pub projects: ProjectsConfig,
+    #[serde(default)]
+    pub workspace_search: WorkspaceSearchConfig,
+    #[serde(default, skip_serializing_if = "PathMap::is_empty")]
+    pub path_map: PathMap,
+    #[serde(default)]
+    pub usage_log: UsageLogConfig,
+    #[serde(default)]
+    pub backups: BackupConfig,
+    /// Profile whose links commands use, from `spine profile use`;
+    /// SPINE_PROFILE overrides it. This file's own links when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub last_written_by: Option<WriterStamp>,
+    /// Layout version of the file; see `CONFIG_SCHEMA_VERSION`
+    #[serde(default)]
+    pub schema_version: u32,
+    /// The version config.toml was migrated from while loading, if it was
+    #[serde(skip)]
+    pub migrated_from: Option<u32>,
+    /// The profile `links` was loaded from, saved back to its own file
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// Link entries that failed to load, kept verbatim so saving doesn't drop them
+    #[serde(skip)]
+    pub broken_links: toml::Table,
+    /// Per-entry problems found while loading
+    #[serde(skip)]
+    pub load_warnings: Vec<String>,
+    /// Links whose path comes from the current project's .spine.toml. They
+    /// are undone before saving, so the global config keeps its own paths.
+    #[serde(skip)]
+    pub workspace_overrides: BTreeMap<String, WorkspaceOverride>,
+    /// Where the config was loaded from and is saved to
+    #[serde(skip)]
+    pub file: ConfigFile,
+    #[serde(skip)]
+    disk_state: DiskState,
+}
+
+/// A link pointed elsewhere by a `[links]` entry in .spine.toml
+#[derive(Debug, Clone)]
+pub struct WorkspaceOverride {
+    /// The .spine.toml it came from
+    pub file: PathBuf,
+    pub path: PathBuf,
+    /// The global entry it replaces; None when only the workspace has the link
+    pub global: Option<PackageLink>,
+}
+
+/// `linked_projects` of each link as this process last read or wrote them.
+/// Another spine process may save in between; comparing against this lets
+/// `save` keep the projects it linked instead of overwriting them. Shared
+/// between clones, which stand for the same file.
+#[derive(Debug, Clone, Default)]
+struct DiskState(std::sync::Arc<std::sync::Mutex<Option<LinkedProjects>>>);
+
+type LinkedProjects = HashMap<String, Vec<PathBuf>>;
+
+impl DiskState {
+    fn record(&self, config: &Config) {
+        let projects = config.links.iter().map(|(name, link)| (name.clone(), link.linked_projects.clone())).collect();
+        if let Ok(mut state) = self.0.lock() {
+            *state = Some(projects);
+        }
+    }
+
+    fn get(&self) -> Option<LinkedProjects> {
+        self.0.lock().ok().and_then(|state| state.clone())
+    }
+}
+
+/// `[path_map]`: roots holding the same repositories on different machines,
+/// e.g. `"/Users/me/dev" = "/home/me/dev"`. The config stores paths under
+/// the first root; where only the second exists, links are read from there.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct PathMap(BTreeMap<String, String>);
+
+/// One `[path_map]` entry with both roots expanded
+#[derive(Debug, Clone)]
+pub struct PathRemap {
+    pub canonical: PathBuf,
+    pub local: PathBuf,
+    /// The canonical root is missing here and the other one exists
+    pub applies: bool,
+}
+
+impl PathMap {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn remaps(&self) -> Vec<PathRemap> {
+        self.0.iter()
+            .map(|(canonical, local)| {
+                let canonical = crate::paths::normalize_path(Path::new(&crate::paths::expand_path(canonical)));
+                let local = crate::paths::normalize_path(Path::new(&crate::paths::expand_path(local)));
+                let applies = !canonical.exists() && local.exists();
+                PathRemap { canonical, local, applies }
+            })
+            .collect()
+    }
+}
+
+impl PathRemap {
+    /// `path` moved from under `from` to under `to`, if it is under `from`
+    fn rebase(path: &Path, from: &Path, to: &Path) -> Option<PathBuf> {
+        path.strip_prefix(from).ok().map(|rest| to.join(rest))
+    }
+}
+
+/// `path` as the config file stores it: under the first root of its mapping
+pub fn canonical_path(path: &Path, remaps: &[PathRemap]) -> PathBuf {
+    remaps.iter()
+        .find_map(|remap| PathRemap::rebase(path, &remap.local, &remap.canonical))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// `path` where it lives on this machine
+pub fn local_path(path: &Path, remaps: &[PathRemap]) -> PathBuf {
+    let canonical = canonical_path(path, remaps);
+    remaps.iter()
+        .filter(|remap| remap.applies)
+        .find_map(|remap| PathRemap::rebase(&canonical, &remap.canonical, &remap.local))
+        .unwrap_or(canonical)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScanConfig {
+    /// Directories `spine scan` walks when no --path is given
+    #[serde(default)]
+    pub roots: Vec<PathBuf>,
+}
+
+/// Keeps throwaway checkouts from piling up in `linked_projects`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectsConfig {
+    /// Path prefixes never recorded as linked projects, e.g. /tmp or a CI
+    /// workspace root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub never_record: Vec<PathBuf>,
+    /// Days a linked project's directory may be missing before status or
+    /// sync forgets it; 0 keeps them until `spine prune --projects`
+    #[serde(default = "default_expire_after_days")]
+    pub expire_after_days: u64,
+}
+
+impl Default for ProjectsConfig {
+    fn default() -> Self {
+        Self {
+            never_record: Vec::new(),
+            expire_after_days: default_expire_after_days(),
+        }
+    }
+}
+
+fn default_expire_after_days() -> u64 {
+    30
+}
+
+/// Bounds on the upward search for angular.json and .spine.toml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSearchConfig {
+    /// Parent directories to climb from a package or project
+    #[serde(default = "default_max_levels")]
+    pub max_levels: usize,
+    /// Stop at a repository root (a directory holding .git)
+    #[serde(default = "default_stop_at_repository")]
+    pub stop_at_repository: bool,
+}
+
+impl Default for WorkspaceSearchConfig {
+    fn default() -> Self {
+        Self {
+            max_levels: default_max_levels(),
+            stop_at_repository: default_stop_at_repository(),
+        }
+    }
+}
+
+impl WorkspaceSearchConfig {
+    pub fn limits(&self) -> crate::paths::WalkLimits {
+        crate::paths::WalkLimits { max_levels: self.max_levels, stop_at_repository: self.stop_at_repository }
+    }
+}
+
+/// Opt-in local log of the commands run, for `spine usage report`. Nothing
+/// in it is ever sent anywhere.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UsageLogConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Defaults to usage.jsonl next to config.toml
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl UsageLogConfig {
+    pub fn file(&self, config_file: &ConfigFile) -> Result<PathBuf> {
+        match &self.path {
+            Some(path) => Ok(PathBuf::from(crate::paths::expand_path(&path.to_string_lossy()))),
+            None => Ok(config_file.dir()?.join("usage.jsonl")),
+        }
+    }
+}
+
+/// Copies of config.toml and profile files kept from before each save, for
+/// `spine config restore`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Newest copies kept in backups/ next to config.toml; 0 turns them off
+    #[serde(default = "default_backups_kept")]
+    pub keep: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self { keep: default_backups_kept() }
+    }
+}
+
+fn default_backups_kept() -> usize {
+    20
+}
+
+fn default_max_levels() -> usize {
+    crate::paths::DEFAULT_WALK_LEVELS
+}
+
+fn default_stop_at_repository() -> bool {
+    true
+}
+
+/// Refresh `last_seen` at most this often, so status doesn't rewrite the
+/// config on every run
+const LAST_SEEN_RESOLUTION_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SetupConfig {
+    /// Where `spine setup` clones missing repositories; ~/src when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_root: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseConfig {
+    /// SPDX identifier prefixes `spine licenses` flags, matched case-insensitively
+    #[serde(default = "default_license_deny")]
+    pub deny: Vec<String>,
+}
+
+impl Default for LicenseConfig {
+    fn default() -> Self {
+        Self {
+            deny: default_license_deny(),
+        }
+    }
+}
+
+fn default_license_deny() -> Vec<String> {
+    ["GPL", "AGPL", "LGPL", "SSPL", "EUPL", "OSL"].iter().map(|s| s.to_string()).collect()
+}
+
+/// Limits on the ng builds Spine starts, to keep the machine usable while they run
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct BuildConfig {
+    /// Builds running at once across build --all, serve's initial library
+    /// builds and smoke; 0 for no limit
+    #[serde(default)]
+    pub max_concurrency: usize,
+    /// Start builds at reduced scheduling priority: nice on Unix, below
+    /// normal priority on Windows
+    #[serde(default)]
+    pub low_priority: bool,
+    /// Node heap cap for each build, in MB, passed through NODE_OPTIONS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_max_old_space_mb: Option<u64>,
+}
+
+/// Per-category limits for external commands, in seconds; 0 disables the limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeoutConfig {
+    /// npm/pnpm link and unlink
+    #[serde(default = "default_link_timeout")]
+    pub link_secs: u64,
+    /// ng build and package installs; unlimited unless set
+    #[serde(default)]
+    pub build_secs: u64,
+    /// Registry queries and npm publish
+    #[serde(default = "default_registry_timeout")]
+    pub registry_secs: u64,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            link_secs: default_link_timeout(),
+            build_secs: 0,
+            registry_secs: default_registry_timeout(),
+        }
+    }
+}
+
+impl TimeoutConfig {
+    pub fn link(&self) -> Option<Duration> {
+        Self::limit(self.link_secs)
+    }
+
+    pub fn build(&self) -> Option<Duration> {
+        Self::limit(self.build_secs)
+    }
+
+    pub fn registry(&self) -> Option<Duration> {
+        Self::limit(self.registry_secs)
+    }
+
+    fn limit(secs: u64) -> Option<Duration> {
+        if secs == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(secs))
+        }
+    }
+}
+
+fn default_link_timeout() -> u64 {
+    120
+}
+
+fn default_registry_timeout() -> u64 {
+    30
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -30,132 +679,1514 @@ pub struct CompletionConfig {
     pub script_path: Option<PathBuf>,
 }
 
-impl Config {
-    pub fn config_path() -> Result<PathBuf> {
-        let config_dir = dirs::config_dir()
-            .ok_or_else(|| SpineError::Config("Could not find config directory".to_string()))?;
-        
-        let spine_dir = config_dir.join("spine");
-        if !spine_dir.exists() {
-            fs::create_dir_all(&spine_dir)?;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TuiConfig {
+    /// Save immediately after add/remove/link/unlink in interactive mode.
+    /// When off, changes are kept until Ctrl+S or confirmed on quit.
+    #[serde(default = "default_auto_save")]
+    pub auto_save: bool,
+    /// Use the line-based interactive mode instead of the full-screen TUI
+    #[serde(default)]
+    pub simple: bool,
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            auto_save: default_auto_save(),
+            simple: false,
+        }
+    }
+}
+
+fn default_auto_save() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Warn in build summaries when a library's bundle grows by more than this percentage
+    #[serde(default = "default_size_warn_percent")]
+    pub size_warn_percent: f64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            size_warn_percent: default_size_warn_percent(),
+        }
+    }
+}
+
+fn default_size_warn_percent() -> f64 {
+    10.0
+}
+
+/// Shown wherever a pending link is listed
+pub const PENDING_MARKER: &str = "pending: path missing (build or clone required)";
+
+impl PackageLink {
+    /// A freshly added link, reading version and npm name from the
+    /// package.json at `path` when there is one
+    pub fn new(name: String, path: PathBuf) -> Self {
+        let package_json_path = path.join("package.json");
+        let version = if package_json_path.exists() {
+            crate::package::get_package_version(&package_json_path).ok()
+        } else {
+            None
+        };
+        let npm_name = crate::package::get_package_name(&package_json_path).ok()
+            .filter(|declared| *declared != name);
+        Self {
+            pending: !path.exists(),
+            name,
+            path,
+            version,
+            default_variant: None,
+            variants: BTreeMap::new(),
+            project_variants: BTreeMap::new(),
+            previous_version: None,
+            package_manager: None,
+            project_package_managers: BTreeMap::new(),
+            linked_projects: Vec::new(),
+            linked_at: BTreeMap::new(),
+            last_seen: BTreeMap::new(),
+            created_by_version: Some(SPINE_VERSION.to_string()),
+            created_at: Some(crate::history::now_timestamp()),
+            npm_name,
+            durable: Vec::new(),
+            pre_link: Vec::new(),
+            post_link: Vec::new(),
+            pre_unlink: Vec::new(),
+            post_unlink: Vec::new(),
+            required_by: Vec::new(),
+            repository: None,
+            build_path: None,
+            tags: Vec::new(),
+            note: None,
+            path_spec: None,
+        }
+    }
+
+    /// The output subfolder holding package.json when `path` itself has none,
+    /// as Angular 17+ builders can leave it, with a hint to point the link there
+    pub fn nested_package_hint(&self) -> Option<String> {
+        let root = crate::angular::package_root(&self.path).filter(|root| *root != self.path)?;
+        Some(format!("package.json is in {}; run 'spine set-path {} {}' to link that directory",
+            display_path(&root), self.name, display_path(&root)))
+    }
+
+    /// " 🏷️ a, b" after a link's name, or nothing when it has no tags
+    pub fn tag_label(&self) -> String {
+        if self.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" 🏷️ {}", self.tags.join(", "))
+        }
+    }
+
+    /// Variant names, the default first; empty for a package without variants
+    pub fn variant_names(&self) -> Vec<&str> {
+        self.default_variant.as_deref().into_iter().chain(self.variants.keys().map(String::as_str)).collect()
+    }
+
+    /// The variant `project` chose with --variant, if it still exists
+    pub fn variant_in(&self, project: &Path) -> Option<&str> {
+        self.project_variants.iter()
+            .find(|(entry, _)| crate::paths::same_path(entry, project))
+            .map(|(_, variant)| variant.as_str())
+            .filter(|variant| self.default_variant.as_deref() == Some(*variant) || self.variants.contains_key(*variant))
+    }
+
+    /// This link with `variant`'s path and version in place of the
+    /// default's; None when the package has no such variant
+    pub fn with_variant(&self, variant: &str) -> Option<PackageLink> {
+        if self.default_variant.as_deref() == Some(variant) {
+            return Some(self.clone());
+        }
+        let chosen = self.variants.get(variant)?;
+        Some(PackageLink {
+            path: chosen.path.clone(),
+            version: chosen.version.clone(),
+            pending: chosen.pending,
+            path_spec: chosen.path_spec.clone(),
+            previous_version: None,
+            ..self.clone()
+        })
+    }
+
+    /// This link as it is linked into `project`: the variant the project
+    /// chose, otherwise the default
+    pub fn for_project(&self, project: &Path) -> PackageLink {
+        self.variant_in(project)
+            .and_then(|variant| self.with_variant(variant))
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// " [ng17]" naming the variant linked into `project`, or nothing for a
+    /// package without variants
+    pub fn variant_label(&self, project: &Path) -> String {
+        match self.variant_in(project).or(self.default_variant.as_deref()) {
+            Some(variant) => format!(" [{}]", variant),
+            None => String::new(),
+        }
+    }
+
+    /// The manager this link sets for `project`: the project's own entry,
+    /// then the link's
+    pub fn package_manager_for(&self, project: &Path) -> Option<PackageManager> {
+        self.project_package_managers.iter()
+            .find(|(entry, _)| crate::paths::same_path(entry, project))
+            .map(|(_, pm)| *pm)
+            .or(self.package_manager)
+    }
+
+    /// The manager linking into `project` uses without --pm, and what chose
+    /// it: "project", "link", "config" or "detected"
+    pub fn effective_package_manager(&self, project: &Path, global: Option<PackageManager>) -> (PackageManager, &'static str) {
+        if let Some((_, pm)) = self.project_package_managers.iter().find(|(entry, _)| crate::paths::same_path(entry, project)) {
+            return (*pm, "project");
+        }
+        match (self.package_manager, global) {
+            (Some(pm), _) => (pm, "link"),
+            (None, Some(pm)) => (pm, "config"),
+            (None, None) => (PackageManager::detect(project).manager, "detected"),
+        }
+    }
+
+    /// Why the configured manager is wrong for `project`: its lockfile or
+    /// packageManager field names another one
+    pub fn package_manager_mismatch(&self, project: &Path, global: Option<PackageManager>) -> Option<String> {
+        let configured = self.package_manager_for(project).or(global)?;
+        let detection = PackageManager::detect(project);
+        if detection.defaulted || detection.is_ambiguous() || detection.manager == configured {
+            return None;
+        }
+        Some(format!("set to link with {}, but {} uses {} ({})",
+            configured, display_path(project), detection.manager, detection.evidence.join(", ")))
+    }
+
+    /// "v1.2.0", or "v1.2.0, was v1.1.0" once a refresh replaced a version
+    pub fn version_label(&self) -> String {
+        let current = format!("v{}", self.version.as_deref().unwrap_or("unknown"));
+        match &self.previous_version {
+            Some(previous) => format!("{}, was v{}", current, previous),
+            None => current,
+        }
+    }
+
+    /// Store the version package.json declares now, keeping the replaced one
+    /// in `previous_version`. The update when it changed; an error when
+    /// package.json can't be read.
+    pub fn sync_version(&mut self) -> Result<Option<VersionUpdate>> {
+        let actual = crate::package::get_package_version(&self.path.join("package.json"))?;
+        if self.version.as_deref() == Some(actual.as_str()) {
+            return Ok(None);
+        }
+        let previous = self.version.replace(actual.clone());
+        if previous.is_some() {
+            self.previous_version = previous.clone();
+        }
+        Ok(Some(VersionUpdate { package: self.name.clone(), previous, current: actual }))
+    }
+
+    /// Whether the link has one of `tags`; any link does when none are given
+    pub fn has_any_tag(&self, tags: &[String]) -> bool {
+        tags.is_empty() || self.tags.iter().any(|tag| tags.contains(tag))
+    }
+
+    /// Rewrite the package path and every recorded project path with `map`
+    pub fn map_paths(&mut self, map: impl Fn(&Path) -> PathBuf) {
+        self.path = map(&self.path);
+        for variant in self.variants.values_mut() {
+            variant.path = map(&variant.path);
+        }
+        self.project_variants = self.project_variants.iter().map(|(project, variant)| (map(project), variant.clone())).collect();
+        for project in &mut self.linked_projects {
+            *project = map(project);
+        }
+        self.linked_at = self.linked_at.iter().map(|(project, at)| (map(project), *at)).collect();
+        self.last_seen = self.last_seen.iter().map(|(project, at)| (map(project), *at)).collect();
+        self.project_package_managers = self.project_package_managers.iter().map(|(project, pm)| (map(project), *pm)).collect();
+        for durable in &mut self.durable {
+            durable.project = map(&durable.project);
+        }
+        for required in &mut self.required_by {
+            required.project = map(&required.project);
+        }
+    }
+
+    /// Expand `~` and environment variables in `path`, remembering the
+    /// written form in `path_spec`
+    pub fn expand_path(&mut self) {
+        let written = self.path.to_string_lossy().to_string();
+        let expanded = crate::paths::expand_path(&written);
+        if expanded != written {
+            self.path = crate::paths::normalize_path(std::path::Path::new(&expanded));
+            self.path_spec = Some(written);
+        }
+        for variant in self.variants.values_mut() {
+            let written = variant.path.to_string_lossy().to_string();
+            let expanded = crate::paths::expand_path(&written);
+            if expanded != written {
+                variant.path = crate::paths::normalize_path(std::path::Path::new(&expanded));
+                variant.path_spec = Some(written);
+            }
+        }
+    }
+
+    /// This link as the config file holds it: `path_spec` in place of the
+    /// expanded path, unless the path has since been changed
+    fn portable(&self) -> PackageLink {
+        let mut link = self.clone();
+        if let Some(spec) = &self.path_spec {
+            if crate::paths::normalize_path(std::path::Path::new(&crate::paths::expand_path(spec))) == self.path {
+                link.path = PathBuf::from(spec);
+            }
+        }
+        for variant in link.variants.values_mut() {
+            if let Some(spec) = &variant.path_spec {
+                if crate::paths::normalize_path(std::path::Path::new(&crate::paths::expand_path(spec))) == variant.path {
+                    variant.path = PathBuf::from(spec);
+                }
+            }
+        }
+        link
+    }
+
+    /// Explicitly added packages living under a node_modules segment are
+    /// treated as intentional vendored forks
+    pub fn is_vendored(&self) -> bool {
+        crate::paths::is_under_node_modules(&self.path)
+    }
+
+    /// Name the package is published and installed under
+    pub fn npm_name(&self) -> &str {
+        self.npm_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// The package.json `name` when it differs from the recorded npm name,
+    /// i.e. the entry needs its npm name corrected
+    pub fn name_mismatch(&self) -> Option<String> {
+        let declared = crate::package::get_package_name(&self.path.join("package.json")).ok()?;
+        (declared != self.npm_name()).then_some(declared)
+    }
+
+    pub fn durable_link_in(&self, project: &std::path::Path) -> Option<&DurableLink> {
+        self.durable.iter().find(|d| crate::paths::same_path(&d.project, project))
+    }
+
+    pub fn is_durable_in(&self, project: &std::path::Path) -> bool {
+        self.durable_link_in(project).is_some()
+    }
+
+    /// When the package was linked into `project`, e.g. "linked 3d ago"
+    pub fn linked_age(&self, project: &std::path::Path) -> Option<String> {
+        self.linked_at.iter()
+            .find(|(p, _)| crate::paths::same_path(p, project))
+            .map(|(_, at)| format!("linked {}", crate::history::format_age(*at)))
+    }
+
+    /// Drop timestamps of projects no longer in `linked_projects`
+    fn prune_linked_at(&mut self) {
+        let projects = &self.linked_projects;
+        self.linked_at.retain(|p, _| projects.iter().any(|linked| crate::paths::same_path(linked, p)));
+        self.last_seen.retain(|p, _| projects.iter().any(|linked| crate::paths::same_path(linked, p)));
+    }
+
+    /// The latest time `project` is known to have existed
+    fn last_known(&self, project: &std::path::Path) -> Option<u64> {
+        let lookup = |times: &BTreeMap<PathBuf, u64>| {
+            times.iter().find(|(p, _)| crate::paths::same_path(p, project)).map(|(_, at)| *at)
+        };
+        lookup(&self.last_seen).max(lookup(&self.linked_at))
+    }
+
+    /// Packages that pulled this one into `project` through `link --with-deps`
+    pub fn required_by_in(&self, project: &std::path::Path) -> &[String] {
+        self.required_by.iter()
+            .find(|r| crate::paths::same_path(&r.project, project))
+            .map(|r| r.packages.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Compare the package's actual version with the range `project` declares
+    /// for it. A durable link overwrites a direct dependency, so the range it
+    /// replaced is used instead. None when either side isn't semver.
+    pub fn range_check(&self, project: &std::path::Path) -> Option<crate::package::RangeCheck> {
+        let declared_range = match self.durable_link_in(project).and_then(|d| d.original_range.clone()) {
+            Some(range) => range,
+            None => crate::package::get_dependency_ranges(&project.join("package.json")).ok()?
+                .remove(self.npm_name())?,
+        };
+        let actual_version = crate::package::get_package_version(&self.path.join("package.json")).ok()?;
+        let satisfies = crate::package::range_satisfies(&declared_range, &actual_version)?;
+        Some(crate::package::RangeCheck { declared_range, actual_version, satisfies })
+    }
+
+    pub fn hooks(&self, point: HookPoint) -> &[String] {
+        match point {
+            HookPoint::PreLink => &self.pre_link,
+            HookPoint::PostLink => &self.post_link,
+            HookPoint::PreUnlink => &self.pre_unlink,
+            HookPoint::PostUnlink => &self.post_unlink,
+        }
+    }
+
+    /// Record the package.json name as the npm name, keeping `name` as the alias
+    pub fn set_npm_name(&mut self, npm_name: String) {
+        self.npm_name = (npm_name != self.name).then_some(npm_name);
+    }
+}
+
+impl Config {
+    /// A config without links or settings that saves to `file`
+    pub fn empty(file: &ConfigFile) -> Self {
+        Self { file: file.clone(), ..Self::default() }
+    }
+
+    /// Load the global config from `file` with the current project's
+    /// .spine.toml link overrides merged on top, creating it on first use
+    pub fn load_or_create(file: &ConfigFile) -> Result<Self> {
+        let config_path = file.path()?;
+        
+        let mut config = if config_path.exists() {
+            Self::load(file)?
+        } else {
+            let config = Self::empty(file);
+            config.save()?;
+            config
+        };
+        crate::paths::set_walk_limits(config.workspace_search.limits());
+        crate::usage::set_log_path(config.usage_log.enabled.then(|| config.usage_log.file(file).ok()).flatten());
+        if let Ok(current_dir) = std::env::current_dir() {
+            config.apply_workspace_overrides(&current_dir);
+        }
+        Ok(config)
+    }
+
+    /// Swap the link overrides of the current directory's .spine.toml for
+    /// those of `project`'s, for commands run on another project with `--to`
+    pub fn use_workspace_overrides_of(&mut self, project: &std::path::Path) {
+        if let Some(global) = self.without_workspace_overrides() {
+            *self = global;
+        }
+        self.workspace_overrides.clear();
+        self.apply_workspace_overrides(project);
+    }
+
+    /// Point links at the paths in the .spine.toml nearest `dir`'s `[links]`
+    /// table. An unreadable .spine.toml is reported and otherwise ignored.
+    fn apply_workspace_overrides(&mut self, dir: &std::path::Path) {
+        use crate::workspace::WorkspaceManager;
+
+        let Some(file) = WorkspaceManager::find_workspace_config(dir) else { return };
+        let workspace = match WorkspaceManager::read_workspace_config(&file) {
+            Ok(workspace) => workspace,
+            Err(e) => {
+                eprintln!("⚠️  Ignoring link overrides in {}: {}", display_path(&file), e);
+                return;
+            }
+        };
+        let base = file.parent().map(std::path::Path::to_path_buf).unwrap_or_default();
+
+        for (name, path) in workspace.links {
+            let path = crate::paths::normalize_path(&crate::paths::resolve_relative(&base, &crate::paths::expand_path(&path)));
+            let mut replacement = PackageLink::new(name.clone(), path.clone());
+            let global = self.links.get(&name).cloned();
+            if let Some(global) = &global {
+                replacement = PackageLink {
+                    path: replacement.path,
+                    version: replacement.version,
+                    npm_name: replacement.npm_name,
+                    pending: replacement.pending,
+                    ..global.clone()
+                };
+            }
+            self.links.insert(name.clone(), replacement);
+            self.workspace_overrides.insert(name, WorkspaceOverride { file: file.clone(), path, global });
+        }
+    }
+
+    /// This config with workspace overrides undone, or None without any. A
+    /// link whose path was changed since loading (by `spine add`, say) keeps
+    /// the new path. A link only the workspace defines is dropped until it
+    /// has been linked somewhere; from then on it is kept, path and all, so
+    /// status and unlink know the projects it went into.
+    fn without_workspace_overrides(&self) -> Option<Config> {
+        if self.workspace_overrides.is_empty() {
+            return None;
+        }
+        let mut config = self.clone();
+        for (name, entry) in &self.workspace_overrides {
+            let Some(link) = config.links.get_mut(name) else { continue };
+            if link.path != entry.path {
+                continue;
+            }
+            match &entry.global {
+                Some(global) => {
+                    link.path = global.path.clone();
+                    link.version = global.version.clone();
+                    link.npm_name = global.npm_name.clone();
+                    link.pending = global.pending;
+                }
+                None if link.linked_projects.is_empty() && link.durable.is_empty() => {
+                    config.links.remove(name);
+                }
+                None => {}
+            }
+        }
+        Some(config)
+    }
+
+    /// Whether `name`'s path comes from .spine.toml rather than the global config
+    pub fn is_workspace_override(&self, name: &str) -> bool {
+        self.workspace_overrides.contains_key(name)
+    }
+
+    /// Load the config with the links of the active profile
+    pub fn load(file: &ConfigFile) -> Result<Self> {
+        let mut config = Self::load_main(file)?;
+        if let Some(name) = config.active_profile_name() {
+            let path = file.profile_path(&name)?;
+            if !path.exists() {
+                return Err(SpineError::Config(format!(
+                    "Profile '{}' does not exist. Create it with 'spine profile create {}' or switch with 'spine profile use default'.",
+                    name, name
+                )).into());
+            }
+            let mut profile = Self::from_toml_lenient(&Self::read_locked(&path)?)?;
+            profile.localize_paths(&config.path_map.remaps());
+            config.links = profile.links;
+            config.broken_links = profile.broken_links;
+            config.load_warnings = profile.load_warnings;
+            config.profile = Some(name);
+        }
+        config.disk_state.record(&config);
+        Ok(config)
+    }
+
+    /// Load config.toml alone, with its own links whatever profile is active
+    pub fn load_main(file: &ConfigFile) -> Result<Self> {
+        let (content, migrated_from) = Self::read_migrated(&file.path()?)?;
+        let mut config = Self::from_toml_lenient(&content)?;
+        config.file = file.clone();
+        config.localize_paths(&config.path_map.remaps());
+        config.migrated_from = migrated_from;
+        config.disk_state.record(&config);
+        Ok(config)
+    }
+
+    /// Read config.toml, upgrading an older layout in place first. The file as
+    /// it was is kept as config.toml.bak-<version>. A file from a newer Spine
+    /// is refused rather than read with its new settings missing.
+    fn read_migrated(path: &std::path::Path) -> Result<(String, Option<u32>)> {
+        let _lock = crate::lock::FileLock::acquire(path)?;
+        let content = fs::read_to_string(path)?;
+        let mut document: toml::Table = toml::from_str(&content)?;
+        let version = document.get("schema_version")
+            .and_then(toml::Value::as_integer)
+            .map(|version| u32::try_from(version).unwrap_or(u32::MAX))
+            .unwrap_or(0);
+
+        if version > CONFIG_SCHEMA_VERSION {
+            let writer = document.get("last_written_by")
+                .and_then(|stamp| stamp.get("version"))
+                .and_then(toml::Value::as_str)
+                .map(|version| format!(" by Spine {}", version))
+                .unwrap_or_default();
+            return Err(SpineError::Config(format!(
+                "{} was written{} with config schema version {}, but Spine {} only understands up to version {}. Upgrade Spine to use it.",
+                display_path(path), writer, version, SPINE_VERSION, CONFIG_SCHEMA_VERSION
+            )).into());
+        }
+        if version == CONFIG_SCHEMA_VERSION {
+            return Ok((content, None));
+        }
+
+        let mut backup_name = path.file_name().unwrap_or_default().to_os_string();
+        backup_name.push(format!(".bak-{}", version));
+        fs::write(path.with_file_name(backup_name), &content)?;
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(&mut document);
+        }
+        document.insert("schema_version".to_string(), toml::Value::Integer(CONFIG_SCHEMA_VERSION.into()));
+        let migrated = toml::to_string_pretty(&document)?;
+        write_replacing(path, &migrated)?;
+        Ok((migrated, Some(version)))
+    }
+
+    fn read_locked(path: &std::path::Path) -> Result<String> {
+        let _lock = crate::lock::FileLock::acquire(path)?;
+        Ok(fs::read_to_string(path)?)
+    }
+
+    /// The profile to use: SPINE_PROFILE, then `active_profile`. "default"
+    /// means config.toml's own links.
+    pub fn active_profile_name(&self) -> Option<String> {
+        std::env::var("SPINE_PROFILE").ok()
+            .filter(|name| !name.is_empty())
+            .or_else(|| self.active_profile.clone())
+            .filter(|name| name != DEFAULT_PROFILE)
+    }
+
+    /// Parse the config, loading each link entry on its own so one bad entry
+    /// doesn't make every command unusable. Broken entries are reported in
+    /// `load_warnings` and preserved in `broken_links`.
+    pub fn from_toml_lenient(content: &str) -> Result<Self> {
+        let mut document: toml::Table = toml::from_str(content)?;
+        let raw_links = match document.remove("links") {
+            Some(toml::Value::Table(links)) => links,
+            Some(_) => return Err(SpineError::Config("'links' must be a table".to_string()).into()),
+            None => toml::Table::new(),
+        };
+        document.insert("links".to_string(), toml::Value::Table(toml::Table::new()));
+
+        let mut config: Config = document.try_into()?;
+        for (name, raw) in raw_links {
+            match raw.clone().try_into::<PackageLink>() {
+                Ok(mut link) => {
+                    if link.created_by_version.is_none() {
+                        link.created_by_version = Some(SPINE_VERSION.to_string());
+                    }
+                    link.expand_path();
+                    config.links.insert(name, link);
+                }
+                Err(e) => {
+                    config.load_warnings.push(format!("{}: {}", name, e.to_string().trim().replace('\n', " ")));
+                    config.broken_links.insert(name, raw);
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Write the config while holding the config lock. Links and projects
+    /// another spine process added since this one loaded are kept. With a
+    /// profile active, links go to the profile's file and config.toml keeps
+    /// its own.
+    pub fn save(&self) -> Result<()> {
+        let global = self.without_workspace_overrides();
+        let global = global.as_ref().unwrap_or(self);
+        match &self.profile {
+            None => global.save_links_to(&self.file.path()?, Self::to_toml_string),
+            Some(name) => {
+                fs::create_dir_all(self.file.profiles_dir()?)?;
+                global.save_links_to(&self.file.profile_path(name)?, Self::to_profile_toml_string)?;
+                global.save_settings()
+            }
+        }
+    }
+
+    fn save_links_to(&self, path: &std::path::Path, render: impl Fn(&Config) -> Result<String>) -> Result<()> {
+        let _lock = crate::lock::FileLock::acquire(path)?;
+
+        let remaps = self.path_map.remaps();
+        let on_disk = fs::read_to_string(path).ok()
+            .and_then(|content| Self::from_toml_lenient(&content).ok())
+            .map(|mut on_disk| {
+                on_disk.localize_paths(&remaps);
+                on_disk
+            });
+        let merged = match (on_disk, self.disk_state.get()) {
+            (Some(on_disk), Some(last_seen)) => Some(self.with_concurrent_changes(&on_disk, &last_seen)),
+            _ => None,
+        };
+        let config = merged.as_ref().unwrap_or(self);
+
+        let content = render(config)?;
+        self.back_up(path, &content);
+        write_replacing(path, &content)?;
+        self.disk_state.record(config);
+        Ok(())
+    }
+
+    /// Write everything but the links to config.toml, keeping the links
+    /// stored there
+    fn save_settings(&self) -> Result<()> {
+        let config_path = self.file.path()?;
+        let _lock = crate::lock::FileLock::acquire(&config_path)?;
+
+        let mut main = self.clone();
+        main.profile = None;
+        match fs::read_to_string(&config_path).ok().and_then(|content| Self::from_toml_lenient(&content).ok()) {
+            Some(on_disk) => {
+                main.links = on_disk.links;
+                main.broken_links = on_disk.broken_links;
+            }
+            None => {
+                main.links.clear();
+                main.broken_links.clear();
+            }
+        }
+        let content = main.to_toml_string()?;
+        self.back_up(&config_path, &content);
+        write_replacing(&config_path, &content)
+    }
+
+    /// Keep a copy of `path` before `content` replaces it; a failed copy
+    /// never stops the save
+    fn back_up(&self, path: &std::path::Path, content: &str) {
+        if let Err(e) = crate::backups::snapshot(&self.file, path, content, self.backups.keep) {
+            eprintln!("Warning: Failed to back up {}: {}", crate::paths::display_path(path), e);
+        }
+    }
+
+    /// This config plus the links and linked projects `on_disk` gained since
+    /// `last_seen`
+    fn with_concurrent_changes(&self, on_disk: &Config, last_seen: &LinkedProjects) -> Config {
+        let mut merged = self.clone();
+        for (name, disk_link) in &on_disk.links {
+            match (merged.links.get_mut(name), last_seen.get(name)) {
+                (Some(link), seen) => {
+                    for project in &disk_link.linked_projects {
+                        let seen_before = seen.is_some_and(|seen| seen.iter().any(|p| crate::paths::same_path(p, project)));
+                        let known = link.linked_projects.iter().any(|p| crate::paths::same_path(p, project));
+                        if !seen_before && !known {
+                            link.linked_projects.push(project.clone());
+                            if let Some((path, at)) = disk_link.linked_at.iter().find(|(p, _)| crate::paths::same_path(p, project)) {
+                                link.linked_at.insert(path.clone(), *at);
+                            }
+                        }
+                    }
+                }
+                // Added by another process rather than removed by this one
+                (None, None) => {
+                    merged.links.insert(name.clone(), disk_link.clone());
+                }
+                (None, Some(_)) => {}
+            }
+        }
+        merged
+    }
+
+    /// Serialize the config, stamping the writer and re-emitting broken link
+    /// entries untouched
+    pub fn to_toml_string(&self) -> Result<String> {
+        let mut stamped = self.with_portable_links();
+        stamped.last_written_by = Some(WriterStamp::current());
+        stamped.schema_version = CONFIG_SCHEMA_VERSION;
+
+        if self.broken_links.is_empty() {
+            return Ok(toml::to_string_pretty(&stamped)?);
+        }
+
+        let mut document = toml::Table::try_from(&stamped)?;
+        if let Some(toml::Value::Table(links)) = document.get_mut("links") {
+            for (name, raw) in &self.broken_links {
+                if !links.contains_key(name) {
+                    links.insert(name.clone(), raw.clone());
+                }
+            }
+        }
+        Ok(toml::to_string_pretty(&document)?)
+    }
+
+    /// A profile file: only the links, plus broken entries as they were
+    fn to_profile_toml_string(&self) -> Result<String> {
+        let mut links = toml::Table::try_from(&self.with_portable_links().links)?;
+        for (name, raw) in &self.broken_links {
+            if !links.contains_key(name) {
+                links.insert(name.clone(), raw.clone());
+            }
+        }
+        let mut document = toml::Table::new();
+        document.insert("links".to_string(), toml::Value::Table(links));
+        Ok(toml::to_string_pretty(&document)?)
+    }
+
+    /// A copy whose links keep `~` and environment variables as written, with
+    /// paths under the first root of their `[path_map]` entry
+    fn with_portable_links(&self) -> Config {
+        let remaps = self.path_map.remaps();
+        let mut config = self.clone();
+        config.links = self.links.iter()
+            .map(|(name, link)| {
+                let mut link = link.portable();
+                if !remaps.is_empty() {
+                    link.map_paths(|path| canonical_path(path, &remaps));
+                }
+                (name.clone(), link)
+            })
+            .collect();
+        config
+    }
+
+    /// Point links stored under another machine's root at this one's
+    fn localize_paths(&mut self, remaps: &[PathRemap]) {
+        if remaps.is_empty() {
+            return;
+        }
+        for link in self.links.values_mut() {
+            link.map_paths(|path| local_path(path, remaps));
+        }
+    }
+
+    /// Print a one-line notice about entries that could not be loaded
+    pub fn report_load_warnings(&self) {
+        for duplicate in self.duplicate_links() {
+            eprintln!(
+                "⚠️  {} share the same {} ({}). Run 'spine verify --fix' to merge them.",
+                duplicate.links.join(" and "), duplicate.shared, duplicate.value
+            );
+        }
+        if self.load_warnings.is_empty() {
+            return;
+        }
+        let names: Vec<&String> = self.broken_links.keys().collect();
+        eprintln!(
+            "⚠️  {} link entr{} could not be loaded: {}. Run 'spine config validate' for details.",
+            names.len(),
+            if names.len() == 1 { "y" } else { "ies" },
+            names.iter().map(|n| n.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    /// Links that resolve to the same package as another: the same
+    /// directory once symlinks are followed, or the same package.json name.
+    /// Pending links have no package yet and are left out.
+    pub fn duplicate_links(&self) -> Vec<DuplicateLinks> {
+        let mut by_path: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+        let mut by_name: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for link in self.links.values().filter(|link| !link.pending) {
+            let path = crate::paths::canonicalize_cached(&link.path).unwrap_or_else(|| crate::paths::normalize_path(&link.path));
+            by_path.entry(path).or_default().push(link.name.clone());
+            let declared = link.name_mismatch().unwrap_or_else(|| link.npm_name().to_string());
+            by_name.entry(declared).or_default().push(link.name.clone());
+        }
+
+        let mut duplicates: Vec<DuplicateLinks> = Vec::new();
+        let by_path = by_path.into_iter().map(|(path, links)| ("path", display_path(&path), links));
+        let by_name = by_name.into_iter().map(|(name, links)| ("npm name", name, links));
+        for (shared, value, mut links) in by_path.chain(by_name).filter(|(_, _, links)| links.len() > 1) {
+            links.sort();
+            if let Some(existing) = duplicates.iter_mut().find(|duplicate| duplicate.links == links) {
+                existing.shared = "path and npm name";
+                continue;
+            }
+            let keep = self.suggested_keep(&links);
+            duplicates.push(DuplicateLinks { shared, value, links, keep });
+        }
+        duplicates
+    }
+
+    fn suggested_keep(&self, links: &[String]) -> String {
+        links.iter()
+            .max_by_key(|name| {
+                let link = &self.links[name.as_str()];
+                let declared = link.name_mismatch().unwrap_or_else(|| link.npm_name().to_string());
+                (link.name == declared, link.linked_projects.len(), std::cmp::Reverse(name.len()))
+            })
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Fold the `remove` links into `keep`: their projects, durable links,
+    /// per-project choices, variants, tags and group memberships move over,
+    /// then their entries go
+    pub fn merge_links(&mut self, keep: &str, remove: &[String]) -> Result<()> {
+        let mut kept = self.links.remove(keep).ok_or_else(|| SpineError::PackageNotFound(keep.to_string()))?;
+        for name in remove.iter().filter(|name| *name != keep) {
+            let Some(link) = self.links.remove(name) else { continue };
+            for project in link.linked_projects {
+                if !kept.linked_projects.iter().any(|existing| crate::paths::same_path(existing, &project)) {
+                    kept.linked_projects.push(project);
+                }
+            }
+            for (project, at) in link.linked_at {
+                let entry = kept.linked_at.entry(project).or_insert(at);
+                *entry = (*entry).max(at);
+            }
+            for (project, at) in link.last_seen {
+                let entry = kept.last_seen.entry(project).or_insert(at);
+                *entry = (*entry).max(at);
+            }
+            for durable in link.durable {
+                if !kept.is_durable_in(&durable.project) {
+                    kept.durable.push(durable);
+                }
+            }
+            for required_by in link.required_by {
+                if !kept.required_by.iter().any(|existing| crate::paths::same_path(&existing.project, &required_by.project)) {
+                    kept.required_by.push(required_by);
+                }
+            }
+            for (variant, checkout) in link.variants {
+                kept.variants.entry(variant).or_insert(checkout);
+            }
+            for (project, variant) in link.project_variants {
+                kept.project_variants.entry(project).or_insert(variant);
+            }
+            for (project, manager) in link.project_package_managers {
+                kept.project_package_managers.entry(project).or_insert(manager);
+            }
+            for tag in link.tags {
+                if !kept.tags.contains(&tag) {
+                    kept.tags.push(tag);
+                }
+            }
+            kept.note = kept.note.or(link.note);
+            kept.repository = kept.repository.or(link.repository);
+        }
+        self.links.insert(keep.to_string(), kept);
+
+        for name in remove.iter().filter(|name| *name != keep) {
+            for members in self.groups.values_mut() {
+                for member in members.iter_mut().filter(|member| *member == name) {
+                    *member = keep.to_string();
+                }
+                let mut seen = BTreeSet::new();
+                members.retain(|member| seen.insert(member.clone()));
+            }
+            for required_by in self.links.values_mut().flat_map(|link| link.required_by.iter_mut()) {
+                for package in required_by.packages.iter_mut().filter(|package| *package == name) {
+                    *package = keep.to_string();
+                }
+            }
+        }
+
+        if self.completion.auto_regenerate {
+            if let Err(e) = self.regenerate_completion() {
+                eprintln!("Warning: Failed to regenerate completion: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        println!("🔍 Validating {}", display_path(&self.file.path()?));
+        println!("  ✅ {} link entr{} loaded", self.links.len(), if self.links.len() == 1 { "y" } else { "ies" });
+
+        if self.load_warnings.is_empty() {
+            println!("\n✨ Configuration is valid.");
+            return Ok(());
+        }
+
+        println!("  ❌ {} link entr{} could not be loaded:", self.load_warnings.len(), if self.load_warnings.len() == 1 { "y" } else { "ies" });
+        for warning in &self.load_warnings {
+            println!("    • {}", warning);
+        }
+        println!("\n💡 Fix or remove these entries with 'spine config-edit'. They are kept as-is until then.");
+
+        Err(SpineError::Config(format!("{} invalid link entr{}", self.load_warnings.len(), if self.load_warnings.len() == 1 { "y" } else { "ies" })).into())
+    }
+
+    pub fn add_link(&mut self, name: String, path: String, allow_missing: bool) -> Result<()> {
+        let link = Self::new_link(name.clone(), path, allow_missing)?;
+        self.links.insert(name, link);
+
+        // Auto-regenerate completion if enabled
+        if self.completion.auto_regenerate {
+            if let Err(e) = self.regenerate_completion() {
+                eprintln!("Warning: Failed to regenerate completion: {}", e);
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// A link to `path` as `spine add` records it
+    fn new_link(name: String, path: String, allow_missing: bool) -> Result<PackageLink> {
+        let expanded = crate::paths::expand_path(&path);
+        let path_buf = crate::paths::normalize_path(std::path::Path::new(&expanded));
+
+        let pending = !path_buf.exists();
+        if pending && !allow_missing {
+            return Err(SpineError::InvalidPath(format!("Path does not exist: {}", path)).into());
+        }
+
+        // Angular 17+ builders can leave package.json in a subfolder such as browser/
+        let portable = expanded != path;
+        let (path_buf, path) = crate::angular::nested_package_root(&path_buf, &path).unwrap_or((path_buf, path));
+        let mut link = PackageLink::new(name, path_buf);
+        link.path_spec = portable.then_some(path);
+        if !pending {
+            (link.repository, link.build_path) = crate::setup::detect_source(&link.path);
+        }
+        Ok(link)
+    }
+
+    /// Add `path` as `variant` of `name`. A new link's first variant is its
+    /// default. A link without variants keeps its path as the default,
+    /// named "default" until a variant is added with that path. Adding an
+    /// existing variant points it at `path`.
+    pub fn add_variant(&mut self, name: String, variant: String, path: String, allow_missing: bool) -> Result<()> {
+        validate_variant(&variant)?;
+        let Some(existing) = self.links.get(&name) else {
+            self.add_link(name.clone(), path, allow_missing)?;
+            if let Some(link) = self.links.get_mut(&name) {
+                link.default_variant = Some(variant);
+            }
+            return Ok(());
+        };
+
+        let fresh = Self::new_link(name.clone(), path, allow_missing)?;
+        if !fresh.pending && fresh.npm_name() != existing.npm_name() {
+            return Err(SpineError::Config(format!(
+                "{} holds {}, not {}; variants must be checkouts of the same package",
+                display_path(&fresh.path), fresh.npm_name(), existing.npm_name()
+            )).into());
+        }
+        let link = self.links.get_mut(&name).expect("checked above");
+        let replaces_default = match &link.default_variant {
+            Some(default) => *default == variant || (default == DEFAULT_VARIANT && link.path == fresh.path),
+            None if link.path == fresh.path => true,
+            None => {
+                link.default_variant = Some(DEFAULT_VARIANT.to_string());
+                variant == DEFAULT_VARIANT
+            }
+        };
+        if replaces_default {
+            link.variants.remove(&variant);
+            if let Some(previous) = link.default_variant.replace(variant.clone()) {
+                for chosen in link.project_variants.values_mut().filter(|chosen| **chosen == previous) {
+                    *chosen = variant.clone();
+                }
+            }
+            link.path = fresh.path;
+            link.path_spec = fresh.path_spec;
+            link.version = fresh.version;
+            link.pending = fresh.pending;
+        } else {
+            link.variants.insert(variant, LinkVariant {
+                path: fresh.path,
+                version: fresh.version,
+                pending: fresh.pending,
+                path_spec: fresh.path_spec,
+            });
+        }
+        Ok(())
+    }
+
+    /// Make `variant` the one projects get unless they chose another.
+    /// Projects linked with the old default without choosing keep it.
+    pub fn set_default_variant(&mut self, name: &str, variant: &str) -> Result<()> {
+        let available: Vec<String> = self.links.keys().cloned().collect();
+        let link = self.links.get_mut(name)
+            .ok_or_else(|| SpineError::package_not_found_with_suggestions(name, &available))?;
+        if link.default_variant.as_deref() == Some(variant) {
+            return Ok(());
+        }
+        let chosen = link.variants.remove(variant).ok_or_else(|| unknown_variant(link, variant))?;
+        let previous_default = link.default_variant.replace(variant.to_string()).unwrap_or_else(|| DEFAULT_VARIANT.to_string());
+        let unchosen: Vec<PathBuf> = link.linked_projects.iter()
+            .filter(|project| !link.project_variants.keys().any(|entry| crate::paths::same_path(entry, project)))
+            .cloned()
+            .collect();
+        for project in unchosen {
+            link.project_variants.insert(project, previous_default.clone());
+        }
+        let replaced = LinkVariant {
+            path: std::mem::replace(&mut link.path, chosen.path),
+            version: std::mem::replace(&mut link.version, chosen.version),
+            pending: std::mem::replace(&mut link.pending, chosen.pending),
+            path_spec: std::mem::replace(&mut link.path_spec, chosen.path_spec),
+        };
+        link.previous_version = None;
+        link.variants.insert(previous_default, replaced);
+        Ok(())
+    }
+
+    /// Record that `project` links `variant` of `name`, for this and later
+    /// links and syncs there
+    pub fn select_variant(&mut self, name: &str, project: &Path, variant: &str) -> Result<()> {
+        let available: Vec<String> = self.links.keys().cloned().collect();
+        let link = self.links.get_mut(name)
+            .ok_or_else(|| SpineError::package_not_found_with_suggestions(name, &available))?;
+        if link.with_variant(variant).is_none() {
+            return Err(unknown_variant(link, variant).into());
+        }
+        let project = crate::paths::normalize_path(&project.canonicalize().unwrap_or_else(|_| project.to_path_buf()));
+        link.project_variants.retain(|entry, _| !crate::paths::same_path(entry, &project));
+        link.project_variants.insert(project, variant.to_string());
+        Ok(())
+    }
+
+    pub fn remove_link(&mut self, name: &str) -> Result<()> {
+        if self.links.remove(name).is_none() {
+            return Err(SpineError::PackageNotFound(name.to_string()).into());
+        }
+        
+        // Auto-regenerate completion if enabled
+        if self.completion.auto_regenerate {
+            if let Err(e) = self.regenerate_completion() {
+                eprintln!("Warning: Failed to regenerate completion: {}", e);
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// Give a link a new name, keeping its projects, hooks and group
+    /// memberships. Returns the projects where the old npm name is still
+    /// symlinked, when the rename changed the name npm uses.
+    pub fn rename_link(&mut self, old: &str, new: &str) -> Result<Vec<PathBuf>> {
+        if self.links.contains_key(new) {
+            return Err(SpineError::Config(format!("A link named '{}' already exists", new)).into());
+        }
+        let mut link = self.links.remove(old).ok_or_else(|| {
+            let available: Vec<String> = self.links.keys().cloned().collect();
+            SpineError::package_not_found_with_suggestions(old, &available)
+        })?;
+
+        let old_npm_name = link.npm_name().to_string();
+        link.name = new.to_string();
+        link.npm_name = crate::package::get_package_name(&link.path.join("package.json")).ok()
+            .filter(|declared| declared != new);
+        let still_linked = if link.npm_name() == old_npm_name {
+            Vec::new()
+        } else {
+            link.linked_projects.iter()
+                .filter(|project| crate::paths::node_modules_path(project, &old_npm_name).is_symlink())
+                .cloned()
+                .collect()
+        };
+        self.links.insert(new.to_string(), link);
+
+        for members in self.groups.values_mut() {
+            for member in members.iter_mut().filter(|member| *member == old) {
+                *member = new.to_string();
+            }
+        }
+        for required_by in self.links.values_mut().flat_map(|link| link.required_by.iter_mut()) {
+            for package in required_by.packages.iter_mut().filter(|package| *package == old) {
+                *package = new.to_string();
+            }
+        }
+
+        if self.completion.auto_regenerate {
+            if let Err(e) = self.regenerate_completion() {
+                eprintln!("Warning: Failed to regenerate completion: {}", e);
+            }
+        }
+        Ok(still_linked)
+    }
+
+    /// Point a link at another package directory, keeping its projects and
+    /// hooks, and read the version from the new package.json
+    pub fn set_link_path(&mut self, name: &str, path: &str) -> Result<()> {
+        let expanded = crate::paths::expand_path(path);
+        let path_buf = crate::paths::normalize_path(std::path::Path::new(&expanded));
+        let (path_buf, written) = crate::angular::nested_package_root(&path_buf, path).unwrap_or((path_buf, path.to_string()));
+        let path = written.as_str();
+        if !path_buf.join("package.json").exists() {
+            return Err(SpineError::InvalidPath(format!("No package.json found in {}", path)).into());
+        }
+        let available: Vec<String> = self.links.keys().cloned().collect();
+        let link = self.links.get_mut(name)
+            .ok_or_else(|| SpineError::package_not_found_with_suggestions(name, &available))?;
+
+        // Kept as written with `~` or variables, absolute otherwise, as `spine add` stores it
+        let (path_buf, path_spec) = if crate::paths::uses_expansion(path) {
+            (path_buf, Some(path.to_string()))
+        } else {
+            let absolute = path_buf.canonicalize()
+                .map_err(|e| SpineError::InvalidPath(format!("Could not resolve {}: {}", path, e)))?;
+            (crate::paths::normalize_path(&absolute), None)
+        };
+        let fresh = PackageLink::new(link.name.clone(), path_buf);
+        link.path = fresh.path;
+        link.path_spec = path_spec;
+        link.version = fresh.version;
+        link.pending = false;
+
+        if self.completion.auto_regenerate {
+            if let Err(e) = self.regenerate_completion() {
+                eprintln!("Warning: Failed to regenerate completion: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Add tags to a link, keeping them sorted. Returns the ones it didn't have.
+    pub fn tag_link(&mut self, name: &str, tags: &[String]) -> Result<Vec<String>> {
+        for tag in tags {
+            validate_tag(tag)?;
+        }
+        let available: Vec<String> = self.links.keys().cloned().collect();
+        let link = self.links.get_mut(name)
+            .ok_or_else(|| SpineError::package_not_found_with_suggestions(name, &available))?;
+        let mut added = Vec::new();
+        for tag in tags {
+            if !link.tags.contains(tag) && !added.contains(tag) {
+                added.push(tag.clone());
+            }
+        }
+        link.tags.extend(added.iter().cloned());
+        link.tags.sort();
+        Ok(added)
+    }
+
+    /// Remove tags from a link, or all of them when none are given. Returns
+    /// the ones it had.
+    pub fn untag_link(&mut self, name: &str, tags: &[String]) -> Result<Vec<String>> {
+        let available: Vec<String> = self.links.keys().cloned().collect();
+        let link = self.links.get_mut(name)
+            .ok_or_else(|| SpineError::package_not_found_with_suggestions(name, &available))?;
+        let removed: Vec<String> = link.tags.iter()
+            .filter(|tag| tags.is_empty() || tags.contains(tag))
+            .cloned()
+            .collect();
+        link.tags.retain(|tag| !removed.contains(tag));
+        Ok(removed)
+    }
+
+    /// Set a link's note, or clear it with None or blank text
+    pub fn set_note(&mut self, name: &str, note: Option<&str>) -> Result<()> {
+        let available: Vec<String> = self.links.keys().cloned().collect();
+        let link = self.links.get_mut(name)
+            .ok_or_else(|| SpineError::package_not_found_with_suggestions(name, &available))?;
+        link.note = note.map(str::trim).filter(|note| !note.is_empty()).map(str::to_string);
+        Ok(())
+    }
+
+    /// Every tag in use, sorted
+    pub fn all_tags(&self) -> Vec<String> {
+        let tags: BTreeSet<&String> = self.links.values().flat_map(|link| &link.tags).collect();
+        tags.into_iter().cloned().collect()
+    }
+
+    /// This config with only the links that have one of `tags`
+    pub fn with_tags(&self, tags: &[String]) -> Config {
+        let mut config = self.clone();
+        config.links.retain(|_, link| link.has_any_tag(tags));
+        config
+    }
+
+    /// Add packages to a group, creating it if needed
+    pub fn add_to_group(&mut self, group: &str, packages: &[String]) -> Result<()> {
+        for package in packages {
+            if !self.links.contains_key(package) {
+                let available: Vec<String> = self.links.keys().cloned().collect();
+                return Err(SpineError::package_not_found_with_suggestions(package, &available).into());
+            }
+        }
+
+        let members = self.groups.entry(group.to_string()).or_default();
+        for package in packages {
+            if !members.contains(package) {
+                members.push(package.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove packages from a group, or the whole group when none are given
+    pub fn remove_from_group(&mut self, group: &str, packages: &[String]) -> Result<()> {
+        let available: Vec<String> = self.groups.keys().cloned().collect();
+        let members = self.groups.get_mut(group)
+            .ok_or_else(|| SpineError::group_not_found_with_suggestions(group, &available))?;
+
+        if packages.is_empty() {
+            self.groups.remove(group);
+            return Ok(());
+        }
+
+        for package in packages {
+            if !members.contains(package) {
+                return Err(SpineError::package_not_found_with_suggestions(package, members).into());
+            }
+        }
+        members.retain(|member| !packages.contains(member));
+        if members.is_empty() {
+            self.groups.remove(group);
         }
-        
-        Ok(spine_dir.join("config.toml"))
+        Ok(())
     }
 
-    pub fn load_or_create() -> Result<Self> {
-        let config_path = Self::config_path()?;
-        
-        if config_path.exists() {
-            Self::load()
-        } else {
-            let config = Self::default();
-            config.save()?;
-            Ok(config)
+    /// The packages in a group. Members that were removed from `links` since
+    /// being grouped are an error, with suggestions.
+    pub fn expand_group(&self, group: &str) -> Result<Vec<String>> {
+        let members = self.groups.get(group).ok_or_else(|| {
+            let available: Vec<String> = self.groups.keys().cloned().collect();
+            SpineError::group_not_found_with_suggestions(group, &available)
+        })?;
+
+        for member in members {
+            if !self.links.contains_key(member) {
+                let available: Vec<String> = self.links.keys().cloned().collect();
+                return Err(SpineError::package_not_found_with_suggestions(member, &available).into());
+            }
         }
+        Ok(members.clone())
     }
 
-    pub fn load() -> Result<Self> {
-        let config_path = Self::config_path()?;
-        let content = fs::read_to_string(&config_path)?;
-        let config: Config = toml::from_str(&content)?;
-        Ok(config)
+    /// Names of the groups a package belongs to, sorted
+    pub fn groups_of(&self, package: &str) -> Vec<&str> {
+        let mut groups: Vec<&str> = self.groups.iter()
+            .filter(|(_, members)| members.iter().any(|m| m == package))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        groups.sort();
+        groups
     }
 
-    pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_path()?;
-        let content = toml::to_string_pretty(self)?;
-        fs::write(&config_path, content)?;
-        Ok(())
+    pub fn list_groups(&self) {
+        if self.groups.is_empty() {
+            println!("No groups configured. Create one with 'spine group add <group> <packages...>'.");
+            return;
+        }
+
+        let mut names: Vec<&String> = self.groups.keys().collect();
+        names.sort();
+        println!("Groups:");
+        for name in names {
+            let members = &self.groups[name];
+            println!("  {} ({} package{})", name, members.len(), if members.len() == 1 { "" } else { "s" });
+            for member in members {
+                let marker = if self.links.contains_key(member) { "" } else { " ⚠️  not configured" };
+                println!("    • {}{}", member, marker);
+            }
+        }
     }
 
-    pub fn add_link(&mut self, name: String, path: String) -> Result<()> {
-        let path_buf = PathBuf::from(&path);
-        
-        if !path_buf.exists() {
-            return Err(SpineError::InvalidPath(format!("Path does not exist: {}", path)).into());
+    /// The effective value at a dotted key such as `completion.auto_regenerate`
+    /// or `links.<package>.path`; the whole config without a key
+    pub fn get_value(&self, key: Option<&str>) -> Result<serde_json::Value> {
+        let mut document = serde_json::to_value(self)?;
+        crate::config_keys::strip_nulls(&mut document);
+        let Some(key) = key else { return Ok(document) };
+        let path = crate::config_keys::resolve(&document, &crate::schema::config_schema(), key)?;
+        self.ensure_link_exists(&path)?;
+        crate::config_keys::get(&document, &path.segments).cloned()
+            .or_else(|| path.schema.get("default").cloned())
+            .ok_or_else(|| SpineError::Config(format!("'{}' is not set", key)).into())
+    }
+
+    /// Set a value addressed by a dotted key, parsed by the type the schema
+    /// gives it. An empty value unsets an optional setting or empties a list
+    /// (a hook takes one command or a JSON array). A link's path must exist
+    /// unless `allow_missing`, in which case the link becomes pending.
+    pub fn set_value(&mut self, key: &str, value: &str, allow_missing: bool) -> Result<()> {
+        let schema = crate::schema::config_schema();
+        let mut document = serde_json::to_value(self.with_portable_links())?;
+        crate::config_keys::strip_nulls(&mut document);
+        let path = crate::config_keys::resolve(&document, &schema, key)?;
+        self.ensure_link_exists(&path)?;
+        if path.is_managed() {
+            return Err(SpineError::Config(format!("'{}' is maintained by Spine and can't be set", key)).into());
         }
 
-        let package_json_path = path_buf.join("package.json");
-        let version = if package_json_path.exists() {
-            crate::package::get_package_version(&package_json_path).ok()
-        } else {
-            None
-        };
+        let parsed = crate::config_keys::parse(value, &path.schema, key)?;
+        if path.is_link_field("path") {
+            if let Some(target) = parsed.as_ref().and_then(|parsed| parsed.as_str()) {
+                if !allow_missing && !std::path::Path::new(&crate::paths::expand_path(target)).exists() {
+                    return Err(SpineError::InvalidPath(format!(
+                        "Path does not exist: {} (use --allow-missing to set it anyway)", target
+                    )).into());
+                }
+            }
+        }
+        crate::config_keys::set(&mut document, &path.segments, parsed);
 
-        let link = PackageLink {
-            name: name.clone(),
-            path: path_buf,
-            version,
-            linked_projects: Vec::new(),
-        };
+        let errors = crate::schema::validate(&document, &schema);
+        if !errors.is_empty() {
+            return Err(SpineError::Config(format!("Invalid value for {}: {}", key, errors.join("; "))).into());
+        }
+        let mut updated: Config = serde_json::from_value(document)
+            .map_err(|e| SpineError::Config(format!("Invalid value for {}: {}", key, e)))?;
+        updated.links.values_mut().for_each(PackageLink::expand_path);
 
-        self.links.insert(name, link);
-        
-        // Auto-regenerate completion if enabled
-        if self.completion.auto_regenerate {
-            if let Err(e) = self.regenerate_completion() {
-                eprintln!("Warning: Failed to regenerate completion: {}", e);
+        if let (true, Some(name)) = (path.is_link_field("path"), path.link()) {
+            if let Some(link) = updated.links.get_mut(name) {
+                let fresh = PackageLink::new(link.name.clone(), link.path.clone());
+                link.pending = fresh.pending;
+                link.version = fresh.version;
             }
         }
-        
+
+        // Carry over what isn't part of the serialized config
+        updated.profile = self.profile.take();
+        updated.migrated_from = self.migrated_from;
+        updated.broken_links = std::mem::take(&mut self.broken_links);
+        updated.load_warnings = std::mem::take(&mut self.load_warnings);
+        updated.workspace_overrides = std::mem::take(&mut self.workspace_overrides);
+        updated.disk_state = self.disk_state.clone();
+        *self = updated;
         Ok(())
     }
 
-    pub fn remove_link(&mut self, name: &str) -> Result<()> {
-        if self.links.remove(name).is_none() {
-            return Err(SpineError::PackageNotFound(name.to_string()).into());
-        }
-        
-        // Auto-regenerate completion if enabled
-        if self.completion.auto_regenerate {
-            if let Err(e) = self.regenerate_completion() {
-                eprintln!("Warning: Failed to regenerate completion: {}", e);
+    fn ensure_link_exists(&self, path: &crate::config_keys::KeyPath) -> Result<()> {
+        match path.link() {
+            Some(name) if !self.links.contains_key(name) => {
+                let available: Vec<String> = self.links.keys().cloned().collect();
+                Err(SpineError::package_not_found_with_suggestions(name, &available).into())
             }
+            _ => Ok(()),
         }
-        
-        Ok(())
     }
 
-    pub fn list_links(&self) {
+    pub fn list_links(&self, detailed: bool, tags: &[String]) {
         if self.links.is_empty() {
             println!("No package links configured.");
             return;
         }
+        if !self.links.values().any(|link| link.has_any_tag(tags)) {
+            println!("No package links tagged {}.", tags.join(" or "));
+            return;
+        }
 
-        println!("Package Links:");
+        match &self.profile {
+            Some(profile) => println!("Package Links (profile '{}'):", profile),
+            None => println!("Package Links:"),
+        }
         
         // Sort packages alphabetically by name
-        let mut sorted_links: Vec<_> = self.links.values().collect();
+        let mut sorted_links: Vec<_> = self.links.values().filter(|link| link.has_any_tag(tags)).collect();
         sorted_links.sort_by(|a, b| a.name.cmp(&b.name));
         
         for link in sorted_links {
-            let version_str = link.version.as_deref().unwrap_or("unknown");
-            println!("  {} (v{}) -> {}", link.name, version_str, link.path.display());
+            let vendored = if link.is_vendored() { " [vendored]" } else { "" };
+            let workspace = if self.is_workspace_override(&link.name) { " (workspace)" } else { "" };
+            let tag_list = link.tag_label();
+            let variant = link.default_variant.as_ref().map(|variant| format!(" [{}]", variant)).unwrap_or_default();
+            println!("  {}{} ({}){}{}{} -> {}", link.name, variant, link.version_label(), vendored, workspace, tag_list, display_path(&link.path));
+            if link.pending {
+                println!("    ⏳ {}", PENDING_MARKER);
+            }
+            for (name, other) in &link.variants {
+                let pending = if other.pending { format!(" ⏳ {}", PENDING_MARKER) } else { String::new() };
+                println!("    ↳ [{}] (v{}) -> {}{}", name, other.version.as_deref().unwrap_or("unknown"), display_path(&other.path), pending);
+            }
             
             if !link.linked_projects.is_empty() {
                 println!("    Linked to {} project(s):", link.linked_projects.len());
                 for project in &link.linked_projects {
-                    println!("      {}", project.display());
+                    let age = link.linked_age(project).map(|age| format!(" ({})", age)).unwrap_or_default();
+                    let variant = link.variant_label(project);
+                    if detailed {
+                        let (manager, source) = link.effective_package_manager(project, self.package_manager);
+                        println!("      {}{}{}, {} ({})", display_path(project), variant, age, manager, source);
+                    } else {
+                        println!("      {}{}{}", display_path(project), variant, age);
+                    }
+                }
+            }
+            
+            if detailed {
+                if let Some(manager) = link.package_manager {
+                    println!("    📦 Links with {}", manager);
+                }
+                for (project, manager) in &link.project_package_managers {
+                    println!("    📦 Links with {} in {}", manager, display_path(project));
+                }
+                if let Some(note) = &link.note {
+                    println!("    🗒️  {}", note);
+                }
+                if let Some(spec) = link.path_spec.as_ref().filter(|spec| **spec != display_path(&link.path)) {
+                    println!("    📝 Written as {}", spec);
+                }
+                for point in HookPoint::ALL {
+                    for hook in link.hooks(point) {
+                        println!("    🪝 {}: {}", point.name(), hook);
+                    }
                 }
             }
         }
     }
 
     pub fn add_linked_project(&mut self, package_name: &str, project_path: PathBuf) -> Result<()> {
+        let canonical_path = crate::paths::normalize_path(&project_path.canonicalize()
+            .unwrap_or(project_path));
+        if let Some(prefix) = self.never_recorded_prefix(&canonical_path) {
+            eprintln!("ℹ️  Not recording {} for {}: it is under projects.never_record {}",
+                display_path(&canonical_path), package_name, display_path(prefix));
+            return Ok(());
+        }
         let link = self.links.get_mut(package_name)
             .ok_or_else(|| SpineError::PackageNotFound(package_name.to_string()))?;
         
-        let canonical_path = project_path.canonicalize()
-            .unwrap_or(project_path);
-        
-        if !link.linked_projects.contains(&canonical_path) {
-            link.linked_projects.push(canonical_path);
+        if !link.linked_projects.iter().any(|p| crate::paths::same_path(p, &canonical_path)) {
+            link.linked_projects.push(canonical_path.clone());
         }
+        link.linked_at.retain(|p, _| !crate::paths::same_path(p, &canonical_path));
+        link.linked_at.insert(canonical_path, crate::history::now_timestamp());
         
         Ok(())
     }
 
+    /// The projects.never_record prefix `project` falls under, if any
+    pub fn never_recorded_prefix(&self, project: &std::path::Path) -> Option<&PathBuf> {
+        let project = crate::paths::normalize_path(project);
+        self.projects.never_record.iter()
+            .find(|prefix| project.starts_with(crate::paths::normalize_path(prefix)))
+    }
+
+    /// Recorded projects that are throwaway: under a never_record prefix, or
+    /// missing for longer than `max_missing_secs` (any missing project when
+    /// None). Each comes with the reason.
+    pub fn stale_projects(&self, now: u64, max_missing_secs: Option<u64>) -> Vec<(String, PathBuf, String)> {
+        let mut stale = Vec::new();
+        for (name, link) in &self.links {
+            for project in &link.linked_projects {
+                if let Some(prefix) = self.never_recorded_prefix(project) {
+                    stale.push((name.clone(), project.clone(), format!("under never_record {}", display_path(prefix))));
+                    continue;
+                }
+                if project.exists() {
+                    continue;
+                }
+                let last_known = link.last_known(project);
+                let expired = match (max_missing_secs, last_known) {
+                    (None, _) => true,
+                    (Some(limit), Some(at)) => now.saturating_sub(at) > limit,
+                    // The clock starts at the next status or sync
+                    (Some(_), None) => false,
+                };
+                if expired {
+                    let reason = match last_known {
+                        Some(at) => format!("missing, last seen {}", crate::history::format_age(at)),
+                        None => "missing".to_string(),
+                    };
+                    stale.push((name.clone(), project.clone(), reason));
+                }
+            }
+        }
+        stale.sort();
+        stale
+    }
+
+    /// Stamp `last_seen` on linked projects that exist (and on missing ones
+    /// with no timestamp at all, starting their expiry clock), then forget
+    /// projects missing for over projects.expire_after_days with a notice.
+    /// Saves only when something changed.
+    pub fn refresh_linked_projects(&mut self) -> Result<()> {
+        let now = crate::history::now_timestamp();
+        let mut changed = false;
+        for link in self.links.values_mut() {
+            for project in link.linked_projects.clone() {
+                let stale_stamp = link.last_known(&project).is_none_or(|at| now.saturating_sub(at) >= LAST_SEEN_RESOLUTION_SECS);
+                let first_check = link.last_known(&project).is_none();
+                if (project.exists() && stale_stamp) || first_check {
+                    link.last_seen.retain(|p, _| !crate::paths::same_path(p, &project));
+                    link.last_seen.insert(project, now);
+                    changed = true;
+                }
+            }
+        }
+
+        if self.projects.expire_after_days > 0 {
+            let expired: Vec<(String, PathBuf, String)> = self.stale_projects(now, Some(self.projects.expire_after_days * 24 * 60 * 60))
+                .into_iter()
+                .filter(|(_, project, _)| !project.exists())
+                .collect();
+            if !expired.is_empty() {
+                eprintln!("🧹 Forgot {} linked project(s) missing for over {} days:", expired.len(), self.projects.expire_after_days);
+                for (name, project, _) in &expired {
+                    eprintln!("   • {} in {}", name, display_path(project));
+                    self.remove_linked_project(name, project)?;
+                }
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
     pub fn remove_linked_project(&mut self, package_name: &str, project_path: &PathBuf) -> Result<()> {
         let link = self.links.get_mut(package_name)
             .ok_or_else(|| SpineError::PackageNotFound(package_name.to_string()))?;
@@ -163,32 +2194,181 @@ impl Config {
         let canonical_path = project_path.canonicalize()
             .unwrap_or_else(|_| project_path.clone());
         
-        link.linked_projects.retain(|p| p != &canonical_path);
+        link.linked_projects.retain(|p| !crate::paths::same_path(p, &canonical_path));
+        link.required_by.retain(|r| !crate::paths::same_path(&r.project, &canonical_path));
+        link.prune_linked_at();
         
         Ok(())
     }
 
-    pub fn verify_and_clean_links(&mut self) -> Result<Vec<String>> {
+    /// Record that `dependents` pulled `package_name` into `project`
+    pub fn add_required_by(&mut self, package_name: &str, project: &std::path::Path, dependents: &[String]) -> Result<()> {
+        let link = self.links.get_mut(package_name)
+            .ok_or_else(|| SpineError::PackageNotFound(package_name.to_string()))?;
+
+        let index = match link.required_by.iter().position(|r| crate::paths::same_path(&r.project, project)) {
+            Some(index) => index,
+            None => {
+                link.required_by.push(RequiredBy { project: project.to_path_buf(), packages: Vec::new() });
+                link.required_by.len() - 1
+            }
+        };
+        let packages = &mut link.required_by[index].packages;
+        for dependent in dependents {
+            if !packages.contains(dependent) {
+                packages.push(dependent.clone());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add_durable_link(&mut self, package_name: &str, durable: DurableLink) -> Result<()> {
+        let link = self.links.get_mut(package_name)
+            .ok_or_else(|| SpineError::PackageNotFound(package_name.to_string()))?;
+
+        if !link.is_durable_in(&durable.project) {
+            link.durable.push(durable);
+        }
+        Ok(())
+    }
+
+    pub fn remove_durable_link(&mut self, package_name: &str, project_path: &std::path::Path) -> Result<()> {
+        let link = self.links.get_mut(package_name)
+            .ok_or_else(|| SpineError::PackageNotFound(package_name.to_string()))?;
+
+        link.durable.retain(|d| !crate::paths::same_path(&d.project, project_path));
+        Ok(())
+    }
+
+    /// Turn pending links whose path now exists into normal links, reading
+    /// their version. Saves the config when anything was promoted.
+    pub fn promote_pending(&mut self) -> Result<Vec<String>> {
+        let promoted = self.activate_pending();
+
+        for link in &promoted {
+            if let Some(declared) = &link.renamed {
+                println!("⚠️  {} is published as {}; using that name for npm", link.name, declared);
+            }
+            println!("✨ Pending link {} is now available (v{})", link.label(), link.version.as_deref().unwrap_or("unknown"));
+        }
+
+        if !promoted.is_empty() {
+            self.save()?;
+        }
+        Ok(promoted.iter().map(Promoted::label).collect())
+    }
+
+    /// Activate pending links and variants whose path now exists, without
+    /// printing or saving
+    pub fn activate_pending(&mut self) -> Vec<Promoted> {
+        let mut promoted = Vec::new();
+
+        for (name, link) in self.links.iter_mut() {
+            if link.pending && link.path.exists() {
+                link.pending = false;
+                settle_package_root(&mut link.path, &mut link.path_spec);
+                link.version = crate::package::get_package_version(&link.path.join("package.json")).ok();
+                let renamed = link.name_mismatch();
+                if let Some(declared) = &renamed {
+                    link.set_npm_name(declared.clone());
+                }
+                promoted.push(Promoted { name: name.clone(), variant: None, renamed, version: link.version.clone() });
+            }
+            for (variant_name, variant) in link.variants.iter_mut() {
+                if !variant.pending || !variant.path.exists() {
+                    continue;
+                }
+                variant.pending = false;
+                settle_package_root(&mut variant.path, &mut variant.path_spec);
+                variant.version = crate::package::get_package_version(&variant.path.join("package.json")).ok();
+                promoted.push(Promoted {
+                    name: name.clone(),
+                    variant: Some(variant_name.clone()),
+                    renamed: None,
+                    version: variant.version.clone(),
+                });
+            }
+        }
+
+        promoted.sort();
+        promoted
+    }
+
+    /// Re-read each package's package.json and update stored versions that drifted
+    pub fn refresh_versions(&mut self) -> VersionRefreshReport {
+        let mut report = VersionRefreshReport::default();
+
+        for (name, link) in self.links.iter_mut() {
+            if link.path.exists() {
+                match link.sync_version() {
+                    Ok(Some(update)) => report.updated.push(update),
+                    Ok(None) => {}
+                    Err(_) => report.unreadable.push(name.clone()),
+                }
+            }
+            for (variant_name, variant) in link.variants.iter_mut().filter(|(_, variant)| !variant.pending && variant.path.exists()) {
+                let label = format!("{} [{}]", name, variant_name);
+                match crate::package::get_package_version(&variant.path.join("package.json")) {
+                    Ok(current) if variant.version.as_deref() != Some(current.as_str()) => {
+                        let previous = variant.version.replace(current.clone());
+                        report.updated.push(VersionUpdate { package: label, previous, current });
+                    }
+                    Ok(_) => {}
+                    Err(_) => report.unreadable.push(label),
+                }
+            }
+        }
+
+        report.updated.sort();
+        report.unreadable.sort();
+        report
+    }
+
+    /// `refresh_versions` when `track_versions` is on; nothing otherwise
+    pub fn sync_tracked_versions(&mut self) -> Vec<VersionUpdate> {
+        if !self.track_versions {
+            return Vec::new();
+        }
+        self.refresh_versions().updated
+    }
+
+    /// Update one link's stored version when `track_versions` is on
+    pub fn sync_tracked_version(&mut self, package_name: &str) -> Option<VersionUpdate> {
+        if !self.track_versions {
+            return None;
+        }
+        let link = self.links.get_mut(package_name).filter(|link| link.path.exists())?;
+        link.sync_version().ok().flatten()
+    }
+
+    /// Forget recorded projects whose node_modules no longer has the link.
+    /// Returns each forgotten (package, project).
+    pub fn verify_and_clean_links(&mut self) -> Result<Vec<(String, PathBuf)>> {
         let mut removed_links = Vec::new();
         let package_names: Vec<String> = self.links.keys().cloned().collect();
         
         for package_name in package_names {
             let mut valid_projects = Vec::new();
-            let linked_projects = self.links.get(&package_name).unwrap().linked_projects.clone();
+            let link = self.links.get(&package_name).unwrap();
+            // Until a mismatched name is fixed, npm links under the package.json name
+            let npm_names: Vec<String> = std::iter::once(link.npm_name().to_string()).chain(link.name_mismatch()).collect();
+            let linked_projects = link.linked_projects.clone();
             
             for project_path in &linked_projects {
-                if Self::is_package_linked_in_project_static(&package_name, project_path) {
+                if npm_names.iter().any(|npm_name| Self::is_package_linked_in_project_static(npm_name, project_path)) {
                     valid_projects.push(project_path.clone());
                 } else {
-                    removed_links.push(format!("{} from {}", package_name, project_path.display()));
+                    removed_links.push((package_name.clone(), project_path.clone()));
                 }
             }
             
             if let Some(link) = self.links.get_mut(&package_name) {
                 link.linked_projects = valid_projects;
+                link.prune_linked_at();
             }
         }
         
+        removed_links.sort();
         Ok(removed_links)
     }
 
@@ -197,67 +2377,36 @@ impl Config {
     }
 
     pub fn is_package_linked_in_project_static(package_name: &str, project_path: &PathBuf) -> bool {
-        let node_modules = project_path.join("node_modules");
-        if !node_modules.exists() {
+        if !project_path.join("node_modules").exists() {
             return false;
         }
         
-        let package_path = if package_name.starts_with('@') {
-            let parts: Vec<&str> = package_name.splitn(2, '/').collect();
-            if parts.len() == 2 {
-                node_modules.join(parts[0]).join(parts[1])
-            } else {
-                node_modules.join(package_name)
-            }
-        } else {
-            node_modules.join(package_name)
-        };
+        let package_path = crate::paths::node_modules_path(project_path, package_name);
         
-        // Check if it's a valid symlink pointing to an existing target
+        // Check if it's a valid symlink pointing to an existing target. Under
+        // pnpm every dependency is a symlink into node_modules/.pnpm, so those
+        // are regular installs rather than links.
         package_path.is_symlink() && 
         package_path.read_link().is_ok() && 
-        package_path.exists()
+        package_path.exists() &&
+        !crate::pm::is_pnpm_store_entry(project_path, &package_path)
     }
 
-    pub fn sync_with_filesystem(&mut self) -> Result<SyncReport> {
-        let mut report = SyncReport::new();
-        let current_dir = std::env::current_dir()?;
-        
-        // Check all configured packages for invalid links
-        for (package_name, package_link) in &mut self.links {
-            let mut valid_projects = Vec::new();
-            
-            for project_path in &package_link.linked_projects {
-                let is_actually_linked = Self::is_package_linked_in_project_static(package_name, project_path);
-                
-                if is_actually_linked {
-                    valid_projects.push(project_path.clone());
-                } else {
-                    report.removed_invalid_links.push(format!("{} from {}", package_name, project_path.display()));
-                }
-            }
-            
-            package_link.linked_projects = valid_projects;
-            
-            // Check if package is linked to current project but not in config
-            if Self::is_package_linked_in_project_static(package_name, &current_dir) {
-                if !package_link.linked_projects.contains(&current_dir) {
-                    package_link.linked_projects.push(current_dir.clone());
-                    report.added_missing_links.push(format!("{} to {}", package_name, current_dir.display()));
-                }
-            }
-        }
-        
-        // Detect packages linked but not in config
-        if let Ok(linked_packages) = crate::npm::NpmManager::get_linked_packages_static() {
-            for package_name in linked_packages {
-                if !self.links.contains_key(&package_name) {
-                    report.untracked_links.push(package_name);
-                }
-            }
+    /// The configured link installed in node_modules under `npm_name`
+    pub fn find_by_npm_name(&self, npm_name: &str) -> Option<&PackageLink> {
+        self.links.get(npm_name)
+            .filter(|link| link.npm_name() == npm_name)
+            .or_else(|| self.links.values().find(|link| link.npm_name() == npm_name))
+    }
+
+    /// Configured name for a package given by alias or by npm name
+    pub fn link_name(&self, name: &str) -> String {
+        if self.links.contains_key(name) {
+            return name.to_string();
         }
-        
-        Ok(report)
+        self.find_by_npm_name(name)
+            .map(|link| link.name.clone())
+            .unwrap_or_else(|| name.to_string())
     }
 
     pub fn get_links(&self) -> Vec<&PackageLink> {
@@ -269,13 +2418,19 @@ impl Config {
         
         // Detect shell if not provided
         let detected_shell = shell.or_else(|| Platform::detect_current_shell());
+        let same_shell = detected_shell.is_some() && detected_shell == self.completion.shell;
         self.completion.shell = detected_shell.clone();
         
-        // Set default script path if not provided
-        if script_path.is_none() && detected_shell.is_some() {
-            self.completion.script_path = Self::get_default_completion_path(&detected_shell.as_ref().unwrap());
-        } else {
-            self.completion.script_path = script_path;
+        // Set default script path if not provided, keeping a script installed
+        // with 'spine completions install' for the same shell
+        let keep_installed = same_shell && self.completion.script_path.is_some();
+        match (script_path, &detected_shell) {
+            (None, Some(shell)) => {
+                if !keep_installed {
+                    self.completion.script_path = Self::get_default_completion_path(shell);
+                }
+            }
+            (script_path, _) => self.completion.script_path = script_path,
         }
         
         // Initial generation
@@ -306,7 +2461,7 @@ impl Config {
         Ok(())
     }
     
-    fn regenerate_completion(&self) -> Result<()> {
+    pub fn regenerate_completion(&self) -> Result<()> {
         if !self.completion.auto_regenerate {
             return Ok(());
         }
@@ -332,11 +2487,7 @@ impl Config {
             _ => return Err(SpineError::Config(format!("Unsupported shell: {}", shell)).into()),
         };
         
-        let mut cmd = crate::cli::Cli::command();
-        let mut output = Vec::new();
-        crate::completion::generate_completions(shell_enum, &mut cmd, "spine", &mut output);
-        
-        fs::write(script_path, output)?;
+        fs::write(script_path, crate::completion::completion_script(shell_enum))?;
         
         Ok(())
     }
@@ -349,19 +2500,407 @@ impl Config {
     }
 }
 
-#[derive(Debug)]
-pub struct SyncReport {
-    pub removed_invalid_links: Vec<String>,
-    pub added_missing_links: Vec<String>,
-    pub untracked_links: Vec<String>,
+/// Configured packages that resolve to one package, so linking or unlinking
+/// under one name undoes the other's bookkeeping
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateLinks {
+    /// "path", "npm name" or "path and npm name"
+    pub shared: &'static str,
+    /// The canonical path or package.json name they share
+    pub value: String,
+    /// Link names, sorted
+    pub links: Vec<String>,
+    /// The link `spine verify --fix` keeps unless told otherwise: the one
+    /// named as npm knows the package, then the one with the most projects
+    pub keep: String,
 }
 
-impl SyncReport {
-    pub fn new() -> Self {
-        Self {
-            removed_invalid_links: Vec::new(),
-            added_missing_links: Vec::new(),
-            untracked_links: Vec::new(),
+/// A stored version that no longer matched the package's package.json
+/// A pending link, or one of its variants, whose path now exists
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Promoted {
+    pub name: String,
+    pub variant: Option<String>,
+    /// The npm name the link switched to, when package.json names it differently
+    pub renamed: Option<String>,
+    pub version: Option<String>,
+}
+
+impl Promoted {
+    /// "ui", or "ui [ng17]" for a variant
+    pub fn label(&self) -> String {
+        match &self.variant {
+            Some(variant) => format!("{} [{}]", self.name, variant),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// A build may put package.json in a subfolder such as browser/; point
+/// `path` (and `path_spec`, as written) there
+fn settle_package_root(path: &mut PathBuf, path_spec: &mut Option<String>) {
+    let written = path_spec.clone().unwrap_or_default();
+    if let Some((root, written)) = crate::angular::nested_package_root(path, &written) {
+        *path_spec = path_spec.is_some().then_some(written);
+        *path = root;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct VersionUpdate {
+    pub package: String,
+    pub previous: Option<String>,
+    pub current: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct VersionRefreshReport {
+    pub updated: Vec<VersionUpdate>,
+    /// Packages whose path exists but whose package.json can't be read
+    pub unreadable: Vec<String>,
+}
+
+impl VersionRefreshReport {
+    pub fn is_clean(&self) -> bool {
+        self.updated.is_empty() && self.unreadable.is_empty()
+    }
+
+    pub fn print(&self) {
+        for update in &self.updated {
+            println!("  🔄 {}: {} → {}", update.package, update.previous.as_deref().unwrap_or("unknown"), update.current);
+        }
+        for name in &self.unreadable {
+            println!("  ⚠️  {}: package.json is missing or unreadable", name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn write_package(dir: &Path, name: &str, version: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("package.json"), format!(r#"{{ "name": "{}", "version": "{}" }}"#, name, version)).unwrap();
+    }
+
+    /// A config with lib-a recorded in `projects`, each last seen at the
+    /// paired timestamp
+    fn config_with(projects: &[(&Path, Option<u64>)]) -> Config {
+        let mut link = PackageLink::new("lib-a".to_string(), PathBuf::from("/nonexistent/lib-a"));
+        for (project, seen) in projects {
+            link.linked_projects.push(project.to_path_buf());
+            if let Some(at) = seen {
+                link.last_seen.insert(project.to_path_buf(), *at);
+            }
+        }
+        let mut config = Config::default();
+        config.links.insert("lib-a".to_string(), link);
+        config
+    }
+
+    fn stale_paths(config: &Config, now: u64, max_missing_secs: Option<u64>) -> Vec<PathBuf> {
+        config.stale_projects(now, max_missing_secs).into_iter().map(|(_, project, _)| project).collect()
+    }
+
+    #[test]
+    fn existing_projects_are_never_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config_with(&[(dir.path(), None)]);
+        assert!(stale_paths(&config, 100 * DAY, None).is_empty());
+        assert!(stale_paths(&config, 100 * DAY, Some(DAY)).is_empty());
+    }
+
+    #[test]
+    fn missing_projects_expire_only_past_the_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let recent = dir.path().join("recent");
+        let old = dir.path().join("old");
+        let unseen = dir.path().join("unseen");
+        let now = 100 * DAY;
+        let config = config_with(&[(&recent, Some(now - DAY)), (&old, Some(now - 40 * DAY)), (&unseen, None)]);
+
+        // `prune --projects` forgets every missing project
+        assert_eq!(stale_paths(&config, now, None), vec![old.clone(), recent, unseen]);
+        // Expiry keeps recently seen ones and starts the clock on unseen ones
+        assert_eq!(stale_paths(&config, now, Some(30 * DAY)), vec![old]);
+    }
+
+    #[test]
+    fn projects_under_never_record_are_stale_even_when_present() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = dir.path().join("scratch");
+        let project = scratch.join("app");
+        fs::create_dir_all(&project).unwrap();
+        let mut config = config_with(&[(&project, Some(100 * DAY))]);
+        config.projects.never_record.push(scratch);
+
+        let stale = config.stale_projects(100 * DAY, Some(30 * DAY));
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].1, project);
+        assert!(stale[0].2.starts_with("under never_record"), "{}", stale[0].2);
+    }
+
+    #[test]
+    fn never_record_prefixes_are_not_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let scratch = dir.path().join("scratch");
+        let kept = dir.path().join("kept");
+        fs::create_dir_all(scratch.join("app")).unwrap();
+        fs::create_dir_all(&kept).unwrap();
+        let mut config = config_with(&[]);
+        config.projects.never_record.push(scratch.clone());
+
+        config.add_linked_project("lib-a", scratch.join("app")).unwrap();
+        config.add_linked_project("lib-a", kept.clone()).unwrap();
+        config.add_linked_project("lib-a", kept.clone()).unwrap();
+
+        let link = &config.links["lib-a"];
+        assert_eq!(link.linked_projects.len(), 1);
+        assert!(crate::paths::same_path(&link.linked_projects[0], &kept));
+        assert!(link.last_known(&kept).is_some());
+    }
+
+    #[test]
+    fn forgetting_a_project_drops_its_timestamps() {
+        let dir = tempfile::tempdir().unwrap();
+        let gone = dir.path().join("gone");
+        let kept = dir.path().join("kept");
+        let mut config = config_with(&[(&gone, Some(DAY)), (&kept, Some(DAY))]);
+
+        for (name, project, _) in config.stale_projects(100 * DAY, None) {
+            config.remove_linked_project(&name, &project).unwrap();
+        }
+
+        let link = &config.links["lib-a"];
+        assert!(link.linked_projects.is_empty());
+        assert!(link.last_seen.is_empty());
+        assert!(config.stale_projects(100 * DAY, None).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_projects_linked_under_the_package_json_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = dir.path().join("ui");
+        fs::create_dir_all(&library).unwrap();
+        fs::write(library.join("package.json"), r#"{ "name": "@acme/ui", "version": "1.0.0" }"#).unwrap();
+        // npm links under the package.json name, not the configured "ui"
+        let app = dir.path().join("app");
+        fs::create_dir_all(app.join("node_modules").join("@acme")).unwrap();
+        std::os::unix::fs::symlink(&library, app.join("node_modules").join("@acme").join("ui")).unwrap();
+        let gone = dir.path().join("gone");
+
+        let mut link = PackageLink::new("ui".to_string(), library);
+        link.linked_projects = vec![app.clone(), gone.clone()];
+        let mut config = Config::default();
+        config.links.insert("ui".to_string(), link);
+
+        assert_eq!(config.verify_and_clean_links().unwrap(), [("ui".to_string(), gone)]);
+        assert_eq!(config.links["ui"].linked_projects, [app]);
+    }
+
+    #[test]
+    fn promotes_and_refreshes_pending_variants() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &Path, version: &str| {
+            fs::create_dir_all(path).unwrap();
+            fs::write(path.join("package.json"), format!(r#"{{ "name": "@x/ui", "version": "{}" }}"#, version)).unwrap();
+        };
+        let default = dir.path().join("ui");
+        let ng17 = dir.path().join("ng17");
+        write(&default, "1.0.0");
+        let mut link = PackageLink::new("@x/ui".to_string(), default);
+        link.default_variant = Some("default".to_string());
+        link.variants.insert("ng17".to_string(), LinkVariant { path: ng17.clone(), version: None, pending: true, path_spec: None });
+        let mut config = Config::default();
+        config.links.insert("@x/ui".to_string(), link);
+
+        assert!(config.activate_pending().is_empty());
+        write(&ng17, "17.0.0");
+        let promoted = config.activate_pending();
+        assert_eq!(promoted.iter().map(Promoted::label).collect::<Vec<_>>(), ["@x/ui [ng17]"]);
+        assert_eq!(promoted[0].version.as_deref(), Some("17.0.0"));
+        let variant = &config.links["@x/ui"].variants["ng17"];
+        assert!(!variant.pending);
+        assert!(!config.links["@x/ui"].with_variant("ng17").unwrap().pending);
+
+        write(&ng17, "17.1.0");
+        let report = config.refresh_versions();
+        assert_eq!(report.updated.len(), 1);
+        assert_eq!(report.updated[0].package, "@x/ui [ng17]");
+        assert_eq!(report.updated[0].previous.as_deref(), Some("17.0.0"));
+        assert_eq!(config.links["@x/ui"].variants["ng17"].version.as_deref(), Some("17.1.0"));
+    }
+
+    #[test]
+    fn configs_from_different_files_stay_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = ConfigFile::new(Some(&dir.path().join("first").join("config.toml")));
+        let second = ConfigFile::new(Some(&dir.path().join("second").join("config.toml")));
+
+        let mut config = Config::empty(&first);
+        config.links.insert("lib-a".to_string(), PackageLink::new("lib-a".to_string(), dir.path().join("lib-a")));
+        config.save().unwrap();
+        Config::empty(&second).save().unwrap();
+
+        assert_eq!(Config::load_main(&first).unwrap().links.len(), 1);
+        let loaded = Config::load_main(&second).unwrap();
+        assert!(loaded.links.is_empty());
+        // Saving again goes back to the file it came from
+        loaded.save().unwrap();
+        assert_eq!(Config::load_main(&first).unwrap().links.len(), 1);
+        assert_eq!(second.profile_path("work").unwrap(), dir.path().join("second").join("profiles").join("work.toml"));
+    }
+
+    #[test]
+    fn profile_names_cannot_leave_the_profiles_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = ConfigFile::new(Some(&dir.path().join("config.toml")));
+        for name in ["../config", "a/b", "..", ""] {
+            assert!(file.profile_path(name).is_err(), "{}", name);
         }
+
+        // A hand-edited active_profile is refused on load rather than read
+        let mut config = Config::empty(&file);
+        config.active_profile = Some("../config".to_string());
+        config.save().unwrap();
+        assert!(Config::load(&file).is_err());
+    }
+
+    #[test]
+    fn projects_link_the_variant_they_chose() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (ui, ng17, app, other) = (root.join("ui"), root.join("ng17"), root.join("app"), root.join("other"));
+        write_package(&ui, "@x/ui", "1.0.0");
+        write_package(&ng17, "@x/ui", "17.0.0");
+        write_package(&root.join("forms"), "@x/forms", "1.0.0");
+        fs::create_dir_all(&app).unwrap();
+        fs::create_dir_all(&other).unwrap();
+
+        let mut config = Config::default();
+        config.add_link("@x/ui".to_string(), ui.to_string_lossy().to_string(), false).unwrap();
+        config.add_variant("@x/ui".to_string(), "ng17".to_string(), ng17.to_string_lossy().to_string(), false).unwrap();
+        // A checkout of another package can't be a variant
+        assert!(config.add_variant("@x/ui".to_string(), "forms".to_string(), root.join("forms").to_string_lossy().to_string(), false).is_err());
+        assert!(config.select_variant("@x/ui", &app, "ng18").is_err());
+        config.select_variant("@x/ui", &app, "ng17").unwrap();
+
+        let link = &config.links["@x/ui"];
+        assert_eq!(link.default_variant.as_deref(), Some(DEFAULT_VARIANT));
+        assert_eq!(link.for_project(&app).path, ng17);
+        assert_eq!(link.for_project(&app).version.as_deref(), Some("17.0.0"));
+        assert_eq!(link.variant_label(&app), " [ng17]");
+        assert_eq!(link.for_project(&other).path, ui);
+        assert_eq!(link.variant_label(&other), " [default]");
+    }
+
+    #[test]
+    fn a_new_default_variant_leaves_linked_projects_on_theirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (ui, ng17, app) = (root.join("ui"), root.join("ng17"), root.join("app"));
+        write_package(&ui, "@x/ui", "1.0.0");
+        write_package(&ng17, "@x/ui", "17.0.0");
+
+        let mut config = Config::default();
+        config.add_link("@x/ui".to_string(), ui.to_string_lossy().to_string(), false).unwrap();
+        config.add_variant("@x/ui".to_string(), "ng17".to_string(), ng17.to_string_lossy().to_string(), false).unwrap();
+        config.links.get_mut("@x/ui").unwrap().linked_projects.push(app.clone());
+        config.set_default_variant("@x/ui", "ng17").unwrap();
+
+        let link = &config.links["@x/ui"];
+        assert_eq!(link.path, ng17);
+        assert_eq!(link.version.as_deref(), Some("17.0.0"));
+        assert_eq!(link.variants[DEFAULT_VARIANT].path, ui);
+        assert_eq!(link.for_project(&app).path, ui);
+        assert_eq!(link.for_project(&root.join("new-app")).path, ng17);
+    }
+
+    #[test]
+    fn path_map_stores_the_first_root_and_loads_the_local_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (mac, linux) = (root.join("mac"), root.join("linux"));
+        write_package(&linux.join("ui"), "ui", "1.0.0");
+        let file = ConfigFile::new(Some(&root.join("config.toml")));
+
+        let mut config = Config::empty(&file);
+        config.path_map = serde_json::from_value(serde_json::json!({ mac.to_string_lossy(): linux.to_string_lossy() })).unwrap();
+        let mut link = PackageLink::new("ui".to_string(), linux.join("ui"));
+        link.linked_projects.push(linux.join("app"));
+        config.links.insert("ui".to_string(), link);
+        config.save().unwrap();
+
+        let saved: toml::Value = toml::from_str(&fs::read_to_string(file.path().unwrap()).unwrap()).unwrap();
+        assert_eq!(saved["links"]["ui"]["path"].as_str(), Some(mac.join("ui").to_string_lossy().as_ref()));
+        assert_eq!(saved["links"]["ui"]["linked_projects"][0].as_str(), Some(mac.join("app").to_string_lossy().as_ref()));
+
+        let loaded = Config::load_main(&file).unwrap();
+        assert_eq!(loaded.links["ui"].path, linux.join("ui"));
+        assert_eq!(loaded.links["ui"].linked_projects, [linux.join("app")]);
+
+        // Once the first root exists the stored paths are used as they are
+        fs::create_dir_all(&mac).unwrap();
+        assert_eq!(Config::load_main(&file).unwrap().links["ui"].path, mac.join("ui"));
+    }
+
+    #[test]
+    fn links_to_the_same_package_merge_into_the_npm_named_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        write_package(&root.join("ui"), "@acme/ui", "1.0.0");
+        let (app, other) = (root.join("app"), root.join("other"));
+
+        let mut config = Config::default();
+        let mut short = PackageLink::new("ui".to_string(), root.join("ui"));
+        short.linked_projects.push(app.clone());
+        short.tags.push("design".to_string());
+        let mut named = PackageLink::new("@acme/ui".to_string(), root.join("ui"));
+        named.linked_projects.push(other.clone());
+        config.links.insert("ui".to_string(), short);
+        config.links.insert("@acme/ui".to_string(), named);
+        config.groups.insert("front".to_string(), vec!["ui".to_string(), "@acme/ui".to_string()]);
+
+        let duplicates = config.duplicate_links();
+        assert_eq!(duplicates.len(), 1, "{:?}", duplicates);
+        assert_eq!(duplicates[0].links, ["@acme/ui", "ui"]);
+        assert_eq!(duplicates[0].shared, "path and npm name");
+        assert_eq!(duplicates[0].keep, "@acme/ui");
+
+        config.merge_links("@acme/ui", &["ui".to_string()]).unwrap();
+        assert!(!config.links.contains_key("ui"));
+        let kept = &config.links["@acme/ui"];
+        assert_eq!(kept.linked_projects, [other, app]);
+        assert_eq!(kept.tags, ["design"]);
+        assert_eq!(config.groups["front"], ["@acme/ui"]);
+        assert!(config.duplicate_links().is_empty());
+    }
+
+    #[test]
+    fn stored_versions_follow_package_json_only_when_tracked() {
+        let dir = tempfile::tempdir().unwrap();
+        let ui = dir.path().join("ui");
+        write_package(&ui, "ui", "1.0.0");
+        let mut config = Config::default();
+        config.links.insert("ui".to_string(), PackageLink::new("ui".to_string(), ui.clone()));
+
+        write_package(&ui, "ui", "1.1.0");
+        assert!(config.sync_tracked_versions().is_empty());
+        assert!(config.sync_tracked_version("ui").is_none());
+        assert_eq!(config.links["ui"].version.as_deref(), Some("1.0.0"));
+
+        config.track_versions = true;
+        let updates = config.sync_tracked_versions();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].previous.as_deref(), Some("1.0.0"));
+        assert_eq!(config.links["ui"].version.as_deref(), Some("1.1.0"));
+        assert!(config.sync_tracked_version("ui").is_none());
+
+        write_package(&ui, "ui", "1.2.0");
+        assert_eq!(config.sync_tracked_version("ui").map(|update| update.current), Some("1.2.0".to_string()));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,193 @@
+use serde_json::{Map, Value};
+use crate::error::SpineError;
+
+/// Link fields Spine maintains itself, which `config set` won't touch
+const MANAGED_LINK_FIELDS: [&str; 11] = [
+    "name", "previous_version", "linked_projects", "linked_at", "last_seen",
+    "created_by_version", "created_at", "pending", "durable", "required_by", "project_variants",
+];
+
+/// A dotted key split into the document's own keys, with the schema of the
+/// value it names
+#[derive(Debug, Clone)]
+pub struct KeyPath {
+    pub segments: Vec<String>,
+    pub schema: Value,
+}
+
+impl KeyPath {
+    /// The package of a `links.<package>...` key
+    pub fn link(&self) -> Option<&str> {
+        match self.segments.as_slice() {
+            [links, package, ..] if links == "links" => Some(package),
+            _ => None,
+        }
+    }
+
+    /// `links.<package>.<field>` where `field` is the given one
+    pub fn is_link_field(&self, field: &str) -> bool {
+        self.segments.len() == 3 && self.link().is_some() && self.segments[2] == field
+    }
+
+    /// Whether Spine writes this key itself
+    pub fn is_managed(&self) -> bool {
+        match self.segments.as_slice() {
+            [first, ..] if first == "last_written_by" || first == "schema_version" => true,
+            [_, _, field, ..] if self.link().is_some() => MANAGED_LINK_FIELDS.contains(&field.as_str()),
+            _ => false,
+        }
+    }
+}
+
+/// Split `key` against `document` and its `schema`. Package names may
+/// contain dots (`links.lodash.merge.path`), so keys already in the document
+/// are matched longest first.
+pub fn resolve(document: &Value, schema: &Value, key: &str) -> Result<KeyPath, SpineError> {
+    let unknown = || SpineError::Config(format!(
+        "Unknown key '{}'. 'spine config schema config' lists the keys", key
+    ));
+
+    let mut segments = Vec::new();
+    let mut node = Some(document);
+    let mut schema = schema.clone();
+    let mut rest = key;
+    while !rest.is_empty() {
+        let existing = node.and_then(Value::as_object).and_then(|object| {
+            object.keys()
+                .filter(|name| rest == name.as_str() || rest.strip_prefix(name.as_str()).is_some_and(|after| after.starts_with('.')))
+                .max_by_key(|name| name.len())
+        });
+        let first = rest.split('.').next().unwrap_or(rest);
+        let segment = match existing {
+            Some(name) => name.clone(),
+            None if property(&schema, first).is_some() => first.to_string(),
+            // A new entry of a map whose values are plain, e.g. groups.<name>
+            None if additional(&schema).is_some_and(|entry| additional(entry).is_none() && entry.get("properties").is_none()) => rest.to_string(),
+            None if additional(&schema).is_some() => first.to_string(),
+            None => return Err(unknown()),
+        };
+
+        schema = property(&schema, &segment)
+            .or_else(|| additional(&schema))
+            .cloned()
+            .ok_or_else(unknown)?;
+        node = node.and_then(|node| node.get(&segment));
+        rest = rest[segment.len()..].strip_prefix('.').unwrap_or_default();
+        segments.push(segment);
+    }
+    if segments.is_empty() {
+        return Err(unknown());
+    }
+    Ok(KeyPath { segments, schema })
+}
+
+fn property<'a>(schema: &'a Value, name: &str) -> Option<&'a Value> {
+    schema.get("properties").and_then(|properties| properties.get(name))
+}
+
+/// Schema of a map's entries, when the object is a map
+fn additional(schema: &Value) -> Option<&Value> {
+    schema.get("additionalProperties").filter(|entry| entry.is_object())
+}
+
+/// Drop nulls, which unset options serialize to. TOML has none, so the
+/// document then matches what config.toml holds.
+pub fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.retain(|_, entry| !entry.is_null());
+            object.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+pub fn get<'a>(document: &'a Value, segments: &[String]) -> Option<&'a Value> {
+    segments.iter().try_fold(document, |node, segment| node.get(segment))
+}
+
+/// Set the value at `segments`, or remove it when `value` is None
+pub fn set(document: &mut Value, segments: &[String], value: Option<Value>) {
+    let Some((last, parents)) = segments.split_last() else { return };
+    let mut node = document;
+    for segment in parents {
+        if !node.is_object() {
+            *node = Value::Object(Map::new());
+        }
+        node = node.as_object_mut().expect("just made an object")
+            .entry(segment.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    let Some(object) = node.as_object_mut() else { return };
+    match value {
+        Some(value) => { object.insert(last.clone(), value); }
+        None => { object.remove(last); }
+    }
+}
+
+/// Parse a command-line value by the type `schema` gives it. Path strings
+/// are made absolute from the current directory. An empty value unsets an
+/// optional string and empties a list; enums and minimums are left to
+/// schema validation of the whole document.
+pub fn parse(raw: &str, schema: &Value, key: &str) -> Result<Option<Value>, SpineError> {
+    let invalid = |expected: &str| SpineError::Config(format!("Invalid value for {}: expected {}, got '{}'", key, expected, raw));
+    let trimmed = raw.trim();
+
+    let value = match schema.get("type").and_then(Value::as_str).unwrap_or("string") {
+        "boolean" => match trimmed.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Value::Bool(true),
+            "false" | "no" | "off" | "0" => Value::Bool(false),
+            _ => return Err(invalid("true or false")),
+        },
+        "integer" => Value::from(trimmed.parse::<i64>().map_err(|_| invalid("a whole number"))?),
+        "number" => trimmed.parse::<f64>().ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| invalid("a number"))?,
+        "array" if trimmed.is_empty() => Value::Array(Vec::new()),
+        "array" if trimmed.starts_with('[') => {
+            let items: Vec<Value> = serde_json::from_str(trimmed)
+                .map_err(|e| SpineError::Config(format!("Invalid JSON array for {}: {}", key, e)))?;
+            let item_schema = schema.get("items").cloned().unwrap_or(Value::Null);
+            let items = items.into_iter()
+                .map(|item| match item {
+                    Value::String(text) => parse(&text, &item_schema, key).map(|parsed| parsed.unwrap_or(Value::String(text))),
+                    other => Ok(other),
+                })
+                .collect::<Result<Vec<Value>, SpineError>>()?;
+            Value::Array(items)
+        }
+        "array" => {
+            let item_schema = schema.get("items").cloned().unwrap_or(Value::Null);
+            Value::Array(parse(raw, &item_schema, key)?.into_iter().collect())
+        }
+        "object" => return Err(SpineError::Config(format!("'{}' is a table; set one of its keys instead", key))),
+        _ if trimmed.is_empty() => return Ok(None),
+        // `~` and variables are kept as written, like the config file has them
+        _ if is_path(schema) && crate::paths::uses_expansion(trimmed) => Value::String(trimmed.to_string()),
+        _ if is_path(schema) => {
+            let current_dir = std::env::current_dir().map_err(SpineError::Io)?;
+            Value::String(crate::paths::resolve_relative(&current_dir, trimmed).to_string_lossy().to_string())
+        }
+        _ => Value::String(raw.to_string()),
+    };
+    Ok(Some(value))
+}
+
+fn is_path(schema: &Value) -> bool {
+    schema.get("format").and_then(Value::as_str) == Some("path")
+}
+
+/// Plain text for scripts: strings unquoted, one list item per line,
+/// tables as TOML
+pub fn display(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join("\n"),
+        Value::Object(_) => toml::to_string_pretty(value)
+            .map(|text| text.trim_end().to_string())
+            .unwrap_or_else(|_| value.to_string()),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,154 @@
+use std::path::Path;
+use crate::config::{Config, ConfigFile};
+use crate::paths::{display_path, same_path};
+
+/// Configured packages needed by a set of requested ones, for `--with-deps`
+#[derive(Debug, Default)]
+pub struct Closure {
+    /// Every package to link, dependencies before the packages needing them
+    pub order: Vec<String>,
+    /// (dependent, dependency) pairs between configured packages
+    pub edges: Vec<(String, String)>,
+    /// Dependencies Spine doesn't manage, with the package declaring them
+    pub unconfigured: Vec<(String, String)>,
+}
+
+impl Closure {
+    /// Direct configured dependencies of `package` within the closure
+    fn dependencies_of(&self, package: &str) -> Vec<&str> {
+        self.edges.iter().filter(|(from, _)| from == package).map(|(_, to)| to.as_str()).collect()
+    }
+
+    /// Packages in the closure that were not asked for
+    pub fn added<'a>(&'a self, requested: &'a [String]) -> impl Iterator<Item = &'a String> {
+        self.order.iter().filter(move |name| !requested.contains(name))
+    }
+
+    /// Print each requested package with the dependencies it pulls in
+    pub fn print_tree(&self, requested: &[String]) {
+        for root in requested {
+            println!("📦 {}", root);
+            self.print_children(root, "", &mut vec![root.as_str()]);
+        }
+    }
+
+    fn print_children<'a>(&'a self, package: &str, indent: &str, path: &mut Vec<&'a str>) {
+        let children = self.dependencies_of(package);
+        for (index, child) in children.iter().enumerate() {
+            let last = index + 1 == children.len();
+            // A cycle is shown once and not followed
+            let cycle = if path.contains(child) { " (cycle)" } else { "" };
+            println!("{}{} {}{}", indent, if last { "└─" } else { "├─" }, child, cycle);
+            if cycle.is_empty() {
+                path.push(child);
+                self.print_children(child, &format!("{}{}", indent, if last { "   " } else { "│  " }), path);
+                path.pop();
+            }
+        }
+    }
+
+    /// Mention dependencies that `spine scan` found but that aren't configured,
+    /// since they can't be linked until added
+    pub fn print_unconfigured_hints(&self, config_file: &ConfigFile) {
+        let Some(cache) = crate::scanner::ScanCache::load(config_file).ok().flatten() else { return };
+        for (dependency, dependent) in &self.unconfigured {
+            let found = cache.package_dirs.iter().find(|dir| {
+                crate::package::get_package_name(&dir.join("package.json")).is_ok_and(|name| name == *dependency)
+            });
+            if let Some(dir) = found {
+                println!("💡 {} needs {}, found by scan at {} but not configured; 'spine add {} {}' to include it",
+                    dependent, dependency, display_path(dir), dependency, display_path(dir));
+            }
+        }
+    }
+}
+
+/// Configured packages `package` declares as dependencies or peerDependencies
+pub fn local_dependencies(config: &Config, package: &str) -> (Vec<String>, Vec<String>) {
+    let Some(link) = config.links.get(package) else { return (Vec::new(), Vec::new()) };
+    let names = crate::package::get_runtime_dependency_names(&link.path.join("package.json")).unwrap_or_default();
+
+    let mut configured = Vec::new();
+    let mut unconfigured = Vec::new();
+    for npm_name in names {
+        match config.find_by_npm_name(&npm_name) {
+            Some(dependency) if dependency.name != package => configured.push(dependency.name.clone()),
+            Some(_) => {}
+            None => unconfigured.push(npm_name),
+        }
+    }
+    (configured, unconfigured)
+}
+
+/// The local dependency closure of `requested`, in link order
+pub fn closure(config: &Config, requested: &[String]) -> Closure {
+    let mut closure = Closure::default();
+    let mut visiting = Vec::new();
+    for package in requested {
+        visit(config, package, &mut closure, &mut visiting);
+    }
+    closure
+}
+
+fn visit(config: &Config, package: &str, closure: &mut Closure, visiting: &mut Vec<String>) {
+    if closure.order.iter().any(|p| p == package) || visiting.iter().any(|p| p == package) {
+        return;
+    }
+    visiting.push(package.to_string());
+
+    let (configured, unconfigured) = local_dependencies(config, package);
+    for dependency in configured {
+        closure.edges.push((package.to_string(), dependency.clone()));
+        visit(config, &dependency, closure, visiting);
+    }
+    for dependency in unconfigured {
+        closure.unconfigured.push((dependency, package.to_string()));
+    }
+
+    visiting.pop();
+    closure.order.push(package.to_string());
+}
+
+/// Whether `name` is linked into `project`, and linked there by request
+/// rather than pulled in by `--with-deps`
+pub fn linked_explicitly(config: &Config, project: &Path, name: &str) -> bool {
+    config.links.get(name).is_some_and(|link| {
+        link.linked_projects.iter().any(|p| same_path(p, project)) && link.required_by_in(project).is_empty()
+    })
+}
+
+/// What `unlink --with-deps` removes from `project`: the requested packages
+/// plus the dependencies `link --with-deps` pulled in for them, except those
+/// another remaining linked package still needs. Dependencies linked by
+/// request stay. Kept ones are returned with the package needing them.
+pub fn unlink_set(config: &Config, project: &Path, requested: &[String]) -> (Vec<String>, Vec<(String, String)>) {
+    let linked_here = |name: &str| {
+        config.links.get(name).is_some_and(|link| link.linked_projects.iter().any(|p| same_path(p, project)))
+    };
+    let pulled_in = |name: &str| {
+        linked_here(name) && config.links.get(name).is_some_and(|link| !link.required_by_in(project).is_empty())
+    };
+    let mut remove: Vec<String> = closure(config, requested).order.into_iter()
+        .filter(|name| requested.contains(name) || pulled_in(name))
+        .collect();
+    let remaining: Vec<&String> = config.links.keys().filter(|name| linked_here(name)).collect();
+
+    let mut kept = Vec::new();
+    loop {
+        let still_needed = remove.iter()
+            .filter(|member| !requested.contains(member))
+            .find_map(|member| {
+                remaining.iter()
+                    .filter(|other| !remove.contains(other))
+                    .find(|other| local_dependencies(config, other).0.contains(member))
+                    .map(|other| (member.clone(), other.to_string()))
+            });
+        let Some((member, needed_by)) = still_needed else { break };
+        remove.retain(|name| *name != member);
+        kept.push((member, needed_by));
+    }
+
+    // Dependents go before what they depend on
+    remove.reverse();
+    (remove, kept)
+}
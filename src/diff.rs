@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::config::{Config, ConfigFile};
+use crate::error::SpineError;
+use crate::history::{self, BuildHistory};
+
+/// File hashes of a package's linked directory at the time it was linked or built
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistSnapshot {
+    pub package: String,
+    pub taken_at: u64,
+    /// What triggered the snapshot ("link", "sync" or "build")
+    pub reason: String,
+    /// Source commit at the time, when the package lives in a git repository
+    #[serde(default)]
+    pub source_sha: Option<String>,
+    pub files: BTreeMap<String, FileFingerprint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileFingerprint {
+    pub size: u64,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    pub path: String,
+    /// "added", "removed" or "modified"
+    pub change: &'static str,
+    pub size_delta: i64,
+}
+
+impl DistSnapshot {
+    pub fn capture(package: &str, dir: &Path, reason: &str) -> Self {
+        let files = fingerprints(dir);
+
+        Self {
+            package: package.to_string(),
+            taken_at: history::now_timestamp(),
+            reason: reason.to_string(),
+            source_sha: git_head(dir),
+            files,
+        }
+    }
+
+    /// Files that differ between `self` (the baseline) and `current`
+    pub fn changes_to(&self, current: &DistSnapshot) -> Vec<FileChange> {
+        let mut changes = Vec::new();
+
+        for (path, now) in &current.files {
+            match self.files.get(path) {
+                None => changes.push(FileChange {
+                    path: path.clone(),
+                    change: "added",
+                    size_delta: now.size as i64,
+                }),
+                Some(before) if before.hash != now.hash => changes.push(FileChange {
+                    path: path.clone(),
+                    change: "modified",
+                    size_delta: now.size as i64 - before.size as i64,
+                }),
+                Some(_) => {}
+            }
+        }
+
+        for (path, before) in &self.files {
+            if !current.files.contains_key(path) {
+                changes.push(FileChange {
+                    path: path.clone(),
+                    change: "removed",
+                    size_delta: -(before.size as i64),
+                });
+            }
+        }
+
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes
+    }
+}
+
+/// Fingerprints of every file under `dir` by relative `/`-separated path,
+/// skipping node_modules and .git
+pub fn fingerprints(dir: &Path) -> BTreeMap<String, FileFingerprint> {
+    let mut files = BTreeMap::new();
+    collect_fingerprints(dir, dir, &mut files);
+    files
+}
+
+fn collect_fingerprints(root: &Path, dir: &Path, files: &mut BTreeMap<String, FileFingerprint>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name() == "node_modules" || entry.file_name() == ".git" {
+            continue;
+        }
+        if path.is_dir() {
+            collect_fingerprints(root, &path, files);
+            continue;
+        }
+
+        let content = match fs::read(&path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let relative = path.strip_prefix(root).unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        files.insert(relative, FileFingerprint {
+            size: content.len() as u64,
+            hash: format!("{:016x}", fnv1a(&content)),
+        });
+    }
+}
+
+/// 64-bit FNV-1a; stable across Rust versions, unlike DefaultHasher
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+pub fn git_head(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn git_log_since(dir: &Path, sha: &str) -> Vec<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["log", "--oneline", &format!("{}..HEAD", sha)])
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|l| l.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Snapshot a package's directory into the build history. Failures are only
+/// reported as warnings so they never break linking or building.
+pub fn record_snapshot(config_file: &ConfigFile, package: &str, dir: &Path, reason: &str) {
+    let mut build_history = match BuildHistory::load(config_file) {
+        Ok(h) => h,
+        Err(e) => {
+            eprintln!("Warning: Could not read build history: {}", e);
+            return;
+        }
+    };
+
+    build_history.record_snapshot(DistSnapshot::capture(package, dir, reason));
+
+    if let Err(e) = build_history.save(config_file) {
+        eprintln!("Warning: Could not save build history: {}", e);
+    }
+}
+
+pub fn diff_command(config: &Config, package: &str, baseline: Option<&str>, json: bool) -> Result<()> {
+    let link = config.links.get(package).ok_or_else(|| {
+        let available: Vec<String> = config.links.keys().cloned().collect();
+        SpineError::package_not_found_with_suggestions(package, &available)
+    })?;
+
+    let build_history = BuildHistory::load(&config.file)?;
+    let snapshots = build_history.snapshots_for(package);
+
+    // Most recent first: "1" is the last snapshot, "2" the one before, ...
+    let (snapshot, base_sha) = match baseline {
+        None => (snapshots.last().copied(), None),
+        Some(value) => match value.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= snapshots.len() => (snapshots.get(snapshots.len() - n).copied(), None),
+            _ => {
+                let by_sha = snapshots.iter().rev().copied()
+                    .find(|s| s.source_sha.as_deref().is_some_and(|sha| sha.starts_with(value)));
+                (by_sha, Some(value.to_string()))
+            }
+        },
+    };
+
+    if snapshot.is_none() && base_sha.is_none() {
+        return Err(SpineError::Config(format!(
+            "No snapshot recorded for {}. Snapshots are taken when the package is linked or built.",
+            package
+        )).into());
+    }
+
+    let current = DistSnapshot::capture(package, &link.path, "current");
+    let changes = snapshot.map(|s| s.changes_to(&current)).unwrap_or_default();
+    let commits = match base_sha.as_deref().or(snapshot.and_then(|s| s.source_sha.as_deref())) {
+        Some(sha) => git_log_since(&link.path, sha),
+        None => Vec::new(),
+    };
+
+    if json {
+        let output = serde_json::json!({
+            "package": package,
+            "path": link.path.display().to_string(),
+            "baseline": snapshot.map(|s| serde_json::json!({
+                "taken_at": s.taken_at,
+                "reason": s.reason,
+                "source_sha": s.source_sha,
+            })),
+            "baseline_sha": base_sha,
+            "changes": changes,
+            "commits": commits,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("🔍 Changes in {} ({})", package, crate::paths::display_path(&link.path));
+    match snapshot {
+        Some(s) => println!("   Baseline: {} snapshot, {}{}", s.reason, history::format_age(s.taken_at),
+            s.source_sha.as_deref().map(|sha| format!(" at {}", &sha[..sha.len().min(8)])).unwrap_or_default()),
+        None => println!("   No snapshot recorded at {}; showing source commits only",
+            base_sha.as_deref().unwrap_or_default()),
+    }
+
+    if snapshot.is_some() {
+        if changes.is_empty() {
+            println!("\n✅ No file changes since the baseline.");
+        } else {
+            println!("\n📄 Files:");
+            for change in &changes {
+                let icon = match change.change {
+                    "added" => "+",
+                    "removed" => "-",
+                    _ => "~",
+                };
+                println!("  {} {} ({}{})", icon, change.path,
+                    if change.size_delta >= 0 { "+" } else { "-" },
+                    history::format_bytes(change.size_delta.unsigned_abs()));
+            }
+            let total: i64 = changes.iter().map(|c| c.size_delta).sum();
+            println!("\n📊 Summary: {} file(s) changed, {}{} total",
+                changes.len(), if total >= 0 { "+" } else { "-" }, history::format_bytes(total.unsigned_abs()));
+        }
+    }
+
+    if !commits.is_empty() {
+        println!("\n📝 Source commits:");
+        for commit in &commits {
+            println!("  {}", commit);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,393 @@
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use crate::config::{Config, SPINE_VERSION};
+use crate::error::SpineError;
+use crate::paths::display_path;
+use crate::platform::Platform;
+use crate::report::{Report, ReportCase, ReportTarget};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Outcome of a single environment check
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    pub details: Vec<String>,
+    pub fix_hint: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(name: &str, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Ok,
+            message: message.into(),
+            details: Vec::new(),
+            fix_hint: None,
+        }
+    }
+
+    fn warning(name: &str, message: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Warning,
+            ..Self::ok(name, message)
+        }
+    }
+
+    fn error(name: &str, message: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Error,
+            ..Self::ok(name, message)
+        }
+    }
+}
+
+pub fn doctor_command(config: &mut Config, fix: bool, report: Option<&ReportTarget>) -> Result<()> {
+    println!("🩺 Spine Doctor");
+    println!("==============");
+
+    let mut checks = vec![
+        check_tool("npm", Platform::npm_command()),
+        check_config_writer(config),
+        check_path_forms(config, fix),
+        check_permissions(config),
+        check_package_names(config, fix),
+        check_moved_outputs(config),
+    ];
+    if cfg!(windows) {
+        checks.push(check_long_paths(config));
+    }
+    if let Some(current_dir) = std::env::current_dir().ok().filter(|dir| crate::pm::is_pnp_project(dir)) {
+        checks.push(check_pnp(config, &current_dir));
+    }
+
+    let mut warnings = 0;
+    let mut errors = 0;
+    for check in &checks {
+        let icon = match check.status {
+            CheckStatus::Ok => "✅",
+            CheckStatus::Warning => {
+                warnings += 1;
+                "⚠️ "
+            }
+            CheckStatus::Error => {
+                errors += 1;
+                "❌"
+            }
+        };
+        println!("{} {}: {}", icon, check.name, check.message);
+        for detail in &check.details {
+            println!("     • {}", detail);
+        }
+        if let Some(hint) = &check.fix_hint {
+            println!("   💡 {}", hint);
+        }
+    }
+
+    println!("\n📊 Summary: {} ok, {} warning(s), {} error(s)",
+        checks.len() - warnings - errors, warnings, errors);
+    if let Some(target) = report {
+        doctor_report(&checks).write(target);
+    }
+
+    if errors > 0 {
+        return Err(SpineError::Config(format!("Doctor found {} error(s)", errors)).into());
+    }
+    Ok(())
+}
+
+/// `spine doctor --report`: each check is a test case; errors fail, warnings
+/// pass with their message in the JSON form
+fn doctor_report(checks: &[DoctorCheck]) -> Report {
+    let cases = checks.iter()
+        .map(|check| match check.status {
+            CheckStatus::Error => {
+                let mut failure = check.message.clone();
+                for detail in &check.details {
+                    failure.push_str(&format!("\n• {}", detail));
+                }
+                if let Some(hint) = &check.fix_hint {
+                    failure.push_str(&format!("\n{}", hint));
+                }
+                ReportCase::failed(&check.name, failure)
+            }
+            _ => ReportCase::passed(&check.name),
+        })
+        .collect();
+    let json_checks: Vec<serde_json::Value> = checks.iter()
+        .map(|check| serde_json::json!({
+            "name": check.name,
+            "status": match check.status {
+                CheckStatus::Ok => "ok",
+                CheckStatus::Warning => "warning",
+                CheckStatus::Error => "error",
+            },
+            "message": check.message,
+            "details": check.details,
+            "fix_hint": check.fix_hint,
+        }))
+        .collect();
+    Report {
+        suite: "spine doctor".to_string(),
+        cases,
+        json: serde_json::json!({ "checks": json_checks }),
+    }
+}
+
+fn check_tool(name: &str, mut command: std::process::Command) -> DoctorCheck {
+    match command.arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            DoctorCheck::ok(name, format!("found ({})", version))
+        }
+        _ => {
+            let mut check = DoctorCheck::error(name, "not found on PATH");
+            check.fix_hint = Some(format!("Install {} or make sure it is on your PATH", name));
+            check
+        }
+    }
+}
+
+/// A config written by a newer Spine may contain fields this binary doesn't
+/// understand and will drop or misread
+fn check_config_writer(config: &Config) -> DoctorCheck {
+    let name = "Config version";
+    let writer = match &config.last_written_by {
+        Some(writer) => writer,
+        None => return DoctorCheck::ok(name, "no writer recorded yet (older config)"),
+    };
+
+    let newer = match (semver::Version::parse(&writer.version), semver::Version::parse(SPINE_VERSION)) {
+        (Ok(written), Ok(running)) => written > running,
+        _ => false,
+    };
+
+    if newer {
+        let mut check = DoctorCheck::warning(name, format!(
+            "last written by spine {} on {}, but this is spine {}", writer.version, writer.os, SPINE_VERSION));
+        check.fix_hint = Some("Upgrade spine to avoid unknown or dropped config fields".to_string());
+        check
+    } else {
+        DoctorCheck::ok(name, format!("last written by spine {} on {}", writer.version, writer.os))
+    }
+}
+
+/// The native form of a stored path, if it differs from what is stored
+fn preferred_form(path: &Path) -> Option<PathBuf> {
+    let normalized = crate::paths::normalize_path(path);
+    if normalized != path {
+        Some(normalized)
+    } else {
+        None
+    }
+}
+
+/// Find stored paths in MSYS (`/c/...`), verbatim (`\\?\`) or mixed-separator
+/// form, which fail existence checks when Spine runs from another shell
+fn check_path_forms(config: &mut Config, fix: bool) -> DoctorCheck {
+    let mut offenders = Vec::new();
+
+    for link in config.links.values_mut() {
+        if let Some(native) = preferred_form(&link.path) {
+            offenders.push(format!("{}: {} → {}", link.name, link.path.display(), native.display()));
+            if fix {
+                link.path = native;
+            }
+        }
+        for project in link.linked_projects.iter_mut() {
+            if let Some(native) = preferred_form(project) {
+                offenders.push(format!("{} project: {} → {}", link.name, project.display(), native.display()));
+                if fix {
+                    *project = native;
+                }
+            }
+        }
+    }
+
+    let name = "Config paths";
+    if offenders.is_empty() {
+        return DoctorCheck::ok(name, format!("all stored paths are native ({})", display_path(&config.file.path().unwrap_or_default())));
+    }
+
+    offenders.sort();
+    let mut check = if fix {
+        DoctorCheck::ok(name, format!("rewrote {} path(s) to native form", offenders.len()))
+    } else {
+        let mut check = DoctorCheck::warning(name, format!("{} path(s) stored in a non-native form", offenders.len()));
+        check.fix_hint = Some("Run 'spine doctor --fix' to rewrite them".to_string());
+        check
+    };
+    check.details = offenders;
+    check
+}
+
+/// A link added under a name other than its package.json `name` makes npm
+/// create the symlink somewhere Spine doesn't look
+fn check_package_names(config: &mut Config, fix: bool) -> DoctorCheck {
+    let mut offenders = Vec::new();
+
+    for link in config.links.values_mut() {
+        if let Some(declared) = link.name_mismatch() {
+            offenders.push(format!("{}: package.json name is {} (stored {})", link.name, declared, link.npm_name()));
+            if fix {
+                link.set_npm_name(declared);
+            }
+        }
+    }
+
+    let name = "Package names";
+    if offenders.is_empty() {
+        return DoctorCheck::ok(name, "stored npm names match package.json");
+    }
+
+    offenders.sort();
+    let mut check = if fix {
+        DoctorCheck::ok(name, format!("recorded the package.json name of {} link(s)", offenders.len()))
+    } else {
+        let mut check = DoctorCheck::warning(name, format!("{} link(s) differ from their package.json name", offenders.len()));
+        check.fix_hint = Some("Run 'spine doctor --fix' or 'spine verify' to use the package.json names for npm".to_string());
+        check
+    };
+    check.details = offenders;
+    check
+}
+
+/// Links broken because their library's build output moved, which
+/// `spine verify --fix` can migrate
+fn check_moved_outputs(config: &Config) -> DoctorCheck {
+    let mut moved: Vec<String> = config.links.values()
+        .filter_map(|link| crate::angular::moved_output_path(link).map(|to| {
+            format!("{}: {} → {}", link.name, display_path(&link.path), display_path(&to))
+        }))
+        .collect();
+
+    let name = "Build output paths";
+    if moved.is_empty() {
+        return DoctorCheck::ok(name, "no links point at moved build output");
+    }
+
+    moved.sort();
+    let mut check = DoctorCheck::error(name, format!("{} link(s) point at build output that has moved", moved.len()));
+    check.details = moved;
+    check.fix_hint = Some("Run 'spine verify --fix' to point them at the new output and relink their projects".to_string());
+    check
+}
+
+/// Packages another user owns on shared machines fail with EACCES deep inside
+/// npm; catch them here along with an unwritable node_modules
+fn check_permissions(config: &Config) -> DoctorCheck {
+    let mut problems = Vec::new();
+    for link in config.links.values().filter(|link| !link.pending) {
+        problems.extend(crate::permissions::check_package(&link.path));
+    }
+    if let Ok(current_dir) = std::env::current_dir() {
+        if current_dir.join("package.json").exists() {
+            problems.extend(crate::permissions::check_project(&current_dir));
+        }
+    }
+
+    let name = "Permissions";
+    if problems.is_empty() {
+        return DoctorCheck::ok(name, "package paths are readable and node_modules is writable");
+    }
+
+    let mut check = DoctorCheck::error(name, format!("{} permission problem(s)", problems.len()));
+    check.fix_hint = problems.first().map(|p| p.suggestion());
+    check.details = problems.iter().map(|p| p.describe()).collect();
+    check
+}
+
+/// In a Plug'n'Play project links can only be package.json overrides; check
+/// that Yarn resolves each durable one to its configured path
+fn check_pnp(config: &Config, project: &Path) -> DoctorCheck {
+    let name = "Plug'n'Play";
+    let durable: Vec<_> = config.links.values()
+        .map(|link| link.for_project(project))
+        .filter(|link| link.is_durable_in(project))
+        .collect();
+    let mut stale = Vec::new();
+    for link in &durable {
+        match crate::pm::pnp_resolution(project, link.npm_name(), config.timeouts.link()) {
+            Some(resolution) if resolution.points_to(&link.path) => {}
+            Some(resolution) => stale.push(format!("{}: resolves to {}, not {}", link.name, resolution.reference, display_path(&link.path))),
+            None => stale.push(format!("{}: not in the PnP data", link.name)),
+        }
+    }
+
+    if stale.is_empty() {
+        return DoctorCheck::ok(name, format!(
+            "{}; {} durable link(s) resolve to their configured paths", crate::pm::PNP_STATUS, durable.len()
+        ));
+    }
+    let mut check = DoctorCheck::warning(name, format!("{} durable link(s) don't resolve to their configured path", stale.len()));
+    check.details = stale;
+    check.fix_hint = Some("Run 'yarn install' so Plug'n'Play picks up the overrides".to_string());
+    check
+}
+
+/// Deep Angular dist trees reached through node_modules easily pass MAX_PATH,
+/// which npm and node report only as ENOENT unless long paths are enabled
+fn check_long_paths(config: &Config) -> DoctorCheck {
+    use crate::longpaths::{self, MAX_PATH};
+
+    let current_dir = std::env::current_dir().ok().filter(|dir| dir.join("package.json").exists());
+    let mut longest = 0;
+    let mut offenders = Vec::new();
+    for link in config.links.values().filter(|link| !link.pending && link.path.exists()) {
+        let mut projects = link.linked_projects.clone();
+        if let Some(dir) = &current_dir {
+            if !projects.iter().any(|p| crate::paths::same_path(p, dir)) {
+                projects.push(dir.clone());
+            }
+        }
+        if projects.is_empty() {
+            // Still worth knowing how deep the package itself goes
+            if let Some((file, length)) = longpaths::deepest_file(&link.path) {
+                longest = longest.max(length);
+                if length >= MAX_PATH {
+                    offenders.push(format!("{}: {} ({} chars)", link.name, display_path(&file), length));
+                }
+            }
+            continue;
+        }
+        for project in &projects {
+            let lengths = longpaths::measure(&link.path, link.npm_name(), project);
+            longest = longest.max(lengths.package).max(lengths.linked.1);
+            if let Some((_, length)) = &lengths.deepest {
+                longest = longest.max(*length);
+            }
+            offenders.extend(lengths.over_limit().into_iter()
+                .map(|(path, length)| format!("{}: {} ({} chars)", link.name, display_path(path), length)));
+        }
+    }
+    offenders.sort();
+    offenders.dedup();
+
+    let name = "Path lengths";
+    let enabled = longpaths::long_paths_enabled();
+    let support = match enabled {
+        Some(true) => "long paths enabled",
+        Some(false) => "long paths disabled",
+        None => "long path setting unreadable",
+    };
+    if offenders.is_empty() {
+        return DoctorCheck::ok(name, format!("longest path is {} of {} chars ({})", longest, MAX_PATH - 1, support));
+    }
+    if enabled == Some(true) {
+        let mut check = DoctorCheck::ok(name, format!("{} path(s) over {} chars, allowed because long paths are enabled", offenders.len(), MAX_PATH - 1));
+        check.details = offenders;
+        return check;
+    }
+
+    let mut check = DoctorCheck::error(name, format!("{} path(s) over the {}-char limit ({})", offenders.len(), MAX_PATH - 1, support));
+    check.details = offenders;
+    check.fix_hint = Some(longpaths::guidance());
+    check
+}
@@ -0,0 +1,223 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde_json::{Map, Value};
+use crate::error::SpineError;
+use crate::pm::PackageManager;
+
+/// Every place a package manager reads dependency overrides from
+const OVERRIDE_FIELDS: &[&[&str]] = &[&["overrides"], &["resolutions"], &["pnpm", "overrides"]];
+
+/// Fields where a project declares its direct dependencies
+const DEPENDENCY_FIELDS: &[&str] = &["dependencies", "devDependencies", "optionalDependencies"];
+
+/// Field of package.json that `pm` reads overrides from
+fn override_field(pm: PackageManager) -> &'static [&'static str] {
+    match pm {
+        PackageManager::Npm | PackageManager::Bun => &["overrides"],
+        PackageManager::Yarn => &["resolutions"],
+        PackageManager::Pnpm => &["pnpm", "overrides"],
+    }
+}
+
+/// Override value pointing at a local package directory
+fn override_spec(pm: PackageManager, package_path: &Path) -> String {
+    match pm {
+        PackageManager::Npm | PackageManager::Bun => format!("file:{}", package_path.display()),
+        PackageManager::Yarn | PackageManager::Pnpm => format!("link:{}", package_path.display()),
+    }
+}
+
+/// An in-memory edit of a project's package.json that keeps the original text
+/// so the change can be shown as a diff and reverted
+pub struct PackageJsonEdit {
+    path: PathBuf,
+    original: String,
+    json: Value,
+    indent: String,
+}
+
+impl PackageJsonEdit {
+    pub fn open(project: &Path) -> Result<Self> {
+        let path = project.join("package.json");
+        let original = fs::read_to_string(&path)
+            .map_err(|e| SpineError::PackageJson(format!("Could not read {}: {}", path.display(), e)))?;
+        let json: Value = serde_json::from_str(&original)?;
+        if !json.is_object() {
+            return Err(SpineError::PackageJson(format!("{} is not a JSON object", path.display())).into());
+        }
+
+        // Reuse the file's own indentation so the diff only shows real changes
+        let indent = original.lines()
+            .nth(1)
+            .map(|line| line.chars().take_while(|c| c.is_whitespace()).collect::<String>())
+            .filter(|indent| !indent.is_empty())
+            .unwrap_or_else(|| "  ".to_string());
+
+        Ok(Self { path, original, json, indent })
+    }
+
+    /// Point `npm_name` at `package_path` in the override field `pm` reads.
+    /// npm and bun refuse an override that differs from a direct dependency, so
+    /// that dependency is pointed at the path as well; its field and previous
+    /// range are returned.
+    pub fn set_override(&mut self, pm: PackageManager, npm_name: &str, package_path: &Path) -> Option<(String, String)> {
+        let spec = override_spec(pm, package_path);
+        let mut replaced = None;
+        if matches!(pm, PackageManager::Npm | PackageManager::Bun) {
+            let root = self.json.as_object_mut().expect("checked in open");
+            for field in DEPENDENCY_FIELDS {
+                let entry = root.get_mut(*field).and_then(|deps| deps.get_mut(npm_name));
+                if let Some(entry) = entry {
+                    let previous = std::mem::replace(entry, Value::String(spec.clone()));
+                    if let Some(range) = previous.as_str().filter(|range| *range != spec) {
+                        replaced = Some((field.to_string(), range.to_string()));
+                    }
+                    break;
+                }
+            }
+        }
+
+        let mut object = self.json.as_object_mut().expect("checked in open");
+        for key in override_field(pm) {
+            let entry = object.entry(key.to_string()).or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            object = entry.as_object_mut().expect("just made an object");
+        }
+        object.insert(npm_name.to_string(), Value::String(spec));
+        replaced
+    }
+
+    /// Drop local overrides of `npm_name` from every override field, removing
+    /// fields left empty, and put back a direct dependency range replaced by
+    /// `set_override`. Overrides to registry versions are left alone.
+    pub fn remove_override(&mut self, npm_name: &str, replaced: Option<(&str, &str)>) {
+        let root = self.json.as_object_mut().expect("checked in open");
+        for field in OVERRIDE_FIELDS {
+            remove_local_override(root, field, npm_name);
+        }
+
+        if let Some((field, range)) = replaced {
+            if let Some(Value::Object(deps)) = root.get_mut(field) {
+                if let Some(entry) = deps.get_mut(npm_name) {
+                    *entry = Value::String(range.to_string());
+                }
+            }
+        }
+    }
+
+    pub fn set_version(&mut self, version: &str) {
+        let root = self.json.as_object_mut().expect("checked in open");
+        root.insert("version".to_string(), Value::String(version.to_string()));
+    }
+
+    /// Replace the range `field` declares for `dependency`, if it declares one
+    pub fn set_range(&mut self, field: &str, dependency: &str, range: &str) {
+        let root = self.json.as_object_mut().expect("checked in open");
+        if let Some(entry) = root.get_mut(field).and_then(|deps| deps.get_mut(dependency)) {
+            *entry = Value::String(range.to_string());
+        }
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.render().map(|updated| updated != self.original).unwrap_or(false)
+    }
+
+    fn render(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+        serde::Serialize::serialize(&self.json, &mut serializer)?;
+
+        let mut rendered = String::from_utf8(buffer)?;
+        if self.original.ends_with('\n') {
+            rendered.push('\n');
+        }
+        Ok(rendered)
+    }
+
+    /// Print the change as `-`/`+` lines with their line numbers
+    pub fn print_diff(&self) -> Result<()> {
+        let updated = self.render()?;
+        println!("--- {}", self.path.display());
+        println!("+++ {}", self.path.display());
+        for line in diff_lines(&self.original, &updated) {
+            println!("{}", line);
+        }
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::write(&self.path, self.render()?)?;
+        Ok(())
+    }
+
+    /// Put the original package.json back
+    pub fn revert(&self) -> Result<()> {
+        fs::write(&self.path, &self.original)?;
+        Ok(())
+    }
+}
+
+fn remove_local_override(object: &mut Map<String, Value>, field: &[&str], npm_name: &str) {
+    let (key, rest) = match field.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+
+    let child = match object.get_mut(*key) {
+        Some(Value::Object(child)) => child,
+        _ => return,
+    };
+
+    if rest.is_empty() {
+        let is_local = child.get(npm_name)
+            .and_then(|v| v.as_str())
+            .is_some_and(|spec| spec.starts_with("file:") || spec.starts_with("link:"));
+        if is_local {
+            child.remove(npm_name);
+        }
+    } else {
+        remove_local_override(child, rest, npm_name);
+    }
+
+    if child.is_empty() {
+        object.remove(*key);
+    }
+}
+
+/// Changed lines between two texts, from their longest common subsequence
+fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j]: common lines of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(format!("-{:>4} {}", i + 1, old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+{:>4} {}", j + 1, new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
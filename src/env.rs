@@ -0,0 +1,257 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::Result;
+use serde::Serialize;
+use crate::config::{Config, ConfigFile};
+use crate::paths::display_path;
+use crate::platform::{run_command, OutputMode, Platform};
+use crate::pm::PackageManager;
+
+/// Longest a `--version` probe may take; `spine env` should answer at once
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Environment variables Spine reads
+const SPINE_VARIABLES: &[&str] = &[
+    "SPINE_CONFIG", "SPINE_PROFILE", "SPINE_LOG_FORMAT", "npm_config_registry", "NPM_CONFIG_REGISTRY", "EDITOR",
+];
+
+/// One resolved setting and where its value came from
+#[derive(Debug, Clone, Serialize)]
+pub struct Setting {
+    pub name: String,
+    pub value: String,
+    /// "default", "config", "env var", "flag", a file, or how it was detected
+    pub source: String,
+}
+
+impl Setting {
+    fn new(name: impl Into<String>, value: impl Into<String>, source: impl Into<String>) -> Self {
+        Self { name: name.into(), value: value.into(), source: source.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Section {
+    pub title: &'static str,
+    pub settings: Vec<Setting>,
+}
+
+/// `spine env`: the tools, files, registries and caches Spine would use
+/// from the current directory, each with where it came from
+pub fn env_command(config: &Config, json: bool) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let sections = vec![
+        Section { title: "Tools", settings: tools(config, &current_dir) },
+        Section { title: "Configuration", settings: configuration(config, &current_dir)? },
+        Section { title: "Workspace", settings: workspace(config, &current_dir) },
+        Section { title: "Registries", settings: registries(config, &current_dir) },
+        Section { title: "Builds", settings: builds(config) },
+        Section { title: "Caches", settings: caches(config)? },
+        Section { title: "Environment", settings: variables() },
+    ];
+
+    if json {
+        let document: serde_json::Map<String, serde_json::Value> = sections.iter()
+            .map(|section| (section.title.to_lowercase(), serde_json::json!(section.settings)))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&document)?);
+        return Ok(());
+    }
+
+    println!("🧭 Spine environment for {}", display_path(&current_dir));
+    for section in &sections {
+        println!("\n{}:", section.title);
+        if section.settings.is_empty() {
+            println!("  (none)");
+        }
+        let width = section.settings.iter().map(|setting| setting.name.len()).max().unwrap_or(0);
+        for setting in &section.settings {
+            println!("  {:<width$}  {}  [{}]", setting.name, setting.value, setting.source, width = width);
+        }
+    }
+    Ok(())
+}
+
+fn tools(config: &Config, current_dir: &Path) -> Vec<Setting> {
+    let npm_name = Platform::get_command_name("npm");
+    let npm = match find_on_path(&npm_name) {
+        Some(path) => Setting::new("npm", format!("{} ({})", display_path(&path), probe_version(Platform::npm_command())), "PATH"),
+        None => Setting::new("npm", "not found", "PATH"),
+    };
+
+    // Spine runs the ng on PATH; the workspace's own @angular/cli is shown beside it
+    let root = crate::paths::walk_up(current_dir, "angular.json").found;
+    let local_version = root.as_ref().and_then(|root| {
+        crate::package::get_package_version(&root.join("node_modules").join("@angular").join("cli").join("package.json")).ok()
+    });
+    let ng = match (find_on_path(&Platform::get_command_name("ng")), local_version) {
+        (Some(path), Some(version)) => Setting::new("ng", format!("{} (workspace @angular/cli {})", display_path(&path), version), "PATH"),
+        (Some(path), None) => Setting::new("ng", display_path(&path), "PATH"),
+        (None, Some(version)) => Setting::new("ng", format!("not on PATH (workspace @angular/cli {})", version), "PATH"),
+        (None, None) => Setting::new("ng", "not found", "PATH"),
+    };
+
+    let package_manager = match config.package_manager {
+        Some(manager) => Setting::new("package manager", manager.name(), "config"),
+        None => {
+            let detection = PackageManager::detect(current_dir);
+            let source = if detection.defaulted {
+                "default".to_string()
+            } else {
+                format!("detected from {}", detection.evidence.join(", "))
+            };
+            Setting::new("package manager", detection.manager.name(), source)
+        }
+    };
+    vec![npm, ng, package_manager]
+}
+
+fn configuration(config: &Config, current_dir: &Path) -> Result<Vec<Setting>> {
+    // clap lets --config override SPINE_CONFIG, so the variable only chose
+    // the file when it names the one in use
+    let from_env = std::env::var_os("SPINE_CONFIG").filter(|value| !value.is_empty())
+        .map(|value| ConfigFile::new(Some(Path::new(&value))));
+    let config_source = match from_env {
+        _ if !config.file.is_given() => "default",
+        Some(file) if file == config.file => "env var SPINE_CONFIG",
+        _ => "flag --config",
+    };
+    let mut settings = vec![Setting::new("config file", display_path(&config.file.path()?), config_source)];
+
+    let from_env = std::env::var("SPINE_PROFILE").ok().filter(|name| !name.is_empty());
+    settings.push(match (config.active_profile_name(), from_env.is_some()) {
+        (Some(name), true) => Setting::new("profile", format!("{} ({})", name, display_path(&config.file.profile_path(&name)?)), "env var SPINE_PROFILE"),
+        (Some(name), false) => Setting::new("profile", format!("{} ({})", name, display_path(&config.file.profile_path(&name)?)), "config"),
+        (None, true) => Setting::new("profile", crate::config::DEFAULT_PROFILE, "env var SPINE_PROFILE"),
+        (None, false) => Setting::new("profile", crate::config::DEFAULT_PROFILE, "default"),
+    });
+
+    settings.push(match crate::workspace::WorkspaceManager::find_workspace_config(current_dir) {
+        Some(path) => Setting::new("workspace config", display_path(&path), "found above the current directory"),
+        None => Setting::new("workspace config", "none", "no .spine.toml within the walk limits"),
+    });
+    Ok(settings)
+}
+
+fn workspace(config: &Config, current_dir: &Path) -> Vec<Setting> {
+    let mut settings = Vec::new();
+    let detected = ["angular.json", "nx.json", "package.json"].into_iter()
+        .find_map(|marker| crate::paths::walk_up(current_dir, marker).found.map(|root| (root, marker)));
+    settings.push(match detected {
+        Some((root, marker)) => Setting::new("root", display_path(&root), format!("nearest {}", marker)),
+        None => Setting::new("root", "none", "no angular.json, nx.json or package.json found"),
+    });
+    let limits = config.workspace_search.limits();
+    let source = if limits == crate::config::WorkspaceSearchConfig::default().limits() { "default" } else { "config" };
+    let stop = if limits.stop_at_repository { ", stopping at .git" } else { "" };
+    settings.push(Setting::new("walk limits", format!("{} level(s){}", limits.max_levels, stop), source));
+    settings
+}
+
+/// The default registry and one per scope of a configured package
+fn registries(config: &Config, current_dir: &Path) -> Vec<Setting> {
+    let search_dirs = [current_dir.to_path_buf()];
+    let default = crate::npmrc::resolve_registry("", &search_dirs);
+    let mut settings = vec![Setting::new("default", default.registry, registry_source(&default.source))];
+
+    let mut scopes: Vec<&str> = config.links.values()
+        .filter_map(|link| crate::npmrc::package_scope(link.npm_name()))
+        .collect();
+    scopes.sort_unstable();
+    scopes.dedup();
+    for scope in scopes {
+        let setting = crate::npmrc::resolve_registry(&format!("{}/package", scope), &search_dirs);
+        settings.push(Setting::new(scope, setting.registry, registry_source(&setting.source)));
+    }
+    settings
+}
+
+/// `resolve_registry` names a file, "$VAR" or "npm default"
+fn registry_source(source: &str) -> String {
+    match source.strip_prefix('$') {
+        Some(variable) => format!("env var {}", variable),
+        None if source == "npm default" => "default".to_string(),
+        None => display_path(Path::new(source)),
+    }
+}
+
+/// The `[build]` limits every build Spine starts runs under
+fn builds(config: &Config) -> Vec<Setting> {
+    let settings = &config.build;
+    let defaults = crate::config::BuildConfig::default();
+    let source = |changed: bool| if changed { "config" } else { "default" };
+    let concurrency = match settings.max_concurrency {
+        0 => "unlimited".to_string(),
+        limit => format!("{} at a time", limit),
+    };
+    let priority = match settings.low_priority {
+        true if cfg!(windows) => "low (below normal)".to_string(),
+        true => format!("low (nice {})", crate::throttle::LOW_PRIORITY_NICENESS),
+        false => "normal".to_string(),
+    };
+    let heap = match settings.node_max_old_space_mb {
+        Some(megabytes) => format!("{} MB", megabytes),
+        None => "node default".to_string(),
+    };
+    vec![
+        Setting::new("max concurrency", concurrency, source(settings.max_concurrency != defaults.max_concurrency)),
+        Setting::new("priority", priority, source(settings.low_priority != defaults.low_priority)),
+        Setting::new("node heap", heap, source(settings.node_max_old_space_mb != defaults.node_max_old_space_mb)),
+    ]
+}
+
+fn caches(config: &Config) -> Result<Vec<Setting>> {
+    let mut files = vec![
+        ("tarball cache", crate::audit::cache_dir(&config.file)?, "default"),
+        ("config backups", crate::backups::backups_dir(&config.file)?, "default"),
+        ("build history", crate::history::BuildHistory::history_path(&config.file)?, "default"),
+    ];
+    if config.usage_log.enabled {
+        let source = if config.usage_log.path.is_some() { "config" } else { "default" };
+        files.push(("usage log", config.usage_log.file(&config.file)?, source));
+    }
+
+    Ok(files.into_iter()
+        .map(|(name, path, source)| {
+            let size = match disk_usage(&path) {
+                Some(bytes) => crate::history::format_bytes(bytes),
+                None => "not created yet".to_string(),
+            };
+            Setting::new(name, format!("{} ({})", display_path(&path), size), source)
+        })
+        .collect())
+}
+
+/// Variables Spine reads that are set in this shell
+fn variables() -> Vec<Setting> {
+    SPINE_VARIABLES.iter()
+        .filter_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()).map(|value| Setting::new(*name, value, "env var")))
+        .collect()
+}
+
+fn find_on_path(command: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(command))
+        .find(|candidate| candidate.is_file())
+}
+
+/// First line of `<command> --version`, or "version unknown"
+fn probe_version(mut command: std::process::Command) -> String {
+    command.arg("--version");
+    run_command(command, Some(PROBE_TIMEOUT), OutputMode::Capture).ok()
+        .filter(|output| output.success())
+        .and_then(|output| output.stdout.lines().next().map(|line| format!("v{}", line.trim().trim_start_matches('v'))))
+        .unwrap_or_else(|| "version unknown".to_string())
+}
+
+/// Bytes used by a file or everything under a directory; None when missing
+fn disk_usage(path: &Path) -> Option<u64> {
+    let metadata = fs::symlink_metadata(path).ok()?;
+    if !metadata.is_dir() {
+        return Some(metadata.len());
+    }
+    let entries = fs::read_dir(path).ok()?;
+    Some(entries.flatten().filter_map(|entry| disk_usage(&entry.path())).sum())
+}
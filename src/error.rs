@@ -26,11 +26,26 @@ pub enum SpineError {
     #[error("Package not found: '{package}'\n💡 {suggestion}")]
     PackageNotFoundWithSuggestion { package: String, suggestion: String },
 
+    #[error("Group not found: '{group}'\n💡 {suggestion}")]
+    GroupNotFound { group: String, suggestion: String },
+
     #[error("Angular workspace error: {message}\n💡 {suggestion}")]
     AngularWorkspace { message: String, suggestion: String },
 
     #[error("Command failed: {command}\n❌ {error}\n💡 {suggestion}")]
     CommandFailed { command: String, error: String, suggestion: String },
+
+    #[error("Permission denied: {message}\n💡 {suggestion}")]
+    PermissionDenied { message: String, suggestion: String },
+
+    #[error("Stopped after failure in {package}, {not_attempted} package(s) not attempted\n❌ {error}")]
+    BatchAborted { package: String, not_attempted: usize, error: String },
+
+    #[error("Refusing to link '{package}': {reason}\n💡 Run the link from the project that consumes the package")]
+    SelfLink { package: String, reason: String },
+
+    #[error("{process} started but never became ready within {seconds}s; its last output:\n{output}\n💡 Raise --wait-timeout if the first compile is just slow")]
+    NeverReady { process: String, seconds: u64, output: String },
 }
 
 impl SpineError {
@@ -52,6 +67,24 @@ impl SpineError {
         }
     }
 
+    pub fn group_not_found_with_suggestions(group: &str, available_groups: &[String]) -> Self {
+        let suggestion = if available_groups.is_empty() {
+            "No groups are configured. Use 'spine group add <group> <packages...>' to create one.".to_string()
+        } else {
+            let similar = find_similar_names(group, available_groups);
+            if similar.is_empty() {
+                format!("Available groups: {}", available_groups.join(", "))
+            } else {
+                format!("Did you mean '{}'? Available: {}", similar[0], available_groups.join(", "))
+            }
+        };
+
+        SpineError::GroupNotFound {
+            group: group.to_string(),
+            suggestion,
+        }
+    }
+
     pub fn angular_workspace_not_found(current_dir: &str) -> Self {
         SpineError::AngularWorkspace {
             message: format!("No angular.json found in {}", current_dir),
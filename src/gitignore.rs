@@ -0,0 +1,333 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Ignore files read in each directory a scan enters
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// One line of an ignore file
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: Vec<char>,
+    negated: bool,
+    /// Written with a trailing slash: matches directories only
+    dir_only: bool,
+    /// Written with a slash before the end: matched against the whole
+    /// path below the file's directory rather than any name
+    anchored: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').filter(|rest| rest.starts_with(['#', '!'])).unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        if line.is_empty() {
+            return None;
+        }
+        Some(Self { pattern: line.chars().collect(), negated, dir_only, anchored })
+    }
+
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            let path: Vec<char> = relative.chars().collect();
+            glob(&self.pattern, &path)
+        } else {
+            let name: Vec<char> = relative.rsplit('/').next().unwrap_or(relative).chars().collect();
+            glob(&self.pattern, &name)
+        }
+    }
+}
+
+/// The rules of one ignore file, or of a pattern list standing in for one
+#[derive(Debug, Clone)]
+struct RuleSet {
+    base: PathBuf,
+    rules: Vec<Rule>,
+}
+
+/// Gitignore-style rules in force while a scan walks a tree: the ignore
+/// files of the directories entered so far, plus any extra patterns. Later
+/// rules win, so a subdirectory's file can re-include what a parent's
+/// excluded, as git does. The default reads no ignore files at all.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    sets: Vec<RuleSet>,
+    read_files: bool,
+    /// The scan root as given and as an absolute path; paths below it are
+    /// matched through the latter, where the ancestors' files live
+    root: Option<(PathBuf, PathBuf)>,
+}
+
+impl IgnoreRules {
+    /// Rules that apply at `root`: the ignore files of the repository above
+    /// it (and .git/info/exclude), up to and including `root` itself
+    pub fn for_root(given: &Path) -> Self {
+        let root = given.canonicalize().unwrap_or_else(|_| given.to_path_buf());
+        let mut rules = Self { sets: Vec::new(), read_files: true, root: Some((given.to_path_buf(), root.clone())) };
+        let mut ancestors: Vec<&Path> = Vec::new();
+        for dir in root.ancestors() {
+            ancestors.push(dir);
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        let repository = ancestors.last().filter(|dir| dir.join(".git").exists()).copied();
+        if let Some(repository) = repository {
+            rules.add_file(repository, &repository.join(".git").join("info").join("exclude"));
+        }
+        // A root outside any repository only reads its own files
+        let dirs: Vec<&Path> = if repository.is_some() { ancestors.into_iter().rev().collect() } else { vec![&root] };
+        for dir in dirs {
+            rules.enter(dir);
+        }
+        rules
+    }
+
+    /// Add the ignore files of `dir`. A scan gives each directory its own
+    /// copy of the rules, so nothing is ever removed again.
+    pub fn enter(&mut self, dir: &Path) {
+        if !self.read_files {
+            return;
+        }
+        let dir = self.absolute(dir);
+        for name in IGNORE_FILES {
+            self.add_file(&dir, &dir.join(name));
+        }
+    }
+
+    /// Gitignore-style patterns relative to `base`, e.g. .spine.toml's scan.exclude
+    pub fn add_patterns(&mut self, base: &Path, patterns: &[String]) {
+        let rules: Vec<Rule> = patterns.iter().filter_map(|pattern| Rule::parse(pattern)).collect();
+        if !rules.is_empty() {
+            self.sets.push(RuleSet { base: self.absolute(base), rules });
+        }
+    }
+
+    fn absolute(&self, path: &Path) -> PathBuf {
+        match &self.root {
+            Some((given, absolute)) => match path.strip_prefix(given) {
+                Ok(rest) => absolute.join(rest),
+                Err(_) => path.to_path_buf(),
+            },
+            None => path.to_path_buf(),
+        }
+    }
+
+    fn add_file(&mut self, base: &Path, file: &Path) {
+        let Ok(content) = fs::read_to_string(file) else { return };
+        self.sets.push(RuleSet { base: base.to_path_buf(), rules: content.lines().filter_map(Rule::parse).collect() });
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let path = self.absolute(path);
+        let mut ignored = false;
+        for set in &self.sets {
+            let Ok(relative) = path.strip_prefix(&set.base) else { continue };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if relative.is_empty() {
+                continue;
+            }
+            for rule in &set.rules {
+                if rule.matches(&relative, is_dir) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+        ignored
+    }
+}
+
+/// Whether one path segment matches a glob segment such as `lib-*`
+pub fn matches_name(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    glob(&pattern, &name)
+}
+
+/// Gitignore globbing: `*` and `?` stay within one path segment, `**`
+/// crosses them, and `**/` also matches no directory at all
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            match rest.strip_prefix(&['/']) {
+                Some(rest) => glob(rest, text) || (0..text.len()).any(|i| text[i] == '/' && glob(rest, &text[i + 1..])),
+                None => (0..=text.len()).any(|i| glob(rest, &text[i..])),
+            }
+        }
+        Some('*') => {
+            for i in 0..=text.len() {
+                if glob(&pattern[1..], &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => text.first().is_some_and(|c| *c != '/') && glob(&pattern[1..], &text[1..]),
+        Some('[') => match class(pattern) {
+            Some((matches, length)) => text.first().is_some_and(|c| *c != '/' && matches(*c)) && glob(&pattern[length..], &text[1..]),
+            None => text.first() == Some(&'[') && glob(&pattern[1..], &text[1..]),
+        },
+        Some('\\') if pattern.len() > 1 => text.first() == Some(&pattern[1]) && glob(&pattern[2..], &text[1..]),
+        Some(c) => text.first() == Some(c) && glob(&pattern[1..], &text[1..]),
+    }
+}
+
+/// A `[...]` class at the start of `pattern`: a test for one character and
+/// the pattern length it used. None when the bracket is never closed.
+fn class(pattern: &[char]) -> Option<(impl Fn(char) -> bool, usize)> {
+    let mut i = 1;
+    let negated = matches!(pattern.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+    let start = i;
+    // A ] straight after the opening bracket is a member, not the end
+    while i < pattern.len() && (pattern[i] != ']' || i == start) {
+        i += 1;
+    }
+    if i >= pattern.len() {
+        return None;
+    }
+
+    let mut ranges = Vec::new();
+    let members = &pattern[start..i];
+    let mut j = 0;
+    while j < members.len() {
+        if j + 2 < members.len() && members[j + 1] == '-' {
+            ranges.push((members[j], members[j + 2]));
+            j += 3;
+        } else {
+            ranges.push((members[j], members[j]));
+            j += 1;
+        }
+    }
+    let test = move |c: char| ranges.iter().any(|(low, high)| *low <= c && c <= *high) != negated;
+    Some((test, i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(patterns: &[&str]) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        rules.add_patterns(Path::new("/repo"), &patterns);
+        rules
+    }
+
+    fn ignores(rules: &IgnoreRules, path: &str) -> bool {
+        rules.is_ignored(&Path::new("/repo").join(path), false)
+    }
+
+    #[test]
+    fn stars_stay_within_one_segment() {
+        assert!(matches_name("lib-*", "lib-ui"));
+        assert!(!matches_name("lib-*", "app-ui"));
+        assert!(matches_name("ui-?", "ui-2"));
+        assert!(!matches_name("ui-?", "ui-10"));
+        assert!(!matches_name("src*", "src/index.ts"));
+        assert!(!matches_name("src?index.ts", "src/index.ts"));
+    }
+
+    #[test]
+    fn double_stars_cross_directories() {
+        let rules = rules(&["**/generated", "docs/**/*.md", "a/**/b", "cache/**"]);
+        assert!(ignores(&rules, "generated"));
+        assert!(ignores(&rules, "x/y/generated"));
+        assert!(ignores(&rules, "docs/index.md"));
+        assert!(ignores(&rules, "docs/api/v1/index.md"));
+        assert!(!ignores(&rules, "docs/api/index.ts"));
+        assert!(ignores(&rules, "a/b"));
+        assert!(ignores(&rules, "a/x/y/b"));
+        assert!(!ignores(&rules, "a/x/bb"));
+        assert!(ignores(&rules, "cache/deep/file.bin"));
+    }
+
+    #[test]
+    fn classes_match_one_character() {
+        assert!(matches_name("v[0-9]", "v7"));
+        assert!(!matches_name("v[0-9]", "vx"));
+        assert!(matches_name("[abc].ts", "b.ts"));
+        assert!(matches_name("[!a-c].ts", "d.ts"));
+        assert!(!matches_name("[^a-c].ts", "a.ts"));
+        // A leading ] is a member, and an unclosed bracket is literal
+        assert!(matches_name("[]x]", "]"));
+        assert!(matches_name("[ab", "[ab"));
+    }
+
+    #[test]
+    fn a_slash_anchors_to_the_base() {
+        let rules = rules(&["/build", "src/gen", "tmp"]);
+        assert!(ignores(&rules, "build"));
+        assert!(!ignores(&rules, "packages/ui/build"));
+        assert!(ignores(&rules, "src/gen"));
+        assert!(!ignores(&rules, "lib/src/gen"));
+        // Without a slash any name matches, at any depth
+        assert!(ignores(&rules, "tmp"));
+        assert!(ignores(&rules, "packages/ui/tmp"));
+    }
+
+    #[test]
+    fn a_trailing_slash_matches_directories_only() {
+        let rules = rules(&["out/"]);
+        assert!(rules.is_ignored(Path::new("/repo/out"), true));
+        assert!(!rules.is_ignored(Path::new("/repo/out"), false));
+    }
+
+    #[test]
+    fn later_negations_re_include() {
+        let rules = rules(&["*.log", "!keep.log", "# *.ts", "", "\\!literal", "\\#hash"]);
+        assert!(ignores(&rules, "debug.log"));
+        assert!(!ignores(&rules, "keep.log"));
+        assert!(!ignores(&rules, "index.ts"));
+        assert!(ignores(&rules, "!literal"));
+        assert!(ignores(&rules, "#hash"));
+    }
+
+    #[test]
+    fn nested_ignore_files_override_their_parents() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".git").join("info")).unwrap();
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join(".git").join("info").join("exclude"), "*.bak\n").unwrap();
+        fs::write(root.join(".gitignore"), "*.tmp\n").unwrap();
+        fs::write(root.join("sub").join(".ignore"), "!keep.tmp\n").unwrap();
+
+        let mut rules = IgnoreRules::for_root(root);
+        assert!(rules.is_ignored(&root.join("old.bak"), false));
+        assert!(rules.is_ignored(&root.join("sub").join("keep.tmp"), false));
+        rules.enter(&root.join("sub"));
+        assert!(!rules.is_ignored(&root.join("sub").join("keep.tmp"), false));
+        assert!(rules.is_ignored(&root.join("sub").join("other.tmp"), false));
+    }
+
+    #[test]
+    fn the_default_reads_no_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".gitignore"), "*\n").unwrap();
+        let mut rules = IgnoreRules::default();
+        rules.enter(dir.path());
+        assert!(!rules.is_ignored(&dir.path().join("index.ts"), false));
+    }
+}
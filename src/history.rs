@@ -0,0 +1,557 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::config::ConfigFile;
+use crate::diff::DistSnapshot;
+
+/// Maximum number of build records kept per library
+const MAX_RECORDS_PER_LIBRARY: usize = 50;
+
+/// Maximum number of serve session summaries kept
+const MAX_SERVE_SESSIONS: usize = 20;
+
+/// Maximum number of dist snapshots kept per package
+const MAX_SNAPSHOTS_PER_PACKAGE: usize = 10;
+
+/// Maximum number of publish records kept
+const MAX_PUBLISHES: usize = 100;
+
+/// Maximum number of smoke test results kept
+const MAX_SMOKES: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BuildHistory {
+    #[serde(default)]
+    pub builds: Vec<BuildRecord>,
+    #[serde(default)]
+    pub sessions: Vec<ServeSession>,
+    #[serde(default)]
+    pub snapshots: Vec<DistSnapshot>,
+    #[serde(default)]
+    pub publishes: Vec<PublishRecord>,
+    #[serde(default)]
+    pub smokes: Vec<SmokeRecord>,
+}
+
+/// Result of a `spine smoke` run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmokeRecord {
+    pub package: String,
+    pub project: PathBuf,
+    pub timestamp: u64,
+    pub passed: bool,
+    /// "build", "link" or "compile"
+    #[serde(default)]
+    pub failed_stage: Option<String>,
+    pub duration_ms: u64,
+    /// Compiler errors reported by the consuming project
+    #[serde(default)]
+    pub errors: usize,
+}
+
+/// A `spine publish` that reached npm, for auditing releases
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishRecord {
+    pub package: String,
+    pub version: Option<String>,
+    pub timestamp: u64,
+    /// Whether the library's source had uncommitted changes; None outside git
+    pub dirty: Option<bool>,
+    /// The uncommitted files, relative to the repository root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dirty_files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildRecord {
+    pub library: String,
+    pub timestamp: u64,
+    pub success: bool,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub sizes: Option<BundleSizes>,
+}
+
+/// Per-format byte sizes of a built library's dist output
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct BundleSizes {
+    /// Keyed by format folder (e.g. "fesm2022", "esm2022") plus "types" for .d.ts files
+    #[serde(default)]
+    pub formats: BTreeMap<String, u64>,
+    pub total: u64,
+    /// Raw "Initial total" reported by the Angular CLI, when the builder prints one
+    #[serde(default)]
+    pub initial_total: Option<u64>,
+}
+
+/// Summary of a `serve --with-libs` session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServeSession {
+    pub app: String,
+    pub started_at: u64,
+    pub duration_ms: u64,
+    /// How long the app server took to answer after it was started; None
+    /// when readiness wasn't waited for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ready_after_ms: Option<u64>,
+    #[serde(default)]
+    pub libraries: Vec<RebuildStats>,
+    /// Watchers paused because their library went missing, and what was done
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watcher_changes: Vec<WatcherChange>,
+}
+
+/// A library watcher that lost its path during a serve session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatcherChange {
+    pub library: String,
+    pub at: u64,
+    /// Why the watcher was paused
+    pub reason: String,
+    /// "re-resolved to <library> at <path>" or "dropped"
+    pub outcome: String,
+}
+
+/// Rebuild cycle statistics for one library during a serve session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebuildStats {
+    pub library: String,
+    pub rebuilds: usize,
+    pub failures: usize,
+    pub min_ms: u64,
+    pub median_ms: u64,
+    pub max_ms: u64,
+}
+
+impl RebuildStats {
+    /// `rebuilds` may exceed the number of durations when a cycle's start was not observed
+    pub fn from_durations(library: &str, rebuilds: usize, durations_ms: &[u64], failures: usize) -> Self {
+        let mut sorted = durations_ms.to_vec();
+        sorted.sort_unstable();
+
+        Self {
+            library: library.to_string(),
+            rebuilds,
+            failures,
+            min_ms: sorted.first().copied().unwrap_or(0),
+            median_ms: sorted.get(sorted.len() / 2).copied().unwrap_or(0),
+            max_ms: sorted.last().copied().unwrap_or(0),
+        }
+    }
+}
+
+impl BuildHistory {
+    pub fn history_path(config_file: &ConfigFile) -> Result<PathBuf> {
+        Ok(config_file.dir()?.join("history.json"))
+    }
+
+    pub fn load(config_file: &ConfigFile) -> Result<Self> {
+        let path = Self::history_path(config_file)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, config_file: &ConfigFile) -> Result<()> {
+        let path = Self::history_path(config_file)?;
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, content)?;
+        Ok(())
+    }
+
+    /// Append a build record, trimming old entries for the same library
+    pub fn record_build(&mut self, record: BuildRecord) {
+        let library = record.library.clone();
+        self.builds.push(record);
+
+        let count = self.builds.iter().filter(|r| r.library == library).count();
+        if count > MAX_RECORDS_PER_LIBRARY {
+            let mut to_drop = count - MAX_RECORDS_PER_LIBRARY;
+            self.builds.retain(|r| {
+                if to_drop > 0 && r.library == library {
+                    to_drop -= 1;
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+    }
+
+    pub fn record_session(&mut self, session: ServeSession) {
+        self.sessions.push(session);
+        if self.sessions.len() > MAX_SERVE_SESSIONS {
+            let excess = self.sessions.len() - MAX_SERVE_SESSIONS;
+            self.sessions.drain(..excess);
+        }
+    }
+
+    /// Append a dist snapshot, keeping only the most recent ones per package
+    pub fn record_snapshot(&mut self, snapshot: DistSnapshot) {
+        let package = snapshot.package.clone();
+        self.snapshots.push(snapshot);
+
+        let count = self.snapshots.iter().filter(|s| s.package == package).count();
+        if count > MAX_SNAPSHOTS_PER_PACKAGE {
+            let mut to_drop = count - MAX_SNAPSHOTS_PER_PACKAGE;
+            self.snapshots.retain(|s| {
+                if to_drop > 0 && s.package == package {
+                    to_drop -= 1;
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+    }
+
+    pub fn record_publish(&mut self, record: PublishRecord) {
+        self.publishes.push(record);
+        if self.publishes.len() > MAX_PUBLISHES {
+            let excess = self.publishes.len() - MAX_PUBLISHES;
+            self.publishes.drain(..excess);
+        }
+    }
+
+    pub fn record_smoke(&mut self, record: SmokeRecord) {
+        self.smokes.push(record);
+        if self.smokes.len() > MAX_SMOKES {
+            let excess = self.smokes.len() - MAX_SMOKES;
+            self.smokes.drain(..excess);
+        }
+    }
+
+    /// Snapshots of a package, oldest first
+    pub fn snapshots_for(&self, package: &str) -> Vec<&DistSnapshot> {
+        self.snapshots.iter().filter(|s| s.package == package).collect()
+    }
+
+    /// Most recent successful build of a library that captured size data
+    pub fn last_sizes(&self, library: &str) -> Option<&BundleSizes> {
+        self.builds
+            .iter()
+            .rev()
+            .filter(|r| r.library == library && r.success)
+            .find_map(|r| r.sizes.as_ref())
+    }
+
+    pub fn builds_for(&self, library: &str) -> Vec<&BuildRecord> {
+        self.builds.iter().filter(|r| r.library == library).collect()
+    }
+}
+
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Time of day of a unix timestamp as `HH:MM:SS` in UTC
+pub fn format_clock(timestamp: u64) -> String {
+    let seconds = timestamp % 86400;
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+/// Render a unix timestamp as a short relative age ("5m ago", "3d ago")
+pub fn format_age(timestamp: u64) -> String {
+    let elapsed = now_timestamp().saturating_sub(timestamp);
+    match elapsed {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", elapsed / 60),
+        3600..=86399 => format!("{}h ago", elapsed / 3600),
+        _ => format!("{}d ago", elapsed / 86400),
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    let b = bytes as f64;
+    if b >= MB {
+        format!("{:.2} MB", b / MB)
+    } else if b >= KB {
+        format!("{:.1} kB", b / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+/// Collect per-format sizes from a library's dist directory.
+/// Returns None when the directory is missing or empty rather than failing.
+pub fn collect_bundle_sizes(dist_dir: &Path, build_output: &str) -> Option<BundleSizes> {
+    if !dist_dir.is_dir() {
+        return None;
+    }
+
+    let mut sizes = BundleSizes {
+        initial_total: parse_initial_total(build_output),
+        ..Default::default()
+    };
+    accumulate_sizes(dist_dir, dist_dir, &mut sizes);
+
+    if sizes.total == 0 {
+        None
+    } else {
+        Some(sizes)
+    }
+}
+
+fn accumulate_sizes(root: &Path, dir: &Path, sizes: &mut BundleSizes) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            accumulate_sizes(root, &path, sizes);
+            continue;
+        }
+
+        let len = match entry.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(_) => continue,
+        };
+        sizes.total += len;
+
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let top_level = path.strip_prefix(root).ok()
+            .and_then(|rel| rel.components().next())
+            .and_then(|c| c.as_os_str().to_str())
+            .unwrap_or("");
+
+        let format = if file_name.ends_with(".d.ts") {
+            Some("types".to_string())
+        } else if top_level.starts_with("fesm") || top_level.starts_with("esm") {
+            Some(top_level.to_string())
+        } else {
+            None
+        };
+
+        if let Some(format) = format {
+            *sizes.formats.entry(format).or_insert(0) += len;
+        }
+    }
+}
+
+/// Parse the "Initial total" row printed by the application builders, e.g.
+/// `                    | Initial total |   1.23 MB |   250.12 kB`
+fn parse_initial_total(output: &str) -> Option<u64> {
+    let line = output.lines().find(|l| l.contains("Initial total"))?;
+    let raw = line.split('|').map(|s| s.trim()).find(|s| {
+        s.ends_with("kB") || s.ends_with("MB") || s.ends_with(" bytes") || s.ends_with(" B")
+    })?;
+
+    let mut parts = raw.split_whitespace();
+    let value: f64 = parts.next()?.parse().ok()?;
+    let multiplier = match parts.next()? {
+        "MB" => 1024.0 * 1024.0,
+        "kB" => 1024.0,
+        _ => 1.0,
+    };
+    Some((value * multiplier) as u64)
+}
+
+/// Percentage growth of `current` relative to `previous`
+pub fn growth_percent(previous: u64, current: u64) -> Option<f64> {
+    if previous == 0 {
+        return None;
+    }
+    Some((current as f64 - previous as f64) / previous as f64 * 100.0)
+}
+
+pub fn history_command(config_file: &ConfigFile, package: Option<&str>, sizes: bool) -> Result<()> {
+    let history = BuildHistory::load(config_file)?;
+
+    if history.builds.is_empty() && history.sessions.is_empty() && history.publishes.is_empty() && history.smokes.is_empty() {
+        println!("No build history recorded yet. Run 'spine build <library>' to start tracking.");
+        return Ok(());
+    }
+
+    match (package, sizes) {
+        (Some(library), true) => print_size_trend(&history, library),
+        (Some(library), false) => {
+            print_builds(&history.builds_for(library));
+            let publishes: Vec<&PublishRecord> = history.publishes.iter().filter(|r| r.package == library).collect();
+            print_publishes(&publishes);
+            let smokes: Vec<&SmokeRecord> = history.smokes.iter().filter(|r| r.package == library).collect();
+            print_smokes(&smokes);
+            Ok(())
+        }
+        (None, true) => {
+            println!("Please specify a library: spine history --sizes <library>");
+            Ok(())
+        }
+        (None, false) => {
+            let all: Vec<&BuildRecord> = history.builds.iter().collect();
+            print_builds(&all);
+            print_publishes(&history.publishes.iter().collect::<Vec<_>>());
+            print_smokes(&history.smokes.iter().collect::<Vec<_>>());
+            for session in history.sessions.iter().rev().take(5) {
+                println!();
+                print_serve_session(session);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_builds(records: &[&BuildRecord]) {
+    if records.is_empty() {
+        println!("No builds recorded for this library.");
+        return;
+    }
+
+    println!("📜 Build History");
+    println!("================");
+    for record in records.iter().rev().take(20) {
+        let status = if record.success { "✅" } else { "❌" };
+        let size = record.sizes.as_ref()
+            .map(|s| format!(" [{}]", format_bytes(s.total)))
+            .unwrap_or_default();
+        println!("  {} {} ({:.1}s, {}){}",
+            status,
+            record.library,
+            record.duration_ms as f64 / 1000.0,
+            format_age(record.timestamp),
+            size
+        );
+    }
+}
+
+fn print_publishes(records: &[&PublishRecord]) {
+    if records.is_empty() {
+        return;
+    }
+
+    println!("\n🚀 Publishes");
+    for record in records.iter().rev().take(10) {
+        let tree = match record.dirty {
+            Some(false) => "clean tree".to_string(),
+            Some(true) => format!("⚠️  dirty tree, {} uncommitted file(s)", record.dirty_files.len()),
+            None => "not in git".to_string(),
+        };
+        println!("  {} v{} ({}, {})",
+            record.package,
+            record.version.as_deref().unwrap_or("?"),
+            format_age(record.timestamp),
+            tree
+        );
+    }
+}
+
+fn print_smokes(records: &[&SmokeRecord]) {
+    if records.is_empty() {
+        return;
+    }
+
+    println!("\n💨 Smoke tests");
+    for record in records.iter().rev().take(10) {
+        let outcome = match (&record.failed_stage, record.errors) {
+            (None, _) => "✅ passed".to_string(),
+            (Some(stage), 0) => format!("❌ failed at {}", stage),
+            (Some(stage), errors) => format!("❌ failed at {}, {} error(s)", stage, errors),
+        };
+        println!("  {} in {} {} ({:.1}s, {})",
+            record.package,
+            crate::paths::display_path(&record.project),
+            outcome,
+            record.duration_ms as f64 / 1000.0,
+            format_age(record.timestamp)
+        );
+    }
+}
+
+pub fn print_serve_session(session: &ServeSession) {
+    println!("🌐 Serve session: {} ({}, ran {})",
+        session.app,
+        format_age(session.started_at),
+        format_duration_ms(session.duration_ms)
+    );
+    if let Some(ready_after_ms) = session.ready_after_ms {
+        println!("  App server ready {} after it started", format_duration_ms(ready_after_ms));
+    }
+
+    if session.libraries.is_empty() {
+        println!("  No library rebuilds recorded.");
+    } else {
+        println!("  {:<30} {:>8} {:>8} {:>8} {:>8} {:>8}", "library", "rebuilds", "min", "median", "max", "failed");
+        for stats in &session.libraries {
+            println!("  {:<30} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                stats.library,
+                stats.rebuilds,
+                format_duration_ms(stats.min_ms),
+                format_duration_ms(stats.median_ms),
+                format_duration_ms(stats.max_ms),
+                stats.failures
+            );
+        }
+    }
+
+    for change in &session.watcher_changes {
+        println!("  ⏸️  {} at {} UTC: {}; {}", change.library, format_clock(change.at), change.reason, change.outcome);
+    }
+}
+
+/// Render milliseconds as "850ms", "3.2s" or "4m12s"
+pub fn format_duration_ms(ms: u64) -> String {
+    match ms {
+        0..=999 => format!("{}ms", ms),
+        1000..=59_999 => format!("{:.1}s", ms as f64 / 1000.0),
+        _ => format!("{}m{:02}s", ms / 60_000, (ms % 60_000) / 1000),
+    }
+}
+
+fn print_size_trend(history: &BuildHistory, library: &str) -> Result<()> {
+    let records: Vec<&BuildRecord> = history.builds_for(library)
+        .into_iter()
+        .filter(|r| r.success && r.sizes.is_some())
+        .collect();
+
+    if records.is_empty() {
+        println!("No size data recorded for '{}'.", library);
+        return Ok(());
+    }
+
+    // Collect the union of formats so each column lines up across rows
+    let mut formats: Vec<String> = records.iter()
+        .flat_map(|r| r.sizes.as_ref().unwrap().formats.keys().cloned())
+        .collect();
+    formats.sort();
+    formats.dedup();
+
+    println!("📈 Bundle size trend for {}", library);
+    print!("  {:<10}", "when");
+    for format in &formats {
+        print!(" {:>12}", format);
+    }
+    println!(" {:>12} {:>8}", "total", "change");
+
+    let mut previous_total: Option<u64> = None;
+    for record in records.iter().rev().take(10).rev() {
+        let sizes = record.sizes.as_ref().unwrap();
+        print!("  {:<10}", format_age(record.timestamp));
+        for format in &formats {
+            let value = sizes.formats.get(format)
+                .map(|v| format_bytes(*v))
+                .unwrap_or_else(|| "-".to_string());
+            print!(" {:>12}", value);
+        }
+        let change = previous_total
+            .and_then(|prev| growth_percent(prev, sizes.total))
+            .map(|pct| format!("{:+.1}%", pct))
+            .unwrap_or_else(|| "-".to_string());
+        println!(" {:>12} {:>8}", format_bytes(sizes.total), change);
+        previous_total = Some(sizes.total);
+    }
+
+    Ok(())
+}
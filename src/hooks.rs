@@ -0,0 +1,86 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use anyhow::Result;
+use crate::config::PackageLink;
+use crate::error::SpineError;
+use crate::paths::display_path;
+use crate::platform::{run_command, OutputMode};
+
+/// When a package's hook commands run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPoint {
+    PreLink,
+    PostLink,
+    PreUnlink,
+    PostUnlink,
+}
+
+impl HookPoint {
+    pub const ALL: [HookPoint; 4] = [HookPoint::PreLink, HookPoint::PostLink, HookPoint::PreUnlink, HookPoint::PostUnlink];
+
+    /// Name of the config field holding the commands
+    pub fn name(self) -> &'static str {
+        match self {
+            HookPoint::PreLink => "pre_link",
+            HookPoint::PostLink => "post_link",
+            HookPoint::PreUnlink => "pre_unlink",
+            HookPoint::PostUnlink => "post_unlink",
+        }
+    }
+
+    fn is_pre(self) -> bool {
+        matches!(self, HookPoint::PreLink | HookPoint::PreUnlink)
+    }
+}
+
+/// Run a package's hook commands for `point`. Pre hooks run in the package
+/// source directory and abort the operation on failure; post hooks run in the
+/// consuming project and only warn, since the link change already happened.
+pub fn run_hooks(link: &PackageLink, point: HookPoint, project: &Path, timeout: Option<Duration>) -> Result<()> {
+    let dir = if point.is_pre() { link.path.as_path() } else { project };
+
+    for hook in link.hooks(point) {
+        println!("🪝 {} {}: {}", link.name, point.name(), hook);
+        let mut command = shell_command(hook);
+        command.current_dir(dir)
+            .env("SPINE_PACKAGE", &link.name)
+            .env("SPINE_PACKAGE_PATH", &link.path)
+            .env("SPINE_PROJECT_PATH", project);
+
+        let failure = match run_command(command, timeout, OutputMode::Stream) {
+            Ok(output) if output.success() => continue,
+            Ok(output) => match output.status.code() {
+                Some(code) => format!("exited with status {}", code),
+                None => "was terminated by a signal".to_string(),
+            },
+            Err(e) => e.to_string(),
+        };
+
+        if point.is_pre() {
+            return Err(SpineError::CommandFailed {
+                command: hook.clone(),
+                error: format!("{} hook of {} {} in {}", point.name(), link.name, failure, display_path(dir)),
+                suggestion: format!("Fix the command, or clear it with 'spine config set links.{}.{} \"\"'", link.name, point.name()),
+            }.into());
+        }
+        println!("⚠️  {} hook of {} {}; the {} was not rolled back", point.name(), link.name, failure,
+            if point == HookPoint::PostLink { "link" } else { "unlink" });
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+fn shell_command(hook: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.args(["/C", hook]);
+    command
+}
+
+#[cfg(not(windows))]
+fn shell_command(hook: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.args(["-c", hook]);
+    command
+}
@@ -0,0 +1,348 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::config::{Config, ConfigFile};
+use crate::history;
+use crate::lock::FileLock;
+use crate::paths::display_path;
+use crate::platform::Platform;
+
+/// Intent record written before a mutating batch operation. If it is still on
+/// disk at the next startup and its owner has exited, the operation was
+/// interrupted part-way through.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationJournal {
+    pub operation: String,
+    pub targets: Vec<String>,
+    pub project: PathBuf,
+    pub started_at: u64,
+    /// The spine process running the operation; 0 in journals written
+    /// before it was recorded
+    #[serde(default)]
+    pub pid: u32,
+    /// When that process started, from `Platform::process_started`, so a
+    /// reused pid isn't mistaken for the owner
+    #[serde(default)]
+    pub process_started: Option<u64>,
+}
+
+/// Handle for an in-flight journaled operation
+pub struct JournalEntry {
+    path: PathBuf,
+}
+
+impl JournalEntry {
+    /// Mark the operation as finished and remove the intent file
+    pub fn complete(self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    /// Save `config` and only then remove the intent file. Call it on the
+    /// error path as well: once the links done so far are saved the journal
+    /// has nothing left to reconcile, and if the save fails it stays behind
+    /// for the next startup.
+    pub fn finish(self, config: &Config) -> Result<()> {
+        config.save()?;
+        self.complete()
+    }
+}
+
+impl OperationJournal {
+    /// Where this process records its operations. Each process has its own
+    /// file, so concurrent operations in different projects don't overwrite
+    /// or remove each other's journal.
+    pub fn journal_path(config_file: &ConfigFile) -> Result<PathBuf> {
+        Ok(config_file.dir()?.join(format!("journal-{}.json", std::process::id())))
+    }
+
+    /// Journals in `dir`, including the single journal.json older versions wrote
+    fn journals_in(dir: &Path) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+        let mut journals: Vec<PathBuf> = entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                name == "journal.json" || (name.starts_with("journal-") && name.ends_with(".json"))
+            })
+            .collect();
+        journals.sort();
+        journals
+    }
+
+    /// Record the intent to run `operation` on `targets` in `project`
+    pub fn begin(config_file: &ConfigFile, operation: &str, targets: &[String], project: &Path) -> Result<JournalEntry> {
+        Self::begin_at(&Self::journal_path(config_file)?, operation, targets, project)
+    }
+
+    fn begin_at(path: &Path, operation: &str, targets: &[String], project: &Path) -> Result<JournalEntry> {
+        let pid = std::process::id();
+        let journal = Self {
+            operation: operation.to_string(),
+            targets: targets.to_vec(),
+            project: project.to_path_buf(),
+            started_at: history::now_timestamp(),
+            pid,
+            process_started: Platform::process_started(pid),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&journal)?)?;
+
+        Ok(JournalEntry { path: path.to_path_buf() })
+    }
+
+    fn load_from(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(path)?;
+        match serde_json::from_str(&content) {
+            Ok(journal) => Ok(Some(journal)),
+            Err(e) => {
+                println!("⚠️  Ignoring unreadable operation journal {}: {}", display_path(path), e);
+                fs::remove_file(path)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Whether the process that wrote the journal is still running it
+    pub fn owner_alive(&self) -> bool {
+        if self.pid == 0 {
+            return false;
+        }
+        match (Platform::process_started(self.pid), self.process_started) {
+            (Some(started), Some(recorded)) => started == recorded,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+/// Check for journals left behind by interrupted operations and reconcile
+/// the recorded links of the affected packages with what is on disk. Holds
+/// the config lock meanwhile, and leaves journals whose owner is still
+/// running alone. Returns true when the config was changed and needs saving.
+pub fn recover_interrupted(config: &mut Config) -> Result<bool> {
+    let _lock = FileLock::acquire(&config.file.path()?)?;
+    recover_all_in(config, &config.file.dir()?)
+}
+
+fn recover_all_in(config: &mut Config, dir: &Path) -> Result<bool> {
+    let mut changed = false;
+    for path in OperationJournal::journals_in(dir) {
+        changed |= recover_from(config, &path)?;
+    }
+    Ok(changed)
+}
+
+fn recover_from(config: &mut Config, path: &Path) -> Result<bool> {
+    let journal = match OperationJournal::load_from(path)? {
+        Some(journal) => journal,
+        None => return Ok(false),
+    };
+    if journal.owner_alive() {
+        return Ok(false);
+    }
+
+    println!(
+        "⚠️  A previous '{}' in {} was interrupted ({}). Reconciling {} package(s)...",
+        journal.operation,
+        display_path(&journal.project),
+        history::format_age(journal.started_at),
+        journal.targets.len()
+    );
+
+    let project = journal.project.canonicalize().unwrap_or_else(|_| journal.project.clone());
+    let mut changed = false;
+
+    for package_name in &journal.targets {
+        let (recorded, npm_name) = match config.links.get(package_name) {
+            Some(link) => (
+                link.linked_projects.iter().any(|p| crate::paths::same_path(p, &project)),
+                link.npm_name().to_string(),
+            ),
+            None => {
+                println!("  ○ {}: no longer configured, skipping", package_name);
+                continue;
+            }
+        };
+        let actual = Config::is_package_linked_in_project_static(&npm_name, &project);
+
+        match (recorded, actual) {
+            (false, true) => {
+                config.add_linked_project(package_name, project.clone())?;
+                println!("  🔗 {}: linked on disk, recorded in config", package_name);
+                changed = true;
+            }
+            (true, false) => {
+                config.remove_linked_project(package_name, &project)?;
+                println!("  🔓 {}: not linked on disk, removed from config", package_name);
+                changed = true;
+            }
+            _ => println!("  ✅ {}: consistent", package_name),
+        }
+    }
+
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    if changed {
+        println!("✨ Recovery complete. Run 'spine sync' in that project to restore any missing links.");
+    } else {
+        println!("✨ Recovery complete. No inconsistencies found.");
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PackageLink;
+
+    /// A library at `root/lib-a` and a project at `root/app`, with the
+    /// library configured but not recorded as linked anywhere
+    fn setup(root: &Path) -> (Config, PathBuf) {
+        let library = root.join("lib-a");
+        let project = root.join("app");
+        fs::create_dir_all(&library).unwrap();
+        fs::create_dir_all(project.join("node_modules")).unwrap();
+        fs::write(library.join("package.json"), r#"{ "name": "lib-a", "version": "1.0.0" }"#).unwrap();
+        fs::write(project.join("package.json"), r#"{ "name": "app", "version": "1.0.0" }"#).unwrap();
+
+        let mut config = Config::default();
+        config.links.insert("lib-a".to_string(), PackageLink::new("lib-a".to_string(), library));
+        (config, project.canonicalize().unwrap())
+    }
+
+    /// The intent file a spine process that died mid-operation leaves behind
+    fn write_crashed_journal(path: &Path, project: &Path) {
+        let mut child = std::process::Command::new(std::env::current_exe().unwrap())
+            .arg("--list")
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        let journal = serde_json::json!({
+            "operation": "link-all",
+            "targets": ["lib-a"],
+            "project": project,
+            "started_at": history::now_timestamp(),
+            "pid": pid,
+        });
+        fs::write(path, journal.to_string()).unwrap();
+    }
+
+    fn is_recorded(config: &Config, project: &Path) -> bool {
+        config.links["lib-a"].linked_projects.iter().any(|p| crate::paths::same_path(p, project))
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn records_a_link_made_before_the_crash() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut config, project) = setup(dir.path());
+        std::os::unix::fs::symlink(dir.path().join("lib-a"), project.join("node_modules/lib-a")).unwrap();
+        let journal = dir.path().join("journal.json");
+        write_crashed_journal(&journal, &project);
+
+        assert!(recover_from(&mut config, &journal).unwrap());
+        assert!(is_recorded(&config, &project));
+        assert!(!journal.exists());
+    }
+
+    #[test]
+    fn forgets_a_recorded_link_missing_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut config, project) = setup(dir.path());
+        config.add_linked_project("lib-a", project.clone()).unwrap();
+        let journal = dir.path().join("journal.json");
+        write_crashed_journal(&journal, &project);
+
+        assert!(recover_from(&mut config, &journal).unwrap());
+        assert!(!is_recorded(&config, &project));
+        assert!(!journal.exists());
+    }
+
+    #[test]
+    fn consistent_state_is_left_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut config, project) = setup(dir.path());
+        let journal = dir.path().join("journal.json");
+        write_crashed_journal(&journal, &project);
+
+        assert!(!recover_from(&mut config, &journal).unwrap());
+        assert!(!is_recorded(&config, &project));
+        assert!(!journal.exists());
+    }
+
+    #[test]
+    fn leaves_the_journal_of_a_running_operation_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut config, project) = setup(dir.path());
+        config.add_linked_project("lib-a", project.clone()).unwrap();
+        let journal = dir.path().join("journal.json");
+        let entry = OperationJournal::begin_at(&journal, "link-all", &["lib-a".to_string()], &project).unwrap();
+
+        assert!(!recover_from(&mut config, &journal).unwrap());
+        assert!(is_recorded(&config, &project));
+        assert!(journal.exists());
+        entry.complete().unwrap();
+        assert!(!journal.exists());
+    }
+
+    #[test]
+    fn recovers_a_crashed_journal_beside_a_running_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut config, project) = setup(dir.path());
+        config.add_linked_project("lib-a", project.clone()).unwrap();
+        let crashed = dir.path().join("journal-1.json");
+        write_crashed_journal(&crashed, &project);
+        let running = dir.path().join(format!("journal-{}.json", std::process::id()));
+        let entry = OperationJournal::begin_at(&running, "sync", &["lib-a".to_string()], &project).unwrap();
+
+        assert!(recover_all_in(&mut config, dir.path()).unwrap());
+        assert!(!is_recorded(&config, &project));
+        assert!(!crashed.exists());
+        assert!(running.exists());
+        entry.complete().unwrap();
+    }
+
+    #[test]
+    fn a_reused_pid_is_not_the_owner() {
+        let pid = std::process::id();
+        let started = Platform::process_started(pid).unwrap();
+        let journal = OperationJournal {
+            operation: "sync".to_string(),
+            targets: Vec::new(),
+            project: PathBuf::new(),
+            started_at: 0,
+            pid,
+            process_started: Some(started.wrapping_add(1)),
+        };
+        // Without a start time to compare on this platform, any live pid counts
+        assert_eq!(journal.owner_alive(), cfg!(all(unix, not(target_os = "linux"))));
+        assert!(OperationJournal { process_started: Some(started), ..journal.clone() }.owner_alive());
+        assert!(!OperationJournal { pid: 0, ..journal }.owner_alive());
+    }
+
+    #[test]
+    fn an_unreadable_journal_is_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut config, _) = setup(dir.path());
+        let journal = dir.path().join("journal.json");
+        fs::write(&journal, "{ half written").unwrap();
+
+        assert!(!recover_from(&mut config, &journal).unwrap());
+        assert!(!journal.exists());
+    }
+}
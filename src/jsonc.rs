@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::Path;
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use crate::error::SpineError;
+
+/// Read a JSON-with-comments file (angular.json, project.json, tsconfig) the way
+/// the Angular CLI does: `//` and `/* */` comments and trailing commas are
+/// allowed. package.json should keep using strict serde_json parsing.
+pub fn read_jsonc_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = fs::read_to_string(path)?;
+    parse_jsonc(&content).map_err(|e| {
+        SpineError::Config(format!("Invalid {}: {}", path.display(), e)).into()
+    })
+}
+
+/// Parse JSONC text. Comments are blanked out rather than removed so serde's
+/// line/column positions still point at the original file.
+pub fn parse_jsonc<T: DeserializeOwned>(content: &str) -> serde_json::Result<T> {
+    let stripped = strip_trailing_commas(&strip_comments(content));
+    serde_json::from_str(&stripped)
+}
+
+fn strip_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        out.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                out.push(' ');
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                    out.push(' ');
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                out.push_str("  ");
+                let mut previous = '\0';
+                for next in chars.by_ref() {
+                    out.push(if next == '\n' { '\n' } else { ' ' });
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+fn strip_trailing_commas(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut out = String::with_capacity(content.len());
+    let mut in_string = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            out.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                out.push(chars[i + 1]);
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+            out.push(c);
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            out.push(if matches!(next, Some('}') | Some(']')) { ' ' } else { c });
+        } else {
+            out.push(c);
+        }
+        i += 1;
+    }
+
+    out
+}
@@ -0,0 +1,218 @@
+use std::fs;
+use std::path::Path;
+use anyhow::Result;
+use serde::Serialize;
+use crate::config::{Config, PackageLink};
+use crate::package;
+
+/// License summary of one package
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseEntry {
+    pub name: String,
+    pub version: Option<String>,
+    pub license: Option<String>,
+    pub license_file: bool,
+    pub author: Option<String>,
+    pub repository: Option<String>,
+    /// Problems worth flagging before shipping
+    pub warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<LicenseEntry>,
+}
+
+impl LicenseEntry {
+    fn for_package(name: &str, dir: &Path, deny: &[String]) -> Self {
+        let package_json = dir.join("package.json");
+        let metadata = package::parse_package_metadata(&package_json).unwrap_or_default();
+        let license_file = has_license_file(dir);
+
+        let mut warnings = Vec::new();
+        match &metadata.license {
+            None => warnings.push("no license field in package.json".to_string()),
+            Some(license) => {
+                if let Some(denied) = denied_license(license, deny) {
+                    warnings.push(format!("copyleft license {} (matches deny-list entry '{}')", license, denied));
+                }
+            }
+        }
+
+        Self {
+            name: name.to_string(),
+            version: package::get_package_version(&package_json).ok(),
+            license: metadata.license,
+            license_file,
+            author: metadata.author,
+            repository: metadata.repository,
+            warnings,
+            dependencies: Vec::new(),
+        }
+    }
+
+    fn has_problems(&self) -> bool {
+        !self.warnings.is_empty() || self.dependencies.iter().any(|d| d.has_problems())
+    }
+}
+
+/// Summarize a configured package, optionally including its direct dependencies
+pub fn license_entry(link: &PackageLink, deny: &[String], deep: bool) -> LicenseEntry {
+    let mut entry = LicenseEntry::for_package(&link.name, &link.path, deny);
+    if !link.path.exists() {
+        entry.warnings = vec!["path does not exist".to_string()];
+        return entry;
+    }
+
+    if deep {
+        let dependencies = package::parse_package_json(&link.path.join("package.json"))
+            .map(|info| info.dependencies)
+            .unwrap_or_default();
+        for dependency in dependencies {
+            entry.dependencies.push(dependency_entry(&link.path, &dependency, deny));
+        }
+        entry.dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    entry
+}
+
+/// Find a dependency's license in the nearest node_modules, falling back to the
+/// `license` recorded in a package-lock.json
+fn dependency_entry(package_dir: &Path, dependency: &str, deny: &[String]) -> LicenseEntry {
+    for dir in package_dir.ancestors() {
+        let installed = crate::paths::node_modules_path(dir, dependency);
+        if installed.join("package.json").exists() {
+            return LicenseEntry::for_package(dependency, &installed, deny);
+        }
+
+        if let Some(license) = lockfile_license(&dir.join("package-lock.json"), dependency) {
+            let mut entry = LicenseEntry {
+                name: dependency.to_string(),
+                version: None,
+                license: Some(license.clone()),
+                license_file: false,
+                author: None,
+                repository: None,
+                warnings: Vec::new(),
+                dependencies: Vec::new(),
+            };
+            if let Some(denied) = denied_license(&license, deny) {
+                entry.warnings.push(format!("copyleft license {} (matches deny-list entry '{}')", license, denied));
+            }
+            return entry;
+        }
+    }
+
+    LicenseEntry {
+        name: dependency.to_string(),
+        version: None,
+        license: None,
+        license_file: false,
+        author: None,
+        repository: None,
+        warnings: vec!["not installed; license unknown".to_string()],
+        dependencies: Vec::new(),
+    }
+}
+
+fn lockfile_license(lockfile: &Path, dependency: &str) -> Option<String> {
+    let content = fs::read_to_string(lockfile).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    json.get("packages")?
+        .get(format!("node_modules/{}", dependency))?
+        .get("license")?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+fn has_license_file(dir: &Path) -> bool {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries.flatten().any(|entry| {
+                let name = entry.file_name().to_string_lossy().to_uppercase();
+                name.starts_with("LICENSE") || name.starts_with("LICENCE") || name.starts_with("COPYING")
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// The deny-list entry an SPDX expression falls foul of. An `OR` expression is
+/// acceptable as long as one alternative is clean.
+pub fn denied_license<'a>(license: &str, deny: &'a [String]) -> Option<&'a String> {
+    let alternatives: Vec<Option<&String>> = license
+        .split(" OR ")
+        .map(|alternative| {
+            alternative
+                .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                .filter(|token| !token.is_empty() && *token != "AND" && *token != "WITH")
+                .find_map(|token| {
+                    let token = token.to_uppercase();
+                    deny.iter().find(|d| token.starts_with(&d.to_uppercase()))
+                })
+        })
+        .collect();
+
+    if alternatives.iter().any(|denied| denied.is_none()) {
+        None
+    } else {
+        alternatives.into_iter().flatten().next()
+    }
+}
+
+pub fn licenses_command(config: &Config, json: bool, deep: bool) -> Result<()> {
+    let deny = &config.licenses.deny;
+    let mut links: Vec<&PackageLink> = config.links.values().collect();
+    links.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let entries: Vec<LicenseEntry> = links.iter().map(|link| license_entry(link, deny, deep)).collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No package links configured.");
+        return Ok(());
+    }
+
+    println!("📜 Licenses of linked packages");
+    println!("   {:<32} {:<10} {:<20} {:<8} author", "package", "version", "license", "file");
+    for entry in &entries {
+        print_row(entry, "");
+        for dependency in &entry.dependencies {
+            print_row(dependency, "└─ ");
+        }
+    }
+
+    let flagged: Vec<&LicenseEntry> = entries.iter().filter(|e| e.has_problems()).collect();
+
+    if flagged.is_empty() {
+        println!("\n✅ No missing or denied licenses.");
+    } else {
+        println!("\n⚠️  Warnings:");
+        for entry in &flagged {
+            for warning in &entry.warnings {
+                println!("  • {}: {}", entry.name, warning);
+            }
+            for dependency in entry.dependencies.iter().filter(|d| !d.warnings.is_empty()) {
+                for warning in &dependency.warnings {
+                    println!("  • {} → {}: {}", entry.name, dependency.name, warning);
+                }
+            }
+        }
+    }
+
+    println!("\n📊 Summary: {} package(s), {} with warnings", entries.len(), flagged.len());
+    Ok(())
+}
+
+fn print_row(entry: &LicenseEntry, prefix: &str) {
+    let icon = if entry.warnings.is_empty() { "✅" } else { "⚠️ " };
+    println!("{} {:<32} {:<10} {:<20} {:<8} {}",
+        icon,
+        format!("{}{}", prefix, entry.name),
+        entry.version.as_deref().unwrap_or("-"),
+        entry.license.as_deref().unwrap_or("(none)"),
+        if entry.license_file { "yes" } else { "no" },
+        entry.author.as_deref().unwrap_or("-"),
+    );
+}
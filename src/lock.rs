@@ -0,0 +1,50 @@
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use crate::error::SpineError;
+
+/// How long to wait for another spine process to finish with a file
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An exclusive advisory lock on `<file>.lock`, released on drop. The OS
+/// drops it too if the process dies, so a leftover lock file is harmless.
+pub struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// Lock `path` for this process, waiting up to ten seconds for another
+    /// holder to let go
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let lock_path = lock_path(path);
+        let file = OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path)
+            .map_err(|e| SpineError::Config(format!("Could not open lock file {}: {}", lock_path.display(), e)))?;
+
+        let started = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(Self { _file: file }),
+                Err(std::fs::TryLockError::WouldBlock) if started.elapsed() < LOCK_TIMEOUT => {
+                    std::thread::sleep(RETRY_INTERVAL);
+                }
+                Err(std::fs::TryLockError::WouldBlock) => {
+                    return Err(SpineError::Config(format!(
+                        "Another spine process has held {} for over {}s; try again once it finishes",
+                        lock_path.display(), LOCK_TIMEOUT.as_secs()
+                    )).into());
+                }
+                Err(std::fs::TryLockError::Error(e)) => {
+                    return Err(SpineError::Config(format!("Could not lock {}: {}", lock_path.display(), e)).into());
+                }
+            }
+        }
+    }
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    path.with_file_name(name)
+}
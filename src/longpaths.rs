@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use crate::error::SpineError;
+use crate::paths::{display_path, node_modules_path};
+
+/// Windows' MAX_PATH, which counts the terminating NUL
+pub const MAX_PATH: usize = 260;
+/// Stop walking a package after this many entries; dist folders are small,
+/// source trees with stray build caches are not
+const WALK_LIMIT: usize = 20_000;
+
+const FILESYSTEM_KEY: &str = r"HKLM\SYSTEM\CurrentControlSet\Control\FileSystem";
+
+/// The longest paths a link involves, in UTF-16 units as Windows counts them
+#[derive(Debug, Clone)]
+pub struct PathLengths {
+    /// The configured package path
+    pub package: usize,
+    /// Deepest file inside the package, if it has any files
+    pub deepest: Option<(PathBuf, usize)>,
+    /// That file as reached through the project's node_modules symlink
+    pub linked: (PathBuf, usize),
+}
+
+impl PathLengths {
+    /// Paths at or over the limit, longest first
+    pub fn over_limit(&self) -> Vec<(&Path, usize)> {
+        let mut over: Vec<(&Path, usize)> = self.deepest.iter()
+            .map(|(path, length)| (path.as_path(), *length))
+            .chain(std::iter::once((self.linked.0.as_path(), self.linked.1)))
+            .filter(|(_, length)| *length >= MAX_PATH)
+            .collect();
+        over.sort_by_key(|(_, length)| std::cmp::Reverse(*length));
+        over
+    }
+}
+
+/// Length of `path` the way the Win32 API measures it: the native form, in
+/// UTF-16 code units. Anything at MAX_PATH or beyond leaves no room for the NUL.
+pub fn path_length(path: &Path) -> usize {
+    let native = crate::paths::normalize_windows_path(&path.to_string_lossy())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+    native.encode_utf16().count()
+}
+
+/// The file with the longest path under `dir`, skipping nested node_modules
+pub fn deepest_file(dir: &Path) -> Option<(PathBuf, usize)> {
+    let mut deepest: Option<(PathBuf, usize)> = None;
+    let mut pending = vec![dir.to_path_buf()];
+    let mut seen = 0;
+
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else { continue };
+        for entry in entries.flatten() {
+            seen += 1;
+            if seen > WALK_LIMIT {
+                return deepest;
+            }
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else { continue };
+            if file_type.is_dir() {
+                if entry.file_name() != "node_modules" {
+                    pending.push(path);
+                }
+                continue;
+            }
+            let length = path_length(&path);
+            if deepest.as_ref().is_none_or(|(_, longest)| length > *longest) {
+                deepest = Some((path, length));
+            }
+        }
+    }
+    deepest
+}
+
+/// Measure a link of the package at `package_path` (npm name `npm_name`) into
+/// `project`. The linked length is what node sees when it resolves the
+/// deepest file through `node_modules/<name>`.
+pub fn measure(package_path: &Path, npm_name: &str, project: &Path) -> PathLengths {
+    let deepest = deepest_file(package_path);
+    let target = node_modules_path(project, npm_name);
+    let linked_path = match &deepest {
+        Some((file, _)) => match file.strip_prefix(package_path) {
+            Ok(relative) => target.join(relative),
+            Err(_) => target,
+        },
+        None => target,
+    };
+    let linked_length = path_length(&linked_path);
+    PathLengths {
+        package: path_length(package_path),
+        deepest,
+        linked: (linked_path, linked_length),
+    }
+}
+
+/// Whether Windows has long path support switched on, from the
+/// `LongPathsEnabled` registry value. None when it can't be read.
+pub fn long_paths_enabled() -> Option<bool> {
+    static ENABLED: OnceLock<Option<bool>> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        if !cfg!(windows) {
+            return None;
+        }
+        let output = std::process::Command::new("reg")
+            .args(["query", FILESYSTEM_KEY, "/v", "LongPathsEnabled"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            // The value is absent on installs that never enabled it
+            return Some(false);
+        }
+        parse_reg_dword(&String::from_utf8_lossy(&output.stdout), "LongPathsEnabled").map(|value| value != 0)
+    })
+}
+
+/// Read a REG_DWORD value from `reg query` output, which lists it as
+/// `    <name>    REG_DWORD    0x1`
+pub fn parse_reg_dword(output: &str, name: &str) -> Option<u32> {
+    output.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        if !fields.next()?.eq_ignore_ascii_case(name) || fields.next()? != "REG_DWORD" {
+            return None;
+        }
+        let value = fields.next()?;
+        match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => value.parse().ok(),
+        }
+    })
+}
+
+/// What to do about paths over the limit
+pub fn guidance() -> String {
+    format!(
+        "Enable long paths (set {}\\LongPathsEnabled to 1 as administrator, or the \"Enable Win32 long paths\" group policy) and restart your terminal; node declares longPathAware in its manifest, so the registry switch is all it needs. Otherwise move the library or project to a shorter path such as C:\\src.",
+        FILESYSTEM_KEY
+    )
+}
+
+/// Refuse a link on Windows when a file in the package would be reached
+/// through node_modules by a path over MAX_PATH and long paths are off, naming
+/// the path rather than leaving npm to fail with ENOENT or EPERM
+pub fn ensure_within_limit(package: &str, package_path: &Path, npm_name: &str, project: &Path) -> Result<(), SpineError> {
+    if !cfg!(windows) || long_paths_enabled() == Some(true) {
+        return Ok(());
+    }
+    let lengths = measure(package_path, npm_name, project);
+    let Some((path, length)) = lengths.over_limit().into_iter().next() else { return Ok(()) };
+    Err(SpineError::InvalidPath(format!(
+        "Linking {} would need the {}-character path {}, over the Windows limit of {}. {}",
+        package, length, display_path(path), MAX_PATH - 1, guidance()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_reg_dword_values() {
+        let output = "\r\nHKEY_LOCAL_MACHINE\\SYSTEM\\CurrentControlSet\\Control\\FileSystem\r\n    LongPathsEnabled    REG_DWORD    0x1\r\n\r\n";
+        assert_eq!(parse_reg_dword(output, "LongPathsEnabled"), Some(1));
+        assert_eq!(parse_reg_dword(&output.replace("0x1", "0x0"), "longpathsenabled"), Some(0));
+        assert_eq!(parse_reg_dword("    LongPathsEnabled    REG_SZ    1", "LongPathsEnabled"), None);
+        assert_eq!(parse_reg_dword("", "LongPathsEnabled"), None);
+    }
+
+    #[test]
+    fn lists_paths_over_the_limit_longest_first() {
+        let lengths = PathLengths {
+            package: 20,
+            deepest: Some((PathBuf::from("deepest"), MAX_PATH)),
+            linked: (PathBuf::from("linked"), MAX_PATH + 40),
+        };
+        let over: Vec<usize> = lengths.over_limit().into_iter().map(|(_, length)| length).collect();
+        assert_eq!(over, [MAX_PATH + 40, MAX_PATH]);
+
+        let short = PathLengths { package: 20, deepest: None, linked: (PathBuf::from("linked"), MAX_PATH - 1) };
+        assert!(short.over_limit().is_empty());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn counts_utf16_units_of_the_native_form() {
+        assert_eq!(path_length(Path::new(r"C:\src\lib")), 10);
+        assert_eq!(path_length(Path::new(r"\\?\C:\src\lib")), 10);
+        assert_eq!(path_length(Path::new("C:/src/lib/")), 10);
+        assert_eq!(path_length(Path::new(r"\\?\UNC\server\share")), r"\\server\share".len());
+        // Characters outside the BMP take a surrogate pair
+        assert_eq!(path_length(Path::new("C:\\src\\\u{1D49C}")), 9);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn measures_the_deepest_file_through_node_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("ui");
+        let project = dir.path().join("app");
+        let nested = package.join("dist").join("fesm2022").join("deeply").join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir_all(package.join("node_modules").join("a-much-longer-dependency-name-than-any-other")).unwrap();
+        std::fs::write(package.join("package.json"), "{}").unwrap();
+        std::fs::write(nested.join("acme-ui.mjs"), "").unwrap();
+        std::fs::write(package.join("node_modules").join("a-much-longer-dependency-name-than-any-other").join("index.js"), "").unwrap();
+
+        let lengths = measure(&package, "@acme/ui", &project);
+        let (deepest, length) = lengths.deepest.clone().expect("a file");
+        assert_eq!(deepest, nested.join("acme-ui.mjs"));
+        assert_eq!(length, path_length(&deepest));
+
+        let linked = project.join("node_modules").join("@acme").join("ui")
+            .join("dist").join("fesm2022").join("deeply").join("nested").join("acme-ui.mjs");
+        assert_eq!(lengths.linked, (linked.clone(), path_length(&linked)));
+        assert_eq!(lengths.package, path_length(&package));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn reads_long_path_support_from_the_registry() {
+        assert!(long_paths_enabled().is_some());
+    }
+}
@@ -1,14 +1,57 @@
+// The config schema is one json! literal, deeper than the default limit allows
+#![recursion_limit = "256"]
+
 mod angular;
 mod angular_cli;
+mod api;
+mod audit;
+mod backups;
+mod batch;
 mod cli;
 mod completion;
 mod config;
+mod config_keys;
+mod deps;
+mod diff;
+mod doctor;
+mod durable;
+mod env;
 mod error;
+mod gitignore;
+mod history;
+mod hooks;
+mod journal;
+mod jsonc;
+mod licenses;
+mod lock;
+mod longpaths;
+mod manifest;
+mod monitor;
+mod monorepo;
 mod npm;
+mod npmrc;
 mod package;
+mod paths;
+mod permissions;
+mod plan;
 mod platform;
+mod pm;
+mod progress;
+mod profile;
+mod prompt;
+mod prompt_status;
+mod registry;
+mod release;
+mod report;
 mod scanner;
+mod schema;
+mod setup;
+mod simple;
+mod smoke;
+mod throttle;
 mod tui;
+mod usage;
+mod watch;
 mod workspace;
 
 use anyhow::Result;
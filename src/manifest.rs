@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::error::SpineError;
+use crate::paths::{display_path, relative_path, resolve_relative, same_path};
+use crate::pm::PackageManager;
+
+/// A portable description of a team's links, written by `spine export-links`
+/// and replayed by `spine apply`. Paths are relative to `root`, which is
+/// itself relative to the manifest's directory.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkManifest {
+    #[serde(default = "default_root")]
+    pub root: String,
+    #[serde(default)]
+    pub links: BTreeMap<String, ManifestLink>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestLink {
+    pub path: String,
+    /// Projects the package is linked into
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    /// Where `spine setup` gets the package when `path` is missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_path: Option<String>,
+}
+
+fn default_root() -> String {
+    ".".to_string()
+}
+
+/// JSON when the file name ends in .json, TOML otherwise
+fn is_json(file: &Path) -> bool {
+    file.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Absolute directory holding `file`, which need not exist yet
+fn manifest_dir(file: &Path) -> Result<PathBuf> {
+    let parent = match file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    parent.canonicalize()
+        .map_err(|e| SpineError::InvalidPath(format!("Cannot access {}: {}", parent.display(), e)).into())
+}
+
+/// `spine export-links`: write every configured link to `file`, with paths
+/// relative to `root` (the manifest's directory by default)
+pub fn export_links(config: &Config, file: &Path, root: Option<&Path>) -> Result<()> {
+    let dir = manifest_dir(file)?;
+    let root = match root {
+        Some(root) => root.canonicalize()
+            .map_err(|e| SpineError::InvalidPath(format!("Cannot access root {}: {}", root.display(), e)))?,
+        None => dir.clone(),
+    };
+
+    let links = config.links.values()
+        .map(|link| {
+            let projects = link.linked_projects.iter().map(|project| relative_path(project, &root)).collect();
+            (link.name.clone(), ManifestLink {
+                path: relative_path(&link.path, &root),
+                projects,
+                repository: link.repository.clone(),
+                build_path: link.build_path.clone(),
+            })
+        })
+        .collect();
+    let manifest = LinkManifest { root: relative_path(&root, &dir), links };
+
+    let content = if is_json(file) {
+        serde_json::to_string_pretty(&manifest)? + "\n"
+    } else {
+        toml::to_string_pretty(&manifest)?
+    };
+    fs::write(file, content).map_err(SpineError::Io)?;
+
+    println!("✓ Exported {} link(s) to {}", manifest.links.len(), file.display());
+    println!("   Paths are relative to {}", display_path(&root));
+    Ok(())
+}
+
+/// `spine apply`: add the manifest's links to the config and link them into
+/// their projects. Entries already configured with another path are conflicts
+/// unless `force`, which points the existing link at the manifest's path and
+/// relinks its projects; paths missing on this machine are listed at the end.
+pub fn apply_manifest(config: &mut Config, file: &Path, root: Option<&Path>, force: bool, pm: Option<PackageManager>) -> Result<()> {
+    let content = fs::read_to_string(file)
+        .map_err(|e| SpineError::Config(format!("Could not read {}: {}", file.display(), e)))?;
+    let manifest: LinkManifest = if is_json(file) {
+        serde_json::from_str(&content).map_err(|e| SpineError::Config(format!("{}: {}", file.display(), e)))?
+    } else {
+        toml::from_str(&content).map_err(|e| SpineError::Config(format!("{}: {}", file.display(), e.message())))?
+    };
+    let root = match root {
+        Some(root) => root.canonicalize()
+            .map_err(|e| SpineError::InvalidPath(format!("Cannot access root {}: {}", root.display(), e)))?,
+        None => resolve_relative(&manifest_dir(file)?, &manifest.root),
+    };
+    println!("Applying {} link(s) from {} (root {})", manifest.links.len(), file.display(), display_path(&root));
+
+    let mut added = 0;
+    let mut unchanged = 0;
+    let mut conflicts = Vec::new();
+    let mut missing = Vec::new();
+    let mut by_project: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+
+    for (name, entry) in &manifest.links {
+        let path = resolve_relative(&root, &entry.path);
+        if !path.exists() {
+            // With a repository the entry is kept, pending, for `spine setup`
+            if let (Some(repository), false) = (&entry.repository, config.links.contains_key(name)) {
+                config.add_link(name.clone(), path.to_string_lossy().to_string(), true)?;
+                let link = config.links.get_mut(name).expect("just added");
+                link.repository = Some(repository.clone());
+                link.build_path = entry.build_path.clone();
+                missing.push(format!("{} → {} (added as pending; 'spine setup {}' clones it)", name, display_path(&path), name));
+            } else {
+                missing.push(format!("{} → {}", name, display_path(&path)));
+            }
+            continue;
+        }
+
+        match config.links.get(name) {
+            Some(existing) if same_path(&existing.path, &path) => unchanged += 1,
+            Some(existing) if !force => {
+                conflicts.push(format!("{}: configured at {}, manifest has {}", name, display_path(&existing.path), display_path(&path)));
+                continue;
+            }
+            Some(existing) => {
+                // The link keeps its projects, tags and hooks, but its
+                // projects still link the old path until relinked
+                let projects = existing.linked_projects.clone();
+                if let Err(e) = config.set_link_path(name, &path.to_string_lossy()) {
+                    conflicts.push(format!("{}: {}", name, e));
+                    continue;
+                }
+                if entry.repository.is_some() {
+                    let link = config.links.get_mut(name).expect("configured");
+                    link.repository = entry.repository.clone();
+                    link.build_path = entry.build_path.clone();
+                }
+                for project in projects.into_iter().filter(|project| project.join("package.json").exists()) {
+                    by_project.entry(project).or_default().push(name.clone());
+                }
+                println!("  ♻️  Replaced {} -> {}", name, display_path(&path));
+                added += 1;
+            }
+            None => {
+                config.add_link(name.clone(), path.to_string_lossy().to_string(), false)?;
+                println!("  ➕ Added {} -> {}", name, display_path(&path));
+                added += 1;
+            }
+        }
+
+        for project in &entry.projects {
+            let project = resolve_relative(&root, project);
+            if project.join("package.json").exists() {
+                let names = by_project.entry(project).or_default();
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            } else {
+                missing.push(format!("{} → project {}", name, display_path(&project)));
+            }
+        }
+    }
+
+    let mut linked = 0;
+    let mut failed = 0;
+    for (project, names) in &by_project {
+        let pm = PackageManager::resolve(pm, config.package_manager, project).manager;
+        println!("\n🔗 Linking {} package(s) into {} with {}", names.len(), display_path(project), pm);
+        let paths: Vec<&Path> = names.iter().map(|name| config.links[name].path.as_path()).collect();
+        if let Err(e) = pm.link_many_in(project, &paths, config.timeouts.link()) {
+            println!("  ❌ {}", e.to_string().trim());
+            failed += names.len();
+            continue;
+        }
+
+        for name in names {
+            let npm_name = config.links[name].npm_name().to_string();
+            if Config::is_package_linked_in_project_static(&npm_name, project) {
+                config.add_linked_project(name, project.clone())?;
+                println!("  ✓ {}", name);
+                linked += 1;
+            } else {
+                println!("  ❌ {}: verification failed", name);
+                failed += 1;
+            }
+        }
+    }
+    config.save()?;
+
+    println!("\n📊 Summary: {} added, {} already configured, {} linked, {} failed, {} conflict(s), {} missing",
+        added, unchanged, linked, failed, conflicts.len(), missing.len());
+    if !conflicts.is_empty() {
+        println!("\n⚠️  Conflicts with the existing configuration (use --force to take the manifest's paths):");
+        for conflict in &conflicts {
+            println!("  • {}", conflict);
+        }
+    }
+    if !missing.is_empty() {
+        println!("\n❔ Not found on this machine:");
+        for entry in &missing {
+            println!("  • {}", entry);
+        }
+        println!("💡 Use --root to resolve the manifest's paths from another directory.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigFile, PackageLink};
+
+    #[test]
+    fn forcing_a_path_keeps_the_rest_of_the_link() {
+        let dir = tempfile::tempdir().unwrap();
+        for (folder, version) in [("old", "1.0.0"), ("new", "2.0.0")] {
+            fs::create_dir_all(dir.path().join(folder)).unwrap();
+            fs::write(dir.path().join(folder).join("package.json"), format!(r#"{{ "name": "ui", "version": "{}" }}"#, version)).unwrap();
+        }
+        let mut config = Config::empty(&ConfigFile::new(Some(&dir.path().join("config.toml"))));
+        let mut link = PackageLink::new("ui".to_string(), dir.path().join("old"));
+        link.tags = vec!["design".to_string()];
+        // A project that has since gone, so nothing is relinked
+        link.linked_projects = vec![dir.path().join("gone")];
+        config.links.insert("ui".to_string(), link);
+
+        let manifest = dir.path().join("links.toml");
+        fs::write(&manifest, "[links.ui]\npath = \"new\"\n").unwrap();
+        apply_manifest(&mut config, &manifest, None, true, None).unwrap();
+
+        let link = &config.links["ui"];
+        assert!(same_path(&link.path, &dir.path().join("new")));
+        assert_eq!(link.version.as_deref(), Some("2.0.0"));
+        assert_eq!(link.tags, ["design"]);
+        assert_eq!(link.linked_projects, [dir.path().join("gone")]);
+    }
+}
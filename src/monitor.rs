@@ -0,0 +1,259 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::config::{Config, ConfigFile, PackageLink};
+use crate::error::SpineError;
+use crate::platform::Platform;
+
+/// Health of one configured link as seen from a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkSnapshot {
+    /// Why the package itself is broken, if it is
+    pub problem: Option<String>,
+    /// Whether the project is expected to have this package linked
+    pub expected_linked: bool,
+    /// Whether node_modules currently points at a valid link
+    pub linked: bool,
+}
+
+impl LinkSnapshot {
+    fn is_problem(&self) -> bool {
+        self.problem.is_some() || (self.expected_linked && !self.linked)
+    }
+}
+
+/// Last snapshot per project, so `--once` runs from cron only alert on new problems
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MonitorState {
+    #[serde(default)]
+    pub projects: HashMap<String, BTreeMap<String, LinkSnapshot>>,
+}
+
+impl MonitorState {
+    pub fn state_path(config_file: &ConfigFile) -> Result<PathBuf> {
+        Ok(config_file.dir()?.join("monitor_state.json"))
+    }
+
+    pub fn load(config_file: &ConfigFile) -> Result<Self> {
+        let path = Self::state_path(config_file)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, config_file: &ConfigFile) -> Result<()> {
+        let path = Self::state_path(config_file)?;
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, content)?;
+        Ok(())
+    }
+}
+
+/// A transition worth reporting between two snapshots
+#[derive(Debug, Clone)]
+pub struct StatusChange {
+    pub package: String,
+    pub message: String,
+    /// True when the change introduces a problem rather than resolving one
+    pub is_new_problem: bool,
+}
+
+/// Check every configured link in parallel
+pub fn collect_snapshots(config: &Config, project: &Path) -> BTreeMap<String, LinkSnapshot> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = config.links.iter()
+            .map(|(name, link)| (name, scope.spawn(move || snapshot_link(link, project))))
+            .collect();
+
+        handles.into_iter()
+            .filter_map(|(name, handle)| handle.join().ok().map(|snapshot| (name.clone(), snapshot)))
+            .collect()
+    })
+}
+
+fn snapshot_link(link: &PackageLink, project: &Path) -> LinkSnapshot {
+    let package_json = link.path.join("package.json");
+    let problem = if link.pending {
+        None
+    } else if !link.path.exists() {
+        Some("path does not exist".to_string())
+    } else if !package_json.exists() {
+        Some("missing package.json".to_string())
+    } else if crate::package::parse_package_json(&package_json).is_err() {
+        Some("invalid package.json".to_string())
+    } else {
+        None
+    };
+
+    let installed = crate::paths::node_modules_path(project, link.npm_name());
+    LinkSnapshot {
+        problem,
+        expected_linked: link.linked_projects.iter().any(|p| crate::paths::same_path(p, project)),
+        linked: installed.is_symlink() && installed.exists(),
+    }
+}
+
+/// Compare two snapshots. Packages that were already broken stay quiet.
+pub fn diff_snapshots(
+    previous: &BTreeMap<String, LinkSnapshot>,
+    current: &BTreeMap<String, LinkSnapshot>,
+) -> Vec<StatusChange> {
+    let mut changes = Vec::new();
+
+    for (name, now) in current {
+        let before = previous.get(name);
+
+        match (before.and_then(|b| b.problem.as_ref()), &now.problem) {
+            (None, Some(problem)) => changes.push(StatusChange {
+                package: name.clone(),
+                message: format!("healthy → broken ({})", problem),
+                is_new_problem: true,
+            }),
+            (Some(_), None) => changes.push(StatusChange {
+                package: name.clone(),
+                message: "broken → healthy".to_string(),
+                is_new_problem: false,
+            }),
+            _ => {}
+        }
+
+        let was_linked = before.map(|b| b.linked).unwrap_or(now.linked);
+        if now.expected_linked && was_linked && !now.linked {
+            changes.push(StatusChange {
+                package: name.clone(),
+                message: "linked → unlinked".to_string(),
+                is_new_problem: true,
+            });
+        } else if !was_linked && now.linked {
+            changes.push(StatusChange {
+                package: name.clone(),
+                message: "unlinked → linked".to_string(),
+                is_new_problem: false,
+            });
+        }
+    }
+
+    for name in previous.keys().filter(|name| !current.contains_key(*name)) {
+        changes.push(StatusChange {
+            package: name.clone(),
+            message: "removed from configuration".to_string(),
+            is_new_problem: false,
+        });
+    }
+
+    changes
+}
+
+/// Problems present with no earlier snapshot to compare against. They count
+/// as new, so a first `--once` run fails on an already broken setup instead
+/// of only recording it as the baseline.
+pub fn initial_problems(current: &BTreeMap<String, LinkSnapshot>) -> Vec<StatusChange> {
+    current.iter()
+        .filter(|(_, snapshot)| snapshot.is_problem())
+        .map(|(name, snapshot)| StatusChange {
+            package: name.clone(),
+            message: match &snapshot.problem {
+                Some(problem) => format!("broken ({})", problem),
+                None => "not linked".to_string(),
+            },
+            is_new_problem: true,
+        })
+        .collect()
+}
+
+pub fn monitor_command(config: &Config, interval: u64, notify: bool, once: bool, quiet: bool) -> Result<()> {
+    let project = std::env::current_dir()?;
+    let project_key = project.display().to_string();
+    let mut state = MonitorState::load(&config.file).unwrap_or_default();
+    let mut previous = state.projects.get(&project_key).cloned();
+
+    if !quiet && !once {
+        println!("👀 Monitoring {} link(s) for {} every {}s (Ctrl+C to stop)",
+            config.links.len(), crate::paths::display_path(&project), interval);
+    }
+
+    loop {
+        // Re-read the config each round so links added meanwhile are picked up
+        let config = Config::load_or_create(&config.file)?;
+        let current = collect_snapshots(&config, &project);
+
+        let changes = match &previous {
+            Some(previous) => diff_snapshots(previous, &current),
+            None => {
+                let problems = initial_problems(&current);
+                if !quiet {
+                    println!("📋 Baseline: {} link(s), {} with problems", current.len(), problems.len());
+                }
+                problems
+            }
+        };
+
+        let new_problems: Vec<&StatusChange> = changes.iter().filter(|c| c.is_new_problem).collect();
+        if !quiet {
+            for change in &changes {
+                let icon = if change.is_new_problem { "❌" } else { "✅" };
+                println!("{} {}: {}", icon, change.package, change.message);
+            }
+        }
+
+        if notify && !new_problems.is_empty() {
+            let body = new_problems.iter()
+                .map(|c| format!("{}: {}", c.package, c.message))
+                .collect::<Vec<_>>()
+                .join("\n");
+            if let Err(e) = Platform::send_notification("Spine: link problem detected", &body) {
+                eprintln!("⚠️  Could not send notification: {}", e);
+            }
+        }
+
+        state.projects.insert(project_key.clone(), current.clone());
+        if let Err(e) = state.save(&config.file) {
+            eprintln!("⚠️  Could not save monitor state: {}", e);
+        }
+        previous = Some(current);
+
+        if once {
+            if !new_problems.is_empty() {
+                return Err(SpineError::Config(format!("{} new link problem(s) detected", new_problems.len())).into());
+            }
+            return Ok(());
+        }
+
+        std::thread::sleep(Duration::from_secs(interval.max(1)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(problem: Option<&str>, expected_linked: bool, linked: bool) -> LinkSnapshot {
+        LinkSnapshot { problem: problem.map(str::to_string), expected_linked, linked }
+    }
+
+    #[test]
+    fn first_run_reports_existing_problems() {
+        let current = BTreeMap::from([
+            ("broken".to_string(), snapshot(Some("missing package.json"), false, false)),
+            ("healthy".to_string(), snapshot(None, true, true)),
+            ("unlinked".to_string(), snapshot(None, true, false)),
+        ]);
+
+        let problems = initial_problems(&current);
+        let packages: Vec<&str> = problems.iter().map(|change| change.package.as_str()).collect();
+        assert_eq!(packages, ["broken", "unlinked"]);
+        assert!(problems.iter().all(|change| change.is_new_problem));
+    }
+
+    #[test]
+    fn later_runs_stay_quiet_about_ongoing_problems() {
+        let current = BTreeMap::from([("broken".to_string(), snapshot(Some("path does not exist"), true, false))]);
+        assert!(diff_snapshots(&current, &current).is_empty());
+    }
+}
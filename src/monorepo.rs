@@ -0,0 +1,213 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories member globs never descend into
+const SKIPPED_DIRS: [&str; 3] = ["node_modules", ".git", "target"];
+
+/// A monorepo's declared package list, read from the file its tool uses
+#[derive(Debug, Clone)]
+pub struct WorkspaceManifest {
+    /// pnpm, npm/yarn or lerna
+    pub tool: &'static str,
+    pub file: PathBuf,
+    /// Globs relative to the root; a leading `!` excludes what it matches
+    pub patterns: Vec<String>,
+}
+
+impl WorkspaceManifest {
+    /// The manifest at `root`, if any: pnpm-workspace.yaml, then package.json
+    /// "workspaces", then lerna.json "packages"
+    pub fn detect(root: &Path) -> Option<Self> {
+        let pnpm = root.join("pnpm-workspace.yaml");
+        if let Some(patterns) = fs::read_to_string(&pnpm).ok().map(|content| pnpm_packages(&content)) {
+            return Some(Self { tool: "pnpm", file: pnpm, patterns });
+        }
+
+        let package_json = root.join("package.json");
+        let json = read_json(&package_json);
+        let workspaces = json.as_ref().and_then(|json| json.get("workspaces"));
+        // An array, or yarn's { "packages": [...] }
+        let list = workspaces.and_then(|workspaces| workspaces.as_array().or_else(|| workspaces.get("packages")?.as_array()));
+        if let Some(list) = list {
+            return Some(Self { tool: "npm/yarn", file: package_json, patterns: strings(list) });
+        }
+
+        let lerna = root.join("lerna.json");
+        let json = read_json(&lerna)?;
+        let patterns = match json.get("packages").and_then(|packages| packages.as_array()) {
+            Some(list) => strings(list),
+            None => vec!["packages/*".to_string()],
+        };
+        Some(Self { tool: "lerna", file: lerna, patterns })
+    }
+
+    /// Member directories holding a package.json, in path order, and how
+    /// many directories were read to find them
+    pub fn member_dirs(&self, root: &Path) -> (Vec<PathBuf>, usize) {
+        let mut included = Vec::new();
+        let mut excluded = Vec::new();
+        let mut read = 0;
+        for pattern in &self.patterns {
+            let (negated, pattern) = match pattern.strip_prefix('!') {
+                Some(pattern) => (true, pattern),
+                None => (false, pattern.as_str()),
+            };
+            let segments: Vec<&str> = pattern.trim_start_matches("./").split('/').filter(|segment| !segment.is_empty()).collect();
+            expand(root, &segments, if negated { &mut excluded } else { &mut included }, &mut read);
+        }
+        // A member reached through a symlink as well is listed once
+        let canonical = |dir: &PathBuf| dir.canonicalize().unwrap_or_else(|_| dir.clone());
+        let excluded: Vec<PathBuf> = excluded.iter().map(canonical).collect();
+        included.sort();
+        let mut seen = Vec::new();
+        included.retain(|dir| {
+            let dir = canonical(dir);
+            let keep = !excluded.contains(&dir) && !seen.contains(&dir);
+            seen.push(dir);
+            keep
+        });
+        (included, read)
+    }
+}
+
+/// Directories below `dir` matching `segments`, where `*` and `?` match
+/// within a name and `**` any number of levels
+fn expand(dir: &Path, segments: &[&str], found: &mut Vec<PathBuf>, read: &mut usize) {
+    let Some((segment, rest)) = segments.split_first() else {
+        if dir.join("package.json").is_file() {
+            found.push(dir.to_path_buf());
+        }
+        return;
+    };
+    if !segment.contains(['*', '?', '[']) {
+        let next = dir.join(segment);
+        if next.is_dir() {
+            expand(&next, rest, found, read);
+        }
+        return;
+    }
+
+    if *segment == "**" {
+        expand(dir, rest, found, read);
+    }
+    *read += 1;
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut subdirs: Vec<(PathBuf, bool)> = entries.flatten()
+        .filter(|entry| !SKIPPED_DIRS.iter().any(|skipped| entry.file_name() == *skipped))
+        .map(|entry| (entry.path(), entry.file_type().is_ok_and(|kind| kind.is_symlink())))
+        .filter(|(path, _)| path.is_dir())
+        .collect();
+    subdirs.sort();
+    for (subdir, symlink) in subdirs {
+        let name = subdir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        if *segment == "**" {
+            // A symlinked directory can be a member but isn't searched below
+            expand(&subdir, if symlink { rest } else { segments }, found, read);
+        } else if crate::gitignore::matches_name(segment, &name) {
+            expand(&subdir, rest, found, read);
+        }
+    }
+}
+
+/// The `packages:` list of a pnpm-workspace.yaml, read without a YAML
+/// parser: the block's `- item` lines, unquoted
+fn pnpm_packages(content: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+    let mut in_packages = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = trimmed.trim_end_matches(':') == "packages" && trimmed.ends_with(':');
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix('-').filter(|_| in_packages) {
+            let item = item.split(" #").next().unwrap_or_default().trim();
+            patterns.push(item.trim_matches(['"', '\'']).to_string());
+        }
+    }
+    patterns
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn strings(list: &[serde_json::Value]) -> Vec<String> {
+    list.iter().filter_map(|item| item.as_str().map(str::to_string)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(patterns: &[&str]) -> WorkspaceManifest {
+        WorkspaceManifest {
+            tool: "npm/yarn",
+            file: PathBuf::from("package.json"),
+            patterns: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+        }
+    }
+
+    fn package(dir: &Path) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("package.json"), "{}").unwrap();
+    }
+
+    #[test]
+    fn double_star_matches_any_depth_minus_exclusions() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        package(&root.join("packages").join("ui"));
+        package(&root.join("packages").join("tools").join("cli"));
+        package(&root.join("packages").join("tools").join("internal"));
+        package(&root.join("packages").join("ui").join("node_modules").join("dep"));
+
+        let (members, _) = manifest(&["packages/**", "!packages/tools/internal"]).member_dirs(root);
+        assert_eq!(members, [root.join("packages").join("tools").join("cli"), root.join("packages").join("ui")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn double_star_lists_symlinked_members_once_without_looping() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        package(&root.join("packages").join("ui"));
+        std::os::unix::fs::symlink(root.join("packages").join("ui"), root.join("packages").join("ui-alias")).unwrap();
+        // A link back up would recurse forever if followed
+        std::os::unix::fs::symlink(root, root.join("packages").join("loop")).unwrap();
+
+        let (members, _) = manifest(&["packages/**"]).member_dirs(root);
+        assert_eq!(members, [root.join("packages").join("ui")]);
+    }
+
+    #[test]
+    fn reads_the_pnpm_packages_block() {
+        let patterns = pnpm_packages("# members\npackages:\n  - 'packages/*'\n  - \"apps/**\" # apps\n  - '!**/test/**'\ncatalog:\n  - not-a-member\n");
+        assert_eq!(patterns, ["packages/*", "apps/**", "!**/test/**"]);
+    }
+
+    #[test]
+    fn detects_each_tools_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        assert!(WorkspaceManifest::detect(root).is_none());
+
+        fs::write(root.join("lerna.json"), "{}").unwrap();
+        let lerna = WorkspaceManifest::detect(root).unwrap();
+        assert_eq!((lerna.tool, lerna.patterns), ("lerna", vec!["packages/*".to_string()]));
+
+        // Yarn's object form, which package.json takes over lerna.json with
+        fs::write(root.join("package.json"), r#"{ "workspaces": { "packages": ["libs/*"] } }"#).unwrap();
+        let yarn = WorkspaceManifest::detect(root).unwrap();
+        assert_eq!((yarn.tool, yarn.patterns), ("npm/yarn", vec!["libs/*".to_string()]));
+        fs::write(root.join("package.json"), r#"{ "workspaces": ["libs/*", "tools/*"] }"#).unwrap();
+        assert_eq!(WorkspaceManifest::detect(root).unwrap().patterns, ["libs/*", "tools/*"]);
+
+        fs::write(root.join("pnpm-workspace.yaml"), "packages:\n  - apps/*\n").unwrap();
+        let pnpm = WorkspaceManifest::detect(root).unwrap();
+        assert_eq!((pnpm.tool, pnpm.patterns), ("pnpm", vec!["apps/*".to_string()]));
+    }
+}
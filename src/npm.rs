@@ -1,71 +1,298 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use anyhow::Result;
+use serde::Serialize;
 use crate::config::Config;
 use crate::error::SpineError;
-use crate::platform::Platform;
+use crate::hooks::{run_hooks, HookPoint};
+use crate::journal::OperationJournal;
+use crate::pm::{ManagerChoice, PackageManager};
+use crate::paths::display_path;
 
 pub struct NpmManager;
 
+/// A recorded link whose project no longer has it in node_modules
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovedLink {
+    pub package: String,
+    pub project: PathBuf,
+}
+
+/// A link whose name differs from the `name` in its package.json
+#[derive(Debug, Clone, Serialize)]
+pub struct NameMismatch {
+    pub package: String,
+    pub declared: String,
+}
+
+/// A link whose build output now lives somewhere else
+#[derive(Debug, Clone, Serialize)]
+pub struct MovedOutput {
+    pub package: String,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// What `spine verify --json`/`--check` found
+#[derive(Debug, Serialize)]
+pub struct VerifyReport {
+    /// Migrate with `spine verify --fix`
+    pub moved_outputs: Vec<MovedOutput>,
+    /// Forgotten from the config, or with --check would be
+    pub removed: Vec<RemovedLink>,
+    pub name_mismatches: Vec<NameMismatch>,
+    /// Links resolving to the same package; merge with `spine verify --fix`
+    pub duplicates: Vec<crate::config::DuplicateLinks>,
+    /// Only with --refresh-versions or --strict
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<crate::config::VersionRefreshReport>,
+    pub check: bool,
+}
+
+impl VerifyReport {
+    pub fn problem_count(&self) -> usize {
+        self.moved_outputs.len()
+            + self.removed.len()
+            + self.name_mismatches.len()
+            + self.duplicates.len()
+            + self.metadata.as_ref().map(|m| m.updated.len() + m.unreadable.len()).unwrap_or(0)
+    }
+}
+
+/// How a relink went for a package that ended up linked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelinkOutcome {
+    /// It was linked before and the link was recreated
+    Restored,
+    /// It wasn't linked before and now is
+    NewlyLinked,
+}
+
+/// The range a project declares for a package, for `unlink --restore`
+#[derive(Debug, Clone)]
+pub struct DeclaredRange {
+    pub range: String,
+    /// Declared only in devDependencies, so it is reinstalled there
+    pub dev: bool,
+}
+
 impl NpmManager {
-    pub fn link_all(config: &mut Config) -> Result<()> {
+    /// Link every configured package into `project`. Returns how many
+    /// packages were linked, or on a dry run how many are not linked yet. With
+    /// `fail_fast` the first failure stops the run and is returned as an error.
+    /// Given `tags`, only packages with one of them are linked.
+    pub fn link_all(config: &mut Config, project: &Path, pm: ManagerChoice, dry_run: bool, fail_fast: bool, tags: &[String]) -> Result<usize> {
         if config.links.is_empty() {
             println!("No packages configured to link.");
-            return Ok(());
+            return Ok(0);
+        }
+
+        let current_dir = project.to_path_buf();
+        let package_names: Vec<String> = config.links.values()
+            .filter(|link| link.has_any_tag(tags))
+            .map(|link| link.name.clone())
+            .collect();
+        if package_names.is_empty() {
+            println!("No packages tagged {} to link.", tags.join(" or "));
+            return Ok(0);
+        }
+
+        if dry_run {
+            println!("🧪 Dry run: would link all configured packages with {}...", pm);
+            let mut would_link = 0;
+            let mut skipped = 0;
+            for name in &package_names {
+                let link = config.links[name].for_project(&current_dir);
+                if link.pending {
+                    println!("⏭️  Would skip {} ({})", name, crate::config::PENDING_MARKER);
+                    skipped += 1;
+                } else if let Err(e) = crate::permissions::ensure_not_self_link(name, &link.path, &current_dir) {
+                    println!("⏭️  Would skip {}: {}", name, e.to_string().lines().next().unwrap_or_default());
+                    skipped += 1;
+                } else if crate::config::Config::is_package_linked_in_project_static(link.npm_name(), &current_dir) {
+                    println!("✓ Already linked: {} (would re-link)", name);
+                } else {
+                    println!("🔗 Would link: {}{} -> {}", name, config.links[name].variant_label(&current_dir), display_path(&link.path));
+                    would_link += 1;
+                }
+            }
+            println!("\n📊 Summary: {} package(s) would be linked, {} skipped", would_link, skipped);
+            return Ok(would_link);
+        }
+
+        if let Some(problem) = crate::permissions::check_project(&current_dir) {
+            return Err(problem.into_error().into());
         }
 
-        println!("Linking all configured packages...");
+        println!("Linking all configured packages with {}...", pm);
         let mut success_count = 0;
         let mut failed_packages = Vec::new();
-        let current_dir = std::env::current_dir()?;
+        let mut aborted = None;
+        let mut fatal = None;
+        let mut timings = Vec::new();
+        let started = std::time::Instant::now();
 
-        let package_names: Vec<String> = config.links.keys().cloned().collect();
-        
-        for name in package_names {
-            let link = config.links.get(&name).unwrap().clone();
-            match Self::npm_link(&link.path) {
-                Ok(_) => {
-                    // Verify the link was actually created
-                    if crate::config::Config::is_package_linked_in_project_static(&name, &current_dir) {
-                        config.add_linked_project(&name, current_dir.clone())?;
-                        println!("✓ Linked: {} -> {}", name, link.path.display());
-                        success_count += 1;
-                    } else {
-                        println!("⚠️  Link command succeeded but verification failed for: {}", name);
-                        failed_packages.push(name);
-                    }
+        let journal = OperationJournal::begin(&config.file, "link-all", &package_names, &current_dir)?;
+        let bar = crate::progress::Progress::bar(package_names.len() as u64, "{spinner:.green} [{elapsed_precise}] {bar:30.cyan/blue} {pos}/{len} {msg}", "█▉▊▋▌▍▎▏  ");
+        bar.enable_steady_tick(std::time::Duration::from_millis(120));
+        
+        for (index, name) in package_names.iter().enumerate() {
+            let link = config.links[name].for_project(&current_dir);
+            if link.pending {
+                bar.suspend(|| println!("⏳ Skipped {} ({})", name, crate::config::PENDING_MARKER));
+                bar.inc(1);
+                continue;
+            }
+            bar.set_message(format!("Linking {}", name));
+            let package_started = std::time::Instant::now();
+            let outcome = 'attempt: {
+                if let Err(e) = crate::permissions::ensure_not_self_link(name, &link.path, &current_dir) {
+                    bar.suspend(|| println!("✗ Failed to link {}: {}", name, e));
+                    break 'attempt Err(e.to_string());
                 }
-                Err(e) => {
-                    println!("✗ Failed to link {}: {}", name, e);
-                    failed_packages.push(name);
+                if let Some(problem) = crate::permissions::check_package(&link.path).into_iter().next() {
+                    bar.suspend(|| {
+                        println!("✗ Failed to link {}: {}", name, problem.describe());
+                        println!("  💡 {}", problem.suggestion());
+                    });
+                    break 'attempt Err(problem.describe());
+                }
+                if let Err(e) = crate::longpaths::ensure_within_limit(name, &link.path, link.npm_name(), &current_dir) {
+                    bar.suspend(|| println!("✗ Failed to link {}: {}", name, e));
+                    break 'attempt Err(e.to_string());
+                }
+                if let Err(e) = bar.suspend(|| run_hooks(&link, HookPoint::PreLink, &current_dir, config.timeouts.build())) {
+                    bar.suspend(|| println!("✗ Failed to link {}: {}", name, e));
+                    break 'attempt Err(e.to_string());
+                }
+                let link_pm = pm.for_link(link.package_manager_for(&current_dir));
+                if let Err(e) = link_pm.link_in(&current_dir, &link.path, config.timeouts.link()) {
+                    bar.suspend(|| println!("✗ Failed to link {}: {}", name, e));
+                    break 'attempt Err(e.to_string());
+                }
+                // Verify the link was actually created
+                if !crate::config::Config::is_package_linked_in_project_static(link.npm_name(), &current_dir) {
+                    bar.suspend(|| println!("⚠️  Link command succeeded but verification failed for: {}", name));
+                    break 'attempt Err("link verification failed".to_string());
                 }
+
+                if let Err(e) = config.add_linked_project(name, current_dir.clone()) {
+                    fatal = Some(e);
+                    break 'attempt Ok(());
+                }
+                crate::diff::record_snapshot(&config.file, name, &link.path, "link");
+                let elapsed = package_started.elapsed();
+                bar.suspend(|| println!("✓ Linked: {}{} -> {} ({})",
+                    name, link.variant_label(&current_dir), display_path(&link.path), crate::history::format_duration_ms(elapsed.as_millis() as u64)));
+                success_count += 1;
+                if let Err(e) = bar.suspend(|| run_hooks(&link, HookPoint::PostLink, &current_dir, config.timeouts.build())) {
+                    fatal = Some(e);
+                }
+                Ok(())
+            };
+            timings.push((name.clone(), package_started.elapsed()));
+            bar.inc(1);
+            if fatal.is_some() {
+                break;
+            }
+            let Err(error) = outcome else { continue };
+
+            failed_packages.push(name.clone());
+            if fail_fast {
+                aborted = Some(crate::batch::BatchAbort::new(name, error, index, package_names.len()));
+                break;
             }
         }
+        bar.finish_and_clear();
 
-        println!("\nSummary: {} successful, {} failed", success_count, failed_packages.len());
+        println!("\nSummary: {} successful, {} failed in {}",
+            success_count, failed_packages.len(), crate::history::format_duration_ms(started.elapsed().as_millis() as u64));
         if !failed_packages.is_empty() {
             println!("Failed packages: {}", failed_packages.join(", "));
         }
+        timings.sort_by_key(|(_, elapsed)| std::cmp::Reverse(*elapsed));
+        if timings.len() > 1 {
+            let slowest: Vec<String> = timings.iter().take(3)
+                .map(|(name, elapsed)| format!("{} ({})", name, crate::history::format_duration_ms(elapsed.as_millis() as u64)))
+                .collect();
+            println!("Slowest: {}", slowest.join(", "));
+        }
+
+        journal.finish(config)?;
+        if let Some(e) = fatal {
+            return Err(e);
+        }
+        if let Some(abort) = aborted {
+            return Err(abort.into_error().into());
+        }
+        Ok(success_count)
+    }
+
+    /// Warn prominently about `@angular/*` peers the project doesn't satisfy,
+    /// or refuse with `strict`
+    fn check_angular_peers(link: &crate::config::PackageLink, project: &Path, strict: bool) -> Result<()> {
+        let mismatches = crate::package::angular_peer_mismatches(&link.path, project);
+        if mismatches.is_empty() {
+            return Ok(());
+        }
 
+        let icon = if strict { "❌" } else { "⚠️ " };
+        eprintln!("{} {} was built for a different Angular than this project has:", icon, link.name);
+        for mismatch in &mismatches {
+            eprintln!("     • {}", mismatch.describe());
+        }
+        if strict {
+            return Err(SpineError::Config(format!(
+                "{} has {} unsatisfied Angular peer dependenc{} (--strict-peers)",
+                link.name, mismatches.len(), if mismatches.len() == 1 { "y" } else { "ies" }
+            )).into());
+        }
+        eprintln!("   Expect runtime errors such as NG0203; rebuild the library against this project's Angular.");
         Ok(())
     }
 
-    pub fn link_package(config: &mut Config, package_name: &str) -> Result<()> {
+    pub fn link_package(config: &mut Config, project: &Path, package_name: &str, pm: ManagerChoice, strict_peers: bool) -> Result<()> {
+        let current_dir = project.to_path_buf();
         let link = config.links.get(package_name)
             .ok_or_else(|| {
                 let available: Vec<String> = config.links.keys().cloned().collect();
                 SpineError::package_not_found_with_suggestions(package_name, &available)
             })?
-            .clone();
+            .for_project(&current_dir);
+
+        if link.pending {
+            return Err(SpineError::InvalidPath(format!(
+                "{} is {}: {}", package_name, crate::config::PENDING_MARKER, display_path(&link.path)
+            )).into());
+        }
 
-        println!("Linking package: {} -> {}", package_name, link.path.display());
+        crate::permissions::ensure_not_self_link(package_name, &link.path, &current_dir)?;
+        crate::pm::ensure_not_pnp(&current_dir)?;
+        crate::permissions::ensure_linkable(&link.path, &current_dir)?;
+        crate::longpaths::ensure_within_limit(package_name, &link.path, link.npm_name(), &current_dir)?;
+        Self::check_angular_peers(&link, &current_dir, strict_peers)?;
+        run_hooks(&link, HookPoint::PreLink, &current_dir, config.timeouts.build())?;
+        
+        let link_pm = pm.for_link(link.package_manager_for(&current_dir));
+        let variant = link.variant_label(&current_dir);
+        if link_pm == pm.manager {
+            println!("Linking package: {}{} -> {}", package_name, variant, display_path(&link.path));
+        } else {
+            println!("Linking package: {}{} -> {} with {}", package_name, variant, display_path(&link.path), link_pm);
+        }
         
-        Self::npm_link(&link.path)?;
+        link_pm.link_in(&current_dir, &link.path, config.timeouts.link())?;
         
         // Verify the link was actually created
-        let current_dir = std::env::current_dir()?;
-        if crate::config::Config::is_package_linked_in_project_static(package_name, &current_dir) {
-            config.add_linked_project(package_name, current_dir)?;
+        if crate::config::Config::is_package_linked_in_project_static(link.npm_name(), &current_dir) {
+            // Tracked versions follow the default variant
+            let update = if link.variant_in(&current_dir).is_none() { config.sync_tracked_version(package_name) } else { None };
+            if let Some(update) = update {
+                println!("🔄 {} is now v{} (was v{})", package_name, update.current, update.previous.as_deref().unwrap_or("unknown"));
+            }
+            config.add_linked_project(package_name, current_dir.clone())?;
+            crate::diff::record_snapshot(&config.file, package_name, &link.path, "link");
             println!("✓ Successfully linked: {}", package_name);
+            run_hooks(&link, HookPoint::PostLink, &current_dir, config.timeouts.build())?;
         } else {
             println!("⚠️  Link command completed but symlink verification failed for: {}", package_name);
             return Err(SpineError::Config("Link verification failed".to_string()).into());
@@ -74,113 +301,706 @@ impl NpmManager {
         Ok(())
     }
 
-    pub fn unlink_package(config: &mut Config, package_name: &str) -> Result<()> {
-        println!("Unlinking package: {}", package_name);
-        
-        let output = Platform::npm_command()
-            .args(&["unlink", package_name])
-            .output()
-            .map_err(|e| SpineError::Io(e))?;
+    /// Link one or more packages. All names are checked against the config before
+    /// anything runs, so a typo doesn't leave the project half linked.
+    pub fn link_packages(config: &mut Config, project: &Path, package_names: &[String], pm: ManagerChoice, strict_peers: bool) -> Result<()> {
+        if let [package_name] = package_names {
+            return Self::link_package(config, project, package_name, pm, strict_peers);
+        }
 
-        if output.status.success() {
-            let current_dir = std::env::current_dir()?;
-            
-            // Verify the link was actually removed
-            if !crate::config::Config::is_package_linked_in_project_static(package_name, &current_dir) {
-                config.remove_linked_project(package_name, &current_dir)?;
-                println!("✓ Successfully unlinked: {}", package_name);
+        let links = Self::resolve_links(config, project, package_names)?;
+        let current_dir = project.to_path_buf();
+        crate::pm::ensure_not_pnp(&current_dir)?;
+        for link in &links {
+            if link.pending {
+                return Err(SpineError::InvalidPath(format!(
+                    "{} is {}: {}", link.name, crate::config::PENDING_MARKER, display_path(&link.path)
+                )).into());
+            }
+            crate::permissions::ensure_not_self_link(&link.name, &link.path, &current_dir)?;
+            crate::permissions::ensure_linkable(&link.path, &current_dir)?;
+            crate::longpaths::ensure_within_limit(&link.name, &link.path, link.npm_name(), &current_dir)?;
+            Self::check_angular_peers(link, &current_dir, strict_peers)?;
+        }
+        for link in &links {
+            run_hooks(link, HookPoint::PreLink, &current_dir, config.timeouts.build())?;
+        }
+
+        for (link_pm, group) in Self::group_by_manager(&links, pm, &current_dir) {
+            println!("Linking {} packages with {}...", group.len(), link_pm);
+            let paths: Vec<&Path> = group.iter().map(|link| link.path.as_path()).collect();
+            link_pm.link_many_in(&current_dir, &paths, config.timeouts.link())?;
+        }
+
+        let mut failed = Vec::new();
+        for link in &links {
+            if crate::config::Config::is_package_linked_in_project_static(link.npm_name(), &current_dir) {
+                config.add_linked_project(&link.name, current_dir.clone())?;
+                crate::diff::record_snapshot(&config.file, &link.name, &link.path, "link");
+                println!("✓ Linked: {}{} -> {}", link.name, link.variant_label(&current_dir), display_path(&link.path));
+                run_hooks(link, HookPoint::PostLink, &current_dir, config.timeouts.build())?;
+            } else {
+                println!("⚠️  Link command completed but symlink verification failed for: {}", link.name);
+                failed.push(link.name.clone());
+            }
+        }
+
+        println!("\n📊 Summary: {} linked, {} failed", links.len() - failed.len(), failed.len());
+        if !failed.is_empty() {
+            return Err(SpineError::Config(format!("Link verification failed for: {}", failed.join(", "))).into());
+        }
+        Ok(())
+    }
+
+    /// Link packages through overrides in `project`'s package.json,
+    /// pointing at the configured paths, so later installs don't undo the link.
+    /// The package.json change is always shown before it is written.
+    pub fn link_durable(config: &mut Config, project: &Path, package_names: &[String], pm: ManagerChoice, dry_run: bool, strict_peers: bool) -> Result<()> {
+        let links = Self::resolve_links(config, project, package_names)?;
+        let current_dir = project.to_path_buf();
+        for link in &links {
+            if link.pending {
+                return Err(SpineError::InvalidPath(format!(
+                    "{} is {}: {}", link.name, crate::config::PENDING_MARKER, display_path(&link.path)
+                )).into());
+            }
+            crate::permissions::ensure_not_self_link(&link.name, &link.path, &current_dir)?;
+            if !dry_run {
+                crate::permissions::ensure_linkable(&link.path, &current_dir)?;
+            }
+            Self::check_angular_peers(link, &current_dir, strict_peers)?;
+        }
+
+        if !dry_run {
+            for link in &links {
+                run_hooks(link, HookPoint::PreLink, &current_dir, config.timeouts.build())?;
+            }
+        }
+
+        let mut edit = crate::durable::PackageJsonEdit::open(&current_dir)?;
+        let mut records = Vec::new();
+        for link in &links {
+            // Linking again keeps the range recorded the first time
+            let previous = link.durable_link_in(&current_dir).and_then(|d| d.dependency_field.clone().zip(d.original_range.clone()));
+            let replaced = edit.set_override(pm.manager, link.npm_name(), &link.path).or(previous);
+            records.push(crate::config::DurableLink {
+                project: current_dir.clone(),
+                dependency_field: replaced.as_ref().map(|(field, _)| field.clone()),
+                original_range: replaced.map(|(_, range)| range),
+            });
+        }
+
+        if edit.has_changes() {
+            println!("📝 package.json changes:");
+            edit.print_diff()?;
+        } else {
+            println!("✓ package.json already has these overrides");
+        }
+
+        if dry_run {
+            println!("\n🧪 Dry run: package.json was not written and {} install was not run.", pm);
+            return Ok(());
+        }
+
+        edit.save()?;
+        println!("\n📥 Running {} install...", pm);
+        if let Err(e) = pm.manager.install_project(&current_dir, config.timeouts.build()) {
+            edit.revert()?;
+            println!("↩️  Restored the original package.json");
+            return Err(e);
+        }
+
+        for (link, record) in links.iter().zip(records) {
+            config.remove_durable_link(&link.name, &current_dir)?;
+            config.add_durable_link(&link.name, record)?;
+            crate::diff::record_snapshot(&config.file, &link.name, &link.path, "link");
+            println!("📌 Durably linked: {}{} -> {}", link.name, link.variant_label(&current_dir), display_path(&link.path));
+            run_hooks(link, HookPoint::PostLink, &current_dir, config.timeouts.build())?;
+        }
+        println!("⚠️  package.json now points at local paths; don't commit it. 'spine unlink' removes the overrides.");
+        Ok(())
+    }
+
+    /// Remove the package.json overrides of durable links and reinstall, which
+    /// brings back the registry versions
+    fn unlink_durable(config: &mut Config, project: &Path, links: &[crate::config::PackageLink], pm: ManagerChoice) -> Result<()> {
+        let current_dir = project.to_path_buf();
+        for link in links {
+            run_hooks(link, HookPoint::PreUnlink, &current_dir, config.timeouts.build())?;
+        }
+        let mut edit = crate::durable::PackageJsonEdit::open(&current_dir)?;
+        for link in links {
+            let replaced = link.durable_link_in(&current_dir)
+                .and_then(|d| d.dependency_field.as_deref().zip(d.original_range.as_deref()));
+            edit.remove_override(link.npm_name(), replaced);
+        }
+
+        if edit.has_changes() {
+            println!("📝 package.json changes:");
+            edit.print_diff()?;
+            edit.save()?;
+        }
+
+        println!("📥 Running {} install...", pm);
+        pm.manager.install_project(&current_dir, config.timeouts.build())?;
+
+        for link in links {
+            config.remove_durable_link(&link.name, &current_dir)?;
+            println!("✓ Removed durable link: {}", link.name);
+            run_hooks(link, HookPoint::PostUnlink, &current_dir, config.timeouts.build())?;
+        }
+        Ok(())
+    }
+
+    /// Unlink one or more packages, validating every name first
+    pub fn unlink_packages(config: &mut Config, project: &Path, package_names: &[String], pm: ManagerChoice, restore: bool) -> Result<()> {
+        if package_names.len() > 1 {
+            Self::resolve_links(config, project, package_names)?;
+        }
+
+        let current_dir = project.to_path_buf();
+        let durable: Vec<crate::config::PackageLink> = package_names.iter()
+            .filter_map(|name| config.links.get(name))
+            .filter(|link| link.is_durable_in(&current_dir))
+            .cloned()
+            .collect();
+        if !durable.is_empty() {
+            Self::unlink_durable(config, project, &durable, pm)?;
+        }
+
+        let package_names: Vec<String> = package_names.iter()
+            .filter(|name| !durable.iter().any(|link| &link.name == *name))
+            .cloned()
+            .collect();
+        if package_names.is_empty() {
+            return Ok(());
+        }
+        if let [package_name] = package_names.as_slice() {
+            return Self::unlink_package(config, project, package_name, pm, restore);
+        }
+
+        let links = Self::resolve_links(config, project, &package_names)?;
+        for link in &links {
+            run_hooks(link, HookPoint::PreUnlink, &current_dir, config.timeouts.build())?;
+        }
+        let npm_names: Vec<&str> = links.iter().map(|link| link.npm_name()).collect();
+        let ranges: Vec<Option<DeclaredRange>> = npm_names.iter().map(|name| Self::declared_range(project, name)).collect();
+        for (link_pm, group) in Self::group_by_manager(&links, pm, &current_dir) {
+            println!("Unlinking {} packages with {}...", group.len(), link_pm);
+            let group_names: Vec<&str> = group.iter().map(|link| link.npm_name()).collect();
+            link_pm.unlink_many_in(&current_dir, &group_names, config.timeouts.link())?;
+        }
+
+        for link in &links {
+            config.remove_linked_project(&link.name, &current_dir)?;
+            if crate::config::Config::is_package_linked_in_project_static(link.npm_name(), &current_dir) {
+                println!("⚠️  Unlink command completed but symlink still exists for: {}", link.name);
             } else {
-                println!("⚠️  Unlink command completed but symlink still exists for: {}", package_name);
-                // Still remove from config since npm unlink succeeded
-                config.remove_linked_project(package_name, &current_dir)?;
+                println!("✓ Unlinked: {}", link.name);
             }
+            run_hooks(link, HookPoint::PostUnlink, &current_dir, config.timeouts.build())?;
+        }
+
+        if restore {
+            for ((link, npm_name), range) in links.iter().zip(npm_names).zip(&ranges) {
+                let link_pm = pm.for_link(link.package_manager_for(&current_dir));
+                Self::restore_registry_version(config, project, npm_name, range.as_ref(), link_pm)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Split `links` by the manager each one is linked with in `project`,
+    /// keeping their order within each group
+    fn group_by_manager<'a>(links: &'a [crate::config::PackageLink], pm: ManagerChoice, project: &Path)
+        -> Vec<(PackageManager, Vec<&'a crate::config::PackageLink>)>
+    {
+        let mut groups: Vec<(PackageManager, Vec<&crate::config::PackageLink>)> = Vec::new();
+        for link in links {
+            let link_pm = pm.for_link(link.package_manager_for(project));
+            match groups.iter_mut().find(|(manager, _)| *manager == link_pm) {
+                Some((_, group)) => group.push(link),
+                None => groups.push((link_pm, vec![link])),
+            }
+        }
+        groups
+    }
+
+    /// Look up every requested package, failing on the first unknown name.
+    /// Each comes as the variant `project` links.
+    fn resolve_links(config: &Config, project: &Path, package_names: &[String]) -> Result<Vec<crate::config::PackageLink>> {
+        let current_dir = project.to_path_buf();
+        let mut links: Vec<crate::config::PackageLink> = Vec::new();
+        for package_name in package_names {
+            let link = config.links.get(package_name).ok_or_else(|| {
+                let available: Vec<String> = config.links.keys().cloned().collect();
+                SpineError::package_not_found_with_suggestions(package_name, &available)
+            })?;
+            if !links.iter().any(|l| l.name == link.name) {
+                links.push(link.for_project(&current_dir));
+            }
+        }
+        Ok(links)
+    }
+
+    pub fn unlink_package(config: &mut Config, project: &Path, package_name: &str, pm: ManagerChoice, restore: bool) -> Result<()> {
+        println!("Unlinking package: {}", package_name);
+        let link = config.links.get(package_name).cloned();
+        let npm_name = link.as_ref()
+            .map(|link| link.npm_name().to_string())
+            .unwrap_or_else(|| package_name.to_string());
+        let range = Self::declared_range(project, &npm_name);
+        let current_dir = project.to_path_buf();
+        if let Some(link) = &link {
+            run_hooks(link, HookPoint::PreUnlink, &current_dir, config.timeouts.build())?;
+        }
+        let pm = pm.for_link(link.as_ref().and_then(|link| link.package_manager_for(&current_dir)));
+        
+        pm.unlink_in(&current_dir, &npm_name, config.timeouts.link())?;
+        
+        // Verify the link was actually removed
+        if !crate::config::Config::is_package_linked_in_project_static(&npm_name, &current_dir) {
+            config.remove_linked_project(package_name, &current_dir)?;
+            println!("✓ Successfully unlinked: {}", package_name);
         } else {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            return Err(SpineError::Config(format!("npm unlink failed: {}", error_msg)).into());
+            println!("⚠️  Unlink command completed but symlink still exists for: {}", package_name);
+            // Still remove from config since the unlink command succeeded
+            config.remove_linked_project(package_name, &current_dir)?;
+        }
+        if let Some(link) = &link {
+            run_hooks(link, HookPoint::PostUnlink, &current_dir, config.timeouts.build())?;
+        }
+
+        if restore {
+            Self::restore_registry_version(config, project, &npm_name, range.as_ref(), pm)?;
+        }
+
+        Ok(())
+    }
+
+    /// Range `project`'s package.json declares for a package. Read it
+    /// before unlinking: npm's unlink also drops the dependency from package.json.
+    fn declared_range(project: &Path, npm_name: &str) -> Option<DeclaredRange> {
+        let package_json = project.join("package.json");
+        let info = crate::package::parse_package_json(&package_json).ok()?;
+        let range = info.declared_range(npm_name)?.to_string();
+        let dev = info.dependency_field(npm_name) == Some("devDependencies");
+        Some(DeclaredRange { range, dev })
+    }
+
+    /// Reinstall a package that was just unlinked into `project` at the range
+    /// it declared, since npm leaves nothing behind in node_modules.
+    /// Packages the project doesn't declare are skipped.
+    pub fn restore_registry_version(config: &Config, project: &Path, npm_name: &str, declared: Option<&DeclaredRange>, pm: PackageManager) -> Result<()> {
+        let declared = match declared {
+            Some(declared) => declared,
+            None => {
+                println!("⏭️  {} is not declared in this project's package.json; nothing to reinstall", npm_name);
+                return Ok(());
+            }
+        };
+
+        let spec = format!("{}@{}", npm_name, declared.range);
+        println!("📥 Restoring {} with {}...", spec, pm);
+        pm.install_in(project, &spec, declared.dev, config.timeouts.build())?;
+        println!("✓ Restored: {}", spec);
+        Ok(())
+    }
+
+    /// Unlink and link a package again, e.g. after `npm install` replaced the
+    /// symlink. A package that isn't linked yet is simply linked.
+    pub fn relink_package(config: &mut Config, package_name: &str, pm: ManagerChoice) -> Result<RelinkOutcome> {
+        let current_dir = std::env::current_dir()?;
+        let link = config.links.get(package_name)
+            .ok_or_else(|| {
+                let available: Vec<String> = config.links.keys().cloned().collect();
+                SpineError::package_not_found_with_suggestions(package_name, &available)
+            })?
+            .for_project(&current_dir);
+
+        if link.pending {
+            return Err(SpineError::InvalidPath(format!(
+                "{} is {}: {}", package_name, crate::config::PENDING_MARKER, display_path(&link.path)
+            )).into());
+        }
+
+        crate::permissions::ensure_not_self_link(package_name, &link.path, &current_dir)?;
+        crate::pm::ensure_not_pnp(&current_dir)?;
+        crate::permissions::ensure_linkable(&link.path, &current_dir)?;
+        let npm_name = link.npm_name();
+        let was_linked = crate::config::Config::is_package_linked_in_project_static(npm_name, &current_dir);
+        // Before unlinking, so a failing hook leaves the existing link alone
+        run_hooks(&link, HookPoint::PreLink, &current_dir, config.timeouts.build())?;
+        let pm = pm.for_link(link.package_manager_for(&current_dir));
+
+        // Phase 1: unlink. Failing is fine when there was nothing to remove.
+        if let Err(e) = pm.unlink_in(&current_dir, npm_name, config.timeouts.link()) {
+            if was_linked {
+                return Err(e);
+            }
+        }
+        if crate::config::Config::is_package_linked_in_project_static(npm_name, &current_dir) {
+            return Err(SpineError::Config(format!("{} is still linked after unlinking", package_name)).into());
+        }
+
+        // Phase 2: link
+        pm.link_in(&current_dir, &link.path, config.timeouts.link())?;
+        if !crate::config::Config::is_package_linked_in_project_static(npm_name, &current_dir) {
+            return Err(SpineError::Config("Link verification failed".to_string()).into());
+        }
+
+        config.add_linked_project(package_name, current_dir.clone())?;
+        crate::diff::record_snapshot(&config.file, package_name, &link.path, "link");
+        run_hooks(&link, HookPoint::PostLink, &current_dir, config.timeouts.build())?;
+        Ok(if was_linked { RelinkOutcome::Restored } else { RelinkOutcome::NewlyLinked })
+    }
+
+    /// Relink one package, or every package configured for the current project
+    pub fn relink(config: &mut Config, package_name: Option<&str>, pm: ManagerChoice) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let package_names: Vec<String> = match package_name {
+            Some(name) => vec![name.to_string()],
+            None => {
+                let mut names: Vec<String> = config.links.values()
+                    .filter(|link| !link.pending && link.linked_projects.iter().any(|p| crate::paths::same_path(p, &current_dir)))
+                    .map(|link| link.name.clone())
+                    .collect();
+                names.sort();
+                names
+            }
+        };
+
+        if package_names.is_empty() {
+            println!("No packages are configured for this project. Link one first with 'spine link <package>'.");
+            return Ok(());
+        }
+
+        println!("Relinking {} package(s) with {}...", package_names.len(), pm);
+        let mut restored = 0;
+        let mut newly_linked = 0;
+        let mut failed = Vec::new();
+
+        for name in &package_names {
+            match Self::relink_package(config, name, pm) {
+                Ok(RelinkOutcome::Restored) => {
+                    println!("  🔄 {}: was linked, restored", name);
+                    restored += 1;
+                }
+                Ok(RelinkOutcome::NewlyLinked) => {
+                    println!("  🔗 {}: was not linked, now linked", name);
+                    newly_linked += 1;
+                }
+                Err(e) => {
+                    println!("  ❌ {}: failed ({})", name, e.to_string().lines().next().unwrap_or_default());
+                    failed.push(name.clone());
+                }
+            }
+        }
+
+        println!("\n📊 Summary: {} restored, {} newly linked, {} failed", restored, newly_linked, failed.len());
+        if !failed.is_empty() {
+            return Err(SpineError::Config(format!("Relink failed for: {}", failed.join(", "))).into());
+        }
+        Ok(())
+    }
+
+    /// Drop `project` from a package's linked projects without running
+    /// npm, so `spine sync` stops restoring a link that was removed on purpose
+    pub fn forget_project(config: &mut Config, project: &Path, package_name: &str) -> Result<()> {
+        let npm_name = match config.links.get(package_name) {
+            Some(link) => link.npm_name().to_string(),
+            None => {
+                let available: Vec<String> = config.links.keys().cloned().collect();
+                return Err(SpineError::package_not_found_with_suggestions(package_name, &available).into());
+            }
+        };
+        
+        let current_dir = project.to_path_buf();
+        config.remove_linked_project(package_name, &current_dir)?;
+        println!("✓ Forgot {} for {}", package_name, display_path(&current_dir));
+        println!("  'spine sync' will no longer restore this link here.");
+        
+        if crate::config::Config::is_package_linked_in_project_static(&npm_name, &current_dir) {
+            println!("  💡 The symlink is still present; run 'spine unlink {}' to remove it as well.", package_name);
+        }
+        
+        Ok(())
+    }
+
+    /// `spine prune --projects`: forget every recorded project that no longer
+    /// exists or sits under projects.never_record, whatever its age
+    pub fn prune_projects(config: &mut Config, yes: bool, plan_only: bool) -> Result<()> {
+        let stale = config.stale_projects(crate::history::now_timestamp(), None);
+        let mut plan = crate::plan::Plan::new("prune --projects", None);
+        for (name, project, reason) in &stale {
+            plan.push("forget", name, Some(format!("{} ({})", display_path(project), reason)));
+        }
+        if plan_only {
+            return plan.print_json();
+        }
+        if stale.is_empty() {
+            println!("✓ Every recorded project exists and none is under projects.never_record");
+            return Ok(());
+        }
+
+        if !plan.confirm(yes) {
+            println!("Cancelled; nothing was changed.");
+            return Ok(());
+        }
+        let mut remaining = stale.iter();
+        let outcome = plan.execute(false, |_| {
+            let (name, project, _) = remaining.next().expect("one action per stale project");
+            config.remove_linked_project(name, project).map_err(|e| e.to_string())
+        })?;
+        config.save()?;
+        println!("\n📊 Summary: {} project entr{} forgotten", outcome.completed.len(), if outcome.completed.len() == 1 { "y" } else { "ies" });
+        Ok(())
+    }
+
+    /// Symlinked packages in the current project that Spine doesn't manage.
+    /// With `remove` they are unlinked, with `adopt` added to the config from
+    /// the package.json at the symlink's target; each is confirmed unless `yes`.
+    pub fn prune(config: &mut Config, remove: bool, adopt: bool, yes: bool, plan_only: bool) -> Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let untracked: Vec<String> = Self::get_linked_packages(&current_dir)?
+            .into_iter()
+            .filter(|npm_name| config.find_by_npm_name(npm_name).is_none())
+            .collect();
+        let mut plan = crate::plan::Plan::new("prune", Some(current_dir.clone()));
+        for npm_name in &untracked {
+            let target = crate::paths::node_modules_path(&current_dir, npm_name).canonicalize()
+                .map(|target| display_path(&target))
+                .ok();
+            plan.push(if remove { "unlink" } else { "adopt" }, npm_name, target);
+        }
+        if plan_only {
+            if !remove && !adopt {
+                return Err(SpineError::Config("--plan needs --remove or --adopt".to_string()).into());
+            }
+            return plan.print_json();
+        }
+
+        if untracked.is_empty() {
+            println!("✓ No unmanaged links in {}", display_path(&current_dir));
+            return Ok(());
+        }
+
+        println!("Linked in {} but not managed by Spine:", display_path(&current_dir));
+        for npm_name in &untracked {
+            let target = crate::paths::node_modules_path(&current_dir, npm_name).canonicalize()
+                .map(|target| display_path(&target))
+                .unwrap_or_default();
+            println!("  ❔ {} → {}", npm_name, target);
+        }
+        if !remove && !adopt {
+            println!("
+💡 Run 'spine prune --remove' to unlink them or 'spine prune --adopt' to add them to the config.");
+            return Ok(());
+        }
+
+        println!();
+        if !plan.confirm(yes) {
+            println!("Cancelled; nothing was changed.");
+            return Ok(());
+        }
+        let outcome = plan.execute(false, |action| {
+            let result = if remove {
+                Self::remove_untracked_link(&current_dir, &action.target)
+            } else {
+                Self::adopt_untracked_link(config, &current_dir, &action.target)
+            };
+            result.map_err(|e| e.to_string())
+        })?;
+
+        if adopt && !outcome.completed.is_empty() {
+            config.save()?;
+        }
+        println!("\n📊 Summary: {} {}, {} failed, {} not started",
+            outcome.completed.len(), if remove { "unlinked" } else { "adopted" }, outcome.failed.len(), outcome.not_started.len());
+        Ok(())
+    }
+
+    /// Remove the symlink itself. `npm unlink` would also prune every other
+    /// link missing from package.json, including the ones Spine manages.
+    fn remove_untracked_link(project: &Path, npm_name: &str) -> Result<()> {
+        let entry = crate::paths::node_modules_path(project, npm_name);
+        crate::paths::remove_symlink(&entry).map_err(SpineError::Io)?;
+        Ok(())
+    }
+
+    /// Add the package a symlink points at, recording the project as linked
+    fn adopt_untracked_link(config: &mut Config, project: &Path, npm_name: &str) -> Result<()> {
+        let target = crate::paths::node_modules_path(project, npm_name).canonicalize()
+            .map_err(SpineError::Io)?;
+        let info = crate::package::parse_package_json(&target.join("package.json"))?;
+        if config.links.contains_key(&info.name) {
+            return Err(SpineError::Config(format!(
+                "'{}' is already configured for another path", info.name
+            )).into());
         }
 
+        config.add_link(info.name.clone(), target.to_string_lossy().to_string(), false)?;
+        config.add_linked_project(&info.name, project.to_path_buf())?;
         Ok(())
     }
 
-    pub fn unlink_all(config: &mut Config) -> Result<()> {
+    /// Unlink every Spine-managed package from the current project. Returns how
+    /// many packages were (or, on a dry run, would be) unlinked.
+    pub fn unlink_all(config: &mut Config, pm: ManagerChoice, dry_run: bool, restore: bool, fail_fast: bool, yes: bool, plan_only: bool) -> Result<usize> {
+        if plan_only {
+            let plan = Self::unlink_all_plan(config, &std::env::current_dir()?, restore)?;
+            plan.print_json()?;
+            return Ok(plan.actions.len());
+        }
+        if dry_run {
+            println!("🧪 Dry run: no links will be changed and the configuration will not be written.");
+        }
         println!("Unlinking all packages from current project...");
         
         let current_dir = std::env::current_dir()?;
         
         // Get packages that are actually linked to the current project
-        let linked_packages = Self::get_linked_packages()?;
+        let linked_packages = Self::get_linked_packages(&current_dir)?;
         
         if linked_packages.is_empty() {
             println!("No packages currently linked in this project.");
-            return Ok(());
+            return Ok(0);
         }
         
-        println!("Found {} linked package(s) to unlink:", linked_packages.len());
-        
-        let mut success_count = 0;
-        let mut failed_packages = Vec::new();
-        
-        for package_name in &linked_packages {
-            // Only unlink if it's in our configuration (managed by Spine)
-            if config.links.contains_key(package_name) {
-                print!("  🔗 Unlinking {}... ", package_name);
-                
-                let output = Platform::npm_command()
-                    .args(&["unlink", package_name])
-                    .output()
-                    .map_err(|e| crate::error::SpineError::Io(e))?;
-
-                if output.status.success() {
-                    // Remove from linked projects for this package
-                    config.remove_linked_project(package_name, &current_dir)?;
-                    success_count += 1;
-                    println!("✅ Success");
+        if dry_run {
+            let mut would_unlink = 0;
+            for package_name in &linked_packages {
+                if config.find_by_npm_name(package_name).is_some() {
+                    if restore {
+                        println!("  🔗 Would unlink {} with {} and reinstall it from the registry", package_name, pm);
+                    } else {
+                        println!("  🔗 Would unlink {} with {}", package_name, pm);
+                    }
+                    would_unlink += 1;
                 } else {
-                    let error_msg = String::from_utf8_lossy(&output.stderr);
-                    failed_packages.push((package_name.clone(), error_msg.to_string()));
-                    println!("❌ Failed");
+                    println!("  ⏭️  Would skip {} (not managed by Spine)", package_name);
                 }
-            } else {
-                println!("  ⚠️  Skipping {} (not managed by Spine)", package_name);
             }
+            println!("\n📊 Summary: {} package(s) would be unlinked, {} skipped",
+                would_unlink, linked_packages.len() - would_unlink);
+            return Ok(would_unlink);
         }
         
+        for package_name in linked_packages.iter().filter(|name| config.find_by_npm_name(name).is_none()) {
+            println!("  ⚠️  Skipping {} (not managed by Spine)", package_name);
+        }
+        let plan = Self::unlink_all_plan(config, &current_dir, restore)?;
+        if plan.is_empty() {
+            println!("No Spine-managed packages are linked in this project.");
+            return Ok(0);
+        }
+        if !plan.confirm(yes) {
+            println!("Cancelled; nothing was unlinked.");
+            return Ok(0);
+        }
+
+        let targets: Vec<String> = plan.actions.iter().map(|action| action.target.clone()).collect();
+        let journal = OperationJournal::begin(&config.file, "unlink-all", &targets, &current_dir)?;
+        let mut unlinked = Vec::new();
+        let outcome = match plan.execute(fail_fast, |action| {
+            let package_name = &action.target;
+            let link = config.find_by_npm_name(package_name).cloned().ok_or("no longer configured")?;
+            run_hooks(&link, HookPoint::PreUnlink, &current_dir, config.timeouts.build()).map_err(|e| e.to_string())?;
+            let range = Self::declared_range(&current_dir, package_name);
+            let link_pm = pm.for_link(link.package_manager_for(&current_dir));
+            link_pm.unlink_in(&current_dir, package_name, config.timeouts.link()).map_err(|e| e.to_string())?;
+            config.remove_linked_project(&link.name, &current_dir).map_err(|e| e.to_string())?;
+            unlinked.push((package_name.clone(), range, link_pm));
+            run_hooks(&link, HookPoint::PostUnlink, &current_dir, config.timeouts.build()).map_err(|e| e.to_string())
+        }) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                journal.finish(config)?;
+                return Err(e);
+            }
+        };
+
         // Summary
         println!("\n📊 Unlink Summary:");
-        println!("  ✅ Successfully unlinked: {}", success_count);
+        println!("  ✅ Successfully unlinked: {}", outcome.completed.len());
         
-        if !failed_packages.is_empty() {
-            println!("  ❌ Failed to unlink: {}", failed_packages.len());
-            for (package, error) in &failed_packages {
+        if !outcome.failed.is_empty() {
+            println!("  ❌ Failed to unlink: {}", outcome.failed.len());
+            for (package, error) in &outcome.failed {
                 println!("    • {}: {}", package, error.trim());
             }
         }
         
-        if success_count > 0 {
+        if !outcome.completed.is_empty() && outcome.failed.is_empty() && outcome.not_started.is_empty() {
             println!("\n✨ All managed packages have been unlinked from the current project.");
         }
         
-        Ok(())
+        journal.finish(config)?;
+        
+        if restore && !unlinked.is_empty() {
+            println!();
+            for (npm_name, range, link_pm) in &unlinked {
+                if let Err(e) = Self::restore_registry_version(config, &current_dir, npm_name, range.as_ref(), *link_pm) {
+                    println!("❌ Could not restore {}: {}", npm_name, e.to_string().trim());
+                }
+            }
+        }
+        if outcome.interrupted {
+            return Err(SpineError::Config("unlink-all was interrupted".to_string()).into());
+        }
+        if let (true, Some((package, error))) = (fail_fast, outcome.failed.first()) {
+            let index = outcome.completed.len() + outcome.failed.len() - 1;
+            return Err(crate::batch::BatchAbort::new(package, error.trim(), index, targets.len()).into_error().into());
+        }
+        Ok(outcome.completed.len())
+    }
+
+    /// Unlink each Spine-managed package linked in `project`
+    fn unlink_all_plan(config: &Config, project: &Path, restore: bool) -> Result<crate::plan::Plan> {
+        let mut plan = crate::plan::Plan::new("unlink-all", Some(project.to_path_buf()));
+        let detail = restore.then(|| "then reinstall from the registry".to_string());
+        for package_name in Self::get_linked_packages(project)? {
+            if config.find_by_npm_name(&package_name).is_some() {
+                plan.push("unlink", &package_name, detail.clone());
+            }
+        }
+        Ok(plan)
     }
 
-    pub fn show_status(config: &Config) -> Result<()> {
-        println!("NPM Link Status for current project:");
+    /// Links in `project` and the configured packages. Given `tags`, only
+    /// tagged packages are listed, so links Spine doesn't manage are left out.
+    pub fn show_status(config: &Config, project: &Path, tags: &[String]) -> Result<()> {
+        if std::env::current_dir().is_ok_and(|cwd| crate::paths::same_path(&cwd, project)) {
+            println!("NPM Link Status for current project:");
+        } else {
+            println!("NPM Link Status for {}:", display_path(project));
+        }
         
-        if !Self::is_npm_project()? {
-            println!("⚠ Warning: Current directory is not an npm project (no package.json found)");
+        if !Self::is_npm_project(project)? {
+            println!("⚠ Warning: {} is not an npm project (no package.json found)", display_path(project));
             return Ok(());
         }
+        let pnp = crate::pm::is_pnp_project(project);
+        if pnp {
+            println!("🧶 {}", crate::pm::PNP_STATUS);
+        }
 
-        let linked_packages = Self::get_linked_packages()?;
+        let linked_packages = Self::get_linked_packages(project)?;
+        let shown: Vec<&String> = linked_packages.iter()
+            .filter(|package| tags.is_empty() || config.find_by_npm_name(package).is_some_and(|link| link.has_any_tag(tags)))
+            .collect();
         
-        if linked_packages.is_empty() {
+        if shown.is_empty() && !pnp {
             println!("No packages currently linked in this project.");
             return Ok(());
         }
 
-        println!("\nCurrently linked packages:");
-        for package in &linked_packages {
-            let status = if config.links.contains_key(package) {
+        if !shown.is_empty() {
+            println!("\nCurrently linked packages:");
+        }
+        for package in shown {
+            let status = if config.find_by_npm_name(package).is_some() {
                 "✓ (managed by Spine)"
             } else {
                 "○ (not in Spine config)"
@@ -190,77 +1010,417 @@ impl NpmManager {
 
         if !config.links.is_empty() {
             println!("\nSpine configured packages:");
-            for (name, link) in &config.links {
-                let linked_status = if linked_packages.contains(name) {
+            let current_dir = project;
+            for (name, configured) in config.links.iter().filter(|(_, link)| link.has_any_tag(tags)) {
+                let link = &configured.for_project(current_dir);
+                let linked_status = if link.is_durable_in(current_dir) {
+                    "📌 durable (package.json override)"
+                } else if pnp {
+                    "🧶 PnP: link with --durable"
+                } else if linked_packages.iter().any(|p| p == link.npm_name()) {
                     "✓ linked"
                 } else {
                     "○ not linked"
                 };
-                println!("  {} -> {} [{}]", name, link.path.display(), linked_status);
+                let linked_status = if link.pending { crate::config::PENDING_MARKER } else { linked_status };
+                println!("  {}{} -> {} [{}]", name, link.variant_label(current_dir), display_path(&link.path), linked_status);
+                let in_use = link.is_durable_in(current_dir) || linked_packages.iter().any(|p| p == link.npm_name());
+                if let Some(check) = link.range_check(current_dir).filter(|c| in_use && !c.satisfies) {
+                    println!("    ⚠️  {}", check.describe());
+                }
+                let required_by = link.required_by_in(current_dir);
+                if in_use && !required_by.is_empty() {
+                    println!("    ↳ linked because {} requires it", required_by.join(", "));
+                }
             }
         }
 
         Ok(())
     }
 
-    pub fn verify_links(config: &mut Config) -> Result<()> {
+    pub fn verify_links(config: &mut Config, refresh_versions: bool, strict: bool, fix: bool, json: bool, check: bool) -> Result<()> {
+        if json || check {
+            return Self::verify_report(config, refresh_versions || strict, strict || check, json, check);
+        }
         println!("Verifying package links...");
         
-        let removed_links = config.verify_and_clean_links()?;
-        
-        if removed_links.is_empty() {
-            println!("✓ All links are valid.");
+        // Fix names first: a link checked under the wrong name looks broken
+        let names_clean = Self::verify_package_names(config)?;
+        // Merge before relinking, so the merged projects get repaired too
+        let duplicates_clean = Self::verify_duplicates(config, fix)?;
+        // Then follow moved build output, or every project using it would be pruned
+        let unmigrated = Self::migrate_moved_outputs(config, fix)?;
+        let removed_links = if fix {
+            Self::repair_links(config)?
         } else {
-            println!("Cleaned up {} broken link(s):", removed_links.len());
-            for link in &removed_links {
-                println!("  ✗ Removed: {}", link);
+            // Projects of a declined migration are kept so a later --fix can relink them
+            let removed_links: Vec<String> = Self::clean_links_keeping(config, &unmigrated)?
+                .into_iter()
+                .map(|(name, project)| format!("{} from {}", name, display_path(&project)))
+                .collect();
+            if removed_links.is_empty() {
+                println!("✓ All links are valid.");
+            } else {
+                println!("Cleaned up {} broken link(s):", removed_links.len());
+                for link in &removed_links {
+                    println!("  ✗ Removed: {}", link);
+                }
+            }
+            removed_links
+        };
+        
+        let mut versions_clean = true;
+        if refresh_versions || strict {
+            let report = config.refresh_versions();
+            if report.is_clean() {
+                println!("✓ All stored versions are up to date.");
+            } else {
+                println!("Refreshed package metadata:");
+                report.print();
+                versions_clean = false;
             }
+            if !report.updated.is_empty() {
+                config.save()?;
+            }
+        }
+        
+        if !removed_links.is_empty() {
             config.save()?;
             println!("\nConfiguration updated.");
         }
         
+        if !duplicates_clean {
+            return Err(SpineError::Config(
+                "Some configured packages resolve to the same package; run 'spine verify --fix' to merge them".to_string()
+            ).into());
+        }
+        if strict && (!removed_links.is_empty() || !versions_clean || !names_clean) {
+            return Err(SpineError::Config("Stored link metadata was out of date (--strict)".to_string()).into());
+        }
+        
         Ok(())
     }
 
-    fn npm_link(package_path: &Path) -> Result<()> {
-        Self::npm_link_static(package_path)
-    }
+    /// `verify --json` and `--check`: the same checks as a report, without
+    /// prompts. --check works on a copy of the config so nothing is written;
+    /// with `fail_on_problems` any finding is an error.
+    fn verify_report(config: &mut Config, refresh_versions: bool, fail_on_problems: bool, json: bool, check: bool) -> Result<()> {
+        let mut copy = config.clone();
+        let target = if check { &mut copy } else { config };
 
-    pub fn npm_link_static(package_path: &Path) -> Result<()> {
-        let output = Platform::npm_command()
-            .args(&["link", &package_path.to_string_lossy()])
-            .output()
-            .map_err(|e| SpineError::Io(e))?;
+        let mut name_mismatches: Vec<NameMismatch> = target.links.values()
+            .filter_map(|link| link.name_mismatch().map(|declared| NameMismatch { package: link.name.clone(), declared }))
+            .collect();
+        name_mismatches.sort_by(|a, b| a.package.cmp(&b.package));
+        let mut moved_outputs: Vec<MovedOutput> = target.links.values()
+            .filter_map(|link| crate::angular::moved_output_path(link)
+                .map(|to| MovedOutput { package: link.name.clone(), from: link.path.clone(), to }))
+            .collect();
+        moved_outputs.sort_by(|a, b| a.package.cmp(&b.package));
+        // Projects using moved output stay recorded until 'verify --fix' relinks them
+        let moved: Vec<String> = moved_outputs.iter().map(|moved| moved.package.clone()).collect();
+        let report = VerifyReport {
+            moved_outputs,
+            removed: Self::clean_links_keeping(target, &moved)?
+                .into_iter()
+                .map(|(package, project)| RemovedLink { package, project })
+                .collect(),
+            name_mismatches,
+            duplicates: target.duplicate_links(),
+            metadata: refresh_versions.then(|| target.refresh_versions()),
+            check,
+        };
+        let changed = !report.removed.is_empty() || report.metadata.as_ref().is_some_and(|m| !m.updated.is_empty());
+        if changed && !check {
+            target.save()?;
+        }
 
-        if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            return Err(SpineError::Config(format!("npm link failed: {}", error_msg)).into());
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            for moved in &report.moved_outputs {
+                println!("  📦 {}: output moved from {} to {} (run 'spine verify --fix')",
+                    moved.package, display_path(&moved.from), display_path(&moved.to));
+            }
+            let verb = if check { "Would remove" } else { "Removed" };
+            for removed in &report.removed {
+                println!("  ✗ {}: {} from {}", verb, removed.package, display_path(&removed.project));
+            }
+            for mismatch in &report.name_mismatches {
+                println!("  ⚠️  {}: published as {}", mismatch.package, mismatch.declared);
+            }
+            for duplicate in &report.duplicates {
+                println!("  🔀 {}: same {} ({}); 'spine verify --fix' keeps {}",
+                    duplicate.links.join(", "), duplicate.shared, duplicate.value, duplicate.keep);
+            }
+            if let Some(metadata) = &report.metadata {
+                metadata.print();
+            }
+            println!("\n📊 Summary: {} moved output(s), {} broken link(s), {} name mismatch(es), {} duplicate set(s), {} stale version(s)",
+                report.moved_outputs.len(), report.removed.len(), report.name_mismatches.len(), report.duplicates.len(),
+                report.metadata.as_ref().map(|m| m.updated.len() + m.unreadable.len()).unwrap_or(0));
         }
 
+        let problems = report.problem_count();
+        if fail_on_problems && problems > 0 {
+            return Err(SpineError::Config(format!("verify found {} problem(s)", problems)).into());
+        }
         Ok(())
     }
 
-    fn is_npm_project() -> Result<bool> {
-        Ok(Path::new("package.json").exists())
+    /// Point links whose build output moved (see `angular::moved_output_path`)
+    /// at the new location and relink the projects using them. Each batch is
+    /// confirmed first unless `auto`. Returns the packages left at their old
+    /// path because the migration was declined.
+    /// `verify_and_clean_links`, leaving the links named in `keep` as they were
+    fn clean_links_keeping(config: &mut Config, keep: &[String]) -> Result<Vec<(String, PathBuf)>> {
+        let kept: Vec<crate::config::PackageLink> = keep.iter().filter_map(|name| config.links.get(name).cloned()).collect();
+        let removed = config.verify_and_clean_links()?
+            .into_iter()
+            .filter(|(name, _)| !keep.contains(name))
+            .collect();
+        for previous in kept {
+            if let Some(link) = config.links.get_mut(&previous.name) {
+                link.linked_projects = previous.linked_projects;
+                link.linked_at = previous.linked_at;
+                link.last_seen = previous.last_seen;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn migrate_moved_outputs(config: &mut Config, auto: bool) -> Result<Vec<String>> {
+        let mut moved: Vec<(String, PathBuf)> = config.links.values()
+            .filter_map(|link| crate::angular::moved_output_path(link).map(|path| (link.name.clone(), path)))
+            .collect();
+        if moved.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        moved.sort();
+        println!("Build output has moved (e.g. after an Angular upgrade):");
+        for (name, new_path) in &moved {
+            println!("  📦 {}: {} → {}", name, display_path(&config.links[name].path), display_path(new_path));
+        }
+        if !auto && !crate::prompt::confirm("Point these links at the new output and relink their projects?", false) {
+            println!("  💡 Run 'spine verify --fix' to migrate them.");
+            return Ok(moved.into_iter().map(|(name, _)| name).collect());
+        }
+
+        let count = moved.len();
+        for (name, new_path) in moved {
+            let Some(link) = config.links.get_mut(&name) else { continue };
+            link.path = new_path;
+            link.version = crate::package::get_package_version(&link.path.join("package.json")).ok();
+            let link = link.clone();
+
+            for project in link.linked_projects.iter().filter(|project| project.is_dir()) {
+                let label = format!("{} in {}", name, display_path(project));
+                let pm = PackageManager::resolve(None, config.package_manager, project).manager;
+                match pm.link_in(project, &link.path, config.timeouts.link()) {
+                    Ok(_) if crate::config::Config::is_package_linked_in_project_static(link.npm_name(), project) => {
+                        crate::diff::record_snapshot(&config.file, &name, &link.path, "link");
+                        println!("  🔧 {}: relinked to the new output", label);
+                    }
+                    Ok(_) => println!("  ⚠️  {}: link command succeeded but verification failed", label),
+                    Err(e) => println!("  ⚠️  {}: could not relink ({})", label, e.to_string().lines().next().unwrap_or_default()),
+                }
+            }
+        }
+
+        config.save()?;
+        println!("✓ Migrated {} link(s) to their new output path.", count);
+        Ok(Vec::new())
+    }
+
+    /// Relink every recorded project whose symlink is missing or broken, running
+    /// the package manager in that project. Config entries are only pruned when
+    /// repair is impossible because the project or the package source is gone;
+    /// those are returned.
+    fn repair_links(config: &mut Config) -> Result<Vec<String>> {
+        let mut names: Vec<String> = config.links.keys().cloned().collect();
+        names.sort();
+
+        let mut fixed = 0;
+        let mut removed = 0;
+        let mut untouched = 0;
+        let mut pruned = Vec::new();
+
+        for name in names {
+            let link = config.links[&name].clone();
+            if link.pending {
+                continue;
+            }
+
+            for project in &link.linked_projects {
+                if crate::config::Config::is_package_linked_in_project_static(link.npm_name(), project) {
+                    continue;
+                }
+                let label = format!("{} in {}", name, display_path(project));
+                let link = link.for_project(project);
+
+                if !project.is_dir() {
+                    println!("  ✗ {}: project no longer exists", label);
+                    pruned.push((name.clone(), project.clone()));
+                    continue;
+                }
+
+                if !link.path.exists() {
+                    let installed = crate::paths::node_modules_path(project, link.npm_name());
+                    if installed.is_symlink() {
+                        match std::fs::remove_file(&installed) {
+                            Ok(_) => {
+                                println!("  🗑️  {}: removed symlink to deleted source {}", label, display_path(&link.path));
+                                removed += 1;
+                            }
+                            Err(e) => println!("  ⚠️  {}: could not remove broken symlink: {}", label, e),
+                        }
+                    } else {
+                        println!("  ✗ {}: source {} no longer exists", label, display_path(&link.path));
+                    }
+                    pruned.push((name.clone(), project.clone()));
+                    continue;
+                }
+
+                let pm = PackageManager::resolve(None, config.package_manager, project).manager;
+                let result = crate::permissions::ensure_not_self_link(&name, &link.path, project)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|_| crate::permissions::ensure_linkable(&link.path, project))
+                    .and_then(|_| pm.link_in(project, &link.path, config.timeouts.link()));
+                match result {
+                    Ok(_) if crate::config::Config::is_package_linked_in_project_static(link.npm_name(), project) => {
+                        crate::diff::record_snapshot(&config.file, &name, &link.path, "link");
+                        println!("  🔧 {}: relinked with {}", label, pm);
+                        fixed += 1;
+                    }
+                    Ok(_) => {
+                        println!("  ⚠️  {}: link command succeeded but verification failed", label);
+                        untouched += 1;
+                    }
+                    Err(e) => {
+                        println!("  ⚠️  {}: could not relink ({})", label, e.to_string().lines().next().unwrap_or_default());
+                        untouched += 1;
+                    }
+                }
+            }
+        }
+
+        if fixed + removed + untouched + pruned.len() == 0 {
+            println!("✓ All links are valid.");
+            return Ok(Vec::new());
+        }
+
+        let mut removed_links = Vec::new();
+        for (name, project) in pruned {
+            config.remove_linked_project(&name, &project)?;
+            removed_links.push(format!("{} from {}", name, display_path(&project)));
+        }
+
+        println!("\n📊 Summary: {} fixed, {} removed, {} untouched, {} pruned from config",
+            fixed, removed, untouched, removed_links.len());
+        Ok(removed_links)
+    }
+
+    /// Report links that resolve to the same package. With `fix` each set is
+    /// merged into one link, picked on the terminal or else the suggested
+    /// one. Returns false if any are left.
+    fn verify_duplicates(config: &mut Config, fix: bool) -> Result<bool> {
+        let duplicates = config.duplicate_links();
+        if duplicates.is_empty() {
+            return Ok(true);
+        }
+
+        println!("Configured packages that resolve to the same package:");
+        for duplicate in &duplicates {
+            println!("  🔀 {}: same {} ({})", duplicate.links.join(", "), duplicate.shared, duplicate.value);
+        }
+        if !fix {
+            println!("  💡 Run 'spine verify --fix' to merge each set into one link.");
+            return Ok(false);
+        }
+
+        // Sets can overlap, so each merge is followed by a fresh look
+        while let Some(duplicate) = config.duplicate_links().into_iter().next() {
+            let options: Vec<String> = duplicate.links.iter()
+                .map(|name| format!("{} ({} project(s))", name, config.links[name].linked_projects.len()))
+                .collect();
+            let question = format!("Keep which link for {}? Enter keeps {}", duplicate.value, duplicate.keep);
+            let keep = crate::prompt::choose(&question, &options)
+                .map(|choice| duplicate.links[choice].clone())
+                .unwrap_or(duplicate.keep);
+            let remove: Vec<String> = duplicate.links.into_iter().filter(|name| *name != keep).collect();
+            config.merge_links(&keep, &remove)?;
+            println!("  🔧 Merged {} into {}", remove.join(", "), keep);
+        }
+        config.save()?;
+        println!("✓ Duplicate links merged.");
+        Ok(true)
+    }
+
+    /// Report links whose name differs from their package.json `name` and offer
+    /// to record the real name for npm. Returns false if any are left as they were.
+    fn verify_package_names(config: &mut Config) -> Result<bool> {
+        let mut mismatched: Vec<(String, String)> = config.links.values()
+            .filter_map(|link| link.name_mismatch().map(|declared| (link.name.clone(), declared)))
+            .collect();
+        if mismatched.is_empty() {
+            return Ok(true);
+        }
+
+        mismatched.sort();
+        println!("Package names that differ from package.json:");
+        for (name, declared) in &mismatched {
+            println!("  ⚠️  {}: published as {}", name, declared);
+        }
+
+        if !crate::prompt::confirm("Use the package.json names for npm, keeping the current names as aliases?", false) {
+            println!("  💡 Re-run 'spine verify' in a terminal or 'spine doctor --fix' to correct them.");
+            return Ok(false);
+        }
+
+        for (name, declared) in mismatched {
+            if let Some(link) = config.links.get_mut(&name) {
+                link.set_npm_name(declared);
+            }
+        }
+        config.save()?;
+        println!("✓ Package names updated.");
+        Ok(true)
+    }
+
+    pub fn is_npm_project(project: &Path) -> Result<bool> {
+        Ok(project.join("package.json").exists())
     }
 
-    fn get_linked_packages() -> Result<Vec<String>> {
-        if !std::path::Path::new("node_modules").exists() {
+    /// Canonicalize a `--project` directory and check it is an npm project
+    pub fn resolve_project_dir(path: &Path) -> Result<PathBuf> {
+        let project = path.canonicalize()
+            .map_err(|e| SpineError::InvalidPath(format!("Cannot access project {}: {}", path.display(), e)))?;
+        if !Self::is_npm_project(&project)? {
+            return Err(SpineError::InvalidPath(format!(
+                "{} is not an npm project (no package.json found)", display_path(&project)
+            )).into());
+        }
+        Ok(project)
+    }
+
+    fn get_linked_packages(project: &Path) -> Result<Vec<String>> {
+        let node_modules = project.join("node_modules");
+        if !node_modules.exists() {
             return Ok(Vec::new());
         }
 
         let mut packages = Vec::new();
-        let node_modules = std::path::Path::new("node_modules");
         
         // Scan for direct symlinks
-        for entry in std::fs::read_dir(node_modules).map_err(|e| SpineError::Io(e))? {
+        for entry in std::fs::read_dir(&node_modules).map_err(SpineError::Io)? {
             let entry = entry.map_err(|e| SpineError::Io(e))?;
             let path = entry.path();
             
             if path.is_symlink() {
                 if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                     // Verify symlink target exists and is valid
-                    if Self::is_valid_symlink(&path) {
+                    if Self::is_valid_symlink(project, &path) {
                         packages.push(name.to_string());
                     }
                 }
@@ -274,7 +1434,7 @@ impl NpmManager {
                         
                         if scope_path.is_symlink() {
                             if let Some(scope_name) = scope_path.file_name().and_then(|n| n.to_str()) {
-                                if Self::is_valid_symlink(&scope_path) {
+                                if Self::is_valid_symlink(project, &scope_path) {
                                     let full_name = format!("{}/{}", entry.file_name().to_string_lossy(), scope_name);
                                     packages.push(full_name);
                                 }
@@ -290,46 +1450,137 @@ impl NpmManager {
         Ok(packages)
     }
 
-    fn is_valid_symlink(path: &std::path::Path) -> bool {
-        // Check if symlink target exists and is readable
+    fn is_valid_symlink(project: &Path, path: &Path) -> bool {
+        // Check if symlink target exists and is readable, ignoring pnpm's
+        // regular installs which are symlinks into node_modules/.pnpm
         path.read_link().is_ok() && path.exists()
+            && !crate::pm::is_pnpm_store_entry(project, path)
     }
 
-    pub fn get_linked_packages_static() -> Result<Vec<String>> {
-        Self::get_linked_packages()
+    pub fn get_linked_packages_static(project: &Path) -> Result<Vec<String>> {
+        Self::get_linked_packages(project)
     }
 
-    pub fn show_enhanced_status(config: &Config, detailed: bool, health: bool, json: bool) -> Result<()> {
-        let current_dir = std::env::current_dir()?;
+    /// Show link status as seen from `project`, normally the current directory
+    pub fn show_enhanced_status(config: &Config, project: &Path, detailed: bool, health: bool, json: bool, tags: &[String]) -> Result<()> {
+        let current_dir = project.to_path_buf();
+        let tagged = config.with_tags(tags);
+        
+        if !json {
+            Self::print_package_manager(config, &current_dir);
+        }
         
         if json {
-            Self::show_status_json(config, detailed, health, &current_dir)
+            Self::show_status_json(&tagged, detailed, health, &current_dir)
         } else if health {
-            Self::show_health_status(config, detailed, &current_dir)
+            Self::show_health_status(&tagged, detailed, &current_dir)
         } else if detailed {
-            Self::show_detailed_status(config, &current_dir)
+            Self::show_detailed_status(&tagged, &current_dir)
         } else {
-            Self::show_status(config)
+            Self::show_status(config, &current_dir, tags)
+        }
+    }
+
+    fn print_package_manager(config: &Config, current_dir: &Path) {
+        let detection = PackageManager::detect(current_dir);
+        match config.package_manager {
+            Some(configured) => println!("📦 Package manager: {} (configured; detected {})", configured, detection.manager),
+            None => println!("📦 Package manager: {} (detected from {})", detection.manager, detection.evidence.join(", ")),
         }
+        detection.print_warning();
+        println!();
     }
 
     fn show_status_json(config: &Config, detailed: bool, health: bool, current_dir: &std::path::PathBuf) -> Result<()> {
+        println!("{}", serde_json::to_string_pretty(&Self::status_json(config, detailed, health, current_dir))?);
+        Ok(())
+    }
+
+    /// `spine status --report`: the `--json --health` document, with each
+    /// package as a test case failing on a missing path, package.json or
+    /// permission problem
+    pub fn status_report(config: &Config, project: &Path) -> crate::report::Report {
+        use crate::report::ReportCase;
+
+        let json = Self::status_json(config, true, true, project);
+        let mut cases = Vec::new();
+        if let Some(packages) = json["packages"].as_object() {
+            for (name, package) in packages {
+                let permission_problems: Vec<&str> = package["permission_problems"].as_array()
+                    .map(|problems| problems.iter().filter_map(|p| p.as_str()).collect())
+                    .unwrap_or_default();
+                cases.push(if package["pending"] == true {
+                    ReportCase::skipped(name, crate::config::PENDING_MARKER)
+                } else if package["path_exists"] == false {
+                    ReportCase::failed(name, format!("path does not exist: {}", package["path"].as_str().unwrap_or_default()))
+                } else if package["package_json_exists"] == false {
+                    ReportCase::failed(name, "no package.json in the package path")
+                } else if !permission_problems.is_empty() {
+                    ReportCase::failed(name, permission_problems.join("\n"))
+                } else {
+                    ReportCase::passed(name)
+                });
+            }
+        }
+        crate::report::Report { suite: "spine status".to_string(), cases, json }
+    }
+
+    pub fn status_json(config: &Config, detailed: bool, health: bool, current_dir: &Path) -> serde_json::Value {
         let mut status = serde_json::Map::new();
         status.insert("current_directory".to_string(), serde_json::Value::String(current_dir.display().to_string()));
         status.insert("total_packages".to_string(), serde_json::Value::Number(config.links.len().into()));
+        let detection = PackageManager::detect(current_dir);
+        let package_manager = config.package_manager.unwrap_or(detection.manager);
+        status.insert("package_manager".to_string(), serde_json::Value::String(package_manager.name().to_string()));
+        status.insert("package_manager_detected".to_string(), serde_json::Value::String(detection.manager.name().to_string()));
+        let pnp = crate::pm::is_pnp_project(current_dir);
+        status.insert("pnp".to_string(), serde_json::Value::Bool(pnp));
         
         let mut packages = serde_json::Map::new();
         
-        for (name, link) in &config.links {
+        for (name, configured) in &config.links {
+            let link = &configured.for_project(current_dir);
             let mut package_info = serde_json::Map::new();
             package_info.insert("path".to_string(), serde_json::Value::String(link.path.display().to_string()));
+            if let Some(variant) = configured.variant_in(current_dir).or(configured.default_variant.as_deref()) {
+                package_info.insert("variant".to_string(), serde_json::Value::String(variant.to_string()));
+                package_info.insert("variants".to_string(), serde_json::json!(configured.variant_names()));
+            }
+            package_info.insert("vendored".to_string(), serde_json::Value::Bool(link.is_vendored()));
+            package_info.insert("pending".to_string(), serde_json::Value::Bool(link.pending));
+            package_info.insert("npm_name".to_string(), serde_json::Value::String(link.npm_name().to_string()));
+            package_info.insert("tags".to_string(), serde_json::json!(link.tags));
+            if let Some(note) = &link.note {
+                package_info.insert("note".to_string(), serde_json::Value::String(note.clone()));
+            }
+            if let Some(created_by) = &link.created_by_version {
+                package_info.insert("created_by_version".to_string(), serde_json::Value::String(created_by.clone()));
+            }
+            if let Some(created_at) = link.created_at {
+                package_info.insert("created_at".to_string(), serde_json::Value::Number(created_at.into()));
+            }
             
             if let Some(version) = &link.version {
                 package_info.insert("version".to_string(), serde_json::Value::String(version.clone()));
             }
+            if let Some(previous) = &link.previous_version {
+                package_info.insert("previous_version".to_string(), serde_json::Value::String(previous.clone()));
+            }
             
-            let is_linked = link.linked_projects.iter().any(|p| p == current_dir);
+            let is_linked = link.linked_projects.iter().any(|p| crate::paths::same_path(p, current_dir));
             package_info.insert("linked_to_current".to_string(), serde_json::Value::Bool(is_linked));
+            package_info.insert("durable_in_current".to_string(), serde_json::Value::Bool(link.is_durable_in(current_dir)));
+            let (link_manager, source) = link.effective_package_manager(current_dir, config.package_manager);
+            package_info.insert("package_manager".to_string(), serde_json::Value::String(link_manager.name().to_string()));
+            package_info.insert("package_manager_source".to_string(), serde_json::Value::String(source.to_string()));
+            if is_linked && !link.required_by_in(current_dir).is_empty() {
+                package_info.insert("required_by".to_string(), serde_json::json!(link.required_by_in(current_dir)));
+            }
+            if let Some(check) = link.range_check(current_dir).filter(|_| is_linked || link.is_durable_in(current_dir)) {
+                package_info.insert("declared_range".to_string(), serde_json::Value::String(check.declared_range));
+                package_info.insert("actual_version".to_string(), serde_json::Value::String(check.actual_version));
+                package_info.insert("satisfies".to_string(), serde_json::Value::Bool(check.satisfies));
+            }
             
             if detailed || health {
                 let path_exists = link.path.exists();
@@ -338,6 +1589,19 @@ impl NpmManager {
                 if health {
                     let package_json_exists = link.path.join("package.json").exists();
                     package_info.insert("package_json_exists".to_string(), serde_json::Value::Bool(package_json_exists));
+                    let permission_problems: Vec<serde_json::Value> = crate::permissions::check_package(&link.path)
+                        .iter()
+                        .map(|p| serde_json::Value::String(p.describe()))
+                        .collect();
+                    package_info.insert("permission_problems".to_string(), serde_json::Value::Array(permission_problems));
+                    if let Some(mismatch) = link.package_manager_mismatch(current_dir, config.package_manager) {
+                        package_info.insert("package_manager_mismatch".to_string(), serde_json::Value::String(mismatch));
+                    }
+                    let resolution = if pnp { crate::pm::pnp_resolution(current_dir, link.npm_name(), config.timeouts.link()) } else { None };
+                    if let Some(resolution) = resolution {
+                        package_info.insert("pnp_reference".to_string(), serde_json::Value::String(resolution.reference.clone()));
+                        package_info.insert("pnp_resolves_to_path".to_string(), serde_json::Value::Bool(resolution.points_to(&link.path)));
+                    }
                     
                     // Check for version mismatch
                     if let Some(current_version) = &link.version {
@@ -355,10 +1619,14 @@ impl NpmManager {
             packages.insert(name.clone(), serde_json::Value::Object(package_info));
         }
         
+        // Maps keep insertion order, so sort rather than follow the HashMap
+        packages.sort_keys();
         status.insert("packages".to_string(), serde_json::Value::Object(packages));
-        
-        println!("{}", serde_json::to_string_pretty(&status)?);
-        Ok(())
+        if health {
+            let project_problem = crate::permissions::check_project(current_dir).map(|p| p.describe());
+            status.insert("project_permission_problem".to_string(), serde_json::json!(project_problem));
+        }
+        serde_json::Value::Object(status)
     }
 
     fn show_health_status(config: &Config, detailed: bool, current_dir: &std::path::PathBuf) -> Result<()> {
@@ -367,19 +1635,51 @@ impl NpmManager {
         
         let mut healthy = 0;
         let mut issues = 0;
+        let mut pending = 0;
+        let pnp = crate::pm::is_pnp_project(current_dir);
+        if pnp {
+            println!("🧶 {}; checking Plug'n'Play resolutions instead", crate::pm::PNP_STATUS);
+        }
         
-        for (name, link) in &config.links {
-            let is_linked = link.linked_projects.iter().any(|p| p == current_dir);
+        for (name, configured) in &config.links {
+            let link = &configured.for_project(current_dir);
+            let name = &format!("{}{}", name, link.variant_label(current_dir));
+            if link.pending {
+                println!("⏳ {} - {}", name, crate::config::PENDING_MARKER);
+                pending += 1;
+                continue;
+            }
+            
+            let is_linked = link.linked_projects.iter().any(|p| crate::paths::same_path(p, current_dir));
             let path_exists = link.path.exists();
             let package_json_exists = link.path.join("package.json").exists();
             
             let mut warnings = Vec::new();
             let mut errors = Vec::new();
             
+            // Under Plug'n'Play a durable link shows up as the package resolving to its path
+            let pnp_resolution = if pnp { crate::pm::pnp_resolution(current_dir, link.npm_name(), config.timeouts.link()) } else { None };
+            let pnp_linked = pnp_resolution.as_ref().is_some_and(|resolution| resolution.points_to(&link.path));
+            if let Some(resolution) = pnp_resolution.as_ref().filter(|_| link.is_durable_in(current_dir) && !pnp_linked) {
+                warnings.push(format!("PnP resolves it to {}, not the configured path (run 'yarn install')", resolution.reference));
+            }
+            
+            let permission_problems = crate::permissions::check_package(&link.path);
             if !path_exists {
-                errors.push("Path does not exist");
-            } else if !package_json_exists {
-                errors.push("Missing package.json");
+                match crate::angular::moved_output_path(link) {
+                    Some(moved) => errors.push(format!(
+                        "Path does not exist; the package is now built at {} (run 'spine verify --fix')", display_path(&moved)
+                    )),
+                    None => errors.push("Path does not exist".to_string()),
+                }
+            } else if !package_json_exists && permission_problems.is_empty() {
+                match link.nested_package_hint() {
+                    Some(hint) => warnings.push(hint),
+                    None => errors.push("Missing package.json".to_string()),
+                }
+            }
+            for problem in &permission_problems {
+                errors.push(problem.describe());
             }
             
             // Check version mismatch
@@ -391,11 +1691,35 @@ impl NpmManager {
                 }
             }
             
+            if let Some(declared) = link.name_mismatch() {
+                warnings.push(format!("Name mismatch: npm uses '{}', stored '{}' (run 'spine verify' to fix)", declared, link.npm_name()));
+            }
+            
+            let in_use = is_linked || link.is_durable_in(current_dir);
+            if let Some(check) = link.range_check(current_dir).filter(|c| in_use && !c.satisfies) {
+                warnings.push(check.describe());
+            }
+            if in_use && path_exists {
+                for mismatch in crate::package::angular_peer_mismatches(&link.path, current_dir) {
+                    warnings.push(format!("Angular peer: {}", mismatch.describe()));
+                }
+            }
+            if let Some(mismatch) = link.package_manager_mismatch(current_dir, config.package_manager).filter(|_| in_use) {
+                warnings.push(format!("Package manager mismatch: {}", mismatch));
+            }
+            
             if errors.is_empty() && warnings.is_empty() {
                 print!("✅ {}", name);
-                if is_linked {
+                if pnp_linked {
+                    print!(" (PnP: resolves to the configured path)");
+                } else if link.is_durable_in(current_dir) {
+                    print!(" (durable)");
+                } else if is_linked {
                     print!(" (linked)");
                 }
+                if link.is_vendored() {
+                    print!(" [vendored]");
+                }
                 println!();
                 healthy += 1;
             } else {
@@ -415,7 +1739,7 @@ impl NpmManager {
                 }
                 
                 if detailed {
-                    println!("   Path: {}", link.path.display());
+                    println!("   Path: {}", display_path(&link.path));
                     if let Some(version) = &link.version {
                         println!("   Stored version: {}", version);
                     }
@@ -423,7 +1747,99 @@ impl NpmManager {
             }
         }
         
-        println!("\n📊 Summary: {} healthy, {} with issues", healthy, issues);
+        if let Some(problem) = crate::permissions::check_project(current_dir) {
+            println!("❌ Project: {}", problem.describe());
+            println!("   💡 {}", problem.suggestion());
+            issues += 1;
+        }
+        
+        if pending > 0 {
+            println!("\n📊 Summary: {} healthy, {} with issues, {} pending", healthy, issues, pending);
+        } else {
+            println!("\n📊 Summary: {} healthy, {} with issues", healthy, issues);
+        }
+        Ok(())
+    }
+
+    pub fn show_package_info(config: &Config, package_name: &str) -> Result<()> {
+        let link = config.links.get(package_name)
+            .ok_or_else(|| {
+                let available: Vec<String> = config.links.keys().cloned().collect();
+                SpineError::package_not_found_with_suggestions(package_name, &available)
+            })?;
+
+        println!("📦 {}", link.name);
+        if let Some(note) = &link.note {
+            println!("   Note: {}", note);
+        }
+        println!("   Path: {}", display_path(&link.path));
+        if let Some(default) = &link.default_variant {
+            println!("   Default variant: {}", default);
+            for (variant, chosen) in &link.variants {
+                println!("   Variant {}: {} (v{})", variant, display_path(&chosen.path), chosen.version.as_deref().unwrap_or("unknown"));
+            }
+        }
+        if let Some(entry) = config.workspace_overrides.get(package_name) {
+            match &entry.global {
+                Some(global) => println!("   Workspace override from {} (global path {})", display_path(&entry.file), display_path(&global.path)),
+                None => println!("   Workspace link from {} (not in the global config)", display_path(&entry.file)),
+            }
+        }
+        if link.is_vendored() {
+            println!("   Vendored: yes (lives under node_modules)");
+        }
+        println!("   Stored version: {}", link.version.as_deref().unwrap_or("unknown"));
+        if let Some(previous) = &link.previous_version {
+            println!("   Previous version: {}", previous);
+        }
+
+        if let Ok(actual_version) = crate::package::get_package_version(&link.path.join("package.json")) {
+            println!("   Actual version: {}", actual_version);
+        }
+
+        // Registry as npm would resolve it from the package's own location
+        let mut npmrc_dirs = vec![link.path.clone()];
+        if let Ok(workspace_root) = crate::angular::AngularBuildManager::find_workspace_root_for_package(&link.path) {
+            npmrc_dirs.push(workspace_root);
+        }
+        let registry = crate::npmrc::resolve_registry(&link.name, &npmrc_dirs);
+        println!("   Registry: {} (from {})", registry.registry, registry.source);
+
+        let licenses = crate::licenses::license_entry(link, &config.licenses.deny, false);
+        println!("   License: {}{}", licenses.license.as_deref().unwrap_or("none declared"),
+            if licenses.license_file { " (LICENSE file present)" } else { "" });
+        if let Some(author) = &licenses.author {
+            println!("   Author: {}", author);
+        }
+        if let Some(repository) = &licenses.repository {
+            println!("   Repository: {}", repository);
+        }
+        for warning in &licenses.warnings {
+            println!("   ⚠️  {}", warning);
+        }
+
+        let created_by = link.created_by_version.as_deref().unwrap_or("unknown");
+        match link.created_at {
+            Some(created_at) => println!("   Created: {} by spine {}", crate::history::format_age(created_at), created_by),
+            None => println!("   Created: before version tracking (first seen by spine {})", created_by),
+        }
+        if let Some(writer) = &config.last_written_by {
+            let host = writer.hostname.as_deref().map(|h| format!(", {}", h)).unwrap_or_default();
+            println!("   Config last written by: spine {} ({}{})", writer.version, writer.os, host);
+        }
+
+        if link.linked_projects.is_empty() {
+            println!("   Linked projects: none");
+        } else {
+            println!("   Linked projects:");
+            for project in &link.linked_projects {
+                match link.linked_age(project) {
+                    Some(age) => println!("     • {} ({})", display_path(project), age),
+                    None => println!("     • {}", display_path(project)),
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -437,10 +1853,16 @@ impl NpmManager {
         }
         
         for (name, link) in &config.links {
-            let is_linked = link.linked_projects.iter().any(|p| p == current_dir);
+            let is_linked = link.linked_projects.iter().any(|p| crate::paths::same_path(p, current_dir));
             
             println!("\n📦 {}", name);
-            println!("   Path: {}", link.path.display());
+            println!("   Path: {}", display_path(&link.path));
+            if link.pending {
+                println!("   ⏳ {}", crate::config::PENDING_MARKER);
+            }
+            if link.is_vendored() {
+                println!("   Vendored: yes (lives under node_modules)");
+            }
             
             if let Some(version) = &link.version {
                 print!("   Version: {}", version);
@@ -454,8 +1876,19 @@ impl NpmManager {
                 println!();
             }
             
-            if is_linked {
+            if let Some(check) = link.range_check(current_dir).filter(|_| is_linked || link.is_durable_in(current_dir)) {
+                let verdict = if check.satisfies { "✅" } else { "⚠️  not satisfied" };
+                println!("   Declared range: {} {}", check.declared_range, verdict);
+            }
+            
+            if link.is_durable_in(current_dir) {
+                println!("   Status: 📌 Durably linked to current project (package.json override)");
+            } else if is_linked {
                 println!("   Status: ✅ Linked to current project");
+                let required_by = link.required_by_in(current_dir);
+                if !required_by.is_empty() {
+                    println!("   Linked because {} requires it", required_by.join(", "));
+                }
             } else {
                 println!("   Status: ⭕ Not linked to current project");
             }
@@ -463,13 +1896,18 @@ impl NpmManager {
             if !link.linked_projects.is_empty() {
                 println!("   Linked projects:");
                 for project in &link.linked_projects {
-                    println!("     • {}", project.display());
+                    match link.linked_age(project) {
+                        Some(age) => println!("     • {} ({})", display_path(project), age),
+                        None => println!("     • {}", display_path(project)),
+                    }
                 }
             }
             
             // Check path health
             if !link.path.exists() {
                 println!("   ❌ Path does not exist");
+            } else if let Some(hint) = link.nested_package_hint() {
+                println!("   ⚠️  {}", hint);
             } else if !link.path.join("package.json").exists() {
                 println!("   ⚠️  No package.json found");
             }
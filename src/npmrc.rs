@@ -0,0 +1,200 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+
+pub const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+
+/// Effective registry for a package and where the setting came from
+#[derive(Debug, Clone)]
+pub struct RegistrySetting {
+    pub registry: String,
+    pub source: String,
+}
+
+/// Collect existing .npmrc files in priority order: the given directories first
+/// (most specific first), then the user-level ~/.npmrc.
+pub fn npmrc_chain(search_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut chain = Vec::new();
+
+    for dir in search_dirs {
+        let candidate = dir.join(".npmrc");
+        if candidate.is_file() && !chain.contains(&candidate) {
+            chain.push(candidate);
+        }
+    }
+
+    if let Some(home) = dirs::home_dir() {
+        let user_npmrc = home.join(".npmrc");
+        if user_npmrc.is_file() && !chain.contains(&user_npmrc) {
+            chain.push(user_npmrc);
+        }
+    }
+
+    chain
+}
+
+/// Resolve the registry npm would use for `package_name`, honouring
+/// `@scope:registry` mappings before the plain `registry` key.
+pub fn resolve_registry(package_name: &str, search_dirs: &[PathBuf]) -> RegistrySetting {
+    let scope_key = package_scope(package_name).map(|scope| format!("{}:registry", scope));
+    let chain = npmrc_chain(search_dirs);
+
+    if let Some(scope_key) = &scope_key {
+        for npmrc in &chain {
+            if let Some(value) = read_npmrc_value(npmrc, scope_key) {
+                return RegistrySetting {
+                    registry: value,
+                    source: npmrc.display().to_string(),
+                };
+            }
+        }
+    }
+
+    for var in ["npm_config_registry", "NPM_CONFIG_REGISTRY"] {
+        if let Ok(value) = std::env::var(var) {
+            if !value.is_empty() {
+                return RegistrySetting {
+                    registry: value,
+                    source: format!("${}", var),
+                };
+            }
+        }
+    }
+
+    for npmrc in &chain {
+        if let Some(value) = read_npmrc_value(npmrc, "registry") {
+            return RegistrySetting {
+                registry: value,
+                source: npmrc.display().to_string(),
+            };
+        }
+    }
+
+    RegistrySetting {
+        registry: DEFAULT_REGISTRY.to_string(),
+        source: "npm default".to_string(),
+    }
+}
+
+/// The `@scope` part of a scoped package name
+pub fn package_scope(package_name: &str) -> Option<&str> {
+    if package_name.starts_with('@') {
+        package_name.split('/').next()
+    } else {
+        None
+    }
+}
+
+fn read_npmrc_value(npmrc: &Path, key: &str) -> Option<String> {
+    let content = fs::read_to_string(npmrc).ok()?;
+    parse_npmrc_value(&content, key)
+}
+
+fn parse_npmrc_value(content: &str, key: &str) -> Option<String> {
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with(';'))
+        .filter_map(|line| line.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, v)| v.trim().trim_matches('"').to_string())
+}
+
+/// Project .npmrc settings written into a publish directory for the duration
+/// of a publish. The file is removed again when this guard is dropped.
+pub struct TemporaryNpmrc {
+    path: PathBuf,
+}
+
+impl TemporaryNpmrc {
+    /// Write the project-level .npmrc files of `search_dirs` (most specific
+    /// first) into `target_dir` as one, unless it already has one. A key set
+    /// in several keeps the most specific value, as npm would resolve it.
+    /// Returns None when there was nothing to write.
+    pub fn install(target_dir: &Path, search_dirs: &[PathBuf]) -> Result<Option<Self>> {
+        let target = target_dir.join(".npmrc");
+        if target.exists() {
+            return Ok(None);
+        }
+
+        let mut sources: Vec<PathBuf> = Vec::new();
+        for candidate in search_dirs.iter().map(|dir| dir.join(".npmrc")) {
+            if candidate.is_file() && !sources.contains(&candidate) {
+                sources.push(candidate);
+            }
+        }
+        if sources.is_empty() {
+            return Ok(None);
+        }
+
+        let contents = sources.iter().map(fs::read_to_string).collect::<std::io::Result<Vec<String>>>()?;
+        fs::write(&target, merge_npmrc(&contents))?;
+        let names: Vec<String> = sources.iter().map(|source| crate::paths::display_path(source)).collect();
+        println!("📄 Using registry settings from {}", names.join(", "));
+        Ok(Some(Self { path: target }))
+    }
+}
+
+/// One .npmrc from several, most specific first: each key keeps its first
+/// setting, while `key[]` array entries are all kept
+fn merge_npmrc(contents: &[String]) -> String {
+    let mut keys: Vec<&str> = Vec::new();
+    let mut merged = String::new();
+    for line in contents.iter().flat_map(|content| content.lines()) {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+        let Some((key, _)) = trimmed.split_once('=') else { continue };
+        let key = key.trim();
+        if !key.ends_with("[]") {
+            if keys.contains(&key) {
+                continue;
+            }
+            keys.push(key);
+        }
+        merged.push_str(trimmed);
+        merged.push('\n');
+    }
+    merged
+}
+
+impl Drop for TemporaryNpmrc {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_npmrc_keeps_the_most_specific_setting() {
+        let library = "registry=https://npm.acme.dev/\n; a comment\n@acme:registry=https://npm.acme.dev/acme/\nca[]=first\n".to_string();
+        let workspace = "registry=https://registry.npmjs.org/\n//npm.acme.dev/:_authToken=${NPM_TOKEN}\nca[]=second\n".to_string();
+
+        let merged = merge_npmrc(&[library, workspace]);
+        assert_eq!(parse_npmrc_value(&merged, "registry").as_deref(), Some("https://npm.acme.dev/"));
+        assert_eq!(parse_npmrc_value(&merged, "@acme:registry").as_deref(), Some("https://npm.acme.dev/acme/"));
+        assert_eq!(parse_npmrc_value(&merged, "//npm.acme.dev/:_authToken").as_deref(), Some("${NPM_TOKEN}"));
+        assert_eq!(merged.lines().filter(|line| line.starts_with("ca[]=")).count(), 2);
+        assert_eq!(merged.lines().filter(|line| line.starts_with("registry=")).count(), 1);
+    }
+
+    #[test]
+    fn installs_and_removes_the_merged_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let (library, workspace, publish) = (dir.path().join("lib"), dir.path().to_path_buf(), dir.path().join("dist"));
+        fs::create_dir_all(&library).unwrap();
+        fs::create_dir_all(&publish).unwrap();
+        fs::write(library.join(".npmrc"), "registry=https://npm.acme.dev/\n").unwrap();
+        fs::write(workspace.join(".npmrc"), "always-auth=true\n").unwrap();
+
+        let guard = TemporaryNpmrc::install(&publish, &[library, workspace]).unwrap().expect("written");
+        let written = fs::read_to_string(publish.join(".npmrc")).unwrap();
+        assert_eq!(written, "registry=https://npm.acme.dev/\nalways-auth=true\n");
+        drop(guard);
+        assert!(!publish.join(".npmrc").exists());
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 use anyhow::Result;
@@ -10,6 +11,39 @@ pub struct PackageInfo {
     pub version: String,
     pub dependencies: Vec<String>,
     pub dev_dependencies: Vec<String>,
+    pub optional_dependencies: Vec<String>,
+    pub peer_dependencies: Vec<String>,
+    /// Declared version range of each dependency and devDependency
+    pub dependency_ranges: BTreeMap<String, String>,
+    /// Ranges from optionalDependencies and peerDependencies, which are
+    /// listed but never installed from
+    pub other_ranges: BTreeMap<String, String>,
+}
+
+impl PackageInfo {
+    /// Version range the package declares for `dependency`, if any
+    pub fn declared_range(&self, dependency: &str) -> Option<&str> {
+        self.dependency_ranges.get(dependency).map(|s| s.as_str())
+    }
+
+    /// Range any of the four fields declares for `dependency`, for listings
+    pub fn listed_range(&self, dependency: &str) -> Option<&str> {
+        self.declared_range(dependency).or_else(|| self.other_ranges.get(dependency).map(|s| s.as_str()))
+    }
+
+    /// The package.json field that declares `dependency`, checked in the
+    /// order npm installs from them
+    pub fn dependency_field(&self, dependency: &str) -> Option<&'static str> {
+        [
+            ("dependencies", &self.dependencies),
+            ("devDependencies", &self.dev_dependencies),
+            ("optionalDependencies", &self.optional_dependencies),
+            ("peerDependencies", &self.peer_dependencies),
+        ]
+        .into_iter()
+        .find(|(_, names)| names.iter().any(|name| name == dependency))
+        .map(|(field, _)| field)
+    }
 }
 
 pub fn get_package_name(package_json_path: &Path) -> Result<String> {
@@ -48,15 +82,172 @@ pub fn parse_package_json(package_json_path: &Path) -> Result<PackageInfo> {
 
     let dependencies = extract_dependencies(&json, "dependencies");
     let dev_dependencies = extract_dependencies(&json, "devDependencies");
+    let optional_dependencies = extract_dependencies(&json, "optionalDependencies");
+    let peer_dependencies = extract_dependencies(&json, "peerDependencies");
 
     Ok(PackageInfo {
         name,
         version,
         dependencies,
         dev_dependencies,
+        optional_dependencies,
+        peer_dependencies,
+        dependency_ranges: extract_dependency_ranges(&json),
+        other_ranges: extract_ranges(&json, &["peerDependencies", "optionalDependencies"]),
     })
 }
 
+/// Declared dependency ranges of a project, which unlike a publishable package
+/// needn't have a name or version
+pub fn get_dependency_ranges(package_json_path: &Path) -> Result<BTreeMap<String, String>> {
+    let content = fs::read_to_string(package_json_path)?;
+    let json: Value = serde_json::from_str(&content)?;
+    Ok(extract_dependency_ranges(&json))
+}
+
+fn extract_dependency_ranges(json: &Value) -> BTreeMap<String, String> {
+    // dependencies win over devDependencies, as they do for npm
+    extract_ranges(json, &["devDependencies", "dependencies"])
+}
+
+/// Ranges declared in `fields`, later fields winning
+fn extract_ranges(json: &Value, fields: &[&str]) -> BTreeMap<String, String> {
+    let mut dependency_ranges = BTreeMap::new();
+    for field in fields {
+        if let Some(deps) = json.get(field).and_then(|deps| deps.as_object()) {
+            for (dependency, range) in deps {
+                if let Some(range) = range.as_str() {
+                    dependency_ranges.insert(dependency.clone(), range.to_string());
+                }
+            }
+        }
+    }
+    dependency_ranges
+}
+
+/// A linked package's actual version against the range a project declares for it
+#[derive(Debug, Clone)]
+pub struct RangeCheck {
+    pub declared_range: String,
+    pub actual_version: String,
+    pub satisfies: bool,
+}
+
+impl RangeCheck {
+    pub fn describe(&self) -> String {
+        format!("Version drift: {} does not satisfy declared range '{}'", self.actual_version, self.declared_range)
+    }
+}
+
+/// An `@angular/*` peer dependency of a package that the consuming project
+/// doesn't satisfy
+#[derive(Debug, Clone)]
+pub struct PeerMismatch {
+    pub peer: String,
+    pub range: String,
+    /// Version installed in the project, None when it isn't installed
+    pub installed: Option<String>,
+}
+
+impl PeerMismatch {
+    pub fn describe(&self) -> String {
+        match &self.installed {
+            Some(installed) => format!("{} {} required, {} installed", self.peer, self.range, installed),
+            None => format!("{} {} required, not installed", self.peer, self.range),
+        }
+    }
+}
+
+/// The package's `@angular/*` peer dependencies that the versions installed
+/// in `project` don't satisfy. Linking across Angular majors fails at runtime
+/// with injection errors (NG0203) rather than at build time. Ranges that
+/// aren't semver are skipped, as are peers marked optional in
+/// `peerDependenciesMeta` that the project doesn't install.
+pub fn angular_peer_mismatches(package_dir: &Path, project: &Path) -> Vec<PeerMismatch> {
+    let json = fs::read_to_string(package_dir.join("package.json")).ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .unwrap_or_default();
+    let peers = json.get("peerDependencies").and_then(|p| p.as_object()).cloned().unwrap_or_default();
+    let is_optional = |peer: &str| json.get("peerDependenciesMeta")
+        .and_then(|meta| meta.get(peer))
+        .and_then(|meta| meta.get("optional"))
+        .and_then(|optional| optional.as_bool())
+        .unwrap_or(false);
+
+    peers.iter()
+        .filter(|(peer, _)| peer.starts_with("@angular/"))
+        .filter_map(|(peer, range)| {
+            let range = range.as_str()?;
+            let installed = get_package_version(&crate::paths::node_modules_path(project, peer).join("package.json")).ok();
+            let satisfied = match &installed {
+                Some(version) => range_satisfies(range, version)?,
+                None => is_optional(peer),
+            };
+            (!satisfied).then(|| PeerMismatch { peer: peer.clone(), range: range.to_string(), installed })
+        })
+        .collect()
+}
+
+/// Whether `version` satisfies an npm range such as `^2.4.0`, `>=1.2 <3` or
+/// `1.x || 2.x`. None when either side isn't semver, e.g. `file:`,
+/// `workspace:*`, git URLs and dist-tags. Pre-releases only match a range
+/// naming the same version's pre-release, as with npm.
+pub fn range_satisfies(range: &str, version: &str) -> Option<bool> {
+    let version = semver::Version::parse(version.trim().trim_start_matches(['v', '='])).ok()?;
+    let alternatives = range.split("||")
+        .map(npm_comparator_set)
+        .collect::<Option<Vec<_>>>()?;
+    Some(alternatives.iter().any(|req| req.matches(&version)))
+}
+
+/// Translate one `||` alternative of an npm range into a semver requirement
+fn npm_comparator_set(set: &str) -> Option<semver::VersionReq> {
+    let set = set.trim();
+    if set.is_empty() {
+        return Some(semver::VersionReq::STAR);
+    }
+
+    if let Some((lower, upper)) = set.split_once(" - ") {
+        return semver::VersionReq::parse(&format!(">={}, <={}", lower.trim(), upper.trim())).ok();
+    }
+
+    // npm separates comparators with spaces and allows `>= 1.2.3` and `v1.2.3`.
+    // A bare version is exact in npm but a caret range to the semver crate.
+    let mut comparators: Vec<String> = Vec::new();
+    let mut operator = String::new();
+    for token in set.split_whitespace() {
+        let version_start = token.find(|c: char| !matches!(c, '<' | '>' | '=' | '~' | '^')).unwrap_or(token.len());
+        let (op, version) = token.split_at(version_start);
+        operator.push_str(op);
+        if version.is_empty() {
+            continue;
+        }
+        let version = version.trim_start_matches('v');
+        if operator.is_empty() && !matches!(version, "*" | "x" | "X") {
+            operator.push('=');
+        }
+        comparators.push(format!("{}{}", std::mem::take(&mut operator), version));
+    }
+    if !operator.is_empty() {
+        return None;
+    }
+
+    semver::VersionReq::parse(&comparators.join(", ")).ok()
+}
+
+/// Names a package needs at runtime: its dependencies and peerDependencies
+pub fn get_runtime_dependency_names(package_json_path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(package_json_path)?;
+    let json: Value = serde_json::from_str(&content)?;
+    let mut names = extract_dependencies(&json, "dependencies");
+    for peer in extract_dependencies(&json, "peerDependencies") {
+        if !names.contains(&peer) {
+            names.push(peer);
+        }
+    }
+    Ok(names)
+}
+
 fn extract_dependencies(json: &Value, field: &str) -> Vec<String> {
     json.get(field)
         .and_then(|deps| deps.as_object())
@@ -75,4 +266,144 @@ pub fn validate_package_path(path: &Path) -> Result<bool> {
     }
 
     parse_package_json(&package_json).map(|_| true)
-}
\ No newline at end of file
+}
+
+/// License and provenance fields of a package.json
+#[derive(Debug, Clone, Default)]
+pub struct PackageMetadata {
+    pub license: Option<String>,
+    pub author: Option<String>,
+    pub repository: Option<String>,
+}
+
+pub fn parse_package_metadata(package_json_path: &Path) -> Result<PackageMetadata> {
+    let content = fs::read_to_string(package_json_path)?;
+    let json: Value = serde_json::from_str(&content)?;
+
+    // `license` is normally an SPDX string; older packages use an object or a
+    // `licenses` array of objects with a `type`
+    let license = match json.get("license") {
+        Some(Value::String(s)) => Some(s.clone()),
+        Some(Value::Object(o)) => o.get("type").and_then(|t| t.as_str()).map(|s| s.to_string()),
+        _ => json.get("licenses")
+            .and_then(|l| l.as_array())
+            .map(|licenses| {
+                licenses.iter()
+                    .filter_map(|l| l.get("type").and_then(|t| t.as_str()))
+                    .collect::<Vec<_>>()
+                    .join(" OR ")
+            })
+            .filter(|s| !s.is_empty()),
+    };
+
+    Ok(PackageMetadata {
+        license,
+        author: string_or_field(json.get("author"), "name"),
+        repository: string_or_field(json.get("repository"), "url"),
+    })
+}
+
+/// Whether the package.json defines script `name`
+pub fn has_script(package_json_path: &Path, name: &str) -> bool {
+    fs::read_to_string(package_json_path).ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .is_some_and(|json| json.get("scripts").and_then(|scripts| scripts.get(name)).is_some())
+}
+
+fn string_or_field(value: Option<&Value>, field: &str) -> Option<String> {
+    match value? {
+        Value::String(s) => Some(s.clone()),
+        Value::Object(o) => o.get(field).and_then(|v| v.as_str()).map(|s| s.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caret_ranges_allow_compatible_updates() {
+        assert_eq!(range_satisfies("^2.4.0", "2.4.0"), Some(true));
+        assert_eq!(range_satisfies("^2.4.0", "2.9.1"), Some(true));
+        assert_eq!(range_satisfies("^2.4.0", "2.3.9"), Some(false));
+        assert_eq!(range_satisfies("^2.4.0", "3.0.0"), Some(false));
+        // Below 1.0 the caret only allows patches
+        assert_eq!(range_satisfies("^0.2.3", "0.2.9"), Some(true));
+        assert_eq!(range_satisfies("^0.2.3", "0.3.0"), Some(false));
+    }
+
+    #[test]
+    fn pre_releases_need_a_range_naming_them() {
+        assert_eq!(range_satisfies("^2.4.0", "2.5.0-next.1"), Some(false));
+        assert_eq!(range_satisfies("^2.5.0-next.0", "2.5.0-next.1"), Some(true));
+    }
+
+    #[test]
+    fn bare_versions_are_exact_pins() {
+        for pin in ["2.4.0", "=2.4.0", "v2.4.0", "= 2.4.0"] {
+            assert_eq!(range_satisfies(pin, "2.4.0"), Some(true), "{}", pin);
+            assert_eq!(range_satisfies(pin, "2.4.1"), Some(false), "{}", pin);
+        }
+        assert_eq!(range_satisfies("^2.4.0", "v2.4.0"), Some(true));
+    }
+
+    #[test]
+    fn understands_npm_range_syntax() {
+        assert_eq!(range_satisfies(">=1.2 <3", "2.9.0"), Some(true));
+        assert_eq!(range_satisfies(">= 1.2.0 < 3.0.0", "3.0.0"), Some(false));
+        assert_eq!(range_satisfies("1.x || 2.x", "2.3.0"), Some(true));
+        assert_eq!(range_satisfies("1.x || 2.x", "3.0.0"), Some(false));
+        assert_eq!(range_satisfies("1.2.0 - 1.4.0", "1.4.0"), Some(true));
+        assert_eq!(range_satisfies("~1.2.0", "1.3.0"), Some(false));
+        assert_eq!(range_satisfies("*", "9.9.9"), Some(true));
+        assert_eq!(range_satisfies("", "9.9.9"), Some(true));
+    }
+
+    #[test]
+    fn non_semver_ranges_are_unknown() {
+        for range in ["workspace:*", "workspace:^1.0.0", "file:../ui", "latest", "github:acme/ui#main", ">="] {
+            assert_eq!(range_satisfies(range, "1.0.0"), None, "{}", range);
+        }
+        assert_eq!(range_satisfies("^1.0.0", "not-a-version"), None);
+    }
+
+    #[test]
+    fn peer_and_optional_ranges_are_listed_but_not_installed_from() {
+        let json: Value = serde_json::from_str(r#"{
+            "dependencies": { "ui": "^2.0.0" },
+            "devDependencies": { "ui": "^1.0.0", "test-kit": "^3.0.0" },
+            "peerDependencies": { "core": "^17.0.0" },
+            "optionalDependencies": { "icons": "^1.2.0" }
+        }"#).unwrap();
+        let ranges = extract_dependency_ranges(&json);
+        assert_eq!(ranges.get("ui").map(String::as_str), Some("^2.0.0"));
+        assert_eq!(ranges.get("test-kit").map(String::as_str), Some("^3.0.0"));
+        assert!(!ranges.contains_key("core"));
+        assert!(!ranges.contains_key("icons"));
+        assert_eq!(extract_ranges(&json, &["peerDependencies"]).get("core").map(String::as_str), Some("^17.0.0"));
+    }
+
+    #[test]
+    fn missing_optional_angular_peers_are_not_mismatches() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("ui");
+        let project = dir.path().join("app");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(package.join("package.json"), r#"{
+            "name": "ui",
+            "peerDependencies": { "@angular/core": "^17.0.0", "@angular/forms": "^17.0.0", "@angular/animations": "^17.0.0" },
+            "peerDependenciesMeta": { "@angular/forms": { "optional": true }, "@angular/animations": { "optional": true } }
+        }"#).unwrap();
+        let animations = project.join("node_modules/@angular/animations");
+        fs::create_dir_all(&animations).unwrap();
+        fs::write(animations.join("package.json"), r#"{ "name": "@angular/animations", "version": "16.2.0" }"#).unwrap();
+
+        let peers: Vec<_> = angular_peer_mismatches(&package, &project).into_iter().map(|m| m.peer).collect();
+        // Required and missing is flagged, optional and missing isn't, and an
+        // optional peer that is installed still has to satisfy its range
+        assert_eq!(peers.len(), 2);
+        assert!(peers.contains(&"@angular/core".to_string()));
+        assert!(peers.contains(&"@angular/animations".to_string()));
+    }
+}
@@ -0,0 +1,738 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Paths longer than this get their middle components abbreviated
+const MAX_DISPLAY_LEN: usize = 72;
+
+static FULL_PATHS: AtomicBool = AtomicBool::new(false);
+
+/// Show untruncated paths everywhere for the rest of the process
+pub fn set_full_paths(enabled: bool) {
+    FULL_PATHS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn full_paths() -> bool {
+    FULL_PATHS.load(Ordering::Relaxed)
+}
+
+/// How far upward walks for workspace markers (angular.json, .spine.toml) may go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalkLimits {
+    /// Parent directories to climb before giving up
+    pub max_levels: usize,
+    /// Stop at the first directory holding `.git`
+    pub stop_at_repository: bool,
+}
+
+pub const DEFAULT_WALK_LEVELS: usize = 12;
+
+static WALK_LIMITS: Mutex<WalkLimits> = Mutex::new(WalkLimits { max_levels: DEFAULT_WALK_LEVELS, stop_at_repository: true });
+
+/// Walks that found nothing, by marker and starting directory
+static WALK_MISSES: OnceLock<Mutex<HashMap<(String, PathBuf), WalkOutcome>>> = OnceLock::new();
+
+/// Apply the configured walk limits for the rest of the process
+pub fn set_walk_limits(limits: WalkLimits) {
+    if let Ok(mut current) = WALK_LIMITS.lock() {
+        *current = limits;
+    }
+}
+
+pub fn walk_limits() -> WalkLimits {
+    WALK_LIMITS.lock().map(|limits| *limits).unwrap_or(WalkLimits { max_levels: DEFAULT_WALK_LEVELS, stop_at_repository: true })
+}
+
+/// Why an upward walk ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalkStop {
+    Found,
+    FilesystemRoot,
+    MaxLevels(usize),
+    RepositoryRoot,
+    /// The parent is on another device (a mount point, often an automounted home)
+    DeviceBoundary,
+}
+
+impl WalkStop {
+    pub fn describe(&self) -> String {
+        match self {
+            WalkStop::Found => "found".to_string(),
+            WalkStop::FilesystemRoot => "reached the filesystem root".to_string(),
+            WalkStop::MaxLevels(levels) => format!("climbed the maximum of {} level(s)", levels),
+            WalkStop::RepositoryRoot => "reached a repository root (.git)".to_string(),
+            WalkStop::DeviceBoundary => "the parent is on another filesystem".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WalkOutcome {
+    /// The directory holding the marker
+    pub found: Option<PathBuf>,
+    /// The last directory checked
+    pub stopped_at: PathBuf,
+    pub reason: WalkStop,
+}
+
+/// Look for `marker` in `start` and its parents, within the walk limits.
+/// The walk never crosses onto another device (on Windows it ends at the
+/// volume root, where paths have no parent). Misses are cached per
+/// starting directory for the rest of the process.
+pub fn walk_up(start: &Path, marker: &str) -> WalkOutcome {
+    let key = (marker.to_string(), start.to_path_buf());
+    let misses = WALK_MISSES.get_or_init(Default::default);
+    if let Some(cached) = misses.lock().ok().and_then(|misses| misses.get(&key).cloned()) {
+        return cached;
+    }
+
+    let outcome = walk_up_within(start, marker, walk_limits());
+    if outcome.found.is_none() {
+        if let Ok(mut misses) = misses.lock() {
+            misses.insert(key, outcome.clone());
+        }
+    }
+    outcome
+}
+
+/// `walk_up` with explicit limits and no cache
+fn walk_up_within(start: &Path, marker: &str, limits: WalkLimits) -> WalkOutcome {
+    let mut current = start.to_path_buf();
+    let mut device = None;
+    let mut level = 0;
+    loop {
+        let stop = |reason| WalkOutcome { found: None, stopped_at: current.clone(), reason };
+        if current.join(marker).exists() {
+            break WalkOutcome { found: Some(current.clone()), stopped_at: current.clone(), reason: WalkStop::Found };
+        }
+        if limits.stop_at_repository && current.join(".git").exists() {
+            break stop(WalkStop::RepositoryRoot);
+        }
+        if level >= limits.max_levels {
+            break stop(WalkStop::MaxLevels(limits.max_levels));
+        }
+        let Some(parent) = current.parent().filter(|parent| !parent.as_os_str().is_empty()) else {
+            break stop(WalkStop::FilesystemRoot);
+        };
+        // The start may not exist yet (a pending link), so take the device
+        // of the first directory that does
+        device = device.or_else(|| device_id(&current));
+        if let (Some(here), Some(above)) = (device, device_id(parent)) {
+            if here != above {
+                break stop(WalkStop::DeviceBoundary);
+            }
+        }
+        current = parent.to_path_buf();
+        level += 1;
+    }
+}
+
+#[cfg(unix)]
+fn device_id(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|metadata| metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device_id(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Canonical forms resolved during this invocation. Failures aren't kept: a
+/// missing path is retried, since it may be a build output still appearing.
+static CANONICAL_CACHE: OnceLock<Mutex<HashMap<PathBuf, PathBuf>>> = OnceLock::new();
+static CANONICAL_HITS: AtomicUsize = AtomicUsize::new(0);
+static CANONICAL_MISSES: AtomicUsize = AtomicUsize::new(0);
+static CANONICAL_MISS_NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// `canonicalize` through a cache kept for the rest of the process, so
+/// matching packages against libraries and workspaces resolves each path once.
+/// That matters on network filesystems, where every lookup is a round trip.
+/// Only for startup work that doesn't expect paths to appear or move meanwhile.
+pub fn canonicalize_cached(path: &Path) -> Option<PathBuf> {
+    let cache = CANONICAL_CACHE.get_or_init(Default::default);
+    if let Some(cached) = cache.lock().ok().and_then(|cache| cache.get(path).cloned()) {
+        CANONICAL_HITS.fetch_add(1, Ordering::Relaxed);
+        return Some(cached);
+    }
+
+    let started = Instant::now();
+    let resolved = path.canonicalize().ok();
+    CANONICAL_MISSES.fetch_add(1, Ordering::Relaxed);
+    CANONICAL_MISS_NANOS.fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+
+    if let (Some(resolved), Ok(mut cache)) = (&resolved, cache.lock()) {
+        cache.insert(path.to_path_buf(), resolved.clone());
+    }
+    resolved
+}
+
+/// Forget cached canonical forms and walks that found nothing, for
+/// long-running sessions whose changes create and move paths
+pub fn forget_cached_paths() {
+    if let Some(Ok(mut cache)) = CANONICAL_CACHE.get().map(|cache| cache.lock()) {
+        cache.clear();
+    }
+    if let Some(Ok(mut misses)) = WALK_MISSES.get().map(|misses| misses.lock()) {
+        misses.clear();
+    }
+}
+
+/// How the canonicalization cache has done so far in this process
+#[derive(Debug, Clone, Copy)]
+pub struct CanonicalizeStats {
+    pub hits: usize,
+    pub misses: usize,
+    /// Time spent in the lookups that did reach the filesystem
+    pub lookup_time: Duration,
+}
+
+impl CanonicalizeStats {
+    /// Time the cache hits would have cost at the average lookup time
+    pub fn estimated_savings(&self) -> Duration {
+        if self.misses == 0 {
+            return Duration::ZERO;
+        }
+        self.lookup_time / self.misses as u32 * self.hits as u32
+    }
+}
+
+pub fn canonicalize_stats() -> CanonicalizeStats {
+    CanonicalizeStats {
+        hits: CANONICAL_HITS.load(Ordering::Relaxed),
+        misses: CANONICAL_MISSES.load(Ordering::Relaxed),
+        lookup_time: Duration::from_nanos(CANONICAL_MISS_NANOS.load(Ordering::Relaxed)),
+    }
+}
+
+/// Render a path for human-facing output. `$HOME` becomes `~` and very long
+/// paths keep their first and last components around an ellipsis, unless
+/// full paths were requested. JSON output should use the raw path instead.
+pub fn display_path(path: &Path) -> String {
+    // Canonical paths on Windows carry a \\?\ prefix no user typed
+    let path = normalize_path(path);
+    if full_paths() {
+        return path.display().to_string();
+    }
+
+    let home = dirs::home_dir();
+    render(&path, home.as_deref(), MAX_DISPLAY_LEN)
+}
+
+/// Expand a leading `~` to the home directory and `$VAR` / `${VAR}` to their
+/// environment values. Unset variables stay as written, so the path is
+/// reported missing rather than resolving somewhere unexpected.
+pub fn expand_path(raw: &str) -> String {
+    match raw.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => match dirs::home_dir() {
+            Some(home) => format!("{}{}", home.to_string_lossy(), expand_variables(rest)),
+            None => expand_variables(raw),
+        },
+        _ => expand_variables(raw),
+    }
+}
+
+/// Whether `raw` has anything for `expand_path` to expand, set or not
+pub fn uses_expansion(raw: &str) -> bool {
+    raw.starts_with('~') || raw.contains('$')
+}
+
+fn expand_variables(text: &str) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        // The variable's name and how many bytes it takes up after the `$`
+        let (name, length) = match after.strip_prefix('{') {
+            Some(braced) => braced.find('}').map(|end| (&braced[..end], end + 2)).unwrap_or(("", 0)),
+            None => {
+                let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        match std::env::var(name).ok().filter(|_| !name.is_empty()) {
+            Some(value) => expanded.push_str(&value),
+            None => expanded.push_str(&rest[start..start + 1 + length]),
+        }
+        rest = &after[length..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Normalize a path entering Spine (CLI arguments, config values) to the
+/// platform's native form. On Windows this undoes Git Bash/MSYS mangling
+/// (`/c/Users/...`), strips `\\?\` verbatim prefixes left by canonicalize and
+/// fixes forward slashes; elsewhere the path is returned unchanged.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        if let Some(native) = path.to_str().and_then(normalize_windows_path) {
+            return PathBuf::from(native);
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Where a package is installed in a project: `node_modules/<name>`, with
+/// scoped names split into their scope directory
+pub fn node_modules_path(project: &Path, package_name: &str) -> PathBuf {
+    package_name.split('/').fold(project.join("node_modules"), |path, part| path.join(part))
+}
+
+/// Remove the symlink or junction at `path` without touching its target.
+/// Anything else, such as a real directory an install put there since the
+/// link was seen, is refused. Windows directory symlinks and junctions are
+/// removed as directories, everything else as a file.
+pub fn remove_symlink(path: &Path) -> std::io::Result<()> {
+    let file_type = std::fs::symlink_metadata(path)?.file_type();
+    if !file_type.is_symlink() {
+        return Err(std::io::Error::other(format!("{} is not a symlink", display_path(path))));
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::FileTypeExt;
+        if file_type.is_symlink_dir() {
+            return std::fs::remove_dir(path);
+        }
+    }
+    std::fs::remove_file(path)
+}
+
+/// Compare two paths after normalization, so stored and freshly computed
+/// forms of the same directory match
+pub fn same_path(a: &Path, b: &Path) -> bool {
+    a == b || normalize_path(a) == normalize_path(b)
+}
+
+/// Windows-native form of an MSYS, verbatim or mixed-separator path, or None
+/// when the path is already native. Kept platform-independent so the
+/// conversion can be exercised anywhere.
+pub fn normalize_windows_path(path: &str) -> Option<String> {
+    let mut converted = path.to_string();
+
+    if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        converted = format!(r"\\{}", rest);
+    } else if let Some(rest) = path.strip_prefix(r"\\?\") {
+        converted = rest.to_string();
+    } else if let Some((drive, rest)) = msys_drive(path) {
+        converted = format!("{}:\\{}", drive.to_ascii_uppercase(), rest);
+    }
+
+    // A drive-letter path should use backslashes throughout
+    if converted.as_bytes().get(1) == Some(&b':') || converted.starts_with(r"\\") {
+        converted = converted.replace('/', "\\");
+        while converted.len() > 3 && converted.ends_with('\\') {
+            converted.pop();
+        }
+    }
+
+    if converted == path {
+        None
+    } else {
+        Some(converted)
+    }
+}
+
+/// Split `/c/Users/x` or `/cygdrive/c/Users/x` into ('c', "Users/x")
+fn msys_drive(path: &str) -> Option<(char, &str)> {
+    let rest = path.strip_prefix("/cygdrive/").or_else(|| path.strip_prefix('/'))?;
+    let mut chars = rest.chars();
+    let drive = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    match chars.next() {
+        None => Some((drive, "")),
+        Some('/') => Some((drive, &rest[2..])),
+        _ => None,
+    }
+}
+
+/// How two directories sit relative to each other on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nesting {
+    Same,
+    /// The first path is inside the second
+    Inside,
+    /// The second path is inside the first
+    Contains,
+}
+
+/// Whether `a` and `b` are the same directory or one contains the other,
+/// compared by their canonical forms so symlinks and `..` don't hide it
+pub fn nesting(a: &Path, b: &Path) -> Option<Nesting> {
+    let a = a.canonicalize().unwrap_or_else(|_| normalize_path(a));
+    let b = b.canonicalize().unwrap_or_else(|_| normalize_path(b));
+    if a == b {
+        Some(Nesting::Same)
+    } else if a.starts_with(&b) {
+        Some(Nesting::Inside)
+    } else if b.starts_with(&a) {
+        Some(Nesting::Contains)
+    } else {
+        None
+    }
+}
+
+/// `path` relative to `base` with `/` separators, climbing out with `..` as
+/// needed, for files shared between machines. Paths on another drive or root
+/// stay absolute.
+pub fn relative_path(path: &Path, base: &Path) -> String {
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return path.iter().collect::<PathBuf>().to_string_lossy().to_string();
+    }
+
+    let mut parts: Vec<String> = vec!["..".to_string(); base.len() - common];
+    parts.extend(path[common..].iter().map(|c| c.as_os_str().to_string_lossy().to_string()));
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// `relative` (which may also be absolute) resolved against `base`, with `.`
+/// and `..` removed without touching the filesystem
+pub fn resolve_relative(base: &Path, relative: &str) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in base.join(normalize_path(Path::new(relative))).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+/// True when any component of `path` is a node_modules directory, as with
+/// vendored forks copied into another package's dependencies
+pub fn is_under_node_modules(path: &Path) -> bool {
+    path.components().any(|c| c.as_os_str() == "node_modules")
+}
+
+fn render(path: &Path, home: Option<&Path>, max_len: usize) -> String {
+    let (prefix, rest) = split_for_display(path, home);
+
+    let joined = join(&prefix, &rest);
+    if joined.chars().count() <= max_len || rest.len() <= 3 {
+        return joined;
+    }
+
+    let abbreviated = vec![
+        rest[0].clone(),
+        "…".to_string(),
+        rest[rest.len() - 2].clone(),
+        rest[rest.len() - 1].clone(),
+    ];
+    join(&prefix, &abbreviated)
+}
+
+/// Split a path into a leading anchor (`~`, `/`, `C:\`, `\\server\share\`) and
+/// the remaining normal components.
+fn split_for_display(path: &Path, home: Option<&Path>) -> (String, Vec<String>) {
+    if let Some(home) = home {
+        if let Ok(relative) = path.strip_prefix(home) {
+            let rest = normal_components(relative);
+            let prefix = if rest.is_empty() { "~".to_string() } else { format!("~{}", MAIN_SEPARATOR) };
+            return (prefix, rest);
+        }
+    }
+
+    let mut prefix = String::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(p) => prefix.push_str(&p.as_os_str().to_string_lossy()),
+            Component::RootDir => prefix.push(MAIN_SEPARATOR),
+            _ => break,
+        }
+    }
+
+    (prefix, normal_components(path))
+}
+
+fn normal_components(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            Component::CurDir => Some(".".to_string()),
+            Component::ParentDir => Some("..".to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn join(prefix: &str, parts: &[String]) -> String {
+    let separator = MAIN_SEPARATOR.to_string();
+    format!("{}{}", prefix, parts.join(&separator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn removes_a_directory_symlink_but_not_its_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("lib");
+        std::fs::create_dir(&target).unwrap();
+        std::fs::write(target.join("package.json"), "{}").unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        remove_symlink(&link).unwrap();
+        assert!(std::fs::symlink_metadata(&link).is_err());
+        assert!(target.join("package.json").is_file());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn removes_a_dangling_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(dir.path().join("gone"), &link).unwrap();
+
+        remove_symlink(&link).unwrap();
+        assert!(std::fs::symlink_metadata(&link).is_err());
+    }
+
+    #[test]
+    fn refuses_what_is_no_longer_a_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let installed = dir.path().join("installed");
+        std::fs::create_dir(&installed).unwrap();
+        let file = dir.path().join("file");
+        std::fs::write(&file, "real").unwrap();
+
+        assert!(remove_symlink(&installed).is_err());
+        assert!(remove_symlink(&file).is_err());
+        assert!(installed.is_dir());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "real");
+    }
+
+    #[test]
+    fn converts_msys_drive_paths() {
+        assert_eq!(normalize_windows_path("/c/Users/dev/lib").as_deref(), Some(r"C:\Users\dev\lib"));
+        assert_eq!(normalize_windows_path("/cygdrive/d/work/lib").as_deref(), Some(r"D:\work\lib"));
+        assert_eq!(normalize_windows_path("/c").as_deref(), Some(r"C:\"));
+        assert_eq!(normalize_windows_path("/c/").as_deref(), Some(r"C:\"));
+    }
+
+    #[test]
+    fn fixes_separators_of_drive_paths() {
+        assert_eq!(normalize_windows_path("C:/Users/dev/lib/").as_deref(), Some(r"C:\Users\dev\lib"));
+        assert_eq!(normalize_windows_path(r"C:\Users/dev\lib").as_deref(), Some(r"C:\Users\dev\lib"));
+        assert_eq!(normalize_windows_path("C:/").as_deref(), Some(r"C:\"));
+    }
+
+    #[test]
+    fn strips_verbatim_prefixes() {
+        assert_eq!(normalize_windows_path(r"\\?\C:\Users\dev\lib").as_deref(), Some(r"C:\Users\dev\lib"));
+        assert_eq!(normalize_windows_path(r"\\?\UNC\server\share\lib").as_deref(), Some(r"\\server\share\lib"));
+    }
+
+    #[test]
+    fn fixes_separators_of_unc_paths() {
+        assert_eq!(normalize_windows_path(r"\\server\share/libs/ui\").as_deref(), Some(r"\\server\share\libs\ui"));
+    }
+
+    #[test]
+    fn leaves_native_and_unix_paths_alone() {
+        for path in [r"C:\Users\dev\lib", r"C:\", r"\\server\share\lib", "/usr/local/lib", "/home/dev/lib", "relative/lib"] {
+            assert_eq!(normalize_windows_path(path), None, "{}", path);
+        }
+    }
+
+    #[test]
+    fn cached_canonical_forms_match_canonicalize() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("libs").join("ui");
+        std::fs::create_dir_all(&lib).unwrap();
+        // Only a symlink on Unix; elsewhere it stands in for another missing path
+        let link = dir.path().join("link");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&lib, &link).unwrap();
+        let paths = [
+            dir.path().to_path_buf(),
+            lib.clone(),
+            lib.join("..").join("ui"),
+            dir.path().join("libs").join(".").join("ui"),
+            dir.path().join("missing"),
+            link,
+        ];
+
+        for path in &paths {
+            let expected = path.canonicalize().ok();
+            assert_eq!(canonicalize_cached(path), expected, "first lookup of {}", path.display());
+            assert_eq!(canonicalize_cached(path), expected, "cached lookup of {}", path.display());
+        }
+    }
+
+    #[test]
+    fn forgetting_cached_paths_sees_moves() {
+        let dir = tempfile::tempdir().unwrap();
+        let before = dir.path().join("before");
+        std::fs::create_dir(&before).unwrap();
+        let resolved = canonicalize_cached(&before);
+        assert!(resolved.is_some());
+
+        std::fs::rename(&before, dir.path().join("after")).unwrap();
+        // Startup work keeps one view of the filesystem
+        assert_eq!(canonicalize_cached(&before), resolved);
+        forget_cached_paths();
+        assert_eq!(canonicalize_cached(&before), None);
+    }
+
+    #[test]
+    fn missing_paths_are_looked_up_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let dist = dir.path().join("dist");
+        assert_eq!(canonicalize_cached(&dist), None);
+
+        std::fs::create_dir(&dist).unwrap();
+        assert_eq!(canonicalize_cached(&dist), dist.canonicalize().ok());
+    }
+
+    /// Times repeated lookups of a deep path with and without the cache:
+    /// `cargo test canonicalize_cache_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn canonicalize_cache_benchmark() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = deep_dir(dir.path(), 12);
+        let lookups = 20_000;
+
+        let started = Instant::now();
+        for _ in 0..lookups {
+            std::hint::black_box(path.canonicalize().ok());
+        }
+        let direct = started.elapsed();
+        let started = Instant::now();
+        for _ in 0..lookups {
+            std::hint::black_box(canonicalize_cached(&path));
+        }
+        let cached = started.elapsed();
+
+        println!("{} lookups: canonicalize {:?}, canonicalize_cached {:?}", lookups, direct, cached);
+        assert!(cached < direct);
+    }
+
+    /// `levels` nested directories below `root`, returning the deepest
+    fn deep_dir(root: &Path, levels: usize) -> PathBuf {
+        let dir = (0..levels).fold(root.to_path_buf(), |dir, level| dir.join(format!("d{}", level)));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn limits(max_levels: usize, stop_at_repository: bool) -> WalkLimits {
+        WalkLimits { max_levels, stop_at_repository }
+    }
+
+    #[test]
+    fn walks_up_to_the_marker() {
+        let root = tempfile::tempdir().unwrap();
+        let start = deep_dir(root.path(), 8);
+        std::fs::write(root.path().join("d0").join("angular.json"), "{}").unwrap();
+
+        let outcome = walk_up_within(&start, "angular.json", limits(DEFAULT_WALK_LEVELS, true));
+        assert_eq!(outcome.reason, WalkStop::Found);
+        assert_eq!(outcome.found.as_deref(), Some(root.path().join("d0").as_path()));
+        assert_eq!(outcome.stopped_at, root.path().join("d0"));
+    }
+
+    #[test]
+    fn stops_after_the_maximum_levels() {
+        let root = tempfile::tempdir().unwrap();
+        let start = deep_dir(root.path(), 20);
+        std::fs::write(root.path().join("angular.json"), "{}").unwrap();
+
+        let outcome = walk_up_within(&start, "angular.json", limits(5, true));
+        assert_eq!(outcome.reason, WalkStop::MaxLevels(5));
+        assert!(outcome.found.is_none());
+        assert_eq!(outcome.stopped_at, start.ancestors().nth(5).unwrap());
+
+        // A marker exactly at the limit is still found
+        let outcome = walk_up_within(&start, "angular.json", limits(20, true));
+        assert_eq!(outcome.found.as_deref(), Some(root.path()));
+    }
+
+    #[test]
+    fn stops_at_the_repository_root() {
+        let root = tempfile::tempdir().unwrap();
+        let start = deep_dir(root.path(), 10);
+        let repository = root.path().join("d0").join("d1");
+        std::fs::create_dir(repository.join(".git")).unwrap();
+        std::fs::write(root.path().join(".spine.toml"), "").unwrap();
+
+        let outcome = walk_up_within(&start, ".spine.toml", limits(DEFAULT_WALK_LEVELS, true));
+        assert_eq!(outcome.reason, WalkStop::RepositoryRoot);
+        assert_eq!(outcome.stopped_at, repository);
+
+        let outcome = walk_up_within(&start, ".spine.toml", limits(DEFAULT_WALK_LEVELS, false));
+        assert_eq!(outcome.found.as_deref(), Some(root.path()));
+
+        // A marker in the repository root itself still counts
+        std::fs::write(repository.join(".spine.toml"), "").unwrap();
+        let outcome = walk_up_within(&start, ".spine.toml", limits(DEFAULT_WALK_LEVELS, true));
+        assert_eq!(outcome.found, Some(repository));
+    }
+
+    #[test]
+    fn walks_from_a_directory_that_does_not_exist_yet() {
+        let root = tempfile::tempdir().unwrap();
+        let workspace = deep_dir(root.path(), 3);
+        std::fs::write(workspace.join("angular.json"), "{}").unwrap();
+        let pending = workspace.join("dist").join("ui");
+
+        let outcome = walk_up_within(&pending, "angular.json", limits(DEFAULT_WALK_LEVELS, true));
+        assert_eq!(outcome.found, Some(workspace));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shortens_the_home_directory() {
+        let home = Path::new("/home/dev");
+        assert_eq!(render(Path::new("/home/dev/src/ui"), Some(home), MAX_DISPLAY_LEN), "~/src/ui");
+        assert_eq!(render(home, Some(home), MAX_DISPLAY_LEN), "~");
+        assert_eq!(render(Path::new("/home/developer/ui"), Some(home), MAX_DISPLAY_LEN), "/home/developer/ui");
+        assert_eq!(render(Path::new("/srv/ui"), None, MAX_DISPLAY_LEN), "/srv/ui");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn abbreviates_the_middle_of_long_paths() {
+        assert_eq!(render(Path::new("/work/clients/acme/libs/ui"), None, 10), "/work/…/libs/ui");
+        assert_eq!(render(Path::new("/home/dev/clients/acme/libs/ui"), Some(Path::new("/home/dev")), 10), "~/clients/…/libs/ui");
+        // Three components or fewer are never shortened
+        assert_eq!(render(Path::new("/work/clients/acme"), None, 5), "/work/clients/acme");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn shows_drive_paths() {
+        let home = Path::new(r"C:\Users\dev");
+        assert_eq!(render(Path::new(r"C:\Users\dev\src\ui"), Some(home), MAX_DISPLAY_LEN), r"~\src\ui");
+        assert_eq!(render(Path::new(r"D:\work\ui"), Some(home), MAX_DISPLAY_LEN), r"D:\work\ui");
+        assert_eq!(render(Path::new(r"D:\work\clients\acme\libs\ui"), None, 10), r"D:\work\…\libs\ui");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn shows_unc_paths() {
+        assert_eq!(render(Path::new(r"\\server\share\libs\ui"), None, MAX_DISPLAY_LEN), r"\\server\share\libs\ui");
+        assert_eq!(render(Path::new(r"\\server\share\clients\acme\libs\ui"), None, 10), r"\\server\share\clients\…\libs\ui");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn drops_verbatim_prefixes_for_display() {
+        assert_eq!(display_path(Path::new(r"\\?\D:\work\ui")), r"D:\work\ui");
+        assert_eq!(display_path(Path::new(r"\\?\UNC\server\share\ui")), r"\\server\share\ui");
+    }
+}
@@ -0,0 +1,221 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use crate::error::SpineError;
+use crate::paths::display_path;
+
+/// Access Spine needs on a path but doesn't have
+#[derive(Debug, Clone)]
+pub struct PermissionProblem {
+    pub path: PathBuf,
+    /// "read", "traverse" or "write"
+    pub access: &'static str,
+    /// Owning user, on Unix
+    pub owner: Option<String>,
+    /// Permission bits, on Unix
+    pub mode: Option<u32>,
+}
+
+impl PermissionProblem {
+    fn new(path: &Path, access: &'static str) -> Self {
+        let (owner, mode) = ownership(path);
+        Self {
+            path: path.to_path_buf(),
+            access,
+            owner,
+            mode,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let mut text = format!("no {} permission on {}", self.access, display_path(&self.path));
+        match (&self.owner, self.mode) {
+            (Some(owner), Some(mode)) => text.push_str(&format!(" (owner {}, mode {:o})", owner, mode & 0o7777)),
+            (None, Some(mode)) => text.push_str(&format!(" (mode {:o})", mode & 0o7777)),
+            _ => {}
+        }
+        text
+    }
+
+    pub fn suggestion(&self) -> String {
+        if !cfg!(unix) {
+            return format!("Check the security settings of {} and grant your account {} access",
+                self.path.display(), self.access);
+        }
+
+        match self.access {
+            "write" => {
+                let user = std::env::var("USER").unwrap_or_else(|_| "$USER".to_string());
+                format!("Run 'sudo chown -R {} {}' or 'chmod -R u+w {}'", user, self.path.display(), self.path.display())
+            }
+            _ => format!("Run 'chmod -R a+rX {}' (or ask its owner to)", self.path.display()),
+        }
+    }
+
+    pub fn into_error(self) -> SpineError {
+        SpineError::PermissionDenied {
+            message: self.describe(),
+            suggestion: self.suggestion(),
+        }
+    }
+}
+
+/// Check that a package directory can be listed and its package.json read.
+/// Missing paths are not reported here; the existence checks cover them.
+pub fn check_package(path: &Path) -> Vec<PermissionProblem> {
+    let mut problems = Vec::new();
+    if !path.exists() {
+        return problems;
+    }
+
+    if is_denied(fs::read_dir(path).map(|_| ())) {
+        problems.push(PermissionProblem::new(path, "read"));
+        return problems;
+    }
+
+    let package_json = path.join("package.json");
+    if is_denied(fs::metadata(&package_json).map(|_| ())) {
+        // Can't even stat it: the directory isn't traversable
+        problems.push(PermissionProblem::new(path, "traverse"));
+    } else if package_json.exists() && is_denied(fs::File::open(&package_json).map(|_| ())) {
+        problems.push(PermissionProblem::new(&package_json, "read"));
+    }
+
+    problems
+}
+
+/// Check that links can be created in a project: its node_modules, or the
+/// project itself when node_modules doesn't exist yet, must be writable
+pub fn check_project(project: &Path) -> Option<PermissionProblem> {
+    let node_modules = project.join("node_modules");
+    let target = if node_modules.is_dir() { node_modules } else { project.to_path_buf() };
+    if !target.is_dir() {
+        return None;
+    }
+
+    let probe = target.join(format!(".spine-write-check-{}", std::process::id()));
+    let result = fs::File::create(&probe).map(|_| ());
+    let _ = fs::remove_file(&probe);
+
+    if is_denied(result) {
+        Some(PermissionProblem::new(&target, "write"))
+    } else {
+        None
+    }
+}
+
+/// Fail fast before running the package manager, so permission problems are
+/// reported against the path at fault rather than as an npm EACCES
+pub fn ensure_linkable(package_path: &Path, project: &Path) -> anyhow::Result<()> {
+    if let Some(problem) = check_package(package_path).into_iter().next() {
+        return Err(problem.into_error().into());
+    }
+    if let Some(problem) = check_project(project) {
+        return Err(problem.into_error().into());
+    }
+    Ok(())
+}
+
+/// Refuse to link a package into its own source tree, or over the project's
+/// own installed copy of it, either of which would leave node_modules
+/// pointing back at itself. A package elsewhere inside the project, such as
+/// a workspace's dist/<lib> linked into the app at its root, is fine.
+pub fn ensure_not_self_link(package: &str, package_path: &Path, project: &Path) -> Result<(), SpineError> {
+    use crate::paths::Nesting;
+
+    let reason = match crate::paths::nesting(project, package_path) {
+        None => return Ok(()),
+        Some(Nesting::Same) => format!("the project {} is the package's own directory", display_path(project)),
+        Some(Nesting::Inside) => format!("the project {} is inside the package's source tree {}",
+            display_path(project), display_path(package_path)),
+        Some(Nesting::Contains) if is_installed_copy(package, package_path, project) => format!(
+            "the package {} is the project's own installed copy", display_path(package_path)),
+        Some(Nesting::Contains) => return Ok(()),
+    };
+    Err(SpineError::SelfLink { package: package.to_string(), reason })
+}
+
+/// Whether `package_path` is `project`'s node_modules/<npm name>, named by
+/// its package.json (or `package` without one)
+fn is_installed_copy(package: &str, package_path: &Path, project: &Path) -> bool {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| crate::paths::normalize_path(path));
+    let npm_name = crate::package::parse_package_json(&package_path.join("package.json"))
+        .map(|info| info.name)
+        .unwrap_or_else(|_| package.to_string());
+    canonical(package_path) == canonical(project).join("node_modules").join(npm_name)
+}
+
+fn is_denied(result: std::io::Result<()>) -> bool {
+    matches!(result, Err(e) if e.kind() == ErrorKind::PermissionDenied)
+}
+
+#[cfg(unix)]
+fn ownership(path: &Path) -> (Option<String>, Option<u32>) {
+    use std::os::unix::fs::MetadataExt;
+
+    match fs::metadata(path) {
+        Ok(metadata) => (Some(user_name(metadata.uid())), Some(metadata.mode())),
+        Err(_) => (None, None),
+    }
+}
+
+#[cfg(not(unix))]
+fn ownership(_path: &Path) -> (Option<String>, Option<u32>) {
+    (None, None)
+}
+
+/// Resolve a uid through /etc/passwd, falling back to the number
+#[cfg(unix)]
+fn user_name(uid: u32) -> String {
+    fs::read_to_string("/etc/passwd")
+        .ok()
+        .and_then(|passwd| {
+            passwd.lines().find_map(|line| {
+                let mut fields = line.split(':');
+                let name = fields.next()?;
+                let id = fields.nth(1)?;
+                (id.parse::<u32>().ok()? == uid).then(|| name.to_string())
+            })
+        })
+        .unwrap_or_else(|| uid.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(dir: &Path, name: &str) -> PathBuf {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("package.json"), format!(r#"{{ "name": "{}", "version": "1.0.0" }}"#, name)).unwrap();
+        dir.to_path_buf()
+    }
+
+    #[test]
+    fn allows_a_workspace_dist_linked_into_its_root_app() {
+        let dir = tempfile::tempdir().unwrap();
+        let dist = package(&dir.path().join("dist").join("ui"), "@acme/ui");
+        assert!(ensure_not_self_link("@acme/ui", &dist, dir.path()).is_ok());
+    }
+
+    #[test]
+    fn refuses_a_project_inside_or_equal_to_the_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = package(&dir.path().join("ui"), "ui");
+        let demo = library.join("demo");
+        fs::create_dir_all(&demo).unwrap();
+        assert!(matches!(ensure_not_self_link("ui", &library, &library), Err(SpineError::SelfLink { .. })));
+        assert!(matches!(ensure_not_self_link("ui", &library, &demo), Err(SpineError::SelfLink { .. })));
+    }
+
+    #[test]
+    fn refuses_the_projects_own_installed_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let installed = package(&dir.path().join("node_modules").join("@acme").join("ui"), "@acme/ui");
+        let error = ensure_not_self_link("ui", &installed, dir.path()).unwrap_err();
+        assert!(error.to_string().contains("installed copy"), "{}", error);
+
+        // Another package vendored in node_modules under its own name is fine
+        let fork = package(&dir.path().join("node_modules").join("ui-fork"), "@acme/ui");
+        assert!(ensure_not_self_link("ui", &fork, dir.path()).is_ok());
+    }
+}
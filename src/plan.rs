@@ -0,0 +1,133 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use anyhow::Result;
+use serde::Serialize;
+use crate::paths::display_path;
+
+/// One concrete change a multi-package operation will make
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanAction {
+    /// What is done, e.g. "unlink", "restore", "add"
+    pub action: String,
+    /// The package it is done to
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Everything an operation is about to change, shown for review before any of
+/// it happens and printed by `--plan` for tooling
+#[derive(Debug, Clone, Serialize)]
+pub struct Plan {
+    pub operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<PathBuf>,
+    pub actions: Vec<PlanAction>,
+    /// Execute without printing per-action status, for JSON output
+    #[serde(skip)]
+    pub quiet: bool,
+}
+
+/// How far execution got
+#[derive(Debug, Default)]
+pub struct PlanOutcome {
+    pub completed: Vec<String>,
+    pub failed: Vec<(String, String)>,
+    /// Targets never attempted, after Ctrl+C or a stop on failure
+    pub not_started: Vec<String>,
+    pub interrupted: bool,
+}
+
+impl Plan {
+    pub fn new(operation: &str, project: Option<PathBuf>) -> Self {
+        Self { operation: operation.to_string(), project, actions: Vec::new(), quiet: false }
+    }
+
+    pub fn push(&mut self, action: &str, target: &str, detail: Option<String>) {
+        self.actions.push(PlanAction { action: action.to_string(), target: target.to_string(), detail });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub fn print_json(&self) -> Result<()> {
+        println!("{}", serde_json::to_string_pretty(self)?);
+        Ok(())
+    }
+
+    /// Render as a tree of actions, each with the packages it applies to
+    pub fn render(&self) {
+        match &self.project {
+            Some(project) => println!("📋 Plan: {} in {}", self.operation, display_path(project)),
+            None => println!("📋 Plan: {}", self.operation),
+        }
+
+        let mut kinds: Vec<&str> = Vec::new();
+        for action in &self.actions {
+            if !kinds.contains(&action.action.as_str()) {
+                kinds.push(&action.action);
+            }
+        }
+        for (kind_index, kind) in kinds.iter().enumerate() {
+            let last_kind = kind_index + 1 == kinds.len();
+            let targets: Vec<&PlanAction> = self.actions.iter().filter(|a| a.action == *kind).collect();
+            println!("{} {} ({})", if last_kind { "└─" } else { "├─" }, kind, targets.len());
+            for (index, action) in targets.iter().enumerate() {
+                let branch = if index + 1 == targets.len() { "└─" } else { "├─" };
+                let detail = action.detail.as_deref().map(|d| format!("  {}", d)).unwrap_or_default();
+                println!("{}  {} {}{}", if last_kind { " " } else { "│" }, branch, action.target, detail);
+            }
+        }
+    }
+
+    /// Show the plan and ask to go ahead. `yes` and running without a
+    /// terminal skip the question.
+    pub fn confirm(&self, yes: bool) -> bool {
+        self.render();
+        yes || crate::prompt::confirm(&format!("Proceed with {} action(s)?", self.actions.len()), true)
+    }
+
+    /// Run each action in order, printing its status. Ctrl+C stops before the
+    /// next action, as does a failure with `stop_on_failure`; either way the
+    /// outcome lists what was and wasn't done.
+    pub fn execute(&self, stop_on_failure: bool, mut run: impl FnMut(&PlanAction) -> Result<(), String>) -> Result<PlanOutcome> {
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let signal = signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&interrupted))?;
+        let mut outcome = PlanOutcome::default();
+
+        for (index, action) in self.actions.iter().enumerate() {
+            if interrupted.load(Ordering::Relaxed) {
+                outcome.interrupted = true;
+            }
+            if outcome.interrupted || (stop_on_failure && !outcome.failed.is_empty()) {
+                outcome.not_started = self.actions[index..].iter().map(|a| a.target.clone()).collect();
+                break;
+            }
+
+            match run(action) {
+                Ok(()) => {
+                    if !self.quiet {
+                        println!("  ✅ {} {}", action.action, action.target);
+                    }
+                    outcome.completed.push(action.target.clone());
+                }
+                Err(error) => {
+                    if !self.quiet {
+                        println!("  ❌ {} {}: {}", action.action, action.target, error.trim());
+                    }
+                    outcome.failed.push((action.target.clone(), error));
+                }
+            }
+        }
+
+        signal_hook::low_level::unregister(signal);
+        outcome.interrupted |= interrupted.load(Ordering::Relaxed);
+        if outcome.interrupted && !self.quiet {
+            println!("\n🛑 Interrupted: {} done, {} failed, {} not started ({})",
+                outcome.completed.len(), outcome.failed.len(), outcome.not_started.len(), outcome.not_started.join(", "));
+        }
+        Ok(outcome)
+    }
+}
@@ -9,7 +9,7 @@ impl Platform {
     #[cfg(target_os = "windows")]
     pub fn get_command_name(base_name: &str) -> String {
         match base_name {
-            "npm" | "ng" | "npx" => format!("{}.cmd", base_name),
+            "npm" | "pnpm" | "yarn" | "ng" | "npx" | "nx" => format!("{}.cmd", base_name),
             _ => base_name.to_string(),
         }
     }
@@ -24,11 +24,35 @@ impl Platform {
         Command::new(Self::get_command_name("npm"))
     }
 
+    /// Create a platform-appropriate Command for pnpm
+    pub fn pnpm_command() -> Command {
+        Command::new(Self::get_command_name("pnpm"))
+    }
+
+    /// Create a platform-appropriate Command for yarn
+    pub fn yarn_command() -> Command {
+        Command::new(Self::get_command_name("yarn"))
+    }
+
     /// Create a platform-appropriate Command for Angular CLI
     pub fn ng_command() -> Command {
         Command::new(Self::get_command_name("ng"))
     }
 
+    /// Nx from the workspace's node_modules/.bin, else from PATH; None when
+    /// neither has it
+    pub fn nx_command(workspace_root: &std::path::Path) -> Option<Command> {
+        let name = Self::get_command_name("nx");
+        let local = workspace_root.join("node_modules").join(".bin").join(&name);
+        if local.is_file() {
+            return Some(Command::new(local));
+        }
+        let path = std::env::var_os("PATH")?;
+        std::env::split_paths(&path)
+            .any(|dir| dir.join(&name).is_file())
+            .then(|| Command::new(name))
+    }
+
     /// Detect the current shell in a cross-platform way
     pub fn detect_current_shell() -> Option<String> {
         #[cfg(target_os = "windows")]
@@ -93,6 +117,88 @@ impl Platform {
         }
     }
 
+    /// Show a desktop notification: a toast on Windows, Notification Center on
+    /// macOS and the freedesktop notification service elsewhere. Returns once
+    /// the notification is handed over rather than when it goes away.
+    pub fn send_notification(title: &str, body: &str) -> Result<(), notify_rust::error::Error> {
+        notify_rust::Notification::new()
+            .summary(title)
+            .body(body)
+            .show()
+            .map(|_| ())
+    }
+
+    /// When process `pid` started, in a platform-specific unit that only
+    /// needs to compare equal for the same process; None when no such
+    /// process is running. Checking it as well as the pid catches a pid
+    /// that was reused after its owner exited.
+    #[cfg(target_os = "linux")]
+    pub fn process_started(pid: u32) -> Option<u64> {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        // The command name may hold spaces and parentheses, so count fields
+        // from the last ')'; starttime is field 22 of the whole line
+        let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+        fields.get(19)?.parse().ok()
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    pub fn process_started(pid: u32) -> Option<u64> {
+        let pid = libc::pid_t::try_from(pid).ok()?;
+        // SAFETY: signal 0 only checks that the process exists
+        let alive = unsafe { libc::kill(pid, 0) } == 0
+            || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+        // No portable start time here; every live process compares equal
+        alive.then_some(0)
+    }
+
+    #[cfg(windows)]
+    pub fn process_started(pid: u32) -> Option<u64> {
+        use windows_sys::Win32::Foundation::{CloseHandle, FILETIME, STILL_ACTIVE};
+        use windows_sys::Win32::System::Threading::{GetExitCodeProcess, GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+        // SAFETY: the handle is checked before use and closed before returning
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if handle.is_null() {
+                return None;
+            }
+            let mut exit_code = 0;
+            let zero = FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 };
+            let (mut created, mut exited, mut kernel, mut user) = (zero, zero, zero, zero);
+            let running = GetExitCodeProcess(handle, &mut exit_code) != 0 && exit_code == STILL_ACTIVE as u32;
+            let timed = GetProcessTimes(handle, &mut created, &mut exited, &mut kernel, &mut user) != 0;
+            CloseHandle(handle);
+            (running && timed).then(|| (u64::from(created.dwHighDateTime) << 32) | u64::from(created.dwLowDateTime))
+        }
+    }
+
+    /// Where a shell looks for completion scripts without extra setup, per user
+    /// or system-wide. PowerShell has no such directory; its script is dot-sourced
+    /// from the profile instead.
+    pub fn completion_install_path(shell: &str, system: bool) -> Option<std::path::PathBuf> {
+        use std::path::PathBuf;
+        let home_dir = dirs::home_dir()?;
+
+        match (shell, system) {
+            ("bash", false) => {
+                let data_dir = std::env::var_os("XDG_DATA_HOME")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| home_dir.join(".local/share"));
+                Some(data_dir.join("bash-completion/completions/spine"))
+            }
+            ("bash", true) if cfg!(target_os = "macos") => Some(PathBuf::from("/usr/local/etc/bash_completion.d/spine")),
+            ("bash", true) if cfg!(unix) => Some(PathBuf::from("/usr/share/bash-completion/completions/spine")),
+            ("zsh", false) => Some(home_dir.join(".zsh/completions/_spine")),
+            ("zsh", true) if cfg!(unix) => Some(PathBuf::from("/usr/local/share/zsh/site-functions/_spine")),
+            // fish reads ~/.config on every platform, not the macOS Application Support dir
+            ("fish", false) => Some(home_dir.join(".config/fish/completions/spine.fish")),
+            ("fish", true) if cfg!(target_os = "macos") => Some(PathBuf::from("/usr/local/share/fish/vendor_completions.d/spine.fish")),
+            ("fish", true) if cfg!(unix) => Some(PathBuf::from("/usr/share/fish/vendor_completions.d/spine.fish")),
+            ("powershell", false) => Some(dirs::data_local_dir()?.join("spine/spine_completion.ps1")),
+            _ => None,
+        }
+    }
+
     /// Get platform-appropriate completion script path
     pub fn get_completion_script_path(shell: &str, home_dir: &std::path::Path) -> Option<std::path::PathBuf> {
         match shell {
@@ -117,4 +223,153 @@ impl Platform {
             _ => Some(home_dir.join(format!(".spine_completion.{}", shell))),
         }
     }
-}
\ No newline at end of file
+}
+
+/// How `run_command` treats the child's output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputMode {
+    /// Collect stdout/stderr silently
+    Capture,
+    /// Pass output straight through to the terminal (not captured)
+    Stream,
+    /// Collect output while showing a spinner with the given message
+    Progress(&'static str),
+}
+
+/// Result of a finished external command. A non-zero exit is not an error
+/// here; callers decide how to report it.
+#[derive(Debug)]
+pub struct CommandOutput {
+    pub status: std::process::ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+    pub elapsed: std::time::Duration,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+}
+
+/// Run an external command, killing it if it outlives `timeout`. Timeouts are
+/// reported as `SpineError::CommandFailed` with whatever output was captured
+/// and the elapsed time.
+pub fn run_command(
+    mut command: Command,
+    timeout: Option<std::time::Duration>,
+    mode: OutputMode,
+) -> anyhow::Result<CommandOutput> {
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+    use std::process::Stdio;
+    use std::time::{Duration, Instant};
+    use crate::error::SpineError;
+
+    let description = describe_command(&command);
+    let plain = crate::progress::is_plain();
+    if plain {
+        // Most tools honour one or the other; captured output is stripped too
+        command.env("NO_COLOR", "1").env("FORCE_COLOR", "0");
+    }
+    let capture = !matches!(mode, OutputMode::Stream);
+    if capture {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+
+    let spinner = match mode {
+        OutputMode::Progress(message) => {
+            let spinner = crate::progress::Progress::spinner(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"], "{spinner} {msg}");
+            spinner.set_message(message);
+            spinner.enable_steady_tick(Duration::from_millis(100));
+            Some(spinner)
+        }
+        _ => None,
+    };
+
+    let start = Instant::now();
+    let mut child = command.spawn().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            SpineError::command_failed_with_suggestion(&description, "command not found on PATH").into()
+        } else {
+            anyhow::Error::from(SpineError::Io(e))
+        }
+    })?;
+
+    // Drain pipes on separate threads so a chatty child can't block on a full
+    // pipe; buffers are shared so partial output is available after a timeout
+    let readers: Vec<_> = [
+        child.stdout.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+        child.stderr.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .map(|pipe| {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&buffer);
+        let handle = std::thread::spawn(move || {
+            let mut chunk = [0u8; 8192];
+            if let Some(mut pipe) = pipe {
+                while let Ok(n) = pipe.read(&mut chunk) {
+                    if n == 0 {
+                        break;
+                    }
+                    sink.lock().unwrap().extend_from_slice(&chunk[..n]);
+                }
+            }
+        });
+        (handle, buffer)
+    })
+    .collect();
+
+    let (status, timed_out) = loop {
+        if let Some(status) = child.try_wait()? {
+            break (status, false);
+        }
+        if timeout.is_some_and(|limit| start.elapsed() >= limit) {
+            let _ = child.kill();
+            break (child.wait()?, true);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    let elapsed = start.elapsed();
+    // After a kill, grandchildren may still hold the pipes open; don't wait on them
+    let mut outputs = readers.into_iter().map(|(handle, buffer)| {
+        if !timed_out {
+            let _ = handle.join();
+        }
+        let bytes = buffer.lock().unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+        if plain { crate::progress::strip_ansi(&text).into_owned() } else { text.into_owned() }
+    });
+    let stdout = outputs.next().unwrap_or_default();
+    let stderr = outputs.next().unwrap_or_default();
+
+    if let Some(spinner) = spinner {
+        spinner.finish_and_clear();
+    }
+
+    if timed_out {
+        let mut error = format!("timed out after {:.1}s and was killed", elapsed.as_secs_f64());
+        let captured = format!("{}{}", stdout, stderr);
+        if !captured.trim().is_empty() {
+            error.push_str(&format!("\n--- output ---\n{}", captured.trim_end()));
+        }
+        return Err(SpineError::CommandFailed {
+            command: description,
+            error,
+            suggestion: "Check for prompts, network or proxy problems, or raise the limit under [timeouts] in the config".to_string(),
+        }
+        .into());
+    }
+
+    Ok(CommandOutput { status, stdout, stderr, elapsed })
+}
+
+fn describe_command(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
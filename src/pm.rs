@@ -0,0 +1,495 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::error::SpineError;
+use crate::platform::{run_command, OutputMode, Platform};
+
+/// Package manager used to create and remove links
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    #[default]
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+/// Lockfiles that identify each package manager
+pub const LOCKFILES: &[(&str, PackageManager)] = &[
+    ("package-lock.json", PackageManager::Npm),
+    ("npm-shrinkwrap.json", PackageManager::Npm),
+    ("pnpm-lock.yaml", PackageManager::Pnpm),
+    ("yarn.lock", PackageManager::Yarn),
+    ("bun.lockb", PackageManager::Bun),
+    ("bun.lock", PackageManager::Bun),
+];
+
+/// Files Yarn Plug'n'Play writes in place of a node_modules folder
+pub const PNP_FILES: [&str; 3] = [".pnp.cjs", ".pnp.js", ".pnp.data.json"];
+
+/// What status shows for a Plug'n'Play project in place of link states
+pub const PNP_STATUS: &str = "PnP project — npm link not supported; use the tsconfig or durable override modes";
+
+/// Result of inspecting a project for its package manager
+#[derive(Debug, Clone)]
+pub struct Detection {
+    pub manager: PackageManager,
+    /// What the decision was based on, e.g. "pnpm-lock.yaml" or "packageManager: pnpm@8.6.0"
+    pub evidence: Vec<String>,
+    /// Lockfiles from different managers when neither wins; detection fell back to npm
+    pub conflicting: Vec<String>,
+    /// Nothing in the project named a manager, so npm was assumed
+    pub defaulted: bool,
+}
+
+impl Detection {
+    pub fn is_ambiguous(&self) -> bool {
+        !self.conflicting.is_empty()
+    }
+
+    /// What to tell the user when detection was ambiguous
+    pub fn warning(&self) -> Option<String> {
+        self.is_ambiguous().then(|| format!(
+            "Conflicting lockfiles found ({}); falling back to npm. Set \"packageManager\" in package.json or use --pm.",
+            self.conflicting.join(", ")))
+    }
+
+    /// Mention that a Yarn 1 project links with npm (see `PackageManager::linker`)
+    fn noting_yarn_classic(mut self, project_dir: &Path) -> Self {
+        if self.manager == PackageManager::Yarn && is_yarn_classic(project_dir) {
+            self.evidence.push("Yarn 1, so links go through npm".to_string());
+        }
+        self
+    }
+
+    pub fn print_warning(&self) {
+        if let Some(warning) = self.warning() {
+            eprintln!("⚠️  {}", warning);
+        }
+    }
+}
+
+impl PackageManager {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::Bun => "bun",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "npm" => Some(PackageManager::Npm),
+            "pnpm" => Some(PackageManager::Pnpm),
+            "yarn" => Some(PackageManager::Yarn),
+            "bun" => Some(PackageManager::Bun),
+            _ => None,
+        }
+    }
+
+    pub fn command(&self) -> Command {
+        match self {
+            PackageManager::Npm => Platform::npm_command(),
+            PackageManager::Pnpm => Platform::pnpm_command(),
+            PackageManager::Yarn => Platform::yarn_command(),
+            PackageManager::Bun => Command::new("bun"),
+        }
+    }
+
+    /// Work out which package manager a project uses. The `packageManager`
+    /// field of package.json wins; otherwise a single kind of lockfile decides.
+    /// Mixed lockfiles are ambiguous and fall back to npm.
+    pub fn detect(project_dir: &Path) -> Detection {
+        let declared = std::fs::read_to_string(project_dir.join("package.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|json| json.get("packageManager")?.as_str().map(|s| s.to_string()));
+
+        if let Some(declared) = declared {
+            let name = declared.split('@').next().unwrap_or_default();
+            if let Some(manager) = Self::from_name(name) {
+                return Detection {
+                    manager,
+                    evidence: vec![format!("packageManager: {}", declared)],
+                    conflicting: Vec::new(),
+                    defaulted: false,
+                }.noting_yarn_classic(project_dir);
+            }
+        }
+
+        let found: Vec<(&str, PackageManager)> = LOCKFILES
+            .iter()
+            .filter(|(file, _)| project_dir.join(file).exists())
+            .copied()
+            .collect();
+        let evidence: Vec<String> = found.iter().map(|(file, _)| file.to_string()).collect();
+
+        let detection = match found.first() {
+            None => Detection {
+                manager: PackageManager::Npm,
+                evidence: vec!["no lockfile or packageManager field (default)".to_string()],
+                conflicting: Vec::new(),
+                defaulted: true,
+            },
+            Some((_, first)) if found.iter().all(|(_, m)| m == first) => Detection {
+                manager: *first,
+                evidence,
+                conflicting: Vec::new(),
+                defaulted: false,
+            },
+            Some(_) => Detection {
+                manager: PackageManager::Npm,
+                evidence: evidence.clone(),
+                conflicting: evidence,
+                defaulted: false,
+            },
+        };
+        detection.noting_yarn_classic(project_dir)
+    }
+
+    /// The manager to use for a project: an explicit --pm, then the configured
+    /// `package_manager`, then detection (warning when it is ambiguous)
+    pub fn resolve(explicit: Option<PackageManager>, configured: Option<PackageManager>, project_dir: &Path) -> ManagerChoice {
+        let (choice, warning) = Self::resolve_quietly(explicit, configured, project_dir);
+        if let Some(warning) = warning {
+            eprintln!("⚠️  {}", warning);
+        }
+        choice
+    }
+
+    /// `resolve`, handing back the ambiguity warning instead of printing it,
+    /// for the TUI to show in its own way
+    pub fn resolve_quietly(explicit: Option<PackageManager>, configured: Option<PackageManager>, project_dir: &Path) -> (ManagerChoice, Option<String>) {
+        if let Some(manager) = explicit {
+            return (ManagerChoice { manager, explicit: true }, None);
+        }
+        if let Some(manager) = configured {
+            return (ManagerChoice { manager, explicit: false }, None);
+        }
+        let detection = Self::detect(project_dir);
+        (ManagerChoice { manager: detection.manager, explicit: false }, detection.warning())
+    }
+
+    /// Link the package at `package_path` into `project`. pnpm links the
+    /// directory straight into node_modules instead of going through npm's
+    /// global prefix, which keeps its content-addressable store untouched.
+    pub fn link_in(&self, project: &Path, package_path: &Path, timeout: Option<Duration>) -> Result<()> {
+        ensure_not_pnp(project)?;
+        let linker = self.linker(project);
+        let mut command = linker.command();
+        command.current_dir(project).args(["link", &package_path.to_string_lossy()]);
+        let output = run_command(command, timeout, OutputMode::Capture)?;
+
+        if !output.success() {
+            return Err(SpineError::Config(format!("{} link failed: {}", linker.name(), output.stderr)).into());
+        }
+
+        Ok(())
+    }
+
+    /// Link several packages into `project` at once. npm and pnpm take them all
+    /// in a single invocation, so dependencies are only resolved once; yarn and
+    /// bun link one at a time.
+    pub fn link_many_in(&self, project: &Path, package_paths: &[&Path], timeout: Option<Duration>) -> Result<()> {
+        ensure_not_pnp(project)?;
+        let linker = self.linker(project);
+        if !linker.links_many_at_once() {
+            for path in package_paths {
+                linker.link_in(project, path, timeout)?;
+            }
+            return Ok(());
+        }
+
+        let mut command = linker.command();
+        command.current_dir(project).arg("link").args(package_paths);
+        let output = run_command(command, timeout, OutputMode::Capture)?;
+
+        if !output.success() {
+            return Err(SpineError::Config(format!("{} link failed: {}", linker.name(), output.stderr)).into());
+        }
+
+        Ok(())
+    }
+
+    /// Remove several package links from `project`
+    pub fn unlink_many_in(&self, project: &Path, package_names: &[&str], timeout: Option<Duration>) -> Result<()> {
+        ensure_not_pnp(project)?;
+        let linker = self.linker(project);
+        if !linker.links_many_at_once() {
+            for name in package_names {
+                linker.unlink_in(project, name, timeout)?;
+            }
+            return Ok(());
+        }
+
+        let mut command = linker.command();
+        command.current_dir(project).arg("unlink").args(package_names);
+        let output = run_command(command, timeout, OutputMode::Capture)?;
+
+        if !output.success() {
+            return Err(SpineError::Config(format!("{} unlink failed: {}", linker.name(), output.stderr)).into());
+        }
+
+        Ok(())
+    }
+
+    fn links_many_at_once(&self) -> bool {
+        matches!(self, PackageManager::Npm | PackageManager::Pnpm)
+    }
+
+    /// The manager that links and unlinks in `project`: npm stands in for
+    /// Yarn 1, whose `yarn link` only takes registered names, not paths
+    fn linker(&self, project: &Path) -> Self {
+        if *self == PackageManager::Yarn && is_yarn_classic(project) {
+            PackageManager::Npm
+        } else {
+            *self
+        }
+    }
+
+    /// Install a package spec such as `lodash@^4.17.0` into `project`, saved
+    /// as a devDependency when `dev` is set
+    pub fn install_in(&self, project: &Path, spec: &str, dev: bool, timeout: Option<Duration>) -> Result<()> {
+        let mut command = self.command();
+        let verb = if *self == PackageManager::Npm { "install" } else { "add" };
+        command.current_dir(project).args([verb, spec]);
+        if dev {
+            command.arg(match self {
+                PackageManager::Npm | PackageManager::Pnpm => "--save-dev",
+                PackageManager::Yarn | PackageManager::Bun => "--dev",
+            });
+        }
+        let output = run_command(command, timeout, OutputMode::Capture)?;
+
+        if !output.success() {
+            return Err(SpineError::Config(format!("{} {} failed: {}", self.name(), verb, output.stderr)).into());
+        }
+
+        Ok(())
+    }
+
+    /// Install a project's dependencies as declared in its package.json
+    pub fn install_project(&self, project: &Path, timeout: Option<Duration>) -> Result<()> {
+        let mut command = self.command();
+        command.current_dir(project).arg("install");
+        let output = run_command(command, timeout, OutputMode::Capture)?;
+
+        if !output.success() {
+            return Err(SpineError::Config(format!("{} install failed: {}", self.name(), output.stderr)).into());
+        }
+
+        Ok(())
+    }
+
+    /// Remove a package link from `project`
+    pub fn unlink_in(&self, project: &Path, package_name: &str, timeout: Option<Duration>) -> Result<()> {
+        ensure_not_pnp(project)?;
+        let linker = self.linker(project);
+        let mut command = linker.command();
+        command.current_dir(project).args(["unlink", package_name]);
+        let output = run_command(command, timeout, OutputMode::Capture)?;
+
+        if !output.success() {
+            return Err(SpineError::Config(format!("{} unlink failed: {}", linker.name(), output.stderr)).into());
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for PackageManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The manager a command runs with, from `PackageManager::resolve`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManagerChoice {
+    pub manager: PackageManager,
+    /// Given with --pm, which beats the managers links set for themselves
+    pub explicit: bool,
+}
+
+impl ManagerChoice {
+    /// The manager for one link: its own setting for the project, unless
+    /// --pm was given, then the command's
+    pub fn for_link(&self, link_setting: Option<PackageManager>) -> PackageManager {
+        match link_setting {
+            Some(setting) if !self.explicit => setting,
+            _ => self.manager,
+        }
+    }
+}
+
+impl std::fmt::Display for ManagerChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.manager.fmt(f)
+    }
+}
+
+/// Whether a node_modules entry resolves into pnpm's virtual store, i.e. it is a
+/// regular pnpm install rather than a link to a local package
+pub fn is_pnpm_store_entry(project_path: &Path, entry: &Path) -> bool {
+    let store = project_path.join("node_modules").join(".pnpm");
+    match (entry.canonicalize(), store.canonicalize()) {
+        (Ok(target), Ok(store)) => target.starts_with(store),
+        _ => false,
+    }
+}
+
+/// Whether `project` installs with Yarn Plug'n'Play, which has no
+/// node_modules for a symlink to go into
+pub fn is_pnp_project(project: &Path) -> bool {
+    PNP_FILES.iter().any(|file| project.join(file).is_file())
+}
+
+/// Whether `project` uses Yarn 1, by its `packageManager` field or else the
+/// header of its yarn.lock
+pub fn is_yarn_classic(project: &Path) -> bool {
+    let declared = std::fs::read_to_string(project.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|json| json.get("packageManager")?.as_str().map(|s| s.to_string()));
+    if let Some(version) = declared.as_deref().and_then(|declared| declared.strip_prefix("yarn@")) {
+        return version.starts_with("1.");
+    }
+    std::fs::read_to_string(project.join("yarn.lock")).is_ok_and(|lock| lock.contains("# yarn lockfile v1"))
+}
+
+/// Refuse symlink-based linking in a Plug'n'Play project, where `link`
+/// would write a node_modules that Yarn then ignores or removes
+pub fn ensure_not_pnp(project: &Path) -> Result<(), SpineError> {
+    if !is_pnp_project(project) {
+        return Ok(());
+    }
+    Err(SpineError::Config(format!(
+        "{} is a Yarn Plug'n'Play project with no node_modules to link into. \
+         Link through a package.json override instead ('spine link --durable <package>', a link: resolution under Yarn), \
+         or map the package in tsconfig.json \"paths\"",
+        crate::paths::display_path(project)
+    )))
+}
+
+/// How a Plug'n'Play project resolves one package
+#[derive(Debug, Clone)]
+pub struct PnpResolution {
+    /// The locator's reference, e.g. `npm:1.2.3` or `portal:../ui::locator=...`
+    pub reference: String,
+    /// Where a portal:, link: or file: reference points, resolved from the project
+    pub target: Option<PathBuf>,
+}
+
+impl PnpResolution {
+    fn new(project: &Path, reference: &str) -> Self {
+        let target = ["portal:", "link:", "file:"].iter()
+            .find_map(|protocol| reference.strip_prefix(protocol))
+            .map(|path| project.join(path.split("::").next().unwrap_or(path)));
+        Self { reference: reference.to_string(), target }
+    }
+
+    /// Resolved to the package at `path`
+    pub fn points_to(&self, path: &Path) -> bool {
+        self.target.as_deref().is_some_and(|target| {
+            crate::paths::same_path(target, path) || matches!((target.canonicalize(), path.canonicalize()), (Ok(a), Ok(b)) if a == b)
+        })
+    }
+}
+
+/// How `project` resolves `npm_name` under Plug'n'Play: read from
+/// .pnp.data.json when Yarn writes one, otherwise asked of `yarn why`
+pub fn pnp_resolution(project: &Path, npm_name: &str, timeout: Option<Duration>) -> Option<PnpResolution> {
+    if let Ok(content) = std::fs::read_to_string(project.join(".pnp.data.json")) {
+        let data: serde_json::Value = serde_json::from_str(&content).ok()?;
+        // [[name, [[reference, { packageLocation, ... }], ...]], ...]
+        let registry = data.get("packageRegistryData")?.as_array()?;
+        let entries = registry.iter().find(|entry| entry.get(0).and_then(|name| name.as_str()) == Some(npm_name))?;
+        let reference = entries.get(1)?.as_array()?.first()?.get(0)?.as_str()?;
+        return Some(PnpResolution::new(project, reference));
+    }
+
+    let mut command = PackageManager::Yarn.command();
+    command.current_dir(project).args(["why", npm_name]);
+    let output = run_command(command, timeout, OutputMode::Capture).ok().filter(|output| output.success())?;
+    parse_yarn_why(&output.stdout, npm_name).map(|reference| PnpResolution::new(project, reference))
+}
+
+/// The first reference `yarn why` shows for `npm_name`, from tree lines such as
+/// "└─ @acme/ui@portal:../ui::locator=app%40workspace%3A. (via portal:../ui)"
+pub fn parse_yarn_why<'a>(stdout: &'a str, npm_name: &str) -> Option<&'a str> {
+    let prefix = format!("{}@", npm_name);
+    stdout.split_whitespace()
+        .find_map(|word| word.strip_prefix(&prefix).filter(|reference| !reference.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output recorded from npm and Yarn, kept under tests/fixtures/npm
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("npm").join(name)
+    }
+
+    #[test]
+    fn reads_the_first_reference_from_yarn_why() {
+        let output = std::fs::read_to_string(fixture("yarn-why-portal.txt")).unwrap();
+        assert_eq!(parse_yarn_why(&output, "@acme/ui"), Some("portal:../ui::locator=app%40workspace%3A."));
+        assert_eq!(parse_yarn_why(&output, "@acme/forms"), Some("npm:3.1.0"));
+        assert_eq!(parse_yarn_why(&output, "@acme/u"), None);
+    }
+
+    #[test]
+    fn resolves_portals_from_pnp_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("app");
+        std::fs::create_dir_all(dir.path().join("ui")).unwrap();
+        std::fs::create_dir(&project).unwrap();
+        std::fs::copy(fixture("pnp-data.json"), project.join(".pnp.data.json")).unwrap();
+
+        let portal = pnp_resolution(&project, "@acme/ui", None).expect("a resolution");
+        assert_eq!(portal.reference, "portal:../ui::locator=app%40workspace%3A.");
+        assert!(portal.points_to(&dir.path().join("ui")));
+
+        let registry = pnp_resolution(&project, "left-pad", None).expect("a resolution");
+        assert_eq!(registry.reference, "npm:1.3.0");
+        assert!(registry.target.is_none());
+        assert!(pnp_resolution(&project, "missing", None).is_none());
+    }
+
+    #[test]
+    fn yarn_classic_links_with_npm() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path();
+        std::fs::write(project.join("package.json"), "{}").unwrap();
+        std::fs::write(project.join("yarn.lock"), "# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.\n# yarn lockfile v1\n").unwrap();
+        assert_eq!(PackageManager::Yarn.linker(project), PackageManager::Npm);
+        assert!(PackageManager::detect(project).evidence.iter().any(|evidence| evidence.contains("through npm")));
+
+        // packageManager beats the lockfile
+        std::fs::write(project.join("package.json"), r#"{ "packageManager": "yarn@4.1.0" }"#).unwrap();
+        assert_eq!(PackageManager::Yarn.linker(project), PackageManager::Yarn);
+        std::fs::write(project.join("package.json"), r#"{ "packageManager": "yarn@1.22.19" }"#).unwrap();
+        std::fs::write(project.join("yarn.lock"), "__metadata:\n  version: 8\n").unwrap();
+        assert_eq!(PackageManager::Yarn.linker(project), PackageManager::Npm);
+        assert_eq!(PackageManager::Pnpm.linker(project), PackageManager::Pnpm);
+    }
+
+    #[test]
+    fn only_an_explicit_choice_beats_a_links_own_manager() {
+        let dir = tempfile::tempdir().unwrap();
+        let explicit = PackageManager::resolve(Some(PackageManager::Pnpm), Some(PackageManager::Yarn), dir.path());
+        assert_eq!(explicit, ManagerChoice { manager: PackageManager::Pnpm, explicit: true });
+        assert_eq!(explicit.for_link(Some(PackageManager::Bun)), PackageManager::Pnpm);
+
+        let configured = PackageManager::resolve(None, Some(PackageManager::Yarn), dir.path());
+        assert_eq!(configured, ManagerChoice { manager: PackageManager::Yarn, explicit: false });
+        assert_eq!(configured.for_link(Some(PackageManager::Bun)), PackageManager::Bun);
+        assert_eq!(configured.for_link(None), PackageManager::Yarn);
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs;
+use anyhow::Result;
+use crate::config::{validate_profile_name, Config, ConfigFile, DEFAULT_PROFILE};
+use crate::error::SpineError;
+use crate::paths::display_path;
+
+/// config.toml on its own, or a fresh config before the first save
+fn load_main(file: &ConfigFile) -> Result<Config> {
+    if file.path()?.exists() {
+        Config::load_main(file)
+    } else {
+        Ok(Config::empty(file))
+    }
+}
+
+/// Names of the profile files, sorted, without "default"
+fn profile_names(file: &ConfigFile) -> Result<Vec<String>> {
+    let Ok(entries) = fs::read_dir(file.profiles_dir()?) else { return Ok(Vec::new()) };
+    let mut names: Vec<String> = entries.flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            (path.extension()? == "toml").then(|| path.file_stem()?.to_str().map(str::to_string))?
+        })
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+fn exists(file: &ConfigFile, name: &str) -> Result<bool> {
+    Ok(name == DEFAULT_PROFILE || file.profile_path(name)?.exists())
+}
+
+/// `spine profile create <name>`: an empty set of links
+pub fn create_profile(file: &ConfigFile, name: &str) -> Result<()> {
+    validate_profile_name(name)?;
+    if exists(file, name)? {
+        return Err(SpineError::Config(format!("Profile '{}' already exists", name)).into());
+    }
+
+    let path = file.profile_path(name)?;
+    fs::create_dir_all(file.profiles_dir()?)?;
+    fs::write(&path, "[links]\n")?;
+    println!("✅ Created profile '{}' at {}", name, display_path(&path));
+    println!("💡 'spine profile use {}' switches to it, or set SPINE_PROFILE={} for one shell", name, name);
+    Ok(())
+}
+
+/// `spine profile use <name>`: record the profile in config.toml, warning
+/// about packages of the profile being left that are still linked here
+pub fn use_profile(file: &ConfigFile, name: &str) -> Result<()> {
+    validate_profile_name(name)?;
+    if !exists(file, name)? {
+        return Err(SpineError::Config(format!(
+            "Profile '{}' does not exist. Create it with 'spine profile create {}'.", name, name
+        )).into());
+    }
+
+    let mut main = load_main(file)?;
+    if main.active_profile.as_deref().unwrap_or(DEFAULT_PROFILE) == name {
+        println!("✓ Already using profile '{}'", name);
+        return Ok(());
+    }
+    let previous = main.active_profile_name().unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    warn_still_linked(file, &previous, name);
+
+    main.active_profile = (name != DEFAULT_PROFILE).then(|| name.to_string());
+    main.save()?;
+    println!("✅ Switched from profile '{}' to '{}'", previous, name);
+    if let Ok(session) = std::env::var("SPINE_PROFILE") {
+        if !session.is_empty() && session != name {
+            println!("⚠️  SPINE_PROFILE={} is set, so this shell keeps using '{}'", session, session);
+        }
+    }
+    Ok(())
+}
+
+/// Packages of `previous` linked into the current project stay linked after
+/// a switch, but `next` won't know about them
+fn warn_still_linked(file: &ConfigFile, previous: &str, next: &str) {
+    let Ok(project) = std::env::current_dir() else { return };
+    let Ok(config) = Config::load(file) else { return };
+    let next_links = if next == DEFAULT_PROFILE {
+        Config::load_main(file).map(|main| main.links).unwrap_or_default()
+    } else {
+        file.profile_path(next).ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| Config::from_toml_lenient(&content).ok())
+            .map(|profile| profile.links)
+            .unwrap_or_default()
+    };
+
+    let mut still_linked: Vec<&str> = config.links.values()
+        .filter(|link| !next_links.contains_key(&link.name))
+        .filter(|link| Config::is_package_linked_in_project_static(link.npm_name(), &project))
+        .map(|link| link.name.as_str())
+        .collect();
+    if still_linked.is_empty() {
+        return;
+    }
+    still_linked.sort();
+    println!("⚠️  Still linked into {} from profile '{}': {}", display_path(&project), previous, still_linked.join(", "));
+    println!("   '{}' doesn't manage them; unlink them first with 'SPINE_PROFILE={} spine unlink-all' if they should go.", next, previous);
+}
+
+/// `spine profile list`
+pub fn list_profiles(file: &ConfigFile) -> Result<()> {
+    let main = load_main(file)?;
+    let active = main.active_profile_name().unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+
+    println!("Profiles:");
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+    names.extend(profile_names(file)?);
+    for name in &names {
+        let count = if name == DEFAULT_PROFILE {
+            Some(main.links.len())
+        } else {
+            file.profile_path(name).ok()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|content| Config::from_toml_lenient(&content).ok())
+                .map(|profile| profile.links.len())
+        };
+        let count = count.map(|count| format!("{} link(s)", count)).unwrap_or_else(|| "unreadable".to_string());
+        let marker = if *name == active { "▶" } else { " " };
+        println!("  {} {} ({})", marker, name, count);
+    }
+    if let Ok(session) = std::env::var("SPINE_PROFILE") {
+        if !session.is_empty() {
+            println!("\nSPINE_PROFILE selects '{}' in this shell", session);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use std::borrow::Cow;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// How progress is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Spinners and bars in a terminal
+    #[default]
+    Pretty,
+    /// One line per step without animation. Escape sequences are stripped
+    /// from tool output and leading emoji from progress lines.
+    Plain,
+}
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+static NO_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+/// Apply --log-format and --no-progress for the rest of the process
+pub fn configure(format: LogFormat, no_progress: bool) {
+    PLAIN.store(format == LogFormat::Plain, Ordering::Relaxed);
+    NO_PROGRESS.store(no_progress, Ordering::Relaxed);
+}
+
+/// Whether --log-format plain is in effect
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// Whether bars may animate. Wrappers that allocate a pty still look like a
+/// terminal, which is what the flags are for.
+fn animated() -> bool {
+    !PLAIN.load(Ordering::Relaxed) && !NO_PROGRESS.load(Ordering::Relaxed) && std::io::stderr().is_terminal()
+}
+
+/// A spinner or bar that falls back to printing each new message as a line
+/// on stderr when animation is off
+pub struct Progress {
+    bar: Option<ProgressBar>,
+    last_message: Mutex<String>,
+}
+
+impl Progress {
+    pub fn spinner(tick_strings: &[&str], template: &str) -> Self {
+        Self::with_bar(|| {
+            let bar = ProgressBar::new_spinner();
+            bar.set_style(ProgressStyle::default_spinner().tick_strings(tick_strings).template(template).unwrap());
+            bar
+        })
+    }
+
+    pub fn bar(len: u64, template: &str, progress_chars: &str) -> Self {
+        Self::with_bar(|| {
+            let bar = ProgressBar::new(len);
+            bar.set_style(ProgressStyle::default_bar().template(template).unwrap().progress_chars(progress_chars));
+            bar
+        })
+    }
+
+    fn with_bar(make: impl FnOnce() -> ProgressBar) -> Self {
+        Self {
+            bar: animated().then(make),
+            last_message: Mutex::new(String::new()),
+        }
+    }
+
+    pub fn enable_steady_tick(&self, interval: Duration) {
+        if let Some(bar) = &self.bar {
+            bar.enable_steady_tick(interval);
+        }
+    }
+
+    pub fn set_message(&self, message: impl Into<Cow<'static, str>>) {
+        let message = message.into();
+        match &self.bar {
+            Some(bar) => bar.set_message(message),
+            None => self.print_line(&message),
+        }
+    }
+
+    pub fn inc(&self, delta: u64) {
+        if let Some(bar) = &self.bar {
+            bar.inc(delta);
+        }
+    }
+
+    /// Print above the bar without disturbing it
+    pub fn println(&self, line: impl AsRef<str>) {
+        match &self.bar {
+            Some(bar) => bar.println(line),
+            None => eprintln!("{}", decorate(line.as_ref())),
+        }
+    }
+
+    /// Run `f` with the bar hidden, for output that must stay on stdout
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        match &self.bar {
+            Some(bar) => bar.suspend(f),
+            None => f(),
+        }
+    }
+
+    pub fn finish_with_message(&self, message: impl Into<Cow<'static, str>>) {
+        let message = message.into();
+        match &self.bar {
+            Some(bar) => bar.finish_with_message(message),
+            None => self.print_line(&message),
+        }
+    }
+
+    pub fn finish_and_clear(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+
+    /// Messages are often re-set while waiting; only print the changes
+    fn print_line(&self, message: &str) {
+        let mut last = self.last_message.lock().unwrap();
+        if *last != message {
+            eprintln!("{}", decorate(message));
+            *last = message.to_string();
+        }
+    }
+}
+
+/// `text` without ANSI escape sequences: CSI sequences (colors, cursor
+/// movement), OSC sequences (titles, hyperlinks) and two-character escapes
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains('\x1b') {
+        return Cow::Borrowed(text);
+    }
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            stripped.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Parameters and intermediates up to a final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // Ended by BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Cow::Owned(stripped)
+}
+
+/// A message as it should appear in line output: plain drops leading emoji
+fn decorate(message: &str) -> &str {
+    if is_plain() {
+        message.trim_start_matches(|c: char| !c.is_ascii() || c.is_whitespace())
+    } else {
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_color_and_cursor_sequences() {
+        assert_eq!(strip_ansi("\x1b[1m\x1b[32m✔\x1b[39m\x1b[22m Built \x1b[38;5;208mui\x1b[0m"), "✔ Built ui");
+        assert_eq!(strip_ansi("50%\x1b[2K\x1b[1G100%"), "50%100%");
+        assert_eq!(strip_ansi("\x1b[?25lhidden cursor\x1b[?25h"), "hidden cursor");
+    }
+
+    #[test]
+    fn strips_osc_and_short_escapes() {
+        assert_eq!(strip_ansi("\x1b]8;;https://angular.dev\x07docs\x1b]8;;\x07"), "docs");
+        assert_eq!(strip_ansi("\x1b]0;title\x1b\\done"), "done");
+        assert_eq!(strip_ansi("a\x1b7b\x1b8c"), "abc");
+        assert_eq!(strip_ansi("cut off \x1b["), "cut off ");
+    }
+
+    #[test]
+    fn leaves_plain_text_borrowed() {
+        assert!(matches!(strip_ansi("[ok] 100% done"), Cow::Borrowed("[ok] 100% done")));
+    }
+}
@@ -0,0 +1,58 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set when stdin carries something other than answers, such as API requests
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Never read answers from stdin for the rest of the process
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+fn can_ask() -> bool {
+    !DISABLED.load(Ordering::Relaxed) && io::stdin().is_terminal()
+}
+
+/// Ask a yes/no question on the terminal. Without a terminal on stdin the
+/// default is returned straight away, so scripts and CI never block.
+pub fn confirm(question: &str, default: bool) -> bool {
+    if !can_ask() {
+        return default;
+    }
+
+    print!("{} {} ", question, if default { "[Y/n]" } else { "[y/N]" });
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return default;
+    }
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    }
+}
+
+/// Ask the user to pick one of `options` by number. Returns None without a
+/// terminal on stdin, or when the answer is empty or out of range.
+pub fn choose(question: &str, options: &[String]) -> Option<usize> {
+    if !can_ask() {
+        return None;
+    }
+
+    println!("{}", question);
+    for (index, option) in options.iter().enumerate() {
+        println!("  {}) {}", index + 1, option);
+    }
+    print!("Choice [1-{}, Enter to skip]: ", options.len());
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return None;
+    }
+    answer.trim().parse::<usize>().ok()
+        .filter(|choice| (1..=options.len()).contains(choice))
+        .map(|choice| choice - 1)
+}
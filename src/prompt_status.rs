@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde::Serialize;
+use crate::config::{Config, ConfigFile};
+use crate::paths::{node_modules_path, same_path};
+
+/// Output format of `spine prompt-status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum PromptFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Link counts for a project, cheap enough to compute on every shell prompt
+#[derive(Debug, Default, Serialize)]
+pub struct PromptStatus {
+    /// Configured packages with a working symlink in node_modules
+    pub linked: usize,
+    /// Dangling symlinks, and links recorded for the project that are gone
+    pub broken: usize,
+}
+
+impl PromptStatus {
+    /// Only stats node_modules entries of configured packages. Unlike `spine
+    /// status` this never parses package.json files, detects workspaces or
+    /// runs the package manager.
+    pub fn collect(config: &Config, project: &Path) -> Self {
+        let mut status = Self::default();
+
+        for link in config.links.values().filter(|link| !link.pending) {
+            let installed = node_modules_path(project, link.npm_name());
+            let is_symlink = fs::symlink_metadata(&installed).is_ok_and(|m| m.file_type().is_symlink());
+
+            if is_symlink && !crate::pm::is_pnpm_store_entry(project, &installed) {
+                if installed.exists() {
+                    status.linked += 1;
+                } else {
+                    status.broken += 1;
+                }
+            } else if link.is_durable_in(project) && installed.exists() {
+                // Durable links may be installed as a copy rather than a symlink
+                status.linked += 1;
+            } else if link.linked_projects.iter().any(|p| same_path(p, project)) {
+                status.broken += 1;
+            }
+        }
+
+        status
+    }
+}
+
+/// The nearest directory at or above `dir` with a package.json
+fn project_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join("package.json").is_file())
+        .map(|ancestor| ancestor.to_path_buf())
+}
+
+pub fn prompt_status_command(config_file: &ConfigFile, format: PromptFormat, porcelain: bool) -> Result<()> {
+    let project = match project_root(&std::env::current_dir()?) {
+        Some(project) => project,
+        None => return print_status(&PromptStatus::default(), format, porcelain),
+    };
+
+    // Don't create a config just to report that nothing is linked
+    let status = if config_file.path()?.exists() {
+        PromptStatus::collect(&Config::load(config_file)?, &project)
+    } else {
+        PromptStatus::default()
+    };
+    print_status(&status, format, porcelain)
+}
+
+fn print_status(status: &PromptStatus, format: PromptFormat, porcelain: bool) -> Result<()> {
+    if porcelain {
+        // Stable: two space-separated counts, linked then broken
+        println!("{} {}", status.linked, status.broken);
+        return Ok(());
+    }
+
+    match format {
+        PromptFormat::Text => println!("linked={} broken={}", status.linked, status.broken),
+        PromptFormat::Json => println!("{}", serde_json::to_string(status)?),
+    }
+    Ok(())
+}
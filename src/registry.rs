@@ -0,0 +1,238 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use anyhow::Result;
+use serde::Serialize;
+use crate::config::Config;
+use crate::error::SpineError;
+use crate::platform::{run_command, OutputMode, Platform};
+
+/// Versions and dist-tags already fetched by this process, by registry and
+/// package name, so several checks of one package ask npm once. None records
+/// a name with nothing published.
+static PACKUMENTS: Mutex<Option<HashMap<PackumentKey, Option<Packument>>>> = Mutex::new(None);
+
+/// The registry (or the directory whose .npmrc picks it) and the package name
+type PackumentKey = (String, String);
+
+/// What the registry publishes under a package name
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Packument {
+    pub versions: Vec<String>,
+    pub dist_tags: BTreeMap<String, String>,
+}
+
+/// The `dist` block of one published version
+#[derive(Debug, Clone, Serialize)]
+pub struct TarballManifest {
+    pub tarball: String,
+    pub shasum: Option<String>,
+    pub integrity: Option<String>,
+    pub file_count: Option<u64>,
+    pub unpacked_size: Option<u64>,
+}
+
+/// Questions to the registry, asked through `npm view --json` so npm's own
+/// .npmrc handling (scopes, auth tokens) applies. Runs in `dir`, whose
+/// .npmrc files are read; `registry` overrides npm's choice.
+#[derive(Debug, Clone)]
+pub struct Registry {
+    dir: PathBuf,
+    registry: Option<String>,
+    timeout: Option<Duration>,
+}
+
+impl Registry {
+    pub fn new(config: &Config, dir: &Path) -> Self {
+        Self { dir: dir.to_path_buf(), registry: None, timeout: config.timeouts.registry() }
+    }
+
+    /// Ask this registry instead of the one npm would pick in `dir`
+    pub fn with_registry(mut self, registry: impl Into<String>) -> Self {
+        self.registry = Some(registry.into());
+        self
+    }
+
+    /// Versions and dist-tags of `name`; None when nothing is published under it
+    pub fn packument(&self, name: &str) -> Result<Option<Packument>> {
+        let key = (self.registry.clone().unwrap_or_else(|| self.dir.to_string_lossy().to_string()), name.to_string());
+        if let Some(cached) = PACKUMENTS.lock().ok().and_then(|cache| cache.as_ref()?.get(&key).cloned()) {
+            return Ok(cached);
+        }
+
+        let packument = self.view(&[name, "versions", "dist-tags"])?.map(|view| parse_packument(&view));
+        if let Ok(mut cache) = PACKUMENTS.lock() {
+            cache.get_or_insert_with(HashMap::new).insert(key, packument.clone());
+        }
+        Ok(packument)
+    }
+
+    pub fn latest_version(&self, name: &str) -> Result<Option<String>> {
+        Ok(self.packument(name)?.and_then(|packument| packument.dist_tags.get("latest").cloned()))
+    }
+
+    pub fn dist_tags(&self, name: &str) -> Result<BTreeMap<String, String>> {
+        Ok(self.packument(name)?.map(|packument| packument.dist_tags).unwrap_or_default())
+    }
+
+    pub fn published_versions(&self, name: &str) -> Result<Vec<String>> {
+        Ok(self.packument(name)?.map(|packument| packument.versions).unwrap_or_default())
+    }
+
+    /// Tarball location and checksums of one version; None when that
+    /// version isn't published
+    pub fn tarball_manifest(&self, name: &str, version: &str) -> Result<Option<TarballManifest>> {
+        let spec = format!("{}@{}", name, version);
+        let Some(view) = self.view(&[&spec, "dist"])? else { return Ok(None) };
+        Ok(parse_tarball_manifest(&view))
+    }
+
+    /// `npm view <args> --json`. None when the package or version doesn't
+    /// exist: npm answers E404, or prints nothing for an unmatched version.
+    fn view(&self, args: &[&str]) -> Result<Option<serde_json::Value>> {
+        let mut command = Platform::npm_command();
+        command.arg("view").args(args).arg("--json").current_dir(&self.dir);
+        if let Some(registry) = &self.registry {
+            command.arg("--registry").arg(registry);
+        }
+        let output = run_command(command, self.timeout, OutputMode::Capture)?;
+
+        if !output.success() {
+            let error = parse_error(&output.stdout, &output.stderr);
+            if error.code.as_deref() == Some("E404") {
+                return Ok(None);
+            }
+            return Err(SpineError::Config(format!("npm view failed: {}", error.summary)).into());
+        }
+        if output.stdout.trim().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&output.stdout)?))
+    }
+}
+
+/// npm's error report: `{"error": {"code", "summary"}}` on stdout or stderr
+/// with --json, or only `npm ERR!` lines from older versions
+#[derive(Debug, Clone)]
+pub struct ViewError {
+    pub code: Option<String>,
+    pub summary: String,
+}
+
+pub fn parse_error(stdout: &str, stderr: &str) -> ViewError {
+    for stream in [stdout, stderr] {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(stream.trim()) else { continue };
+        let error = &value["error"];
+        if error.is_object() {
+            return ViewError {
+                code: error["code"].as_str().map(str::to_string),
+                summary: error["summary"].as_str().unwrap_or_default().trim().to_string(),
+            };
+        }
+    }
+
+    let reason = stderr.lines().find(|line| line.contains("ERR!")).unwrap_or(stderr.trim()).trim().to_string();
+    let code = stderr.split_whitespace()
+        .skip_while(|word| *word != "code")
+        .nth(1)
+        .map(str::to_string)
+        .or_else(|| stderr.contains("E404").then(|| "E404".to_string()));
+    ViewError { code, summary: reason }
+}
+
+/// `versions` is a plain string when only one version is published
+pub fn parse_packument(view: &serde_json::Value) -> Packument {
+    let versions = match &view["versions"] {
+        serde_json::Value::Array(versions) => versions.iter().filter_map(|v| v.as_str().map(str::to_string)).collect(),
+        serde_json::Value::String(version) => vec![version.clone()],
+        _ => Vec::new(),
+    };
+    let dist_tags = view["dist-tags"].as_object()
+        .map(|tags| tags.iter().filter_map(|(tag, version)| Some((tag.clone(), version.as_str()?.to_string()))).collect())
+        .unwrap_or_default();
+    Packument { versions, dist_tags }
+}
+
+/// `npm view <spec> dist --json` prints the dist object, or an array of them
+/// when the spec matched several versions; the last is the newest
+pub fn parse_tarball_manifest(view: &serde_json::Value) -> Option<TarballManifest> {
+    let dist = match view {
+        serde_json::Value::Array(items) => items.last()?,
+        other => other,
+    };
+    Some(TarballManifest {
+        tarball: dist["tarball"].as_str()?.to_string(),
+        shasum: dist["shasum"].as_str().map(str::to_string),
+        integrity: dist["integrity"].as_str().map(str::to_string),
+        file_count: dist["fileCount"].as_u64(),
+        unpacked_size: dist["unpackedSize"].as_u64(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output recorded from `npm view`, kept under tests/fixtures/npm
+    fn fixture(name: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("npm").join(name);
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+    }
+
+    fn json_fixture(name: &str) -> serde_json::Value {
+        serde_json::from_str(&fixture(name)).unwrap()
+    }
+
+    #[test]
+    fn parses_versions_and_dist_tags() {
+        let packument = parse_packument(&json_fixture("view-versions-dist-tags.json"));
+        assert_eq!(packument.versions, ["1.0.0", "1.1.0", "2.0.0-next.0", "2.0.0"]);
+        assert_eq!(packument.dist_tags.get("latest").map(String::as_str), Some("2.0.0"));
+        assert_eq!(packument.dist_tags.get("next").map(String::as_str), Some("2.0.0-next.0"));
+    }
+
+    #[test]
+    fn parses_a_single_version_printed_as_a_string() {
+        let packument = parse_packument(&json_fixture("view-versions-dist-tags-single.json"));
+        assert_eq!(packument.versions, ["0.1.0"]);
+        assert_eq!(packument.dist_tags.len(), 1);
+    }
+
+    #[test]
+    fn parses_a_dist_object() {
+        let manifest = parse_tarball_manifest(&json_fixture("view-dist.json")).expect("a tarball");
+        assert_eq!(manifest.tarball, "https://registry.npmjs.org/@acme/ui/-/ui-2.0.0.tgz");
+        assert_eq!(manifest.shasum.as_deref(), Some("4f1d2c3b4a5968778695a4b3c2d1e0f9a8b7c6d5"));
+        assert!(manifest.integrity.is_some_and(|integrity| integrity.starts_with("sha512-")));
+        assert_eq!((manifest.file_count, manifest.unpacked_size), (Some(42), Some(183211)));
+    }
+
+    #[test]
+    fn takes_the_newest_dist_of_a_range() {
+        let manifest = parse_tarball_manifest(&json_fixture("view-dist-range.json")).expect("a tarball");
+        assert_eq!(manifest.tarball, "https://registry.npmjs.org/@acme/ui/-/ui-1.1.0.tgz");
+        assert!(parse_tarball_manifest(&serde_json::json!([])).is_none());
+        assert!(parse_tarball_manifest(&serde_json::json!({ "shasum": "abc" })).is_none());
+    }
+
+    #[test]
+    fn reads_json_errors_from_either_stream() {
+        let report = fixture("view-e404.json");
+        for (stdout, stderr) in [(report.as_str(), ""), ("", report.as_str())] {
+            let error = parse_error(stdout, stderr);
+            assert_eq!(error.code.as_deref(), Some("E404"));
+            assert_eq!(error.summary, "Not Found - GET https://registry.npmjs.org/@acme%2fmissing - Not found");
+        }
+    }
+
+    #[test]
+    fn reads_legacy_err_lines() {
+        let not_found = parse_error("", &fixture("view-e404-legacy.txt"));
+        assert_eq!(not_found.code.as_deref(), Some("E404"));
+        assert_eq!(not_found.summary, "npm ERR! code E404");
+
+        let unauthorized = parse_error("", &fixture("view-e401-legacy.txt"));
+        assert_eq!(unauthorized.code.as_deref(), Some("E401"));
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use anyhow::Result;
+use crate::angular::AngularBuildManager;
+use crate::config::Config;
+use crate::durable::PackageJsonEdit;
+use crate::error::SpineError;
+use crate::paths::display_path;
+
+/// Fields whose ranges on other libraries of the set must admit their new versions
+const RANGE_FIELDS: [&str; 2] = ["dependencies", "peerDependencies"];
+
+/// Version increment applied to every library of a `publish --all`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    /// The next version as `npm version` computes it: a pre-release of the
+    /// target version is released rather than skipped over
+    pub fn apply(self, version: &str) -> Option<String> {
+        let mut version = semver::Version::parse(version.trim()).ok()?;
+        let released = !version.pre.is_empty() && match self {
+            Bump::Patch => true,
+            Bump::Minor => version.patch == 0,
+            Bump::Major => version.minor == 0 && version.patch == 0,
+        };
+        if !released {
+            match self {
+                Bump::Patch => version.patch += 1,
+                Bump::Minor => (version.minor, version.patch) = (version.minor + 1, 0),
+                Bump::Major => (version.major, version.minor, version.patch) = (version.major + 1, 0, 0),
+            }
+        }
+        version.pre = semver::Prerelease::EMPTY;
+        version.build = semver::BuildMetadata::EMPTY;
+        Some(version.to_string())
+    }
+}
+
+/// One library of the set being published
+#[derive(Debug, Clone)]
+struct Member {
+    /// The configured link name
+    package: String,
+    npm_name: String,
+    /// The library's source directory, whose package.json the build copies
+    source: PathBuf,
+    current: String,
+    planned: String,
+    /// (field, dependency, range) for each dependency on another member
+    ranges: Vec<(String, String, String)>,
+}
+
+/// A range on a sibling that doesn't admit the sibling's planned version
+#[derive(Debug, Clone)]
+pub struct RangeProblem {
+    pub library: String,
+    pub field: String,
+    pub dependency: String,
+    pub declared: String,
+    pub planned: String,
+    pub suggested: String,
+}
+
+pub struct PublishAllOptions {
+    pub skip_build: bool,
+    pub dry_run: bool,
+    pub require_clean: bool,
+    pub allow_dirty: bool,
+    pub bump: Option<Bump>,
+    pub update_ranges: bool,
+}
+
+/// `spine publish --all`: publish every linked library of the current
+/// workspace, dependencies first. Before anything is built, each library's
+/// ranges on the others are checked against their planned versions, so the
+/// set can be installed together once it is out.
+pub fn publish_all_command(config: &Config, options: PublishAllOptions) -> Result<()> {
+    let manager = AngularBuildManager::new(config.clone())?;
+    if !manager.has_workspace() {
+        return Err(SpineError::Config("No Angular workspace detected; run 'spine publish --all' in one".to_string()).into());
+    }
+    let members = plan_members(config, &manager, options.bump)?;
+    if members.is_empty() {
+        println!("No linked libraries found to publish");
+        return Ok(());
+    }
+    let order = build_order(&members);
+
+    println!("📋 Publish plan, in build order:");
+    for (position, member) in order.iter().enumerate() {
+        let change = if member.planned == member.current {
+            member.current.clone()
+        } else {
+            format!("{} → {}", member.current, member.planned)
+        };
+        println!("  {}. {} {}", position + 1, member.npm_name, change);
+    }
+
+    let problems = range_problems(&members);
+    if !problems.is_empty() {
+        println!("\n❌ {} range(s) on libraries of this set don't admit their planned versions:", problems.len());
+        print_problems(&problems);
+        if !options.update_ranges {
+            return Err(SpineError::Config(format!(
+                "{} cross-library range(s) would keep the set from installing together; update them or pass --update-ranges",
+                problems.len()
+            )).into());
+        }
+    } else {
+        println!("\n✓ Every range between these libraries admits the planned versions");
+    }
+
+    let writes = !options.dry_run && (options.bump.is_some() || !problems.is_empty());
+    // Writing package.json dirties the trees, so check them before it happens
+    if writes && options.require_clean && !options.allow_dirty {
+        for member in &members {
+            let dirty = crate::angular::uncommitted_files(&member.source).unwrap_or_default();
+            if !dirty.is_empty() {
+                return Err(SpineError::Config(format!(
+                    "Refusing to publish {} from a dirty working tree (--require-clean); {} uncommitted file(s) in {}",
+                    member.npm_name, dirty.len(), display_path(&member.source)
+                )).into());
+            }
+        }
+    }
+    write_source_changes(&members, &problems, options.dry_run)?;
+
+    let require_clean = options.require_clean && !writes;
+    for (position, member) in order.iter().enumerate() {
+        println!("\n━━ {}/{} {} ━━", position + 1, order.len(), member.npm_name);
+        if let Err(e) = crate::angular::publish_command(
+            config, &member.package, options.skip_build, options.dry_run, require_clean, options.allow_dirty,
+        ) {
+            let remaining: Vec<&str> = order[position + 1..].iter().map(|member| member.npm_name.as_str()).collect();
+            if !remaining.is_empty() {
+                println!("⏭️  Not published, as they may depend on it: {}", remaining.join(", "));
+            }
+            return Err(e);
+        }
+    }
+
+    let verb = if options.dry_run { "would be published" } else { "published" };
+    println!("\n✨ {} librar{} {}", order.len(), if order.len() == 1 { "y" } else { "ies" }, verb);
+    Ok(())
+}
+
+/// The workspace's linked libraries with their current and planned versions
+fn plan_members(config: &Config, manager: &AngularBuildManager, bump: Option<Bump>) -> Result<Vec<Member>> {
+    let mut members = Vec::new();
+    let mut libraries = BTreeSet::new();
+    for link in config.links.values() {
+        let Some((library, source)) = manager.resolve_library_root(&link.name) else { continue };
+        if !libraries.insert(library) {
+            continue;
+        }
+        let package_json = source.join("package.json");
+        let info = crate::package::parse_package_json(&package_json)?;
+        let planned = match bump {
+            Some(bump) => bump.apply(&info.version).ok_or_else(|| SpineError::PackageJson(format!(
+                "{} has version '{}', which isn't semver and can't be bumped", display_path(&package_json), info.version
+            )))?,
+            None => info.version.clone(),
+        };
+        members.push(Member { package: link.name.clone(), npm_name: info.name, source, current: info.version, planned, ranges: Vec::new() });
+    }
+
+    let names: BTreeSet<String> = members.iter().map(|member| member.npm_name.clone()).collect();
+    for member in &mut members {
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(member.source.join("package.json"))?)?;
+        for field in RANGE_FIELDS {
+            let Some(dependencies) = json.get(field).and_then(|deps| deps.as_object()) else { continue };
+            for (dependency, range) in dependencies {
+                if let Some(range) = range.as_str().filter(|_| names.contains(dependency) && *dependency != member.npm_name) {
+                    member.ranges.push((field.to_string(), dependency.clone(), range.to_string()));
+                }
+            }
+        }
+    }
+    members.sort_by(|a, b| a.npm_name.cmp(&b.npm_name));
+    Ok(members)
+}
+
+/// Members ordered so each comes after the members it depends on; members
+/// in a dependency cycle follow in name order
+fn build_order(members: &[Member]) -> Vec<Member> {
+    let mut remaining: BTreeMap<&str, &Member> = members.iter().map(|member| (member.npm_name.as_str(), member)).collect();
+    let mut order: Vec<Member> = Vec::new();
+    while !remaining.is_empty() {
+        let ready: Vec<&str> = remaining.iter()
+            .filter(|(_, member)| member.ranges.iter().all(|(_, dependency, _)| !remaining.contains_key(dependency.as_str())))
+            .map(|(name, _)| *name)
+            .collect();
+        if ready.is_empty() {
+            let cycle: Vec<&str> = remaining.keys().copied().collect();
+            println!("⚠️  Dependency cycle between {}; publishing them in name order", cycle.join(", "));
+            order.extend(remaining.values().map(|member| (*member).clone()));
+            break;
+        }
+        for name in ready {
+            if let Some(member) = remaining.remove(name) {
+                order.push(member.clone());
+            }
+        }
+    }
+    order
+}
+
+fn range_problems(members: &[Member]) -> Vec<RangeProblem> {
+    let planned: BTreeMap<&str, &str> = members.iter().map(|member| (member.npm_name.as_str(), member.planned.as_str())).collect();
+    let mut problems = Vec::new();
+    for member in members {
+        for (field, dependency, declared) in &member.ranges {
+            let Some(version) = planned.get(dependency.as_str()) else { continue };
+            if let Some(suggested) = suggested_range(declared, version) {
+                problems.push(RangeProblem {
+                    library: member.npm_name.clone(),
+                    field: field.clone(),
+                    dependency: dependency.clone(),
+                    declared: declared.clone(),
+                    planned: version.to_string(),
+                    suggested,
+                });
+            }
+        }
+    }
+    problems
+}
+
+/// The range to declare instead of `declared` when it doesn't admit
+/// `planned`: a caret range, or an exact or tilde one when that's what was
+/// declared. None when it admits it or isn't a semver range (file:, tags).
+fn suggested_range(declared: &str, planned: &str) -> Option<String> {
+    let (protocol, range) = match declared.trim().strip_prefix("workspace:") {
+        Some(range) => ("workspace:", range),
+        None => ("", declared.trim()),
+    };
+    // Replaced with the sibling's own version when the package is packed
+    if !protocol.is_empty() && matches!(range, "*" | "^" | "~") {
+        return None;
+    }
+    if crate::package::range_satisfies(range, planned)? {
+        return None;
+    }
+    let operator = if range.starts_with('~') {
+        "~"
+    } else if semver::Version::parse(range.trim_start_matches(['v', '='])).is_ok() {
+        ""
+    } else {
+        "^"
+    };
+    Some(format!("{}{}{}", protocol, operator, planned))
+}
+
+fn print_problems(problems: &[RangeProblem]) {
+    let header = ["Library", "Dependency", "Declared", "Planned", "Suggested"];
+    let rows: Vec<[String; 5]> = problems.iter()
+        .map(|problem| [
+            problem.library.clone(),
+            format!("{} ({})", problem.dependency, problem.field),
+            problem.declared.clone(),
+            problem.planned.clone(),
+            problem.suggested.clone(),
+        ])
+        .collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| rows.iter().map(|row| row[column].chars().count()).chain([header[column].len()]).max().unwrap_or(0))
+        .collect();
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells.iter().zip(&widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+        println!("  {}", padded.join("  ").trim_end());
+    };
+    let rules: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    line(header.to_vec());
+    line(rules.iter().map(String::as_str).collect());
+    for row in &rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
+
+/// Write the bumped versions and the suggested ranges into each library's
+/// source package.json, or show the changes on a dry run
+fn write_source_changes(members: &[Member], problems: &[RangeProblem], dry_run: bool) -> Result<()> {
+    for member in members {
+        let mut edit = PackageJsonEdit::open(&member.source)?;
+        if member.planned != member.current {
+            edit.set_version(&member.planned);
+        }
+        for problem in problems.iter().filter(|problem| problem.library == member.npm_name) {
+            edit.set_range(&problem.field, &problem.dependency, &problem.suggested);
+        }
+        if !edit.has_changes() {
+            continue;
+        }
+        println!("\n📝 {}package.json of {}:", if dry_run { "Would change the " } else { "" }, member.npm_name);
+        edit.print_diff()?;
+        if !dry_run {
+            edit.save()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_ranges_that_admit_the_planned_version() {
+        assert_eq!(suggested_range("^2.4.0", "2.5.0"), None);
+        assert_eq!(suggested_range("2.5.0", "2.5.0"), None);
+        assert_eq!(suggested_range("workspace:^2.4.0", "2.5.0"), None);
+    }
+
+    #[test]
+    fn keeps_the_declared_operator() {
+        assert_eq!(suggested_range("^2.4.0", "3.0.0").as_deref(), Some("^3.0.0"));
+        assert_eq!(suggested_range("2.4.0", "2.5.0").as_deref(), Some("2.5.0"));
+        assert_eq!(suggested_range("~2.4.0", "2.5.0").as_deref(), Some("~2.5.0"));
+        assert_eq!(suggested_range(">=1 <2", "2.0.0").as_deref(), Some("^2.0.0"));
+    }
+
+    #[test]
+    fn keeps_the_workspace_protocol() {
+        assert_eq!(suggested_range("workspace:^2.4.0", "3.0.0").as_deref(), Some("workspace:^3.0.0"));
+        assert_eq!(suggested_range("workspace:2.4.0", "2.5.0").as_deref(), Some("workspace:2.5.0"));
+        // Packing replaces these with the sibling's own version
+        for declared in ["workspace:*", "workspace:^", "workspace:~"] {
+            assert_eq!(suggested_range(declared, "3.0.0"), None, "{}", declared);
+        }
+    }
+
+    #[test]
+    fn leaves_non_semver_ranges_alone() {
+        assert_eq!(suggested_range("file:../ui", "3.0.0"), None);
+        assert_eq!(suggested_range("latest", "3.0.0"), None);
+    }
+}
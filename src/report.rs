@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::Result;
+use crate::paths::display_path;
+use crate::progress::strip_ansi;
+
+/// File format for `--report`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Json,
+    /// JUnit XML, which CI systems render as test results
+    Junit,
+}
+
+/// Where `--report` writes and in which format
+#[derive(Debug, Clone)]
+pub struct ReportTarget {
+    pub path: PathBuf,
+    pub format: ReportFormat,
+}
+
+impl ReportTarget {
+    pub fn from_args(path: Option<&Path>, format: ReportFormat) -> Option<Self> {
+        path.map(|path| Self { path: path.to_path_buf(), format })
+    }
+}
+
+/// One checked item: a library build, a link, a doctor check
+#[derive(Debug, Clone)]
+pub struct ReportCase {
+    pub name: String,
+    pub duration: Option<Duration>,
+    pub failure: Option<String>,
+    pub skipped: Option<String>,
+}
+
+impl ReportCase {
+    pub fn passed(name: &str) -> Self {
+        Self { name: name.to_string(), duration: None, failure: None, skipped: None }
+    }
+
+    pub fn failed(name: &str, failure: impl Into<String>) -> Self {
+        Self { failure: Some(failure.into()), ..Self::passed(name) }
+    }
+
+    pub fn skipped(name: &str, reason: impl Into<String>) -> Self {
+        Self { skipped: Some(reason.into()), ..Self::passed(name) }
+    }
+}
+
+/// A command's structured results for a CI artifact, written next to (and
+/// independently of) whatever the command prints
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// Suite name, e.g. "spine build"
+    pub suite: String,
+    pub cases: Vec<ReportCase>,
+    /// The JSON form; JUnit is rendered from `cases`
+    pub json: serde_json::Value,
+}
+
+impl Report {
+    /// Write to `target`, replacing the file atomically. A failure is only a
+    /// warning, so reporting never changes the command's exit code.
+    pub fn write(&self, target: &ReportTarget) {
+        let content = match target.format {
+            ReportFormat::Json => serde_json::to_string_pretty(&self.json).map(|json| json + "\n").map_err(anyhow::Error::from),
+            ReportFormat::Junit => Ok(self.to_junit()),
+        };
+        match content.and_then(|content| write_atomic(&target.path, &content)) {
+            Ok(()) => eprintln!("📝 Wrote report to {}", display_path(&target.path)),
+            Err(e) => eprintln!("Warning: Could not write the report to {}: {}", display_path(&target.path), e),
+        }
+    }
+
+    pub fn to_junit(&self) -> String {
+        let failures = self.cases.iter().filter(|case| case.failure.is_some()).count();
+        let skipped = self.cases.iter().filter(|case| case.skipped.is_some()).count();
+        let total: Duration = self.cases.iter().filter_map(|case| case.duration).sum();
+        let suite = xml_escape(&self.suite);
+        let counts = format!("tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\"",
+            self.cases.len(), failures, skipped, total.as_secs_f64());
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!("<testsuites name=\"{}\" {}>\n", suite, counts));
+        xml.push_str(&format!("  <testsuite name=\"{}\" {}>\n", suite, counts));
+        for case in &self.cases {
+            let time = case.duration.map(|d| format!(" time=\"{:.3}\"", d.as_secs_f64())).unwrap_or_default();
+            let open = format!("    <testcase name=\"{}\" classname=\"{}\"{}", xml_escape(&case.name), suite, time);
+            match (&case.failure, &case.skipped) {
+                (Some(failure), _) => {
+                    // Stripped first, so a line holding only colour codes isn't the message
+                    let failure = strip_ansi(failure);
+                    let message = failure.lines().find(|line| !line.trim().is_empty()).unwrap_or("failed").trim();
+                    xml.push_str(&format!("{}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                        open, xml_escape(message), xml_escape(&failure)));
+                }
+                (None, Some(reason)) => {
+                    xml.push_str(&format!("{}>\n      <skipped message=\"{}\"/>\n    </testcase>\n", open, xml_escape(reason)));
+                }
+                (None, None) => xml.push_str(&format!("{}/>\n", open)),
+            }
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+/// Write beside `path` and rename over it, so a CI step never picks up half a report
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// Escape text for XML attributes and content. Build output's ANSI escape
+/// sequences are removed whole, and any other control characters XML 1.0
+/// can't represent are dropped.
+fn xml_escape(text: &str) -> String {
+    let text = strip_ansi(text);
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\r' | '\t' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(cases: Vec<ReportCase>) -> Report {
+        Report { suite: "spine build".to_string(), cases, json: serde_json::Value::Null }
+    }
+
+    #[test]
+    fn junit_counts_and_times_cases() {
+        let mut built = ReportCase::passed("@acme/ui");
+        built.duration = Some(Duration::from_millis(1500));
+        let xml = report(vec![
+            built,
+            ReportCase::failed("@acme/forms", "Build failed"),
+            ReportCase::skipped("@acme/charts", "not linked"),
+        ]).to_junit();
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains("<testsuite name=\"spine build\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\" time=\"1.500\">"), "{}", xml);
+        assert!(xml.contains("<testcase name=\"@acme/ui\" classname=\"spine build\" time=\"1.500\"/>"), "{}", xml);
+        assert!(xml.contains("<failure message=\"Build failed\">Build failed</failure>"), "{}", xml);
+        assert!(xml.contains("<skipped message=\"not linked\"/>"), "{}", xml);
+    }
+
+    #[test]
+    fn junit_strips_escape_sequences_from_build_output() {
+        let output = "\x1b[31m\n\x1b[1m\x1b[31mERROR\x1b[39m\x1b[22m: \x1b[4msrc/lib/ui.ts:3:1\x1b[24m - <Button> & 'x'\x1b]8;;https://angular.dev\x07\x1b]8;;\x07\x1b[0m\n";
+        let xml = report(vec![ReportCase::failed("@acme/ui", output)]).to_junit();
+
+        assert!(!xml.chars().any(|c| c.is_control() && c != '\n'), "{:?}", xml);
+        // No leftover CSI parameters such as "[31m" once ESC is gone
+        assert!(!xml.contains("[31m") && !xml.contains("[0m") && !xml.contains("8;;"), "{}", xml);
+        let line = "ERROR: src/lib/ui.ts:3:1 - &lt;Button&gt; &amp; &apos;x&apos;";
+        assert!(xml.contains(&format!("<failure message=\"{}\">\n{}\n</failure>", line, line)), "{}", xml);
+    }
+}
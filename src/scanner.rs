@@ -1,62 +1,360 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use anyhow::Result;
-use crate::config::Config;
-use crate::workspace::WorkspaceManager;
-use crate::platform::Platform;
+use serde::{Deserialize, Serialize};
+use crate::config::{Config, ConfigFile, VersionRefreshReport};
+use crate::error::SpineError;
+use crate::workspace::{DiscoveredPackage, ScanOutcome, WorkspaceManager};
+use crate::platform::{run_command, OutputMode, Platform};
+use crate::paths::display_path;
+use crate::npm::NpmManager;
+use crate::pm::PackageManager;
 
 pub struct Scanner;
 
+pub struct SuggestOptions {
+    pub add: bool,
+    pub link: bool,
+    pub yes: bool,
+    pub pm: Option<PackageManager>,
+    pub search_path: Option<String>,
+    pub no_ignore: bool,
+    pub depth: Option<usize>,
+}
+
+/// A package sync left alone, and why
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct SkippedLink {
+    pub package: String,
+    pub reason: String,
+}
+
+/// A link sync could not restore
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedLink {
+    pub package: String,
+    pub error: String,
+}
+
+/// What `spine sync` found for the current project, and what it did (or, on a
+/// dry run, would do) about it. Built before anything is printed so the human
+/// and JSON output share it.
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    pub project: PathBuf,
+    pub dry_run: bool,
+    /// Pending links whose path now exists
+    pub promoted: Vec<String>,
+    pub metadata: VersionRefreshReport,
+    pub already_linked: Vec<String>,
+    /// Recorded for this project but missing from node_modules
+    pub to_restore: Vec<String>,
+    pub not_configured_here: Vec<String>,
+    pub skipped: Vec<SkippedLink>,
+    /// Recorded for this project, but the package source is gone so the link
+    /// can't be restored; the project is dropped from their config entry.
+    /// Missing build output is skipped as pending instead.
+    pub removed_invalid_links: Vec<String>,
+    /// Linked in node_modules, but their config entry doesn't record this
+    /// project; it is added
+    pub added_missing_links: Vec<String>,
+    /// Linked in node_modules but not configured in Spine at all
+    pub untracked_links: Vec<String>,
+    pub restored: Vec<String>,
+    pub failed: Vec<FailedLink>,
+}
+
+impl SyncReport {
+    /// How many links and config entries sync changes, or on a dry run would change
+    pub fn change_count(&self) -> usize {
+        self.to_restore.len()
+            + self.removed_invalid_links.len()
+            + self.added_missing_links.len()
+            + self.promoted.len()
+            + self.metadata.updated.len()
+    }
+
+    pub fn has_work(&self) -> bool {
+        self.change_count() > 0
+    }
+
+    pub fn print_analysis(&self) {
+        for name in &self.promoted {
+            if self.dry_run {
+                println!("✨ Would promote pending link {} (path now exists)", name);
+            } else {
+                println!("✨ Pending link {} is now available", name);
+            }
+        }
+        if !self.metadata.is_clean() {
+            if self.dry_run {
+                println!("🔄 Would refresh package metadata:");
+            } else {
+                println!("🔄 Refreshed package metadata:");
+            }
+            self.metadata.print();
+        }
+
+        println!("📊 Current state analysis:");
+        println!("  ✅ Already linked as configured: {}", self.already_linked.len());
+        println!("  🔗 Need to restore links: {}", self.to_restore.len());
+        println!("  📦 Not configured for this project: {}", self.not_configured_here.len());
+        if !self.skipped.is_empty() {
+            println!("  ⏭️  Skipped by .spine.toml overrides: {}", self.skipped.len());
+            for skipped in &self.skipped {
+                println!("    • {} ({})", skipped.package, skipped.reason);
+            }
+        }
+        let verb = if self.dry_run { "Would forget" } else { "Forgetting" };
+        for package in &self.removed_invalid_links {
+            println!("  🗑️  {} {} here (package source no longer exists)", verb, package);
+        }
+        let verb = if self.dry_run { "Would record" } else { "Recording" };
+        for package in &self.added_missing_links {
+            println!("  📝 {} {} here (linked but missing from the config)", verb, package);
+        }
+        for package in &self.untracked_links {
+            println!("  ❔ {} is linked but not managed by Spine", package);
+        }
+    }
+
+    /// What a dry run would restore and skip
+    pub fn print_dry_run(&self, config: &Config) {
+        println!("\n🧪 Dry run:");
+        for package_name in &self.to_restore {
+            let package_link = config.links[package_name].for_project(&self.project);
+            println!("  🔗 Would restore {}{} -> {}", package_name, package_link.variant_label(&self.project), display_path(&package_link.path));
+        }
+        for skipped in &self.skipped {
+            println!("  ⏭️  Would skip {} ({})", skipped.package, skipped.reason);
+        }
+        println!("\n📊 Summary: {} package(s) would be restored, {} skipped",
+            self.to_restore.len(), self.skipped.len());
+    }
+
+    /// Everything sync would change, as a plan
+    pub fn plan(&self, config: &Config) -> crate::plan::Plan {
+        let mut plan = crate::plan::Plan::new("sync", Some(self.project.clone()));
+        for package_name in &self.to_restore {
+            let path = config.links.get(package_name).map(|link| display_path(&link.for_project(&self.project).path));
+            plan.push("restore", package_name, path);
+        }
+        for package_name in &self.removed_invalid_links {
+            plan.push("forget", package_name, Some("package source is gone".to_string()));
+        }
+        for package_name in &self.added_missing_links {
+            plan.push("record", package_name, Some("linked here but not in the config".to_string()));
+        }
+        for package_name in &self.promoted {
+            plan.push("promote", package_name, Some("pending path now exists".to_string()));
+        }
+        for update in &self.metadata.updated {
+            let previous = update.previous.as_deref().unwrap_or("unknown");
+            plan.push("update version", &update.package, Some(format!("{} → {}", previous, update.current)));
+        }
+        plan
+    }
+
+    fn print_results(&self) {
+        println!("\n📊 Sync Summary:");
+        println!("  ✅ Successfully restored: {}", self.restored.len());
+        if !self.failed.is_empty() {
+            println!("  ❌ Failed to restore: {}", self.failed.len());
+            for failed in &self.failed {
+                println!("    • {}", failed.package);
+            }
+        }
+        
+        if !self.restored.is_empty() {
+            println!("\n✨ Spine configuration has been enforced. {} package(s) restored.", self.restored.len());
+        }
+    }
+}
+
+/// Which of several packages discovered under one name `scan` uses
+#[derive(Debug, Clone)]
+pub struct DuplicateChoice {
+    /// None when no candidate stood out and none was chosen
+    pub selected: Option<PathBuf>,
+    pub reason: String,
+}
+
+/// Package directories found by the last `spine scan`, kept so shell
+/// completion can suggest paths without walking the tree again.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScanCache {
+    pub updated_at: u64,
+    #[serde(default)]
+    pub package_dirs: Vec<PathBuf>,
+}
+
+impl ScanCache {
+    pub fn cache_path(config_file: &ConfigFile) -> Result<PathBuf> {
+        Ok(config_file.dir()?.join("scan_cache.json"))
+    }
+
+    pub fn from_packages(packages: &[DiscoveredPackage]) -> Self {
+        Self {
+            updated_at: crate::history::now_timestamp(),
+            package_dirs: packages.iter().map(|p| p.path.clone()).collect(),
+        }
+    }
+
+    /// Load the cache, or None if no scan has been run yet
+    pub fn load(config_file: &ConfigFile) -> Result<Option<Self>> {
+        let path = Self::cache_path(config_file)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Merge into the existing cache so scanning one root doesn't forget the others
+    pub fn save(&self, config_file: &ConfigFile) -> Result<()> {
+        let mut merged = Self::load(config_file).ok().flatten().unwrap_or_default();
+        merged.updated_at = self.updated_at;
+        for dir in &self.package_dirs {
+            if !merged.package_dirs.contains(dir) {
+                merged.package_dirs.push(dir.clone());
+            }
+        }
+        merged.package_dirs.retain(|dir| dir.join("package.json").exists());
+        merged.package_dirs.sort();
+
+        let path = Self::cache_path(config_file)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(&merged)?)?;
+        Ok(())
+    }
+}
+
 impl Scanner {
-    pub fn scan_packages(add_packages: bool, search_path: Option<&str>) -> Result<()> {
+    /// Packages under `search_path`, or under the configured scan roots when
+    /// none is given and some are set. Roots that overlap find a package once.
+    pub fn discover(config: &Config, search_path: Option<&str>, no_ignore: bool, depth: Option<usize>) -> Result<ScanOutcome> {
+        if search_path.is_some() || config.scan.roots.is_empty() {
+            return WorkspaceManager::scan_for_packages(search_path, no_ignore, depth);
+        }
+
+        let mut outcome = ScanOutcome::default();
+        for root in &config.scan.roots {
+            if !root.is_dir() {
+                println!("⚠️  Skipping missing scan root: {}", display_path(root));
+                continue;
+            }
+            let scanned = WorkspaceManager::scan_for_packages(Some(&root.to_string_lossy()), no_ignore, depth)?;
+            outcome.directories += scanned.directories;
+            for package in scanned.packages {
+                if !outcome.packages.iter().any(|p: &DiscoveredPackage| crate::paths::same_path(&p.path, &package.path)) {
+                    outcome.packages.push(package);
+                }
+            }
+        }
+        outcome.packages.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+        Ok(outcome)
+    }
+
+    pub fn scan_packages(config: &Config, add_packages: bool, search_path: Option<&str>, yes: bool, no_ignore: bool, depth: Option<usize>) -> Result<()> {
         println!("Scanning for packages...");
         
-        let packages = WorkspaceManager::scan_for_packages(search_path)?;
+        let started = std::time::Instant::now();
+        let ScanOutcome { packages, directories } = Self::discover(config, search_path, no_ignore, depth)?;
+        let scanned = format!(
+            "⏱️  Scanned {} director{} in {}",
+            directories,
+            if directories == 1 { "y" } else { "ies" },
+            crate::history::format_duration_ms(started.elapsed().as_millis() as u64)
+        );
+        
+        if let Err(e) = ScanCache::from_packages(&packages).save(&config.file) {
+            println!("⚠️  Failed to update scan cache: {}", e);
+        }
         
         if packages.is_empty() {
             println!("No packages found in the specified directory.");
+            println!("{}", scanned);
             return Ok(());
         }
 
+        let duplicates = Self::resolve_duplicates(&packages, add_packages);
         println!("Found {} package(s):", packages.len());
         
         // Load workspace config if available
-        let workspace_config = WorkspaceManager::load_workspace_config()?.unwrap_or_default();
+        let workspace_config = WorkspaceManager::load_workspace_config(&std::env::current_dir()?)?.unwrap_or_default();
         let filtered_packages = WorkspaceManager::filter_packages_by_workspace_config(&packages, &workspace_config);
         
         for package in &packages {
             let included = filtered_packages.iter().any(|p| p.name == package.name);
             let dist_indicator = if package.is_dist { " (dist)" } else { "" };
-            let status = if included { "✓" } else { "○" };
+            let (status, marker) = match duplicates.get(&package.name) {
+                Some(choice) if choice.selected.as_ref() == Some(&package.path) => {
+                    (if included { "✓" } else { "○" }, format!("  ← selected: {}", choice.reason))
+                }
+                Some(choice) if choice.selected.is_some() => ("○", "  ← not selected".to_string()),
+                Some(choice) => ("⚠️", format!("  ← {}", choice.reason)),
+                None => (if included { "✓" } else { "○" }, String::new()),
+            };
             
-            println!("  {} {} (v{}) -> {}{}", 
+            println!("  {} {} (v{}) -> {}{} [{}]{}", 
                 status, 
                 package.name, 
                 package.version, 
-                package.path.display(),
-                dist_indicator
+                display_path(&package.path),
+                dist_indicator,
+                package.source.label(),
+                marker
             );
         }
+        println!("{}", scanned);
 
         if add_packages {
-            println!("\nAdding packages to configuration...");
-            let mut config = Config::load_or_create()?;
-            let mut added_count = 0;
+            println!();
+            let mut config = config.clone();
+            let mut plan = crate::plan::Plan::new("scan --add", None);
+            let mut paths = HashMap::new();
             
             for package in filtered_packages {
-                match config.add_link(package.name.clone(), package.path.to_string_lossy().to_string()) {
-                    Ok(_) => {
-                        println!("✓ Added: {}", package.name);
-                        added_count += 1;
-                    }
-                    Err(e) => {
-                        println!("✗ Failed to add {}: {}", package.name, e);
+                if let Some(choice) = duplicates.get(&package.name) {
+                    match &choice.selected {
+                        Some(selected) if *selected == package.path => {}
+                        Some(_) => continue,
+                        None => {
+                            // Warn once per name, at its first candidate
+                            if packages.iter().find(|p| p.name == package.name).is_some_and(|p| p.path == package.path) {
+                                println!("⚠️  Skipped {}: {}", package.name, choice.reason);
+                            }
+                            continue;
+                        }
                     }
                 }
+                plan.push("add", &package.name, Some(display_path(&package.path)));
+                paths.insert(package.name.clone(), package.path.clone());
+            }
+            if plan.is_empty() {
+                println!("No packages to add.");
+                return Ok(());
             }
+            if !plan.confirm(yes) {
+                println!("Cancelled; nothing was added.");
+                return Ok(());
+            }
+            
+            let outcome = plan.execute(false, |action| {
+                let path = &paths[&action.target];
+                config.add_link(action.target.clone(), path.to_string_lossy().to_string(), false)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            })?;
             
-            if added_count > 0 {
+            if !outcome.completed.is_empty() {
                 config.save()?;
-                println!("\nAdded {} package(s) to configuration.", added_count);
+                println!("\nAdded {} package(s) to configuration.", outcome.completed.len());
             }
         } else {
             println!("\nUse --add to automatically add discovered packages to your configuration.");
@@ -66,103 +364,257 @@ impl Scanner {
         Ok(())
     }
 
-    pub fn sync_links() -> Result<()> {
-        println!("Enforcing Spine configuration as authority for package links...");
+    /// Settle every name found more than once. Built output whose entry points
+    /// exist beats other built output, which beats source. When no candidate
+    /// stands out the user is asked, if `interactive` and there is a terminal;
+    /// otherwise the name is left unresolved.
+    fn resolve_duplicates(packages: &[DiscoveredPackage], interactive: bool) -> HashMap<String, DuplicateChoice> {
+        let mut groups: BTreeMap<&str, Vec<&DiscoveredPackage>> = BTreeMap::new();
+        for package in packages {
+            groups.entry(package.name.as_str()).or_default().push(package);
+        }
+
+        let mut choices = HashMap::new();
+        for (name, candidates) in groups.into_iter().filter(|(_, candidates)| candidates.len() > 1) {
+            let ranked: Vec<(u8, &DiscoveredPackage)> = candidates.iter()
+                .map(|package| {
+                    let valid = package.is_dist && crate::angular::missing_entry_points(&package.path).is_empty();
+                    (package.is_dist as u8 + valid as u8, *package)
+                })
+                .collect();
+            let best = ranked.iter().map(|(rank, _)| *rank).max().unwrap_or_default();
+            let top: Vec<&DiscoveredPackage> = ranked.iter().filter(|(rank, _)| *rank == best).map(|(_, p)| *p).collect();
+
+            let choice = if let [winner] = top.as_slice() {
+                DuplicateChoice {
+                    selected: Some(winner.path.clone()),
+                    reason: if best == 2 {
+                        "built output with its entry points present".to_string()
+                    } else {
+                        "built output (entry points missing; rebuild it)".to_string()
+                    },
+                }
+            } else {
+                let options: Vec<String> = candidates.iter()
+                    .map(|p| format!("{}{}", display_path(&p.path), if p.is_dist { " (dist)" } else { "" }))
+                    .collect();
+                let question = format!("\n❓ {} found in {} places; which should be linked?", name, candidates.len());
+                match interactive.then(|| crate::prompt::choose(&question, &options)).flatten() {
+                    Some(index) => DuplicateChoice {
+                        selected: Some(candidates[index].path.clone()),
+                        reason: "chosen at the prompt".to_string(),
+                    },
+                    None if interactive => DuplicateChoice {
+                        selected: None,
+                        reason: format!("{} candidates and none chosen; add the right one with 'spine add'", candidates.len()),
+                    },
+                    None => DuplicateChoice {
+                        selected: None,
+                        reason: "ambiguous; 'spine scan --add' asks which to use".to_string(),
+                    },
+                }
+            };
+            choices.insert(name.to_string(), choice);
+        }
+        choices
+    }
+
+    /// Restore configured links for the current project. With `dry_run` the same
+    /// analysis is made but nothing is linked and the config is not written.
+    /// With `json` nothing is printed; the caller renders the report.
+    /// What `spine sync` would change in the current project, for `--plan`
+    pub fn sync_plan(config: &Config) -> Result<crate::plan::Plan> {
+        let report = Self::analyze_sync(config, &std::env::current_dir()?, true)?;
+        Ok(report.plan(config))
+    }
+
+    /// Enforce the config in the current project. Unless `yes` (or `json`,
+    /// which is for tooling), the changes are confirmed first.
+    pub fn sync_links(config_file: &ConfigFile, dry_run: bool, json: bool, yes: bool) -> Result<SyncReport> {
+        if !json {
+            if dry_run {
+                println!("🧪 Dry run: no links will be changed and the configuration will not be written.");
+            }
+            println!("Enforcing Spine configuration as authority for package links...");
+        }
         
-        let mut config = Config::load_or_create()?;
+        let mut config = Config::load_or_create(config_file)?;
+        let current_dir = std::env::current_dir()?;
         
         if config.links.is_empty() {
-            println!("No packages configured to sync.");
-            return Ok(());
+            if !json {
+                println!("No packages configured to sync.");
+            }
+            return Ok(SyncReport { project: current_dir, dry_run, ..SyncReport::default() });
         }
-
-        let current_dir = std::env::current_dir()?;
         
-        // Check which configured packages should be linked to current project
-        let mut packages_to_restore = Vec::new();
-        let mut packages_already_linked = Vec::new();
-        let mut packages_not_configured_here = Vec::new();
+        let mut report = Self::analyze_sync(&config, &current_dir, dry_run)?;
+        if !json {
+            report.print_analysis();
+        }
         
-        for (package_name, package_link) in &config.links {
-            // Check if this package should be linked to the current project according to config
-            let should_be_linked = package_link.linked_projects.contains(&current_dir);
-            
-            if should_be_linked {
-                // Check if it's actually linked
-                let is_actually_linked = crate::config::Config::is_package_linked_in_project_static(package_name, &current_dir);
-                
-                if is_actually_linked {
-                    packages_already_linked.push(package_name.clone());
-                } else {
-                    packages_to_restore.push(package_name.clone());
-                }
-            } else {
-                packages_not_configured_here.push(package_name.clone());
+        if !report.has_work() {
+            if !json {
+                println!("\n✅ All configured packages are properly linked.");
             }
+            return Ok(report);
         }
         
-        // Report current state
-        println!("📊 Current state analysis:");
-        println!("  ✅ Already linked as configured: {}", packages_already_linked.len());
-        println!("  🔗 Need to restore links: {}", packages_to_restore.len());
-        println!("  📦 Not configured for this project: {}", packages_not_configured_here.len());
+        if dry_run {
+            if !json {
+                report.print_dry_run(&config);
+            }
+            return Ok(report);
+        }
         
-        if packages_to_restore.is_empty() {
-            println!("\n✅ All configured packages are properly linked.");
-            return Ok(());
+        if !json && !report.plan(&config).confirm(yes) {
+            println!("Cancelled; nothing was changed.");
+            return Ok(report);
+        }
+        Self::apply_sync_analysis(&mut config, &report)?;
+        if report.to_restore.is_empty() {
+            config.save()?;
+            return Ok(report);
+        }
+        
+        if let Some(problem) = crate::permissions::check_project(&current_dir) {
+            return Err(problem.into_error().into());
         }
         
         // Restore links that should exist according to configuration
-        println!("\n🔧 Restoring package links according to Spine configuration...");
-        let mut restored_count = 0;
-        let mut failed_packages = Vec::new();
+        if !json {
+            println!("\n🔧 Restoring package links according to Spine configuration...");
+        }
+        let journal = crate::journal::OperationJournal::begin(&config.file, "sync", &report.to_restore, &current_dir)?;
         
-        for package_name in &packages_to_restore {
-            let package_link = config.links.get(package_name).unwrap();
-            
-            print!("  🔗 Restoring link for {}... ", package_name);
-            
-            match crate::npm::NpmManager::npm_link_static(&package_link.path) {
-                Ok(_) => {
-                    // Verify the link was actually created
-                    if crate::config::Config::is_package_linked_in_project_static(package_name, &current_dir) {
-                        restored_count += 1;
-                        println!("✅ Success");
-                    } else {
-                        println!("❌ Failed (verification failed)");
-                        failed_packages.push(package_name.clone());
-                    }
-                }
-                Err(e) => {
-                    println!("❌ Failed ({})", e);
-                    failed_packages.push(package_name.clone());
-                }
+        let pm = crate::pm::PackageManager::resolve(None, config.package_manager, &current_dir);
+        let mut restores = crate::plan::Plan::new("sync", Some(current_dir.clone()));
+        for package_name in &report.to_restore {
+            restores.push("restore", package_name, None);
+        }
+        restores.quiet = json;
+        let outcome = match restores.execute(false, |action| {
+            let package_link = &config.links[&action.target].for_project(&current_dir);
+            if let Some(problem) = crate::permissions::check_package(&package_link.path).into_iter().next() {
+                return Err(problem.describe());
+            }
+            let link_pm = pm.for_link(package_link.package_manager_for(&current_dir));
+            link_pm.link_in(&current_dir, &package_link.path, config.timeouts.link()).map_err(|e| e.to_string())?;
+            // Verify the link was actually created
+            if !crate::config::Config::is_package_linked_in_project_static(package_link.npm_name(), &current_dir) {
+                return Err("verification failed".to_string());
+            }
+            crate::diff::record_snapshot(&config.file, &action.target, &package_link.path, "sync");
+            Ok(())
+        }) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                journal.finish(&config)?;
+                return Err(e);
             }
+        };
+        report.restored = outcome.completed;
+        report.failed = outcome.failed.into_iter().map(|(package, error)| FailedLink { package, error }).collect();
+        
+        journal.finish(&config)?;
+        if !json {
+            report.print_results();
+        }
+        if outcome.interrupted {
+            return Err(SpineError::Config("sync was interrupted".to_string()).into());
         }
+        Ok(report)
+    }
+
+    /// Work out what sync would do for `current_dir` without changing
+    /// anything. Pending links that would be promoted are judged as promoted.
+    pub fn analyze_sync(config: &Config, current_dir: &Path, dry_run: bool) -> Result<SyncReport> {
+        let mut report = SyncReport {
+            project: current_dir.to_path_buf(),
+            dry_run,
+            ..SyncReport::default()
+        };
+        let mut config = config.clone();
+        report.promoted = config.activate_pending().iter().map(crate::config::Promoted::label).collect();
+        report.metadata = config.refresh_versions();
         
-        // Summary
-        println!("\n📊 Sync Summary:");
-        println!("  ✅ Successfully restored: {}", restored_count);
-        if !failed_packages.is_empty() {
-            println!("  ❌ Failed to restore: {}", failed_packages.len());
-            for package in &failed_packages {
-                println!("    • {}", package);
+        let current_dir = current_dir.to_path_buf();
+        let overrides = WorkspaceManager::load_workspace_config(&current_dir)?.unwrap_or_default().sync;
+        
+        // Check which configured packages should be linked to current project
+        for (package_name, package_link) in &config.links {
+            let package_link = &package_link.for_project(&current_dir);
+            // Check if this package should be linked to the current project according to config
+            let should_be_linked = package_link.linked_projects.iter().any(|p| crate::paths::same_path(p, &current_dir));
+            // Check if it's actually linked
+            let is_actually_linked = crate::config::Config::is_package_linked_in_project_static(package_link.npm_name(), &current_dir);
+            
+            if !should_be_linked {
+                if is_actually_linked {
+                    report.added_missing_links.push(package_name.clone());
+                } else {
+                    report.not_configured_here.push(package_name.clone());
+                }
+                continue;
+            }
+            // Build output that is missing is mid-rebuild (ng clears dist
+            // first), so the project is kept rather than forgotten
+            let rebuilding = !package_link.path.exists() && WorkspaceManager::is_dist_dir(&package_link.path);
+            if package_link.pending || rebuilding {
+                report.skipped.push(SkippedLink { package: package_name.clone(), reason: crate::config::PENDING_MARKER.to_string() });
+                continue;
+            }
+            if let Some(reason) = overrides.skip_reason(package_name) {
+                report.skipped.push(SkippedLink { package: package_name.clone(), reason });
+                continue;
+            }
+            
+            if is_actually_linked {
+                report.already_linked.push(package_name.clone());
+            } else if !package_link.path.exists() {
+                report.removed_invalid_links.push(package_name.clone());
+            } else {
+                report.to_restore.push(package_name.clone());
             }
         }
         
-        if restored_count > 0 {
-            println!("\n✨ Spine configuration has been enforced. {} package(s) restored.", restored_count);
+        if let Ok(linked_packages) = crate::npm::NpmManager::get_linked_packages_static(&current_dir) {
+            report.untracked_links = linked_packages.into_iter()
+                .filter(|package_name| config.find_by_npm_name(package_name).is_none())
+                .collect();
         }
         
+        report.already_linked.sort();
+        report.to_restore.sort();
+        report.not_configured_here.sort();
+        report.skipped.sort();
+        report.removed_invalid_links.sort();
+        report.added_missing_links.sort();
+        report.untracked_links.sort();
+        Ok(report)
+    }
+
+    /// Make the config changes an analysis found: promote pending links,
+    /// refresh versions and correct which projects are recorded. Linking the
+    /// packages in `to_restore` is left to the caller.
+    fn apply_sync_analysis(config: &mut Config, report: &SyncReport) -> Result<()> {
+        config.activate_pending();
+        config.refresh_versions();
+        for package_name in &report.removed_invalid_links {
+            config.remove_linked_project(package_name, &report.project)?;
+        }
+        for package_name in &report.added_missing_links {
+            config.add_linked_project(package_name, report.project.clone())?;
+        }
         Ok(())
     }
 
-    pub fn open_config_editor() -> Result<()> {
-        let config_path = Config::config_path()?;
+    pub fn open_config_editor(config_file: &ConfigFile) -> Result<()> {
+        let config_path = config_file.path()?;
         
         if !config_path.exists() {
             println!("Configuration file doesn't exist yet. Creating it...");
-            let config = Config::default();
+            let config = Config::empty(config_file);
             config.save()?;
         }
 
@@ -182,9 +634,9 @@ impl Scanner {
                 continue;
             }
 
-            let result = Command::new(editor)
-                .arg(&config_path)
-                .status();
+            let mut command = Command::new(editor);
+            command.arg(&config_path);
+            let result = run_command(command, None, OutputMode::Stream).map(|output| output.status);
 
             match result {
                 Ok(status) => {
@@ -219,10 +671,15 @@ impl Scanner {
         Ok(())
     }
 
-    pub fn suggest_packages() -> Result<()> {
+    /// `spine suggest`: the local packages the current project depends on.
+    /// With `add` the unconfigured ones are added to the config; with `link`
+    /// they are added and then every suggested package is linked here.
+    pub fn suggest_packages(config: &mut Config, options: SuggestOptions) -> Result<()> {
         println!("Analyzing current project dependencies...");
         
-        let suggested = WorkspaceManager::suggest_packages_for_current_project()?;
+        let project = std::env::current_dir()?;
+        let ScanOutcome { packages, .. } = Self::discover(config, options.search_path.as_deref(), options.no_ignore, options.depth)?;
+        let suggested = WorkspaceManager::suggest_packages_for_current_project(packages)?;
         
         if suggested.is_empty() {
             println!("No local packages found that match your project's dependencies.");
@@ -230,21 +687,133 @@ impl Scanner {
             return Ok(());
         }
 
+        let project_info = crate::package::parse_package_json(&project.join("package.json"))?;
+        let writes = options.add || options.link;
+        let duplicates = Self::resolve_duplicates(&suggested, writes);
         println!("Found {} local package(s) that match your project dependencies:", suggested.len());
         
         for package in &suggested {
             let dist_indicator = if package.is_dist { " (dist)" } else { "" };
-            println!("  {} (v{}) -> {}{}", 
+            let field = project_info.dependency_field(&package.name).unwrap_or_default();
+            let range = project_info.listed_range(&package.name).map(|range| format!(" {}", range)).unwrap_or_default();
+            let configured = if config.find_by_npm_name(&package.name).is_some() { "  ← configured" } else { "" };
+            println!("  {} (v{}) -> {}{} [{}{}]{}", 
                 package.name, 
                 package.version, 
-                package.path.display(),
-                dist_indicator
+                display_path(&package.path),
+                dist_indicator,
+                field,
+                range,
+                configured
             );
         }
 
-        println!("\nUse 'spine link <package-name>' to link individual packages,");
-        println!("or 'spine scan --add' to add all discovered packages to your configuration.");
+        if !writes {
+            println!("\nUse --add to add them to your configuration, or --link to also link them into this project.");
+            return Ok(());
+        }
+
+        println!();
+        let mut plan = crate::plan::Plan::new("suggest --add", None);
+        let mut paths = HashMap::new();
+        let mut to_link = Vec::new();
+        for package in &suggested {
+            if let Some(choice) = duplicates.get(&package.name) {
+                if choice.selected.as_ref() != Some(&package.path) {
+                    if choice.selected.is_none() && suggested.iter().find(|p| p.name == package.name).is_some_and(|p| p.path == package.path) {
+                        println!("⚠️  Skipped {}: {}", package.name, choice.reason);
+                    }
+                    continue;
+                }
+            }
+            if let Some(link) = config.find_by_npm_name(&package.name) {
+                to_link.push(link.name.clone());
+                continue;
+            }
+            plan.push("add", &package.name, Some(display_path(&package.path)));
+            paths.insert(package.name.clone(), package.path.clone());
+        }
+
+        if !plan.is_empty() {
+            if !plan.confirm(options.yes) {
+                println!("Cancelled; nothing was added.");
+                return Ok(());
+            }
+            let outcome = plan.execute(false, |action| {
+                let path = &paths[&action.target];
+                config.add_link(action.target.clone(), path.to_string_lossy().to_string(), false)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            })?;
+            if !outcome.completed.is_empty() {
+                config.save()?;
+                println!("\nAdded {} package(s) to configuration.", outcome.completed.len());
+            }
+            to_link.extend(outcome.completed.iter().map(|target| config.link_name(target)));
+        } else {
+            println!("Every suggested package is already configured.");
+        }
+
+        if options.link && !to_link.is_empty() {
+            to_link.sort();
+            to_link.dedup();
+            println!();
+            let pm = PackageManager::resolve(options.pm, config.package_manager, &project);
+            let result = NpmManager::link_packages(config, &project, &to_link, pm, false);
+            config.save()?;
+            result?;
+        }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Links recorded in `project`, added while their paths existed
+    fn config_linking(project: &Path, paths: &[(&str, PathBuf)]) -> Config {
+        let mut config = Config::default();
+        for (name, path) in paths {
+            let mut link = crate::config::PackageLink::new(name.to_string(), path.clone());
+            link.pending = false;
+            link.linked_projects.push(project.to_path_buf());
+            config.links.insert(name.to_string(), link);
+        }
+        config
+    }
+
+    #[test]
+    fn sync_keeps_projects_of_build_output_that_is_rebuilding() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("app");
+        std::fs::create_dir_all(&project).unwrap();
+        let config = config_linking(&project, &[
+            ("ui", dir.path().join("ws").join("dist").join("ui")),
+            ("forms", dir.path().join("forms")),
+        ]);
+
+        let report = Scanner::analyze_sync(&config, &project, true).unwrap();
+        assert_eq!(report.skipped.len(), 1, "{:?}", report.skipped);
+        assert_eq!(report.skipped[0].package, "ui");
+        assert_eq!(report.skipped[0].reason, crate::config::PENDING_MARKER);
+        // A package source that is gone is still forgotten
+        assert_eq!(report.removed_invalid_links, ["forms"]);
+    }
+
+    #[test]
+    fn sync_preview_reads_the_analyzed_projects_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("app");
+        let ui = dir.path().join("ui");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir_all(&ui).unwrap();
+        std::fs::write(project.join(".spine.toml"), "[sync]\nexclude = [\"ui\"]\n").unwrap();
+        let config = config_linking(&project, &[("ui", ui)]);
+
+        let report = Scanner::analyze_sync(&config, &project, true).unwrap();
+        assert_eq!(report.skipped.len(), 1, "{:?}", report.skipped);
+        assert_eq!(report.skipped[0].reason, "sync.exclude \"ui\"");
+    }
+}
@@ -0,0 +1,556 @@
+use serde_json::{json, Map, Value};
+
+/// File `spine init` writes next to .spine.toml for editors to validate against
+pub const WORKSPACE_SCHEMA_FILE: &str = ".spine.schema.json";
+
+/// Which document a schema describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SchemaTarget {
+    /// A project's .spine.toml
+    Workspace,
+    /// The global config.toml
+    Config,
+}
+
+impl SchemaTarget {
+    pub fn schema(&self) -> Value {
+        match self {
+            SchemaTarget::Workspace => workspace_schema(),
+            SchemaTarget::Config => config_schema(),
+        }
+    }
+}
+
+/// JSON Schema for .spine.toml (`WorkspaceConfig`). Kept by hand; update it
+/// along with the serde structs.
+pub fn workspace_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Spine workspace configuration (.spine.toml)",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "auto_link": {
+                "description": "Which discovered packages `spine scan` suggests for this project",
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "enabled": { "type": "boolean", "default": false },
+                    "patterns": string_list("Package name patterns to include; `*` matches any run of characters"),
+                    "exclude": string_list("Package name patterns to leave out"),
+                },
+            },
+            "sync": {
+                "description": "Which configured packages `spine sync` enforces in this project",
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "exclude": string_list("Package name patterns sync leaves alone"),
+                    "include_only": string_list("When set, only packages matching one of these patterns are synced"),
+                },
+            },
+            "links": {
+                "description": "Package paths used instead of the global config's while Spine runs in this project; relative paths start from this file's directory",
+                "type": "object",
+                "additionalProperties": { "type": "string" },
+            },
+            "scan": {
+                "description": "Directories `spine scan` skips, on top of .gitignore and .ignore files",
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "exclude": string_list("Gitignore-style patterns relative to this file's directory; applied even with --no-ignore"),
+                },
+            },
+        },
+    })
+}
+
+/// JSON Schema for the global config.toml (`Config`)
+pub fn config_schema() -> Value {
+    let hooks = |when: &str| string_list(&format!("Shell commands run {}", when));
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Spine configuration (config.toml)",
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["links"],
+        "properties": {
+            "package_manager": package_manager("Package manager for link/unlink unless --pm is given; detected per project when unset"),
+            "track_versions": {
+                "description": "Update stored versions when link, status or the TUI finds a changed package.json",
+                "type": "boolean",
+                "default": false,
+            },
+            "links": {
+                "description": "Configured packages by name",
+                "type": "object",
+                "additionalProperties": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "required": ["name", "path"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "path": { "type": "string", "format": "path", "description": "Package directory (usually the built output)" },
+                        "version": { "type": "string" },
+                        "previous_version": { "type": "string", "description": "Version stored before the last refresh" },
+                        "default_variant": { "type": "string", "description": "Variant name of `path` when the package has several checkouts" },
+                        "variants": {
+                            "description": "The package's other checkouts by variant name",
+                            "type": "object",
+                            "additionalProperties": link_variant(),
+                        },
+                        "project_variants": {
+                            "description": "Variant chosen with --variant in each project, by project path",
+                            "type": "object",
+                            "additionalProperties": { "type": "string" },
+                        },
+                        "package_manager": package_manager("Package manager for this package, beating the global package_manager"),
+                        "project_package_managers": {
+                            "description": "Package manager for this package in particular projects, by project path",
+                            "type": "object",
+                            "additionalProperties": package_manager("Package manager in this project"),
+                        },
+                        "linked_projects": string_list("Projects the package is linked into"),
+                        "linked_at": {
+                            "description": "Unix timestamp of the last link into each project",
+                            "type": "object",
+                            "additionalProperties": { "type": "integer", "minimum": 0 },
+                        },
+                        "last_seen": {
+                            "description": "Unix timestamp of when status or sync last found each project on disk",
+                            "type": "object",
+                            "additionalProperties": { "type": "integer", "minimum": 0 },
+                        },
+                        "created_by_version": { "type": "string" },
+                        "created_at": { "type": "integer", "minimum": 0, "description": "Unix timestamp" },
+                        "pending": { "type": "boolean", "description": "Added before the path existed" },
+                        "npm_name": { "type": "string", "description": "package.json name when `name` is an alias" },
+                        "durable": {
+                            "description": "Projects linked through a package.json override",
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "additionalProperties": false,
+                                "required": ["project"],
+                                "properties": {
+                                    "project": { "type": "string" },
+                                    "dependency_field": { "type": "string" },
+                                    "original_range": { "type": "string" },
+                                },
+                            },
+                        },
+                        "required_by": {
+                            "description": "Projects the package was linked into only as a dependency of other linked packages",
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "additionalProperties": false,
+                                "required": ["project", "packages"],
+                                "properties": {
+                                    "project": { "type": "string" },
+                                    "packages": string_list("Linked packages there that depend on it"),
+                                },
+                            },
+                        },
+                        "repository": { "type": "string", "description": "Git URL `spine setup` clones when the path is missing" },
+                        "build_path": { "type": "string", "description": "Package directory inside a checkout of the repository" },
+                        "tags": { "type": "array", "items": { "type": "string" }, "description": "Labels for `--tag` filters on list, status and link-all" },
+                        "note": { "type": "string", "description": "Why the link exists, shown by list --detailed and info" },
+                        "pre_link": hooks("in the package directory before linking"),
+                        "post_link": hooks("in the project after linking"),
+                        "pre_unlink": hooks("in the package directory before unlinking"),
+                        "post_unlink": hooks("in the project after unlinking"),
+                    },
+                },
+            },
+            "groups": {
+                "description": "Named sets of packages linked and unlinked together",
+                "type": "object",
+                "additionalProperties": string_list("Member packages"),
+            },
+            "completion": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "auto_regenerate": { "type": "boolean" },
+                    "shell": { "type": "string" },
+                    "script_path": { "type": "string", "format": "path" },
+                },
+            },
+            "history": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "size_warn_percent": { "type": "number", "default": 10.0, "description": "Warn when a bundle grows by more than this percentage" },
+                },
+            },
+            "scan": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "roots": path_list("Directories `spine scan` walks when no --path is given"),
+                },
+            },
+            "timeouts": {
+                "description": "Limits for external commands in seconds; 0 disables a limit",
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "link_secs": { "type": "integer", "minimum": 0, "default": 120 },
+                    "build_secs": { "type": "integer", "minimum": 0, "default": 0 },
+                    "registry_secs": { "type": "integer", "minimum": 0, "default": 30 },
+                },
+            },
+            "build": {
+                "description": "Limits on the ng builds Spine starts, to keep the machine usable while they run",
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "max_concurrency": { "type": "integer", "minimum": 0, "default": 0, "description": "Builds running at once across build --all, serve's initial library builds and smoke; 0 for no limit" },
+                    "low_priority": { "type": "boolean", "default": false, "description": "Start builds with nice 10 on Unix, below normal priority on Windows" },
+                    "node_max_old_space_mb": { "type": "integer", "minimum": 1, "description": "Node heap cap for each build in MB, added to NODE_OPTIONS as --max-old-space-size" },
+                },
+            },
+            "licenses": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "deny": string_list("SPDX identifier prefixes `spine licenses` flags"),
+                },
+            },
+            "tui": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "auto_save": { "type": "boolean", "default": true },
+                    "simple": { "type": "boolean", "default": false, "description": "Line-based interactive mode for screen readers (also used when TERM=dumb)" },
+                },
+            },
+            "setup": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "source_root": { "type": "string", "format": "path", "description": "Where `spine setup` clones repositories (default ~/src)" },
+                },
+            },
+            "projects": {
+                "description": "Keeps throwaway checkouts out of linked_projects",
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "never_record": path_list("Path prefixes never recorded as linked projects, e.g. /tmp"),
+                    "expire_after_days": {
+                        "description": "Days a linked project may be missing before status or sync forgets it; 0 disables",
+                        "type": "integer",
+                        "minimum": 0,
+                        "default": 30,
+                    },
+                },
+            },
+            "workspace_search": {
+                "description": "Bounds on the upward search for angular.json and .spine.toml; it never crosses onto another filesystem",
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "max_levels": { "type": "integer", "minimum": 0, "default": 12, "description": "Parent directories to climb" },
+                    "stop_at_repository": { "type": "boolean", "default": true, "description": "Stop at a directory holding .git" },
+                },
+            },
+            "path_map": {
+                "description": "Roots holding the same repositories on different machines, e.g. \"/Users/me/dev\" = \"/home/me/dev\". Paths are stored under the first root and read from the second where only that exists.",
+                "type": "object",
+                "additionalProperties": { "type": "string", "format": "path" },
+            },
+            "usage_log": {
+                "description": "Opt-in local log of which commands run, for 'spine usage report'; never sent anywhere",
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "enabled": { "type": "boolean", "default": false, "description": "Append a line per command: its name, the options given (no values or arguments), duration and success" },
+                    "path": { "type": "string", "format": "path", "description": "Log file; usage.jsonl next to config.toml by default" },
+                },
+            },
+            "backups": {
+                "description": "Copies of config.toml and profile files taken before each save, for 'spine config restore'",
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "keep": { "type": "integer", "minimum": 0, "default": 20, "description": "Newest copies kept in backups/ next to config.toml; 0 turns them off" },
+                },
+            },
+            "active_profile": {
+                "description": "Profile (profiles/<name>.toml) whose links commands use; SPINE_PROFILE overrides it",
+                "type": "string",
+            },
+            "schema_version": {
+                "description": "Written by Spine; layout version of the file, upgraded automatically on load",
+                "type": "integer",
+                "minimum": 0,
+            },
+            "last_written_by": {
+                "description": "Written by Spine; which build last saved the config",
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["version", "os"],
+                "properties": {
+                    "version": { "type": "string" },
+                    "os": { "type": "string" },
+                    "hostname": { "type": "string" },
+                },
+            },
+        },
+    })
+}
+
+/// One entry of a link's `variants`
+fn link_variant() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["path"],
+        "properties": {
+            "path": { "type": "string", "format": "path", "description": "Package directory of this variant" },
+            "version": { "type": "string" },
+            "pending": { "type": "boolean", "description": "Added before the path existed" },
+        },
+    })
+}
+
+fn package_manager(description: &str) -> Value {
+    json!({ "description": description, "type": "string", "enum": ["npm", "pnpm", "yarn", "bun"] })
+}
+
+fn string_list(description: &str) -> Value {
+    json!({ "description": description, "type": "array", "items": { "type": "string" } })
+}
+
+/// A list of paths; `spine config set` resolves relative ones
+fn path_list(description: &str) -> Value {
+    json!({ "description": description, "type": "array", "items": { "type": "string", "format": "path" } })
+}
+
+/// Check a document against one of the schemas above, returning a message
+/// per violation. Supports the keywords they use: type, enum, properties,
+/// additionalProperties, required, items and minimum.
+pub fn validate(document: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(document, schema, "", &mut errors);
+    errors
+}
+
+fn check(value: &Value, schema: &Value, at: &str, errors: &mut Vec<String>) {
+    let location = if at.is_empty() { "(root)" } else { at };
+
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        if !has_type(value, expected) {
+            errors.push(format!("{}: expected {}, found {}", location, expected, type_name(value)));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            errors.push(format!("{}: expected one of {}, found {}", location, allowed.join(", "), value));
+        }
+    }
+    if let (Some(minimum), Some(number)) = (schema.get("minimum").and_then(Value::as_f64), value.as_f64()) {
+        if number < minimum {
+            errors.push(format!("{}: must be at least {}", location, minimum));
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            check(item, items, &format!("{}[{}]", at, index), errors);
+        }
+    }
+    if let Some(object) = value.as_object() {
+        check_object(object, schema, at, location, errors);
+    }
+}
+
+fn check_object(object: &Map<String, Value>, schema: &Value, at: &str, location: &str, errors: &mut Vec<String>) {
+    for required in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+        if !object.contains_key(required) {
+            errors.push(format!("{}: missing required key \"{}\"", location, required));
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (key, value) in object {
+        let path = if at.is_empty() { key.clone() } else { format!("{}.{}", at, key) };
+        match (properties.and_then(|p| p.get(key)), schema.get("additionalProperties")) {
+            (Some(property), _) => check(value, property, &path, errors),
+            (None, Some(Value::Bool(false))) => errors.push(format!("{}: unknown key", path)),
+            (None, Some(additional)) if additional.is_object() => check(value, additional, &path, errors),
+            (None, _) => {}
+        }
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// One line per key with its description, for `spine config schema`
+pub fn describe(schema: &Value) -> Vec<String> {
+    let mut lines = Vec::new();
+    describe_into(schema, "", &mut lines);
+    lines
+}
+
+fn describe_into(schema: &Value, at: &str, lines: &mut Vec<String>) {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        if let Some(additional) = schema.get("additionalProperties").filter(|a| a.is_object()) {
+            describe_into(additional, &format!("{}.<name>", at), lines);
+        }
+        return;
+    };
+
+    for (key, property) in properties {
+        let path = if at.is_empty() { key.clone() } else { format!("{}.{}", at, key) };
+        let kind = property.get("type").and_then(Value::as_str).unwrap_or("any");
+        match property.get("description").and_then(Value::as_str) {
+            Some(description) => lines.push(format!("{} ({}): {}", path, kind, description)),
+            None => lines.push(format!("{} ({})", path, kind)),
+        }
+        describe_into(property, &path, lines);
+    }
+}
+
+/// Check a TOML file against the schema for `target`. A file that isn't
+/// valid TOML is reported as a single violation.
+pub fn check_file(path: &std::path::Path, target: SchemaTarget) -> anyhow::Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)?;
+    let document: toml::Value = match toml::from_str(&content) {
+        Ok(document) => document,
+        Err(e) => return Ok(vec![format!("not valid TOML: {}", e.message().trim().replace('\n', "; "))]),
+    };
+    Ok(validate(&serde_json::to_value(document)?, &target.schema()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::PathBuf;
+    use crate::config::*;
+    use crate::pm::PackageManager;
+    use crate::workspace::{AutoLinkConfig, ScanExclusions, SyncOverrides, WorkspaceConfig};
+
+    fn as_json(toml: &str) -> Value {
+        serde_json::to_value(toml::from_str::<toml::Table>(toml).unwrap()).unwrap()
+    }
+
+    // The struct literals below name every field, so a field added to the
+    // serde structs doesn't compile here until it is set, and then fails
+    // the test until the schema knows it
+
+    fn full_link() -> PackageLink {
+        let project = PathBuf::from("/work/app");
+        PackageLink {
+            name: "ui".to_string(),
+            path: PathBuf::from("/work/ui/dist"),
+            version: Some("2.0.0".to_string()),
+            default_variant: Some("main".to_string()),
+            variants: BTreeMap::from([("ng17".to_string(), LinkVariant {
+                path: PathBuf::from("/work/ui-ng17/dist"),
+                version: Some("1.9.0".to_string()),
+                pending: true,
+                path_spec: Some("~/ui-ng17/dist".to_string()),
+            })]),
+            project_variants: BTreeMap::from([(project.clone(), "ng17".to_string())]),
+            package_manager: Some(PackageManager::Pnpm),
+            project_package_managers: BTreeMap::from([(project.clone(), PackageManager::Yarn)]),
+            previous_version: Some("1.0.0".to_string()),
+            linked_projects: vec![project.clone()],
+            linked_at: BTreeMap::from([(project.clone(), 1_700_000_000)]),
+            last_seen: BTreeMap::from([(project.clone(), 1_700_000_100)]),
+            created_by_version: Some("0.1.0".to_string()),
+            created_at: Some(1_690_000_000),
+            pending: true,
+            npm_name: Some("@acme/ui".to_string()),
+            durable: vec![DurableLink {
+                project: project.clone(),
+                dependency_field: Some("dependencies".to_string()),
+                original_range: Some("^1.0.0".to_string()),
+            }],
+            pre_link: vec!["npm run build".to_string()],
+            post_link: vec!["echo linked".to_string()],
+            pre_unlink: vec!["echo unlinking".to_string()],
+            post_unlink: vec!["npm install".to_string()],
+            required_by: vec![RequiredBy { project, packages: vec!["forms".to_string()] }],
+            repository: Some("https://example.com/ui.git".to_string()),
+            build_path: Some("dist/ui".to_string()),
+            tags: vec!["design".to_string()],
+            note: Some("Pinned for the redesign".to_string()),
+            path_spec: Some("~/work/ui/dist".to_string()),
+        }
+    }
+
+    #[test]
+    fn a_fully_populated_config_matches_its_schema() {
+        let mut config = Config::default();
+        config.package_manager = Some(PackageManager::Npm);
+        config.links.insert("ui".to_string(), full_link());
+        config.groups = HashMap::from([("design".to_string(), vec!["ui".to_string()])]);
+        config.track_versions = true;
+        config.completion = CompletionConfig { auto_regenerate: true, shell: Some("zsh".to_string()), script_path: Some(PathBuf::from("/work/_spine")) };
+        config.history = HistoryConfig { size_warn_percent: 5.0 };
+        config.scan = ScanConfig { roots: vec![PathBuf::from("/work")] };
+        config.timeouts = TimeoutConfig { link_secs: 60, build_secs: 600, registry_secs: 10 };
+        config.build = BuildConfig { max_concurrency: 2, low_priority: true, node_max_old_space_mb: Some(4096) };
+        config.licenses = LicenseConfig { deny: vec!["GPL".to_string()] };
+        config.tui = TuiConfig { auto_save: false, simple: true };
+        config.setup = SetupConfig { source_root: Some(PathBuf::from("/src")) };
+        config.projects = ProjectsConfig { never_record: vec![PathBuf::from("/tmp")], expire_after_days: 7 };
+        config.workspace_search = WorkspaceSearchConfig { max_levels: 4, stop_at_repository: false };
+        config.path_map = serde_json::from_value(json!({ "/Users/me/work": "/work" })).unwrap();
+        config.usage_log = UsageLogConfig { enabled: true, path: Some(PathBuf::from("/work/usage.jsonl")) };
+        config.backups = BackupConfig { keep: 3 };
+        config.active_profile = Some("client".to_string());
+        config.last_written_by = Some(WriterStamp { version: "0.1.0".to_string(), os: "linux".to_string(), hostname: Some("box".to_string()) });
+
+        let document = as_json(&config.to_toml_string().unwrap());
+        assert_eq!(validate(&document, &config_schema()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn a_fully_populated_workspace_config_matches_its_schema() {
+        let workspace = WorkspaceConfig {
+            auto_link: AutoLinkConfig { patterns: vec!["@acme/*".to_string()], exclude: vec!["@acme/legacy".to_string()], enabled: true },
+            sync: SyncOverrides { exclude: vec!["ui".to_string()], include_only: vec!["@acme/*".to_string()] },
+            links: BTreeMap::from([("ui".to_string(), "../ui/dist".to_string())]),
+            scan: ScanExclusions { exclude: vec!["fixtures/".to_string()] },
+        };
+
+        let document = as_json(&toml::to_string(&workspace).unwrap());
+        assert_eq!(validate(&document, &workspace_schema()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unknown_keys_are_reported() {
+        let document = as_json("[links.ui]\nname = \"ui\"\npath = \"/work/ui\"\nlinked_project = []\n");
+        assert_eq!(validate(&document, &config_schema()).len(), 1);
+    }
+}
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use anyhow::Result;
+use crate::config::Config;
+use crate::error::SpineError;
+use crate::npm::NpmManager;
+use crate::paths::{display_path, relative_path};
+use crate::platform::{run_command, OutputMode};
+use crate::pm::PackageManager;
+
+/// The repository a package directory was built from and the package's path
+/// inside it: the package.json `repository` field, falling back to the
+/// checkout's origin remote. Only reads local files and git metadata.
+pub fn detect_source(package_dir: &Path) -> (Option<String>, Option<String>) {
+    let declared = crate::package::parse_package_metadata(&package_dir.join("package.json")).ok()
+        .and_then(|metadata| metadata.repository)
+        .and_then(|repository| normalize_repository(&repository));
+    let toplevel = git_output(package_dir, &["rev-parse", "--show-toplevel"]).map(PathBuf::from);
+    let repository = declared.or_else(|| git_output(package_dir, &["remote", "get-url", "origin"]));
+
+    let build_path = toplevel
+        .and_then(|toplevel| toplevel.canonicalize().ok())
+        .zip(package_dir.canonicalize().ok())
+        .map(|(toplevel, dir)| relative_path(&dir, &toplevel))
+        .filter(|relative| relative != ".");
+    (repository, build_path)
+}
+
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !text.is_empty()).then_some(text)
+}
+
+/// A cloneable URL from a package.json `repository` value such as
+/// `git+https://…`, `github:owner/repo` or the bare `owner/repo` shorthand
+fn normalize_repository(repository: &str) -> Option<String> {
+    let repository = repository.trim();
+    if repository.is_empty() {
+        return None;
+    }
+    if let Some(url) = repository.strip_prefix("git+") {
+        return Some(url.to_string());
+    }
+    let shorthand = repository.strip_prefix("github:").unwrap_or(repository);
+    let is_shorthand = !shorthand.contains(':') && shorthand.matches('/').count() == 1;
+    if is_shorthand {
+        return Some(format!("https://github.com/{}.git", shorthand));
+    }
+    Some(repository.to_string())
+}
+
+/// Directory a clone of `repository` gets under the source root
+fn checkout_name(repository: &str) -> String {
+    let last = repository.trim_end_matches('/').rsplit(['/', ':']).next().unwrap_or(repository);
+    last.trim_end_matches(".git").to_string()
+}
+
+/// `spine setup <pkg>`: when the package's path is missing, clone its
+/// repository under the source root, install and build it, point the config
+/// at the result and link it into the current project. Nothing touches the
+/// network without confirmation (or --yes); --dry-run only shows the plan.
+pub fn setup_command(config: &mut Config, package_name: &str, source_root: Option<&Path>, dry_run: bool, yes: bool, pm: Option<PackageManager>) -> Result<()> {
+    let link = config.links.get(package_name).cloned().ok_or_else(|| {
+        let available: Vec<String> = config.links.keys().cloned().collect();
+        SpineError::package_not_found_with_suggestions(package_name, &available)
+    })?;
+    let project = std::env::current_dir()?;
+    let link_here = project.join("package.json").exists();
+
+    if link.path.join("package.json").exists() {
+        println!("✓ {} is already present at {}", package_name, display_path(&link.path));
+        if link_here && !dry_run {
+            let pm = PackageManager::resolve(pm, config.package_manager, &project);
+            NpmManager::link_package(config, &project, package_name, pm, false)?;
+        }
+        return Ok(());
+    }
+
+    let repository = link.repository.clone().ok_or_else(|| SpineError::Config(format!(
+        "{} is missing at {} and has no repository to clone. Set one with 'spine add' on a machine that has it, or add `repository` to its config entry.",
+        package_name, display_path(&link.path)
+    )))?;
+    let source_root = match source_root.map(Path::to_path_buf).or_else(|| config.setup.source_root.clone()) {
+        Some(root) => root,
+        None => dirs::home_dir().ok_or_else(|| SpineError::Config("Could not find the home directory".to_string()))?.join("src"),
+    };
+    let checkout = source_root.join(checkout_name(&repository));
+    let package_dir = match &link.build_path {
+        Some(build_path) => crate::paths::resolve_relative(&checkout, build_path),
+        None => checkout.clone(),
+    };
+
+    let mut plan = crate::plan::Plan::new(&format!("setup {}", package_name), None);
+    if checkout.exists() {
+        plan.push("use checkout", &display_path(&checkout), Some("already cloned".to_string()));
+    } else {
+        plan.push("clone", &repository, Some(format!("into {}", display_path(&checkout))));
+    }
+    plan.push("install", &display_path(&checkout), None);
+    plan.push("build", &display_path(&checkout), Some("its build script, if it has one".to_string()));
+    plan.push("configure", package_name, Some(format!("path {}", display_path(&package_dir))));
+    if link_here {
+        plan.push("link", package_name, Some(format!("into {}", display_path(&project))));
+    }
+
+    if dry_run {
+        plan.render();
+        println!("\n🧪 Dry run: nothing was cloned, built or linked.");
+        return Ok(());
+    }
+    plan.render();
+    if !yes && !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        return Err(SpineError::Config("setup clones and builds code; pass --yes to run it without a terminal".to_string()).into());
+    }
+    if !yes && !crate::prompt::confirm("Run these steps?", false) {
+        println!("Cancelled; nothing was changed.");
+        return Ok(());
+    }
+
+    let checkout_pm = || PackageManager::resolve(pm, None, &checkout).manager;
+    let outcome = plan.execute(true, |action| {
+        match action.action.as_str() {
+            "clone" => {
+                println!("\n📥 Cloning {}...", repository);
+                std::fs::create_dir_all(&source_root).map_err(|e| e.to_string())?;
+                let mut command = Command::new("git");
+                command.arg("clone").arg(&repository).arg(&checkout);
+                check(run_command(command, None, OutputMode::Stream), "git clone")
+            }
+            "install" => {
+                let pm = checkout_pm();
+                println!("\n📦 Installing dependencies with {}...", pm);
+                let mut command = pm.command();
+                command.current_dir(&checkout).arg("install");
+                check(run_command(command, None, OutputMode::Stream), &format!("{} install", pm))
+            }
+            "build" => {
+                if !crate::package::has_script(&checkout.join("package.json"), "build") {
+                    println!("\n⏭️  No build script; using the checkout as is");
+                    return Ok(());
+                }
+                let pm = checkout_pm();
+                println!("\n🔨 Building with {} run build...", pm);
+                let mut command = pm.command();
+                command.current_dir(&checkout).args(["run", "build"]);
+                check(run_command(command, config.timeouts.build(), OutputMode::Stream), &format!("{} run build", pm))
+            }
+            "configure" => {
+                if !package_dir.join("package.json").exists() {
+                    return Err(format!("no package.json at {} after building", display_path(&package_dir)));
+                }
+                let entry = config.links.get_mut(package_name).expect("checked above");
+                entry.path = package_dir.clone();
+                entry.pending = true;
+                config.activate_pending();
+                Ok(())
+            }
+            "link" => {
+                let pm = PackageManager::resolve(pm, config.package_manager, &project);
+                NpmManager::link_package(config, &project, package_name, pm, false).map_err(|e| e.to_string())
+            }
+            _ => Ok(()),
+        }
+    })?;
+    config.save()?;
+
+    if let Some((step, error)) = outcome.failed.first() {
+        return Err(SpineError::Config(format!("setup stopped at {}: {}", step, error.trim())).into());
+    }
+    if outcome.interrupted {
+        return Err(SpineError::Config("setup was interrupted".to_string()).into());
+    }
+    println!("\n✨ {} is set up at {}", package_name, display_path(&package_dir));
+    Ok(())
+}
+
+fn check(output: Result<crate::platform::CommandOutput>, what: &str) -> Result<(), String> {
+    match output {
+        Ok(output) if output.success() => Ok(()),
+        Ok(_) => Err(format!("{} failed", what)),
+        Err(e) => Err(e.to_string()),
+    }
+}
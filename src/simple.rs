@@ -0,0 +1,176 @@
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use anyhow::Result;
+use crate::config::Config;
+use crate::paths::display_path;
+use crate::pm::PackageManager;
+use crate::tui::{self, HealthStatus, LinkStatus};
+
+/// `spine interactive --simple`: the TUI's add/remove/link/unlink/status as
+/// numbered, line-based prompts. No alternate screen or raw mode, so screen
+/// readers and dumb terminals can follow every line.
+pub struct SimpleApp {
+    config: Config,
+    project: PathBuf,
+    /// Changes not yet written, when `tui.auto_save` is off
+    dirty: bool,
+}
+
+const MENU: [&str; 6] = ["status", "add", "remove", "link", "unlink", "quit"];
+
+/// Whether `spine` without a subcommand should use the line-based mode
+pub fn preferred(config: &Config) -> bool {
+    config.tui.simple || std::env::var("TERM").is_ok_and(|term| term == "dumb")
+}
+
+impl SimpleApp {
+    pub fn new(config: Config) -> Result<Self> {
+        Ok(Self { config, project: std::env::current_dir()?, dirty: false })
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        println!("Spine interactive, simple mode. Project: {}", display_path(&self.project));
+        println!("Enter a number or command name. Enter q to quit.");
+
+        loop {
+            println!();
+            for (index, item) in MENU.iter().enumerate() {
+                println!("{}) {}", index + 1, item);
+            }
+            let Some(answer) = read_line("Command: ") else { break };
+            let command = match answer.parse::<usize>() {
+                Ok(choice) => MENU.get(choice.wrapping_sub(1)).copied().unwrap_or(""),
+                Err(_) => MENU.iter().copied().find(|item| *item == answer || (answer.len() == 1 && item.starts_with(&answer))).unwrap_or(""),
+            };
+            let outcome = match command {
+                "status" => { self.show_status(); Ok(()) }
+                "add" => self.add(),
+                "remove" => self.remove(),
+                "link" => self.link(),
+                "unlink" => self.unlink(),
+                "quit" => break,
+                _ => { println!("Unknown command: {}", answer); Ok(()) }
+            };
+            if let Err(e) = outcome {
+                println!("Error: {}", e);
+            }
+        }
+
+        if self.dirty && confirm("Save changes before quitting?", true) {
+            self.config.save()?;
+            println!("Saved.");
+        }
+        Ok(())
+    }
+
+    fn show_status(&self) {
+        if self.config.links.is_empty() {
+            println!("No packages configured. Use add to configure one.");
+            return;
+        }
+        println!("{} package(s):", self.config.links.len());
+        for (index, link) in self.config.links.values().enumerate() {
+            let linked = match tui::link_status(&self.project, link.npm_name()) {
+                LinkStatus::Linked => "linked",
+                LinkStatus::Unlinked => "not linked",
+                LinkStatus::Unknown => "broken link",
+                LinkStatus::Pnp => "PnP project, link with --durable",
+            };
+            let health = match tui::package_health(link, &self.project, self.config.package_manager) {
+                HealthStatus::Healthy => "healthy".to_string(),
+                HealthStatus::Pending => "pending, path not created yet".to_string(),
+                HealthStatus::Warning(reason) => format!("warning, {}", reason),
+                HealthStatus::Broken(reason) => format!("broken, {}", reason),
+            };
+            println!("{}) {}{}: {}, {}. Path {}", index + 1, link.name, link.variant_label(&self.project), linked, health,
+                display_path(&link.for_project(&self.project).path));
+        }
+    }
+
+    fn add(&mut self) -> Result<()> {
+        let Some(name) = read_line("Package name (empty to cancel): ").filter(|name| !name.is_empty()) else { return Ok(()) };
+        let Some(path) = read_line("Package path (empty to cancel): ").filter(|path| !path.is_empty()) else { return Ok(()) };
+        tui::add_package(&mut self.config, name.clone(), path)?;
+        println!("Added {}.", name);
+        self.changed()
+    }
+
+    fn remove(&mut self) -> Result<()> {
+        let Some(name) = self.pick_package("Remove which package?") else { return Ok(()) };
+        if !confirm(&format!("Remove {} from the configuration?", name), false) {
+            return Ok(());
+        }
+        tui::remove_package(&mut self.config, &name)?;
+        println!("Removed {}.", name);
+        self.changed()
+    }
+
+    fn link(&mut self) -> Result<()> {
+        let Some(name) = self.pick_package("Link which package?") else { return Ok(()) };
+        let pm = PackageManager::resolve(None, self.config.package_manager, &self.project);
+        tui::link_package(&mut self.config, &name, &self.project, pm)?;
+        self.changed()
+    }
+
+    fn unlink(&mut self) -> Result<()> {
+        let Some(name) = self.pick_package("Unlink which package?") else { return Ok(()) };
+        let restore = confirm("Reinstall the registry version afterwards?", false);
+        let pm = PackageManager::resolve(None, self.config.package_manager, &self.project);
+        tui::unlink_package(&mut self.config, &name, &self.project, pm, restore)?;
+        self.changed()
+    }
+
+    /// Same as the TUI: write straight away unless auto-save is off
+    fn changed(&mut self) -> Result<()> {
+        self.dirty = true;
+        if self.config.tui.auto_save {
+            self.config.save()?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+
+    /// Numbered list of packages; None when cancelled or out of range
+    fn pick_package(&self, question: &str) -> Option<String> {
+        let names: Vec<&String> = self.config.links.keys().collect();
+        if names.is_empty() {
+            println!("No packages configured.");
+            return None;
+        }
+        println!("{}", question);
+        for (index, name) in names.iter().enumerate() {
+            let linked = tui::link_status(&self.project, self.config.links[*name].npm_name()) == LinkStatus::Linked;
+            println!("{}) {}{}", index + 1, name, if linked { ", linked" } else { "" });
+        }
+        let answer = read_line(&format!("Package number 1 to {} (empty to cancel): ", names.len()))?;
+        let picked = answer.parse::<usize>().ok()
+            .and_then(|choice| names.get(choice.wrapping_sub(1)))
+            .map(|name| name.to_string());
+        if picked.is_none() && !answer.is_empty() {
+            println!("No package numbered {}.", answer);
+        }
+        picked
+    }
+}
+
+/// A trimmed line from stdin; None at end of input
+fn read_line(prompt: &str) -> Option<String> {
+    print!("{}", prompt);
+    let _ = io::stdout().flush();
+    let mut line = String::new();
+    match io::stdin().lock().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
+
+/// A y/n question read as a line. Unlike `prompt::confirm` this asks even
+/// when stdin isn't a terminal, since the whole session is driven from stdin.
+fn confirm(question: &str, default: bool) -> bool {
+    let hint = if default { "yes or no, default yes" } else { "yes or no, default no" };
+    match read_line(&format!("{} ({}): ", question, hint)).as_deref().map(str::to_lowercase).as_deref() {
+        Some("y" | "yes") => true,
+        Some("n" | "no") => false,
+        _ => default,
+    }
+}
@@ -0,0 +1,312 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+use anyhow::Result;
+use serde::Serialize;
+use crate::angular::AngularBuildManager;
+use crate::config::{Config, ConfigFile, PackageLink};
+use crate::error::SpineError;
+use crate::history::{self, BuildHistory, SmokeRecord};
+use crate::npm::NpmManager;
+use crate::paths::display_path;
+use crate::platform::{run_command, OutputMode, Platform};
+use crate::pm::PackageManager;
+
+/// Compiler errors shown before the rest are only counted
+const SHOWN_ERRORS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StageStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// One step of a smoke test: "build", "link" or "compile"
+#[derive(Debug, Clone, Serialize)]
+pub struct Stage {
+    pub stage: &'static str,
+    pub status: StageStatus,
+    pub duration_ms: u64,
+    pub detail: Option<String>,
+}
+
+impl Stage {
+    fn new(stage: &'static str, status: StageStatus, started: Instant, detail: Option<String>) -> Self {
+        Self { stage, status, duration_ms: started.elapsed().as_millis() as u64, detail }
+    }
+}
+
+/// An error line from the consuming project's compiler
+#[derive(Debug, Clone, Serialize)]
+pub struct CompilerError {
+    pub message: String,
+    /// Names the linked package or a file in it, so the change likely caused it
+    pub mentions_package: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SmokeReport {
+    pub package: String,
+    pub project: PathBuf,
+    pub passed: bool,
+    pub stages: Vec<Stage>,
+    /// The first compiler errors
+    pub errors: Vec<CompilerError>,
+    pub total_errors: usize,
+    pub duration_ms: u64,
+}
+
+impl SmokeReport {
+    fn failed_stage(&self) -> Option<&'static str> {
+        self.stages.iter().find(|stage| stage.status == StageStatus::Failed).map(|stage| stage.stage)
+    }
+}
+
+/// `spine smoke <package>`: build the library, relink it into a consuming
+/// project and compile that project, stopping at the first failing stage.
+/// The result goes to the history; a failure is an error, so the command
+/// works as a pre-push hook.
+pub fn smoke_command(config: &mut Config, package: &str, app: Option<&Path>, json: bool) -> Result<()> {
+    // Stage output goes to stderr so stdout carries only the report
+    let mut out = if json { Some(crate::api::take_stdout()?) } else { None };
+    let link = config.links.get(package).cloned().ok_or_else(|| {
+        let available: Vec<String> = config.links.keys().cloned().collect();
+        SpineError::package_not_found_with_suggestions(package, &available)
+    })?;
+    let project = consuming_project(&link, app)?;
+
+    println!("💨 Smoke testing {} in {}", package, display_path(&project));
+    let started = Instant::now();
+    let mut report = SmokeReport {
+        package: package.to_string(),
+        project: project.clone(),
+        passed: false,
+        stages: Vec::new(),
+        errors: Vec::new(),
+        total_errors: 0,
+        duration_ms: 0,
+    };
+
+    println!("\n🔨 Building {}", package);
+    report.stages.push(build_stage(config, &link));
+    if report.failed_stage().is_none() {
+        println!("\n🔗 Relinking into {}", display_path(&project));
+        report.stages.push(link_stage(config, package, &project));
+    }
+    if report.failed_stage().is_none() {
+        println!("\n🧪 Compiling {}", display_path(&project));
+        let (stage, errors) = compile_stage(config, &link, &project, json);
+        report.stages.push(stage);
+        report.total_errors = errors.len();
+        report.errors = errors.into_iter().take(SHOWN_ERRORS).collect();
+    }
+    report.passed = report.failed_stage().is_none();
+    report.duration_ms = started.elapsed().as_millis() as u64;
+    record_smoke(&config.file, &report);
+
+    match out.as_mut() {
+        Some(out) => {
+            use std::io::Write;
+            writeln!(out, "{}", serde_json::to_string_pretty(&report)?)?;
+        }
+        None => print_report(&report, &link),
+    }
+    match report.failed_stage() {
+        Some(stage) => Err(SpineError::Config(format!("Smoke test of {} failed at the {} stage", package, stage)).into()),
+        None => Ok(()),
+    }
+}
+
+/// `--app`, or the project the package is linked into. The current
+/// directory wins when it is one of several; otherwise the user picks.
+fn consuming_project(link: &PackageLink, app: Option<&Path>) -> Result<PathBuf> {
+    if let Some(app) = app {
+        return NpmManager::resolve_project_dir(app);
+    }
+
+    let current_dir = std::env::current_dir()?;
+    let mut consumers: Vec<&PathBuf> = Vec::new();
+    for project in link.linked_projects.iter().chain(link.durable.iter().map(|durable| &durable.project)) {
+        if project.exists() && !consumers.iter().any(|known| crate::paths::same_path(known, project)) {
+            consumers.push(project);
+        }
+    }
+
+    if let Some(project) = consumers.iter().find(|project| crate::paths::same_path(project, &current_dir)) {
+        return Ok((*project).clone());
+    }
+    match consumers.as_slice() {
+        [project] => return Ok((*project).clone()),
+        [] => {
+            // Not linked anywhere yet: the current project, unless it is the library's own
+            if NpmManager::is_npm_project(&current_dir)? && !link.path.starts_with(&current_dir) {
+                return Ok(current_dir);
+            }
+            return Err(SpineError::Config(format!(
+                "{} isn't linked into any project. Pass --app <project> to choose one", link.name
+            )).into());
+        }
+        _ => {}
+    }
+
+    let choices: Vec<String> = consumers.iter().map(|project| display_path(project)).collect();
+    match crate::prompt::choose(&format!("{} is linked into several projects. Smoke test which one?", link.name), &choices) {
+        Some(index) => Ok(consumers[index].clone()),
+        None => Err(SpineError::Config(format!(
+            "{} is linked into several projects; pass --app with one of: {}", link.name, choices.join(", ")
+        )).into()),
+    }
+}
+
+/// Build the library the package comes from. Packages outside an Angular
+/// workspace are linked as they are.
+fn build_stage(config: &Config, link: &PackageLink) -> Stage {
+    let started = Instant::now();
+    let manager = match AngularBuildManager::new_from_linked_package(config.clone(), &link.name) {
+        Ok(manager) => manager,
+        Err(e) => return Stage::new("build", StageStatus::Failed, started, Some(e.to_string())),
+    };
+    let Some((library, _)) = manager.resolve_library_root(&link.name) else {
+        println!("⏭️  {} isn't a library of an Angular workspace; linking it as it is", link.name);
+        return Stage::new("build", StageStatus::Skipped, started, Some("not built by an Angular workspace".to_string()));
+    };
+
+    match manager.build_library(&library, false) {
+        Ok(result) if result.success => Stage::new("build", StageStatus::Passed, started, None),
+        Ok(result) => {
+            let error = result.error.as_deref().map(str::trim).filter(|error| !error.is_empty()).unwrap_or("build failed");
+            Stage::new("build", StageStatus::Failed, started, Some(error.to_string()))
+        }
+        Err(e) => Stage::new("build", StageStatus::Failed, started, Some(e.to_string())),
+    }
+}
+
+/// Relink the package into `project`, as `spine relink` would there
+fn link_stage(config: &mut Config, package: &str, project: &Path) -> Stage {
+    let started = Instant::now();
+    let result = std::env::set_current_dir(project).map_err(anyhow::Error::from).and_then(|_| {
+        let pm = PackageManager::resolve(None, config.package_manager, project);
+        NpmManager::relink_package(config, package, pm)?;
+        config.save()
+    });
+    match result {
+        Ok(()) => {
+            println!("✓ Linked: {}", package);
+            Stage::new("link", StageStatus::Passed, started, None)
+        }
+        Err(e) => Stage::new("link", StageStatus::Failed, started, Some(e.to_string())),
+    }
+}
+
+/// Compile `project` without serving it: a development `ng build` in an
+/// Angular workspace, otherwise `tsc --noEmit`
+fn compile_stage(config: &Config, link: &PackageLink, project: &Path, json: bool) -> (Stage, Vec<CompilerError>) {
+    let started = Instant::now();
+    let Some((mut command, label)) = compile_command(project) else {
+        println!("⏭️  Nothing to compile with: {} has no angular.json or tsconfig.json", display_path(project));
+        return (Stage::new("compile", StageStatus::Skipped, started, Some("no angular.json or tsconfig.json".to_string())), Vec::new());
+    };
+    command.current_dir(project);
+    crate::throttle::prepare(&mut command, &config.build);
+    println!("Running {}", label);
+    let _slot = crate::throttle::acquire(&config.build);
+
+    let mode = if json { OutputMode::Capture } else { OutputMode::Progress("Compiling...") };
+    let output = match run_command(command, config.timeouts.build(), mode) {
+        Ok(output) => output,
+        Err(e) => return (Stage::new("compile", StageStatus::Failed, started, Some(e.to_string())), Vec::new()),
+    };
+    let errors = compiler_errors(&format!("{}\n{}", output.stdout, output.stderr), link);
+    if output.success() {
+        (Stage::new("compile", StageStatus::Passed, started, Some(label)), errors)
+    } else {
+        let detail = match output.status.code() {
+            Some(code) => format!("{} exited with code {}", label, code),
+            None => format!("{} was stopped by a signal", label),
+        };
+        (Stage::new("compile", StageStatus::Failed, started, Some(detail)), errors)
+    }
+}
+
+fn compile_command(project: &Path) -> Option<(Command, String)> {
+    if project.join("angular.json").exists() {
+        let mut command = Platform::ng_command();
+        command.args(["build", "--configuration", "development"]);
+        return Some((command, "ng build --configuration development".to_string()));
+    }
+    if project.join("tsconfig.json").exists() {
+        let local = project.join("node_modules").join(".bin").join(Platform::get_command_name("tsc"));
+        let mut command = if local.exists() { Command::new(local) } else { Command::new(Platform::get_command_name("tsc")) };
+        command.arg("--noEmit");
+        return Some((command, "tsc --noEmit".to_string()));
+    }
+    None
+}
+
+/// TypeScript (`error TS1234`) and Angular CLI (`[ERROR]`) error lines, in order
+fn compiler_errors(output: &str, link: &PackageLink) -> Vec<CompilerError> {
+    let package_path = link.path.to_string_lossy();
+    let mut errors: Vec<CompilerError> = Vec::new();
+    for line in output.lines().map(str::trim) {
+        let is_error = line.contains("error TS") || line.contains("[ERROR]") || line.starts_with("Error:");
+        if !is_error || errors.iter().any(|error| error.message == line) {
+            continue;
+        }
+        errors.push(CompilerError {
+            message: line.to_string(),
+            mentions_package: line.contains(link.npm_name()) || line.contains(package_path.as_ref()),
+        });
+    }
+    errors
+}
+
+fn print_report(report: &SmokeReport, link: &PackageLink) {
+    for error in &report.errors {
+        let marker = if error.mentions_package { "📦" } else { "  " };
+        println!("   {} {}", marker, error.message);
+    }
+    if report.total_errors > report.errors.len() {
+        println!("   … and {} more", report.total_errors - report.errors.len());
+    }
+    let mentioning = report.errors.iter().filter(|error| error.mentions_package).count();
+    if mentioning > 0 {
+        println!("   📦 {} of the errors shown mention {}", mentioning, link.npm_name());
+    }
+
+    println!("\n📊 Smoke test summary ({})", history::format_duration_ms(report.duration_ms));
+    for stage in &report.stages {
+        let status = match stage.status {
+            StageStatus::Passed => "✅",
+            StageStatus::Failed => "❌",
+            StageStatus::Skipped => "⏭️ ",
+        };
+        match stage.detail.as_deref().filter(|_| stage.status != StageStatus::Passed) {
+            Some(detail) => println!("  {} {}: {}", status, stage.stage, detail.lines().next().unwrap_or_default()),
+            None => println!("  {} {} ({})", status, stage.stage, history::format_duration_ms(stage.duration_ms)),
+        }
+    }
+    if report.passed {
+        println!("\n✨ {} works in {}", report.package, display_path(&report.project));
+    }
+}
+
+/// Failures here are only warnings; the result was already reported
+fn record_smoke(config_file: &ConfigFile, report: &SmokeReport) {
+    let result = BuildHistory::load(config_file).and_then(|mut history| {
+        history.record_smoke(SmokeRecord {
+            package: report.package.clone(),
+            project: report.project.clone(),
+            timestamp: history::now_timestamp(),
+            passed: report.passed,
+            failed_stage: report.failed_stage().map(str::to_string),
+            duration_ms: report.duration_ms,
+            errors: report.total_errors,
+        });
+        history.save(config_file)
+    });
+    if let Err(e) = result {
+        eprintln!("Warning: Could not record the smoke test in the history: {}", e);
+    }
+}
@@ -0,0 +1,89 @@
+use std::process::Command;
+use std::sync::{Condvar, Mutex};
+use crate::config::BuildConfig;
+
+/// Niceness builds get with `build.low_priority` on Unix
+pub const LOW_PRIORITY_NICENESS: i32 = 10;
+
+/// Builds holding a slot right now
+static RUNNING: Mutex<usize> = Mutex::new(0);
+static SLOT_FREED: Condvar = Condvar::new();
+
+/// Builds allowed at once; None when unlimited
+pub fn max_concurrency(settings: &BuildConfig) -> Option<usize> {
+    Some(settings.max_concurrency).filter(|limit| *limit > 0)
+}
+
+/// A place among the builds allowed to run at once, given back on drop
+pub struct BuildSlot(());
+
+impl Drop for BuildSlot {
+    fn drop(&mut self) {
+        let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+        *running = running.saturating_sub(1);
+        SLOT_FREED.notify_one();
+    }
+}
+
+/// Wait until fewer than `build.max_concurrency` builds hold a slot, then take one
+pub fn acquire(settings: &BuildConfig) -> BuildSlot {
+    let limit = max_concurrency(settings);
+    let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    while limit.is_some_and(|limit| *running >= limit) {
+        running = SLOT_FREED.wait(running).unwrap_or_else(|e| e.into_inner());
+    }
+    *running += 1;
+    BuildSlot(())
+}
+
+/// Apply `build.low_priority` and `build.node_max_old_space_mb` to a build
+/// command before it is spawned
+pub fn prepare(command: &mut Command, settings: &BuildConfig) {
+    if let Some(megabytes) = settings.node_max_old_space_mb {
+        let existing = std::env::var("NODE_OPTIONS").unwrap_or_default();
+        let option = format!("--max-old-space-size={}", megabytes);
+        command.env("NODE_OPTIONS", format!("{} {}", existing, option).trim());
+    }
+    if settings.low_priority {
+        lower_priority(command);
+    }
+}
+
+#[cfg(unix)]
+fn lower_priority(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: nice, errno access and write are async-signal-safe, so they may
+    // run in the forked child between fork and exec; nothing here allocates
+    unsafe {
+        command.pre_exec(|| {
+            // -1 is also a valid new niceness; only errno tells them apart
+            clear_errno();
+            if libc::nice(LOW_PRIORITY_NICENESS) == -1 && std::io::Error::last_os_error().raw_os_error().is_some_and(|code| code != 0) {
+                let warning = "⚠️  Could not lower the build's priority (build.low_priority); running it at normal priority\n";
+                libc::write(libc::STDERR_FILENO, warning.as_ptr().cast(), warning.len());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+unsafe fn clear_errno() {
+    *libc::__errno_location() = 0;
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly"))]
+unsafe fn clear_errno() {
+    *libc::__error() = 0;
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly"))))]
+unsafe fn clear_errno() {}
+
+#[cfg(windows)]
+fn lower_priority(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+    command.creation_flags(BELOW_NORMAL_PRIORITY_CLASS);
+}
+
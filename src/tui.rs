@@ -1,7 +1,7 @@
 use std::io;
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -15,7 +15,9 @@ use ratatui::{
 };
 use crate::config::{Config, PackageLink};
 use crate::npm::NpmManager;
+use crate::pm::{ManagerChoice, PackageManager};
 use crate::angular::AngularBuildManager;
+use crate::paths::display_path;
 use std::time::{Instant, Duration};
 use std::collections::HashMap;
 
@@ -31,6 +33,19 @@ pub struct TuiApp {
     angular_workspace: Option<crate::angular::AngularWorkspace>,
     last_refresh: Instant,
     current_project_path: std::path::PathBuf,
+    /// Angular workspace containing each linked package, if any (cached)
+    package_workspaces: HashMap<String, Option<std::path::PathBuf>>,
+    workspace_picker_index: usize,
+    /// The in-memory config has changes that haven't been written yet
+    dirty: bool,
+    /// Reinstall the declared registry version after unlinking
+    restore_on_unlink: bool,
+    /// List the workspace's application projects after the packages in build mode
+    show_apps_in_build: bool,
+    /// Only packages with this tag are listed
+    tag_filter: Option<String>,
+    /// A notice shown in the footer until the next key press
+    status_message: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -38,11 +53,18 @@ pub struct PackageStatus {
     pub health: HealthStatus,
     pub link_status: LinkStatus,
     pub is_angular_lib: bool,
+    /// Other links resolving to the same package (see `Config::duplicate_links`)
+    pub duplicate_of: Vec<String>,
+    /// The version in the package.json now, which only replaces the stored
+    /// one when `track_versions` is on
+    pub version: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum HealthStatus {
     Healthy,
+    /// Added before its path existed; waiting for a build or clone
+    Pending,
     Warning(String),
     Broken(String),
 }
@@ -52,6 +74,104 @@ pub enum LinkStatus {
     Linked,
     Unlinked,
     Unknown,
+    /// The project uses Yarn Plug'n'Play, which has no node_modules to link into
+    Pnp,
+}
+
+// Actions shared by the TUI and the line-based `interactive --simple` mode
+
+pub fn add_package(config: &mut Config, name: String, path: String) -> Result<()> {
+    config.add_link(name, path, false)
+}
+
+pub fn remove_package(config: &mut Config, name: &str) -> Result<()> {
+    config.remove_link(name)
+}
+
+/// Link `name` into `project` with `pm`, refusing to link a package into itself
+pub fn link_package(config: &mut Config, name: &str, project: &std::path::Path, pm: ManagerChoice) -> Result<()> {
+    if let Some(link_path) = config.links.get(name).map(|link| link.for_project(project).path) {
+        crate::permissions::ensure_not_self_link(name, &link_path, project)?;
+    }
+    NpmManager::link_package(config, project, name, pm, false)
+}
+
+pub fn unlink_package(config: &mut Config, name: &str, project: &std::path::Path, pm: ManagerChoice, restore: bool) -> Result<()> {
+    NpmManager::unlink_packages(config, project, &[name.to_string()], pm, restore)
+}
+
+/// Health of a configured package as seen from `project`. `package_manager`
+/// is the config-wide manager setting.
+pub fn package_health(package_link: &PackageLink, project: &std::path::Path, package_manager: Option<PackageManager>) -> HealthStatus {
+    let package_link = &package_link.for_project(project);
+    if package_link.pending {
+        return HealthStatus::Pending;
+    }
+
+    // Check if path exists
+    if !package_link.path.exists() {
+        return HealthStatus::Broken(match &package_link.path_spec {
+            Some(spec) => format!("Path does not exist: {} expands to {}", spec, display_path(&package_link.path)),
+            None => "Path does not exist".to_string(),
+        });
+    }
+
+    // Check if package.json exists
+    let package_json_path = package_link.path.join("package.json");
+    if !package_json_path.exists() {
+        return match package_link.nested_package_hint() {
+            Some(hint) => HealthStatus::Warning(hint),
+            None => HealthStatus::Broken("No package.json found".to_string()),
+        };
+    }
+
+    // Try to parse package.json
+    if crate::package::parse_package_json(&package_json_path).is_err() {
+        return HealthStatus::Broken("Invalid package.json".to_string());
+    }
+
+    // Check for symlink issues
+    if package_link.path.is_symlink() && package_link.path.read_link().is_err() {
+        return HealthStatus::Warning("Broken symlink".to_string());
+    }
+
+    // Linked here, but outside the range the project declares
+    let in_use = package_link.is_durable_in(project)
+        || package_link.linked_projects.iter().any(|p| crate::paths::same_path(p, project));
+    if let Some(check) = package_link.range_check(project).filter(|c| in_use && !c.satisfies) {
+        return HealthStatus::Warning(check.describe());
+    }
+
+    // Linked with one manager into a project whose lockfile belongs to another
+    if let Some(mismatch) = package_link.package_manager_mismatch(project, package_manager).filter(|_| in_use) {
+        return HealthStatus::Warning(format!("Package manager mismatch: {}", mismatch));
+    }
+
+    HealthStatus::Healthy
+}
+
+/// Whether `package_name` is symlinked into `project`'s node_modules
+pub fn link_status(project: &std::path::Path, package_name: &str) -> LinkStatus {
+    if crate::pm::is_pnp_project(project) {
+        return LinkStatus::Pnp;
+    }
+    let node_modules_path = project.join("node_modules");
+    if !node_modules_path.exists() {
+        return LinkStatus::Unlinked;
+    }
+    
+    let package_path = crate::paths::node_modules_path(project, package_name);
+    
+    if package_path.is_symlink() {
+        // Verify the symlink target exists and is valid
+        if package_path.read_link().is_ok() && package_path.exists() {
+            LinkStatus::Linked
+        } else {
+            LinkStatus::Unknown // Broken symlink
+        }
+    } else {
+        LinkStatus::Unlinked // Missing, or a regular directory/file
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -64,6 +184,9 @@ enum AppMode {
     UnlinkPackage,
     BuildPackage,
     TestPackage,
+    SwitchWorkspace,
+    /// Quitting with unsaved changes: save, discard or cancel
+    ConfirmQuit,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -90,22 +213,82 @@ impl TuiApp {
             angular_workspace,
             last_refresh: Instant::now(),
             current_project_path,
+            package_workspaces: HashMap::new(),
+            workspace_picker_index: 0,
+            dirty: false,
+            restore_on_unlink: false,
+            show_apps_in_build: false,
+            tag_filter: None,
+            status_message: None,
         };
         
         app.refresh_package_status()?;
         Ok(app)
     }
 
+    /// Record a change to the config, writing it straight away when auto-save is on
+    fn mark_dirty(&mut self) -> Result<()> {
+        self.dirty = true;
+        if self.config.tui.auto_save {
+            self.save_config()?;
+        }
+        Ok(())
+    }
+
+    /// The current project's package manager; an ambiguous detection is
+    /// reported in the footer rather than printed over the screen
+    fn package_manager(&mut self) -> ManagerChoice {
+        let (pm, warning) = PackageManager::resolve_quietly(None, self.config.package_manager, &self.current_project_path);
+        if let Some(warning) = warning {
+            self.status_message = Some(format!("⚠️  {}", warning));
+        }
+        pm
+    }
+
+    fn save_config(&mut self) -> Result<()> {
+        self.config.save()?;
+        self.dirty = false;
+        Ok(())
+    }
+
     fn refresh_package_status(&mut self) -> Result<()> {
+        // Builds, links and edits since the last refresh may have created or
+        // moved paths the caches remember
+        crate::paths::forget_cached_paths();
+        // Pick up pending paths, and with track_versions version bumps, that
+        // appeared while the TUI is open. Both write the config, so hold off
+        // while edits are unsaved. The list shows the current version either way.
+        if !self.dirty {
+            let promoted = self.config.activate_pending();
+            if !promoted.is_empty() {
+                let labels: Vec<String> = promoted.iter().map(crate::config::Promoted::label).collect();
+                self.status_message = Some(format!("✨ Pending link(s) now available: {}", labels.join(", ")));
+            }
+            if !self.config.sync_tracked_versions().is_empty() || !promoted.is_empty() {
+                let _ = self.config.save();
+            }
+        }
+        self.refresh_package_workspaces();
+        let duplicates = self.config.duplicate_links();
+        
         for (package_name, package_link) in &self.config.links {
             let health = self.check_package_health(package_link);
-            let link_status = self.check_link_status(package_name);
+            let link_status = self.check_link_status(package_link.npm_name());
             let is_angular_lib = self.is_angular_library(package_link);
+            let mut duplicate_of: Vec<String> = duplicates.iter()
+                .filter(|duplicate| duplicate.links.contains(package_name))
+                .flat_map(|duplicate| duplicate.links.iter().filter(|name| *name != package_name).cloned())
+                .collect();
+            duplicate_of.sort();
+            duplicate_of.dedup();
+            let version = crate::package::get_package_version(&package_link.for_project(&self.current_project_path).path.join("package.json")).ok();
 
             self.package_status.insert(package_name.clone(), PackageStatus {
                 health,
                 link_status,
                 is_angular_lib,
+                duplicate_of,
+                version,
             });
         }
         self.last_refresh = Instant::now();
@@ -113,78 +296,102 @@ impl TuiApp {
     }
 
     fn check_package_health(&self, package_link: &PackageLink) -> HealthStatus {
-        // Check if path exists
-        if !package_link.path.exists() {
-            return HealthStatus::Broken("Path does not exist".to_string());
-        }
+        package_health(package_link, &self.current_project_path, self.config.package_manager)
+    }
 
-        // Check if package.json exists
-        let package_json_path = package_link.path.join("package.json");
-        if !package_json_path.exists() {
-            return HealthStatus::Broken("No package.json found".to_string());
-        }
+    fn check_link_status(&self, package_name: &str) -> LinkStatus {
+        link_status(&self.current_project_path, package_name)
+    }
 
-        // Try to parse package.json
-        if let Err(_) = crate::package::parse_package_json(&package_json_path) {
-            return HealthStatus::Broken("Invalid package.json".to_string());
-        }
+    fn is_angular_library(&self, package_link: &PackageLink) -> bool {
+        // Check if this is an Angular library by looking for Angular-specific files
+        package_link.path.join("ng-package.json").exists() ||
+        package_link.path.join("public-api.ts").exists() ||
+        (self.package_workspace(&package_link.name).is_some() && 
+         package_link.path.to_string_lossy().contains("dist"))
+    }
 
-        // Check for symlink issues
-        if package_link.path.is_symlink() {
-            if let Err(_) = package_link.path.read_link() {
-                return HealthStatus::Warning("Broken symlink".to_string());
+    /// Resolve the Angular workspace of any package not seen before. Results are
+    /// cached because walking up from every package on each refresh adds up.
+    fn refresh_package_workspaces(&mut self) {
+        for (name, link) in &self.config.links {
+            if self.package_workspaces.contains_key(name) {
+                continue;
             }
+            let root = AngularBuildManager::find_workspace_root_for_package(&link.path)
+                .ok()
+                .filter(|root| AngularBuildManager::is_workspace_root(root));
+            self.package_workspaces.insert(name.clone(), root);
         }
+        self.package_workspaces.retain(|name, _| self.config.links.contains_key(name));
+    }
 
-        HealthStatus::Healthy
+    fn package_workspace(&self, package_name: &str) -> Option<&std::path::PathBuf> {
+        self.package_workspaces.get(package_name).and_then(|root| root.as_ref())
     }
 
-    fn check_link_status(&self, package_name: &str) -> LinkStatus {
-        let node_modules_path = self.current_project_path.join("node_modules");
-        if !node_modules_path.exists() {
-            return LinkStatus::Unlinked;
-        }
-        
-        let package_path = if package_name.starts_with('@') {
-            let parts: Vec<&str> = package_name.splitn(2, '/').collect();
-            if parts.len() == 2 {
-                node_modules_path.join(parts[0]).join(parts[1])
-            } else {
-                node_modules_path.join(package_name)
-            }
-        } else {
-            node_modules_path.join(package_name)
-        };
-        
-        if package_path.is_symlink() {
-            // Verify the symlink target exists and is valid
-            if package_path.read_link().is_ok() && package_path.exists() {
-                LinkStatus::Linked
-            } else {
-                LinkStatus::Unknown // Broken symlink
-            }
-        } else if package_path.exists() {
-            LinkStatus::Unlinked // Regular directory/file, not linked
-        } else {
-            LinkStatus::Unlinked
+    /// Every Angular workspace the launch directory or a linked package belongs to
+    fn known_workspaces(&self) -> Vec<std::path::PathBuf> {
+        let mut workspaces: Vec<std::path::PathBuf> = self.package_workspaces.values().flatten().cloned().collect();
+        if AngularBuildManager::detect_angular_workspace(&self.current_project_path).ok().flatten().is_some() {
+            workspaces.push(self.current_project_path.clone());
         }
+        workspaces.sort();
+        workspaces.dedup();
+        workspaces
     }
 
+    fn has_angular_context(&self) -> bool {
+        self.angular_workspace.is_some() || self.package_workspaces.values().any(|root| root.is_some())
+    }
 
-    fn is_angular_library(&self, package_link: &PackageLink) -> bool {
-        // Check if this is an Angular library by looking for Angular-specific files
-        package_link.path.join("ng-package.json").exists() ||
-        package_link.path.join("public-api.ts").exists() ||
-        (self.angular_workspace.is_some() && 
-         package_link.path.to_string_lossy().contains("dist"))
+    fn switch_workspace(&mut self, root: std::path::PathBuf) {
+        self.angular_workspace = AngularBuildManager::detect_angular_workspace(&root).ok().flatten();
+        self.workspace_root = root;
+    }
+
+    /// Run an ng command for a package's library inside the package's own
+    /// workspace, regardless of which workspace is active
+    fn run_library_command(&self, package_name: &str, command: &str, extra_args: &[&str]) {
+        let workspace_root = self.package_workspace(package_name)
+            .cloned()
+            .unwrap_or_else(|| self.workspace_root.clone());
+        let lib_name = AngularBuildManager::new_from_linked_package(self.config.clone(), package_name)
+            .ok()
+            .and_then(|manager| manager.resolve_package_to_library_name(package_name))
+            .unwrap_or_else(|| package_name.to_string());
+
+        let mut ng = crate::platform::Platform::ng_command();
+        ng.arg(command).arg(&lib_name).args(extra_args).current_dir(&workspace_root);
+        crate::throttle::prepare(&mut ng, &self.config.build);
+        let _slot = crate::throttle::acquire(&self.config.build);
+        let _ = ng.status();
+    }
+
+    /// Packages in display order: sorted by name, with the tag filter applied
+    fn visible_links(&self) -> Vec<&crate::config::PackageLink> {
+        let mut links: Vec<_> = self.config.links.values()
+            .filter(|link| self.tag_filter.as_ref().is_none_or(|tag| link.tags.contains(tag)))
+            .collect();
+        links.sort_by(|a, b| a.name.cmp(&b.name));
+        links
+    }
+
+    /// Step the tag filter through every tag in use, then back to none
+    fn cycle_tag_filter(&mut self) {
+        let tags = self.config.all_tags();
+        self.tag_filter = match &self.tag_filter {
+            None => tags.first().cloned(),
+            Some(current) => tags.iter().skip_while(|tag| *tag != current).nth(1).cloned(),
+        };
+        self.selected_index = 0;
     }
 
     fn get_total_items(&self) -> usize {
         let mut count = 0;
         
         // Sort packages alphabetically by name (same as display order)
-        let mut sorted_links: Vec<_> = self.config.links.values().collect();
-        sorted_links.sort_by(|a, b| a.name.cmp(&b.name));
+        let sorted_links = self.visible_links();
         
         for link in sorted_links {
             count += 1; // Package itself
@@ -195,6 +402,9 @@ impl TuiApp {
                     count += 1; // Health detail line
                 }
             }
+            if link.note.is_some() {
+                count += 1; // Note line
+            }
             
             // Count linked projects
             count += link.linked_projects.len();
@@ -206,8 +416,7 @@ impl TuiApp {
         let mut current_index = 0;
         
         // Sort packages alphabetically by name (same as display order)
-        let mut sorted_links: Vec<_> = self.config.links.values().collect();
-        sorted_links.sort_by(|a, b| a.name.cmp(&b.name));
+        let sorted_links = self.visible_links();
         
         for link in sorted_links {
             if current_index == target_index {
@@ -224,6 +433,12 @@ impl TuiApp {
                     current_index += 1;
                 }
             }
+            if link.note.is_some() {
+                if current_index == target_index {
+                    return Some(link.name.clone());
+                }
+                current_index += 1;
+            }
             
             // Skip linked projects
             for _ in &link.linked_projects {
@@ -267,6 +482,13 @@ impl TuiApp {
 
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
+                    self.status_message = None;
+                    if key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                        if self.dirty {
+                            self.save_config()?;
+                        }
+                        continue;
+                    }
                     match self.mode {
                         AppMode::Normal => {
                             if self.handle_normal_mode_input(key.code)? {
@@ -307,11 +529,22 @@ impl TuiApp {
                                 let _ = self.refresh_package_status();
                             }
                         }
+                        AppMode::SwitchWorkspace => {
+                            if self.handle_workspace_mode_input(key.code)? {
+                                self.mode = AppMode::Normal;
+                                let _ = self.refresh_package_status();
+                            }
+                        }
                         AppMode::Help => {
                             if matches!(key.code, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h')) {
                                 self.mode = AppMode::Normal;
                             }
                         }
+                        AppMode::ConfirmQuit => {
+                            if self.handle_confirm_quit_input(key.code)? {
+                                break;
+                            }
+                        }
                     }
                 }
             }
@@ -321,7 +554,12 @@ impl TuiApp {
 
     fn handle_normal_mode_input(&mut self, key: KeyCode) -> Result<bool> {
         match key {
-            KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if !self.dirty {
+                    return Ok(true);
+                }
+                self.mode = AppMode::ConfirmQuit;
+            }
             KeyCode::Char('h') => self.mode = AppMode::Help,
             KeyCode::Char('a') => {
                 self.mode = AppMode::AddPackage;
@@ -343,16 +581,35 @@ impl TuiApp {
                     self.mode = AppMode::UnlinkPackage;
                 }
             }
-            KeyCode::Char('b') => {
-                if !self.config.links.is_empty() && self.angular_workspace.is_some() {
-                    self.mode = AppMode::BuildPackage;
+            KeyCode::Char('R') => {
+                if let Some(package_name) = self.get_package_at_index(self.selected_index) {
+                    let pm = self.package_manager();
+                    match NpmManager::relink_package(&mut self.config, &package_name, pm) {
+                        Ok(_) => self.mark_dirty()?,
+                        Err(e) => eprintln!("Error relinking package: {}", e),
+                    }
+                    let _ = self.refresh_package_status();
                 }
             }
-            KeyCode::Char('t') => {
-                if !self.config.links.is_empty() && self.angular_workspace.is_some() {
-                    self.mode = AppMode::TestPackage;
+            KeyCode::Char('b') if !self.config.links.is_empty() && self.has_angular_context() => {
+                self.mode = AppMode::BuildPackage;
+            }
+            KeyCode::Char('t') if !self.config.links.is_empty() && self.has_angular_context() => {
+                self.mode = AppMode::TestPackage;
+            }
+            KeyCode::Char('W') => {
+                let workspaces = self.known_workspaces();
+                if !workspaces.is_empty() {
+                    self.workspace_picker_index = workspaces.iter()
+                        .position(|root| root == &self.workspace_root)
+                        .unwrap_or(0);
+                    self.mode = AppMode::SwitchWorkspace;
                 }
             }
+            KeyCode::Char('p') => {
+                crate::paths::set_full_paths(!crate::paths::full_paths());
+            }
+            KeyCode::Char('T') => self.cycle_tag_filter(),
             KeyCode::F(5) => {
                 // F5 to refresh
                 let _ = self.refresh_package_status();
@@ -372,6 +629,22 @@ impl TuiApp {
         Ok(false)
     }
 
+    /// Returns true when the app should exit
+    fn handle_confirm_quit_input(&mut self, key: KeyCode) -> Result<bool> {
+        match key {
+            KeyCode::Char('s') | KeyCode::Char('y') => {
+                self.save_config()?;
+                Ok(true)
+            }
+            KeyCode::Char('d') | KeyCode::Char('n') => Ok(true),
+            KeyCode::Char('c') | KeyCode::Esc => {
+                self.mode = AppMode::Normal;
+                Ok(false)
+            }
+            _ => Ok(false),
+        }
+    }
+
     fn handle_add_mode_input(&mut self, key: KeyCode) -> Result<bool> {
         match key {
             KeyCode::Esc => return Ok(true),
@@ -389,10 +662,10 @@ impl TuiApp {
                             let name = parts[0].trim().to_string();
                             let path = parts[1].trim().to_string();
                             
-                            if let Err(e) = self.config.add_link(name, path) {
+                            if let Err(e) = add_package(&mut self.config, name, path) {
                                 eprintln!("Error adding link: {}", e);
                             } else {
-                                self.config.save()?;
+                                self.mark_dirty()?;
                             }
                             
                             self.input_buffer.clear();
@@ -422,8 +695,8 @@ impl TuiApp {
             KeyCode::Esc => return Ok(true),
             KeyCode::Enter => {
                 if let Some(package_name) = self.get_package_at_index(self.selected_index) {
-                    self.config.remove_link(&package_name)?;
-                    self.config.save()?;
+                    remove_package(&mut self.config, &package_name)?;
+                    self.mark_dirty()?;
                     if self.selected_index >= self.get_total_items() && self.selected_index > 0 {
                         self.selected_index -= 1;
                     }
@@ -450,9 +723,10 @@ impl TuiApp {
             KeyCode::Esc => return Ok(true),
             KeyCode::Enter => {
                 if let Some(package_name) = self.get_package_at_index(self.selected_index) {
-                    match NpmManager::link_package(&mut self.config, &package_name) {
+                    let pm = self.package_manager();
+                    match link_package(&mut self.config, &package_name, &self.current_project_path, pm) {
                         Ok(_) => {
-                            self.config.save()?;
+                            self.mark_dirty()?;
                         }
                         Err(e) => {
                             eprintln!("Error linking package: {}", e);
@@ -481,9 +755,10 @@ impl TuiApp {
             KeyCode::Esc => return Ok(true),
             KeyCode::Enter => {
                 if let Some(package_name) = self.get_package_at_index(self.selected_index) {
-                    match NpmManager::unlink_package(&mut self.config, &package_name) {
+                    let pm = self.package_manager();
+                    match unlink_package(&mut self.config, &package_name, &self.current_project_path, pm, self.restore_on_unlink) {
                         Ok(_) => {
-                            self.config.save()?;
+                            self.mark_dirty()?;
                         }
                         Err(e) => {
                             eprintln!("Error unlinking package: {}", e);
@@ -492,6 +767,9 @@ impl TuiApp {
                 }
                 return Ok(true);
             }
+            KeyCode::Char('i') => {
+                self.restore_on_unlink = !self.restore_on_unlink;
+            }
             KeyCode::Up | KeyCode::Char('k') => {
                 if self.selected_index > 0 {
                     self.selected_index -= 1;
@@ -507,28 +785,48 @@ impl TuiApp {
         Ok(false)
     }
 
+    /// Application projects of the active workspace, when shown in build mode
+    fn build_mode_applications(&self) -> Vec<String> {
+        if !self.show_apps_in_build {
+            return Vec::new();
+        }
+        let mut applications: Vec<String> = self.angular_workspace.iter()
+            .flat_map(|workspace| workspace.projects.iter())
+            .filter(|(_, project)| project.project_type == "application")
+            .map(|(name, _)| name.clone())
+            .collect();
+        applications.sort();
+        applications
+    }
+
     fn handle_build_mode_input(&mut self, key: KeyCode) -> Result<bool> {
+        // Applications are listed after every package row
+        let package_rows = self.get_total_items();
         match key {
             KeyCode::Esc => return Ok(true),
+            KeyCode::Char('a') => {
+                self.show_apps_in_build = !self.show_apps_in_build;
+                self.selected_index = self.selected_index.min(package_rows.saturating_sub(1));
+            }
+            KeyCode::Enter if self.selected_index >= package_rows => {
+                if let Some(application) = self.build_mode_applications().get(self.selected_index - package_rows) {
+                    let mut ng = crate::platform::Platform::ng_command();
+                    ng.args(["build", application]).current_dir(&self.workspace_root);
+                    crate::throttle::prepare(&mut ng, &self.config.build);
+                    let _slot = crate::throttle::acquire(&self.config.build);
+                    let _ = ng.status();
+                }
+                return Ok(true);
+            }
             KeyCode::Enter => {
                 if let Some(package_name) = self.get_package_at_index(self.selected_index) {
                     if let Some(status) = self.package_status.get(&package_name) {
-                        if status.is_angular_lib {
-                            // Extract library name from package name for ng build
-                            let lib_name = if let Some(workspace) = &self.angular_workspace {
-                                // Try to find matching library name in workspace
-                                workspace.projects.iter()
-                                    .find(|(_, project)| project.project_type == "library")
-                                    .map(|(name, _)| name.clone())
-                                    .unwrap_or_else(|| package_name.clone())
-                            } else {
-                                package_name.clone()
-                            };
-                            
-                            let _ = std::process::Command::new("ng")
-                                .args(&["build", &lib_name])
-                                .current_dir(&self.workspace_root)
-                                .status();
+                        // Pending links inside a workspace can be built to create their path
+                        let buildable_pending = status.health == HealthStatus::Pending
+                            && self.package_workspace(&package_name).is_some();
+                        if status.is_angular_lib || buildable_pending {
+                            self.run_library_command(&package_name, "build", &[]);
+                            let _ = self.refresh_package_status();
                         }
                     }
                 }
@@ -540,7 +838,8 @@ impl TuiApp {
                 }
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                if self.selected_index < self.get_total_items().saturating_sub(1) {
+                let total = package_rows + self.build_mode_applications().len();
+                if self.selected_index < total.saturating_sub(1) {
                     self.selected_index += 1;
                 }
             }
@@ -556,20 +855,7 @@ impl TuiApp {
                 if let Some(package_name) = self.get_package_at_index(self.selected_index) {
                     if let Some(status) = self.package_status.get(&package_name) {
                         if status.is_angular_lib {
-                            // Extract library name from package name for ng test
-                            let lib_name = if let Some(workspace) = &self.angular_workspace {
-                                workspace.projects.iter()
-                                    .find(|(_, project)| project.project_type == "library")
-                                    .map(|(name, _)| name.clone())
-                                    .unwrap_or_else(|| package_name.clone())
-                            } else {
-                                package_name.clone()
-                            };
-                            
-                            let _ = std::process::Command::new("ng")
-                                .args(&["test", &lib_name, "--watch=false"])
-                                .current_dir(&self.workspace_root)
-                                .status();
+                            self.run_library_command(&package_name, "test", &["--watch=false"]);
                         }
                     }
                 }
@@ -590,6 +876,27 @@ impl TuiApp {
         Ok(false)
     }
 
+    fn handle_workspace_mode_input(&mut self, key: KeyCode) -> Result<bool> {
+        let workspaces = self.known_workspaces();
+        match key {
+            KeyCode::Esc => return Ok(true),
+            KeyCode::Enter => {
+                if let Some(root) = workspaces.get(self.workspace_picker_index) {
+                    self.switch_workspace(root.clone());
+                }
+                return Ok(true);
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.workspace_picker_index > 0 => {
+                self.workspace_picker_index -= 1;
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.workspace_picker_index + 1 < workspaces.len() => {
+                self.workspace_picker_index += 1;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
     fn ui(&mut self, f: &mut Frame) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -607,15 +914,18 @@ impl TuiApp {
         if self.mode == AppMode::Help {
             self.render_help_popup(f);
         }
+        if self.mode == AppMode::ConfirmQuit {
+            self.render_confirm_quit_popup(f);
+        }
     }
 
     fn render_header(&self, f: &mut Frame, area: Rect) {
         let title = match self.mode {
             AppMode::Normal => {
                 let workspace_info = if self.angular_workspace.is_some() {
-                    " (Angular Workspace)"
+                    format!(" (Angular Workspace: {})", workspace_label(&self.workspace_root))
                 } else {
-                    ""
+                    String::new()
                 };
                 format!("Spine - Package Link Manager{}", workspace_info)
             },
@@ -625,7 +935,23 @@ impl TuiApp {
             AppMode::UnlinkPackage => "Unlink Package from Current Project".to_string(),
             AppMode::BuildPackage => "Build Angular Library".to_string(),
             AppMode::TestPackage => "Test Angular Library".to_string(),
+            AppMode::SwitchWorkspace => "Switch Angular Workspace".to_string(),
             AppMode::Help => "Help".to_string(),
+            AppMode::ConfirmQuit => "Quit".to_string(),
+        };
+        let title = match &self.tag_filter {
+            Some(tag) => format!("{} 🏷️ {}", title, tag),
+            None => title,
+        };
+        let title = if crate::pm::is_pnp_project(&self.current_project_path) {
+            format!("{} 🧶 PnP", title)
+        } else {
+            title
+        };
+        let title = if self.dirty {
+            format!("{} ● unsaved changes", title)
+        } else {
+            title
         };
 
         let header = Paragraph::new(title)
@@ -645,7 +971,9 @@ impl TuiApp {
             AppMode::UnlinkPackage => self.render_action_package_list(f, area, "Unlink", Color::Red),
             AppMode::BuildPackage => self.render_action_package_list(f, area, "Build", Color::Blue),
             AppMode::TestPackage => self.render_action_package_list(f, area, "Test", Color::Cyan),
+            AppMode::SwitchWorkspace => self.render_workspace_picker(f, area),
             AppMode::Help => {},
+            AppMode::ConfirmQuit => self.render_enhanced_package_list(f, area),
         }
     }
 
@@ -663,17 +991,19 @@ impl TuiApp {
         let mut current_index = 0;
         
         // Sort packages alphabetically by name
-        let mut sorted_links: Vec<_> = self.config.links.values().collect();
-        sorted_links.sort_by(|a, b| a.name.cmp(&b.name));
+        let sorted_links = self.visible_links();
         
         for link in sorted_links {
-            let version = link.version.as_deref().unwrap_or("unknown");
+            let variant = link.variant_label(&self.current_project_path);
+            let link = &link.for_project(&self.current_project_path);
             let status = self.package_status.get(&link.name);
+            let version = status.and_then(|s| s.version.as_deref()).or(link.version.as_deref()).unwrap_or("unknown");
             
             // Health indicator
             let health_icon = if let Some(status) = status {
                 match &status.health {
                     HealthStatus::Healthy => "✅",
+                    HealthStatus::Pending => "⏳",
                     HealthStatus::Warning(_) => "⚠️",
                     HealthStatus::Broken(_) => "❌",
                 }
@@ -687,20 +1017,27 @@ impl TuiApp {
                     LinkStatus::Linked => "[🔗 LINKED]",
                     LinkStatus::Unlinked => "[🔓 UNLINKED]",
                     LinkStatus::Unknown => "[❓ UNKNOWN]",
+                    LinkStatus::Pnp => "[🧶 PNP]",
                 }
             } else {
                 "[❓ UNKNOWN]"
             };
             
             // Angular library indicator
-            let lib_icon = if let Some(status) = status {
-                if status.is_angular_lib { " 🅰️" } else { "" }
-            } else {
-                ""
+            let lib_icon = match (status, self.package_workspace(&link.name)) {
+                (Some(status), Some(root)) if status.is_angular_lib => format!(" 🅰️ [{}]", workspace_label(root)),
+                (Some(status), None) if status.is_angular_lib => " 🅰️".to_string(),
+                _ => String::new(),
             };
+            let vendored = if link.is_vendored() { " [vendored]" } else { "" };
+            let duplicate = match status.filter(|status| !status.duplicate_of.is_empty()) {
+                Some(status) => format!(" [🔀 DUPLICATE of {}]", status.duplicate_of.join(", ")),
+                None => String::new(),
+            };
+            let groups: String = self.config.groups_of(&link.name).iter().map(|g| format!(" #{}", g)).collect();
             
-            let main_content = format!("{} {} {} (v{}){} -> {}", 
-                health_icon, link_icon, link.name, version, lib_icon, link.path.display());
+            let main_content = format!("{} {} {}{} (v{}){}{}{}{}{} -> {}", 
+                health_icon, link_icon, link.name, variant, version, lib_icon, vendored, duplicate, groups, link.tag_label(), display_path(&link.path));
             
             let style = if current_index == self.selected_index {
                 Style::default().bg(Color::Blue).fg(Color::White)
@@ -718,12 +1055,22 @@ impl TuiApp {
                     let detail_style = Style::default().fg(Color::Red);
                     items.push(ListItem::new(detail_content).style(detail_style));
                     current_index += 1;
+                } else if status.health == HealthStatus::Pending {
+                    let hint = if self.package_workspace(&link.name).is_some() { " - press 'b' to build it" } else { "" };
+                    let detail_content = format!("    └─ ⏳ {}{}", crate::config::PENDING_MARKER, hint);
+                    items.push(ListItem::new(detail_content).style(Style::default().fg(Color::Yellow)));
+                    current_index += 1;
                 }
             }
+            if let Some(note) = &link.note {
+                items.push(ListItem::new(format!("    └─ 🗒️ {}", note)).style(Style::default().fg(Color::Cyan)));
+                current_index += 1;
+            }
             
             if !link.linked_projects.is_empty() {
                 for project_path in &link.linked_projects {
-                    let project_content = format!("    └─ 🔗 Linked to: {}", project_path.display());
+                    let age = link.linked_age(project_path).map(|age| format!(" ({})", age)).unwrap_or_default();
+                    let project_content = format!("    └─ 🔗 Linked to: {}{}", display_path(project_path), age);
                     let project_style = Style::default().fg(Color::Gray);
                     items.push(ListItem::new(project_content).style(project_style));
                     current_index += 1;
@@ -755,12 +1102,13 @@ impl TuiApp {
         let mut current_index = 0;
         
         // Sort packages alphabetically by name
-        let mut sorted_links: Vec<_> = self.config.links.values().collect();
-        sorted_links.sort_by(|a, b| a.name.cmp(&b.name));
+        let sorted_links = self.visible_links();
         
         for link in sorted_links {
-            let version = link.version.as_deref().unwrap_or("unknown");
+            let variant = link.variant_label(&self.current_project_path);
+            let link = &link.for_project(&self.current_project_path);
             let status = self.package_status.get(&link.name);
+            let version = status.and_then(|s| s.version.as_deref()).or(link.version.as_deref()).unwrap_or("unknown");
             
             // Filter for action-appropriate packages
             let should_show = match action {
@@ -776,6 +1124,7 @@ impl TuiApp {
             let health_icon = if let Some(status) = status {
                 match &status.health {
                     HealthStatus::Healthy => "✅",
+                    HealthStatus::Pending => "⏳",
                     HealthStatus::Warning(_) => "⚠️",
                     HealthStatus::Broken(_) => "❌",
                 }
@@ -790,6 +1139,7 @@ impl TuiApp {
                         LinkStatus::Linked => " [CURRENTLY LINKED]",
                         LinkStatus::Unlinked => " [NOT LINKED]",
                         LinkStatus::Unknown => " [STATUS UNKNOWN]",
+                        LinkStatus::Pnp => " [PnP: LINK WITH --durable]",
                     }
                 } else {
                     " [STATUS UNKNOWN]"
@@ -798,8 +1148,8 @@ impl TuiApp {
                 ""
             };
             
-            let content = format!("{} {} (v{}){} -> {}", 
-                health_icon, link.name, version, link_status_text, link.path.display());
+            let content = format!("{} {}{} (v{}){} -> {}", 
+                health_icon, link.name, variant, version, link_status_text, display_path(&link.path));
             
             let style = if current_index == self.selected_index {
                 Style::default().bg(color).fg(Color::White)
@@ -811,7 +1161,25 @@ impl TuiApp {
             current_index += 1;
         }
 
-        let title = format!("Select Package to {} (Enter to confirm, Esc to cancel)", action);
+        if action == "Build" {
+            let package_rows = self.get_total_items();
+            for (offset, application) in self.build_mode_applications().iter().enumerate() {
+                let style = if package_rows + offset == self.selected_index {
+                    Style::default().bg(color).fg(Color::White)
+                } else {
+                    Style::default()
+                };
+                items.push(ListItem::new(format!("🖥️  {} (application)", application)).style(style));
+            }
+        }
+
+        let mut title = format!("Select Package to {} (Enter to confirm, Esc to cancel)", action);
+        if action == "Build" {
+            title.push_str(&format!(" | Applications: {}", if self.show_apps_in_build { "shown" } else { "hidden" }));
+        }
+        if action == "Unlink" {
+            title.push_str(&format!(" | Restore registry version: {}", if self.restore_on_unlink { "on" } else { "off" }));
+        }
         let list = List::new(items)
             .block(Block::default().borders(Borders::ALL).title(title))
             .highlight_style(Style::default().bg(color).fg(Color::White));
@@ -864,11 +1232,10 @@ impl TuiApp {
         let mut current_index = 0;
         
         // Sort packages alphabetically by name
-        let mut sorted_links: Vec<_> = self.config.links.values().collect();
-        sorted_links.sort_by(|a, b| a.name.cmp(&b.name));
+        let sorted_links = self.visible_links();
         
         for link in sorted_links {
-            let content = format!("{} -> {}", link.name, link.path.display());
+            let content = format!("{} -> {}", link.name, display_path(&link.path));
             let style = if current_index == self.selected_index {
                 Style::default().bg(Color::Red).fg(Color::White)
             } else {
@@ -879,7 +1246,8 @@ impl TuiApp {
             
             if !link.linked_projects.is_empty() {
                 for project_path in &link.linked_projects {
-                    let project_content = format!("  └─ Linked to: {}", project_path.display());
+                    let age = link.linked_age(project_path).map(|age| format!(" ({})", age)).unwrap_or_default();
+                    let project_content = format!("  └─ Linked to: {}{}", display_path(project_path), age);
                     let project_style = if current_index == self.selected_index {
                         Style::default().bg(Color::Red).fg(Color::White)
                     } else {
@@ -901,26 +1269,61 @@ impl TuiApp {
         f.render_stateful_widget(list, area, &mut state);
     }
 
+    fn render_workspace_picker(&self, f: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self.known_workspaces()
+            .iter()
+            .enumerate()
+            .map(|(index, root)| {
+                let marker = if root == &self.workspace_root { "●" } else { " " };
+                let libraries = self.package_workspaces.values()
+                    .filter(|ws| ws.as_ref() == Some(root))
+                    .count();
+                let content = format!("{} {} ({} linked package(s)) -> {}",
+                    marker, workspace_label(root), libraries, display_path(root));
+                let style = if index == self.workspace_picker_index {
+                    Style::default().bg(Color::Magenta).fg(Color::White)
+                } else {
+                    Style::default()
+                };
+                ListItem::new(content).style(style)
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Select Active Workspace (Enter to switch, Esc to cancel)"));
+
+        let mut state = ListState::default();
+        state.select(Some(self.workspace_picker_index));
+
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
     fn render_footer(&self, f: &mut Frame, area: Rect) {
         let help_text = match self.mode {
             AppMode::Normal => {
-                if self.angular_workspace.is_some() {
-                    "q: Quit | h: Help | a: Add | r: Remove | l: Link | u: Unlink | b: Build | t: Test | F5: Refresh"
+                if self.has_angular_context() {
+                    "q: Quit | h: Help | a: Add | r: Remove | l: Link | u: Unlink | R: Relink | b: Build | t: Test | W: Workspace | T: Tag | p: Paths | F5: Refresh | ^S: Save"
                 } else {
-                    "q: Quit | h: Help | a: Add | r: Remove | l: Link | u: Unlink | F5: Refresh"
+                    "q: Quit | h: Help | a: Add | r: Remove | l: Link | u: Unlink | R: Relink | T: Tag | p: Paths | F5: Refresh | ^S: Save"
                 }
             },
             AppMode::AddPackage => "Enter: Next/Confirm | Esc: Cancel | Backspace: Delete",
             AppMode::RemovePackage => "Enter: Remove Selected | Esc: Cancel | ↑↓/jk: Navigate",
             AppMode::LinkPackage => "Enter: Link Selected | Esc: Cancel | ↑↓/jk: Navigate",
-            AppMode::UnlinkPackage => "Enter: Unlink Selected | Esc: Cancel | ↑↓/jk: Navigate",
-            AppMode::BuildPackage => "Enter: Build Selected | Esc: Cancel | ↑↓/jk: Navigate",
+            AppMode::UnlinkPackage => "Enter: Unlink Selected | i: Toggle Restore | Esc: Cancel | ↑↓/jk: Navigate",
+            AppMode::BuildPackage => "Enter: Build Selected | a: Toggle Applications | Esc: Cancel | ↑↓/jk: Navigate",
             AppMode::TestPackage => "Enter: Test Selected | Esc: Cancel | ↑↓/jk: Navigate",
+            AppMode::SwitchWorkspace => "Enter: Switch Workspace | Esc: Cancel | ↑↓/jk: Navigate",
             AppMode::Help => "Press h, q, or Esc to close help",
+            AppMode::ConfirmQuit => "s: Save and quit | d: Discard and quit | c/Esc: Cancel",
         };
 
+        let (help_text, color) = match (&self.mode, &self.status_message) {
+            (AppMode::Normal, Some(message)) => (message.as_str(), Color::Yellow),
+            _ => (help_text, Color::Cyan),
+        };
         let footer = Paragraph::new(help_text)
-            .style(Style::default().fg(Color::Cyan))
+            .style(Style::default().fg(color))
             .alignment(Alignment::Center)
             .block(Block::default().borders(Borders::ALL));
 
@@ -942,16 +1345,22 @@ impl TuiApp {
             Line::from("  a          - Add new package link"),
             Line::from("  r/Delete   - Remove selected package link"),
             Line::from("  l          - Link package to current project"),
-            Line::from("  u          - Unlink package from current project"),
+            Line::from("  u          - Unlink package from current project (i toggles reinstalling"),
+            Line::from("               the registry version afterwards)"),
+            Line::from("  R          - Relink selected package (unlink, then link)"),
             Line::from(""),
             Line::from("Angular Development (if workspace detected):"),
             Line::from("  b          - Build selected Angular library"),
             Line::from("  t          - Test selected Angular library"),
+            Line::from("  W          - Switch active Angular workspace"),
             Line::from(""),
             Line::from("System:"),
             Line::from("  h          - Show this help"),
+            Line::from("  p          - Toggle full/shortened paths"),
+            Line::from("  T          - Filter by tag (cycles through tags, then shows all)"),
             Line::from("  F5         - Refresh package status"),
-            Line::from("  q/Esc      - Quit application"),
+            Line::from("  Ctrl+S     - Save configuration changes"),
+            Line::from("  q/Esc      - Quit application (asks first if changes are unsaved)"),
             Line::from(""),
             Line::from("Status Indicators:"),
             Line::from("  ✅ - Package healthy    ⚠️ - Warning    ❌ - Broken"),
@@ -970,6 +1379,25 @@ impl TuiApp {
 
         f.render_widget(help_paragraph, area);
     }
+
+    fn render_confirm_quit_popup(&self, f: &mut Frame) {
+        let area = centered_rect(50, 25, f.size());
+        f.render_widget(Clear, area);
+
+        let text = vec![
+            Line::from(vec![Span::styled("You have unsaved configuration changes.", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))]),
+            Line::from(""),
+            Line::from("  s - Save and quit"),
+            Line::from("  d - Discard changes and quit"),
+            Line::from("  c/Esc - Cancel"),
+        ];
+
+        let popup = Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL).title("Unsaved Changes"))
+            .wrap(Wrap { trim: true });
+
+        f.render_widget(popup, area);
+    }
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -990,4 +1418,118 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
             Constraint::Percentage((100 - percent_x) / 2),
         ])
         .split(popup_layout[1])[1]
-}
\ No newline at end of file
+}
+
+/// Short name for a workspace root: its directory name
+fn workspace_label(root: &std::path::Path) -> String {
+    root.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| root.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An app over a config with one library, saving to a fresh
+    /// config.toml in `dir` that doesn't exist until something is written
+    fn app(dir: &std::path::Path, auto_save: bool) -> (TuiApp, std::path::PathBuf) {
+        let library = dir.join("lib-a");
+        std::fs::create_dir_all(&library).unwrap();
+        std::fs::write(library.join("package.json"), r#"{ "name": "lib-a", "version": "1.0.0" }"#).unwrap();
+        let config_file = dir.join("config").join("config.toml");
+
+        let mut config = Config::empty(&crate::config::ConfigFile::new(Some(&config_file)));
+        config.tui.auto_save = auto_save;
+        config.links.insert("lib-a".to_string(), PackageLink::new("lib-a".to_string(), library));
+        (TuiApp::new(config).unwrap(), config_file)
+    }
+
+    fn remove_selected(app: &mut TuiApp) {
+        assert!(!app.handle_normal_mode_input(KeyCode::Char('r')).unwrap());
+        assert_eq!(app.mode, AppMode::RemovePackage);
+        assert!(app.handle_remove_mode_input(KeyCode::Enter).unwrap());
+        app.mode = AppMode::Normal;
+    }
+
+    #[test]
+    fn quits_straight_away_without_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, config_file) = app(dir.path(), false);
+
+        assert!(app.handle_normal_mode_input(KeyCode::Char('q')).unwrap());
+        assert!(!config_file.exists());
+    }
+
+    #[test]
+    fn unsaved_changes_ask_before_quitting() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, config_file) = app(dir.path(), false);
+
+        remove_selected(&mut app);
+        assert!(app.dirty);
+        assert!(!config_file.exists());
+
+        assert!(!app.handle_normal_mode_input(KeyCode::Char('q')).unwrap());
+        assert_eq!(app.mode, AppMode::ConfirmQuit);
+        // Cancelling goes back to the list with the change still pending
+        assert!(!app.handle_confirm_quit_input(KeyCode::Esc).unwrap());
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.dirty);
+
+        assert!(!app.handle_normal_mode_input(KeyCode::Esc).unwrap());
+        assert_eq!(app.mode, AppMode::ConfirmQuit);
+        assert!(!app.handle_confirm_quit_input(KeyCode::Char('x')).unwrap());
+        assert_eq!(app.mode, AppMode::ConfirmQuit);
+    }
+
+    #[test]
+    fn discarding_on_quit_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, config_file) = app(dir.path(), false);
+
+        remove_selected(&mut app);
+        app.handle_normal_mode_input(KeyCode::Char('q')).unwrap();
+        assert!(app.handle_confirm_quit_input(KeyCode::Char('d')).unwrap());
+        // Not even a backup or temporary file beside it
+        let written = std::fs::read_dir(config_file.parent().unwrap()).map(|entries| entries.count()).unwrap_or(0);
+        assert_eq!(written, 0);
+    }
+
+    #[test]
+    fn saving_on_quit_writes_the_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, config_file) = app(dir.path(), false);
+
+        remove_selected(&mut app);
+        app.handle_normal_mode_input(KeyCode::Char('q')).unwrap();
+        assert!(app.handle_confirm_quit_input(KeyCode::Char('s')).unwrap());
+        assert!(!app.dirty);
+        let saved = std::fs::read_to_string(&config_file).unwrap();
+        assert!(!saved.contains("lib-a"), "{}", saved);
+    }
+
+    #[test]
+    fn auto_save_writes_each_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, config_file) = app(dir.path(), true);
+
+        remove_selected(&mut app);
+        assert!(!app.dirty);
+        assert!(config_file.exists());
+        assert!(app.handle_normal_mode_input(KeyCode::Char('q')).unwrap());
+    }
+
+    #[test]
+    fn shows_version_bumps_without_saving_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, config_file) = app(dir.path(), false);
+
+        std::fs::write(dir.path().join("lib-a").join("package.json"), r#"{ "name": "lib-a", "version": "1.1.0" }"#).unwrap();
+        app.refresh_package_status().unwrap();
+        assert_eq!(app.package_status["lib-a"].version.as_deref(), Some("1.1.0"));
+        // Without track_versions the stored version stays as it was
+        assert_eq!(app.config.links["lib-a"].version.as_deref(), Some("1.0.0"));
+        assert!(!config_file.exists());
+    }
+}
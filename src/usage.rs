@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use anyhow::Result;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory};
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::paths::display_path;
+
+/// Where invocations are appended; None while `usage_log` is off, so a
+/// disabled log costs one lock per run
+static LOG_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Turn the log on for this process, from the loaded config
+pub fn set_log_path(path: Option<PathBuf>) {
+    if let Ok(mut current) = LOG_PATH.lock() {
+        *current = path;
+    }
+}
+
+fn log_path() -> Option<PathBuf> {
+    LOG_PATH.lock().ok().and_then(|path| path.clone())
+}
+
+/// One line of the usage log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageEntry {
+    pub timestamp: u64,
+    /// Subcommand path, e.g. "config set"
+    pub command: String,
+    /// Names of the options given, never their values
+    pub flags: BTreeMap<String, bool>,
+    pub duration_ms: u64,
+    pub success: bool,
+}
+
+/// Append this invocation to the usage log when it's enabled. Errors are
+/// ignored; the log must never change what a command does.
+pub fn record(duration: Duration, success: bool) {
+    let Some(path) = log_path() else { return };
+    let Some((command, flags)) = describe_invocation(std::env::args_os()) else { return };
+    // Shell completion runs these on every tab press
+    if command.ends_with("-for-completion") {
+        return;
+    }
+    let entry = UsageEntry {
+        timestamp: crate::history::now_timestamp(),
+        command,
+        flags,
+        duration_ms: duration.as_millis() as u64,
+        success,
+    };
+    if let Ok(line) = serde_json::to_string(&entry) {
+        let _ = append_line(&path, &line);
+    }
+}
+
+fn append_line(path: &Path, line: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// The subcommand path and the options of a command line, taken from clap's
+/// own parse. Only options defined as such are kept, by name: positional
+/// arguments (package names, paths) and every value are dropped.
+pub fn describe_invocation(args: impl IntoIterator<Item = OsString>) -> Option<(String, BTreeMap<String, bool>)> {
+    let root = crate::cli::Cli::command();
+    let root_matches = root.clone().try_get_matches_from(args).ok()?;
+
+    let mut names = Vec::new();
+    let mut flags = BTreeMap::new();
+    let (mut command, mut matches) = (&root, &root_matches);
+    loop {
+        collect_flags(command, matches, &mut flags);
+        let Some((name, sub_matches)) = matches.subcommand() else { break };
+        let Some(sub_command) = command.find_subcommand(name) else { break };
+        names.push(name.to_string());
+        (command, matches) = (sub_command, sub_matches);
+    }
+
+    let command = if names.is_empty() { "interactive".to_string() } else { names.join(" ") };
+    Some((command, flags))
+}
+
+fn collect_flags(command: &clap::Command, matches: &ArgMatches, flags: &mut BTreeMap<String, bool>) {
+    for arg in command.get_arguments().filter(|arg| !arg.is_positional()) {
+        let id = arg.get_id().as_str();
+        if matches.value_source(id) == Some(ValueSource::CommandLine) {
+            flags.insert(arg.get_long().unwrap_or(id).to_string(), true);
+        }
+    }
+}
+
+/// Totals for one command across the log
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandUsage {
+    pub command: String,
+    pub count: usize,
+    pub failures: usize,
+    pub average_ms: u64,
+    /// How often each option was given
+    pub flags: BTreeMap<String, usize>,
+}
+
+/// Group entries by command, most used first
+pub fn aggregate(entries: &[UsageEntry]) -> Vec<CommandUsage> {
+    let mut by_command: BTreeMap<&str, Vec<&UsageEntry>> = BTreeMap::new();
+    for entry in entries {
+        by_command.entry(&entry.command).or_default().push(entry);
+    }
+
+    let mut usage: Vec<CommandUsage> = by_command.into_iter()
+        .map(|(command, entries)| {
+            let mut flags = BTreeMap::new();
+            for flag in entries.iter().flat_map(|entry| entry.flags.keys()) {
+                *flags.entry(flag.clone()).or_insert(0) += 1;
+            }
+            let total_ms: u64 = entries.iter().map(|entry| entry.duration_ms).sum();
+            CommandUsage {
+                command: command.to_string(),
+                count: entries.len(),
+                failures: entries.iter().filter(|entry| !entry.success).count(),
+                average_ms: total_ms / entries.len() as u64,
+                flags,
+            }
+        })
+        .collect();
+    usage.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.command.cmp(&b.command)));
+    usage
+}
+
+/// Entries of the log file and how many lines couldn't be read
+fn read_log(path: &Path) -> Result<(Vec<UsageEntry>, usize)> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+        Err(e) => return Err(e.into()),
+    };
+    let mut entries = Vec::new();
+    let mut unreadable = 0;
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) => unreadable += 1,
+        }
+    }
+    Ok((entries, unreadable))
+}
+
+/// `spine usage report`: counts and average durations per command
+pub fn report_command(config: &Config, json: bool) -> Result<()> {
+    let path = config.usage_log.file(&config.file)?;
+    let (entries, unreadable) = read_log(&path)?;
+    let usage = aggregate(&entries);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+            "log": path,
+            "enabled": config.usage_log.enabled,
+            "invocations": entries.len(),
+            "unreadable_lines": unreadable,
+            "commands": usage,
+        }))?);
+        return Ok(());
+    }
+
+    if !config.usage_log.enabled {
+        println!("ℹ️  The usage log is off; 'spine config set usage_log.enabled true' turns it on");
+    }
+    if entries.is_empty() {
+        println!("No usage recorded in {}", display_path(&path));
+        return Ok(());
+    }
+
+    println!("📊 {} invocation(s) recorded in {}", entries.len(), display_path(&path));
+    let width = usage.iter().map(|command| command.command.len()).max().unwrap_or(0).max("Command".len());
+    println!("\n  {:<width$}  {:>6}  {:>6}  {:>8}", "Command", "Runs", "Failed", "Average", width = width);
+    for command in &usage {
+        println!("  {:<width$}  {:>6}  {:>6}  {:>8}", command.command, command.count, command.failures,
+            crate::history::format_duration_ms(command.average_ms), width = width);
+        let mut flags: Vec<(&String, &usize)> = command.flags.iter().collect();
+        flags.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        if !flags.is_empty() {
+            let listed: Vec<String> = flags.iter().map(|(flag, count)| format!("--{} ×{}", flag, count)).collect();
+            println!("  {:<width$}  {}", "", listed.join(", "), width = width);
+        }
+    }
+    if unreadable > 0 {
+        println!("\n⚠️  Skipped {} unreadable line(s)", unreadable);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(args: &[&str]) -> (String, BTreeMap<String, bool>) {
+        describe_invocation(args.iter().map(OsString::from)).unwrap_or_else(|| panic!("{:?}", args))
+    }
+
+    fn entry(command: &str, flags: &[&str], duration_ms: u64, success: bool) -> UsageEntry {
+        UsageEntry {
+            timestamp: 0,
+            command: command.to_string(),
+            flags: flags.iter().map(|flag| (flag.to_string(), true)).collect(),
+            duration_ms,
+            success,
+        }
+    }
+
+    #[test]
+    fn keeps_option_names_and_drops_values() {
+        let secret = "/home/dev/clients/acme-secret";
+        let (command, flags) = describe(&["spine", "--config", secret, "add", "acme-ui", secret, "--note", "for the acme demo", "--allow-missing"]);
+        assert_eq!(command, "add");
+        assert_eq!(flags.keys().collect::<Vec<_>>(), ["allow-missing", "config", "note"]);
+
+        let line = serde_json::to_string(&UsageEntry { timestamp: 0, command, flags, duration_ms: 1, success: true }).unwrap();
+        for value in ["acme", "home", "demo"] {
+            assert!(!line.contains(value), "{} leaked into {}", value, line);
+        }
+    }
+
+    #[test]
+    fn names_nested_subcommands() {
+        let (command, flags) = describe(&["spine", "config", "set", "links.acme-ui.path", "/tmp/acme", "--allow-missing"]);
+        assert_eq!(command, "config set");
+        assert!(flags.contains_key("allow-missing"));
+
+        let (command, flags) = describe(&["spine", "link", "acme-ui", "--pm", "pnpm", "--strict-peers"]);
+        assert_eq!(command, "link");
+        assert_eq!(flags.keys().collect::<Vec<_>>(), ["pm", "strict-peers"]);
+
+        assert_eq!(describe(&["spine"]).0, "interactive");
+        assert!(describe_invocation(["spine", "no-such-command"].map(OsString::from)).is_none());
+    }
+
+    #[test]
+    fn aggregates_by_command_most_used_first() {
+        let entries = [
+            entry("status", &[], 100, true),
+            entry("link", &["yes"], 1000, true),
+            entry("status", &["json"], 300, false),
+            entry("status", &["json"], 200, true),
+            entry("add", &[], 50, true),
+            entry("link", &["yes", "durable"], 3000, true),
+        ];
+
+        let usage = aggregate(&entries);
+        let order: Vec<&str> = usage.iter().map(|command| command.command.as_str()).collect();
+        assert_eq!(order, ["status", "link", "add"]);
+
+        let status = &usage[0];
+        assert_eq!((status.count, status.failures, status.average_ms), (3, 1, 200));
+        assert_eq!(status.flags, BTreeMap::from([("json".to_string(), 2)]));
+        let link = &usage[1];
+        assert_eq!((link.count, link.failures, link.average_ms), (2, 0, 2000));
+        assert_eq!(link.flags, BTreeMap::from([("durable".to_string(), 1), ("yes".to_string(), 2)]));
+    }
+
+    #[test]
+    fn skips_unreadable_log_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.jsonl");
+        assert_eq!(read_log(&path).unwrap().0.len(), 0);
+
+        let line = serde_json::to_string(&entry("status", &[], 10, true)).unwrap();
+        append_line(&path, &line).unwrap();
+        append_line(&path, "{\"truncated\":").unwrap();
+        append_line(&path, "").unwrap();
+        append_line(&path, &line).unwrap();
+
+        let (entries, unreadable) = read_log(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(unreadable, 1);
+    }
+}
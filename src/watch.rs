@@ -0,0 +1,172 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant, SystemTime};
+use anyhow::Result;
+use notify::{RecursiveMode, Watcher};
+use crate::config::{Config, ConfigFile};
+use crate::history::{format_clock, now_timestamp};
+use crate::scanner::Scanner;
+
+/// Longest wait for a filesystem event before checking for Ctrl+C
+const SHUTDOWN_CHECK: Duration = Duration::from_millis(250);
+
+/// What an install changes in a project: its lockfiles and which of the
+/// project's managed links are still in place
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProjectState {
+    lockfiles: Vec<(PathBuf, Option<(SystemTime, u64)>)>,
+    linked: BTreeSet<String>,
+    /// Packages configured for this project whose link is gone
+    missing: BTreeSet<String>,
+}
+
+impl ProjectState {
+    fn read(config_file: &ConfigFile, project: &Path) -> Result<Self> {
+        // Re-read the config so links added meanwhile are watched too
+        let config = Config::load_or_create(config_file)?;
+        let lockfiles = crate::pm::LOCKFILES.iter()
+            .map(|(file, _)| {
+                let path = project.join(file);
+                let stamp = std::fs::metadata(&path).ok()
+                    .and_then(|meta| Some((meta.modified().ok()?, meta.len())));
+                (path, stamp)
+            })
+            .collect();
+
+        let mut linked = BTreeSet::new();
+        let mut missing = BTreeSet::new();
+        let managed = config.links.values()
+            .filter(|link| !link.pending && link.linked_projects.iter().any(|p| crate::paths::same_path(p, project)));
+        for link in managed {
+            if Config::is_package_linked_in_project_static(link.npm_name(), &project.to_path_buf()) {
+                linked.insert(link.name.clone());
+            } else {
+                missing.insert(link.name.clone());
+            }
+        }
+
+        Ok(Self { lockfiles, linked, missing })
+    }
+}
+
+/// Directories whose entries tell an install apart from other churn: the
+/// project (lockfiles, node_modules itself), node_modules, and the scope
+/// directories of scoped managed packages. None are watched recursively, so
+/// a large node_modules costs a handful of watches.
+fn watched_dirs(config_file: &ConfigFile, project: &Path) -> Vec<PathBuf> {
+    let node_modules = project.join("node_modules");
+    let mut dirs = vec![project.to_path_buf()];
+    if node_modules.is_dir() {
+        dirs.push(node_modules.clone());
+    }
+    if let Ok(config) = Config::load_or_create(config_file) {
+        let scopes: BTreeSet<PathBuf> = config.links.values()
+            .filter(|link| link.linked_projects.iter().any(|p| crate::paths::same_path(p, project)))
+            .filter_map(|link| link.npm_name().split_once('/').map(|(scope, _)| node_modules.join(scope)))
+            .filter(|scope| scope.is_dir())
+            .collect();
+        dirs.extend(scopes);
+    }
+    dirs
+}
+
+/// Watch `dirs` afresh. node_modules and scope directories are deleted and
+/// recreated by installs, which drops their watches, so this runs after
+/// every batch of events.
+fn rewatch(watcher: &mut impl Watcher, watched: &mut Vec<PathBuf>, dirs: Vec<PathBuf>) {
+    for dir in watched.drain(..) {
+        let _ = watcher.unwatch(&dir);
+    }
+    for dir in dirs {
+        match watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            Ok(()) => watched.push(dir),
+            Err(e) => eprintln!("[{}] ⚠️  Could not watch {}: {}", format_clock(now_timestamp()), crate::paths::display_path(&dir), e),
+        }
+    }
+}
+
+/// `spine sync --watch`: sync once, then restore the project's links whenever
+/// an install changes a lockfile or removes a managed symlink. Filesystem
+/// events are acted on once the project has been quiet for `debounce`, so a
+/// running install is never raced, and churn that leaves the lockfiles and
+/// links as they were (editor temp files, say) does nothing. A failed
+/// restore is logged and watching goes on. Stops on Ctrl+C.
+pub fn watch_sync(config_file: &ConfigFile, debounce: Duration) -> Result<()> {
+    let project = std::env::current_dir()?;
+    Scanner::sync_links(config_file, false, false, true)?;
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&shutdown))?;
+
+    let (events, received) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(events)?;
+    let mut watched = Vec::new();
+    rewatch(&mut watcher, &mut watched, watched_dirs(config_file, &project));
+    println!("\n👀 Watching {} for installs (Ctrl+C to stop)", crate::paths::display_path(&project));
+
+    let mut settled = ProjectState::read(config_file, &project)?;
+    // When the project last changed, while waiting for it to go quiet
+    let mut quiet_from: Option<Instant> = None;
+
+    while !shutdown.load(Ordering::Relaxed) {
+        let wait = quiet_from.map_or(SHUTDOWN_CHECK, |since| debounce.saturating_sub(since.elapsed()).min(SHUTDOWN_CHECK));
+        match received.recv_timeout(wait) {
+            Ok(Ok(_)) => {
+                quiet_from = Some(Instant::now());
+                continue;
+            }
+            Ok(Err(e)) => {
+                eprintln!("[{}] ⚠️  Watch error: {}", format_clock(now_timestamp()), e);
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+        if quiet_from.is_none_or(|since| since.elapsed() < debounce) {
+            continue;
+        }
+        quiet_from = None;
+
+        match ProjectState::read(config_file, &project) {
+            Ok(current) if current == settled => {}
+            Ok(current) => settled = restore(config_file, &project, current),
+            Err(e) => eprintln!("[{}] ⚠️  Could not read the project state: {}", format_clock(now_timestamp()), e),
+        }
+        rewatch(&mut watcher, &mut watched, watched_dirs(config_file, &project));
+        // Restoring touches node_modules itself; those events aren't an install
+        while received.try_recv().is_ok() {}
+    }
+
+    println!("\n🛑 Stopped watching");
+    Ok(())
+}
+
+/// Restore whatever the settled `state` is missing and return the state
+/// after, or `state` itself if it can't be read again
+fn restore(config_file: &ConfigFile, project: &Path, state: ProjectState) -> ProjectState {
+    if state.missing.is_empty() {
+        return state;
+    }
+
+    let time = format_clock(now_timestamp());
+    match Scanner::sync_links(config_file, false, true, true) {
+        Ok(report) => {
+            if !report.restored.is_empty() {
+                println!("[{}] 🔗 Restored {}", time, report.restored.join(", "));
+            }
+            for failed in &report.failed {
+                println!("[{}] ❌ Could not restore {}: {}", time, failed.package, failed.error);
+            }
+        }
+        Err(e) => println!("[{}] ❌ Sync failed: {}", time, e),
+    }
+    match ProjectState::read(config_file, project) {
+        Ok(after) => after,
+        Err(e) => {
+            eprintln!("[{}] ⚠️  Could not read the project state: {}", time, e);
+            state
+        }
+    }
+}
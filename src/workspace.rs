@@ -1,13 +1,57 @@
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use crate::gitignore::IgnoreRules;
 use crate::package;
+use crate::paths::display_path;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct WorkspaceConfig {
     #[serde(default)]
     pub auto_link: AutoLinkConfig,
+    #[serde(default)]
+    pub sync: SyncOverrides,
+    /// Package paths that replace the global config's while Spine runs in
+    /// this project, e.g. a fork of a library. Relative paths are resolved
+    /// from the directory holding .spine.toml.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub links: BTreeMap<String, String>,
+    #[serde(default)]
+    pub scan: ScanExclusions,
+}
+
+/// Directories `spine scan` skips below this project, on top of ignore files
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScanExclusions {
+    /// Gitignore-style patterns relative to the directory holding .spine.toml
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+/// Project-local overrides for which packages `spine sync` enforces
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SyncOverrides {
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub include_only: Vec<String>,
+}
+
+impl SyncOverrides {
+    /// Why sync should leave `package_name` alone, or None if it is enforced
+    pub fn skip_reason(&self, package_name: &str) -> Option<String> {
+        if let Some(pattern) = self.exclude.iter().find(|p| WorkspaceManager::matches_pattern(package_name, p)) {
+            return Some(format!("sync.exclude \"{}\"", pattern));
+        }
+        if !self.include_only.is_empty()
+            && !self.include_only.iter().any(|p| WorkspaceManager::matches_pattern(package_name, p))
+        {
+            return Some("not in sync.include_only".to_string());
+        }
+        None
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -26,6 +70,50 @@ pub struct DiscoveredPackage {
     pub path: PathBuf,
     pub version: String,
     pub is_dist: bool,
+    pub source: ScanSource,
+}
+
+/// How a scan found a package
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanSource {
+    /// A library's build output under an Angular workspace's dist/
+    AngularDist,
+    /// A member declared by pnpm-workspace.yaml, package.json "workspaces" or lerna.json
+    WorkspaceManifest,
+    DirectoryWalk,
+}
+
+impl ScanSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            ScanSource::AngularDist => "angular dist",
+            ScanSource::WorkspaceManifest => "workspace manifest",
+            ScanSource::DirectoryWalk => "directory walk",
+        }
+    }
+}
+
+/// What a scan found and how many directories it read to find it
+#[derive(Debug, Clone, Default)]
+pub struct ScanOutcome {
+    pub packages: Vec<DiscoveredPackage>,
+    pub directories: usize,
+}
+
+/// Levels below the search root a directory scan descends by default
+pub const DEFAULT_SCAN_DEPTH: usize = 5;
+
+/// Directories a scan never descends into, ignore files or not
+const SKIPPED_DIRS: [&str; 3] = ["node_modules", ".git", "target"];
+
+/// A directory waiting to be read, with the ignore rules in force above it
+struct PendingDir {
+    path: PathBuf,
+    canonical: PathBuf,
+    /// Reached through a symlink; the real path wins when both are found
+    via_symlink: bool,
+    ignores: IgnoreRules,
 }
 
 pub struct WorkspaceManager;
@@ -35,8 +123,9 @@ impl WorkspaceManager {
         PathBuf::from(".spine.toml")
     }
 
-    pub fn load_workspace_config() -> Result<Option<WorkspaceConfig>> {
-        let config_path = Self::workspace_config_path();
+    /// The .spine.toml directly in `project`, if there is one
+    pub fn load_workspace_config(project: &Path) -> Result<Option<WorkspaceConfig>> {
+        let config_path = project.join(Self::workspace_config_path());
         if !config_path.exists() {
             return Ok(None);
         }
@@ -46,6 +135,20 @@ impl WorkspaceManager {
         Ok(Some(config))
     }
 
+    /// The nearest .spine.toml in `start` or one of its ancestors, within
+    /// the walk limits
+    pub fn find_workspace_config(start: &Path) -> Option<PathBuf> {
+        let marker = Self::workspace_config_path();
+        crate::paths::walk_up(start, &marker.to_string_lossy()).found
+            .map(|dir| dir.join(marker))
+            .filter(|path| path.is_file())
+    }
+
+    pub fn read_workspace_config(path: &Path) -> Result<WorkspaceConfig> {
+        let content = fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
     pub fn save_workspace_config(config: &WorkspaceConfig) -> Result<()> {
         let config_path = Self::workspace_config_path();
         let content = toml::to_string_pretty(config)?;
@@ -53,28 +156,83 @@ impl WorkspaceManager {
         Ok(())
     }
 
-    pub fn scan_for_packages(search_path: Option<&str>) -> Result<Vec<DiscoveredPackage>> {
+    /// Write .spine.toml (unless it exists) and the JSON Schema next to it,
+    /// with a `#:schema` directive so Even Better TOML and other taplo-based
+    /// editors validate and complete it. The schema is refreshed every run.
+    pub fn init_workspace_config() -> Result<()> {
+        let config_path = Self::workspace_config_path();
+        let schema_path = PathBuf::from(crate::schema::WORKSPACE_SCHEMA_FILE);
+        let directive = format!("#:schema ./{}", crate::schema::WORKSPACE_SCHEMA_FILE);
+
+        fs::write(&schema_path, serde_json::to_string_pretty(&crate::schema::workspace_schema())? + "\n")?;
+        println!("📝 Wrote {}", display_path(&schema_path));
+
+        if config_path.exists() {
+            let content = fs::read_to_string(&config_path)?;
+            if content.lines().any(|line| line.trim_start().starts_with("#:schema")) {
+                println!("✓ {} already references a schema", display_path(&config_path));
+            } else {
+                fs::write(&config_path, format!("{}\n{}", directive, content))?;
+                println!("📝 Added the schema reference to {}", display_path(&config_path));
+            }
+        } else {
+            let content = toml::to_string_pretty(&WorkspaceConfig::default())?;
+            fs::write(&config_path, format!("{}\n\n{}", directive, content))?;
+            println!("📝 Created {}", display_path(&config_path));
+        }
+
+        println!("💡 Commit both files so everyone's editor picks up the schema.");
+        Ok(())
+    }
+
+    /// Packages under `search_path`. Directories that .gitignore or .ignore
+    /// files exclude are skipped unless `no_ignore`; .spine.toml's
+    /// scan.exclude applies either way. `depth` counts levels below
+    /// `search_path`, defaulting to DEFAULT_SCAN_DEPTH.
+    pub fn scan_for_packages(search_path: Option<&str>, no_ignore: bool, depth: Option<usize>) -> Result<ScanOutcome> {
         let search_dir = match search_path {
             Some(path) => PathBuf::from(path),
             None => std::env::current_dir()?,
         };
 
         let mut packages = Vec::new();
+        let mut directories = 1;
         
         // First, try to detect if this is an Angular workspace
         if let Ok(Some(angular_workspace)) = crate::angular::AngularBuildManager::detect_angular_workspace(&search_dir) {
-            println!("🅰️  Angular workspace detected at: {}", search_dir.display());
+            println!("🅰️  Angular workspace detected at: {}", display_path(&search_dir));
             Self::scan_angular_workspace(&search_dir, &angular_workspace, &mut packages)?;
+        } else if let Some(manifest) = crate::monorepo::WorkspaceManifest::detect(&search_dir) {
+            println!("📚 {} workspace detected: members from {}", manifest.tool, display_path(&manifest.file));
+            let (members, read) = manifest.member_dirs(&search_dir);
+            directories += read;
+            for member in members {
+                if let Ok(package_info) = package::parse_package_json(&member.join("package.json")) {
+                    packages.push(DiscoveredPackage {
+                        name: package_info.name,
+                        is_dist: Self::is_dist_dir(&member),
+                        path: member,
+                        version: package_info.version,
+                        source: ScanSource::WorkspaceManifest,
+                    });
+                }
+            }
         } else {
             // Fallback to regular directory scanning
-            println!("📁 Scanning directory for packages: {}", search_dir.display());
-            Self::scan_directory(&search_dir, &mut packages)?;
+            println!("📁 Scanning directory for packages: {}", display_path(&search_dir));
+            let mut ignores = if no_ignore { IgnoreRules::default() } else { IgnoreRules::for_root(&search_dir) };
+            let absolute = search_dir.canonicalize().unwrap_or_else(|_| search_dir.clone());
+            if let Some(workspace_config) = Self::find_workspace_config(&absolute) {
+                let exclude = Self::read_workspace_config(&workspace_config).map(|config| config.scan.exclude).unwrap_or_default();
+                ignores.add_patterns(workspace_config.parent().unwrap_or(&absolute), &exclude);
+            }
+            directories = Self::scan_directory(&search_dir, depth.unwrap_or(DEFAULT_SCAN_DEPTH), ignores, &mut packages);
         }
         
-        // Sort by name for consistent output
-        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        // Sort by name, then path, so duplicates always come out in the same order
+        packages.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
         
-        Ok(packages)
+        Ok(ScanOutcome { packages, directories })
     }
 
     fn scan_angular_workspace(
@@ -104,20 +262,20 @@ impl WorkspaceManager {
             // Scan for built libraries in dist/LIBRARY_NAME
             for (lib_name, _) in &library_projects {
                 let lib_dist_path = dist_dir.join(lib_name);
-                let package_json_path = lib_dist_path.join("package.json");
                 
-                if package_json_path.exists() {
-                    if let Ok(package_info) = package::parse_package_json(&package_json_path) {
-                        println!("    ✅ Found built library: {} at {}", package_info.name, lib_dist_path.display());
+                if let Some(package_root) = crate::angular::package_root(&lib_dist_path) {
+                    if let Ok(package_info) = package::parse_package_json(&package_root.join("package.json")) {
+                        println!("    ✅ Found built library: {} at {}", package_info.name, display_path(&package_root));
                         packages.push(DiscoveredPackage {
                             name: package_info.name,
-                            path: lib_dist_path,
+                            path: package_root,
                             version: package_info.version,
                             is_dist: true,
+                            source: ScanSource::AngularDist,
                         });
                     }
                 } else {
-                    println!("    ⚠️  Library '{}' not built yet (no package.json in {})", lib_name, lib_dist_path.display());
+                    println!("    ⚠️  Library '{}' not built yet (no package.json in {})", lib_name, display_path(&lib_dist_path));
                     println!("       Run 'ng build {}' to build this library", lib_name);
                 }
             }
@@ -140,53 +298,96 @@ impl WorkspaceManager {
         Ok(())
     }
 
-    fn scan_directory(dir: &Path, packages: &mut Vec<DiscoveredPackage>) -> Result<()> {
-        if !dir.is_dir() {
-            return Ok(());
+    /// Walk `root` a level at a time, reading each level's directories on a
+    /// pool of threads, down to `depth` levels below it. A directory reached
+    /// twice, e.g. through a symlink, is read once. Returns how many
+    /// directories were read.
+    fn scan_directory(root: &Path, depth: usize, ignores: IgnoreRules, packages: &mut Vec<DiscoveredPackage>) -> usize {
+        if !root.is_dir() {
+            return 0;
         }
-
-        // Skip node_modules and other common directories to avoid
-        if let Some(dir_name) = dir.file_name() {
-            if dir_name == "node_modules" || dir_name == ".git" || dir_name == "target" {
-                return Ok(());
+        let threads = std::thread::available_parallelism().map_or(4, |threads| threads.get());
+        let canonical = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let mut level = vec![PendingDir { path: root.to_path_buf(), canonical, via_symlink: false, ignores }];
+        let mut visited: HashSet<PathBuf> = HashSet::new();
+        let mut directories = 0;
+
+        for current_depth in 0..=depth {
+            level.sort_by(|a, b| a.via_symlink.cmp(&b.via_symlink).then_with(|| a.path.cmp(&b.path)));
+            level.retain(|dir| visited.insert(dir.canonical.clone()));
+            if level.is_empty() {
+                break;
+            }
+            directories += level.len();
+            let descend = current_depth < depth;
+            let chunk_size = level.len().div_ceil(threads);
+            let results: Vec<(Vec<DiscoveredPackage>, Vec<PendingDir>)> = std::thread::scope(|scope| {
+                let workers: Vec<_> = level.chunks_mut(chunk_size)
+                    .map(|chunk| scope.spawn(move || {
+                        let mut found = Vec::new();
+                        let mut next = Vec::new();
+                        for dir in chunk {
+                            Self::read_scan_dir(dir, descend, &mut found, &mut next);
+                        }
+                        (found, next)
+                    }))
+                    .collect();
+                workers.into_iter().filter_map(|worker| worker.join().ok()).collect()
+            });
+
+            level = Vec::new();
+            for (found, next) in results {
+                packages.extend(found);
+                level.extend(next);
             }
         }
+        directories
+    }
 
-        // Check if this directory contains a package.json
-        let package_json_path = dir.join("package.json");
+    /// Record the package in `dir`, if any, and queue the subdirectories to
+    /// read next. The directory we were pointed at is always honoured, but
+    /// SKIPPED_DIRS are never entered.
+    fn read_scan_dir(dir: &mut PendingDir, descend: bool, found: &mut Vec<DiscoveredPackage>, next: &mut Vec<PendingDir>) {
+        dir.ignores.enter(&dir.path);
+
+        let package_json_path = dir.path.join("package.json");
         if package_json_path.exists() {
             if let Ok(package_info) = package::parse_package_json(&package_json_path) {
-                let is_dist = dir.file_name()
-                    .and_then(|n| n.to_str())
-                    .map(|n| n == "dist" || n.contains("dist"))
-                    .unwrap_or(false);
-
-                packages.push(DiscoveredPackage {
+                found.push(DiscoveredPackage {
                     name: package_info.name,
-                    path: dir.to_path_buf(),
+                    path: dir.path.clone(),
                     version: package_info.version,
-                    is_dist,
+                    is_dist: Self::is_dist_dir(&dir.path),
+                    source: ScanSource::DirectoryWalk,
                 });
             }
         }
 
-        // Recursively scan subdirectories (up to reasonable depth)
-        if let Ok(entries) = fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                if entry.path().is_dir() {
-                    // Limit recursion depth to avoid scanning too deep
-                    if Self::get_depth(&entry.path()) < 6 {
-                        Self::scan_directory(&entry.path(), packages)?;
-                    }
-                }
+        if !descend {
+            return;
+        }
+        let Ok(entries) = fs::read_dir(&dir.path) else { return };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            if SKIPPED_DIRS.iter().any(|skipped| name == *skipped) {
+                continue;
+            }
+            let path = entry.path();
+            if path.is_dir() && !dir.ignores.is_ignored(&path, true) {
+                let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+                let via_symlink = dir.via_symlink || entry.file_type().is_ok_and(|kind| kind.is_symlink());
+                next.push(PendingDir { path, canonical, via_symlink, ignores: dir.ignores.clone() });
             }
         }
-
-        Ok(())
     }
 
-    fn get_depth(path: &Path) -> usize {
-        path.components().count()
+    /// Build output: a directory named like dist, or anything inside a dist/
+    /// folder such as dist/ui-kit
+    pub fn is_dist_dir(dir: &Path) -> bool {
+        let named_dist = dir.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.contains("dist"));
+        named_dist || dir.components().any(|c| c.as_os_str() == "dist")
     }
 
     fn scan_directory_shallow(dir: &Path, packages: &mut Vec<DiscoveredPackage>) -> Result<()> {
@@ -207,16 +408,14 @@ impl WorkspaceManager {
             if let Ok(package_info) = package::parse_package_json(&package_json_path) {
                 // Only add if not already found (avoid duplicates)
                 if !packages.iter().any(|p| p.name == package_info.name) {
-                    let is_dist = dir.file_name()
-                        .and_then(|n| n.to_str())
-                        .map(|n| n == "dist" || n.contains("dist"))
-                        .unwrap_or(false);
+                    let is_dist = Self::is_dist_dir(dir);
 
                     packages.push(DiscoveredPackage {
                         name: package_info.name,
                         path: dir.to_path_buf(),
                         version: package_info.version,
                         is_dist,
+                        source: ScanSource::DirectoryWalk,
                     });
                 }
             }
@@ -232,16 +431,14 @@ impl WorkspaceManager {
                         if let Ok(package_info) = package::parse_package_json(&package_json_subpath) {
                             // Only add if not already found (avoid duplicates)
                             if !packages.iter().any(|p| p.name == package_info.name) {
-                                let is_dist = entry.path().file_name()
-                                    .and_then(|n| n.to_str())
-                                    .map(|n| n == "dist" || n.contains("dist"))
-                                    .unwrap_or(false);
+                                let is_dist = Self::is_dist_dir(&entry.path());
 
                                 packages.push(DiscoveredPackage {
                                     name: package_info.name,
                                     path: entry.path(),
                                     version: package_info.version,
                                     is_dist,
+                                    source: ScanSource::DirectoryWalk,
                                 });
                             }
                         }
@@ -284,7 +481,7 @@ impl WorkspaceManager {
             .collect()
     }
 
-    fn matches_pattern(name: &str, pattern: &str) -> bool {
+    pub fn matches_pattern(name: &str, pattern: &str) -> bool {
         // Simple glob-style pattern matching
         if pattern.ends_with('*') {
             let prefix = &pattern[..pattern.len() - 1];
@@ -297,7 +494,9 @@ impl WorkspaceManager {
         }
     }
 
-    pub fn suggest_packages_for_current_project() -> Result<Vec<DiscoveredPackage>> {
+    /// The discovered packages the current project depends on, through any
+    /// of its dependency fields
+    pub fn suggest_packages_for_current_project(discovered: Vec<DiscoveredPackage>) -> Result<Vec<DiscoveredPackage>> {
         let current_dir = std::env::current_dir()?;
         let package_json_path = current_dir.join("package.json");
         
@@ -307,17 +506,9 @@ impl WorkspaceManager {
 
         // Parse current project's dependencies
         let project_info = package::parse_package_json(&package_json_path)?;
-        let all_deps: std::collections::HashSet<String> = project_info.dependencies
-            .iter()
-            .chain(project_info.dev_dependencies.iter())
-            .cloned()
-            .collect();
-
-        // Scan for packages and filter by current project's dependencies
-        let discovered = Self::scan_for_packages(None)?;
         let suggested = discovered
             .into_iter()
-            .filter(|pkg| all_deps.contains(&pkg.name))
+            .filter(|pkg| project_info.dependency_field(&pkg.name).is_some())
             .collect();
 
         Ok(suggested)
@@ -0,0 +1,60 @@
+//! Helpers shared by the integration tests
+
+// Each test binary compiles its own copy and uses only some of these
+#![allow(dead_code)]
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// spine, run in `dir` with `config` as its config file and plain output
+pub fn spine_command(config: &Path, dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_spine"));
+    command.current_dir(dir)
+        .env("SPINE_CONFIG", config)
+        .env("SPINE_LOG_FORMAT", "plain");
+    command
+}
+
+/// Run spine with `args` and fail the test unless it succeeds
+pub fn spine(config: &Path, dir: &Path, args: &[&str]) -> Output {
+    let output = spine_command(config, dir).args(args).output().expect("spine runs");
+    assert!(output.status.success(), "spine {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// `dir` with a package.json for `name`
+pub fn write_package(dir: &Path, name: &str, version: &str) {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(dir.join("package.json"), format!(r#"{{ "name": "{}", "version": "{}" }}"#, name, version)).unwrap();
+}
+
+/// PATH with `bin` searched first, for the stand-in tools written there
+pub fn path_with(bin: &Path) -> OsString {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::join_paths(std::iter::once(bin.to_path_buf()).chain(std::env::split_paths(&path))).unwrap()
+}
+
+/// Write an executable shell script to `path`
+#[cfg(unix)]
+pub fn script(path: &Path, body: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    fs::write(path, format!("#!/bin/sh\n{}", body)).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// A stand-in npm in `dir` whose `link <path>` symlinks the package into
+/// node_modules
+#[cfg(unix)]
+pub fn fake_npm(dir: &Path) {
+    fs::create_dir_all(dir).unwrap();
+    script(&dir.join("npm"), "[ \"$1\" = link ] || exit 0\n\
+        shift\n\
+        mkdir -p node_modules\n\
+        for package in \"$@\"; do ln -sfn \"$package\" \"node_modules/$(basename \"$package\")\"; done\n");
+}
@@ -0,0 +1,79 @@
+//! Links, scans and health checks against the dist layouts in
+//! tests/fixtures/workspaces/dist-layouts: package.json at the output root
+//! (legacy), in the application builder's browser/ folder (shop) and in
+//! ng-packagr's nested dist/<name>/<name> (nested)
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use common::{spine, stdout};
+
+fn workspace() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("workspaces").join("dist-layouts")
+}
+
+/// spine's output with untruncated paths, run beside `config`
+fn spine_full(config: &Path, args: &[&str]) -> String {
+    stdout(&spine(config, config.parent().unwrap(), &[&["--full-paths"], args].concat()))
+}
+
+/// The path the config file stores for link `name`
+fn stored_path(config: &Path, name: &str) -> PathBuf {
+    let config: toml::Value = toml::from_str(&fs::read_to_string(config).unwrap()).unwrap();
+    PathBuf::from(config["links"][name]["path"].as_str().unwrap())
+}
+
+#[test]
+fn scan_shows_the_directory_holding_package_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let dist = workspace().join("dist");
+    let out = spine_full(&dir.path().join("config.toml"), &["scan", "--path", &workspace().to_string_lossy()]);
+
+    let legacy = dist.join("legacy");
+    let nested = dist.join("nested").join("nested");
+    assert!(out.contains(&format!("@acme/legacy (v1.0.0) -> {} (dist)", legacy.display())), "{}", out);
+    assert!(out.contains(&format!("@acme/nested (v2.0.0) -> {} (dist)", nested.display())), "{}", out);
+}
+
+#[test]
+fn add_links_the_browser_folder() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let shop = workspace().join("dist").join("shop");
+    let out = spine_full(&config, &["add", "shop", &shop.to_string_lossy()]);
+
+    let browser = shop.join("browser");
+    assert!(out.contains(&format!("Using {}, where the build put its package.json", browser.display())), "{}", out);
+    assert_eq!(stored_path(&config, "shop"), browser);
+}
+
+#[test]
+fn add_keeps_the_root_of_the_old_layout() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let legacy = workspace().join("dist").join("legacy");
+    let out = spine_full(&config, &["add", "legacy", &legacy.to_string_lossy()]);
+
+    assert!(!out.contains("where the build put its package.json"), "{}", out);
+    assert_eq!(stored_path(&config, "legacy"), legacy);
+}
+
+#[test]
+fn health_points_a_root_link_at_the_nested_package() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let root = workspace().join("dist").join("nested");
+    fs::write(&config, format!("[links.\"@acme/nested\"]\nname = \"@acme/nested\"\npath = '{}'\n", root.display())).unwrap();
+
+    let nested = root.join("nested");
+    let out = spine_full(&config, &["status", "--health"]);
+    assert!(out.contains(&format!("⚠️  @acme/nested - package.json is in {}; run 'spine set-path @acme/nested {}'", nested.display(), nested.display())), "{}", out);
+    assert!(!out.contains("Missing package.json"), "{}", out);
+
+    // set-path with the outer folder lands on the nested one
+    spine_full(&config, &["set-path", "@acme/nested", &root.to_string_lossy()]);
+    assert_eq!(stored_path(&config, "@acme/nested"), nested);
+    let out = spine_full(&config, &["status", "--health"]);
+    assert!(out.contains("1 healthy, 0 with issues"), "{}", out);
+}
@@ -0,0 +1,40 @@
+//! Where `spine env` says its files came from
+
+mod common;
+
+use std::path::Path;
+use common::{spine_command, stdout};
+
+fn env(config: &Path, dir: &Path, args: &[&str]) -> serde_json::Value {
+    // Nothing on PATH, so the tool probes don't wait on real installs
+    let output = spine_command(config, dir).env("PATH", dir).args([&["env", "--json"], args].concat()).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_str(&stdout(&output)).unwrap()
+}
+
+fn setting<'a>(env: &'a serde_json::Value, section: &str, name: &str) -> &'a serde_json::Value {
+    env[section].as_array().unwrap().iter().find(|setting| setting["name"] == name).unwrap()
+}
+
+#[test]
+fn the_config_flag_beats_the_variable() {
+    let dir = tempfile::tempdir().unwrap();
+    let from_env = dir.path().join("env").join("config.toml");
+    let from_flag = dir.path().join("flag").join("config.toml");
+
+    let report = env(&from_env, dir.path(), &[]);
+    assert_eq!(setting(&report, "configuration", "config file")["source"], "env var SPINE_CONFIG");
+
+    let report = env(&from_env, dir.path(), &["--config", from_flag.to_str().unwrap()]);
+    let config = setting(&report, "configuration", "config file");
+    assert_eq!(config["source"], "flag --config");
+    assert!(config["value"].as_str().unwrap().contains("flag"), "{}", config);
+}
+
+#[test]
+fn reports_the_tarball_cache_audit_uses() {
+    let dir = tempfile::tempdir().unwrap();
+    let report = env(&dir.path().join("config.toml"), dir.path(), &[]);
+    let cache = setting(&report, "caches", "tarball cache")["value"].as_str().unwrap().replace('\\', "/");
+    assert!(cache.contains("cache/tarballs"), "{}", cache);
+}
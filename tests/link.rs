@@ -0,0 +1,137 @@
+//! `spine link` and `unlink` through a stand-in npm
+
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The projects config.toml records for link `name`
+fn linked_projects(config: &Path, name: &str) -> Vec<PathBuf> {
+    let config: toml::Value = toml::from_str(&fs::read_to_string(config).unwrap()).unwrap();
+    config["links"][name]["linked_projects"].as_array().unwrap().iter()
+        .map(|project| PathBuf::from(project.as_str().unwrap()))
+        .collect()
+}
+
+#[test]
+fn records_the_packages_that_linked_when_another_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let project = dir.path().join("app");
+    common::write_package(&project, "app", "1.0.0");
+    for name in ["lib-a", "lib-b"] {
+        let library = dir.path().join(name);
+        common::write_package(&library, name, "1.0.0");
+        common::spine(&config, dir.path(), &["add", name, library.to_str().unwrap()]);
+    }
+    // An npm that only ever links the first package it is given
+    let bin = dir.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    common::script(&bin.join("npm"), "[ \"$1\" = link ] || exit 0\n\
+        mkdir -p node_modules\n\
+        ln -sfn \"$2\" \"node_modules/$(basename \"$2\")\"\n");
+
+    let output = common::spine_command(&config, &project)
+        .args(["link", "lib-a", "lib-b", "--pm", "npm"])
+        .env("PATH", common::path_with(&bin))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Link verification failed for: lib-b"));
+
+    let project = project.canonicalize().unwrap();
+    assert_eq!(linked_projects(&config, "lib-a"), [project]);
+    assert!(linked_projects(&config, "lib-b").is_empty());
+}
+
+#[test]
+fn links_a_variant_added_before_its_checkout_existed() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let project = dir.path().join("app");
+    common::write_package(&project, "app", "1.0.0");
+    let default = dir.path().join("main").join("ui");
+    let ng17 = dir.path().join("ng17").join("ui");
+    common::write_package(&default, "ui", "1.0.0");
+    common::spine(&config, dir.path(), &["add", "ui", default.to_str().unwrap()]);
+    common::spine(&config, dir.path(), &["add", "ui", "--variant", "ng17", ng17.to_str().unwrap(), "--allow-missing"]);
+    common::write_package(&ng17, "ui", "17.0.0");
+    let bin = dir.path().join("bin");
+    common::fake_npm(&bin);
+
+    let output = common::spine_command(&config, &project)
+        .args(["link", "ui", "--variant", "ng17", "--pm", "npm"])
+        .env("PATH", common::path_with(&bin))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(project.join("node_modules").join("ui").canonicalize().unwrap(), ng17.canonicalize().unwrap());
+
+    let list = common::stdout(&common::spine(&config, dir.path(), &["list"]));
+    assert!(list.contains("[ng17] (v17.0.0)"), "{}", list);
+    assert!(!list.contains("pending"), "{}", list);
+}
+
+#[test]
+fn unlink_with_deps_keeps_a_dependency_linked_by_request() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let project = dir.path().join("app");
+    common::write_package(&project, "app", "1.0.0");
+    let lib_a = dir.path().join("lib-a");
+    let lib_b = dir.path().join("lib-b");
+    common::write_package(&lib_a, "lib-a", "1.0.0");
+    fs::create_dir_all(&lib_b).unwrap();
+    fs::write(lib_b.join("package.json"), r#"{ "name": "lib-b", "version": "1.0.0", "dependencies": { "lib-a": "^1.0.0" } }"#).unwrap();
+    common::spine(&config, dir.path(), &["add", "lib-a", lib_a.to_str().unwrap()]);
+    common::spine(&config, dir.path(), &["add", "lib-b", lib_b.to_str().unwrap()]);
+    let bin = dir.path().join("bin");
+    common::fake_npm(&bin);
+    let run = |args: &[&str]| {
+        let output = common::spine_command(&config, &project)
+            .args(args)
+            .env("PATH", common::path_with(&bin))
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "spine {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    };
+
+    run(&["link", "lib-a", "--pm", "npm"]);
+    run(&["link", "lib-b", "--with-deps", "--pm", "npm"]);
+    run(&["unlink", "lib-b", "--with-deps", "--pm", "npm"]);
+
+    assert_eq!(linked_projects(&config, "lib-a"), [project.canonicalize().unwrap()]);
+    assert!(linked_projects(&config, "lib-b").is_empty());
+}
+
+#[test]
+fn unlink_restore_reinstalls_a_dev_dependency_as_one() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let project = dir.path().join("app");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join("package.json"), r#"{ "name": "app", "version": "1.0.0", "devDependencies": { "test-kit": "^2.0.0" } }"#).unwrap();
+    let library = dir.path().join("test-kit");
+    common::write_package(&library, "test-kit", "2.1.0");
+    common::spine(&config, dir.path(), &["add", "test-kit", library.to_str().unwrap()]);
+    // Links like fake_npm and logs every install
+    let bin = dir.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let log = dir.path().join("installs.log");
+    common::script(&bin.join("npm"), &format!("[ \"$1\" = install ] && echo \"$@\" >> '{}'\n\
+        [ \"$1\" = link ] || exit 0\n\
+        mkdir -p node_modules\n\
+        ln -sfn \"$2\" \"node_modules/$(basename \"$2\")\"\n", log.display()));
+    for args in [&["link", "test-kit", "--pm", "npm"][..], &["unlink", "test-kit", "--restore", "--pm", "npm"]] {
+        let output = common::spine_command(&config, &project)
+            .args(args)
+            .env("PATH", common::path_with(&bin))
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "spine {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    }
+
+    assert_eq!(fs::read_to_string(&log).unwrap().trim(), "install test-kit@^2.0.0 --save-dev");
+}
@@ -0,0 +1,89 @@
+//! `spine ng-proxy` against a stub ng whose schematic prompts for an answer
+
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Output, Stdio};
+
+/// An `ng` in `dir` that logs its arguments, then asks about routing unless
+/// `--defaults` was passed. A prompt that reaches end of input fails, the
+/// way a real prompt left hanging in CI never finishes.
+fn fake_ng(dir: &Path) {
+    common::script(&dir.join("ng"), "echo \"$@\" > \"$NG_ARGS_LOG\"\n\
+        case \" $* \" in *\" --defaults \"*) echo 'routing: no (default)'; exit 0;; esac\n\
+        printf '? Would you like to add routing? (y/N) '\n\
+        read answer || { echo; echo 'prompt got no answer' >&2; exit 3; }\n\
+        echo \"routing: $answer\"\n");
+}
+
+fn ng_proxy(dir: &Path, args: &[&str], stdin: Option<&str>) -> (Output, String) {
+    let bin = dir.join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    fake_ng(&bin);
+    let log = dir.join("ng-args");
+
+    let mut child = common::spine_command(&dir.join("config.toml"), dir)
+        .arg("ng-proxy")
+        .args(args)
+        .env("PATH", common::path_with(&bin))
+        .env("NG_ARGS_LOG", &log)
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    if let Some(input) = stdin {
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    }
+    let output = child.wait_with_output().unwrap();
+    let args = fs::read_to_string(log).unwrap_or_default().trim().to_string();
+    (output, args)
+}
+
+#[test]
+fn explicit_interactive_lets_ng_read_the_prompt_answer() {
+    let dir = tempfile::tempdir().unwrap();
+    let (output, args) = ng_proxy(dir.path(), &["generate", "module", "admin", "--interactive"], Some("y\n"));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(args, "generate module admin --interactive");
+    // The answer reached ng through the inherited stdin, and Spine printed
+    // nothing once the prompt was up
+    assert!(stdout.ends_with("? Would you like to add routing? (y/N) routing: y\n"), "{}", stdout);
+}
+
+#[test]
+fn non_terminal_stdin_answers_prompts_with_defaults() {
+    let dir = tempfile::tempdir().unwrap();
+    let (output, args) = ng_proxy(dir.path(), &["generate", "module", "admin"], None);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(args, "generate module admin --interactive=false --defaults");
+    assert!(stdout.contains("stdin is not a terminal"), "{}", stdout);
+    assert!(stdout.ends_with("routing: no (default)\n"), "{}", stdout);
+}
+
+#[test]
+fn interactive_false_adds_skip_confirmation_for_add() {
+    let dir = tempfile::tempdir().unwrap();
+    let (output, args) = ng_proxy(dir.path(), &["add", "@angular/material", "--interactive=false"], Some(""));
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(args, "add @angular/material --interactive=false --defaults --skip-confirmation");
+}
+
+#[test]
+fn unanswered_prompt_fails_the_proxy() {
+    let dir = tempfile::tempdir().unwrap();
+    let (output, args) = ng_proxy(dir.path(), &["generate", "module", "admin", "--interactive"], Some(""));
+
+    assert!(!output.status.success());
+    assert_eq!(args, "generate module admin --interactive");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("prompt got no answer"));
+}
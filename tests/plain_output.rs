@@ -0,0 +1,80 @@
+//! `--log-format plain` output carries no escape sequences, even when the
+//! build tool it runs colors its own output
+
+#![cfg(unix)]
+
+mod common;
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::Path;
+use std::process::Output;
+
+/// A library workspace at `root/ws` whose configured link is its dist
+/// folder, and an `ng` in `root/bin` that colors everything it prints.
+/// The ng fails when NG_FAIL is set.
+fn fixture(root: &Path) -> OsString {
+    let workspace = root.join("ws");
+    fs::create_dir_all(workspace.join("projects").join("ui")).unwrap();
+    fs::write(workspace.join("angular.json"), r#"{
+        "version": 1,
+        "projects": {
+            "ui": { "root": "projects/ui", "projectType": "library", "architect": { "build": { "builder": "@angular-devkit/build-angular:ng-packagr", "options": {} } } }
+        }
+    }"#).unwrap();
+    common::write_package(&workspace.join("projects").join("ui"), "@acme/ui", "1.0.0");
+
+    let bin = root.join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    common::script(&bin.join("ng"), "printf '\\033[1m\\033[32m✔\\033[39m\\033[22m Building entry point \\033[36m%s\\033[39m\\n' \"$2\"\n\
+        printf '\\033]8;;https://angular.dev\\007docs\\033]8;;\\007\\n'\n\
+        printf '\\033[33mWARNING\\033[39m bundle budget\\n' >&2\n\
+        mkdir -p dist/ui\n\
+        echo '{ \"name\": \"@acme/ui\", \"version\": \"1.0.0\" }' > dist/ui/package.json\n\
+        if [ -n \"$NG_FAIL\" ]; then printf '\\033[31mFAILED\\033[0m\\n' >&2; exit 1; fi\n");
+
+    let dist = workspace.join("dist").join("ui");
+    common::spine(&root.join("config.toml"), root, &["add", "ui", "--allow-missing", dist.to_str().unwrap()]);
+    common::path_with(&bin)
+}
+
+fn build(root: &Path, path: &OsStr, fail: bool) -> Output {
+    let mut command = common::spine_command(&root.join("config.toml"), &root.join("ws"));
+    command.args(["--log-format", "plain", "build", "ui"])
+        .env("PATH", path)
+        .env_remove("NO_COLOR")
+        .env("FORCE_COLOR", "1");
+    if fail {
+        command.env("NG_FAIL", "1");
+    }
+    command.output().unwrap()
+}
+
+fn assert_no_escapes(output: &Output) {
+    for (stream, bytes) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+        let text = String::from_utf8_lossy(bytes);
+        assert!(!text.contains('\x1b'), "escape sequence in {}: {:?}", stream, text);
+    }
+}
+
+#[test]
+fn successful_build_output_has_no_escapes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = fixture(dir.path());
+
+    let output = build(dir.path(), &path, false);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Successfully built ui"));
+    assert_no_escapes(&output);
+}
+
+#[test]
+fn failed_build_output_has_no_escapes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = fixture(dir.path());
+
+    let output = build(dir.path(), &path, true);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("WARNING bundle budget") && stderr.contains("FAILED"), "{}", stderr);
+    assert_no_escapes(&output);
+}
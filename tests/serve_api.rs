@@ -0,0 +1,136 @@
+//! Drives `spine serve-api --stdio` through a scripted session, as an editor
+//! extension would
+
+mod common;
+
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use serde_json::{json, Value};
+
+struct Session {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Session {
+    fn start(config: &Path, project: &Path, path: &OsStr) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_spine"))
+            .args(["serve-api", "--stdio"])
+            .current_dir(project)
+            .env("SPINE_CONFIG", config)
+            .env("PATH", path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spine runs");
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Self { child, stdin, stdout }
+    }
+
+    fn read(&mut self) -> Value {
+        let mut line = String::new();
+        assert!(self.stdout.read_line(&mut line).unwrap() > 0, "serve-api closed stdout");
+        serde_json::from_str(&line).unwrap_or_else(|e| panic!("not a JSON line ({}): {}", e, line))
+    }
+
+    fn send_line(&mut self, line: &str) {
+        writeln!(self.stdin, "{}", line).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    /// The response to `request`, skipping its progress events
+    fn call(&mut self, request: Value) -> Value {
+        let id = request["id"].clone();
+        self.send_line(&request.to_string());
+        loop {
+            let message = self.read();
+            if message["id"] == id && message.get("event").is_none() {
+                return message;
+            }
+        }
+    }
+
+    fn finish(mut self) {
+        let response = self.call(json!({ "id": "bye", "method": "shutdown" }));
+        assert_eq!(response["result"], Value::Null);
+        assert!(self.child.wait().unwrap().success());
+    }
+}
+
+/// A config with `lib-a` configured, a consumer project and a library
+fn fixture(root: &Path) -> (PathBuf, PathBuf) {
+    let library = root.join("lib-a");
+    let project = root.join("app");
+    common::write_package(&library, "lib-a", "1.2.3");
+    common::write_package(&project, "app", "1.0.0");
+
+    let config = root.join("config").join("config.toml");
+    common::spine(&config, root, &["add", "lib-a", library.to_str().unwrap()]);
+    (config, project)
+}
+
+#[test]
+fn lists_and_reports_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, project) = fixture(dir.path());
+    let mut session = Session::start(&config, &project, &std::env::var_os("PATH").unwrap_or_default());
+
+    let ready = session.read();
+    assert_eq!(ready["event"], "ready");
+    assert_eq!(ready["api_version"], 1);
+
+    let list = session.call(json!({ "id": 1, "method": "list" }));
+    let links = list["result"]["links"].as_array().unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0]["name"], "lib-a");
+    assert_eq!(links[0]["version"], "1.2.3");
+
+    let unknown = session.call(json!({ "id": 2, "method": "frobnicate" }));
+    assert_eq!(unknown["error"]["code"], "unknown_method");
+
+    let invalid = session.call(json!({ "id": 3, "method": "link", "params": { "packages": "lib-a" } }));
+    assert_eq!(invalid["error"]["code"], "invalid_params");
+
+    let version = session.call(json!({ "id": 4, "method": "list", "api_version": 99 }));
+    assert_eq!(version["error"]["code"], "unsupported_version");
+
+    let missing = session.call(json!({ "id": 5, "method": "link", "params": { "packages": ["nope"] } }));
+    assert_eq!(missing["error"]["code"], "failed");
+
+    session.send_line("{ not json");
+    assert_eq!(session.read()["error"]["code"], "parse_error");
+
+    session.finish();
+}
+
+#[cfg(unix)]
+#[test]
+fn links_into_the_requested_project() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, project) = fixture(dir.path());
+    let bin = dir.path().join("bin");
+    common::fake_npm(&bin);
+    // Started elsewhere, so the project has to come from the request
+    let mut session = Session::start(&config, dir.path(), &common::path_with(&bin));
+    session.read();
+
+    let linked = session.call(json!({ "id": "l", "method": "link", "params": { "packages": ["lib-a"], "project": "app", "pm": "npm" } }));
+    assert_eq!(linked["result"]["linked"], json!(["lib-a"]), "{}", linked);
+    assert!(project.join("node_modules/lib-a").is_symlink());
+
+    let status = session.call(json!({ "id": "s", "method": "status", "params": { "project": "app" } }));
+    let packages = status["result"].to_string();
+    assert!(packages.contains("lib-a"), "{}", packages);
+
+    let list = session.call(json!({ "id": "after", "method": "list" }));
+    let recorded = list["result"]["links"][0]["linked_projects"].as_array().unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(PathBuf::from(recorded[0].as_str().unwrap()), project.canonicalize().unwrap());
+
+    session.finish();
+}
@@ -0,0 +1,49 @@
+//! `spine serve --with-libs` started in a workspace that only has libraries
+
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::process::Stdio;
+
+#[test]
+fn names_the_apps_its_libraries_are_linked_into() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let workspace = dir.path().join("ws");
+    fs::create_dir_all(&workspace).unwrap();
+    fs::write(workspace.join("angular.json"), r#"{
+        "version": 1,
+        "projects": {
+            "ui": { "root": "projects/ui", "projectType": "library", "architect": { "build": { "builder": "@angular-devkit/build-angular:ng-packagr", "options": {} } } }
+        }
+    }"#).unwrap();
+    let library = workspace.join("projects").join("ui");
+    common::write_package(&library, "ui", "1.0.0");
+    let app = dir.path().join("app");
+    common::write_package(&app, "app", "1.0.0");
+
+    let bin = dir.path().join("bin");
+    common::fake_npm(&bin);
+    common::spine(&config, dir.path(), &["add", "ui", library.to_str().unwrap()]);
+    let linked = common::spine_command(&config, &app)
+        .args(["link", "ui", "--pm", "npm"])
+        .env("PATH", common::path_with(&bin))
+        .output()
+        .unwrap();
+    assert!(linked.status.success(), "{}", String::from_utf8_lossy(&linked.stderr));
+
+    // Without a terminal there is no one to ask which app to serve
+    let output = common::spine_command(&config, &workspace)
+        .args(["serve", "--with-libs"])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = common::stdout(&output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("has no application project"), "{}", stdout);
+    assert!(stderr.contains("pass --app-dir with one of:"), "{}", stderr);
+    assert!(stderr.contains(app.canonicalize().unwrap().to_str().unwrap()), "{}", stderr);
+}
@@ -0,0 +1,55 @@
+//! `spine suggest` finds the local packages a project declares
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use common::{spine, stdout, write_package};
+
+/// `app` declaring ui, test-kit, icons and core in its four dependency
+/// fields, and each of them plus an unrelated package under `libs`
+fn fixture(root: &Path) -> (PathBuf, PathBuf) {
+    let libs = root.join("libs");
+    for name in ["ui", "test-kit", "icons", "core", "other"] {
+        write_package(&libs.join(name), name, "1.0.0");
+    }
+    let app = root.join("app");
+    fs::create_dir_all(&app).unwrap();
+    fs::write(app.join("package.json"), r#"{
+        "name": "app",
+        "version": "1.0.0",
+        "dependencies": { "ui": "^1.0.0" },
+        "devDependencies": { "test-kit": "^1.0.0" },
+        "optionalDependencies": { "icons": "~1.0.0" },
+        "peerDependencies": { "core": ">=1" }
+    }"#).unwrap();
+    (app, libs)
+}
+
+#[test]
+fn lists_packages_from_every_dependency_field() {
+    let dir = tempfile::tempdir().unwrap();
+    let (app, libs) = fixture(dir.path());
+    let output = stdout(&spine(&dir.path().join("config.toml"), &app, &["suggest", "--path", libs.to_str().unwrap()]));
+
+    assert!(output.contains("Found 4 local package(s)"), "{}", output);
+    for (name, field) in [("ui", "dependencies ^1.0.0"), ("test-kit", "devDependencies ^1.0.0"), ("icons", "optionalDependencies ~1.0.0"), ("core", "peerDependencies >=1")] {
+        let line = output.lines().find(|line| line.trim_start().starts_with(&format!("{} (", name))).unwrap_or_else(|| panic!("{} missing: {}", name, output));
+        assert!(line.contains(&format!("[{}]", field)), "{}", line);
+    }
+    assert!(!output.contains("other"), "{}", output);
+}
+
+#[test]
+fn add_configures_only_the_suggested_packages() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let (app, libs) = fixture(dir.path());
+    spine(&config, dir.path(), &["add", "ui", libs.join("ui").to_str().unwrap()]);
+    spine(&config, &app, &["suggest", "--add", "--yes", "--path", libs.to_str().unwrap()]);
+
+    let saved: toml::Value = toml::from_str(&fs::read_to_string(&config).unwrap()).unwrap();
+    let mut names: Vec<&String> = saved["links"].as_table().unwrap().keys().collect();
+    names.sort();
+    assert_eq!(names, ["core", "icons", "test-kit", "ui"]);
+}
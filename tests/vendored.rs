@@ -0,0 +1,77 @@
+//! Packages added from inside another package's node_modules (vendored
+//! forks) are linked and health-checked like any other
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use common::{spine, stdout, write_package};
+
+/// A fork vendored at `host/node_modules/vendored-ui` (with its own
+/// node_modules), a regular library with one, and a consumer project
+fn fixture(root: &Path) -> (PathBuf, PathBuf, PathBuf) {
+    let vendored = root.join("host").join("node_modules").join("vendored-ui");
+    let regular = root.join("regular-ui");
+    let project = root.join("app");
+    for (dir, name) in [(&vendored, "vendored-ui"), (&regular, "regular-ui"), (&project, "app")] {
+        write_package(dir, name, "1.0.0");
+    }
+    fs::create_dir_all(vendored.join("node_modules").join("tslib")).unwrap();
+    fs::create_dir_all(regular.join("node_modules").join("tslib")).unwrap();
+    (vendored, regular, project)
+}
+
+#[test]
+fn adds_and_marks_a_vendored_package() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let (vendored, _, _) = fixture(dir.path());
+
+    spine(&config, dir.path(), &["add", "vendored-ui", vendored.to_str().unwrap()]);
+
+    let list = stdout(&spine(&config, dir.path(), &["list"]));
+    assert!(list.contains("vendored-ui (v1.0.0) [vendored]"), "{}", list);
+    let status: serde_json::Value = serde_json::from_slice(&spine(&config, dir.path(), &["status", "--json"]).stdout).unwrap();
+    assert_eq!(status["packages"]["vendored-ui"]["vendored"], true);
+    assert_eq!(status["packages"]["vendored-ui"]["version"], "1.0.0");
+}
+
+#[test]
+fn health_check_passes_packages_with_their_own_node_modules() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let (vendored, regular, project) = fixture(dir.path());
+    spine(&config, dir.path(), &["add", "vendored-ui", vendored.to_str().unwrap()]);
+    spine(&config, dir.path(), &["add", "regular-ui", regular.to_str().unwrap()]);
+
+    let health = stdout(&spine(&config, &project, &["status", "--health"]));
+    assert!(health.lines().any(|line| line.starts_with("✅ vendored-ui") && line.contains("[vendored]")), "{}", health);
+    assert!(health.lines().any(|line| line.starts_with("✅ regular-ui")), "{}", health);
+    assert!(!health.contains("⚠️"), "{}", health);
+}
+
+#[cfg(unix)]
+#[test]
+fn links_a_vendored_package() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let (vendored, _, project) = fixture(dir.path());
+    let bin = dir.path().join("bin");
+    common::fake_npm(&bin);
+    spine(&config, dir.path(), &["add", "vendored-ui", vendored.to_str().unwrap()]);
+
+    let output = common::spine_command(&config, &project)
+        .args(["link", "vendored-ui", "--pm", "npm"])
+        .env("PATH", common::path_with(&bin))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let installed = project.join("node_modules").join("vendored-ui");
+    assert!(installed.is_symlink());
+    assert_eq!(installed.canonicalize().unwrap(), vendored.canonicalize().unwrap());
+
+    let health = stdout(&spine(&config, &project, &["status", "--health"]));
+    assert!(health.lines().any(|line| line.contains("vendored-ui") && line.contains("(linked)") && line.contains("[vendored]")), "{}", health);
+    let status: serde_json::Value = serde_json::from_slice(&spine(&config, &project, &["status", "--json"]).stdout).unwrap();
+    assert_eq!(status["packages"]["vendored-ui"]["linked_to_current"], true);
+}
@@ -0,0 +1,68 @@
+//! Link paths from a project's .spine.toml `[links]` table
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use common::{spine, write_package};
+
+/// `ui` configured globally at lib/ui; app-a's .spine.toml points it at
+/// fork/ui and adds `extra`, which only it defines; app-b has no overrides
+fn fixture(root: &Path) -> (PathBuf, PathBuf, PathBuf) {
+    let config = root.join("config.toml");
+    for dir in ["lib/ui", "fork/ui"] {
+        write_package(&root.join(dir), "ui", "1.0.0");
+    }
+    write_package(&root.join("extra"), "extra", "1.0.0");
+    let (app_a, app_b) = (root.join("app-a"), root.join("app-b"));
+    write_package(&app_a, "app-a", "1.0.0");
+    write_package(&app_b, "app-b", "1.0.0");
+    fs::write(app_a.join(".spine.toml"), "[links]\nui = \"../fork/ui\"\nextra = \"../extra\"\n").unwrap();
+    spine(&config, root, &["add", "ui", root.join("lib").join("ui").to_str().unwrap()]);
+    (config, app_a, app_b)
+}
+
+fn status(config: &Path, dir: &Path, args: &[&str]) -> serde_json::Value {
+    let output = spine(config, dir, &[&["status", "--json"], args].concat());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn status_of_another_project_uses_its_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, app_a, app_b) = fixture(dir.path());
+    let global = dir.path().join("lib").join("ui");
+
+    let here = status(&config, &app_a, &[]);
+    assert_eq!(PathBuf::from(here["packages"]["ui"]["path"].as_str().unwrap()), dir.path().join("fork").join("ui"));
+
+    let other = status(&config, &app_a, &["--project", app_b.to_str().unwrap()]);
+    assert_eq!(PathBuf::from(other["packages"]["ui"]["path"].as_str().unwrap()), global);
+    assert!(other["packages"].get("extra").is_none(), "{}", other);
+}
+
+#[cfg(unix)]
+#[test]
+fn remembers_where_a_workspace_only_link_went() {
+    let dir = tempfile::tempdir().unwrap();
+    let (config, app_a, _) = fixture(dir.path());
+    let bin = dir.path().join("bin");
+    common::fake_npm(&bin);
+
+    let output = common::spine_command(&config, &app_a)
+        .args(["link", "extra", "--pm", "npm"])
+        .env("PATH", common::path_with(&bin))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let saved: toml::Value = toml::from_str(&fs::read_to_string(&config).unwrap()).unwrap();
+    let projects = saved["links"]["extra"]["linked_projects"].as_array().unwrap();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(PathBuf::from(projects[0].as_str().unwrap()), app_a.canonicalize().unwrap());
+    // The overridden global link keeps its own path
+    assert_eq!(PathBuf::from(saved["links"]["ui"]["path"].as_str().unwrap()), dir.path().join("lib").join("ui"));
+
+    let here = status(&config, &app_a, &[]);
+    assert_eq!(here["packages"]["extra"]["linked_to_current"], true);
+}